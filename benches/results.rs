@@ -0,0 +1,36 @@
+//! Saving and loading a million results with their values in the binary and JSON
+//! formats, run with `cargo bench --bench results`.
+use cheat_engine_rs::core::results::{ResultsFormat, ScanMetadata, load_saved, save_results};
+use cheat_engine_rs::core::scan::{ScanResult, ValueType};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+const COUNT: u64 = 1_000_000;
+
+fn results_files(c: &mut Criterion) {
+    let results: Vec<ScanResult> = (0..COUNT)
+        .map(|i| ScanResult::new(0x7f00_0000_0000 + i * 4, ValueType::U32, (i as u32).to_le_bytes().to_vec(), vec![]))
+        .collect();
+    let metadata = ScanMetadata::now("100", "u32", None, None);
+
+    let mut group = c.benchmark_group("results");
+    group.sample_size(10);
+    for format in [ResultsFormat::Bin, ResultsFormat::Json] {
+        let mut file = vec![];
+        save_results(&mut file, format, 1, ValueType::U32, &results, true, &metadata).unwrap();
+        let name = format.name();
+        group.bench_function(format!("save {name}"), |b| {
+            b.iter_batched_ref(
+                || Vec::with_capacity(file.len()),
+                |out| save_results(out, format, 1, ValueType::U32, &results, true, &metadata).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(format!("load {name}"), |b| {
+            b.iter(|| assert_eq!(load_saved(&mut file.as_slice()).unwrap().results.len(), COUNT as usize))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, results_files);
+criterion_main!(benches);
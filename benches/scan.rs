@@ -0,0 +1,67 @@
+//! Matches per second of the initial scan for exact, AOB and string values over a 64 MiB
+//! buffer, and of the memchr/memmem fast path against comparing every offset, run with
+//! `cargo bench --bench scan`.
+use std::sync::Arc;
+
+use cheat_engine_rs::core::mem::MemoryRegionPerms;
+use cheat_engine_rs::core::scan::{Scan, ValueType, find_exact, find_exact_scalar};
+use cheat_engine_rs::core::utils::FakeMemory;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const SIZE: usize = 64 << 20;
+const BLOCK: usize = 0x10000;
+
+fn scan_throughput(c: &mut Criterion) {
+    // the same pseudo-random bytes every run, with one of the values planted per block
+    let mut state: u64 = 0x9e3779b97f4a7c15;
+    let mut data: Vec<u8> = (0..SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 56) as u8
+        })
+        .collect();
+    let cases: [(&str, ValueType, Vec<u8>); 3] = [
+        ("exact u32", ValueType::U32, 1337_u32.to_le_bytes().to_vec()),
+        ("AOB", ValueType::Hex, vec![0x48, 0x8b, 0x05, 0x99, 0x12, 0x34]),
+        ("string", ValueType::String, b"PlayerHealth".to_vec()),
+    ];
+    for (i, block) in data.chunks_mut(BLOCK).enumerate() {
+        let value = &cases[i % cases.len()].2;
+        block[0x100..0x100 + value.len()].copy_from_slice(value);
+    }
+    let memory = Arc::new(FakeMemory::new());
+    memory.add_region(0x1000_0000, data.clone(), vec![MemoryRegionPerms::Write]);
+
+    let mut group = c.benchmark_group("Scan::init");
+    group.sample_size(10);
+    // the whole initial scan, including the copy FakeMemory makes of every block
+    for (name, value_type, value) in &cases {
+        let init = || {
+            let mut scan = Scan::with_source(memory.clone(), value.clone(), *value_type, None, None, None).unwrap();
+            scan.init().unwrap().len()
+        };
+        let matches = init();
+        assert!(matches >= SIZE / BLOCK / cases.len());
+        group.throughput(Throughput::Elements(matches as u64));
+        group.bench_function(*name, |b| b.iter(init));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("find_exact");
+    group.sample_size(10);
+    for (name, _, value) in &cases {
+        let fast = || data.chunks(BLOCK).map(|block| find_exact(block, value).len()).sum::<usize>();
+        let scalar = || data.chunks(BLOCK).map(|block| find_exact_scalar(block, value).len()).sum::<usize>();
+        let matches = fast();
+        assert_eq!(matches, scalar());
+        group.throughput(Throughput::Elements(matches as u64));
+        group.bench_function(format!("{name} fast path"), |b| b.iter(fast));
+        group.bench_function(format!("{name} scalar"), |b| b.iter(scalar));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan_throughput);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+//! Diagnostic logging to stderr, quiet unless asked for with `-v`/`-vv` or `RUST_LOG`
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Only records of this crate are printed, dependencies stay quiet
+const TARGET: &str = "cheat_engine_rs";
+
+struct StderrLogger {
+    level: LevelFilter,
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && metadata.target().starts_with(TARGET)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let module = record.target().strip_prefix(TARGET).unwrap_or("");
+            let module = module.trim_start_matches("::");
+            eprintln!("[{:<5} {}] {}", record.level(), module, record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Level set by `RUST_LOG`: a bare level (`debug`) or a `cheat_engine_rs=<level>`
+/// directive, the last one wins. This is the part of the `env_logger` syntax the CLI
+/// supports, directives for other crates or modules and `/regex` filters are ignored.
+fn parse_filter(filter: &str) -> Option<LevelFilter> {
+    filter
+        .split(',')
+        .filter_map(|directive| match directive.trim().split_once('=') {
+            Some((target, level)) if target.trim() == TARGET => level.trim().parse().ok(),
+            Some(_) => None,
+            None => directive.trim().parse().ok(),
+        })
+        .next_back()
+}
+
+/// Log at the more verbose of `RUST_LOG` and the `-v` count: one for debug, two or more
+/// for trace. Nothing is logged by default.
+pub fn init(verbose: u8) {
+    let from_flags = match verbose {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    let from_env = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|filter| parse_filter(&filter))
+        .unwrap_or(LevelFilter::Off);
+    let level = from_flags.max(from_env);
+
+    if log::set_boxed_logger(Box::new(StderrLogger { level })).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(parse_filter("debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_filter("TRACE"), Some(LevelFilter::Trace));
+        assert_eq!(parse_filter("rayon=trace,cheat_engine_rs=info"), Some(LevelFilter::Info));
+        assert_eq!(parse_filter("warn, cheat_engine_rs = off"), Some(LevelFilter::Off));
+        assert_eq!(parse_filter("rayon=trace"), None);
+        // module directives and regex filters aren't supported
+        assert_eq!(parse_filter("cheat_engine_rs::core::mem=trace"), None);
+        assert_eq!(parse_filter("debug/scan"), None);
+        assert_eq!(parse_filter("loud"), None);
+        assert_eq!(parse_filter(""), None);
+    }
+}
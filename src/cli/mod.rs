@@ -1,7 +1,28 @@
-use clap::Parser;
+pub mod logger;
+mod printer;
+mod progress;
+mod table;
+
+use clap::{Args, Parser};
 use std::error::Error;
+use std::fmt::Display;
+use std::time::Duration;
 
-use crate::core::scan::{Scan, ValueType};
+use crate::core::address::{parse_address_str, resolve_address, resolve_address_in};
+use crate::core::config::{self, resolve_read_size};
+use crate::core::histogram::DEFAULT_MAX_DISTINCT;
+use crate::core::debug::WatchCondition;
+use crate::core::mem::{
+    DEFAULT_MAX_CSTRING_LEN, MemoryError, MemoryRegion, MemorySource, WriteOp, all_regions, format_perms,
+};
+use crate::core::proc::{RegionKind, RegionNames, region_list, thread_stack, threads};
+use crate::core::results::Selection;
+use crate::core::scan::{
+    CompareBaseline, CompareExpr, CompareMode, Scan, ScanError, ScanResult, SortKey, ValueType, XorCodec,
+    custom_value_type, register_codec, retain_near, skipped_summary, sort_results,
+};
+use printer::{Columns, OutputFormat};
+use table::Table;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -15,71 +36,1131 @@ pub struct Cli {
 pub enum Commands {
     /// List running processes
     ListProcesses,
-    /// Scan memory for a value
-    Scan {
+    /// List the value types with their size, scan alignment and an example value
+    Types,
+    /// Wait until a process whose name starts with a pattern runs and print its PID, e.g.
+    /// `cli scan --pid $(cli wait-for --name game)` right after launching it. Give a command
+    /// after `--` to run it on the process instead, with `--pid <pid>` added:
+    /// `cli wait-for --name game -- scan --type i32 --value 100`
+    WaitFor {
+        /// Start of the process name, not case sensitive
+        #[arg(short, long)]
+        name: String,
+        /// How long to wait, e.g. `60s`, `2m` or `500` (milliseconds)
+        #[arg(long, value_parser = parse_duration, default_value = "60s")]
+        timeout: Duration,
+        /// Command to run on the process once it appeared
+        #[arg(last = true)]
+        then: Vec<String>,
+    },
+    /// Show executable, command line, working directory and start time of a process
+    Info {
         /// Process ID
         #[arg(short, long)]
         pid: u32,
+        /// Also print the environment variables
+        #[arg(long)]
+        env: bool,
+    },
+    /// Scan memory for a value
+    Scan(Box<ScanArgs>),
+    /// Scan several processes for a value on one shared thread pool
+    ScanAll {
+        /// Process IDs, comma separated
+        #[arg(short, long, value_delimiter = ',', required_unless_present = "name")]
+        pids: Vec<u32>,
+        /// Also scan every process whose name starts with this (case insensitive)
+        #[arg(short, long)]
+        name: Option<String>,
         /// Value to search for
         #[arg(short, long)]
         value: String,
-        /// Value type (u32, i32, u64, i64, string, hex)
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Total scan threads across all processes, defaults to one per CPU
+        #[arg(long, value_name = "N")]
+        threads: Option<usize>,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Scan for other addresses holding the value currently at a reference address
+    ScanLike {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Reference address (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        address: String,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
         #[arg(short, long, default_value = "u32")]
         r#type: String,
-        /// Start address (hex)
+        /// Number of bytes to compare for string/hex (defaults to 32), or the width of a bare
+        /// `--type raw`
+        #[arg(long)]
+        size: Option<usize>,
+        /// Decimal places to show for f32/f64 values
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Guided scan for when the type of a value is unknown: shows the bytes at an address
+    /// read as each common type, asks which one looks right, then scans for the values
+    /// typed in with that type, each one narrowing the results of the last
+    Assist {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address of a known instance of the value (hex, decimal with a 0d prefix, or
+        /// module+offset)
+        #[arg(short, long)]
+        address: String,
+    },
+    /// Print the address an address expression like `libc.so.6+0x1234` resolves to
+    Resolve {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address, module name or expression of them joined by + and -
+        #[arg(short, long)]
+        address: String,
+    },
+    /// Print the module-relative form of an address, the inverse of `resolve`:
+    /// `libgame.so+0x1234` in a mapped file, `[heap]+0x10` in another named mapping, or
+    /// `[anon]` in anonymous memory
+    AddrToOffset {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address, module name or expression of them joined by + and -
         #[arg(short, long)]
-        start: Option<String>,
-        /// End address (hex)
+        address: String,
+    },
+    /// List the memory regions of a process with the indices `scan --region-index` takes
+    Regions {
+        /// Process ID
+        #[arg(short, long, required_unless_present = "dump_file")]
+        pid: Option<u32>,
+        /// List the regions of a memory dump file instead of a live process
+        #[arg(long, conflicts_with = "pid")]
+        dump_file: Option<String>,
+        /// Region map for --dump-file, JSON (`map.json`) or lines of `<start>-<end> <perms>
+        /// <file offset>` in hex
+        #[arg(long, requires = "dump_file")]
+        map: Option<String>,
+    },
+    /// List the threads of a process with their state and stack, see `scan --thread`
+    Threads {
+        /// Process ID
         #[arg(short, long)]
-        end: Option<String>,
+        pid: u32,
     },
     /// Read memory at a specific address
     Read {
+        /// Process ID
+        #[arg(short, long, required_unless_present = "dump_file")]
+        pid: Option<u32>,
+        /// Read from a memory dump file instead of a live process
+        #[arg(long, conflicts_with = "pid")]
+        dump_file: Option<String>,
+        /// Region map for --dump-file, JSON (`map.json`) or lines of `<start>-<end> <perms>
+        /// <file offset>` in hex
+        #[arg(long, requires = "dump_file")]
+        map: Option<String>,
+        /// Address to read from (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long, required_unless_present_any = ["addresses_file", "results"])]
+        address: Option<String>,
+        /// Read every address listed in a file instead, one `address[:size]` per line.
+        /// `#` starts a comment, blank lines are skipped
+        #[arg(long, conflicts_with_all = ["address", "count", "until_null"])]
+        addresses_file: Option<String>,
+        /// Read at every address of a result set saved with `scan --save` instead
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["address", "addresses_file", "count", "until_null"])]
+        results: Option<String>,
+        /// Signed byte offset added to each --results address, e.g. `0x10` or `-0x8` for a
+        /// field of the struct a result is the base of. Every moved address has to be mapped
+        #[arg(long, value_parser = parse_offset, allow_hyphen_values = true, requires = "results")]
+        offset: Option<i64>,
+        /// Read only some of the --results by index: `5..15`, `-10..` for the last ten, `..5`
+        /// or a single `7`. Negative indices count from the end, slice ends are exclusive
+        #[arg(long, value_parser = parse_selection, allow_hyphen_values = true, requires = "results")]
+        select: Option<Selection>,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Read size (for string/hex types, 32 unless the config file sets a `read_size`
+        /// for the type), or the width of a bare `--type raw`
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// Decimal places to show for f32/f64 values
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
+        /// Read N consecutive values (of the type, or of --size bytes) and print them
+        /// indexed
+        #[arg(long, value_name = "N")]
+        count: Option<usize>,
+        /// Read a string up to its terminating null byte instead of a fixed --size. The read
+        /// stops early at --max-len bytes or at memory that can't be read
+        #[arg(long, conflicts_with_all = ["size", "count"])]
+        until_null: bool,
+        /// Longest string --until-null reads
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_CSTRING_LEN, requires = "until_null")]
+        max_len: usize,
+    },
+    /// Read every field of a struct described by a schema file and print them labeled
+    ReadStruct {
+        /// Process ID
+        #[arg(short, long, required_unless_present = "dump_file")]
+        pid: Option<u32>,
+        /// Read from a memory dump file instead of a live process
+        #[arg(long, conflicts_with = "pid")]
+        dump_file: Option<String>,
+        /// Region map for --dump-file, JSON (`map.json`) or lines of `<start>-<end> <perms>
+        /// <file offset>` in hex
+        #[arg(long, requires = "dump_file")]
+        map: Option<String>,
+        /// Start of the struct (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        address: String,
+        /// JSON schema of the struct: `{"fields": [{"name": "health", "offset": "0x10",
+        /// "type": "i32"}, ...]}` in offset order, with an optional `count` of values (the
+        /// byte length of strings and hex). Overlapping fields need `"allow_overlap": true`
+        #[arg(long)]
+        schema: String,
+        /// Decimal places to show for f32/f64 values
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
+    },
+    /// Read a value repeatedly to tell a stable value from a noisy one, printing how
+    /// often each distinct value was seen
+    Probe {
         /// Process ID
         #[arg(short, long)]
         pid: u32,
-        /// Address to read from (hex)
+        /// Address to read from (hex, decimal with a 0d prefix, or module+offset)
         #[arg(short, long)]
         address: String,
-        /// Value type (u32, i32, u64, i64, string, hex)
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
         #[arg(short, long, default_value = "u32")]
         r#type: String,
-        /// Read size (for string/hex types)
+        /// Read size (for string/hex types), or the width of a bare `--type raw`
         #[arg(short, long)]
         size: Option<usize>,
+        /// Number of reads
+        #[arg(long, default_value_t = 20)]
+        samples: usize,
+        /// Delay between reads, e.g. `10ms`, `1s` or `50` (milliseconds)
+        #[arg(long, value_parser = parse_duration, default_value = "10ms")]
+        interval: Duration,
+        /// Decimal places to show for f32/f64 values
+        #[arg(long, value_name = "N")]
+        precision: Option<usize>,
     },
     /// Write memory at a specific address
     Write {
         /// Process ID
         #[arg(short, long)]
         pid: u32,
-        /// Address to write to (hex)
+        /// Address to write to (hex, decimal with a 0d prefix, or module+offset)
         #[arg(short, long)]
         address: String,
-        /// Value to write
+        /// Value to write, a comma separated list of N values with --count
+        #[arg(short, long, required_unless_present = "op")]
+        value: Option<String>,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Write N consecutive values, back to back from the address
+        #[arg(long, value_name = "N")]
+        count: Option<usize>,
+        /// Width of a bare `--type raw` value in bytes, same as `--type raw:<n>`
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// Write a value computed from the current one instead of --value: add, sub, mul or
+        /// div by --by, half, or set-max (the largest value of the type). Integer results
+        /// saturate at the limits of the type, fractions are cut off
+        #[arg(long, conflicts_with_all = ["value", "count"])]
+        op: Option<String>,
+        /// Operand of --op add/sub/mul/div, e.g. `--op mul --by 2`
+        #[arg(long, requires = "op", allow_negative_numbers = true)]
+        by: Option<f64>,
+        /// Take a hex or raw:<n> --value byte-reversed, so `0x01020304` writes the
+        /// little-endian number 0x01020304 (04 03 02 01) instead of the bytes as typed
+        #[arg(long, requires = "value")]
+        reverse_hex: bool,
+        /// Print the bytes at the address, the bytes that would be written and which of
+        /// them change, then exit without writing
+        #[arg(long, conflicts_with = "confirm")]
+        preview: bool,
+        /// Show the --preview and ask before writing
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Log every change of the value at an address by polling it
+    LogChanges(LogChangesArgs),
+    /// Keep a value in place by writing it again and again until Ctrl+C, e.g. to pin a
+    /// counter the game keeps lowering
+    Freeze {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address to freeze (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long, required_unless_present = "addresses_file")]
+        address: Option<String>,
+        /// Freeze every address listed in a file instead, one `address[:size]` per line. A
+        /// line's size has to match the width of the value
+        #[arg(long, conflicts_with = "address")]
+        addresses_file: Option<String>,
+        /// Value to keep at the address(es)
         #[arg(short, long)]
         value: String,
-        /// Value type (u32, i32, u64, i64, string, hex)
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Width of a bare `--type raw` value in bytes, same as `--type raw:<n>`
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// Time between writes, e.g. `50ms`, `1.5s` or `250` (milliseconds)
+        #[arg(short, long, value_parser = parse_duration, default_value = "100ms")]
+        interval: Duration,
+    },
+    /// Report the instructions that access an address, using a hardware watchpoint
+    /// (Linux x86-64, needs root or a permissive ptrace_scope). One debug register is
+    /// used, so the watched range is a single aligned 1/2/4/8 byte value
+    Watchpoint {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address to watch (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        address: String,
+        /// Watched length in bytes (1, 2, 4 or 8), the address must be aligned to it
+        #[arg(short, long, default_value_t = 4)]
+        len: usize,
+        /// What triggers the watchpoint (write, access, execute)
+        #[arg(long, default_value = "write")]
+        on: String,
+        /// Stop after this many hits
+        #[arg(short, long)]
+        count: Option<usize>,
+    },
+    /// Overwrite instructions with x86 NOPs (0x90). Run from a terminal, the patch stays
+    /// active until Enter is pressed and is reverted on exit, Ctrl+C or a crash, unless
+    /// --no-restore is given. Without a terminal on stdin the patch is kept. The original
+    /// bytes are printed and can be saved with --backup to undo a kept patch with `restore`
+    Nop {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address of the first instruction to patch (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        address: String,
+        /// Number of bytes to overwrite
+        #[arg(short, long)]
+        len: usize,
+        /// Append the original bytes to this file
+        #[arg(short, long)]
+        backup: Option<String>,
+        /// Skip disassembling to check the length covers whole instructions
+        #[arg(long)]
+        no_verify: bool,
+        /// Allow patching regions that are not executable
+        #[arg(long)]
+        force: bool,
+        /// Keep the patch after exiting instead of restoring the original bytes
+        #[arg(long)]
+        no_restore: bool,
+    },
+    /// Undo patches by writing back original bytes, from a backup file or given directly
+    Restore {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Backup file written by `nop --backup`
+        #[arg(short, long, conflicts_with_all = ["address", "bytes"])]
+        backup: Option<String>,
+        /// Address to restore (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long, requires = "bytes")]
+        address: Option<String>,
+        /// Original bytes as hex
+        #[arg(long, requires = "address")]
+        bytes: Option<String>,
+    },
+    /// Undo the most recent `write` (or TUI edit) by writing back the bytes it replaced.
+    /// Writes are journaled per process, so this can be repeated to step further back
+    UndoLast {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Forget every journaled write of the process instead of undoing one
+        #[arg(long)]
+        clear_journal: bool,
+    },
+    /// Filter and summarise a log written by `log-changes`, no process needed
+    AnalyzeLog {
+        /// Log file written by `log-changes --output`, as a table or as CSV with a
+        /// `timestamp,elapsed,old,new` header (`--output-format csv`)
+        file: String,
+        /// Only show rows whose value changed by more than this much
+        #[arg(long, default_value_t = 0.0)]
+        min_delta: f64,
+        /// Only show increases (up), decreases (down) or both (any)
+        #[arg(long, default_value = "any")]
+        direction: String,
+        /// Ignore rows logged less than this many seconds after logging started
+        #[arg(long, value_name = "SECONDS")]
+        since: Option<f64>,
+    },
+    /// Print scan results saved with `scan --save`
+    Load {
+        /// File written by `scan --save`
+        #[arg(short, long)]
+        file: String,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+        /// Print only the results close to this address, or to the N-th result with `#N`,
+        /// see `scan --near-result`
+        #[arg(long, value_name = "ADDR|#N")]
+        near_result: Option<String>,
+        /// Byte distance from --near-result, decimal or `0x` hex
+        #[arg(long, value_name = "BYTES", value_parser = parse_distance, default_value = "0x100",
+              requires = "near_result")]
+        within: u64,
+    },
+    /// Find pointer-aligned values in writable memory that point to any of the given
+    /// addresses (64-bit processes)
+    FindRefs {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Target addresses, comma separated (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            required_unless_present_any = ["results", "addresses_file"]
+        )]
+        addresses: Vec<String>,
+        /// Also use the addresses listed in a file, one `address[:size]` per line (sizes
+        /// are ignored)
+        #[arg(long)]
+        addresses_file: Option<String>,
+        /// Also use the addresses from a file written by `scan --save`
+        #[arg(short, long)]
+        results: Option<String>,
+    },
+    /// Print the addresses of a file written by `scan --save` as an address list, one
+    /// `address:size` per line, for --addresses-file. While the process the results were
+    /// saved from runs, addresses inside a module are written as `module+offset`, which
+    /// still resolves after the module is loaded at another base (ASLR)
+    ScanToAddresses {
+        /// File written by `scan --save`
+        file: String,
+        /// Write every address as is, even the ones inside a module
+        #[arg(long)]
+        absolute: bool,
+    },
+    /// Print how a file written by `scan --save` was produced (value, type, range, filters
+    /// and when), without loading its results
+    ScanInfo {
+        /// File written by `scan --save`
+        file: String,
+    },
+    /// Print a checksum of an address range, e.g. to check whether a patch is applied or a
+    /// module matches a known build. Unreadable pages are left out of the digest
+    HashRegion {
+        /// Process ID
+        #[arg(short, long, required_unless_present = "dump_file")]
+        pid: Option<u32>,
+        /// Hash a memory dump file instead of a live process
+        #[arg(long, conflicts_with = "pid")]
+        dump_file: Option<String>,
+        /// Region map for --dump-file, JSON (`map.json`) or lines of `<start>-<end> <perms>
+        /// <file offset>` in hex
+        #[arg(long, requires = "dump_file")]
+        map: Option<String>,
+        /// Start address (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        start: String,
+        /// End address, exclusive
+        #[arg(short, long)]
+        end: String,
+        /// Digest algorithm (crc32, sha256)
+        #[arg(long, default_value = "sha256")]
+        algo: String,
+    },
+    /// Find pointers into a module: pointer-aligned values in writable memory that point
+    /// inside the span the module is mapped at, e.g. vtables and back-references
+    ScanPtrInto {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Module file name, e.g. libgame.so
+        #[arg(short, long)]
+        module: String,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Find the pointers to one address, e.g. a function's from `nm` or the exports:
+    /// pointer-aligned values in writable memory equal to it, like callbacks and vtable slots
+    ScanPtrEq {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Address the pointers hold (hex, decimal with a 0d prefix, or module+offset)
+        #[arg(short, long)]
+        target: String,
+        /// Only search writable regions, leaving out read-only data such as vtables
+        #[arg(long)]
+        writable_only: bool,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Resolve the pointer paths of a paths file in a running process and report which
+    /// of them still lead to the expected value, e.g. after restarting the game
+    Ptrtest {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// JSON file of pointer paths, `[{"base": "libgame.so+0x1a2b0", "offsets": ["0x10",
+        /// 8]}]`
+        #[arg(long)]
+        paths: String,
+        /// Value the final address of a stable path holds
+        #[arg(long)]
+        expect_value: String,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+        /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
         #[arg(short, long, default_value = "u32")]
         r#type: String,
+        /// Width of a bare `--type raw` value in bytes, same as `--type raw:<n>`
+        #[arg(short, long)]
+        size: Option<usize>,
+    },
+    /// Find instructions in executable memory, e.g. `mov eax, [rcx+?]` (see the README for
+    /// the supported instructions)
+    FindCode {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Instruction in Intel syntax, `?` matches any displacement or immediate
+        #[arg(long)]
+        pattern: String,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Find the places that hold the same (or a different) value in two processes, e.g.
+    /// two instances of a game. Regions are paired by module+offset, anonymous memory
+    /// can't be paired and is skipped
+    ScanCompare {
+        /// First process ID
+        #[arg(long)]
+        pid_a: u32,
+        /// Second process ID
+        #[arg(long)]
+        pid_b: u32,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, xor:<key>)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Report values that are equal in both processes or that differ
+        #[arg(long, default_value = "equal")]
+        mode: String,
+        /// Stop after finding N values
+        #[arg(long, value_name = "N", default_value_t = 100_000)]
+        max_results: usize,
+        /// Print at most N result rows, asking on a terminal before printing more. 0
+        /// prints every row
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+        max_display: usize,
+    },
+    /// Print the most frequent aligned values in a mapped region, e.g. to tell padding
+    /// from real data before choosing a value to scan for
+    ValueHistogram {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, xor:<key>)
+        #[arg(short, long, default_value = "u32")]
+        r#type: String,
+        /// Mapping to tally, by file name (`libc.so.6`), full path or pseudo name
+        /// (`[heap]`, `[stack]`), as listed in /proc/<pid>/maps
+        #[arg(long)]
+        region: String,
+        /// Number of values to print
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        top: usize,
+        /// Tally at most N distinct values, bounding memory use. Values first seen after
+        /// the limit is reached are only counted in the total
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISTINCT)]
+        max_distinct: usize,
+    },
+    /// Write a value to every address of a file written by `scan --save`, e.g. all copies
+    /// of a coordinate, reporting each address that failed
+    WriteResults {
+        /// File written by `scan --save`
+        file: String,
+        /// Process ID, defaults to the process the results were saved from if it still
+        /// runs: a file from a process that exited (its pid may be another process's now)
+        /// needs --pid
+        #[arg(short, long)]
+        pid: Option<u32>,
+        /// Value to write
+        #[arg(short, long)]
+        value: String,
+        /// Value type, defaults to the type the results were scanned as
+        #[arg(short, long)]
+        r#type: Option<String>,
+        /// Width of a bare `--type raw` value in bytes, same as `--type raw:<n>`
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// Read every address back after writing it and report the ones holding other bytes
+        #[arg(long)]
+        verify: bool,
+        /// Signed byte offset added to each address before writing, e.g. `0x10` or `-0x8` for
+        /// a field of the struct a result is the base of. Every moved address has to be in
+        /// writable memory
+        #[arg(long, value_parser = parse_offset, allow_hyphen_values = true, default_value = "0")]
+        offset: i64,
+        /// Write only to some of the results by index: `5..15`, `-10..` for the last ten,
+        /// `..5` or a single `7`. Negative indices count from the end, slice ends are exclusive
+        #[arg(long, value_parser = parse_selection, allow_hyphen_values = true)]
+        select: Option<Selection>,
+        /// Confirm writing to more than 100 addresses
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Copy shellcode into newly allocated executable memory of a process and run it in a
+    /// new thread. Faulty shellcode will crash the target
+    Inject {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// File with the raw machine code to run
+        #[arg(short, long)]
+        file: String,
+        /// Confirm that the target may crash
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Suspend, resume or kill a process, e.g. to freeze a game while editing it. Pid 0,
+    /// init and this tool itself are refused
+    Process {
+        /// Process ID
+        #[arg(short, long)]
+        pid: u32,
+        /// suspend, resume or kill
+        #[arg(short, long)]
+        action: String,
+        /// Confirm killing the process
+        #[arg(long)]
+        yes: bool,
     },
 }
 
-pub fn run() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::parse();
+#[derive(Args, Debug)]
+pub struct LogChangesArgs {
+    /// Process ID
+    #[arg(short, long)]
+    pub pid: u32,
+    /// Address to watch (hex, decimal with a 0d prefix, or module+offset)
+    #[arg(short, long, required_unless_present = "addresses_file")]
+    pub address: Option<String>,
+    /// Watch every address listed in a file instead, one `address[:size]` per line, each
+    /// logged line ends with the entry that changed
+    #[arg(long, conflicts_with_all = ["address", "follow_forks"])]
+    pub addresses_file: Option<String>,
+    /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+    /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+    #[arg(short, long, default_value = "u32")]
+    pub r#type: String,
+    /// Read size (for string/hex types), or the width of a bare `--type raw`. A size on a
+    /// line of --addresses-file takes precedence
+    #[arg(short, long)]
+    pub size: Option<usize>,
+    /// Polls per second, 0 polls as fast as possible
+    #[arg(short, long, default_value_t = 1000)]
+    pub rate: u64,
+    /// Time between polls instead of --rate, e.g. `50ms`, `1.5s` or `250` (milliseconds)
+    #[arg(short, long, value_parser = parse_duration, conflicts_with = "rate")]
+    pub interval: Option<Duration>,
+    /// Back off while the value stays the same, doubling the time between polls up to
+    /// --max-interval, and return to the --interval/--rate pace as soon as it changes
+    #[arg(long)]
+    pub adaptive: bool,
+    /// Longest time between polls with --adaptive
+    #[arg(long, value_parser = parse_duration, default_value = "1s", requires = "adaptive")]
+    pub max_interval: Duration,
+    /// Write the log to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Decimal places to show for f32/f64 values
+    #[arg(long, value_name = "N")]
+    pub precision: Option<usize>,
+
+    /// Show how each value changed instead of the old and new columns: the delta for
+    /// numbers (plus the relative change for floats), the changed bytes otherwise
+    #[arg(long)]
+    pub show_delta: bool,
+    /// When the process exits, keep logging in the process that took over from it: the
+    /// same PID after an exec, else the newest child, else the newest sibling running
+    /// the same executable. Module-relative addresses are resolved again in it. Off
+    /// by default, an unrelated instance of the program can be taken for the successor
+    #[arg(long)]
+    pub follow_forks: bool,
+    /// How long --follow-forks waits for a successor to appear
+    #[arg(long, value_parser = parse_duration, default_value = "10s", requires = "follow_forks")]
+    pub follow_timeout: Duration,
+}
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Process ID
+    #[arg(short, long, required_unless_present = "dump_file")]
+    pub pid: Option<u32>,
+    /// Scan a memory dump file instead of a live process. ELF core files are laid out by
+    /// their segments, other files start at address 0 unless --map is given
+    #[arg(long, conflicts_with = "pid")]
+    pub dump_file: Option<String>,
+    /// Region map for --dump-file, JSON (`map.json`) or lines of `<start>-<end> <perms>
+    /// <file offset>` in hex
+    #[arg(long, requires = "dump_file")]
+    pub map: Option<String>,
+    /// Value to search for. For numeric types `a..b` matches every value from a to b
+    /// (inclusive) at the aligned positions, e.g. `100..110`
+    #[arg(short, long, required_unless_present = "unknown")]
+    pub value: Option<String>,
+    /// Unknown initial value: snapshot every position and narrow it down with --compare
+    /// or --expr.
+    /// Only positions aligned to the type size are kept, which is 4-8x smaller than
+    /// every byte offset but misses unaligned values, see --dense
+    #[arg(long, conflicts_with_all = ["value", "not"], requires = "comparing")]
+    pub unknown: bool,
+    /// Snapshot every byte offset in an --unknown scan instead of aligned ones only
+    #[arg(long, requires = "unknown", conflicts_with = "align_to")]
+    pub dense: bool,
+    /// Value type (u32, i32, u64, i64, f32, f64, ptr, bool, string, hex, raw:<n> for n-byte
+    /// blobs, or xor:<key> for u32 values XOR-ed with a hex key)
+    #[arg(short, long, default_value = "u32")]
+    pub r#type: String,
+    /// Width of a bare `--type raw` value in bytes, same as `--type raw:<n>`
+    #[arg(long)]
+    pub size: Option<usize>,
+    /// Start address (hex, decimal with a 0d prefix, or module+offset)
+    #[arg(short, long)]
+    pub start: Option<String>,
+    /// End address (hex, decimal with a 0d prefix, or module+offset)
+    #[arg(short, long)]
+    pub end: Option<String>,
+    /// Only scan the region with this index in the `regions` list, whatever its
+    /// permissions. Repeat to scan several. Indices change whenever the process maps or
+    /// unmaps memory, so take them from a fresh `regions` listing
+    #[arg(long, value_name = "N", conflicts_with_all = ["start", "end"])]
+    pub region_index: Vec<usize>,
+    /// Only scan the stack of this thread of the process, see `threads`. Finds the locals
+    /// of one thread, e.g. a worker's loop counter
+    #[arg(long, value_name = "TID", requires = "pid", conflicts_with_all = ["start", "end", "region_index"])]
+    pub thread: Option<u32>,
+    /// Match addresses whose value is NOT equal to the given value. On a full
+    /// memory scan this matches almost every (type-aligned) offset, so narrow
+    /// the range with --start/--end
+    #[arg(long)]
+    pub not: bool,
+    /// Treat a string --value as a pattern: `?` matches any single character, `[0-9]`,
+    /// `[abc]` and `[!a-z]` one character of (or outside) a class, and `\\` escapes the
+    /// next character. Without it `?` and `[` are matched literally
+    #[arg(long, requires = "value")]
+    pub glob: bool,
+    /// Don't look for values that start at the end of one region and continue in the
+    /// next one right after it, e.g. a byte pattern running from code into data
+    #[arg(long)]
+    pub no_stitch: bool,
+    /// Only report addresses that are a multiple of N, regardless of the value type
+    #[arg(long, value_name = "N")]
+    pub align_to: Option<usize>,
+    /// Rough first pass: check only every K-th aligned position (addresses that are a
+    /// multiple of the type size, or --align-to, times K), exact scans too. Faster, but
+    /// MISSES every match in between, so rescan the area it points to without --stride
+    #[arg(long, value_name = "K")]
+    pub stride: Option<usize>,
+    /// List the matches at addresses that are a multiple of the type size first, they are
+    /// more likely real values than the misaligned ones (marked `-` in the A column)
+    #[arg(long)]
+    pub prefer_aligned: bool,
+    /// Order the results by address, value (the decoded value, numerically for numbers)
+    /// or module (the mapping they are in, anonymous memory last) before they are printed
+    /// and saved
+    #[arg(long, value_name = "KEY")]
+    pub sort: Option<String>,
+    /// Sort in descending order, e.g. the highest values first
+    #[arg(long, requires = "sort")]
+    pub desc: bool,
+    /// Keep only the results close to this address (an address expression, or `#N` for
+    /// the N-th result from 0 in address order), e.g. the other fields of the struct a
+    /// known value is in
+    #[arg(long, value_name = "ADDR|#N")]
+    pub near_result: Option<String>,
+    /// Byte distance before or after the --near-result address a result may be at,
+    /// decimal or `0x` hex
+    #[arg(long, value_name = "BYTES", value_parser = parse_distance, default_value = "0x100",
+          requires = "near_result")]
+    pub within: u64,
+    /// Keep only the results in regions of these kinds, comma separated: code, stack,
+    /// heap (also writable anonymous memory), file (a mapped file's data) or data, as in
+    /// the Kind column. E.g. `--only heap` for dynamically allocated objects
+    #[arg(long, value_name = "KINDS", value_delimiter = ',', value_parser = parse_region_kind)]
+    pub only: Vec<RegionKind>,
+    /// Skip regions that are a single repeated byte (e.g. zero-filled pages) unless the
+    /// value can match there. Heuristic: regions are sampled and only read in full when
+    /// the samples agree
+    #[arg(long)]
+    pub skip_uniform: bool,
+    /// Take a hex or raw:<n> --value byte-reversed, so `0x01020304` finds the
+    /// little-endian number 0x01020304 (04 03 02 01 in memory) instead of the bytes as typed
+    #[arg(long)]
+    pub reverse_hex: bool,
+    /// Skip the regions that can't be read instead of failing the scan, and print how
+    /// many were skipped and why after the scan
+    #[arg(long)]
+    pub continue_on_error: bool,
+    /// Start from a result set saved with --save instead of scanning everything: each saved
+    /// address is read again and kept if it holds --value, like a next scan
+    #[arg(long, value_name = "FILE", requires = "value",
+          conflicts_with_all = ["unknown", "start", "end", "region_index", "thread", "spill_to"])]
+    pub previous: Option<String>,
+    /// With --previous, also scan the regions the saved addresses are in for --value and add
+    /// the new matches, for a value that moved within its region (e.g. a reallocated buffer)
+    #[arg(long, requires = "previous")]
+    pub also_scan: bool,
+    /// After the initial scan, keep filtering results by how their value changed
+    /// (increased, decreased, changed, unchanged)
+    #[arg(long, group = "comparing")]
+    pub compare: Option<String>,
+    /// Like --compare, but keep the results for which this condition over the baseline
+    /// value `prev` and the current value `cur` holds, e.g. `cur > prev * 2` or
+    /// `cur == prev + 100 || cur == prev - 100`. Takes numbers, `+ - * /`, comparisons,
+    /// `&&`, `||` and parentheses; needs a numeric type
+    #[arg(long, group = "comparing")]
+    pub expr: Option<String>,
+    /// Read every match once more --stable-delay after the scan and keep the ones still
+    /// holding the value, to drop values that only matched in passing
+    #[arg(long)]
+    pub stable: bool,
+    /// Delay before the --stable read, e.g. `100ms`, `1s` or `250` (milliseconds)
+    #[arg(long, value_parser = parse_duration, default_value = "100ms", requires = "stable")]
+    pub stable_delay: Duration,
+    /// Instead of compare rounds, read every result --samples times, --interval apart, and
+    /// keep the ones whose value fell (dec) or rose (inc) at every sample, to find timers
+    /// and cooldowns. Results that can't be read at some sample are dropped
+    #[arg(long, group = "comparing", value_name = "DIRECTION")]
+    pub monotonic: Option<String>,
+    /// Number of samples --monotonic takes
+    #[arg(long, value_name = "K", default_value_t = 5, requires = "monotonic",
+          value_parser = clap::value_parser!(u32).range(2..))]
+    pub samples: u32,
+    /// What compare rounds check against: the value from the first scan (initial)
+    /// or from the last round (previous)
+    #[arg(long, default_value = "previous", requires = "comparing", conflicts_with = "monotonic")]
+    pub baseline: String,
+    /// Number of compare rounds
+    #[arg(long, default_value_t = 1, requires = "comparing", conflicts_with = "monotonic")]
+    pub rounds: u32,
+    /// Delay before each compare round or --monotonic sample, e.g. `500ms`, `2s` or `1000` (milliseconds)
+    #[arg(long, value_parser = parse_duration, default_value = "1s", requires = "comparing")]
+    pub interval: Duration,
+    /// Print how many result reads the compare rounds served from cached pages
+    #[arg(long, requires = "comparing")]
+    pub cache_stats: bool,
+    /// Decimal places to show for f32/f64 values
+    #[arg(long, value_name = "N")]
+    pub precision: Option<usize>,
+    /// Show the N bytes (at most 4096) before and after each result as hex, to recognize
+    /// the struct it is in. Reads memory for every row, so only the first 1000 rows get it
+    #[arg(long, value_name = "N")]
+    pub context: Option<usize>,
+    /// Save the final results to this file, see `--format` and `load`
+    #[arg(long)]
+    pub save: Option<String>,
+    /// Format of the `--save` file: bin, compact for millions of results, or json for
+    /// other tools to read. `load` and the other commands reading it take either
+    #[arg(long, value_name = "FORMAT", default_value = "bin", requires = "save")]
+    pub format: String,
+    /// Also write the results table to this file, with every row and full values
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Print at most N result rows, asking on a terminal before printing more. 0
+    /// prints every row
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISPLAY)]
+    pub max_display: usize,
+    /// Store the values along with the addresses in the saved file
+    #[arg(long, requires = "save")]
+    pub save_values: bool,
+    /// Stop the process while its memory is read (SIGSTOP/SIGCONT on Linux, every thread
+    /// suspended on Windows), so each scan and compare round sees one consistent state.
+    /// It runs between compare rounds. A paused game can drop its server connection
+    #[arg(long, requires = "pid")]
+    pub freeze_target: bool,
+    /// Keep the results in a memory-mapped file at this path instead of in memory, for
+    /// scans with tens of millions of matches (e.g. --unknown). Compare rounds rewrite it
+    /// in place, and it is left with the final results in fixed size records. Linux only
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["save", "prefer_aligned", "sort", "near_result", "only"])]
+    pub spill_to: Option<String>,
+    /// Write the progress of the initial scan to this file every 10 seconds and when it is
+    /// interrupted, to continue it with --resume, e.g. for an --unknown scan over
+    /// gigabytes. The file is removed once the scan is done
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["previous", "spill_to", "resume"])]
+    pub checkpoint: Option<String>,
+    /// Continue the initial scan from a file written with --checkpoint, given the same
+    /// scan arguments, and keep checkpointing to it. Refused when the regions the scan
+    /// covers were mapped, unmapped or changed since
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["previous", "spill_to"])]
+    pub resume: Option<String>,
+}
 
-    match cli.command {
+pub fn run(command: Commands, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    match command {
         Commands::ListProcesses => list_processes()?,
-        Commands::Scan { pid, value, r#type, start, end } => {
-            scan_memory(pid, &value, &r#type, start.as_deref(), end.as_deref())?
+        Commands::Types => list_types()?,
+        Commands::WaitFor { name, timeout, then } => wait_for(&name, timeout, &then, format)?,
+        Commands::Info { pid, env } => process_info(pid, env)?,
+        Commands::Scan(args) => scan_memory(&args, format)?,
+        Commands::ScanAll { pids, name, value, r#type, threads, max_display } => {
+            scan_all(&pids, name.as_deref(), &value, &r#type, threads, max_display, format)?
+        }
+        Commands::ScanLike { pid, address, r#type, size, precision, max_display } => {
+            scan_like(pid, &address, &r#type, size, precision, max_display, format)?
+        }
+        Commands::Assist { pid, address } => assist(pid, &address)?,
+        Commands::Resolve { pid, address } => {
+            println!("{} = 0x{:x}", address.trim(), resolve_address(Some(pid), &address)?)
+        }
+        Commands::AddrToOffset { pid, address } => {
+            let address = resolve_address(Some(pid), &address)?;
+            println!("0x{:x} = {}", address, crate::core::mem::addr_to_offset(pid, address)?)
+        }
+        Commands::Regions { pid, dump_file, map } => {
+            list_regions(&MemoryTarget::from_args(pid, dump_file, map))?
+        }
+        Commands::Threads { pid } => list_threads(pid)?,
+        Commands::Read {
+            pid,
+            dump_file,
+            map,
+            address,
+            addresses_file,
+            results,
+            offset,
+            select,
+            r#type,
+            size,
+            precision,
+            count,
+            until_null,
+            max_len,
+        } => {
+            let target = MemoryTarget::from_args(pid, dump_file, map);
+            match (addresses_file, results, address) {
+                (Some(path), _, _) => read_many(&target, &path, &r#type, size, precision, format)?,
+                (None, Some(path), _) => {
+                    let offset = offset.unwrap_or(0);
+                    read_results(&target, &path, offset, select, &r#type, size, precision, format)?
+                }
+                (None, None, Some(address)) => {
+                    let until_null = until_null.then_some(max_len);
+                    read_memory(&target, &address, &r#type, size, precision, count, until_null, format)?
+                }
+                (None, None, None) => unreachable!("clap requires --address, --addresses-file or --results"),
+            }
+        }
+        Commands::ReadStruct { pid, dump_file, map, address, schema, precision } => {
+            let target = MemoryTarget::from_args(pid, dump_file, map);
+            read_struct_fields(&target, &address, &schema, precision, format)?
+        }
+        Commands::Write { pid, address, value, r#type, count, size, op, by, reverse_hex, preview, confirm } => {
+            let mode = match (preview, confirm) {
+                (true, _) => WriteMode::Preview,
+                (_, true) => WriteMode::Confirm,
+                _ => WriteMode::Write,
+            };
+            match (op, value) {
+                (Some(op), _) => write_op(pid, &address, &r#type, &op, by, mode)?,
+                (None, Some(value)) => {
+                    let value_type = parse_sized_value_type(&r#type, size)?;
+                    write_memory(pid, &address, &value, value_type, count, reverse_hex, mode)?
+                }
+                (None, None) => unreachable!("clap requires --value or --op"),
+            }
+        }
+        Commands::LogChanges(args) => log_changes(&args, format)?,
+        Commands::Freeze { pid, address, addresses_file, value, r#type, size, interval } => {
+            let value_type = parse_sized_value_type(&r#type, size)?;
+            let target = match (&address, &addresses_file) {
+                (_, Some(path)) => FreezeTarget::List(path),
+                (Some(address), None) => FreezeTarget::Address(address),
+                (None, None) => unreachable!("clap requires --address or --addresses-file"),
+            };
+            freeze_memory(pid, target, &value, value_type, interval)?
+        }
+        Commands::Watchpoint { pid, address, len, on, count } => {
+            watchpoint(pid, &address, len, &on, count)?
+        }
+        Commands::Nop { pid, address, len, backup, no_verify, force, no_restore } => {
+            nop_memory(pid, &address, len, backup.as_deref(), !no_verify, force, !no_restore)?
+        }
+        Commands::Restore { pid, backup, address, bytes } => {
+            let entries = match (backup, address, bytes) {
+                (Some(backup), _, _) => parse_backup(&std::fs::read_to_string(backup)?)?,
+                (None, Some(address), Some(bytes)) => vec![(
+                    resolve_address(Some(pid), &address)?,
+                    hex::decode(bytes.trim_start_matches("0x"))?,
+                )],
+                _ => return Err("Either --backup or --address with --bytes is required".into()),
+            };
+            restore_memory(pid, &entries)?
         }
-        Commands::Read { pid, address, r#type, size } => {
-            read_memory(pid, &address, &r#type, size)?
+        Commands::UndoLast { pid, clear_journal } => undo_last_write(pid, clear_journal)?,
+        Commands::AnalyzeLog { file, min_delta, direction, since } => {
+            analyze_log(&file, min_delta, &direction, since)?
         }
-        Commands::Write { pid, address, value, r#type } => {
-            write_memory(pid, &address, &value, &r#type)?
+        Commands::Load { file, max_display, near_result, within } => {
+            load_results(&file, max_display, near_result.as_deref().map(|spec| (spec, within)), format)?
         }
+        Commands::FindRefs { pid, addresses, addresses_file, results } => {
+            let listed = match addresses_file {
+                Some(path) => {
+                    let entries = read_address_file(&path)?;
+                    warn_absolute_entries(&path, &entries);
+                    resolve_entries(Some(pid), &path, &entries)?
+                }
+                None => vec![],
+            };
+            find_refs(pid, &addresses, &listed, results.as_deref(), format)?
+        }
+        Commands::ScanToAddresses { file, absolute } => scan_to_addresses(&file, absolute)?,
+        Commands::ScanInfo { file } => scan_info(&file)?,
+        Commands::Probe { pid, address, r#type, size, samples, interval, precision } => {
+            probe(pid, &address, &r#type, size, samples, interval, precision, format)?
+        }
+        Commands::HashRegion { pid, dump_file, map, start, end, algo } => {
+            let target = MemoryTarget::from_args(pid, dump_file, map);
+            hash_region(&target, &start, &end, &algo)?
+        }
+        Commands::ScanPtrInto { pid, module, max_display } => {
+            scan_ptr_into(pid, &module, max_display, format)?
+        }
+        Commands::ScanPtrEq { pid, target, writable_only, max_display } => {
+            scan_ptr_eq(pid, &target, writable_only, max_display, format)?
+        }
+        Commands::Ptrtest { pid, paths, expect_value, r#type, size } => {
+            test_pointer_paths(pid, &paths, &expect_value, &r#type, size, format)?
+        }
+        Commands::FindCode { pid, pattern, max_display } => find_code(pid, &pattern, max_display, format)?,
+        Commands::ScanCompare { pid_a, pid_b, r#type, mode, max_results, max_display } => {
+            scan_compare(pid_a, pid_b, &r#type, &mode, max_results, max_display, format)?
+        }
+        Commands::ValueHistogram { pid, r#type, region, top, max_distinct } => {
+            value_histogram(pid, &r#type, &region, top, max_distinct, format)?
+        }
+        Commands::WriteResults { file, pid, value, r#type, size, verify, offset, select, yes } => {
+            let options = WriteResultsOptions { verify, offset, select, yes };
+            write_results(&file, pid, &value, r#type.as_deref(), size, options, format)?
+        }
+        Commands::Inject { pid, file, yes } => inject_code(pid, &file, yes)?,
+        Commands::Process { pid, action, yes } => control_process(pid, &action, yes)?,
+    }
+
+    Ok(())
+}
+
+/// Select the memory access of the process handles the command opens, see `--io`
+pub fn set_io_backend(name: &str) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{self, IoBackend};
+
+    let backend = match name.to_lowercase().as_str() {
+        "auto" => IoBackend::Auto,
+        "proc-mem" | "proc" => IoBackend::ProcMem,
+        _ => return Err(format!("Invalid --io '{name}', use auto or proc-mem").into()),
+    };
+    mem::set_io_backend(backend);
+    Ok(())
+}
+
+/// The format result rows are printed in, see `--output-format`
+pub fn output_format(name: &str) -> Result<OutputFormat, Box<dyn Error>> {
+    Ok(OutputFormat::parse(name)?)
+}
+
+/// Status lines go to stderr, stdout only gets the PID for `$(cli wait-for ...)`
+pub fn wait_for(
+    name: &str,
+    timeout: Duration,
+    then: &[String],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::proc::wait_for_process;
+
+    eprintln!("Waiting up to {timeout:?} for a process named '{name}...'");
+    let found = wait_for_process(name, timeout, interrupt_flag())?;
+    let pid = found[0].pid;
+    if found.len() > 1 {
+        let list: Vec<String> = found.iter().map(|p| format!("{} ({})", p.pid, p.name)).collect();
+        eprintln!("{} processes match: {}, using {}", found.len(), list.join(", "), pid);
+    }
+    if then.is_empty() {
+        println!("{pid}");
+        return Ok(());
+    }
+
+    eprintln!("Found {} ({}), running {}", pid, found[0].name, then.join(" "));
+    let pid = pid.to_string();
+    let args = ["cli"].into_iter().chain(then.iter().map(String::as_str)).chain(["--pid", pid.as_str()]);
+    run(Commands::try_parse_from(args)?, format)
+}
+
+pub fn list_types() -> Result<(), Box<dyn Error>> {
+    let mut table = Table::new(&["Type", "Size", "Alignment", "Example"]);
+    for value_type in ValueType::all() {
+        let size = match value_type {
+            ValueType::Pointer => String::from("4 or 8"),
+            _ if value_type.get_size() == 0 => String::from("variable"),
+            _ => value_type.get_size().to_string(),
+        };
+        let alignment = match value_type {
+            ValueType::Pointer => String::from("4 or 8"),
+            _ => value_type.alignment().to_string(),
+        };
+        table.add_row(vec![
+            value_type.name().to_string(),
+            size,
+            alignment,
+            value_type.example_value().to_string(),
+        ]);
     }
+    table.print(None)?;
+    println!();
+    println!("Pointers are as wide as the target process' pointers.");
+    println!("Unknown value and --not scans step by the alignment, exact scans match anywhere.");
+    println!("xor:<hex key> reads u32 values stored XOR-ed with the key, e.g. --type xor:5a5a5a5a.");
+    println!("raw:<n> is an n-byte blob in hex, e.g. --type raw:16 for 128-bit vectors.");
 
     Ok(())
 }
@@ -90,121 +1171,3599 @@ pub fn list_processes() -> Result<(), Box<dyn Error>> {
     let mut system = System::new_all();
     system.refresh_all();
 
-    println!("{:<10} {:<20} {:<10}", "PID", "Name", "Status");
-    println!("{:-<10} {:-<20} {:-<10}", "", "", "");
-
+    let mut table = Table::new(&["PID", "Name", "Status"]);
     for (pid, process) in system.processes() {
-        println!("{:<10} {:<20} {:<10}", pid, process.name().to_string_lossy(), process.status());
+        table.add_row(vec![
+            pid.to_string(),
+            process.name().to_string_lossy().into_owned(),
+            process.status().to_string(),
+        ]);
     }
+    table.print(None)?;
 
     Ok(())
 }
 
-fn parse_address(addr_str: Option<&str>) -> Result<Option<u64>, Box<dyn Error>> {
-    match addr_str {
-        Some(addr) => {
-            let addr = addr.trim_start_matches("0x");
-            let addr = u64::from_str_radix(addr, 16)?;
-            Ok(Some(addr))
-        }
-        None => Ok(None),
+fn parse_address(pid: Option<u32>, addr_str: Option<&str>) -> Result<Option<u64>, Box<dyn Error>> {
+    addr_str.map(|a| resolve_address(pid, a)).transpose()
+}
+
+/// Warn when a user supplied range doesn't intersect any mapped region, listing the
+/// closest regions on either side so typos are easy to spot.
+fn warn_if_range_unmapped(pid: u32, start: Option<u64>, end: Option<u64>, scan: &Scan) {
+    use crate::core::mem::get_memory_regions;
+
+    if (start.is_none() && end.is_none()) || !scan.memory_regions().is_empty() {
+        return;
+    }
+
+    let start = start.unwrap_or(0);
+    let end = end.unwrap_or(u64::MAX);
+    eprintln!(
+        "Warning: range 0x{:x}-0x{:x} does not overlap any mapped region",
+        start, end
+    );
+
+    let Ok(regions) = get_memory_regions(pid, None, None, Some(scan.memory_permissions())) else {
+        return;
+    };
+
+    let before = regions.iter().rev().find(|r| r.end <= start);
+    let after = regions.iter().find(|r| r.start >= end);
+    if before.is_none() && after.is_none() {
+        return;
+    }
+
+    eprintln!("Nearest regions:");
+    for region in [before, after].into_iter().flatten() {
+        eprintln!("  0x{:x}-0x{:x}", region.start, region.end);
     }
 }
 
 fn parse_value_type(type_str: &str) -> Result<ValueType, Box<dyn Error>> {
-    match type_str.to_lowercase().as_str() {
-        "u32" => Ok(ValueType::U32),
-        "i32" => Ok(ValueType::I32),
-        "u64" => Ok(ValueType::U64),
-        "i64" => Ok(ValueType::I64),
-        "string" => Ok(ValueType::String),
-        "hex" => Ok(ValueType::Hex),
-        _ => Err("Invalid value type".into()),
+    let type_str = type_str.to_lowercase();
+    if let Some(value_type) = ValueType::all().into_iter().find(|t| t.name() == type_str) {
+        return Ok(value_type);
+    }
+    match type_str.as_str() {
+        "pointer" => Ok(ValueType::Pointer),
+        "raw" => Err("--type raw needs a width, e.g. raw:16 or --size 16".into()),
+        raw if raw.starts_with("raw:") => match raw["raw:".len()..].parse::<usize>() {
+            Ok(width) if width > 0 => Ok(ValueType::Raw(width)),
+            _ => Err(format!("Invalid raw width '{}', expected a byte count", &raw["raw:".len()..]).into()),
+        },
+        custom => {
+            if let Some(key) = custom.strip_prefix("xor:") {
+                let key = u32::from_str_radix(key.trim_start_matches("0x"), 16)
+                    .map_err(|_| format!("Invalid XOR key '{key}', expected hex like 0x5a5a5a5a"))?;
+                // leaked once per run, registered names live as long as the registry
+                let name: &'static str = Box::leak(custom.to_string().into_boxed_str());
+                register_codec(name, Box::new(XorCodec { key }));
+            }
+            custom_value_type(custom).ok_or_else(|| "Invalid value type".into())
+        }
     }
 }
 
-pub fn scan_memory(
-    pid: u32,
-    value: &str,
-    type_str: &str,
-    start_str: Option<&str>,
-    end_str: Option<&str>,
-) -> Result<(), Box<dyn Error>> {
-    let value_type = parse_value_type(type_str)?;
-    let start = parse_address(start_str)?;
-    let end = parse_address(end_str)?;
+/// Like [`parse_value_type`], with `size` giving the width of a bare `raw` type
+fn parse_sized_value_type(type_str: &str, size: Option<usize>) -> Result<ValueType, Box<dyn Error>> {
+    match size {
+        Some(size) if type_str.eq_ignore_ascii_case("raw") => parse_value_type(&format!("raw:{size}")),
+        _ => parse_value_type(type_str),
+    }
+}
 
-    let mut scan = Scan::new(pid, vec![], value_type, start, end, None)?;
-    scan.set_value_from_str(value)?;
+/// Parse a duration like `250ms`, `1.5s`, `2m` or `100us`, a bare number is milliseconds
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{text}', expected e.g. 250ms or 1s"))?;
+    let seconds = match unit.trim() {
+        "" | "ms" => number / 1e3,
+        "us" | "µs" => number / 1e6,
+        "s" => number,
+        "m" | "min" => number * 60.0,
+        unit => return Err(format!("Unknown duration unit '{unit}', use us, ms, s or m")),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("Invalid duration '{text}': {e}"))
+}
 
-    println!("Scanning process {} for value '{}' (type: {})...", pid, value, type_str);
-    let results = scan.init()?;
+fn parse_compare_mode(mode_str: &str) -> Result<CompareMode, Box<dyn Error>> {
+    match mode_str.to_lowercase().as_str() {
+        "increased" => Ok(CompareMode::Increased),
+        "decreased" => Ok(CompareMode::Decreased),
+        "changed" => Ok(CompareMode::Changed),
+        "unchanged" => Ok(CompareMode::Unchanged),
+        _ => Err("Invalid compare mode".into()),
+    }
+}
 
-    println!("Found {} matches:", results.len());
-    println!("{:<20} {:<10} {:<10} {:<}", "Address", "Type", "Perms", "Value");
-    println!("{:-<20} {:-<10} {:-<10} {:-<}", "", "", "", "");
+/// A byte count, decimal or `0x` hex
+fn parse_distance(text: &str) -> Result<u64, String> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.map_err(|_| format!("Invalid byte count '{text}', expected decimal or 0x hex"))
+}
 
-    for result in results {
-        let value_str = result.get_string()?;
-        let perms_str = result
-            .perms
-            .iter()
-            .map(|p| match p {
-                crate::core::mem::MemoryRegionPerms::Read => "R",
-                crate::core::mem::MemoryRegionPerms::Write => "W",
-            })
-            .collect::<String>();
+/// Parse a region kind of `scan --only`
+fn parse_region_kind(name: &str) -> Result<RegionKind, String> {
+    use crate::core::proc::REGION_KINDS;
 
-        println!("0x{:<18} {:<10} {:<10} {:<}", result.address, type_str, perms_str, value_str);
-    }
+    RegionKind::from_name(name).ok_or_else(|| {
+        let kinds: Vec<&str> = REGION_KINDS.iter().map(RegionKind::name).collect();
+        format!("Unknown region kind '{name}', expected one of {}", kinds.join(", "))
+    })
+}
 
-    Ok(())
+/// Parse a `--select` of saved results, see [`Selection`]
+fn parse_selection(text: &str) -> Result<Selection, String> {
+    Selection::parse(text).map_err(|e| e.to_string())
 }
 
-pub fn read_memory(
-    pid: u32,
-    address_str: &str,
-    type_str: &str,
-    size: Option<usize>,
-) -> Result<(), Box<dyn Error>> {
-    use crate::core::mem::read_memory_address;
+/// The `results` `select` picks with their indices in the result set, all of them without
+fn select_results(
+    results: &[ScanResult],
+    select: Option<Selection>,
+) -> Result<Vec<(usize, &ScanResult)>, crate::core::results::SelectError> {
+    let range = select.map_or(Ok(0..results.len()), |select| select.range(results.len()))?;
+    Ok(results[range.clone()].iter().zip(range).map(|(result, i)| (i, result)).collect())
+}
 
-    let address = u64::from_str_radix(address_str.trim_start_matches("0x"), 16)?;
-    let value_type = parse_value_type(type_str)?;
+/// The process the results in `path` were saved from, as long as it still runs. Files
+/// only name it by pid, the start time saved with them tells a later process that got
+/// the pid apart.
+fn saved_process(path: &str, saved: &crate::core::results::SavedResults) -> Result<u32, Box<dyn Error>> {
+    use crate::core::proc::start_time;
 
-    let read_size = size.unwrap_or_else(|| match value_type {
-        ValueType::U64 | ValueType::I64 => 8,
-        ValueType::U32 | ValueType::I32 => 4,
-        ValueType::String | ValueType::Hex => 32,
-    });
+    let saved_start = saved.metadata.as_ref().and_then(|metadata| metadata.process_start);
+    match (saved_start, start_time(saved.pid)) {
+        (None, _) => Err(format!("{path} doesn't record which process it was saved from, pass --pid").into()),
+        (Some(saved_start), Some(started)) if saved_start == started => Ok(saved.pid),
+        _ => Err(format!(
+            "Process {} the results in {path} were saved from has exited, its pid may be another \
+             process's now. Pass --pid to use them with the process that runs now",
+            saved.pid
+        )
+        .into()),
+    }
+}
+
+/// Parse a signed byte offset, decimal or `0x` hex, e.g. `0x10` or `-8`
+fn parse_offset(text: &str) -> Result<i64, String> {
+    let (negative, magnitude) = match text.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.trim().trim_start_matches('+')),
+    };
+    let magnitude = parse_distance(magnitude)
+        .ok()
+        .and_then(|m| i64::try_from(m).ok())
+        .ok_or_else(|| format!("Invalid offset '{text}', expected e.g. 0x10, -0x8 or 16"))?;
+    Ok(if negative { -magnitude } else { magnitude })
+}
 
-    println!("Reading {} bytes from address 0x{} in process {}...", read_size, address_str, pid);
-    let value = read_memory_address(pid, address as usize, read_size)?;
+/// The address of a --near-result reference: `#N` picks the N-th of `results`, anything
+/// else is an address expression
+fn near_reference(pid: Option<u32>, spec: &str, results: &[ScanResult]) -> Result<u64, Box<dyn Error>> {
+    let Some(index) = spec.trim().strip_prefix('#') else {
+        return resolve_address(pid, spec);
+    };
+    let index: usize = index.parse().map_err(|_| format!("Invalid result index '{spec}'"))?;
+    match results.get(index) {
+        Some(result) => Ok(result.address),
+        None => Err(format!("Result #{index} doesn't exist, there are {} result(s)", results.len()).into()),
+    }
+}
 
-    let value_str = value_type.get_value_string(&value)?;
-    println!("Value: {}", value_str);
+/// Direction of a --monotonic filter, `dec` or `inc`
+fn parse_monotonic(direction: &str) -> Result<CompareMode, Box<dyn Error>> {
+    match direction.to_lowercase().as_str() {
+        "dec" | "decreasing" => Ok(CompareMode::Decreased),
+        "inc" | "increasing" => Ok(CompareMode::Increased),
+        _ => Err(format!("Invalid monotonic direction '{direction}', expected dec or inc").into()),
+    }
+}
 
-    Ok(())
+fn parse_compare_baseline(baseline_str: &str) -> Result<CompareBaseline, Box<dyn Error>> {
+    match baseline_str.to_lowercase().as_str() {
+        "initial" => Ok(CompareBaseline::Initial),
+        "previous" => Ok(CompareBaseline::Previous),
+        _ => Err("Invalid compare baseline".into()),
+    }
 }
 
-pub fn write_memory(
+pub fn scan_like(
     pid: u32,
     address_str: &str,
-    value: &str,
     type_str: &str,
+    size: Option<usize>,
+    precision: Option<usize>,
+    max_display: usize,
+    format: OutputFormat,
 ) -> Result<(), Box<dyn Error>> {
-    use crate::core::mem::write_memory_address;
-
-    let address = u64::from_str_radix(address_str.trim_start_matches("0x"), 16)?;
-    let value_type = parse_value_type(type_str)?;
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let address = resolve_address(Some(pid), address_str)?;
+    let size = resolve_read_size(size, value_type, Some(pid), config::current());
 
     let mut scan = Scan::new(pid, vec![], value_type, None, None, None)?;
-    let value_bytes = scan.value_from_str(value)?;
+    scan.set_value_from_address(address, size)?;
+    format.status(format_args!(
+        "Value at 0x{:x} is '{}', scanning process {} (type: {})...",
+        address,
+        value_type.format_value(&scan.value, precision).unwrap_or_default(),
+        pid,
+        type_str
+    ));
 
-    println!("Writing value '{}' to address 0x{} in process {}...", value, address_str, pid);
-    write_memory_address(pid, address as usize, &value_bytes)?;
+    let region_names = scan.region_names();
+    let results = scan.init()?;
+    let siblings: Vec<&ScanResult> = results.iter().filter(|r| r.address != address).collect();
+    let layout = Columns { region_names: Some(&region_names), type_str, precision, ..Default::default() };
+    print_scan_results(siblings, layout, max_display, None, false, format)
+}
 
-    println!("Write successful!");
+/// Bytes `assist` reads at the address, enough for 8 byte values and a short string
+const ASSIST_READ_SIZE: usize = 32;
 
-    Ok(())
+/// Results `assist` lists after each scan
+const ASSIST_MAX_DISPLAY: usize = 10;
+
+pub fn assist(pid: u32, address_str: &str) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{MemorySource, ProcessHandle};
+    use crate::core::scan::interpret_bytes;
+    use std::io::{BufRead, Write};
+
+    let address = resolve_address(Some(pid), address_str)?;
+    let handle = ProcessHandle::open(pid)?;
+    // a value near the end of a region can't be read in full, read what is there
+    let bytes = [ASSIST_READ_SIZE, 8, 4]
+        .into_iter()
+        .find_map(|size| handle.read(address as usize, size).ok().filter(|bytes| bytes.len() == size))
+        .ok_or_else(|| format!("0x{address:x} can't be read in process {pid}"))?;
+    let interpretations = interpret_bytes(&bytes);
+
+    println!("The bytes at 0x{:x} are {}, read as:", address, hex::encode(&bytes));
+    for (i, interpretation) in interpretations.iter().enumerate() {
+        println!("  {}) {:<6} {}", i + 1, interpretation.value_type.name(), interpretation.value);
+    }
+    let mut lines = std::io::stdin().lock().lines();
+    let mut ask = |question: &str| -> Result<Option<String>, Box<dyn Error>> {
+        print!("{question}");
+        std::io::stdout().flush()?;
+        Ok(lines.next().transpose()?.map(|line| line.trim().to_string()).filter(|line| !line.is_empty()))
+    };
+
+    let value_type = loop {
+        let question = format!("Which one is the value you see in the program? [1-{}] ", interpretations.len());
+        let Some(answer) = ask(&question)? else {
+            return Ok(());
+        };
+        match answer.parse::<usize>().ok().and_then(|choice| interpretations.get(choice.checked_sub(1)?)) {
+            Some(interpretation) => break interpretation.value_type,
+            None => println!("Enter a number from 1 to {}", interpretations.len()),
+        }
+    };
+
+    let type_name = value_type.name();
+    println!("Scanning for {type_name} values from now on, the same without assist:");
+    println!("  cli scan --pid {pid} --type {type_name} --value <value>");
+    let mut scan = Scan::new(pid, vec![], value_type, None, None, None)?;
+    let region_names = scan.region_names();
+    let mut scanned = false;
+    loop {
+        let question = match scanned {
+            false => "Value to scan for (empty line quits): ",
+            true => "Value now, after it changed in the program (empty line quits): ",
+        };
+        let Some(value) = ask(question)? else {
+            return Ok(());
+        };
+        if let Err(e) = scan.set_value_from_str(&value) {
+            println!("{e}");
+            continue;
+        }
+        match scanned {
+            false => scan.init()?,
+            true => scan.next_scan()?,
+        };
+        scanned = true;
+        if scan.maps_changed() {
+            println!("The memory map changed since the last scan, every address was read again");
+        }
+        let results = &scan.results;
+
+        let known = match results.iter().any(|r| r.address == address) {
+            true => format!(", 0x{address:x} among them"),
+            false => String::new(),
+        };
+        println!("{} match(es){}", results.len(), known);
+        for result in results.iter().take(ASSIST_MAX_DISPLAY) {
+            let region = region_names.region_name_for(result.address).unwrap_or("");
+            println!("  0x{:x} {} {}", result.address, value_type.format_value(&result.value, None)?, region);
+        }
+        if results.len() > ASSIST_MAX_DISPLAY {
+            println!("  ... change the value in the program and enter it again to narrow them down");
+        }
+        if results.is_empty() {
+            return Ok(());
+        }
+    }
+}
+
+pub fn process_info(pid: u32, show_env: bool) -> Result<(), Box<dyn Error>> {
+    use crate::core::proc::process_details;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const UNAVAILABLE: &str = "(unavailable)";
+
+    let details = process_details(pid).ok_or_else(|| format!("No process with PID {pid}"))?;
+    let path_or_unavailable = |path: &Option<std::path::PathBuf>| {
+        path.as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| String::from(UNAVAILABLE))
+    };
+    let started = details.start_time.map(|start| {
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(start))
+            .unwrap_or_default()
+            .as_secs();
+        format!("{} ({}h {}m {}s ago)", start, age / 3600, age / 60 % 60, age % 60)
+    });
+
+    println!("{:<12} {}", "PID", details.pid);
+    println!("{:<12} {}", "Name", details.name);
+    println!("{:<12} {}", "Status", details.status);
+    println!("{:<12} {}", "Executable", path_or_unavailable(&details.exe));
+    println!(
+        "{:<12} {}",
+        "Command",
+        details
+            .cmdline
+            .map(|args| args.join(" "))
+            .unwrap_or_else(|| String::from(UNAVAILABLE))
+    );
+    println!("{:<12} {}", "Directory", path_or_unavailable(&details.cwd));
+    println!("{:<12} {}", "Started", started.as_deref().unwrap_or(UNAVAILABLE));
+
+    if show_env {
+        match details.environ {
+            None => println!("{:<12} {}", "Environment", UNAVAILABLE),
+            Some(vars) => {
+                println!("Environment:");
+                for var in vars {
+                    println!("  {var}");
+                }
+            }
+        }
+    }
+
+    if details.exe.is_none() || details.cwd.is_none() {
+        println!();
+        println!(
+            "Some details are unavailable: the process may be a zombie, or reading them needs more permissions (e.g. run as root)"
+        );
+    }
+
+    Ok(())
+}
+
+/// Results of a `scan --previous` file, which has to hold the type being scanned for
+fn load_previous(path: &str, value_type: ValueType) -> Result<Vec<ScanResult>, Box<dyn Error>> {
+    use crate::core::results::load_saved;
+
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let saved = load_saved(&mut file)?;
+    // custom and raw types are saved as hex, their width is in the values
+    if saved.value_type != value_type && saved.value_type != ValueType::Hex {
+        let name = saved.value_type.name();
+        return Err(format!("{path} holds {name} results, scan it with --type {name}").into());
+    }
+    Ok(saved.results)
+}
+
+/// Where `scan` and `read` get memory from
+#[derive(Debug, Clone)]
+pub enum MemoryTarget {
+    Process(u32),
+    Dump { path: String, map: Option<String> },
+}
+
+impl MemoryTarget {
+    /// Clap makes sure exactly one of `pid` and `dump_file` is set
+    fn from_args(pid: Option<u32>, dump_file: Option<String>, map: Option<String>) -> Self {
+        match dump_file {
+            Some(path) => MemoryTarget::Dump { path, map },
+            None => MemoryTarget::Process(pid.unwrap_or_default()),
+        }
+    }
+
+    fn open(&self) -> Result<std::sync::Arc<dyn crate::core::mem::MemorySource>, Box<dyn Error>> {
+        use crate::core::{dump::DumpFile, mem::ProcessHandle};
+
+        Ok(match self {
+            MemoryTarget::Process(pid) => std::sync::Arc::new(ProcessHandle::open(*pid)?),
+            MemoryTarget::Dump { path, map } => {
+                std::sync::Arc::new(DumpFile::open(path, map.as_deref())?)
+            }
+        })
+    }
+
+    /// Process ID to record in saved results, 0 for dumps
+    fn pid(&self) -> u32 {
+        match self {
+            MemoryTarget::Process(pid) => *pid,
+            MemoryTarget::Dump { .. } => 0,
+        }
+    }
+
+    /// Process ID of a live process target
+    fn live_pid(&self) -> Option<u32> {
+        match self {
+            MemoryTarget::Process(pid) => Some(*pid),
+            MemoryTarget::Dump { .. } => None,
+        }
+    }
+}
+
+impl Display for MemoryTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryTarget::Process(pid) => write!(f, "process {pid}"),
+            MemoryTarget::Dump { path, .. } => write!(f, "dump {path}"),
+        }
+    }
+}
+
+pub fn scan_memory(args: &ScanArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+
+    let target = MemoryTarget::from_args(args.pid, args.dump_file.clone(), args.map.clone());
+    let (value, type_str, not) = (args.value.as_deref().unwrap_or(""), args.r#type.as_str(), args.not);
+    let value_type = parse_sized_value_type(type_str, args.size)?;
+    let (start, end) = match args.thread {
+        Some(tid) => {
+            let stack = thread_stack(target.pid(), tid)?;
+            format.status(format_args!("Scanning the stack of thread {tid} at 0x{:x}-0x{:x}", stack.start, stack.end));
+            (Some(stack.start), Some(stack.end))
+        }
+        None => (
+            parse_address(target.live_pid(), args.start.as_deref())?,
+            parse_address(target.live_pid(), args.end.as_deref())?,
+        ),
+    };
+    let compare = args.compare.as_deref().map(parse_compare_mode).transpose()?;
+    let expr = args.expr.as_deref().map(CompareExpr::parse).transpose()?;
+    let baseline = parse_compare_baseline(&args.baseline)?;
+    let monotonic = args.monotonic.as_deref().map(parse_monotonic).transpose()?;
+    let file_format = crate::core::results::ResultsFormat::parse(&args.format)?;
+    let sort = args
+        .sort
+        .as_deref()
+        .map(|key| {
+            SortKey::parse(key).ok_or(format!("Invalid sort key '{key}', expected address, value or module"))
+        })
+        .transpose()?;
+
+    if let (Some(start), Some(end)) = (start, end)
+        && start > end
+    {
+        return Err(ScanError::AddressMismatch.into());
+    }
+
+    let previous = args.previous.as_deref().map(|path| load_previous(path, value_type)).transpose()?;
+    let source = target.open()?;
+    let selected = match args.region_index.is_empty() {
+        true => None,
+        false => Some(select_regions(&all_regions(source.as_ref())?, &args.region_index)?),
+    };
+    let mut scan = Scan::with_source(source.clone(), vec![], value_type, start, end, None)?;
+    if let Some(regions) = selected {
+        scan.set_regions(regions);
+    }
+    scan.set_reverse_hex(args.reverse_hex);
+    if args.unknown {
+        scan.set_unknown(true)?;
+    } else {
+        scan.set_value_from_str(value)?;
+        scan.set_glob(args.glob)?;
+    }
+    scan.set_inverse(not);
+    scan.set_align_to(if args.dense { Some(1) } else { args.align_to })?;
+    if let Some(stride) = args.stride {
+        scan.set_stride(stride)?;
+    }
+    scan.set_skip_uniform(args.skip_uniform);
+    scan.set_stitch_regions(!args.no_stitch);
+    scan.set_continue_on_error(args.continue_on_error);
+    if let Some(path) = &args.spill_to {
+        scan.set_spill_file(std::path::Path::new(path))?;
+    }
+    let stop = interrupt_flag();
+    scan.set_stop_flag(stop);
+    if let MemoryTarget::Process(pid) = target {
+        warn_if_range_unmapped(pid, start, end, &scan);
+        scan.set_region_names(RegionNames::for_process(pid));
+    }
+    let region_names = scan.region_names();
+
+    if args.unknown {
+        format.status(format_args!("Snapshotting {} for an unknown value (type: {})...", target, type_str));
+    } else if not {
+        format.status(format_args!(
+            "Scanning {} for values not equal to '{}' (type: {})...",
+            target, value, type_str
+        ));
+    } else {
+        format.status(format_args!("Scanning {} for value '{}' (type: {})...", target, value, type_str));
+    }
+    let freeze_pid = target.live_pid().filter(|_| args.freeze_target);
+    let paused = pause_target(freeze_pid)?;
+    let progress = progress::ProgressLine::start(scan.progress(), Some(scan.total_bytes()));
+    match previous {
+        Some(saved) => {
+            let count = saved.len();
+            let new = scan.rescan_saved(saved, args.also_scan)?;
+            drop(progress);
+            let kept = scan.result_count() - new;
+            match args.also_scan {
+                false => format.status(format_args!("{kept} of {count} saved address(es) still match")),
+                true => format.status(format_args!(
+                    "{kept} of {count} saved address(es) still match, {new} new match(es) in their regions"
+                )),
+            }
+        }
+        None => {
+            let resume = args.resume.is_some();
+            match args.resume.as_deref().or(args.checkpoint.as_deref()) {
+                Some(path) => {
+                    let metadata = scan_metadata(args, start, end);
+                    init_checkpointed(&mut scan, path, resume, target.pid(), &metadata, format)?
+                }
+                None => {
+                    scan.init()?;
+                }
+            }
+            drop(progress);
+        }
+    }
+    drop(paused);
+    if let Some(summary) = skipped_summary(scan.skipped_regions()) {
+        format.status(format_args!("{summary}"));
+    }
+    let interrupted = stop.load(std::sync::atomic::Ordering::SeqCst);
+    if interrupted {
+        format.status(format_args!("Interrupted, keeping the matches found so far"));
+    }
+    if args.stable && !interrupted {
+        let found = scan.result_count();
+        if scan.confirm_stable(|| sleep_unless_stopped(args.stable_delay, stop))? {
+            format.status(format_args!(
+                "{} of {found} matches still held the value after {:?}",
+                scan.result_count(),
+                args.stable_delay
+            ));
+            if scan.maps_changed() {
+                format.status(format_args!("The memory map changed meanwhile, every address was read again"));
+            }
+        }
+    }
+    if let Some(stride) = args.stride.filter(|&stride| stride > 1) {
+        format.status(format_args!(
+            "Only 1 in {stride} aligned positions was checked, matches in between were missed"
+        ));
+    }
+
+    if (compare.is_some() || expr.is_some()) && !interrupted {
+        format.status(format_args!(
+            "Found {} matches, running {} compare round(s)...",
+            scan.result_count(),
+            args.rounds
+        ));
+        for round in 1..=args.rounds {
+            if !sleep_unless_stopped(args.interval, stop) {
+                format.status(format_args!("Interrupted, skipping the remaining compare rounds"));
+                break;
+            }
+            let paused = pause_target(freeze_pid)?;
+            match (compare, &expr) {
+                (Some(mode), _) => scan.next_compare(mode, baseline)?,
+                (None, Some(expr)) => scan.next_compare_expr(expr, baseline)?,
+                (None, None) => unreachable!("compare rounds need --compare or --expr"),
+            };
+            drop(paused);
+            format.status(format_args!("Round {}: {} matches", round, scan.result_count()));
+            if scan.maps_changed() {
+                format.status(format_args!("The memory map changed since the last round, every address was read again"));
+            }
+        }
+    }
+
+    if let Some(direction) = monotonic.filter(|_| !interrupted) {
+        format.status(format_args!(
+            "Found {} matches, taking {} samples {:?} apart...",
+            scan.result_count(),
+            args.samples,
+            args.interval
+        ));
+        let taken = scan.next_monotonic(direction, args.samples as usize, || {
+            sleep_unless_stopped(args.interval, stop)
+        })?;
+        if taken < args.samples as usize {
+            format.status(format_args!("Interrupted after {taken} of {} samples", args.samples));
+        }
+        if scan.maps_changed() {
+            format.status(format_args!("The memory map changed before the last sample, every address was read again"));
+        }
+        format.status(format_args!(
+            "{} matches {} at every sample",
+            scan.result_count(),
+            if direction == CompareMode::Decreased { "decreased" } else { "increased" }
+        ));
+    }
+
+    if let Some(spec) = &args.near_result {
+        let reference = near_reference(target.live_pid(), spec, &scan.results)?;
+        retain_near(&mut scan.results, reference, args.within);
+        format.status(format_args!(
+            "{} result(s) within 0x{:x} bytes of 0x{reference:x}",
+            scan.results.len(),
+            args.within
+        ));
+    }
+    if !args.only.is_empty() {
+        scan.results.retain(|result| {
+            let mapping = result.region.and_then(|i| region_names.name(i));
+            args.only.contains(&RegionKind::classify(&result.perms, mapping))
+        });
+        let kinds: Vec<&str> = args.only.iter().map(RegionKind::name).collect();
+        format.status(format_args!("{} result(s) in {} memory", scan.results.len(), kinds.join("/")));
+    }
+    if let Some(key) = sort {
+        sort_results(&mut scan.results, key, args.desc, &region_names);
+    }
+    if args.prefer_aligned {
+        // stable, so both groups stay sorted by address
+        scan.results.sort_by_key(|result| !result.aligned);
+    }
+    let results = &scan.results;
+    if args.cache_stats {
+        let stats = scan.cache_stats();
+        format.status(format_args!(
+            "Read cache: {} hit(s), {} miss(es), {:.1}% hit ratio",
+            stats.hits,
+            stats.misses,
+            stats.hit_ratio() * 100.0,
+        ));
+    }
+
+    if let Some(path) = &args.save {
+        use crate::core::results::save_results;
+
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut metadata = scan_metadata(args, start, end);
+        metadata.process_start = target.live_pid().and_then(crate::core::proc::start_time);
+        save_results(&mut file, file_format, target.pid(), value_type, results, args.save_values, &metadata)?;
+        format.status(format_args!("Saved {} result(s) to {}", results.len(), path));
+    }
+
+    let layout = Columns {
+        region_names: Some(&region_names),
+        type_str,
+        precision: args.precision,
+        context: args.context.map(|bytes| (source.as_ref(), bytes)),
+        ..Default::default()
+    };
+    if let Some(spilled) = scan.spilled_results() {
+        // --save doesn't go with --spill-to
+        let output = args.output.as_deref();
+        return print_scan_results(spilled, layout, args.max_display, output, false, format);
+    }
+    print_scan_results(results, layout, args.max_display, args.output.as_deref(), true, format)
+}
+
+/// Time between the checkpoints of `scan --checkpoint`
+const CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The initial scan of `scan --checkpoint`/`--resume`: continue from the checkpoint at
+/// `path` when resuming, write one there every [`CHECKPOINT_INTERVAL`] and when
+/// interrupted, and remove it once every region was scanned
+fn init_checkpointed(
+    scan: &mut Scan,
+    path: &str,
+    resume: bool,
+    pid: u32,
+    metadata: &crate::core::results::ScanMetadata,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::checkpoint::{CheckpointWriter, ScanPosition, load_checkpoint};
+
+    let file = std::path::Path::new(path);
+    let (from, found, mut writer) = match resume {
+        false => {
+            let writer = CheckpointWriter::create(file, scan.memory_regions(), pid, scan.value_type, metadata)?;
+            (ScanPosition::default(), vec![], writer)
+        }
+        true => {
+            let checkpoint = load_checkpoint(file).map_err(|e| format!("{path}: {e}"))?;
+            checkpoint.check(scan.memory_regions(), metadata).map_err(|e| format!("{path}: {e}"))?;
+            let ScanPosition { region, offset } = checkpoint.position;
+            format.status(format_args!(
+                "Resuming at region {} of {} (0x{offset:x} bytes into it), {} match(es) so far",
+                region + 1,
+                scan.memory_regions().len(),
+                checkpoint.saved.results.len()
+            ));
+            let writer = CheckpointWriter::resume(file, &checkpoint).map_err(|e| format!("{path}: {e}"))?;
+            (checkpoint.position, checkpoint.saved.results, writer)
+        }
+    };
+
+    let complete = scan.init_resumable(from, found, CHECKPOINT_INTERVAL, |position, results| {
+        writer.record(position, results)
+    })?;
+    match complete {
+        true => match std::fs::remove_file(file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                format.status(format_args!("Could not remove the checkpoint {path}: {e}"))
+            }
+            _ => {}
+        },
+        false => format.status(format_args!("Progress saved to {path}, continue the scan with --resume {path}")),
+    }
+    Ok(())
+}
+
+/// What `args` scanned for, to save along with the results
+fn scan_metadata(args: &ScanArgs, start: Option<u64>, end: Option<u64>) -> crate::core::results::ScanMetadata {
+    use crate::core::results::ScanMetadata;
+
+    let mut metadata =
+        ScanMetadata::now(args.value.as_deref().unwrap_or(""), &args.r#type, start, end);
+    let filters = [
+        args.unknown.then(|| String::from(if args.dense { "unknown dense" } else { "unknown" })),
+        args.not.then(|| String::from("not")),
+        args.glob.then(|| String::from("glob")),
+        args.no_stitch.then(|| String::from("no_stitch")),
+        args.align_to.map(|n| format!("align_to={n}")),
+        args.stride.map(|k| format!("stride={k}")),
+        args.skip_uniform.then(|| String::from("skip_uniform")),
+        (!args.only.is_empty()).then(|| {
+            let kinds: Vec<&str> = args.only.iter().map(RegionKind::name).collect();
+            format!("only={}", kinds.join(","))
+        }),
+        (!args.region_index.is_empty()).then(|| {
+            let indices: Vec<String> = args.region_index.iter().map(usize::to_string).collect();
+            format!("region_index={}", indices.join(","))
+        }),
+        args.compare.as_ref().map(|mode| {
+            format!("compare={} baseline={} rounds={}", mode, args.baseline, args.rounds)
+        }),
+        args.expr.as_ref().map(|expr| {
+            format!("expr={} baseline={} rounds={}", expr, args.baseline, args.rounds)
+        }),
+        args.monotonic.as_ref().map(|direction| {
+            format!("monotonic={} samples={} interval={:?}", direction, args.samples, args.interval)
+        }),
+        args.stable.then(|| format!("stable delay={:?}", args.stable_delay)),
+    ];
+    metadata.filters = filters.into_iter().flatten().collect();
+    metadata
+}
+
+/// Pause process `pid` if given, it runs again when the guard is dropped
+fn pause_target(pid: Option<u32>) -> Result<Option<crate::core::proc::PausedProcess>, Box<dyn Error>> {
+    use crate::core::proc::pause;
+
+    pid.map(|pid| pause(pid).map_err(|e| format!("Couldn't pause process {pid}: {e}").into()))
+        .transpose()
+}
+
+/// Sleep for `duration` in short steps, returns `false` early if `stop` is raised
+fn sleep_unless_stopped(duration: std::time::Duration, stop: &std::sync::atomic::AtomicBool) -> bool {
+    use std::sync::atomic::Ordering;
+
+    let deadline = std::time::Instant::now() + duration;
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            return false;
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        std::thread::sleep(std::cmp::min(deadline - now, std::time::Duration::from_millis(50)));
+    }
+}
+
+pub fn scan_all(
+    pids: &[u32],
+    name: Option<&str>,
+    value: &str,
+    type_str: &str,
+    threads: Option<usize>,
+    max_display: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::proc::get_list;
+    use crate::core::scan::ScanExecutor;
+    use printer::{Heading, printer};
+
+    let value_type = parse_value_type(type_str)?;
+    let mut targets: Vec<u32> = pids.to_vec();
+    if let Some(name) = name {
+        targets.extend(get_list(Some(name)).iter().map(|p| p.pid));
+    }
+    targets.sort_unstable();
+    targets.dedup();
+    if targets.is_empty() {
+        return Err("No processes to scan".into());
+    }
+
+    let mut scans = vec![];
+    let mut scanned = vec![];
+    for pid in targets {
+        let scan = Scan::new(pid, vec![], value_type, None, None, None).and_then(|mut scan| {
+            scan.set_value_from_str(value)?;
+            Ok(scan)
+        });
+        match scan {
+            Ok(mut scan) => {
+                scan.set_stop_flag(interrupt_flag());
+                scans.push(scan);
+                scanned.push(pid);
+            }
+            Err(e) => eprintln!("Skipping process {pid}: {e}"),
+        }
+    }
+
+    let executor = match threads {
+        Some(threads) => ScanExecutor::with_threads(threads)?,
+        None => ScanExecutor::global(),
+    };
+    format.status(format_args!(
+        "Scanning {} process(es) for value '{}' (type: {})...",
+        scans.len(),
+        value,
+        type_str
+    ));
+    let outcomes = executor.init(&mut scans);
+    if scans.first().is_some_and(|scan| scan.is_stopped()) {
+        format.status(format_args!("Interrupted, showing the matches found so far"));
+    }
+
+    // one table or document for every process, rows start with the PID
+    let mut found = vec![];
+    for ((pid, scan), outcome) in scanned.iter().zip(&scans).zip(outcomes) {
+        match outcome {
+            Ok(()) => {
+                format.status(format_args!("Process {pid}: {} match(es)", scan.results.len()));
+                found.push((*pid, scan));
+            }
+            Err(e) => format.status(format_args!("Process {pid}: scan failed: {e}")),
+        }
+    }
+    let total = found.iter().map(|(_, scan)| scan.results.len()).sum();
+    format.status(format_args!("Found {} matches:", total));
+    let shown = display_limit(total, max_display, format);
+
+    let named = found.iter().any(|(_, scan)| scan.results.iter().any(|r| r.region.is_some()));
+    let columns = scan_columns(named, false);
+    let mut headings = vec![Heading::new("PID", "pid").number()];
+    headings.extend(Columns::new(&columns).headings());
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    let mut left = shown;
+    for (pid, scan) in found {
+        let region_names = scan.region_names();
+        let layout = Columns { region_names: Some(&region_names), type_str, ..Columns::new(&columns) };
+        for (index, result) in scan.results.iter().take(left).enumerate() {
+            let mut cells = vec![pid.to_string()];
+            cells.extend(layout.cells(result, index)?);
+            printer.row(cells)?;
+        }
+        left -= std::cmp::min(left, scan.results.len());
+    }
+    printer.footer(total)?;
+    if printer.was_cut() {
+        format.status(format_args!("Long values were cut to {} characters", table::MAX_CELL_WIDTH));
+    }
+    print_truncation_note(total - shown, false, format);
+
+    Ok(())
+}
+
+/// Default for `--max-display`
+const DEFAULT_MAX_DISPLAY: usize = 1000;
+
+/// Number of the `total` rows to print with `--max-display max_display`. Above the
+/// limit a table on an interactive terminal asks (on stderr) whether to print everything,
+/// otherwise (other formats, piped output, or the question declined) the rows are cut off
+/// at the limit, so JSON and CSV output never has a question in it.
+fn display_limit(total: usize, max_display: usize, format: OutputFormat) -> usize {
+    use std::io::{IsTerminal, Write};
+
+    if max_display == 0 || total <= max_display {
+        return total;
+    }
+    let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    if format != OutputFormat::Table || !interactive {
+        return max_display;
+    }
+
+    eprint!("{total} rows to print, show all of them? [y/N] ");
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    let _ = std::io::stdin().read_line(&mut answer);
+    if answer.trim().eq_ignore_ascii_case("y") { total } else { max_display }
+}
+
+fn print_truncation_note(hidden: usize, save_hint: bool, format: OutputFormat) {
+    if hidden > 0 {
+        let save = if save_hint { " or --save to keep them" } else { "" };
+        format.status(format_args!(
+            "... {hidden} more row(s) not shown, use --max-display 0 to show all{save}"
+        ));
+    }
+}
+
+/// The regions at `indices` of `regions`, in address order and each once
+fn select_regions(
+    regions: &[MemoryRegion],
+    indices: &[usize],
+) -> Result<Vec<MemoryRegion>, Box<dyn Error>> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+        .into_iter()
+        .map(|i| {
+            regions.get(i).cloned().ok_or_else(|| {
+                format!(
+                    "Region index {} is out of range, there are {} regions (0-{}), see `regions`",
+                    i,
+                    regions.len(),
+                    regions.len().saturating_sub(1)
+                )
+                .into()
+            })
+        })
+        .collect()
+}
+
+pub fn list_threads(pid: u32) -> Result<(), Box<dyn Error>> {
+    let threads = threads(pid);
+    if threads.is_empty() {
+        return Err(format!("The threads of process {pid} can't be listed").into());
+    }
+
+    println!("{} thread(s) in process {}:", threads.len(), pid);
+    let mut table = Table::new(&["TID", "Name", "State", "Stack"]);
+    for thread in &threads {
+        let stack = match thread_stack(pid, thread.tid) {
+            Ok(stack) => format!("0x{:x}-0x{:x}", stack.start, stack.end),
+            Err(_) => String::from("?"),
+        };
+        table.add_row(vec![
+            thread.tid.to_string(),
+            thread.name.clone(),
+            thread.state.map(String::from).unwrap_or_default(),
+            stack,
+        ]);
+    }
+    table.print(None)?;
+    println!();
+    println!("The stack of other threads than the main one is only found while they wait in a system call.");
+
+    Ok(())
+}
+
+pub fn list_regions(target: &MemoryTarget) -> Result<(), Box<dyn Error>> {
+    use crate::core::proc::classify_region;
+
+    let region_names = target.live_pid().map(RegionNames::for_process).unwrap_or_default();
+    let regions = region_list(target.open()?.as_ref(), &region_names, None)?;
+
+    println!("{} region(s) in {}:", regions.len(), target);
+    let mut table = Table::new(&["Index", "Start", "End", "Size", "Perms", "Kind", "Mapping"]);
+    for (i, entry) in regions.iter().enumerate() {
+        let region = &entry.region;
+        table.add_row(vec![
+            i.to_string(),
+            format!("0x{:x}", region.start),
+            format!("0x{:x}", region.end),
+            format!("0x{:x}", region.end - region.start),
+            format_perms(&region.perms),
+            classify_region(region, &region_names).name().to_string(),
+            entry.mapping.clone().unwrap_or_default(),
+        ]);
+    }
+    table.print(None)?;
+
+    Ok(())
+}
+
+/// Most rows `--context` reads the surrounding bytes of
+const MAX_CONTEXT_ROWS: usize = 1000;
+
+/// Most bytes `--context` shows on either side of a value, larger counts are cut to it
+const MAX_CONTEXT_BYTES: usize = 4096;
+
+/// The `bytes` bytes before and after a result around its value, in hex with the value
+/// in brackets: `00ff [2a000000] 0100`, at most [`MAX_CONTEXT_BYTES`] on either side.
+/// Everything is read now, so the value is the current one rather than the one the scan
+/// saw. The bytes are read a page at a time and only those of a page that can't be read
+/// show as `??`.
+fn format_context(source: &dyn MemorySource, result: &ScanResult, bytes: usize) -> String {
+    use crate::core::cache::PAGE_SIZE;
+
+    let bytes = bytes.min(MAX_CONTEXT_BYTES);
+    let before = bytes.min(result.address as usize);
+    let start = result.address - before as u64;
+    let end = result.address.saturating_add((result.value.len() + bytes) as u64);
+    let mut window: Vec<Option<u8>> = Vec::with_capacity((end - start) as usize);
+    let mut address = start;
+    while address < end {
+        let next = (address / PAGE_SIZE + 1)
+            .checked_mul(PAGE_SIZE)
+            .map_or(end, |page| page.min(end));
+        let len = (next - address) as usize;
+        match source.read(address as usize, len) {
+            Ok(data) if data.len() == len => window.extend(data.into_iter().map(Some)),
+            _ => window.extend(std::iter::repeat_n(None, len)),
+        }
+        address = next;
+    }
+
+    let hex = |bytes: &[Option<u8>]| -> String {
+        bytes
+            .iter()
+            .map(|byte| byte.map_or_else(|| "??".to_string(), |b| format!("{b:02x}")))
+            .collect()
+    };
+    let (head, rest) = window.split_at(before);
+    let (value, tail) = rest.split_at(result.value.len().min(rest.len()));
+    format!("{} [{}] {}", hex(head), hex(value), hex(tail))
+}
+
+/// The columns of scan results: a Region column naming the mapping of each result when
+/// some are named, and a Context column with the bytes around each value when `context`
+fn scan_columns(named: bool, context: bool) -> Vec<printer::Column> {
+    use printer::Column;
+
+    let mut columns = vec![Column::Address];
+    if named {
+        columns.push(Column::Region);
+    }
+    columns.extend([Column::Type, Column::Aligned, Column::Perms, Column::Kind, Column::Value]);
+    if context {
+        columns.push(Column::Context);
+    }
+    columns
+}
+
+/// Print up to `max_display` scan results in `format`, the cells formatted by `layout`,
+/// whose columns are picked here, see [`scan_columns`]. With `output` every result is
+/// also written to that file, without cutting long values. `file_hints` is whether the
+/// notes about cut rows and values may point to `--save` and `--output`, which only
+/// `scan` takes.
+fn print_scan_results<I>(
+    results: I,
+    layout: Columns,
+    max_display: usize,
+    output: Option<&str>,
+    file_hints: bool,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>>
+where
+    I: IntoIterator,
+    I::Item: std::borrow::Borrow<ScanResult>,
+    I::IntoIter: ExactSizeIterator,
+{
+    use printer::{print_rows, printer};
+    use std::borrow::Borrow;
+
+    let results = results.into_iter();
+    let total = results.len();
+    format.status(format_args!("Found {} matches:", total));
+    // the file gets every row, so the terminal output is cut at the limit without asking
+    let shown = match output {
+        Some(_) if max_display > 0 => std::cmp::min(total, max_display),
+        Some(_) => total,
+        None => display_limit(total, max_display, format),
+    };
+
+    let rows = if output.is_some() { total } else { shown };
+    let results: Vec<I::Item> = results.take(rows).collect();
+    let named = results.iter().any(|r| r.borrow().region.is_some());
+    let layout = Columns { columns: scan_columns(named, layout.context.is_some()), ..layout };
+    if layout.context.is_some() && results.len() > MAX_CONTEXT_ROWS {
+        format.status(format_args!(
+            "Warning: --context reads the bytes around the first {MAX_CONTEXT_ROWS} of {} rows only",
+            results.len()
+        ));
+    }
+    let rows = || results.iter().map(|result| result.borrow());
+
+    if let Some(path) = output {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        print_rows(printer(format, layout.headings(), file, None).as_mut(), &layout, rows(), total)?;
+        format.status(format_args!("Wrote all {} result(s) to {}", total, path));
+    }
+    let stdout = std::io::stdout().lock();
+    let mut printer = printer(format, layout.headings(), stdout, Some(table::MAX_CELL_WIDTH));
+    let cut = print_rows(printer.as_mut(), &layout, rows().take(shown), total)?;
+    if cut && output.is_none() && file_hints {
+        format.status(format_args!(
+            "Long values were cut to {} characters, use --output <file> for the full values",
+            table::MAX_CELL_WIDTH
+        ));
+    }
+    print_truncation_note(total - shown, file_hints, format);
+
+    Ok(())
+}
+
+/// Size of a pointer in process `pid`, 4 for 32-bit processes on a 64-bit host
+fn pointer_size(pid: u32) -> usize {
+    if crate::core::proc::process_is_64bit(pid) { 8 } else { 4 }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn read_memory(
+    target: &MemoryTarget,
+    address_str: &str,
+    type_str: &str,
+    size: Option<usize>,
+    precision: Option<usize>,
+    count: Option<usize>,
+    until_null: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{CStringEnd, read_array, read_cstring};
+    use printer::{Column, print_rows, printer};
+
+    let pid = target.live_pid();
+    let address = resolve_address(pid, address_str)?;
+    let value_type = parse_sized_value_type(type_str, size)?;
+
+    if let Some(max_len) = until_null {
+        if value_type != ValueType::String {
+            return Err("--until-null reads strings, use --type string".into());
+        }
+        format.status(format_args!(
+            "Reading a string of up to {} bytes from address 0x{:x} in {}...",
+            max_len, address, target
+        ));
+        let (bytes, end) = read_cstring(target.open()?.as_ref(), address, max_len)?;
+        let len = bytes.len();
+        print_read_value(&ScanResult::new(address, value_type, bytes, vec![]), precision, format)?;
+        match end {
+            CStringEnd::Null => format.status(format_args!("Length: {len} byte(s)")),
+            CStringEnd::MaxLength => format.status(format_args!(
+                "No null byte within {max_len} bytes, raise --max-len to read further"
+            )),
+            CStringEnd::Unreadable => {
+                format.status(format_args!("Unterminated: memory after {len} byte(s) can't be read"))
+            }
+        }
+        return Ok(());
+    }
+
+    let read_size = resolve_read_size(size, value_type, pid, config::current());
+
+    if let Some(count) = count {
+        if count == 0 || read_size == 0 {
+            return Err("--count and the read size must be at least 1".into());
+        }
+        format.status(format_args!(
+            "Reading {} x {} byte(s) from address 0x{:x} in {}...",
+            count, read_size, address, target
+        ));
+        let values = read_array(target.open()?.as_ref(), address, read_size, count)?;
+        let elements: Vec<ScanResult> = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| ScanResult::new(address + (i * read_size) as u64, value_type, value, vec![]))
+            .collect();
+
+        let columns = Columns { precision, ..Columns::new(&[Column::Index, Column::Address, Column::Value]) };
+        let stdout = std::io::stdout().lock();
+        let mut printer = printer(format, columns.headings(), stdout, Some(table::MAX_CELL_WIDTH));
+        if print_rows(printer.as_mut(), &columns, &elements, elements.len())? {
+            format.status(format_args!("Long values were cut, read a single element for its full value"));
+        }
+        return Ok(());
+    }
+
+    format.status(format_args!("Reading {} bytes from address 0x{:x} in {}...", read_size, address, target));
+    let value = target.open()?.read(address as usize, read_size)?;
+    print_read_value(&ScanResult::new(address, value_type, value, vec![]), precision, format)
+}
+
+/// Print a single value read, as `Value: <value>` next to a table and as a one-row
+/// result in the other output formats
+fn print_read_value(
+    read: &ScanResult,
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use printer::{Column, print_rows, printer};
+
+    if format == OutputFormat::Table {
+        println!("Value: {}", read.value_type.format_value(&read.value, precision)?);
+        return Ok(());
+    }
+    let columns = Columns { precision, ..Columns::new(&[Column::Address, Column::Size, Column::Value]) };
+    let mut printer = printer(format, columns.headings(), std::io::stdout().lock(), None);
+    print_rows(printer.as_mut(), &columns, [read], 1)?;
+    Ok(())
+}
+
+/// Read the struct at `address_str` field by field as the schema at `schema_path` lays
+/// it out. Fields that can't be read show as `??`, and fail the command after the table.
+fn read_struct_fields(
+    target: &MemoryTarget,
+    address_str: &str,
+    schema_path: &str,
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{StructError, parse_struct_schema, read_struct};
+    use printer::{Heading, printer};
+
+    let pid = target.live_pid();
+    let address = resolve_address(pid, address_str)?;
+    let text = std::fs::read_to_string(schema_path).map_err(|e| format!("{schema_path}: {e}"))?;
+    let pointer_size = pid.map_or(size_of::<usize>(), pointer_size);
+    let fields = parse_struct_schema(&text, pointer_size, |name| parse_value_type(name).ok())
+        .map_err(|e| format!("{schema_path}: {e}"))?;
+
+    format.status(format_args!("Reading {} field(s) at 0x{:x} in {}...", fields.len(), address, target));
+    let values = read_struct(target.open()?.as_ref(), address, &fields);
+    let mut failed: Vec<&StructError> = vec![];
+    let headings = vec![
+        Heading::new("Name", "name"),
+        Heading::new("Offset", "offset"),
+        Heading::new("Address", "address").plain(),
+        Heading::new("Type", "type"),
+        Heading::new("Value", "value").plain(),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for (field, value) in fields.iter().zip(&values) {
+        let value = match value {
+            Err(e) => {
+                failed.push(e);
+                String::from("??")
+            }
+            // a char array holds the string up to its null byte
+            Ok(bytes) if field.value_type == ValueType::String => {
+                let len = memchr::memchr(0, bytes).unwrap_or(bytes.len());
+                field.value_type.format_value(&bytes[..len], precision)?
+            }
+            Ok(bytes) => {
+                let elements: Vec<String> = bytes
+                    .chunks_exact(field.element_size)
+                    .map(|element| field.value_type.format_value(element, precision))
+                    .collect::<Result<_, _>>()?;
+                match field.count {
+                    1 => elements.concat(),
+                    _ => format!("[{}]", elements.join(", ")),
+                }
+            }
+        };
+        printer.row(vec![
+            field.name.clone(),
+            format!("0x{:x}", field.offset),
+            format!("0x{:x}", address + field.offset),
+            match (field.value_type, field.count) {
+                (ValueType::String | ValueType::Hex, _) => {
+                    format!("{}[{}]", field.value_type.name(), field.element_size)
+                }
+                (ValueType::Raw(width), 1) => format!("raw:{width}"),
+                (ValueType::Raw(width), count) => format!("raw:{width}[{count}]"),
+                (value_type, 1) => value_type.name().to_string(),
+                (value_type, count) => format!("{}[{count}]", value_type.name()),
+            },
+            value,
+        ])?;
+    }
+    printer.footer(fields.len())?;
+
+    match failed.as_slice() {
+        [] => Ok(()),
+        [e] => Err(e.to_string().into()),
+        [e, ..] => Err(format!("{} fields can't be read, the first: {e}", failed.len()).into()),
+    }
+}
+
+/// One line of an address list: the address expression and the size given for it, if any
+#[derive(Debug, Clone, PartialEq)]
+struct AddressEntry {
+    /// 1-based line in the file, for errors
+    line: usize,
+    spec: String,
+    size: Option<usize>,
+}
+
+type AddressList = Vec<AddressEntry>;
+
+/// Entries of an address list: one `address[:size]` per line, `#` starts a comment and
+/// blank lines are skipped. Errors name the 1-based line.
+fn parse_address_list(text: &str) -> Result<AddressList, String> {
+    let mut entries = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (address, size) = match line.rsplit_once(':') {
+            Some((address, size)) => {
+                let size = size.trim().parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(
+                    || format!("line {}: invalid size '{}', expected a byte count", i + 1, size.trim()),
+                )?;
+                (address.trim(), Some(size))
+            }
+            None => (line, None),
+        };
+        if address.is_empty() || address.contains(char::is_whitespace) {
+            return Err(format!("line {}: expected `address[:size]`, got '{}'", i + 1, line));
+        }
+        entries.push(AddressEntry { line: i + 1, spec: address.to_string(), size });
+    }
+    Ok(entries)
+}
+
+/// The address list in the file at `path`, see [`parse_address_list`]
+fn read_address_file(path: &str) -> Result<AddressList, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {path}: {e}"))?;
+    Ok(parse_address_list(&text).map_err(|e| format!("{path} {e}"))?)
+}
+
+/// Warn when entries of the address list at `path` are absolute addresses, which only
+/// hold as long as the process they were taken from keeps running
+fn warn_absolute_entries(path: &str, entries: &AddressList) {
+    // without modules only plain numbers resolve
+    let absolute =
+        entries.iter().filter(|entry| resolve_address_in(&[], None, &entry.spec).is_ok()).count();
+    if absolute > 0 {
+        eprintln!(
+            "Warning: {absolute} of {} entries of {path} are absolute addresses, they are wrong once \
+             the target restarts and its modules move (ASLR), `module+offset` entries are relocated",
+            entries.len()
+        );
+    }
+}
+
+/// Resolve every entry of the address list at `path`, errors name the line of the entry
+fn resolve_entries(
+    pid: Option<u32>,
+    path: &str,
+    entries: &AddressList,
+) -> Result<Vec<u64>, Box<dyn Error>> {
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            resolve_address(pid, &entry.spec).map_err(|e| format!("{path} line {}: {e}", entry.line))
+        })
+        .collect::<Result<Vec<u64>, _>>()?)
+}
+
+/// Read every address of an address list file, a line's size takes precedence over `size`
+pub fn read_many(
+    target: &MemoryTarget,
+    path: &str,
+    type_str: &str,
+    size: Option<usize>,
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let pid = target.live_pid();
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let entries = read_address_file(path)?;
+    if pid.is_some() {
+        warn_absolute_entries(path, &entries);
+    }
+    // resolve every line first, so a typo fails before anything is printed
+    let addresses = resolve_entries(pid, path, &entries)?;
+
+    format.status(format_args!("Reading {} address(es) from {} in {}...", entries.len(), path, target));
+    let source = target.open()?;
+    let reads: Vec<(u64, usize)> = entries
+        .iter()
+        .zip(addresses)
+        .map(|(entry, address)| {
+            (address, resolve_read_size(entry.size.or(size), value_type, pid, config::current()))
+        })
+        .collect();
+    let specs: Vec<String> = entries.into_iter().map(|entry| entry.spec).collect();
+    print_reads(source.as_ref(), value_type, &reads, &specs, precision, format)
+}
+
+/// `read --results`: read at every address of a saved result set, moved by `offset`
+#[allow(clippy::too_many_arguments)]
+pub fn read_results(
+    target: &MemoryTarget,
+    path: &str,
+    offset: i64,
+    select: Option<Selection>,
+    type_str: &str,
+    size: Option<usize>,
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{Coverage, MemoryRegionPerms, format_offset, offset_address};
+    use crate::core::results::load_saved;
+
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let read_size = resolve_read_size(size, value_type, target.live_pid(), config::current());
+    let saved = load_saved(&mut std::io::BufReader::new(std::fs::File::open(path)?))?;
+    let selected = select_results(&saved.results, select).map_err(|e| format!("{path}: {e}"))?;
+    let source = target.open()?;
+    let readable = Coverage::new(source.as_ref(), MemoryRegionPerms::Read)?;
+    // every address is checked first, so a wrong offset fails before anything is printed
+    let reads = selected
+        .iter()
+        .map(|(i, result)| {
+            offset_address(&readable, result.address, offset, read_size)
+                .map(|address| (address, read_size))
+                .map_err(|e| format!("{path} result #{i}: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let shown_offset = if offset == 0 { String::new() } else { format_offset(offset) };
+    let specs: Vec<String> = selected.iter().map(|(i, _)| format!("#{i}{shown_offset}")).collect();
+
+    format.status(format_args!("Reading {} result(s) of {} in {}...", reads.len(), path, target));
+    print_reads(source.as_ref(), value_type, &reads, &specs, precision, format)
+}
+
+/// Read `size` bytes at each of `reads` and print them as `value_type` values, labeled with
+/// the `specs` they came from
+fn print_reads(
+    source: &dyn MemorySource,
+    value_type: ValueType,
+    reads: &[(u64, usize)],
+    specs: &[String],
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use printer::{Column, print_rows, printer};
+
+    let reads: Vec<ScanResult> = reads
+        .iter()
+        .map(|&(address, read_size)| {
+            // an empty value prints as unreadable
+            let value = source.read(address as usize, read_size).unwrap_or_else(|e| {
+                log::debug!("Reading {read_size} bytes at 0x{address:x} failed: {e}");
+                vec![]
+            });
+            ScanResult::new(address, value_type, value, vec![])
+        })
+        .collect();
+
+    // the entries are shown when some of them aren't plain addresses, e.g. module+offset
+    let mut columns = vec![Column::Address];
+    if specs.iter().any(|spec| !spec.starts_with(|c: char| c.is_ascii_digit())) {
+        columns.push(Column::Entry);
+    }
+    columns.extend([Column::Size, Column::Value]);
+    let columns = Columns { precision, entries: specs, ..Columns::new(&columns) };
+    let stdout = std::io::stdout().lock();
+    let mut printer = printer(format, columns.headings(), stdout, Some(table::MAX_CELL_WIDTH));
+    if print_rows(printer.as_mut(), &columns, &reads, reads.len())? {
+        format.status(format_args!("Long values were cut, read a single address for its full value"));
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn probe(
+    pid: u32,
+    address_str: &str,
+    type_str: &str,
+    size: Option<usize>,
+    samples: usize,
+    interval: Duration,
+    precision: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::{ProcessHandle, probe_value};
+    use printer::{Heading, printer};
+
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let address = resolve_address(Some(pid), address_str)?;
+    let read_size = resolve_read_size(size, value_type, Some(pid), config::current());
+    let handle = ProcessHandle::open(pid)?;
+
+    format.status(format_args!("Reading 0x{:x} {} time(s), {:?} apart...", address, samples, interval));
+    let stop = interrupt_flag();
+    let probe = probe_value(&handle, address as usize, read_size, samples, interval, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted after {} read(s)", probe.reads + probe.failed));
+    }
+
+    let verdict = if probe.is_stable() { "stable" } else { "not stable" };
+    format.status(format_args!(
+        "{}: {:.1}% of {} read(s) saw the most frequent value, {} distinct value(s)",
+        verdict,
+        probe.stability(),
+        probe.reads,
+        probe.values.len()
+    ));
+    if probe.failed > 0 {
+        format.status(format_args!("{} read(s) failed", probe.failed));
+    }
+
+    let headings = vec![
+        Heading::new("Value", "value").plain(),
+        Heading::new("Reads", "reads").number().plain(),
+        Heading::new("Share", "share"),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for (value, count) in &probe.values {
+        printer.row(vec![
+            value_type.format_value(value, precision)?,
+            count.to_string(),
+            format!("{:.1}%", *count as f64 * 100.0 / probe.reads as f64),
+        ])?;
+    }
+    printer.footer(probe.values.len())?;
+
+    Ok(())
+}
+
+pub fn write_memory(
+    pid: u32,
+    address_str: &str,
+    value: &str,
+    value_type: ValueType,
+    count: Option<usize>,
+    reverse_hex: bool,
+    mode: WriteMode,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::journal::{self, JournalEntry};
+    use crate::core::mem::{MemorySource, ProcessHandle, write_array, write_memory_address};
+
+    let address = resolve_address(Some(pid), address_str)?;
+    let mut scan = Scan::new(pid, vec![], value_type, None, None, None)?;
+    scan.set_reverse_hex(reverse_hex);
+    if let Some(count) = count {
+        let items: Vec<&str> = value.split(',').map(str::trim).collect();
+        if items.len() != count {
+            return Err(format!("--count {} needs {} values, got {}", count, count, items.len()).into());
+        }
+        let values = items
+            .iter()
+            .map(|item| scan.value_from_str(item))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !preview_write(pid, address, &values.concat(), mode)? {
+            return Ok(());
+        }
+
+        println!("Writing {} value(s) to address 0x{:x} in process {}...", count, address, pid);
+        let handle = ProcessHandle::open(pid)?;
+        let previous = handle.read(address as usize, values.concat().len()).ok();
+        write_array(&handle, address, &values)?;
+        if let Some(previous) = previous {
+            let entry = JournalEntry { address, previous, new: values.concat() };
+            let _ = journal::record_for(pid, vec![entry]);
+        }
+        println!("Write successful!");
+        return Ok(());
+    }
+    let value_bytes = scan.value_from_str(value)?;
+    if !preview_write(pid, address, &value_bytes, mode)? {
+        return Ok(());
+    }
+
+    println!("Writing value '{}' to address 0x{:x} in process {}...", value, address, pid);
+    write_memory_address(pid, address as usize, &value_bytes)?;
+
+    println!("Write successful!");
+
+    Ok(())
+}
+
+/// What `freeze` writes to: one address, or every entry of an address list file
+pub enum FreezeTarget<'a> {
+    Address(&'a str),
+    List(&'a str),
+}
+
+/// Write `value` to every address of `target` once per `interval` until Ctrl+C, a failed
+/// write ends it, e.g. once the process exits
+pub fn freeze_memory(
+    pid: u32,
+    target: FreezeTarget,
+    value: &str,
+    value_type: ValueType,
+    interval: Duration,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::{MemorySource, ProcessHandle};
+
+    let value_bytes = Scan::new(pid, vec![], value_type, None, None, None)?.value_from_str(value)?;
+    // (entry, address) of each address to write
+    let targets: Vec<(String, u64)> = match target {
+        FreezeTarget::Address(spec) => vec![(spec.to_string(), resolve_address(Some(pid), spec)?)],
+        FreezeTarget::List(path) => {
+            let entries = read_address_file(path)?;
+            warn_absolute_entries(path, &entries);
+            let misfit = |entry: &&AddressEntry| entry.size.is_some_and(|size| size != value_bytes.len());
+            if let Some(entry) = entries.iter().find(misfit) {
+                return Err(format!(
+                    "{path} line {}: size {} doesn't fit the value, '{value}' is {} byte(s)",
+                    entry.line,
+                    entry.size.unwrap_or_default(),
+                    value_bytes.len()
+                )
+                .into());
+            }
+            let addresses = resolve_entries(Some(pid), path, &entries)?;
+            entries.into_iter().map(|entry| entry.spec).zip(addresses).collect()
+        }
+    };
+    if targets.is_empty() {
+        return Err("The address list has no entries".into());
+    }
+
+    let handle = ProcessHandle::open(pid)?;
+    println!(
+        "Freezing {} address(es) of process {} at '{}', writing every {:?}, Ctrl+C to stop...",
+        targets.len(),
+        pid,
+        value,
+        interval
+    );
+    let stop = interrupt_flag();
+    let mut rounds = 0;
+    loop {
+        for (spec, address) in &targets {
+            handle.write(*address as usize, &value_bytes).map_err(|e| {
+                format!("Writing {spec} (0x{address:x}) failed after {rounds} round(s): {e}")
+            })?;
+        }
+        rounds += 1;
+        if !sleep_unless_stopped(interval, stop) {
+            break;
+        }
+    }
+    println!("Interrupted after {rounds} round(s) of writes");
+
+    Ok(())
+}
+
+/// Parse `--op` with its `--by` operand
+fn parse_write_op(name: &str, by: Option<f64>) -> Result<WriteOp, Box<dyn Error>> {
+    let by = || by.ok_or_else(|| format!("--op {name} needs --by, e.g. --op {name} --by 2"));
+    Ok(match name.to_lowercase().as_str() {
+        "add" => WriteOp::Add(by()?),
+        "sub" => WriteOp::Sub(by()?),
+        "mul" => WriteOp::Mul(by()?),
+        "div" => WriteOp::Div(by()?),
+        "half" => WriteOp::Half,
+        "set-max" | "max" => WriteOp::SetMax,
+        _ => return Err(format!("Invalid --op '{name}', use add, sub, mul, div, half or set-max").into()),
+    })
+}
+
+/// `write --op`: apply an operation to the current value at an address
+pub fn write_op(
+    pid: u32,
+    address_str: &str,
+    type_str: &str,
+    op: &str,
+    by: Option<f64>,
+    mode: WriteMode,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::journal::{self, JournalEntry};
+    use crate::core::mem::{ProcessHandle, apply_op, op_result};
+
+    let address = resolve_address(Some(pid), address_str)?;
+    let value_type = parse_value_type(type_str)?;
+    let op = parse_write_op(op, by)?;
+    let handle = ProcessHandle::open(pid)?;
+    if mode != WriteMode::Write {
+        let current = handle.read(address as usize, value_type.get_size() as usize)?;
+        let preview = op_result(value_type, &current, op)?;
+        if !preview_write(pid, address, &preview.new, mode)? {
+            return Ok(());
+        }
+    }
+
+    // computed from the value at the time of the write, which may differ from the preview
+    let write = apply_op(&handle, address, value_type, op)?;
+    let entry = JournalEntry { address, previous: write.previous.clone(), new: write.new.clone() };
+    let _ = journal::record_for(pid, vec![entry]);
+    println!(
+        "Wrote {} over {} at 0x{:x} in process {}",
+        value_type.format_value(&write.new, None)?,
+        value_type.format_value(&write.previous, None)?,
+        address,
+        pid
+    );
+    if write.saturated {
+        println!("The result didn't fit {} and was clamped to its limit", value_type.name());
+    }
+
+    Ok(())
+}
+
+/// Result sets `write-results` writes to without --yes
+const WRITE_RESULTS_CONFIRM: usize = 100;
+
+/// How `write-results` writes, see its arguments
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteResultsOptions {
+    pub verify: bool,
+    pub offset: i64,
+    pub select: Option<Selection>,
+    pub yes: bool,
+}
+
+pub fn write_results(
+    path: &str,
+    pid: Option<u32>,
+    value: &str,
+    type_str: Option<&str>,
+    size: Option<usize>,
+    options: WriteResultsOptions,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::journal::{self, JournalEntry};
+    use printer::{Heading, printer};
+    use crate::core::mem::{Coverage, MemoryRegionPerms, ProcessHandle, WriteStatus, offset_address, write_each};
+    use crate::core::results::load_saved;
+
+    let WriteResultsOptions { verify, offset, select, yes } = options;
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let saved = load_saved(&mut file)?;
+    let selected = select_results(&saved.results, select).map_err(|e| format!("{path}: {e}"))?;
+    let pid = match pid {
+        Some(pid) => pid,
+        None => saved_process(path, &saved)?,
+    };
+    let saved_type = saved
+        .metadata
+        .as_ref()
+        .map(|metadata| metadata.type_name.clone())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| saved.value_type.get_string());
+    let type_str = type_str.unwrap_or(&saved_type);
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let value_bytes = Scan::new(pid, vec![], value_type, None, None, None)?.value_from_str(value)?;
+
+    let handle = ProcessHandle::open(pid)?;
+    let addresses: Vec<u64> = match offset {
+        0 => selected.iter().map(|(_, result)| result.address).collect(),
+        // every address is checked first, a wrong offset doesn't write anything
+        _ => {
+            let writable = Coverage::new(&handle, MemoryRegionPerms::Write)?;
+            selected
+                .iter()
+                .map(|(i, result)| {
+                    offset_address(&writable, result.address, offset, value_bytes.len())
+                        .map_err(|e| format!("{path} result #{i}: {e}"))
+                })
+                .collect::<Result<_, _>>()?
+        }
+    };
+    if addresses.len() > WRITE_RESULTS_CONFIRM && !yes {
+        return Err(format!(
+            "{path} selects {} addresses, pass --yes to write to more than {WRITE_RESULTS_CONFIRM}",
+            addresses.len()
+        )
+        .into());
+    }
+
+    format.status(format_args!(
+        "Writing '{}' ({}) to {} address(es) in process {}...",
+        value,
+        type_str,
+        addresses.len(),
+        pid
+    ));
+    let outcomes = write_each(&handle, &addresses, &value_bytes, verify);
+
+    let headings = vec![
+        Heading::new("Address", "address").plain(),
+        Heading::new("Result", "result").plain(),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    let (mut written, mut mismatched) = (0, 0);
+    let mut journaled = vec![];
+    for outcome in outcomes {
+        let result = match &outcome.status {
+            WriteStatus::Written => String::from("ok"),
+            WriteStatus::Verified => String::from("ok, verified"),
+            WriteStatus::Mismatch(read_back) => {
+                mismatched += 1;
+                format!("MISMATCH: reads {}", value_type.format_value(read_back, None)?)
+            }
+            WriteStatus::Failed(e) => format!("FAIL: {e}"),
+        };
+        if !matches!(outcome.status, WriteStatus::Failed(_)) {
+            written += 1;
+            if let Some(previous) = outcome.previous {
+                journaled.push(JournalEntry { address: outcome.address, previous, new: value_bytes.clone() });
+            }
+        }
+        printer.row(vec![format!("0x{:x}", outcome.address), result])?;
+    }
+    // one undo-last restores the whole set
+    let _ = journal::record_for(pid, journaled);
+    printer.footer(addresses.len())?;
+    format.status(format_args!("Wrote {} of {} address(es)", written, addresses.len()));
+    if mismatched > 0 {
+        format.status(format_args!("{mismatched} of them read back a different value, the target may overwrite it"));
+    }
+
+    Ok(())
+}
+
+/// What `write` does with the encoded value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WriteMode {
+    Write,
+    /// Only show what would be written
+    Preview,
+    /// Show what would be written and ask first
+    Confirm,
+}
+
+/// The bytes at `address`, the `new` ones and a `^^` under each byte that changes. Bytes
+/// that can't be read show as `??`.
+fn format_write_preview(address: u64, current: Option<&[u8]>, new: &[u8]) -> Vec<String> {
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+    let Some(current) = current else {
+        return vec![
+            format!("{} byte(s) at 0x{:x}, the current bytes can't be read", new.len(), address),
+            format!("  new:     {}", hex(new)),
+        ];
+    };
+
+    let changed = current.iter().zip(new).filter(|(a, b)| a != b).count();
+    let diff = current
+        .iter()
+        .zip(new)
+        .map(|(a, b)| if a == b { "  " } else { "^^" })
+        .collect::<Vec<_>>()
+        .join(" ");
+    vec![
+        format!("{} of {} byte(s) at 0x{:x} change", changed, new.len(), address),
+        format!("  current: {}", hex(current)),
+        format!("  new:     {}", hex(new)),
+        format!("  diff:    {}", diff.trim_end()),
+    ]
+}
+
+/// Print the preview for `mode`, true when the write should go ahead
+fn preview_write(pid: u32, address: u64, new: &[u8], mode: WriteMode) -> Result<bool, Box<dyn Error>> {
+    use crate::core::mem::read_memory_address;
+    use std::io::Write;
+
+    if mode == WriteMode::Write {
+        return Ok(true);
+    }
+    let current = read_memory_address(pid, address as usize, new.len()).ok();
+    for line in format_write_preview(address, current.as_deref(), new) {
+        println!("{line}");
+    }
+    if mode == WriteMode::Preview {
+        println!("Preview only, nothing was written");
+        return Ok(false);
+    }
+
+    print!("Write these bytes? [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("Cancelled, nothing was written");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+pub fn undo_last_write(pid: u32, clear_journal: bool) -> Result<(), Box<dyn Error>> {
+    use crate::core::journal::{self, journal_path};
+
+    if clear_journal {
+        journal::clear(&journal_path(pid)?)?;
+        println!("Cleared the write journal of process {pid}");
+        return Ok(());
+    }
+
+    match journal::undo_last(pid)? {
+        None => println!("No journaled writes to undo in process {pid}"),
+        Some(restored) => {
+            for (entry, _) in &restored {
+                println!(
+                    "Restored {} byte(s) at 0x{:x}: {} -> {}",
+                    entry.previous.len(),
+                    entry.address,
+                    hex::encode(&entry.new),
+                    hex::encode(&entry.previous)
+                );
+            }
+            let changed = restored.iter().filter(|(_, untouched)| !untouched).count();
+            match (changed, restored.len()) {
+                (0, _) => {}
+                (_, 1) => println!("Note: the process had changed these bytes since the write"),
+                (changed, _) => println!("Note: the process had changed {changed} of these since the write"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Log the changes of the watched value(s) as a table, or as CSV rows with `format` CSV,
+/// which `analyze-log` reads either way
+pub fn log_changes(args: &LogChangesArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::{ProcessHandle, Throttle, poll_many_changes};
+    use crate::core::proc::{LineageRecorder, lineage};
+    use std::io::Write;
+    use std::time::UNIX_EPOCH;
+
+    let (pid, type_str, rate, precision) = (args.pid, args.r#type.as_str(), args.rate, args.precision);
+    let output = args.output.as_deref();
+    let value_type = parse_sized_value_type(type_str, args.size)?;
+    let read_size = |size| resolve_read_size(size, value_type, Some(pid), config::current());
+    // (entry, address, size) of each address to watch, --follow-forks only follows one
+    let mut targets: Vec<(String, u64, usize)> = match (&args.addresses_file, &args.address) {
+        (Some(path), _) => {
+            let entries = read_address_file(path)?;
+            warn_absolute_entries(path, &entries);
+            let addresses = resolve_entries(Some(pid), path, &entries)?;
+            entries
+                .into_iter()
+                .zip(addresses)
+                .map(|(entry, address)| {
+                    let size = read_size(entry.size.or(args.size));
+                    (entry.spec, address, size)
+                })
+                .collect()
+        }
+        (None, Some(address)) => {
+            vec![(address.clone(), resolve_address(Some(pid), address)?, read_size(args.size))]
+        }
+        (None, None) => return Err("Either --address or --addresses-file is required".into()),
+    };
+    if targets.is_empty() {
+        return Err("The address list has no entries".into());
+    }
+    let listed = args.addresses_file.is_some();
+    let interval = match (args.interval, rate) {
+        (Some(interval), _) => interval,
+        (None, 0) => Duration::ZERO,
+        (None, rate) => Duration::from_secs_f64(1.0 / rate as f64),
+    };
+    let throttle = match args.adaptive {
+        true => Throttle::adaptive(interval, args.max_interval),
+        false => Throttle::fixed(interval),
+    };
+    let pace = match args.adaptive {
+        true => format!("every {:?} to {:?} (adaptive)", interval, args.max_interval.max(interval)),
+        false => format!("every {:?}", interval),
+    };
+
+    let mut out: Box<dyn Write> = match output {
+        None => Box::new(std::io::stdout()),
+        Some(path) => Box::new(std::fs::File::create(path)?),
+    };
+
+    let mut handle = ProcessHandle::open(pid)?;
+    // children are only known as such while the followed process runs, see ProcessLineage
+    let recorder = |lineage| LineageRecorder::start(lineage, Duration::from_millis(500));
+    let mut followed = match args.follow_forks {
+        true => Some(recorder(
+            lineage(pid).ok_or_else(|| format!("Can't read the executable of process {pid}"))?,
+        )),
+        false => None,
+    };
+    let watched = match (&args.addresses_file, targets.as_slice()) {
+        (Some(path), _) => format!("{} address(es) from {}", targets.len(), path),
+        (None, [(_, address, _)]) => format!("0x{address:x}"),
+        (None, _) => unreachable!("--address is a single target"),
+    };
+    let csv = format == OutputFormat::Csv;
+    // a CSV log holds only its rows, the notes go to the terminal
+    let write_note = |out: &mut dyn Write, note: &str| -> std::io::Result<()> {
+        if csv {
+            format.status(format_args!("{note}"));
+            return Ok(());
+        }
+        writeln!(out, "{note}")?;
+        if output.is_some() {
+            println!("{note}");
+        }
+        Ok(())
+    };
+    let note = format!(
+        "# Logging changes at {} in process {} (type: {}), polling {}.\n\
+         # This is sampling-based: changes that happen and revert between polls are missed.",
+        watched, pid, type_str, pace
+    );
+    write_note(&mut out, &note)?;
+    // the entry goes last, so `analyze-log` reads these logs too
+    match (args.show_delta, listed) {
+        _ if csv => {
+            let mut keys = vec!["timestamp", "elapsed", "old", "new"];
+            keys.extend(args.show_delta.then_some("change"));
+            keys.extend(listed.then_some("address"));
+            writeln!(out, "{}", keys.join(","))?
+        }
+        (true, false) => writeln!(out, "{:<20} {:<12} {:<}", "Timestamp", "Elapsed", "Change")?,
+        (true, true) => {
+            writeln!(out, "{:<20} {:<12} {:<32} Address", "Timestamp", "Elapsed", "Change")?
+        }
+        (false, _) => {
+            let header = format!("{:<20} {:<12} {:<20} {:<20}", "Timestamp", "Elapsed", "Old", "New");
+            match listed {
+                true => writeln!(out, "{header} Address")?,
+                false => writeln!(out, "{header}")?,
+            }
+        }
+    }
+
+    let stop = interrupt_flag();
+    let mut write_error = None;
+    let mut count = 0;
+    let result = loop {
+        let reads: Vec<(usize, usize)> =
+            targets.iter().map(|&(_, address, size)| (address as usize, size)).collect();
+        let result = poll_many_changes(&handle, &reads, throttle, stop, |index, change| {
+            let timestamp = change.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+            let timestamp = format!("{}.{:06}", timestamp.as_secs(), timestamp.subsec_micros());
+            let elapsed = format!("+{:.6}s", change.elapsed.as_secs_f64());
+            let line = if csv {
+                let mut fields = vec![
+                    timestamp,
+                    format!("{:.6}", change.elapsed.as_secs_f64()),
+                    value_type.format_value(&change.old, precision).unwrap_or_default(),
+                    value_type.format_value(&change.new, precision).unwrap_or_default(),
+                ];
+                if args.show_delta {
+                    fields.push(value_type.describe_change(&change.old, &change.new, precision));
+                }
+                if listed {
+                    fields.push(targets[index].0.clone());
+                }
+                fields.iter().map(|field| printer::csv_field(field)).collect::<Vec<_>>().join(",")
+            } else if args.show_delta {
+                let change = value_type.describe_change(&change.old, &change.new, precision);
+                match listed {
+                    true => format!("{:<20} {:<12} {:<32} {}", timestamp, elapsed, change, targets[index].0),
+                    false => format!("{:<20} {:<12} {:<}", timestamp, elapsed, change),
+                }
+            } else {
+                let line = format!(
+                    "{:<20} {:<12} {:<20} {:<20}",
+                    timestamp,
+                    elapsed,
+                    value_type.format_value(&change.old, precision).unwrap_or_default(),
+                    value_type.format_value(&change.new, precision).unwrap_or_default(),
+                );
+                match listed {
+                    true => format!("{line} {}", targets[index].0),
+                    false => line,
+                }
+            };
+            count += 1;
+            match writeln!(out, "{line}").and_then(|_| out.flush()) {
+                Ok(_) => true,
+                Err(e) => {
+                    write_error = Some(e);
+                    false
+                }
+            }
+        });
+
+        if write_error.is_some() || !matches!(result, Err(MemoryError::ProcessExited)) {
+            break result;
+        }
+        let Some(of) = followed.take().map(LineageRecorder::finish) else {
+            break result;
+        };
+        let Some((next, next_address)) = wait_for_successor(&of, &targets[0].0, args.follow_timeout, stop) else {
+            break result;
+        };
+        let note = format!(
+            "# Process {} exited, following process {} (address 0x{:x})",
+            of.pid, next.pid, next_address
+        );
+        write_note(&mut out, &note)?;
+        handle = ProcessHandle::open(next.pid)?;
+        targets[0].1 = next_address;
+        followed = Some(recorder(next));
+    };
+
+    if let Some(e) = write_error {
+        return Err(e.into());
+    }
+    match result {
+        Err(MemoryError::ProcessExited) => {
+            println!("Process exited, logged {count} change(s)");
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+        Ok(_) => {
+            if stop.load(std::sync::atomic::Ordering::SeqCst) {
+                out.flush()?;
+                println!("Interrupted, logged {count} change(s)");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Wait up to `timeout` for the process that takes over from `of`, see
+/// [`crate::core::proc::find_successor`], and resolve `address` in it. A successor whose
+/// modules aren't mapped yet is checked again until the address resolves.
+fn wait_for_successor(
+    of: &crate::core::proc::ProcessLineage,
+    address: &str,
+    timeout: Duration,
+    stop: &std::sync::atomic::AtomicBool,
+) -> Option<(crate::core::proc::ProcessLineage, u64)> {
+    use crate::core::proc::find_successor;
+
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if let Some(next) = find_successor(of)
+            && let Ok(resolved) = resolve_address(Some(next.pid), address)
+        {
+            return Some((next, resolved));
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline || !sleep_unless_stopped(Duration::from_millis(100).min(deadline - now), stop) {
+            return None;
+        }
+    }
+}
+
+fn parse_watch_condition(on: &str) -> Result<WatchCondition, Box<dyn Error>> {
+    match on.to_lowercase().as_str() {
+        "write" => Ok(WatchCondition::Write),
+        "access" | "read" => Ok(WatchCondition::Access),
+        "execute" | "exec" => Ok(WatchCondition::Execute),
+        _ => Err("Invalid watch condition, expected write, access or execute".into()),
+    }
+}
+
+pub fn watchpoint(
+    pid: u32,
+    address_str: &str,
+    len: usize,
+    on: &str,
+    count: Option<usize>,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::{DebugError, Watchpoint, interrupt_flag};
+    use crate::core::mem::read_memory_address;
+
+    let address = resolve_address(Some(pid), address_str)?;
+    let condition = parse_watch_condition(on)?;
+
+    let stop = interrupt_flag();
+    let mut watchpoint = Watchpoint::attach(pid, address, len, condition)?;
+    println!(
+        "Watching 0x{:x} ({} byte(s), on {}) in process {} using DR0, Ctrl+C to stop...",
+        address, len, on, pid
+    );
+    println!("{:<10} {:<20} {:<}", "TID", "RIP", "Value");
+    println!("{:-<10} {:-<20} {:-<}", "", "", "");
+
+    let mut hits = 0;
+    let result = watchpoint.watch(stop, |hit| {
+        let value = read_memory_address(pid, address as usize, len)
+            .map(hex::encode)
+            .unwrap_or_else(|_| String::from("??"));
+        println!("{:<10} 0x{:<18x} {:<}", hit.tid, hit.rip, value);
+        hits += 1;
+        count.is_none_or(|count| hits < count)
+    });
+    watchpoint.detach();
+
+    match result {
+        Err(DebugError::ProcessExited) => println!("Process exited"),
+        Err(e) => return Err(e.into()),
+        Ok(_) => {}
+    }
+    println!("Detached after {hits} hit(s)");
+
+    Ok(())
+}
+
+/// Address and original bytes of a patch
+type PatchEntry = (u64, Vec<u8>);
+
+fn format_backup_entry(address: u64, original: &[u8]) -> String {
+    format!("0x{:x} {}", address, hex::encode(original))
+}
+
+/// Parse backup lines of the form `0x<address> <original bytes hex>`, `#` starts a comment
+fn parse_backup(contents: &str) -> Result<Vec<PatchEntry>, Box<dyn Error>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (address, bytes) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Invalid backup line '{line}'"))?;
+            Ok((parse_address_str(address)?, hex::decode(bytes.trim())?))
+        })
+        .collect()
+}
+
+pub fn nop_memory(
+    pid: u32,
+    address_str: &str,
+    len: usize,
+    backup: Option<&str>,
+    verify: bool,
+    force: bool,
+    restore: bool,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::disasm::{MAX_INSTRUCTION_LEN, instructions_covering};
+    use crate::core::mem::{
+        MemoryRegionPerms, MemorySource, PatchRegistry, ProcessHandle, X86_NOP, get_memory_regions,
+    };
+    use std::io::{IsTerminal, Write};
+
+    if !cfg!(target_arch = "x86_64") {
+        return Err("NOP patching is only supported for x86-64 processes".into());
+    }
+    if len == 0 {
+        return Err("Length should be at least 1".into());
+    }
+
+    let address = resolve_address(Some(pid), address_str)?;
+    let end = address + len as u64;
+    let regions = get_memory_regions(
+        pid,
+        Some(address),
+        Some(end),
+        Some(&[MemoryRegionPerms::Read, MemoryRegionPerms::Write, MemoryRegionPerms::Execute]),
+    )?;
+    let region = regions
+        .iter()
+        .find(|r| r.start <= address && address < r.end)
+        .ok_or_else(|| format!("Address 0x{address:x} is not in a mapped region"))?;
+    if end > region.end {
+        return Err(format!(
+            "Patch 0x{:x}-0x{:x} crosses the end of region 0x{:x}-0x{:x}",
+            address, end, region.start, region.end
+        )
+        .into());
+    }
+    if !region.perms.contains(&MemoryRegionPerms::Execute) && !force {
+        return Err(format!(
+            "Refusing to patch non-executable region 0x{:x}-0x{:x}, use --force to patch anyway",
+            region.start, region.end
+        )
+        .into());
+    }
+
+    let handle = ProcessHandle::open(pid)?;
+    if verify {
+        let read_len = std::cmp::min(len + MAX_INSTRUCTION_LEN, (region.end - address) as usize);
+        let code = handle.read(address as usize, read_len)?;
+        let instructions = instructions_covering(&code, address, len)
+            .map_err(|e| format!("{e} (use --no-verify to patch anyway)"))?;
+
+        println!("Replacing:");
+        for instruction in &instructions {
+            let offset = (instruction.address - address) as usize;
+            println!(
+                "  0x{:x}  {:<30} {}",
+                instruction.address,
+                hex::encode(&code[offset..offset + instruction.len]),
+                instruction.text
+            );
+        }
+    }
+
+    // without a terminal there is nobody to press Enter, so a script keeps the patch
+    let interactive = std::io::stdin().is_terminal();
+    let mut patches = PatchRegistry::new(&handle);
+    if !restore || !interactive {
+        patches.keep();
+    }
+    let original = patches.apply(address as usize, &vec![X86_NOP; len])?;
+    println!("Wrote {} NOP byte(s) at 0x{:x}", len, address);
+    println!("Original bytes: {}", hex::encode(&original));
+
+    if let Some(path) = backup {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", format_backup_entry(address, &original))?;
+        println!("Backup appended to {path}");
+    }
+
+    if restore && interactive {
+        // dropping `patches` writes the original bytes back, also when unwinding
+        let stop = interrupt_flag();
+        println!("Patch active, press Enter or Ctrl+C to restore the original bytes and exit");
+        wait_for_enter(stop);
+        return Ok(());
+    }
+
+    if restore {
+        println!("Standard input is not a terminal, keeping the patch");
+    }
+    match backup {
+        Some(path) => println!("Undo with: cli restore --pid {pid} --backup {path}"),
+        None => println!(
+            "Undo with: cli restore --pid {} --address 0x{:x} --bytes {}",
+            pid,
+            address,
+            hex::encode(&original)
+        ),
+    }
+
+    Ok(())
+}
+
+/// Block until a line (or end of input, Ctrl+D) is read from stdin, or `stop` is raised
+fn wait_for_enter(stop: &std::sync::atomic::AtomicBool) {
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        let _ = std::io::stdin().read_line(&mut line);
+        let _ = tx.send(());
+    });
+    while !stop.load(Ordering::SeqCst) {
+        match rx.recv_timeout(std::time::Duration::from_millis(100)) {
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            _ => break,
+        }
+    }
+}
+
+pub fn restore_memory(pid: u32, entries: &[PatchEntry]) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{MemorySource, ProcessHandle};
+
+    let handle = ProcessHandle::open(pid)?;
+    // newest patch first, so overlapping patches unwind to the oldest original bytes
+    for (address, bytes) in entries.iter().rev() {
+        handle.write(*address as usize, bytes)?;
+        println!("Restored {} byte(s) at 0x{:x}", bytes.len(), address);
+    }
+
+    Ok(())
+}
+
+pub fn find_refs(
+    pid: u32,
+    address_strs: &[String],
+    listed: &[u64],
+    results_path: Option<&str>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{MemorySource, ProcessHandle};
+    use crate::core::debug::interrupt_flag;
+    use crate::core::ptrscan::find_references;
+    use crate::core::results::load_saved;
+    use printer::{Heading, printer};
+    use std::collections::HashSet;
+
+    let mut targets = address_strs
+        .iter()
+        .map(|a| resolve_address(Some(pid), a))
+        .collect::<Result<HashSet<u64>, _>>()?;
+    targets.extend(listed);
+    if let Some(path) = results_path {
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let saved = load_saved(&mut file)?;
+        if saved.pid != pid {
+            eprintln!("Note: {} was saved from process {}, not {}", path, saved.pid, pid);
+        }
+        targets.extend(saved.results.iter().map(|r| r.address));
+    }
+
+    let handle = ProcessHandle::open(pid)?;
+    let regions = handle.regions(None, None, None)?;
+    format.status(format_args!(
+        "Searching {} writable region(s) of process {} for pointers to {} address(es)...",
+        regions.len(),
+        pid,
+        targets.len()
+    ));
+    let stop = interrupt_flag();
+    let references = find_references(&handle, &regions, pointer_size(pid), &targets, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the references found so far"));
+    }
+
+    format.status(format_args!("Found {} reference(s):", references.len()));
+    let region_names = RegionNames::for_process(pid);
+    let headings = vec![
+        Heading::new("Address", "address").plain(),
+        Heading::new("Region", "region"),
+        Heading::new("Points to", "points_to").plain(),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), None);
+    printer.header()?;
+    for reference in &references {
+        printer.row(vec![
+            format!("0x{:x}", reference.address),
+            region_names.region_name_for(reference.address).unwrap_or("").to_string(),
+            format!("0x{:x}", reference.target),
+        ])?;
+    }
+    printer.footer(references.len())?;
+
+    Ok(())
+}
+
+pub fn hash_region(
+    target: &MemoryTarget,
+    start_str: &str,
+    end_str: &str,
+    algo_str: &str,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::digest::HashAlgo;
+    use crate::core::mem::hash_region;
+
+    let algo = match algo_str.to_lowercase().as_str() {
+        "crc32" => HashAlgo::Crc32,
+        "sha256" => HashAlgo::Sha256,
+        _ => return Err(format!("Invalid algorithm '{algo_str}', use crc32 or sha256").into()),
+    };
+    let pid = target.live_pid();
+    let (start, end) = (resolve_address(pid, start_str)?, resolve_address(pid, end_str)?);
+    if end <= start {
+        return Err("The end address must be above the start address".into());
+    }
+
+    println!("Hashing 0x{:x}-0x{:x} ({} bytes) in {}...", start, end, end - start, target);
+    let hash = hash_region(target.open()?.as_ref(), start, end, algo)?;
+    if hash.hashed == 0 {
+        return Err(format!("None of 0x{start:x}-0x{end:x} could be read").into());
+    }
+    println!("{}: {}", algo_str.to_lowercase(), hex::encode(&hash.digest));
+    if hash.unreadable > 0 {
+        println!(
+            "Partial: hashed {} of {} bytes, {} bytes could not be read",
+            hash.hashed,
+            end - start,
+            hash.unreadable
+        );
+    } else {
+        println!("Hashed {} bytes", hash.hashed);
+    }
+
+    Ok(())
+}
+
+pub fn scan_ptr_into(
+    pid: u32,
+    module_name: &str,
+    max_display: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use printer::{Heading, printer};
+    use crate::core::mem::{MemorySource, ProcessHandle};
+    use crate::core::proc::modules;
+    use crate::core::ptrscan::find_pointers_into;
+
+    let modules = modules(pid);
+    let module = modules
+        .iter()
+        .find(|m| m.name == module_name)
+        .ok_or_else(|| format!("Module '{module_name}' is not mapped in process {pid}"))?;
+
+    let handle = ProcessHandle::open(pid)?;
+    let regions = handle.regions(None, None, None)?;
+    format.status(format_args!(
+        "Searching {} writable region(s) of process {} for pointers into {} (0x{:x}-0x{:x})...",
+        regions.len(),
+        pid,
+        module.name,
+        module.base,
+        module.end
+    ));
+    let stop = interrupt_flag();
+    let pointers =
+        find_pointers_into(&handle, &regions, pointer_size(pid), module.base..module.end, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the pointers found so far"));
+    }
+
+    format.status(format_args!("Found {} pointer(s):", pointers.len()));
+    let shown = display_limit(pointers.len(), max_display, format);
+    let region_names = RegionNames::for_process(pid);
+    let headings = vec![
+        Heading::new("Address", "address").plain(),
+        Heading::new("Region", "region"),
+        Heading::new("Points to", "points_to").plain(),
+        Heading::new("Offset", "offset"),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for pointer in pointers.iter().take(shown) {
+        printer.row(vec![
+            format!("0x{:x}", pointer.address),
+            region_names.region_name_for(pointer.address).unwrap_or("").to_string(),
+            format!("0x{:x}", pointer.target),
+            format!("{}+0x{:x}", module.name, pointer.target - module.base),
+        ])?;
+    }
+    printer.footer(pointers.len())?;
+    print_truncation_note(pointers.len() - shown, false, format);
+
+    Ok(())
+}
+
+pub fn scan_ptr_eq(
+    pid: u32,
+    target_str: &str,
+    writable_only: bool,
+    max_display: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::ProcessHandle;
+    use printer::{Heading, printer};
+    use crate::core::ptrscan::{find_pointers_to, pointer_regions};
+
+    let target = resolve_address(Some(pid), target_str)?;
+    let handle = ProcessHandle::open(pid)?;
+    let regions = pointer_regions(&handle, writable_only)?;
+    format.status(format_args!(
+        "Searching {} {} region(s) of process {} for pointers to 0x{:x}...",
+        regions.len(),
+        if writable_only { "writable" } else { "readable" },
+        pid,
+        target
+    ));
+    let stop = interrupt_flag();
+    let pointers = find_pointers_to(&handle, &regions, pointer_size(pid), target, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the pointers found so far"));
+    }
+
+    format.status(format_args!("Found {} pointer(s):", pointers.len()));
+    let shown = display_limit(pointers.len(), max_display, format);
+    let region_names = RegionNames::for_process(pid);
+    let headings = vec![
+        Heading::new("Address", "address").plain(),
+        Heading::new("Region", "region"),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for pointer in pointers.iter().take(shown) {
+        printer.row(vec![
+            format!("0x{:x}", pointer.address),
+            region_names.region_name_for(pointer.address).unwrap_or("").to_string(),
+        ])?;
+    }
+    printer.footer(pointers.len())?;
+    print_truncation_note(pointers.len() - shown, false, format);
+
+    Ok(())
+}
+
+pub fn test_pointer_paths(
+    pid: u32,
+    paths_file: &str,
+    expect_value: &str,
+    type_str: &str,
+    size: Option<usize>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::ProcessHandle;
+    use crate::core::ptrpath::{parse_paths, resolve_chain};
+    use printer::{Heading, printer};
+
+    let text = std::fs::read_to_string(paths_file)
+        .map_err(|e| format!("Could not read paths file '{paths_file}': {e}"))?;
+    let paths = parse_paths(&text)?;
+    let value_type = parse_sized_value_type(type_str, size)?;
+    let scan = Scan::new(pid, vec![], value_type, None, None, None)?;
+    let expected = scan.value_from_str(expect_value)?;
+    let handle = ProcessHandle::open(pid)?;
+    let pointer_size = pointer_size(pid);
+
+    format.status(format_args!("Resolving {} pointer path(s) in process {}...", paths.len(), pid));
+    let headings = vec![
+        Heading::new("Path", "path").plain(),
+        Heading::new("Address", "address").plain(),
+        Heading::new("Value", "value").plain(),
+        Heading::new("Result", "result"),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    let mut passed = 0;
+    for path in &paths {
+        let resolved = resolve_address(Some(pid), &path.base)
+            .and_then(|base| Ok(resolve_chain(&handle, pointer_size, base, &path.offsets)?));
+        let (address, value, result) = match resolved {
+            Err(e) => (String::new(), String::new(), format!("FAIL: {e}")),
+            Ok(address) => match handle.read(address as usize, expected.len()) {
+                Err(_) => (format!("0x{address:x}"), String::new(), String::from("FAIL: unreadable")),
+                Ok(current) => {
+                    let value = value_type.format_value(&current, None)?;
+                    let result = if current == expected {
+                        passed += 1;
+                        String::from("ok")
+                    } else {
+                        String::from("FAIL: value differs")
+                    };
+                    (format!("0x{address:x}"), value, result)
+                }
+            },
+        };
+        printer.row(vec![path.to_string(), address, value, result])?;
+    }
+    printer.footer(paths.len())?;
+    format.status(format_args!("{} of {} path(s) resolve to {}", passed, paths.len(), expect_value));
+
+    Ok(())
+}
+
+pub fn find_code(
+    pid: u32,
+    pattern_str: &str,
+    max_display: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::codescan::{CodePattern, find_code, format_pattern};
+    use printer::{Heading, printer};
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::{MemoryRegionPerms, MemorySource, ProcessHandle};
+    use crate::core::proc::process_is_64bit;
+
+    let bitness = if process_is_64bit(pid) { 64 } else { 32 };
+    let pattern = CodePattern::parse(pattern_str, bitness)?;
+
+    let handle = ProcessHandle::open(pid)?;
+    let regions = handle.regions(None, None, Some(&[MemoryRegionPerms::Execute]))?;
+    format.status(format_args!(
+        "Searching {} executable region(s) of process {} ({}-bit) for '{}', pattern(s):",
+        regions.len(),
+        pid,
+        bitness,
+        pattern_str
+    ));
+    for encoding in &pattern.encodings {
+        format.status(format_args!("  {}", format_pattern(encoding)));
+    }
+    let stop = interrupt_flag();
+    let matches = find_code(&handle, &regions, &pattern, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the instructions found so far"));
+    }
+
+    format.status(format_args!("Found {} instruction(s):", matches.len()));
+    let shown = display_limit(matches.len(), max_display, format);
+    let region_names = RegionNames::for_process(pid);
+    let headings = vec![
+        Heading::new("Address", "address").plain(),
+        Heading::new("Region", "region"),
+        Heading::new("Instruction", "instruction").plain(),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for found in matches.iter().take(shown) {
+        printer.row(vec![
+            format!("0x{:x}", found.address),
+            region_names.region_name_for(found.address).unwrap_or("").to_string(),
+            found.text.clone(),
+        ])?;
+    }
+    printer.footer(matches.len())?;
+    print_truncation_note(matches.len() - shown, false, format);
+
+    Ok(())
+}
+
+/// Size of a `value_type` value in process `pid`, for commands that only take types with
+/// a fixed size
+fn fixed_value_size(value_type: ValueType, pid: u32, command: &str) -> Result<usize, Box<dyn Error>> {
+    match value_type {
+        ValueType::Pointer => Ok(pointer_size(pid)),
+        ValueType::String | ValueType::Hex => {
+            Err(format!("{command} needs a fixed size type, not string or hex").into())
+        }
+        value_type => match value_type.get_size() {
+            0 => Err(format!("{command} needs a fixed size type, not {}", value_type.get_string()).into()),
+            size => Ok(size as usize),
+        },
+    }
+}
+
+pub fn scan_compare(
+    pid_a: u32,
+    pid_b: u32,
+    type_str: &str,
+    mode_str: &str,
+    max_results: usize,
+    max_display: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::mem::ProcessHandle;
+    use crate::core::pairscan::{PairMode, layout, scan_pair};
+    use crate::core::proc::mappings;
+    use printer::{Heading, printer};
+
+    let value_type = parse_value_type(type_str)?;
+    let value_size = fixed_value_size(value_type, pid_a, "scan-compare")?;
+    let mode = match mode_str.to_lowercase().as_str() {
+        "equal" => PairMode::Equal,
+        "differ" => PairMode::Differ,
+        _ => return Err(format!("Invalid mode '{mode_str}', use equal or differ").into()),
+    };
+
+    let (handle_a, handle_b) = (ProcessHandle::open(pid_a)?, ProcessHandle::open(pid_b)?);
+    let regions_a = handle_a.regions(None, None, None)?;
+    let regions_b = handle_b.regions(None, None, None)?;
+    let (layout_a, layout_b) =
+        (layout(&regions_a, &mappings(pid_a)), layout(&regions_b, &mappings(pid_b)));
+    format.status(format_args!(
+        "Comparing {} values of processes {} and {} by module offset, {} and {} named \
+         writable region(s) ({} and {} anonymous ones are skipped)...",
+        value_type.get_string(),
+        pid_a,
+        pid_b,
+        layout_a.len(),
+        layout_b.len(),
+        regions_a.len() - layout_a.len(),
+        regions_b.len() - layout_b.len()
+    ));
+
+    let stop = interrupt_flag();
+    let pairs = scan_pair(
+        (&handle_a, &layout_a),
+        (&handle_b, &layout_b),
+        value_size,
+        mode,
+        max_results,
+        stop,
+    )?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the values compared so far"));
+    } else if pairs.len() >= max_results {
+        format.status(format_args!("Stopped at --max-results {max_results}"));
+    }
+
+    let label = if mode == PairMode::Equal { "equal" } else { "differing" };
+    format.status(format_args!("Found {} {} value(s):", pairs.len(), label));
+    let shown = display_limit(pairs.len(), max_display, format);
+    let headings = vec![
+        Heading::new("Location", "location").plain(),
+        Heading::new("Address A", "address_a"),
+        Heading::new("Address B", "address_b"),
+        Heading::new("Value A", "value_a").plain(),
+        Heading::new("Value B", "value_b").plain(),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), None);
+    printer.header()?;
+    for pair in pairs.iter().take(shown) {
+        printer.row(vec![
+            format!("{}+0x{:x}", pair.name, pair.offset),
+            format!("0x{:x}", pair.address_a),
+            format!("0x{:x}", pair.address_b),
+            value_type.format_value(&pair.value_a, None)?,
+            value_type.format_value(&pair.value_b, None)?,
+        ])?;
+    }
+    printer.footer(pairs.len())?;
+    print_truncation_note(pairs.len() - shown, false, format);
+
+    Ok(())
+}
+
+pub fn value_histogram(
+    pid: u32,
+    type_str: &str,
+    region_name: &str,
+    top: usize,
+    max_distinct: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use printer::{Heading, printer};
+    use crate::core::histogram;
+    use crate::core::mem::{MemoryRegion, MemoryRegionPerms, MemorySource, ProcessHandle};
+    use crate::core::proc::mappings;
+
+    let value_type = parse_value_type(type_str)?;
+    let value_size = fixed_value_size(value_type, pid, "value-histogram")?;
+
+    let named: Vec<_> = mappings(pid)
+        .into_iter()
+        .filter(|m| m.path == region_name || m.name() == region_name)
+        .collect();
+    if named.is_empty() {
+        return Err(format!("No mapping named '{region_name}' in process {pid}").into());
+    }
+    let handle = ProcessHandle::open(pid)?;
+    let regions: Vec<MemoryRegion> = handle
+        .regions(None, None, Some(&[MemoryRegionPerms::Read]))?
+        .into_iter()
+        .flat_map(|r| {
+            named.iter().filter_map(move |m| {
+                let (start, end) = (r.start.max(m.start), r.end.min(m.end));
+                (start < end).then(|| MemoryRegion { start, end, perms: r.perms.clone() })
+            })
+        })
+        .collect();
+    let size: u64 = regions.iter().map(|r| r.end - r.start).sum();
+    format.status(format_args!(
+        "Tallying {} values in {} readable region(s) of '{}' ({} bytes)...",
+        value_type.get_string(),
+        regions.len(),
+        region_name,
+        size
+    ));
+
+    let stop = interrupt_flag();
+    let histogram = histogram::value_histogram(&handle, &regions, value_size, max_distinct, stop)?;
+    if stop.load(std::sync::atomic::Ordering::SeqCst) {
+        format.status(format_args!("Interrupted, showing the values tallied so far"));
+    }
+    format.status(format_args!(
+        "{} value(s), {} distinct, top {}:",
+        histogram.total,
+        histogram.counts.len(),
+        top.min(histogram.counts.len())
+    ));
+    let headings = vec![
+        Heading::new("Value", "value").plain(),
+        Heading::new("Count", "count").number().plain(),
+        Heading::new("Share", "share"),
+    ];
+    let mut printer = printer(format, headings, std::io::stdout().lock(), Some(table::MAX_CELL_WIDTH));
+    printer.header()?;
+    for (value, count) in histogram.counts.iter().take(top) {
+        printer.row(vec![
+            value_type.format_value(value, None)?,
+            count.to_string(),
+            format!("{:.2}%", *count as f64 * 100.0 / histogram.total as f64),
+        ])?;
+    }
+    printer.footer(histogram.counts.len())?;
+    if histogram.untracked > 0 {
+        format.status(format_args!(
+            "Note: {} value(s) were not tallied after reaching --max-distinct {}",
+            histogram.untracked, max_distinct
+        ));
+    }
+
+    Ok(())
+}
+
+/// A row of a `log-changes` log
+#[derive(Debug, Clone, PartialEq)]
+struct LogRow {
+    timestamp: String,
+    elapsed: f64,
+    old: f64,
+    new: f64,
+}
+
+/// Columns of a CSV log, found by the names in its header line
+#[derive(Debug, Clone, PartialEq)]
+struct CsvLogColumns {
+    timestamp: usize,
+    elapsed: usize,
+    old: usize,
+    new: usize,
+}
+
+impl CsvLogColumns {
+    /// The columns named in `header`, `None` if it isn't the header of a CSV log
+    fn find(header: &str) -> Option<Self> {
+        let names = printer::parse_csv_row(header)?;
+        let column = |name: &str| names.iter().position(|n| n.trim().eq_ignore_ascii_case(name));
+        Some(CsvLogColumns {
+            timestamp: column("timestamp")?,
+            elapsed: column("elapsed")?,
+            old: column("old")?,
+            new: column("new")?,
+        })
+    }
+
+    /// A row of the log, `None` if it's malformed or its values aren't numbers
+    fn parse_row(&self, line: &str) -> Option<LogRow> {
+        let fields = printer::parse_csv_row(line)?;
+        let field = |i: usize| fields.get(i).map(|field| field.trim());
+        // seconds, with or without the `+` and `s` of the table layout
+        let elapsed = field(self.elapsed)?;
+        let elapsed = elapsed.strip_prefix('+').unwrap_or(elapsed);
+        Some(LogRow {
+            timestamp: field(self.timestamp)?.to_string(),
+            elapsed: elapsed.strip_suffix('s').unwrap_or(elapsed).parse().ok()?,
+            old: field(self.old)?.parse().ok()?,
+            new: field(self.new)?.parse().ok()?,
+        })
+    }
+}
+
+/// Parse a `log-changes` row, in either the old/new or the `--show-delta` layout. Only
+/// numeric values can be analysed, other rows are `None`.
+fn parse_log_row(line: &str) -> Option<LogRow> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let (old, new) = match parts.as_slice() {
+        [_, _, old, "->", new, ..] => (old, new),
+        // `log-changes --addresses-file` adds the entry as a last column
+        [_, _, old, new] | [_, _, old, new, _] => (old, new),
+        _ => return None,
+    };
+    Some(LogRow {
+        timestamp: parts[0].to_string(),
+        elapsed: parts[1].strip_prefix('+')?.strip_suffix('s')?.parse().ok()?,
+        old: old.parse().ok()?,
+        new: new.parse().ok()?,
+    })
+}
+
+pub fn analyze_log(
+    path: &str,
+    min_delta: f64,
+    direction: &str,
+    since: Option<f64>,
+) -> Result<(), Box<dyn Error>> {
+    let (want_up, want_down) = match direction.to_lowercase().as_str() {
+        "any" => (true, true),
+        "up" => (true, false),
+        "down" => (false, true),
+        _ => return Err(format!("Invalid direction '{direction}', use up, down or any").into()),
+    };
+    let contents = std::fs::read_to_string(path)?;
+
+    let (mut increases, mut decreases, mut skipped) = (0, 0, 0);
+    let mut largest: Option<LogRow> = None;
+    let mut header_printed = false;
+    // set by the header line of a CSV log, table logs are read by position
+    let mut csv: Option<CsvLogColumns> = None;
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.contains(',') && line.to_lowercase().starts_with("timestamp") {
+            csv = Some(CsvLogColumns::find(line).ok_or_else(|| {
+                format!("{path} line {}: a CSV log needs timestamp, elapsed, old and new columns", i + 1)
+            })?);
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Timestamp") {
+            continue;
+        }
+        let row = match &csv {
+            Some(columns) => columns.parse_row(line),
+            None => parse_log_row(line),
+        };
+        let Some(row) = row else {
+            eprintln!("Skipping line {}: '{}'", i + 1, line);
+            skipped += 1;
+            continue;
+        };
+        if since.is_some_and(|since| row.elapsed < since) {
+            continue;
+        }
+
+        let delta = row.new - row.old;
+        if delta > 0.0 {
+            increases += 1;
+        } else if delta < 0.0 {
+            decreases += 1;
+        }
+        if largest
+            .as_ref()
+            .is_none_or(|l| delta.abs() > (l.new - l.old).abs())
+        {
+            largest = Some(row.clone());
+        }
+
+        let shown = delta.abs() > min_delta
+            && ((delta > 0.0 && want_up) || (delta < 0.0 && want_down));
+        if shown {
+            if !header_printed {
+                println!(
+                    "{:<20} {:<12} {:<20} {:<20} {:<}",
+                    "Timestamp", "Elapsed", "Old", "New", "Delta"
+                );
+                header_printed = true;
+            }
+            println!(
+                "{:<20} {:<12} {:<20} {:<20} {:+}",
+                row.timestamp,
+                format!("+{:.6}s", row.elapsed),
+                row.old,
+                row.new,
+                delta
+            );
+        }
+    }
+
+    println!();
+    println!("Increases: {increases}, decreases: {decreases}");
+    if let Some(row) = largest {
+        println!(
+            "Largest jump: {} -> {} ({:+}) at +{:.6}s",
+            row.old,
+            row.new,
+            row.new - row.old,
+            row.elapsed
+        );
+    }
+    if skipped > 0 {
+        println!("Skipped {skipped} malformed or non-numeric row(s)");
+    }
+
+    Ok(())
+}
+
+/// Print the results saved at `path`, only the ones within `near.1` bytes of the
+/// reference `near.0` if given (see `scan --near-result`)
+pub fn load_results(
+    path: &str,
+    max_display: usize,
+    near: Option<(&str, u64)>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    use crate::core::results::load_saved;
+    use printer::{Column, print_rows, printer};
+
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut saved = load_saved(&mut file)?;
+
+    format.status(format_args!(
+        "{} result(s) from process {} (type: {})",
+        saved.results.len(),
+        saved.pid,
+        saved.value_type.get_string()
+    ));
+    if let Some(metadata) = saved.metadata.as_ref().filter(|m| !m.value.is_empty()) {
+        format.status(format_args!(
+            "Scanned for '{}' as {}, see `scan-info` for details",
+            metadata.value, metadata.type_name
+        ));
+    }
+    if let Some((spec, within)) = near {
+        // module names resolve in the process the results came from, while it runs
+        let reference = near_reference(saved_process(path, &saved).ok(), spec, &saved.results)?;
+        retain_near(&mut saved.results, reference, within);
+        format.status(format_args!("{} of them within 0x{within:x} bytes of 0x{reference:x}", saved.results.len()));
+    }
+    let shown = display_limit(saved.results.len(), max_display, format);
+    // files saved without values only have the addresses to show
+    let columns: &[Column] = match saved.has_values {
+        true => &[Column::Address, Column::Value],
+        false => &[Column::Address],
+    };
+    let columns = Columns::new(columns);
+    let stdout = std::io::stdout().lock();
+    let mut printer = printer(format, columns.headings(), stdout, Some(table::MAX_CELL_WIDTH));
+    if print_rows(printer.as_mut(), &columns, saved.results.iter().take(shown), saved.results.len())? {
+        format.status(format_args!("Long values were cut to {} characters", table::MAX_CELL_WIDTH));
+    }
+    print_truncation_note(saved.results.len() - shown, false, format);
+
+    Ok(())
+}
+
+pub fn scan_to_addresses(path: &str, absolute: bool) -> Result<(), Box<dyn Error>> {
+    use crate::core::mem::{RelativeAddress, relative_address};
+    use crate::core::proc::{mappings, modules_in};
+    use crate::core::results::load_saved;
+    use std::io::Write;
+
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let saved = load_saved(&mut file)?;
+    let size = match saved.value_type {
+        ValueType::Pointer => None,
+        value_type => Some(value_type.get_size() as usize).filter(|&size| size > 0),
+    };
+    // the modules of the process the results came from, if it still runs
+    let (mappings, modules) = match absolute {
+        true => (vec![], vec![]),
+        false => match saved_process(path, &saved) {
+            Ok(pid) => {
+                let mappings = mappings(pid);
+                let modules = modules_in(&mappings);
+                (mappings, modules)
+            }
+            Err(e) => {
+                if !saved.results.is_empty() {
+                    eprintln!("Warning: {e}");
+                    eprintln!("The addresses are written as they are and won't resolve after a restart");
+                }
+                (vec![], vec![])
+            }
+        },
+    };
+
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    writeln!(
+        out,
+        "# {} result(s) of process {} ({})",
+        saved.results.len(),
+        saved.pid,
+        saved.value_type.get_string()
+    )?;
+    let mut fixed = 0;
+    for result in &saved.results {
+        let address = match relative_address(&mappings, &modules, result.address) {
+            Some(relative @ RelativeAddress::Module { .. }) => relative.to_string(),
+            _ => {
+                fixed += 1;
+                format!("0x{:x}", result.address)
+            }
+        };
+        // saved values give the size of string/hex results
+        match (saved.has_values && !result.value.is_empty()).then_some(result.value.len()).or(size) {
+            Some(size) => writeln!(out, "{address}:{size}")?,
+            None => writeln!(out, "{address}")?,
+        }
+    }
+    out.flush()?;
+    if !absolute && !modules.is_empty() && fixed > 0 {
+        eprintln!(
+            "Warning: {fixed} of {} address(es) aren't inside a module (heap, stack or anonymous \
+             memory) and can't be relocated, they won't resolve after a restart",
+            saved.results.len()
+        );
+    }
+
+    Ok(())
+}
+
+pub fn scan_info(path: &str) -> Result<(), Box<dyn Error>> {
+    use crate::core::results::load_saved_header;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let header = load_saved_header(&mut file)?;
+
+    println!("{:<12} {}, version {}", "Format", header.format.name(), header.version);
+    println!("{:<12} {}", "Process", header.pid);
+    println!("{:<12} {}", "Results", header.count);
+    println!("{:<12} {}", "Values", if header.has_values { "saved" } else { "not saved" });
+    let Some(metadata) = header.metadata else {
+        println!("{:<12} {}", "Type", header.value_type.get_string());
+        println!("(saved before format version 2, no scan parameters recorded)");
+        return Ok(());
+    };
+
+    let age = SystemTime::now()
+        .duration_since(UNIX_EPOCH + Duration::from_secs(metadata.created))
+        .unwrap_or_default()
+        .as_secs();
+    let address = |address: Option<u64>| address.map(|a| format!("0x{a:x}"));
+    println!("{:<12} {}", "Type", metadata.type_name);
+    println!(
+        "{:<12} {}",
+        "Value",
+        if metadata.value.is_empty() { "(none)" } else { metadata.value.as_str() }
+    );
+    println!(
+        "{:<12} {} - {}",
+        "Range",
+        address(metadata.start).unwrap_or_else(|| String::from("start")),
+        address(metadata.end).unwrap_or_else(|| String::from("end"))
+    );
+    println!(
+        "{:<12} {}",
+        "Filters",
+        if metadata.filters.is_empty() { String::from("(none)") } else { metadata.filters.join(", ") }
+    );
+    println!(
+        "{:<12} {} ({}h {}m {}s ago)",
+        "Created", metadata.created, age / 3600, age / 60 % 60, age % 60
+    );
+    println!("{:<12} {}", "Written by", metadata.tool_version);
+
+    Ok(())
+}
+
+pub fn inject_code(pid: u32, file: &str, yes: bool) -> Result<(), Box<dyn Error>> {
+    use crate::core::debug::interrupt_flag;
+    use crate::core::inject::inject;
+
+    if !yes {
+        return Err(
+            "Injected code runs inside the target and can crash it, pass --yes to go ahead".into(),
+        );
+    }
+
+    let code = std::fs::read(file)?;
+    // the target is stopped with changed registers while the code goes in, Ctrl+C must
+    // not kill us before they are put back
+    interrupt_flag();
+    let injection = inject(pid, &code)?;
+    println!(
+        "Injected {} byte(s) at 0x{:x} (allocated 0x{:x}-0x{:x})",
+        code.len(),
+        injection.entry,
+        injection.base,
+        injection.base + injection.size as u64
+    );
+    println!("Started thread {}", injection.tid);
+
+    Ok(())
+}
+
+pub fn control_process(pid: u32, action_str: &str, yes: bool) -> Result<(), Box<dyn Error>> {
+    use crate::core::proc::{self, ProcessAction, process_details};
+
+    let action = match action_str.to_lowercase().as_str() {
+        "suspend" => ProcessAction::Suspend,
+        "resume" => ProcessAction::Resume,
+        "kill" => ProcessAction::Kill,
+        _ => return Err(format!("Invalid action '{action_str}', use suspend, resume or kill").into()),
+    };
+    if action == ProcessAction::Kill && !yes {
+        return Err("Killing the process loses its unsaved state, pass --yes to go ahead".into());
+    }
+    let details = process_details(pid).ok_or_else(|| format!("No process with pid {pid}"))?;
+
+    proc::control_process(pid, action)
+        .map_err(|e| format!("Couldn't {} process {pid}: {e}", action_str.to_lowercase()))?;
+    let done = match action {
+        ProcessAction::Suspend => "Suspended",
+        ProcessAction::Resume => "Resumed",
+        ProcessAction::Kill => "Killed",
+    };
+    println!(
+        "{done} process {pid} ({}), it was {}",
+        details.name,
+        details.status.to_lowercase()
+    );
+
+    Ok(())
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_display_limit_within_max() {
+        assert_eq!(display_limit(10, 0, OutputFormat::Table), 10);
+        assert_eq!(display_limit(10, 10, OutputFormat::Table), 10);
+        assert_eq!(display_limit(0, DEFAULT_MAX_DISPLAY, OutputFormat::Table), 0);
+        // only a table asks, the other formats are cut off even on a terminal
+        assert_eq!(display_limit(10, 2, OutputFormat::Json), 2);
+        assert_eq!(display_limit(10, 2, OutputFormat::Csv), 2);
+    }
+
+    #[test]
+    fn test_module_offsets_survive_relocation() {
+        use crate::core::mem::relative_address;
+        use crate::core::proc::{Mapping, Module};
+        use std::path::PathBuf;
+
+        let mapped_at = |game: u64, libc: u64| {
+            vec![
+                Module { name: "libgame.so".into(), path: PathBuf::from("/g"), base: game, end: game + 0x5000 },
+                Module { name: "libc.so.6".into(), path: PathBuf::from("/c"), base: libc, end: libc + 0x9000 },
+            ]
+        };
+        let first = mapped_at(0x5555_0000_0000, 0x7f00_0000_0000);
+        let second = mapped_at(0x5612_3400_0000, 0x7fab_0000_0000);
+        let mapping = |start, end, path: &str| Mapping { start, end, path: path.to_string() };
+        let mappings = [
+            mapping(0x5555_0000_0000, 0x5555_0000_5000, "/g"),
+            mapping(0x5555_0001_0000, 0x5555_0002_0000, "[heap]"),
+            mapping(0x7f00_0000_0000, 0x7f00_0000_9000, "/c"),
+        ];
+        let module_relative = |address| {
+            relative_address(&mappings, &first, address)
+                .filter(|r| matches!(r, crate::core::mem::RelativeAddress::Module { .. }))
+                .map(|r| r.to_string())
+        };
+
+        let entry = module_relative(0x5555_0000_1a2b).unwrap();
+        assert_eq!(entry, "libgame.so+0x1a2b");
+        assert_eq!(resolve_address_in(&second, Some(1), &entry).unwrap(), 0x5612_3400_1a2b);
+        assert_eq!(module_relative(0x7f00_0000_8fff).as_deref(), Some("libc.so.6+0x8fff"));
+        // the end is past the module, heap addresses aren't in any
+        assert_eq!(module_relative(0x5555_0000_5000), None);
+        assert_eq!(module_relative(0x5555_0001_0010), None);
+        assert_eq!(module_relative(0x1000), None);
+    }
+
+    #[test]
+    fn test_select_regions() {
+        let region = |start| MemoryRegion { start, end: start + 0x1000, perms: vec![] };
+        let regions = vec![region(0x1000), region(0x4000), region(0x8000)];
+
+        let selected = select_regions(&regions, &[2, 0, 2]).unwrap();
+        assert_eq!(selected.iter().map(|r| r.start).collect::<Vec<_>>(), vec![0x1000, 0x8000]);
+        let err = select_regions(&regions, &[1, 3]).unwrap_err().to_string();
+        assert!(err.contains("index 3 is out of range, there are 3 regions (0-2)"), "{err}");
+    }
+
+    #[test]
+    fn test_scan_memory_reversed_range() {
+        let args = ScanArgs {
+            pid: Some(0),
+            dump_file: None,
+            map: None,
+            value: Some(String::from("1")),
+            unknown: false,
+            dense: false,
+            r#type: String::from("u32"),
+            start: Some(String::from("0x2000")),
+            end: Some(String::from("0x1000")),
+            region_index: vec![],
+            thread: None,
+            not: false,
+            glob: false,
+            no_stitch: false,
+            align_to: None,
+            stride: None,
+            skip_uniform: false,
+            reverse_hex: false,
+            checkpoint: None,
+            resume: None,
+            continue_on_error: false,
+            previous: None,
+            also_scan: false,
+            compare: None,
+            expr: None,
+            monotonic: None,
+            samples: 5,
+            baseline: String::from("previous"),
+            rounds: 1,
+            interval: Duration::ZERO,
+            precision: None,
+            save: None,
+            output: None,
+            max_display: DEFAULT_MAX_DISPLAY,
+            save_values: false,
+            format: String::from("bin"),
+            freeze_target: false,
+            spill_to: None,
+            context: None,
+            cache_stats: false,
+            prefer_aligned: false,
+            sort: None,
+            desc: false,
+            near_result: None,
+            only: vec![],
+            within: 0x100,
+            stable: false,
+            stable_delay: Duration::from_millis(100),
+            size: None,
+        };
+        let result = scan_memory(&args, OutputFormat::Table);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            ScanError::AddressMismatch.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("1s").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("100us").unwrap(), Duration::from_micros(100));
+        assert_eq!(parse_duration(" 50 ").unwrap(), Duration::from_millis(50));
+        assert!(parse_duration("fast").is_err());
+        assert!(parse_duration("5h").unwrap_err().contains("unit"));
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_every_type_parses_by_name() {
+        use crate::core::scan::Scan;
+        use crate::core::utils::FakeMemory;
+        use std::sync::Arc;
+
+        for value_type in ValueType::all() {
+            assert_eq!(parse_value_type(value_type.name()).unwrap(), value_type);
+            // the example is a valid value of the type
+            let scan =
+                Scan::with_source(Arc::new(FakeMemory::new()), vec![], value_type, None, None, None)
+                    .unwrap();
+            let value = scan.value_from_str(value_type.example_value()).unwrap();
+            assert!(value_type.get_size() == 0 || value.len() as u64 == value_type.get_size());
+        }
+        assert_eq!(parse_value_type("POINTER").unwrap(), ValueType::Pointer);
+        assert!(parse_value_type("u128").is_err());
+        assert_eq!(parse_value_type("raw:16").unwrap(), ValueType::Raw(16));
+        assert!(parse_value_type("raw").is_err() && parse_value_type("raw:0").is_err());
+        assert_eq!(parse_sized_value_type("RAW", Some(16)).unwrap(), ValueType::Raw(16));
+        assert_eq!(parse_sized_value_type("hex", Some(16)).unwrap(), ValueType::Hex);
+    }
+
+    #[test]
+    fn test_parse_address_list() {
+        let text = "\
+# health and ammo
+0x1000
+  0x2000:8   # with a size
+
+libgame.so+0x10:4
+";
+        assert_eq!(
+            parse_address_list(text).unwrap(),
+            vec![
+                AddressEntry { line: 2, spec: String::from("0x1000"), size: None },
+                AddressEntry { line: 3, spec: String::from("0x2000"), size: Some(8) },
+                AddressEntry { line: 5, spec: String::from("libgame.so+0x10"), size: Some(4) },
+            ]
+        );
+        assert!(parse_address_list("").unwrap().is_empty());
+
+        // resolving names the line, not the entry's position among the entries
+        let entries = parse_address_list("# ids\n0x1000\n\nlibgame.so+0x10\n").unwrap();
+        let err = resolve_entries(None, "list.txt", &entries).unwrap_err().to_string();
+        assert!(err.starts_with("list.txt line 4:"), "{err}");
+
+        let err = parse_address_list("0x1000\n0x2000:big\n").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+        let err = parse_address_list("0x1000:0").unwrap_err();
+        assert!(err.starts_with("line 1:"), "{err}");
+        let err = parse_address_list("\n\n0x1000 0x2000").unwrap_err();
+        assert!(err.starts_with("line 3:"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_compare_options() {
+        assert_eq!(parse_compare_mode("Increased").unwrap(), CompareMode::Increased);
+        assert_eq!(parse_compare_mode("unchanged").unwrap(), CompareMode::Unchanged);
+        assert!(parse_compare_mode("bigger").is_err());
+        assert_eq!(parse_compare_baseline("initial").unwrap(), CompareBaseline::Initial);
+        assert!(parse_compare_baseline("first").is_err());
+        assert_eq!(parse_monotonic("DEC").unwrap(), CompareMode::Decreased);
+        assert_eq!(parse_monotonic("increasing").unwrap(), CompareMode::Increased);
+        assert!(parse_monotonic("up").is_err());
+    }
+
+    #[test]
+    fn test_near_reference_by_address_or_index() {
+        let results: Vec<ScanResult> = [0x1000, 0x2000]
+            .into_iter()
+            .map(|address| ScanResult::new(address, ValueType::U32, vec![0; 4], vec![]))
+            .collect();
+        assert_eq!(near_reference(None, "#1", &results).unwrap(), 0x2000);
+        assert_eq!(near_reference(None, "0x1234+4", &results).unwrap(), 0x1238);
+        let err = near_reference(None, "#2", &results).unwrap_err().to_string();
+        assert!(err.contains("there are 2 result(s)"), "{err}");
+        assert!(near_reference(None, "#x", &results).is_err());
+        assert_eq!(parse_distance("0x100"), Ok(0x100));
+        assert_eq!(parse_distance("256"), Ok(256));
+        assert!(parse_distance("1k").is_err());
+    }
+
+    #[test]
+    fn test_saved_process_checks_the_start_time() {
+        use crate::core::results::{SavedResults, ScanMetadata};
+
+        let pid = std::process::id();
+        let saved = |process_start| SavedResults {
+            pid,
+            value_type: ValueType::U32,
+            results: vec![],
+            has_values: false,
+            metadata: Some(ScanMetadata { process_start, ..ScanMetadata::default() }),
+        };
+        let started = crate::core::proc::start_time(pid);
+        assert_eq!(saved_process("r.bin", &saved(started)).unwrap(), pid);
+        // a process that got the pid after the file was saved
+        let err = saved_process("r.bin", &saved(started.map(|t| t + 1))).unwrap_err().to_string();
+        assert!(err.contains("has exited"), "{err}");
+        let err = saved_process("r.bin", &saved(None)).unwrap_err().to_string();
+        assert!(err.contains("pass --pid"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_log_row() {
+        let row = parse_log_row("1791952953.558453    +0.489401s   3                    4").unwrap();
+        assert_eq!(row.timestamp, "1791952953.558453");
+        assert_eq!((row.elapsed, row.old, row.new), (0.489401, 3.0, 4.0));
+
+        let row = parse_log_row("1791952954.059628    +0.990575s   2.5 -> -1 (Δ-3.5, -140.00%)");
+        assert_eq!(row.map(|r| (r.old, r.new)), Some((2.5, -1.0)));
+        let row = parse_log_row("1791952954.059628    +0.990575s   7    9    libgame.so+0x10");
+        assert_eq!(row.map(|r| (r.old, r.new)), Some((7.0, 9.0)));
+
+        assert!(parse_log_row("1791952954.059628 +0.990575s abc def").is_none());
+        assert!(parse_log_row("1791952954.059628 +0.990575s 1").is_none());
+    }
+
+    #[test]
+    fn test_parse_csv_log_rows() {
+        let columns = CsvLogColumns::find("timestamp,elapsed,old,new,change,address").unwrap();
+        let row = columns.parse_row("1791952953.558453,0.489401,3,4,3 -> 4 (Δ+1),\"a,b\"").unwrap();
+        assert_eq!(row.timestamp, "1791952953.558453");
+        assert_eq!((row.elapsed, row.old, row.new), (0.489401, 3.0, 4.0));
+        // columns go by name, the table's elapsed format works too
+        let columns = CsvLogColumns::find("Old, New, Elapsed, Timestamp").unwrap();
+        let row = columns.parse_row("2.5,-1,+0.5s,17").unwrap();
+        assert_eq!((row.elapsed, row.old, row.new), (0.5, 2.5, -1.0));
+
+        assert!(columns.parse_row("abc,1,0.5,17").is_none());
+        assert!(columns.parse_row("1,2").is_none());
+        assert!(CsvLogColumns::find("timestamp,old,new").is_none());
+    }
+
+    #[test]
+    fn test_format_context() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let data = (0..0x1000).map(|i| i as u8).collect();
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read]);
+        let result = |address| {
+            ScanResult::new(address, ValueType::U32, vec![0xaa; 4], vec![MemoryRegionPerms::Read])
+        };
+        // the value as it is now, not the one the scan saw
+        assert_eq!(format_context(&memory, &result(0x1004), 2), "0203 [04050607] 0809");
+        assert_eq!(format_context(&memory, &result(0x1004), 0), " [04050607] ");
+        // the region ends two bytes after the value, only the next page is unknown
+        assert_eq!(format_context(&memory, &result(0x1ffa), 3), "f7f8f9 [fafbfcfd] feff??");
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x2000, vec![0x33; 0x1000], vec![MemoryRegionPerms::Read]);
+        assert_eq!(format_context(&memory, &result(0x1ffe), 1), "?? [????3333] 33");
+        // cut to the most bytes it shows
+        let long = format_context(&memory, &result(0x2000), 10_000);
+        assert_eq!(long.len(), 2 * (2 * MAX_CONTEXT_BYTES + 4) + 4);
+    }
+
+    #[test]
+    fn test_write_preview() {
+        let lines = format_write_preview(0x1000, Some(&[0x64, 0, 0, 0]), &[0xe8, 0x03, 0, 0]);
+        assert_eq!(
+            lines,
+            [
+                "2 of 4 byte(s) at 0x1000 change",
+                "  current: 64 00 00 00",
+                "  new:     e8 03 00 00",
+                "  diff:    ^^ ^^",
+            ]
+        );
+        assert_eq!(format_write_preview(0x10, Some(&[1]), &[1])[0], "0 of 1 byte(s) at 0x10 change");
+        let unreadable = format_write_preview(0x10, None, &[1, 2]);
+        assert_eq!(unreadable, ["2 byte(s) at 0x10, the current bytes can't be read", "  new:     01 02"]);
+
+        // a preview reads the target but leaves it alone
+        let value = 5_u32;
+        let address = &value as *const u32 as u64;
+        assert!(!preview_write(std::process::id(), address, &[9, 0, 0, 0], WriteMode::Preview).unwrap());
+        assert_eq!(unsafe { std::ptr::read_volatile(&value) }, 5);
+    }
+
+    #[test]
+    fn test_backup_roundtrip() {
+        let contents = format!(
+            "# nop backup\n{}\n\n{}\n",
+            format_backup_entry(0x401000, &[0x55, 0x48]),
+            format_backup_entry(0x401010, &[0xc3])
+        );
+        let entries = parse_backup(&contents).unwrap();
+        assert_eq!(
+            entries,
+            vec![(0x401000, vec![0x55, 0x48]), (0x401010, vec![0xc3])]
+        );
+        assert!(parse_backup("0x401000").is_err());
+        assert!(parse_backup("0x401000 zz").is_err());
+    }
 }
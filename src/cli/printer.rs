@@ -0,0 +1,490 @@
+//! Output formats for result rows: an aligned table for the terminal, or JSON, CSV and
+//! plain lines for scripts. Commands describe their rows with [`Columns`], or with
+//! [`Heading`]s when the rows aren't scan results, and hand every row to the
+//! [`ResultPrinter`] of the format chosen with `--output-format`.
+//!
+//! With a format other than the table, status lines (`Found 3 matches:`, notes) go to
+//! stderr so stdout holds only the results.
+use std::error::Error;
+use std::io::Write;
+
+use crate::core::json::quote;
+use crate::core::mem::MemorySource;
+use crate::core::proc::{RegionKind, RegionNames};
+use crate::core::scan::ScanResult;
+
+use super::table::Table;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    /// `{"results": [{"address": "0x1000", ...}], "count": 1}`
+    Json,
+    /// A header line with the column keys, then one line per result
+    Csv,
+    /// `<address> <value>` per result, for grep and shell loops
+    Plain,
+}
+
+impl OutputFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "plain" => Ok(OutputFormat::Plain),
+            _ => Err(format!("Invalid --output-format '{name}', use table, json, csv or plain")),
+        }
+    }
+
+    /// Print a status line, to stderr unless the results are printed as a table
+    pub fn status(self, line: std::fmt::Arguments) {
+        match self {
+            OutputFormat::Table => println!("{line}"),
+            _ => eprintln!("{line}"),
+        }
+    }
+}
+
+/// What the printers show of a column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Heading {
+    /// Name in the table header
+    pub header: &'static str,
+    /// Name of the JSON key and CSV column
+    pub key: &'static str,
+    pub kind: CellKind,
+    /// Whether the plain format prints the column, e.g. the address and the value
+    pub plain: bool,
+}
+
+impl Heading {
+    pub const fn new(header: &'static str, key: &'static str) -> Self {
+        Heading { header, key, kind: CellKind::Text, plain: false }
+    }
+
+    pub const fn number(self) -> Self {
+        Heading { kind: CellKind::Number, ..self }
+    }
+
+    pub const fn plain(self) -> Self {
+        Heading { plain: true, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellKind {
+    /// A JSON string
+    Text,
+    /// A JSON number
+    Number,
+    /// `A` or `-`, true or false in JSON and CSV
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    /// Position of the row, from 0
+    Index,
+    Address,
+    /// How the address was given, e.g. the line of an address list
+    Entry,
+    /// Name of the mapping holding the result
+    Region,
+    Type,
+    Aligned,
+    Perms,
+    /// What the result's region holds, see [`crate::core::proc::RegionKind`]
+    Kind,
+    Size,
+    Value,
+    /// Bytes around the value, see `scan --context`
+    Context,
+}
+
+impl Column {
+    fn heading(&self) -> Heading {
+        match self {
+            Column::Index => Heading::new("Index", "index").number(),
+            Column::Address => Heading::new("Address", "address").plain(),
+            Column::Entry => Heading::new("Entry", "entry"),
+            Column::Region => Heading::new("Region", "region"),
+            Column::Type => Heading::new("Type", "type"),
+            Column::Aligned => Heading { kind: CellKind::Flag, ..Heading::new("A", "aligned") },
+            Column::Perms => Heading::new("Perms", "perms"),
+            Column::Kind => Heading::new("Kind", "kind"),
+            Column::Size => Heading::new("Size", "size").number(),
+            Column::Value => Heading::new("Value", "value").plain(),
+            Column::Context => Heading::new("Context", "context"),
+        }
+    }
+}
+
+/// The columns of a command's rows and what formatting their cells needs
+#[derive(Default)]
+pub struct Columns<'a> {
+    pub columns: Vec<Column>,
+    /// Names for [`Column::Region`]
+    pub region_names: Option<&'a RegionNames>,
+    /// Type as given on the command line, for [`Column::Type`]
+    pub type_str: &'a str,
+    /// Decimal places of f32/f64 values
+    pub precision: Option<usize>,
+    /// Memory and byte count for [`Column::Context`]
+    pub context: Option<(&'a dyn MemorySource, usize)>,
+    /// [`Column::Entry`] of each row
+    pub entries: &'a [String],
+}
+
+impl Columns<'_> {
+    pub fn new(columns: &[Column]) -> Self {
+        Columns { columns: columns.to_vec(), ..Default::default() }
+    }
+
+    /// For [`printer`]
+    pub fn headings(&self) -> Vec<Heading> {
+        self.columns.iter().map(Column::heading).collect()
+    }
+
+    /// The cells of `result`, the `index`-th row
+    pub fn cells(&self, result: &ScanResult, index: usize) -> Result<Vec<String>, Box<dyn Error>> {
+        self.columns.iter().map(|column| self.cell(*column, result, index)).collect()
+    }
+
+    /// The `column` cell of `result`, an empty value is a read that failed
+    fn cell(&self, column: Column, result: &ScanResult, index: usize) -> Result<String, Box<dyn Error>> {
+        Ok(match column {
+            Column::Index => index.to_string(),
+            Column::Address => format!("0x{:x}", result.address),
+            Column::Entry => self.entries.get(index).cloned().unwrap_or_default(),
+            Column::Region => result
+                .region
+                .and_then(|i| self.region_names?.name(i))
+                .unwrap_or("")
+                .to_string(),
+            Column::Type => self.type_str.to_string(),
+            Column::Aligned => String::from(if result.aligned { "A" } else { "-" }),
+            Column::Perms => super::format_perms(&result.perms),
+            Column::Kind => {
+                let mapping = result.region.and_then(|i| self.region_names?.name(i));
+                RegionKind::classify(&result.perms, mapping).name().to_string()
+            }
+            Column::Size => result.value.len().to_string(),
+            Column::Value if result.value.is_empty() => String::from("(unreadable)"),
+            Column::Value => result.value_type.format_value(&result.value, self.precision)?,
+            Column::Context => match self.context {
+                Some((source, bytes)) if index < super::MAX_CONTEXT_ROWS => {
+                    super::format_context(source, result, bytes)
+                }
+                _ => String::new(),
+            },
+        })
+    }
+}
+
+/// Writes rows in one output format
+pub trait ResultPrinter {
+    /// Start the output, e.g. with the column names
+    fn header(&mut self) -> Result<(), Box<dyn Error>>;
+    /// One row, a cell per heading
+    fn row(&mut self, cells: Vec<String>) -> Result<(), Box<dyn Error>>;
+    /// Finish the output, `count` is the number of results, which is more than the rows
+    /// when they were cut at `--max-display`
+    fn footer(&mut self, count: usize) -> Result<(), Box<dyn Error>>;
+
+    /// Whether long cells were cut, once the footer is written
+    fn was_cut(&self) -> bool {
+        false
+    }
+}
+
+/// Printer of `format` writing rows of `headings` to `out`. Table cells longer than
+/// `max_cell` characters are cut, the other formats write every cell in full.
+pub fn printer<'a, W: Write + 'a>(
+    format: OutputFormat,
+    headings: Vec<Heading>,
+    out: W,
+    max_cell: Option<usize>,
+) -> Box<dyn ResultPrinter + 'a> {
+    match format {
+        OutputFormat::Table => {
+            let headers: Vec<&str> = headings.iter().map(|heading| heading.header).collect();
+            let table = Table::new(&headers);
+            Box::new(TablePrinter { out, table, max_cell, was_cut: false })
+        }
+        OutputFormat::Json => Box::new(JsonPrinter { headings, out, rows: 0 }),
+        OutputFormat::Csv => Box::new(CsvPrinter { headings, out }),
+        OutputFormat::Plain => Box::new(PlainPrinter { headings, out }),
+    }
+}
+
+/// Every row of `results` laid out by `columns` through `printer`, returns whether cells
+/// were cut
+pub fn print_rows<'r>(
+    printer: &mut dyn ResultPrinter,
+    columns: &Columns,
+    results: impl IntoIterator<Item = &'r ScanResult>,
+    count: usize,
+) -> Result<bool, Box<dyn Error>> {
+    printer.header()?;
+    for (index, result) in results.into_iter().enumerate() {
+        printer.row(columns.cells(result, index)?)?;
+    }
+    printer.footer(count)?;
+    Ok(printer.was_cut())
+}
+
+/// Collects the rows, the column widths are only known once all of them are in
+struct TablePrinter<W: Write> {
+    out: W,
+    table: Table,
+    max_cell: Option<usize>,
+    was_cut: bool,
+}
+
+impl<W: Write> ResultPrinter for TablePrinter<W> {
+    fn header(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn row(&mut self, cells: Vec<String>) -> Result<(), Box<dyn Error>> {
+        self.table.add_row(cells);
+        Ok(())
+    }
+
+    fn footer(&mut self, _count: usize) -> Result<(), Box<dyn Error>> {
+        self.was_cut = self.table.write(&mut self.out, self.max_cell, None)?;
+        Ok(self.out.flush()?)
+    }
+
+    fn was_cut(&self) -> bool {
+        self.was_cut
+    }
+}
+
+struct JsonPrinter<W: Write> {
+    headings: Vec<Heading>,
+    out: W,
+    rows: usize,
+}
+
+impl<W: Write> ResultPrinter for JsonPrinter<W> {
+    fn header(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(write!(self.out, "{{\"results\": [")?)
+    }
+
+    fn row(&mut self, cells: Vec<String>) -> Result<(), Box<dyn Error>> {
+        let fields: Vec<String> = self
+            .headings
+            .iter()
+            .zip(cells)
+            .map(|(heading, cell)| {
+                let value = match heading.kind {
+                    CellKind::Number => cell,
+                    CellKind::Flag => (cell == "A").to_string(),
+                    CellKind::Text => quote(&cell),
+                };
+                format!("{}: {}", quote(heading.key), value)
+            })
+            .collect();
+        let separator = if self.rows == 0 { "" } else { "," };
+        write!(self.out, "{separator}\n  {{{}}}", fields.join(", "))?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn footer(&mut self, count: usize) -> Result<(), Box<dyn Error>> {
+        let newline = if self.rows == 0 { "" } else { "\n" };
+        writeln!(self.out, "{newline}], \"count\": {count}}}")?;
+        Ok(self.out.flush()?)
+    }
+}
+
+/// `cell` quoted when it holds a separator, a quote, a line break or outer spaces
+pub fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) || cell.trim() != cell {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// The fields of a CSV line, the other way round from [`csv_field`]: a field in quotes
+/// may hold commas and doubled quotes. `None` if a quote isn't closed, e.g. by a field
+/// that runs on into the next line, or text follows a closing quote.
+pub fn parse_csv_row(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        let next = if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' => break chars.next(),
+                    c => field.push(c),
+                }
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    Some(',') => break Some(','),
+                    None => break None,
+                    Some(c) => field.push(c),
+                }
+            }
+        };
+        fields.push(field);
+        match next {
+            None => return Some(fields),
+            Some(',') => {}
+            Some(_) => return None,
+        }
+    }
+}
+
+struct CsvPrinter<W: Write> {
+    headings: Vec<Heading>,
+    out: W,
+}
+
+impl<W: Write> ResultPrinter for CsvPrinter<W> {
+    fn header(&mut self) -> Result<(), Box<dyn Error>> {
+        let keys: Vec<&str> = self.headings.iter().map(|heading| heading.key).collect();
+        Ok(writeln!(self.out, "{}", keys.join(","))?)
+    }
+
+    fn row(&mut self, cells: Vec<String>) -> Result<(), Box<dyn Error>> {
+        let fields: Vec<String> = self
+            .headings
+            .iter()
+            .zip(cells)
+            .map(|(heading, cell)| match heading.kind {
+                CellKind::Flag => (cell == "A").to_string(),
+                _ => csv_field(&cell),
+            })
+            .collect();
+        Ok(writeln!(self.out, "{}", fields.join(","))?)
+    }
+
+    fn footer(&mut self, _count: usize) -> Result<(), Box<dyn Error>> {
+        Ok(self.out.flush()?)
+    }
+}
+
+struct PlainPrinter<W: Write> {
+    headings: Vec<Heading>,
+    out: W,
+}
+
+impl<W: Write> ResultPrinter for PlainPrinter<W> {
+    fn header(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn row(&mut self, cells: Vec<String>) -> Result<(), Box<dyn Error>> {
+        let fields: Vec<String> = self
+            .headings
+            .iter()
+            .zip(cells)
+            .filter(|(heading, _)| heading.plain)
+            .map(|(_, cell)| cell)
+            .collect();
+        Ok(writeln!(self.out, "{}", fields.join(" "))?)
+    }
+
+    fn footer(&mut self, _count: usize) -> Result<(), Box<dyn Error>> {
+        Ok(self.out.flush()?)
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_printers_print_the_same_results() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::scan::ValueType;
+
+        let results = [
+            ScanResult::new(0x1000, ValueType::U32, 100_u32.to_le_bytes().to_vec(), vec![MemoryRegionPerms::Read]),
+            ScanResult::new(0x2002, ValueType::String, b"a, \"b\"".to_vec(), vec![MemoryRegionPerms::Write]),
+            ScanResult::new(0x3000, ValueType::U32, vec![], vec![]),
+        ];
+        let print = |format| {
+            let columns = Columns {
+                type_str: "u32",
+                ..Columns::new(&[Column::Address, Column::Aligned, Column::Perms, Column::Value])
+            };
+            let mut out = vec![];
+            let mut printer = printer(format, columns.headings(), &mut out, None);
+            let cut = print_rows(printer.as_mut(), &columns, &results[..2], 3).unwrap();
+            drop(printer);
+            assert!(!cut);
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            print(OutputFormat::Table),
+            "Address A Perms Value\n\
+             ------- - ----- ------\n\
+             0x1000  A R     100\n\
+             0x2002  A W     a, \"b\"\n"
+        );
+        assert_eq!(
+            print(OutputFormat::Json),
+            "{\"results\": [\n  \
+             {\"address\": \"0x1000\", \"aligned\": true, \"perms\": \"R\", \"value\": \"100\"},\n  \
+             {\"address\": \"0x2002\", \"aligned\": true, \"perms\": \"W\", \"value\": \"a, \\\"b\\\"\"}\n\
+             ], \"count\": 3}\n"
+        );
+        assert_eq!(
+            print(OutputFormat::Csv),
+            "address,aligned,perms,value\n0x1000,true,R,100\n0x2002,true,W,\"a, \"\"b\"\"\"\n"
+        );
+        assert_eq!(print(OutputFormat::Plain), "0x1000 100\n0x2002 a, \"b\"\n");
+
+        // no rows still makes a valid document, failed reads print as unreadable
+        let mut out = vec![];
+        let columns = Columns::new(&[Column::Value]);
+        print_rows(printer(OutputFormat::Json, columns.headings(), &mut out, None).as_mut(), &columns, [], 0)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"results\": [], \"count\": 0}\n");
+        let mut out = vec![];
+        let columns = Columns::new(&[Column::Index, Column::Size, Column::Value]);
+        print_rows(printer(OutputFormat::Csv, columns.headings(), &mut out, None).as_mut(), &columns, &results[2..], 1)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "index,size,value\n0,0,(unreadable)\n");
+
+        // rows that aren't scan results, e.g. the PID column of scan-all
+        let rows = |format| {
+            let headings = vec![Heading::new("PID", "pid").number(), Heading::new("Address", "address").plain()];
+            let mut out = vec![];
+            let mut printer = printer(format, headings, &mut out, None);
+            printer.header().unwrap();
+            printer.row(vec![String::from("7"), String::from("0x10")]).unwrap();
+            printer.footer(1).unwrap();
+            drop(printer);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(rows(OutputFormat::Json), "{\"results\": [\n  {\"pid\": 7, \"address\": \"0x10\"}\n], \"count\": 1}\n");
+        assert_eq!(rows(OutputFormat::Plain), "0x10\n");
+        assert_eq!(OutputFormat::parse("JSON"), Ok(OutputFormat::Json));
+        assert!(OutputFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_row_reads_csv_field() {
+        let cells = ["7", "", "a,b", "say \"hi\"", " padded "];
+        let line = cells.map(csv_field).join(",");
+        assert_eq!(parse_csv_row(&line), Some(cells.map(String::from).to_vec()));
+        assert_eq!(parse_csv_row("1,2,"), Some(vec![String::from("1"), String::from("2"), String::new()]));
+        assert_eq!(parse_csv_row("\"open,2"), None);
+        assert_eq!(parse_csv_row("\"a\"b,2"), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! Progress line for the initial scan: how far it got, the throughput and the time left.
+//! Drawn on stderr while it is a terminal, so piped output stays clean.
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Weight of the newest sample in the throughput average, lower is steadier
+const SMOOTHING: f64 = 0.2;
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Exponentially weighted moving average of bytes per second, so a few slow or fast blocks
+/// don't make the estimate jump
+#[derive(Debug, Clone)]
+pub struct Throughput {
+    rate: Option<f64>,
+    last: (Instant, u64),
+}
+
+impl Throughput {
+    pub fn new(now: Instant, bytes: u64) -> Self {
+        Throughput { rate: None, last: (now, bytes) }
+    }
+
+    /// Add a sample of the total bytes done at `now`, returns the smoothed rate
+    pub fn update(&mut self, now: Instant, bytes: u64) -> Option<f64> {
+        let (then, before) = self.last;
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if elapsed <= 0.0 {
+            return self.rate;
+        }
+        let sample = bytes.saturating_sub(before) as f64 / elapsed;
+        self.rate = Some(match self.rate {
+            None => sample,
+            Some(rate) => rate + SMOOTHING * (sample - rate),
+        });
+        self.last = (now, bytes);
+        self.rate
+    }
+}
+
+fn format_rate(rate: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut rate = rate;
+    let mut unit = 0;
+    while rate >= 1000.0 && unit < UNITS.len() - 1 {
+        rate /= 1000.0;
+        unit += 1;
+    }
+    format!("{rate:.1} {}", UNITS[unit])
+}
+
+/// `42% — 1.2 GB/s — ~12s left`, or only the rate when the total isn't known
+pub fn format_progress(done: u64, total: Option<u64>, rate: Option<f64>) -> String {
+    let rate_str = rate.map(format_rate).unwrap_or_else(|| String::from("..."));
+    let Some(total) = total.filter(|total| *total > 0) else {
+        return rate_str;
+    };
+
+    let percent = done.min(total) * 100 / total;
+    let left = match rate.filter(|rate| *rate > 0.0) {
+        Some(rate) => format!("~{}s left", ((total.saturating_sub(done)) as f64 / rate).ceil() as u64),
+        None => String::from("estimating"),
+    };
+    format!("{percent}% — {rate_str} — {left}")
+}
+
+/// Redraws the progress line from a background thread until it is dropped
+pub struct ProgressLine {
+    done: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ProgressLine {
+    /// Start drawing the bytes counted by `counter` out of `total`, does nothing when stderr
+    /// isn't a terminal
+    pub fn start(counter: Arc<AtomicU64>, total: Option<u64>) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        if !std::io::stderr().is_terminal() {
+            return ProgressLine { done, thread: None };
+        }
+
+        let stop = done.clone();
+        let thread = std::thread::spawn(move || {
+            let mut throughput = Throughput::new(Instant::now(), counter.load(Ordering::Relaxed));
+            while !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(REDRAW_INTERVAL);
+                let bytes = counter.load(Ordering::Relaxed);
+                let rate = throughput.update(Instant::now(), bytes);
+                eprint!("\r\x1b[2K{}", format_progress(bytes, total, rate));
+                let _ = std::io::stderr().flush();
+            }
+            eprint!("\r\x1b[2K");
+        });
+        ProgressLine { done, thread: Some(thread) }
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_progress_estimate() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut throughput = Throughput::new(start, 0);
+        assert_eq!(throughput.update(at(1000), 1000), Some(1000.0));
+        // a burst moves the average only part of the way
+        let rate = throughput.update(at(2000), 11_000).unwrap();
+        assert!(rate > 1000.0 && rate < 5000.0, "{rate}");
+        assert_eq!(throughput.update(at(2000), 12_000), Some(rate));
+
+        assert_eq!(
+            format_progress(600_000_000, Some(1_800_000_000), Some(100_000_000.0)),
+            "33% — 100.0 MB/s — ~12s left"
+        );
+        assert_eq!(format_progress(0, Some(10), None), "0% — ... — estimating");
+        assert_eq!(format_progress(5_000, None, Some(2_500.0)), "2.5 KB/s");
+        assert_eq!(format_progress(5_000, Some(0), Some(2_500.0)), "2.5 KB/s");
+    }
+}
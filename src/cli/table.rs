@@ -0,0 +1,149 @@
+//! Column-aligned text tables for the CLI output
+use std::io::{self, Write};
+
+/// Longest cell printed in full to the terminal, longer cells are cut with an ellipsis
+pub const MAX_CELL_WIDTH: usize = 48;
+
+/// Rows of text cells, printed with every column as wide as its widest cell
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// `cell` cut to `max` characters, with an ellipsis as the last one
+fn cut(cell: &str, max: Option<usize>) -> (String, bool) {
+    match max {
+        Some(max) if cell.chars().count() > max => {
+            let mut cut: String = cell.chars().take(max.saturating_sub(1)).collect();
+            cut.push('…');
+            (cut, true)
+        }
+        _ => (cell.to_string(), false),
+    }
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Table {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    /// Add a row, missing cells are left empty
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Write the header, a separator and the first `max_rows` rows (all for `None`). Cells
+    /// longer than `max_cell` characters are cut, returns whether any cell was. Lines have
+    /// no trailing padding.
+    pub fn write<W: Write>(
+        &self,
+        out: &mut W,
+        max_cell: Option<usize>,
+        max_rows: Option<usize>,
+    ) -> io::Result<bool> {
+        let mut was_cut = false;
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .take(max_rows.unwrap_or(usize::MAX))
+            .map(|row| {
+                (0..self.headers.len())
+                    .map(|i| {
+                        let (cell, cut) = cut(row.get(i).map_or("", String::as_str), max_cell);
+                        was_cut |= cut;
+                        cell
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([self.headers[i].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |cells: &[String]| {
+            let last = cells.len().saturating_sub(1);
+            cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell.clone()
+                    } else {
+                        format!("{:<width$}", cell, width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end()
+                .to_string()
+        };
+
+        writeln!(out, "{}", line(&self.headers))?;
+        let separator: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        writeln!(out, "{}", line(&separator))?;
+        for row in &rows {
+            writeln!(out, "{}", line(row))?;
+        }
+        Ok(was_cut)
+    }
+
+    /// Print the first `max_rows` rows to stdout with cells cut at [`MAX_CELL_WIDTH`],
+    /// returns whether any cell was
+    pub fn print(&self, max_rows: Option<usize>) -> io::Result<bool> {
+        self.write(&mut io::stdout().lock(), Some(MAX_CELL_WIDTH), max_rows)
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_table_widths_follow_the_data() {
+        let mut table = Table::new(&["Address", "Value"]);
+        table.add_row(vec![String::from("0x1000"), String::from("1")]);
+        table.add_row(vec![String::from("0x7ffd00001000"), String::from("123456789")]);
+
+        let mut out = vec![];
+        assert!(!table.write(&mut out, None, None).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Address        Value\n\
+             -------------- ---------\n\
+             0x1000         1\n\
+             0x7ffd00001000 123456789\n"
+        );
+    }
+
+    #[test]
+    fn test_table_cuts_long_cells() {
+        let mut table = Table::new(&["Value", "Type"]);
+        table.add_row(vec![String::from("abcdefghij"), String::from("string")]);
+        table.add_row(vec![String::from("abc")]);
+
+        let mut out = vec![];
+        assert!(table.write(&mut out, Some(6), None).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Value  Type\n\
+             ------ ------\n\
+             abcde… string\n\
+             abc\n"
+        );
+
+        // widths only follow the rows that are written
+        let mut out = vec![];
+        assert!(!table.write(&mut out, Some(6), Some(0)).unwrap());
+        assert_eq!(String::from_utf8(out).unwrap(), "Value Type\n----- ----\n");
+    }
+}
@@ -0,0 +1,177 @@
+//! Address expressions as the CLI and the TUI take them: plain numbers, and sums with the
+//! names of the modules mapped in a process standing for their base addresses.
+use std::error::Error;
+
+use crate::core::proc::{Module, modules};
+
+/// Parse an address given as hex (`0x` prefix optional) or as decimal with a `0d` prefix.
+/// `0d` always means decimal, hex starting with `0d` needs the `0x` (`0x0d10`). Invalid
+/// input is reported with a marker under the offending characters.
+pub fn parse_address_str(addr_str: &str) -> Result<u64, Box<dyn Error>> {
+    let trimmed = addr_str.trim();
+    let (digits, radix, prefix_len) = if let Some(rest) = trimmed.strip_prefix("0d") {
+        (rest, 10, 2)
+    } else if let Some(rest) = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        (rest, 16, 2)
+    } else {
+        (trimmed, 16, 0)
+    };
+
+    if digits.is_empty() {
+        return Err(format!("Invalid address '{addr_str}': no digits after prefix").into());
+    }
+
+    let marker: String = digits
+        .chars()
+        .map(|c| if c.is_digit(radix) { ' ' } else { '^' })
+        .collect();
+    if marker.contains('^') {
+        let kind = if radix == 10 { "decimal" } else { "hex" };
+        let header = format!("Invalid {kind} address '");
+        let padding = " ".repeat(header.len() + prefix_len);
+        return Err(format!("{header}{trimmed}'\n{padding}{}", marker.trim_end()).into());
+    }
+
+    u64::from_str_radix(digits, radix)
+        .map_err(|_| format!("Invalid address '{addr_str}': value does not fit in 64 bits").into())
+}
+
+/// Resolve an address given as a plain address (see [`parse_address_str`]) or as terms
+/// joined by `+` and `-`, where a term is a number or the file name of a module mapped in
+/// process `pid` standing for its base address, e.g. `libc.so.6+0x1234` or
+/// `0x7f0000+0x10-8`. Module names need a live process.
+pub fn resolve_address(pid: Option<u32>, spec: &str) -> Result<u64, Box<dyn Error>> {
+    resolve_address_in(&pid.map(modules).unwrap_or_default(), pid, spec)
+}
+
+/// [`resolve_address`] with the modules of process `pid` given, e.g. as mapped in an
+/// earlier or later run of the process
+pub fn resolve_address_in(
+    modules: &[Module],
+    pid: Option<u32>,
+    spec: &str,
+) -> Result<u64, Box<dyn Error>> {
+    let spec = spec.trim();
+    // longest names first, so `libfoo.so.1` isn't taken for `libfoo.so`
+    let mut modules = modules.to_vec();
+    modules.sort_by_key(|m| std::cmp::Reverse(m.name.len()));
+
+    let mut total: i128 = 0;
+    let mut sign = 1;
+    let mut rest = spec;
+    loop {
+        let module = modules.iter().find(|m| {
+            rest.strip_prefix(m.name.as_str()).is_some_and(|after| {
+                let after = after.trim_start();
+                after.is_empty() || after.starts_with(['+', '-'])
+            })
+        });
+        let term_len = match module {
+            Some(module) => module.name.len(),
+            None => rest.find(['+', '-']).unwrap_or(rest.len()),
+        };
+        let term = rest[..term_len].trim();
+        if term.is_empty() {
+            return Err(format!("Invalid address '{spec}': missing a term").into());
+        }
+        let looks_numeric = ["0x", "0X", "0d"].iter().any(|p| term.starts_with(p))
+            || term.chars().all(|c| c.is_ascii_hexdigit());
+        let value = match (module, parse_address_str(term)) {
+            (Some(module), _) => module.base,
+            (None, Ok(value)) => value,
+            (None, Err(e)) if looks_numeric => return Err(e),
+            (None, Err(_)) => {
+                return Err(match pid {
+                    Some(pid) => format!("Module '{term}' is not mapped in process {pid}"),
+                    None => format!("Module '{term}' can only be resolved in a live process"),
+                }
+                .into());
+            }
+        };
+        total += sign * value as i128;
+
+        rest = rest[term_len..].trim_start();
+        match rest.chars().next() {
+            None => break,
+            Some(op) => {
+                sign = if op == '+' { 1 } else { -1 };
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    u64::try_from(total).map_err(|_| format!("Address '{spec}' is out of range").into())
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_address_hex() {
+        assert_eq!(parse_address_str("0x1000").unwrap(), 0x1000);
+        assert_eq!(parse_address_str("1000").unwrap(), 0x1000);
+        assert_eq!(parse_address_str("0XfF").unwrap(), 0xff);
+    }
+
+    #[test]
+    fn test_parse_address_decimal_prefix() {
+        assert_eq!(parse_address_str("0d4096").unwrap(), 4096);
+        // hex starting with 0d needs the 0x
+        assert_eq!(parse_address_str("0x0d10").unwrap(), 0xd10);
+        assert!(parse_address_str("0dff").unwrap_err().to_string().starts_with("Invalid decimal"));
+    }
+
+    #[test]
+    fn test_parse_address_invalid_chars_marked() {
+        let err = parse_address_str("0x12zz4").unwrap_err().to_string();
+        let mut lines = err.lines();
+        let first = lines.next().unwrap();
+        let marker = lines.next().unwrap();
+        assert_eq!(first, "Invalid hex address '0x12zz4'");
+        let pos = first.find("zz").unwrap();
+        assert_eq!(marker.find('^'), Some(pos));
+        assert_eq!(marker.trim(), "^^");
+
+        let err = parse_address_str("0d12a").unwrap_err().to_string();
+        assert!(err.starts_with("Invalid decimal address '0d12a'"));
+    }
+
+    #[test]
+    fn test_parse_address_empty_and_overflow() {
+        assert!(parse_address_str("0x").is_err());
+        assert!(parse_address_str("0x1ffffffffffffffff").is_err());
+    }
+
+    #[test]
+    fn test_resolve_address_expressions() {
+        assert_eq!(resolve_address(None, "0x1000").unwrap(), 0x1000);
+        assert_eq!(resolve_address(None, "0x1000+0x10-8").unwrap(), 0x1008);
+        assert_eq!(resolve_address(None, " 1000 + 0d16 ").unwrap(), 0x1010);
+        assert!(resolve_address(None, "0x10-0x20").unwrap_err().to_string().contains("out of range"));
+        assert!(resolve_address(None, "0x1000+").unwrap_err().to_string().contains("missing a term"));
+        assert!(resolve_address(None, "0x1000+0xzz").unwrap_err().to_string().contains('^'));
+        assert!(
+            resolve_address(None, "libc.so.6+0x10")
+                .unwrap_err()
+                .to_string()
+                .contains("live process")
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_resolve_address_modules() {
+        use crate::core::proc::modules;
+
+        let pid = std::process::id();
+        let module = modules(pid).into_iter().next().unwrap();
+        let spec = format!("{}+0x10", module.name);
+        assert_eq!(resolve_address(Some(pid), &spec).unwrap(), module.base + 0x10);
+        let err = resolve_address(Some(pid), "no-such-module.so+1").unwrap_err().to_string();
+        assert!(err.contains("is not mapped in process"), "{err}");
+    }
+}
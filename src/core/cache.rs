@@ -0,0 +1,152 @@
+//! Page cache for the passes that re-read scan results (next scan, compare, refresh).
+//! Results tend to cluster, so each page is read once per pass instead of once per result.
+//!
+//! Pages are only shared within a pass: every pass starts from an empty cache, so a
+//! compare always reads fresh memory, and the pages are dropped when it ends rather than
+//! staying resident until the next one.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::core::mem::{MemoryError, MemorySource};
+
+pub const PAGE_SIZE: u64 = 0x1000;
+
+/// How well the cache did since it was created
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheStats {
+    /// Reads served from cached pages only
+    pub hits: u64,
+    /// Reads that had to read at least one page, or memory directly
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of reads that were hits, 0 before anything was read
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            reads => self.hits as f64 / reads as f64,
+        }
+    }
+}
+
+/// Pages by page number, `None` for pages that couldn't be read as a whole (reads there
+/// go to the source)
+type Pages = HashMap<u64, Option<Arc<Vec<u8>>>>;
+
+#[derive(Debug, Default)]
+pub struct ReadCache {
+    pages: Mutex<Pages>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ReadCache {
+    /// Drop every cached page, the next reads go to memory
+    pub fn invalidate(&self) {
+        self.pages.lock().unwrap().clear();
+    }
+
+    /// Start a pass from an empty cache, so none of its reads is served from a page an
+    /// earlier pass read
+    pub fn begin_pass(&self) {
+        self.invalidate();
+    }
+
+    /// Release the pages the pass read
+    pub fn end_pass(&self) {
+        self.invalidate();
+    }
+
+    /// Read `size` bytes at `addr` from cached pages, reading the missing pages from
+    /// `source` first
+    pub fn read(
+        &self,
+        source: &dyn MemorySource,
+        addr: usize,
+        size: usize,
+    ) -> Result<Vec<u8>, MemoryError> {
+        if size == 0 {
+            return source.read(addr, size);
+        }
+
+        let first = addr as u64 / PAGE_SIZE;
+        let last = (addr as u64 + size as u64 - 1) / PAGE_SIZE;
+        let mut hit = true;
+        let mut pages = Vec::with_capacity((last - first + 1) as usize);
+        for page in first..=last {
+            let cached = self.pages.lock().unwrap().get(&page).cloned();
+            let data = match cached {
+                Some(data) => data,
+                None => {
+                    hit = false;
+                    // read outside the lock, other threads may read the same page meanwhile
+                    let data = source
+                        .read((page * PAGE_SIZE) as usize, PAGE_SIZE as usize)
+                        .ok()
+                        .map(Arc::new);
+                    self.pages.lock().unwrap().insert(page, data.clone());
+                    data
+                }
+            };
+            match data {
+                Some(data) => pages.push(data),
+                None => {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    return source.read(addr, size);
+                }
+            }
+        }
+
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let offset = addr - (first * PAGE_SIZE) as usize;
+        let joined: Vec<u8> = pages.iter().flat_map(|page| page.iter().copied()).collect();
+        // copied out, so the value doesn't keep the pages' allocation alive
+        Ok(joined[offset..(offset + size).min(joined.len())].to_vec())
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { hits: self.hits.load(Ordering::Relaxed), misses: self.misses.load(Ordering::Relaxed) }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_read_cache_hits_and_forced_refresh() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x2000];
+        data[0x10..0x14].copy_from_slice(&1_u32.to_le_bytes());
+        data[0xffe..0x1002].copy_from_slice(&2_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        // not page sized, read directly
+        memory.add_region(0x10000, vec![3, 0, 0, 0], vec![MemoryRegionPerms::Read]);
+
+        let cache = ReadCache::default();
+        assert_eq!(cache.read(&memory, 0x1010, 4).unwrap(), 1_u32.to_le_bytes());
+        assert_eq!(cache.read(&memory, 0x1010, 4).unwrap(), 1_u32.to_le_bytes());
+        // spans both pages, the first one is cached already
+        assert_eq!(cache.read(&memory, 0x1ffe, 4).unwrap(), 2_u32.to_le_bytes());
+        assert_eq!(cache.read(&memory, 0x10000, 4).unwrap(), [3, 0, 0, 0]);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 3));
+        assert_eq!(cache.pages.lock().unwrap().len(), 3);
+
+        // within a pass a cached page is served even after memory changed
+        memory.write(0x1010, &5_u32.to_le_bytes()).unwrap();
+        assert_eq!(cache.read(&memory, 0x1010, 4).unwrap(), 1_u32.to_le_bytes());
+        // the next pass reads it again, and the pages don't outlive their pass
+        cache.begin_pass();
+        assert_eq!(cache.read(&memory, 0x1010, 4).unwrap(), 5_u32.to_le_bytes());
+        assert_eq!(cache.stats().misses, 4);
+        cache.end_pass();
+        assert_eq!(cache.pages.lock().unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,292 @@
+//! Resume files of long initial scans (unknown value scans over gigabytes), so an
+//! interrupted scan continues where it stopped instead of starting over, see
+//! [`crate::core::scan::Scan::init_resumable`].
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic        b"CECP"
+//! version      u16
+//! fingerprint  u64      maps_fingerprint of the regions the scan covers
+//! header       the header of a results file without results, see `crate::core::results`
+//! records      one per checkpoint, appended as the scan goes:
+//!   region     u64      index of the region the scan got to
+//!   offset     u64      bytes of that region done
+//!   count      u32
+//!   results    count * (address u64 + u16 length + value)
+//! ```
+//!
+//! Each record only holds the matches found since the one before, so a checkpoint costs
+//! the new matches rather than all of them. The last record gives the position to resume
+//! at; a record cut short by an interruption while writing is ignored, and dropped from
+//! the file once the scan resumes.
+//!
+//! A checkpoint is only resumed when the regions the scan covers are still the ones it
+//! was written for, a process that mapped or unmapped memory in between has moved the
+//! regions the position refers to.
+use std::fmt::Display;
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+
+use crate::core::mem::{MemoryRegion, maps_fingerprint};
+use crate::core::results::{SavedResults, ScanMetadata, load_header_bin, save_results_bin};
+use crate::core::scan::{ScanResult, ValueType};
+
+const MAGIC: &[u8; 4] = b"CECP";
+const VERSION: u16 = 2;
+const HEADER_LEN: usize = 14;
+const RECORD_HEADER_LEN: usize = 20;
+
+/// How far an initial scan got: the first `offset` bytes of the `region`-th region it
+/// covers, and every region before
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanPosition {
+    pub region: usize,
+    pub offset: u64,
+}
+
+/// A checkpoint read back by [`load_checkpoint`]
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub fingerprint: u64,
+    /// Where the scan stopped, `0, 0` when it stopped before the first record
+    pub position: ScanPosition,
+    /// The matches before `position`, with the pid, type and metadata of the scan
+    pub saved: SavedResults,
+    /// Bytes of the file up to the end of the last complete record
+    len: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResumeError {
+    /// The regions the scan covers aren't the ones the checkpoint was written for
+    MapsChanged,
+    /// The checkpoint is of a scan for another value, type or filters
+    OtherScan { field: &'static str, saved: String, now: String },
+}
+
+impl Display for ResumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MapsChanged => {
+                write!(f, "The memory regions changed since the checkpoint was written, scan again without it")
+            }
+            Self::OtherScan { field, saved, now } => {
+                write!(f, "The checkpoint is of a scan with {field} '{saved}', not '{now}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResumeError {}
+
+impl Checkpoint {
+    /// Check that the checkpoint can be resumed by a scan of `regions` described by
+    /// `metadata`, which has to be for the same value and type with the same filters
+    pub fn check(&self, regions: &[MemoryRegion], metadata: &ScanMetadata) -> Result<(), ResumeError> {
+        if let Some(saved) = &self.saved.metadata {
+            let fields = [
+                ("type", saved.type_name.clone(), metadata.type_name.clone()),
+                ("value", saved.value.clone(), metadata.value.clone()),
+                ("filters", saved.filters.join(" "), metadata.filters.join(" ")),
+            ];
+            for (field, saved, now) in fields {
+                if saved != now {
+                    return Err(ResumeError::OtherScan { field, saved, now });
+                }
+            }
+        }
+        let ScanPosition { region, offset } = self.position;
+        let in_range = match regions.get(region) {
+            Some(r) => offset < r.end - r.start,
+            None => region == regions.len() && offset == 0,
+        };
+        if maps_fingerprint(regions) != self.fingerprint || !in_range {
+            return Err(ResumeError::MapsChanged);
+        }
+        Ok(())
+    }
+}
+
+/// Appends the records of a checkpoint file, see [`CheckpointWriter::record`]
+#[derive(Debug)]
+pub struct CheckpointWriter {
+    file: std::fs::File,
+}
+
+impl CheckpointWriter {
+    /// Start a checkpoint file at `path` for a scan of `regions`, replacing any there
+    pub fn create(
+        path: &Path,
+        regions: &[MemoryRegion],
+        pid: u32,
+        value_type: ValueType,
+        metadata: &ScanMetadata,
+    ) -> io::Result<CheckpointWriter> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&maps_fingerprint(regions).to_le_bytes());
+        save_results_bin(&mut header, pid, value_type, &[], true, metadata)?;
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(&header)?;
+        Ok(CheckpointWriter { file })
+    }
+
+    /// Keep appending to the file `checkpoint` was loaded from, dropping a record an
+    /// interruption cut short
+    pub fn resume(path: &Path, checkpoint: &Checkpoint) -> io::Result<CheckpointWriter> {
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+        file.set_len(checkpoint.len)?;
+        file.seek(io::SeekFrom::End(0))?;
+        Ok(CheckpointWriter { file })
+    }
+
+    /// Append the `results` found since the last record and the `position` the scan got
+    /// to, in a single write
+    pub fn record(&mut self, position: ScanPosition, results: &[ScanResult]) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let count = u32::try_from(results.len()).map_err(|_| invalid(String::from("Too many results for a record")))?;
+        let values: usize = results.iter().map(|r| 10 + r.value.len()).sum();
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + values);
+        record.extend_from_slice(&(position.region as u64).to_le_bytes());
+        record.extend_from_slice(&position.offset.to_le_bytes());
+        record.extend_from_slice(&count.to_le_bytes());
+        for result in results {
+            let len = u16::try_from(result.value.len())
+                .map_err(|_| invalid(format!("Value at 0x{:x} is too long", result.address)))?;
+            record.extend_from_slice(&result.address.to_le_bytes());
+            record.extend_from_slice(&len.to_le_bytes());
+            record.extend_from_slice(&result.value);
+        }
+        self.file.write_all(&record)?;
+        self.file.flush()
+    }
+}
+
+pub fn load_checkpoint(path: &Path) -> io::Result<Checkpoint> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut file = io::BufReader::new(std::fs::File::open(path)?);
+    let mut header = [0_u8; HEADER_LEN];
+    file.read_exact(&mut header)?;
+    if &header[0..4] != MAGIC {
+        return Err(invalid("Not a scan checkpoint file"));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version > VERSION {
+        return Err(invalid(&format!("Checkpoint version {version} is newer than supported version {VERSION}")));
+    }
+    if version < VERSION {
+        return Err(invalid("The checkpoint was written by an older version, scan again without it"));
+    }
+    let results_header = load_header_bin(&mut file)?;
+    let len = file.stream_position()?;
+    let mut records = vec![];
+    file.read_to_end(&mut records)?;
+
+    let u64_at = |at: usize| u64::from_le_bytes(records[at..at + 8].try_into().unwrap());
+    let mut position = ScanPosition::default();
+    let mut results = vec![];
+    let mut at = 0;
+    // every complete record, a torn one at the end is left out
+    while let Some(record) = records.get(at..at + RECORD_HEADER_LEN) {
+        let count = u32::from_le_bytes(record[16..20].try_into().unwrap()) as usize;
+        let mut end = at + RECORD_HEADER_LEN;
+        let mut found = Vec::with_capacity(count.min(records.len() / 10));
+        for _ in 0..count {
+            let Some(entry) = records.get(end..end + 10) else {
+                break;
+            };
+            let value_len = u16::from_le_bytes([entry[8], entry[9]]) as usize;
+            let Some(value) = records.get(end + 10..end + 10 + value_len) else {
+                break;
+            };
+            found.push(ScanResult::new(u64_at(end), results_header.value_type, value.to_vec(), vec![]));
+            end += 10 + value_len;
+        }
+        if found.len() < count {
+            break;
+        }
+        position = ScanPosition { region: u64_at(at) as usize, offset: u64_at(at + 8) };
+        results.extend(found);
+        at = end;
+    }
+
+    Ok(Checkpoint {
+        fingerprint: u64::from_le_bytes(header[6..14].try_into().unwrap()),
+        position,
+        saved: SavedResults {
+            pid: results_header.pid,
+            value_type: results_header.value_type,
+            results,
+            has_values: true,
+            metadata: results_header.metadata,
+        },
+        len: len + at as u64,
+    })
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_roundtrip_and_check() {
+        use crate::core::mem::MemoryRegionPerms;
+
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        let regions = vec![
+            MemoryRegion { start: 0x1000, end: 0x2000, perms: rw.clone() },
+            MemoryRegion { start: 0x5000, end: 0x6000, perms: rw.clone() },
+        ];
+        let result = |address: u64| ScanResult::new(address, ValueType::U32, 7_u32.to_le_bytes().to_vec(), rw.clone());
+        let metadata = ScanMetadata::now("7", "u32", None, None);
+        let path = std::env::temp_dir().join(format!("cheat-engine-rs-checkpoint-{}", std::process::id()));
+        let mut writer = CheckpointWriter::create(&path, &regions, 42, ValueType::U32, &metadata).unwrap();
+        let checkpoint = load_checkpoint(&path).unwrap();
+        assert_eq!(checkpoint.position, ScanPosition::default());
+        assert!(checkpoint.saved.results.is_empty());
+
+        // each record appends the new matches
+        writer.record(ScanPosition { region: 0, offset: 0x800 }, &[result(0x1010)]).unwrap();
+        writer.record(ScanPosition { region: 1, offset: 0 }, &[result(0x1900), result(0x1a00)]).unwrap();
+        drop(writer);
+        let checkpoint = load_checkpoint(&path).unwrap();
+        assert_eq!(checkpoint.position, ScanPosition { region: 1, offset: 0 });
+        assert_eq!(checkpoint.saved.pid, 42);
+        let addresses: Vec<u64> = checkpoint.saved.results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, [0x1010, 0x1900, 0x1a00]);
+        assert_eq!(checkpoint.saved.results[0].value, 7_u32.to_le_bytes());
+        assert_eq!(checkpoint.check(&regions, &metadata), Ok(()));
+
+        // a record cut short is ignored, and dropped once the scan resumes
+        let full = std::fs::metadata(&path).unwrap().len();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0x10]).unwrap();
+        drop(file);
+        let torn = load_checkpoint(&path).unwrap();
+        assert_eq!((torn.position, torn.saved.results.len()), (checkpoint.position, 3));
+        let mut writer = CheckpointWriter::resume(&path, &torn).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), full);
+        writer.record(ScanPosition { region: 1, offset: 0x10 }, &[result(0x5004)]).unwrap();
+        let resumed = load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resumed.position, ScanPosition { region: 1, offset: 0x10 });
+        assert_eq!(resumed.saved.results.len(), 4);
+
+        let other = |value: &str, type_name: &str| ScanMetadata::now(value, type_name, None, None);
+        let field = |metadata| match checkpoint.check(&regions, &metadata) {
+            Err(ResumeError::OtherScan { field, .. }) => Some(field),
+            _ => None,
+        };
+        assert_eq!(field(other("8", "u32")), Some("value"));
+        assert_eq!(field(other("7", "i32")), Some("type"));
+        assert_eq!(field(ScanMetadata { filters: vec![String::from("not")], ..metadata.clone() }), Some("filters"));
+        let mut remapped = regions.clone();
+        remapped[1].end = 0x7000;
+        assert_eq!(checkpoint.check(&remapped, &metadata), Err(ResumeError::MapsChanged));
+        assert_eq!(checkpoint.check(&regions[..1], &metadata), Err(ResumeError::MapsChanged));
+        let past_the_region = Checkpoint { position: ScanPosition { region: 1, offset: 0x1000 }, ..checkpoint.clone() };
+        assert_eq!(past_the_region.check(&regions, &metadata), Err(ResumeError::MapsChanged));
+    }
+}
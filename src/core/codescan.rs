@@ -0,0 +1,599 @@
+//! Instruction search: assemble a simple x86 instruction into byte patterns, find them
+//! in executable memory and check every hit with the disassembler.
+//!
+//! Supported instructions, Intel syntax and case insensitive:
+//!
+//! ```text
+//! instruction := mnemonic operand "," operand
+//! mnemonic    := mov | add | sub | and | or | xor | cmp | test | lea
+//! operand     := register | memory | immediate
+//! register    := eax ... edi, r8d ... r15d | rax ... rdi, r8 ... r15
+//! memory      := [("dword" | "qword") "ptr"] "[" register [("+" | "-") displacement] "]"
+//! immediate   := number | "?"
+//! displacement:= number | "?"
+//! number      := decimal, or hex with a 0x prefix (immediates may be negative)
+//! ```
+//!
+//! The forms are `reg, reg`, `reg, mem` and `mem, reg`, plus `reg, imm` and `mem, imm`
+//! for everything but `test` and `lea` (`mem, imm` needs a `ptr` size). `test` only
+//! takes `reg, reg` and `mem, reg`, `lea` only `reg, mem`. A `?` displacement or
+//! immediate matches any value of any encoded size, numbers use the shortest encoding a
+//! compiler would emit. Index registers, scales and RIP-relative operands aren't
+//! supported.
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter};
+use memchr::memmem;
+use rayon::prelude::*;
+
+use crate::core::mem::{MemoryError, MemoryRegion, MemorySource};
+
+const BLOCK_SIZE: usize = 0x10000;
+
+const REGISTERS_32: [&str; 16] = [
+    "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d",
+    "r12d", "r13d", "r14d", "r15d",
+];
+const REGISTERS_64: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12",
+    "r13", "r14", "r15",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeScanError {
+    Syntax(String),
+    UnknownMnemonic(String),
+    UnknownRegister(String),
+    UnsupportedForm(String),
+    ValueTooLarge(i64),
+}
+
+impl Display for CodeScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(msg) => write!(f, "Invalid instruction: {msg}"),
+            Self::UnknownMnemonic(name) => write!(f, "Unsupported mnemonic '{name}'"),
+            Self::UnknownRegister(name) => write!(f, "Unknown register '{name}'"),
+            Self::UnsupportedForm(msg) => write!(f, "Unsupported operands: {msg}"),
+            Self::ValueTooLarge(value) => write!(f, "Value {value:#x} doesn't fit the operand"),
+        }
+    }
+}
+
+impl std::error::Error for CodeScanError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Register {
+    num: u8,
+    bits: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Displacement {
+    None,
+    Value(i64),
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Register(Register),
+    Memory {
+        size: Option<u32>,
+        base: Register,
+        disp: Displacement,
+    },
+    /// `None` for a `?` wildcard
+    Immediate(Option<i64>),
+}
+
+/// Opcodes of a mnemonic: `mr` has the r/m operand first, `rm` the register first,
+/// `imm_ext` is the ModRM reg field of the `81`/`83` immediate forms and `acc_imm` the
+/// short form for an eax/rax destination
+struct Opcodes {
+    mr: Option<u8>,
+    rm: Option<u8>,
+    imm_ext: Option<u8>,
+    acc_imm: Option<u8>,
+}
+
+fn opcodes(mnemonic: &str) -> Option<Opcodes> {
+    let alu = |base: u8, ext: u8| Opcodes {
+        mr: Some(base + 1),
+        rm: Some(base + 3),
+        imm_ext: Some(ext),
+        acc_imm: Some(base + 5),
+    };
+    Some(match mnemonic {
+        "mov" => Opcodes { mr: Some(0x89), rm: Some(0x8b), imm_ext: None, acc_imm: None },
+        "add" => alu(0x00, 0),
+        "or" => alu(0x08, 1),
+        "and" => alu(0x20, 4),
+        "sub" => alu(0x28, 5),
+        "xor" => alu(0x30, 6),
+        "cmp" => alu(0x38, 7),
+        "test" => Opcodes { mr: Some(0x85), rm: None, imm_ext: None, acc_imm: None },
+        "lea" => Opcodes { mr: None, rm: Some(0x8d), imm_ext: None, acc_imm: None },
+        _ => return None,
+    })
+}
+
+fn parse_register(text: &str) -> Result<Register, CodeScanError> {
+    let position = |names: &[&str]| names.iter().position(|&n| n == text).map(|i| i as u8);
+    if let Some(num) = position(&REGISTERS_32) {
+        Ok(Register { num, bits: 32 })
+    } else if let Some(num) = position(&REGISTERS_64) {
+        Ok(Register { num, bits: 64 })
+    } else {
+        Err(CodeScanError::UnknownRegister(text.to_string()))
+    }
+}
+
+fn parse_number(text: &str) -> Result<i64, CodeScanError> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse(),
+    }
+    .map_err(|_| CodeScanError::Syntax(format!("'{text}' is not a number")))?;
+    Ok(if negative { -value } else { value })
+}
+
+fn parse_operand(text: &str) -> Result<Operand, CodeScanError> {
+    let Some(open) = text.find('[') else {
+        if text == "?" {
+            return Ok(Operand::Immediate(None));
+        }
+        if text.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+            return Ok(Operand::Immediate(Some(parse_number(text)?)));
+        }
+        return Ok(Operand::Register(parse_register(text)?));
+    };
+
+    let size = match text[..open].trim() {
+        "" => None,
+        "dword ptr" => Some(32),
+        "qword ptr" => Some(64),
+        other => return Err(CodeScanError::UnsupportedForm(format!("operand size '{other}'"))),
+    };
+    let inner = text[open + 1..]
+        .strip_suffix(']')
+        .ok_or_else(|| CodeScanError::Syntax(format!("missing ']' in '{text}'")))?;
+    let (base, disp) = match inner.find(['+', '-']) {
+        None => (inner, Displacement::None),
+        Some(i) => {
+            let disp = inner[i + 1..].trim();
+            let disp = if disp == "?" {
+                Displacement::Any
+            } else {
+                let value = parse_number(disp)?;
+                Displacement::Value(if &inner[i..=i] == "-" { -value } else { value })
+            };
+            (&inner[..i], disp)
+        }
+    };
+    if let Displacement::Value(value) = disp
+        && i32::try_from(value).is_err()
+    {
+        return Err(CodeScanError::ValueTooLarge(value));
+    }
+
+    Ok(Operand::Memory {
+        size,
+        base: parse_register(base.trim())?,
+        disp,
+    })
+}
+
+/// ModRM byte (plus SIB and displacement) for `rm`, with `reg` in the reg field. A
+/// wildcard displacement gives a disp8 and a disp32 encoding.
+fn modrm_encodings(reg: u8, rm: &Operand) -> Vec<Vec<Option<u8>>> {
+    let reg = (reg & 7) << 3;
+    let (base, disp) = match *rm {
+        Operand::Register(r) => return vec![vec![Some(0xc0 | reg | (r.num & 7))]],
+        Operand::Memory { base, disp, .. } => (base.num & 7, disp),
+        Operand::Immediate(_) => unreachable!("immediates have no ModRM encoding"),
+    };
+
+    let disps: Vec<(u8, Vec<Option<u8>>)> = match disp {
+        // [rbp]/[r13] can only be encoded with a zero displacement
+        Displacement::None | Displacement::Value(0) if base != 5 => vec![(0x00, vec![])],
+        Displacement::None => vec![(0x40, vec![Some(0)])],
+        Displacement::Value(value) => match i8::try_from(value) {
+            Ok(value) => vec![(0x40, vec![Some(value as u8)])],
+            Err(_) => vec![(0x80, (value as i32).to_le_bytes().map(Some).to_vec())],
+        },
+        Displacement::Any => vec![(0x40, vec![None]), (0x80, vec![None; 4])],
+    };
+
+    disps
+        .into_iter()
+        .map(|(mode, disp)| {
+            let mut bytes = vec![Some(mode | reg | base)];
+            // [rsp]/[r12] need a SIB byte without an index
+            if base == 4 {
+                bytes.push(Some(0x24));
+            }
+            bytes.extend(disp);
+            bytes
+        })
+        .collect()
+}
+
+/// Address size and REX prefixes for an instruction with `bits` operand size
+fn prefixes(bits: u32, reg: u8, rm: &Operand, bitness: u32) -> Result<Vec<u8>, CodeScanError> {
+    let mut prefixes = vec![];
+    let rm_num = match *rm {
+        Operand::Register(r) => r.num,
+        Operand::Memory { base, .. } => {
+            match (bitness, base.bits) {
+                (64, 32) => prefixes.push(0x67),
+                (32, 64) => {
+                    return Err(CodeScanError::UnsupportedForm(String::from(
+                        "64-bit addresses in 32-bit code",
+                    )));
+                }
+                _ => {}
+            }
+            base.num
+        }
+        Operand::Immediate(_) => 0,
+    };
+
+    let rex = 0x40 | u8::from(bits == 64) << 3 | (reg >> 3) << 2 | rm_num >> 3;
+    if rex != 0x40 {
+        if bitness == 32 {
+            return Err(CodeScanError::UnsupportedForm(String::from(
+                "64-bit or r8-r15 registers in 32-bit code",
+            )));
+        }
+        prefixes.push(rex);
+    }
+    Ok(prefixes)
+}
+
+/// Immediate bytes of `size` bytes, all wildcards for `None`
+fn immediate(value: Option<i64>, size: usize) -> Vec<Option<u8>> {
+    match value {
+        Some(value) => value.to_le_bytes()[..size].iter().copied().map(Some).collect(),
+        None => vec![None; size],
+    }
+}
+
+/// The encodings of `mnemonic dst, src`
+fn encode(
+    mnemonic: &str,
+    dst: Operand,
+    src: Operand,
+    bitness: u32,
+) -> Result<Vec<Vec<Option<u8>>>, CodeScanError> {
+    let ops = opcodes(mnemonic).ok_or_else(|| CodeScanError::UnknownMnemonic(mnemonic.to_string()))?;
+    let unsupported = || CodeScanError::UnsupportedForm(format!("{mnemonic} with these operands"));
+    // opcode bytes followed by every ModRM variant
+    let with_modrm = |opcode: u8, bits: u32, reg: u8, rm: &Operand, tail: &[Option<u8>]| {
+        let mut head: Vec<Option<u8>> =
+            prefixes(bits, reg, rm, bitness)?.into_iter().map(Some).collect();
+        head.push(Some(opcode));
+        Ok::<_, CodeScanError>(
+            modrm_encodings(reg, rm)
+                .into_iter()
+                .map(|modrm| [&head[..], &modrm, tail].concat())
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let mut encodings = vec![];
+    match (dst, src) {
+        (Operand::Register(a), Operand::Register(b)) => {
+            if a.bits != b.bits {
+                return Err(CodeScanError::UnsupportedForm(String::from("register sizes differ")));
+            }
+            if let Some(op) = ops.mr {
+                encodings.extend(with_modrm(op, a.bits, b.num, &dst, &[])?);
+            }
+            if let Some(op) = ops.rm.filter(|_| mnemonic != "lea") {
+                encodings.extend(with_modrm(op, a.bits, a.num, &src, &[])?);
+            }
+        }
+        (Operand::Register(reg), Operand::Memory { size, .. })
+        | (Operand::Memory { size, .. }, Operand::Register(reg)) => {
+            if mnemonic != "lea" && size.is_some_and(|size| size != reg.bits) {
+                return Err(CodeScanError::UnsupportedForm(String::from(
+                    "operand size doesn't match the register",
+                )));
+            }
+            let (op, rm) = match dst {
+                Operand::Register(_) => (ops.rm, src),
+                _ => (ops.mr.filter(|_| mnemonic != "lea"), dst),
+            };
+            encodings.extend(with_modrm(op.ok_or_else(unsupported)?, reg.bits, reg.num, &rm, &[])?);
+        }
+        (rm @ (Operand::Register(_) | Operand::Memory { .. }), Operand::Immediate(value)) => {
+            let bits = match rm {
+                Operand::Register(r) => r.bits,
+                Operand::Memory { size, .. } => size.ok_or_else(|| {
+                    CodeScanError::UnsupportedForm(String::from(
+                        "memory with an immediate needs dword ptr or qword ptr",
+                    ))
+                })?,
+                Operand::Immediate(_) => unreachable!(),
+            };
+            // 32-bit operands are sign extended to 64 bits
+            let value = value
+                .map(|v| match bits {
+                    32 if (i32::MIN as i64..=u32::MAX as i64).contains(&v) => Ok(v as u32 as i32 as i64),
+                    _ if i32::try_from(v).is_ok() => Ok(v),
+                    _ => Err(CodeScanError::ValueTooLarge(v)),
+                })
+                .transpose()?;
+
+            if mnemonic == "mov" {
+                match rm {
+                    Operand::Register(r) if bits == 32 => {
+                        let mut bytes: Vec<Option<u8>> =
+                            prefixes(bits, 0, &rm, bitness)?.into_iter().map(Some).collect();
+                        bytes.push(Some(0xb8 + (r.num & 7)));
+                        bytes.extend(immediate(value, 4));
+                        encodings.push(bytes);
+                    }
+                    _ => encodings.extend(with_modrm(0xc7, bits, 0, &rm, &immediate(value, 4))?),
+                }
+            } else {
+                let ext = ops.imm_ext.ok_or_else(unsupported)?;
+                let fits_imm8 = value.is_none_or(|v| i8::try_from(v).is_ok());
+                if fits_imm8 {
+                    encodings.extend(with_modrm(0x83, bits, ext, &rm, &immediate(value, 1))?);
+                }
+                if value.is_none() || !fits_imm8 {
+                    encodings.extend(with_modrm(0x81, bits, ext, &rm, &immediate(value, 4))?);
+                    if let (Operand::Register(Register { num: 0, .. }), Some(op)) = (rm, ops.acc_imm)
+                    {
+                        let mut bytes: Vec<Option<u8>> =
+                            prefixes(bits, 0, &rm, bitness)?.into_iter().map(Some).collect();
+                        bytes.push(Some(op));
+                        bytes.extend(immediate(value, 4));
+                        encodings.push(bytes);
+                    }
+                }
+            }
+        }
+        _ => return Err(unsupported()),
+    }
+
+    if encodings.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(encodings)
+}
+
+/// An instruction to search for, see the module documentation for the grammar
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodePattern {
+    pub mnemonic: String,
+    /// 32 or 64, the code the target runs
+    pub bitness: u32,
+    /// Every encoding of the instruction, `None` bytes match anything
+    pub encodings: Vec<Vec<Option<u8>>>,
+}
+
+impl CodePattern {
+    pub fn parse(text: &str, bitness: u32) -> Result<Self, CodeScanError> {
+        let text = text.trim().to_lowercase();
+        let (mnemonic, operands) = text
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| CodeScanError::Syntax(String::from("expected two operands")))?;
+        let operands: Vec<&str> = operands.split(',').map(str::trim).collect();
+        let [dst, src] = operands[..] else {
+            return Err(CodeScanError::Syntax(String::from("expected two operands")));
+        };
+
+        let encodings = encode(mnemonic, parse_operand(dst)?, parse_operand(src)?, bitness)?;
+        Ok(CodePattern {
+            mnemonic: mnemonic.to_string(),
+            bitness,
+            encodings,
+        })
+    }
+
+    fn max_len(&self) -> usize {
+        self.encodings.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Whether `code` holds exactly one instruction with the pattern's mnemonic, returns
+    /// it formatted
+    fn check(&self, code: &[u8], address: u64) -> Option<String> {
+        let mut decoder = Decoder::with_ip(self.bitness, code, address, DecoderOptions::NONE);
+        let instruction = decoder.decode();
+        let mnemonic = format!("{:?}", instruction.mnemonic());
+        if instruction.is_invalid()
+            || instruction.len() != code.len()
+            || !mnemonic.eq_ignore_ascii_case(&self.mnemonic)
+        {
+            return None;
+        }
+
+        let mut text = String::new();
+        IntelFormatter::new().format(&instruction, &mut text);
+        Some(text)
+    }
+}
+
+/// Format an encoding as hex bytes, with `??` for wildcards
+pub fn format_pattern(bytes: &[Option<u8>]) -> String {
+    bytes
+        .iter()
+        .map(|b| b.map_or(String::from("??"), |b| format!("{b:02X}")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An instruction found by [`find_code`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeMatch {
+    pub address: u64,
+    pub text: String,
+}
+
+/// Find every instruction in `regions` matching `pattern`, sorted by address. Byte
+/// matches that don't decode to a single instruction with the pattern's mnemonic are
+/// dropped. Blocks that can't be read are skipped, and once `stop` is raised no further
+/// blocks are read.
+pub fn find_code(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    pattern: &CodePattern,
+    stop: &AtomicBool,
+) -> Result<Vec<CodeMatch>, MemoryError> {
+    let overlap = pattern.max_len().saturating_sub(1);
+    if pattern.encodings.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut blocks = vec![];
+    for region in regions {
+        // Validate region with a single byte read to catch ProcessAttach errors early
+        if let Err(e @ MemoryError::ProcessAttach(_)) = source.read(region.start as usize, 1) {
+            return Err(e);
+        }
+
+        let mut address = region.start;
+        while address < region.end {
+            let len = std::cmp::min(BLOCK_SIZE as u64, region.end - address);
+            blocks.push((address, len as usize));
+            if address + len == region.end {
+                break;
+            }
+            address += len - overlap as u64;
+        }
+    }
+
+    let mut matches: Vec<CodeMatch> = blocks
+        .par_iter()
+        .filter_map(|&(address, len)| {
+            if stop.load(Ordering::SeqCst) {
+                return None;
+            }
+            source.read(address as usize, len).ok().map(|b| (address, b))
+        })
+        .flat_map_iter(|(address, block)| {
+            let mut found = vec![];
+            for encoding in &pattern.encodings {
+                // encodings start with the fixed prefix and opcode bytes
+                let prefix: Vec<u8> = encoding.iter().map_while(|b| *b).collect();
+                for i in memmem::find_iter(&block, &prefix) {
+                    let Some(code) = block.get(i..i + encoding.len()) else {
+                        continue;
+                    };
+                    let fits = code.iter().zip(encoding).all(|(b, p)| p.is_none_or(|p| p == *b));
+                    let address = address + i as u64;
+                    if let Some(text) = fits.then(|| pattern.check(code, address)).flatten() {
+                        found.push(CodeMatch { address, text });
+                    }
+                }
+            }
+            found
+        })
+        .collect();
+    // blocks overlap by the longest encoding
+    matches.sort_by_key(|m| m.address);
+    matches.dedup_by_key(|m| m.address);
+
+    Ok(matches)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn encodings(text: &str, bitness: u32) -> Vec<String> {
+        CodePattern::parse(text, bitness)
+            .unwrap()
+            .encodings
+            .iter()
+            .map(|e| format_pattern(e))
+            .collect()
+    }
+
+    #[test]
+    fn test_code_pattern_encodings() {
+        assert_eq!(encodings("mov eax, [ecx+0x10]", 64), vec!["67 8B 41 10"]);
+        assert_eq!(encodings("MOV EAX, dword ptr [ECX + 0x10]", 32), vec!["8B 41 10"]);
+        assert_eq!(encodings("mov rax, qword ptr [rsp+8]", 64), vec!["48 8B 44 24 08"]);
+        assert_eq!(encodings("mov [r12], r9", 64), vec!["4D 89 0C 24"]);
+        assert_eq!(encodings("add dword ptr [rbp-4], 1", 64), vec!["83 45 FC 01"]);
+        assert_eq!(encodings("lea rdx, [rbp]", 64), vec!["48 8D 55 00"]);
+        assert_eq!(encodings("mov eax, ecx", 64), vec!["89 C8", "8B C1"]);
+        assert_eq!(encodings("mov ecx, -1", 64), vec!["B9 FF FF FF FF"]);
+        assert_eq!(
+            encodings("cmp eax, ?", 64),
+            vec!["83 F8 ??", "81 F8 ?? ?? ?? ??", "3D ?? ?? ?? ??"]
+        );
+        assert_eq!(
+            encodings("mov eax, [rcx+?]", 64),
+            vec!["8B 41 ??", "8B 81 ?? ?? ?? ??"]
+        );
+
+        // every concrete encoding decodes back to the instruction
+        for (text, expected) in [
+            ("mov eax, [ecx+0x10]", "mov eax,[ecx+10h]"),
+            ("sub qword ptr [r13+0x1000], 0x200", "sub qword ptr [r13+1000h],200h"),
+            ("xor r10d, r11d", "xor r10d,r11d"),
+            ("test [rsp], eax", "test [rsp],eax"),
+        ] {
+            let pattern = CodePattern::parse(text, 64).unwrap();
+            let code: Vec<u8> = pattern.encodings[0].iter().map(|b| b.unwrap()).collect();
+            assert_eq!(pattern.check(&code, 0).as_deref(), Some(expected), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_code_pattern_errors() {
+        let err = |text: &str, bitness: u32| CodePattern::parse(text, bitness).unwrap_err();
+        assert_eq!(err("push rax, rbx", 64), CodeScanError::UnknownMnemonic(String::from("push")));
+        assert_eq!(err("mov eax, [foo]", 64), CodeScanError::UnknownRegister(String::from("foo")));
+        assert!(matches!(err("mov eax", 64), CodeScanError::Syntax(_)));
+        assert!(matches!(err("mov eax, rcx", 64), CodeScanError::UnsupportedForm(_)));
+        assert!(matches!(err("lea [rax], eax", 64), CodeScanError::UnsupportedForm(_)));
+        assert!(matches!(err("add [rax], 1", 64), CodeScanError::UnsupportedForm(_)));
+        assert!(matches!(err("mov rax, [eax]", 32), CodeScanError::UnsupportedForm(_)));
+        assert_eq!(err("add rax, 0x100000000", 64), CodeScanError::ValueTooLarge(0x1_0000_0000));
+    }
+
+    #[test]
+    fn test_find_code() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        // nop; mov eax,[rcx+10h]; nop; mov eax,[rcx+1000h]; mov ecx,[rcx+8]
+        let code = [
+            0x90, 0x8b, 0x41, 0x10, 0x90, 0x8b, 0x81, 0x00, 0x10, 0x00, 0x00, 0x8b, 0x49, 0x08,
+        ];
+        memory.add_region(0x1000, code.to_vec(), vec![MemoryRegionPerms::Execute]);
+        let regions = memory.regions(None, None, Some(&[MemoryRegionPerms::Execute])).unwrap();
+
+        let stop = AtomicBool::new(false);
+        let pattern = CodePattern::parse("mov eax, [rcx+?]", 64).unwrap();
+        let matches = find_code(&memory, &regions, &pattern, &stop).unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                CodeMatch { address: 0x1001, text: String::from("mov eax,[rcx+10h]") },
+                CodeMatch { address: 0x1005, text: String::from("mov eax,[rcx+1000h]") },
+            ]
+        );
+
+        let pattern = CodePattern::parse("mov eax, [rcx+0x1000]", 64).unwrap();
+        let matches = find_code(&memory, &regions, &pattern, &stop).unwrap();
+        assert_eq!(matches.iter().map(|m| m.address).collect::<Vec<_>>(), vec![0x1005]);
+
+        stop.store(true, Ordering::SeqCst);
+        let pattern = CodePattern::parse("mov eax, [rcx+?]", 64).unwrap();
+        assert!(find_code(&memory, &regions, &pattern, &stop).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! Settings file of the CLI and the TUI, JSON at `$CHEAT_ENGINE_RS_CONFIG`, else
+//! `$XDG_CONFIG_HOME/cheat-engine-rs/config.json` (`~/.config/...` without it). A missing
+//! file leaves every setting at its default.
+//!
+//! ```text
+//! { "read_size": { "string": 128, "hex": 64 } }
+//! ```
+//!
+//! `read_size` is the number of bytes `read` (and the other commands that read one value)
+//! take for a type when no `--size` is given, for the types without a fixed size. An
+//! explicit `--size` wins over it, and it wins over the built-in 32 bytes.
+//! Other keys are skipped.
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::core::json::{Json, parse_json};
+use crate::core::proc::process_is_64bit;
+use crate::core::scan::ValueType;
+
+/// Types `read_size` can be set for, the others have a fixed size
+const SIZED_TYPES: [ValueType; 2] = [ValueType::String, ValueType::Hex];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// Default read size by type name
+    read_sizes: HashMap<&'static str, usize>,
+}
+
+impl Config {
+    /// Parse the text of a settings file, see the module docs for the format
+    pub fn parse(text: &str) -> Result<Config, Box<dyn Error>> {
+        let value = parse_json(text)?;
+        if !matches!(value, Json::Object(_)) {
+            return Err("expected an object of settings".into());
+        }
+        let mut config = Config::default();
+        match value.field("read_size") {
+            None => {}
+            Some(Json::Object(sizes)) => {
+                for (name, size) in sizes {
+                    let value_type = SIZED_TYPES.iter().find(|t| t.name() == name).ok_or_else(|| {
+                        format!("read_size.{name}: only string and hex have a configurable size")
+                    })?;
+                    let size = size
+                        .integer()
+                        .filter(|size| *size > 0)
+                        .ok_or_else(|| format!("read_size.{name} must be a positive byte count"))?;
+                    config.read_sizes.insert(value_type.name(), size as usize);
+                }
+            }
+            Some(_) => return Err("\"read_size\" must be an object of type names and sizes".into()),
+        }
+        Ok(config)
+    }
+
+    /// Read size set for `value_type`, `None` to use the built-in one
+    pub fn read_size(&self, value_type: ValueType) -> Option<usize> {
+        self.read_sizes.get(value_type.name()).copied()
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Where the settings file is looked for, see the module docs
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("CHEAT_ENGINE_RS_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("cheat-engine-rs").join("config.json"))
+}
+
+/// Read the settings file for the rest of the run. A missing file is no error, one that
+/// can't be parsed is.
+pub fn load() -> Result<(), Box<dyn Error>> {
+    let config = match config_path() {
+        Some(path) => match std::fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(format!("{}: {e}", path.display()).into()),
+        },
+        None => Config::default(),
+    };
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// The settings loaded by [`load`], the defaults before that
+pub fn current() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Bytes to read for a value of `value_type`: an explicit `--size`, else the size `config`
+/// sets for the type, else the built-in one, see [`default_read_size`]
+pub fn resolve_read_size(
+    size: Option<usize>,
+    value_type: ValueType,
+    pid: Option<u32>,
+    config: &Config,
+) -> usize {
+    size.unwrap_or_else(|| default_read_size(value_type, pid, config))
+}
+
+/// Bytes to read for a value of `value_type` when no `--size` is given: the size `config`
+/// sets for the type, else the built-in one. Pointers use the width of process `pid` when
+/// it can be detected
+fn default_read_size(value_type: ValueType, pid: Option<u32>, config: &Config) -> usize {
+    if let Some(size) = config.read_size(value_type) {
+        return size;
+    }
+    match value_type {
+        ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+        ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+        ValueType::Pointer => match pid {
+            Some(pid) if !process_is_64bit(pid) => 4,
+            Some(_) => 8,
+            None => size_of::<usize>(),
+        },
+        ValueType::Bool => 1,
+        ValueType::String | ValueType::Hex => 32,
+        ValueType::Raw(size) => size,
+        ValueType::Custom(_) => match value_type.get_size() {
+            0 => 32,
+            size => size as usize,
+        },
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_config_read_sizes() {
+        let config = Config::parse(r#"{"read_size": {"string": 128, "hex": "0x40"}, "later": true}"#).unwrap();
+        assert_eq!(config.read_size(ValueType::String), Some(128));
+        assert_eq!(config.read_size(ValueType::Hex), Some(64));
+        assert_eq!(config.read_size(ValueType::U32), None);
+        assert_eq!(Config::parse("{}").unwrap(), Config::default());
+
+        for text in [
+            r#"{"read_size": {"u32": 8}}"#,
+            r#"{"read_size": {"string": 0}}"#,
+            r#"{"read_size": [128]}"#,
+            "[]",
+            "{",
+        ] {
+            assert!(Config::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_read_size_resolution_order() {
+        let builtin = Config::default();
+        let config = Config::parse(r#"{"read_size": {"string": 128}}"#).unwrap();
+        assert_eq!(default_read_size(ValueType::String, None, &builtin), 32);
+        assert_eq!(default_read_size(ValueType::String, None, &config), 128);
+        // types the config doesn't set and fixed size types keep the built-in size
+        assert_eq!(default_read_size(ValueType::Hex, None, &config), 32);
+        assert_eq!(default_read_size(ValueType::U64, None, &config), 8);
+        // an explicit --size wins over both
+        assert_eq!(resolve_read_size(Some(16), ValueType::String, None, &config), 16);
+        assert_eq!(resolve_read_size(Some(2), ValueType::U64, None, &config), 2);
+        assert_eq!(resolve_read_size(None, ValueType::String, None, &config), 128);
+    }
+}
@@ -0,0 +1,553 @@
+//! Hardware watchpoints using the x86-64 debug registers, set through ptrace (Linux only).
+//!
+//! The CPU has four address registers (DR0-DR3), each watching one naturally aligned
+//! 1, 2, 4 or 8 byte range, so at most four watchpoints can be active per thread and a
+//! longer range has to be split across registers. A [`Watchpoint`] uses DR0 of every
+//! thread of the target. Attaching needs `CAP_SYS_PTRACE` (root) or a permissive
+//! `/proc/sys/kernel/yama/ptrace_scope` (0 allows any process of the same user, 1 only
+//! descendants, 2 only admins, 3 disables attaching completely).
+use std::fmt::Display;
+use std::sync::atomic::AtomicBool;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchCondition {
+    /// Trap when the instruction at the address is executed
+    Execute,
+    /// Trap on writes
+    Write,
+    /// Trap on reads and writes
+    Access,
+}
+
+impl WatchCondition {
+    fn rw_bits(&self) -> u64 {
+        match self {
+            WatchCondition::Execute => 0b00,
+            WatchCondition::Write => 0b01,
+            WatchCondition::Access => 0b11,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugError {
+    // only constructed on platforms without watchpoint support
+    #[allow(dead_code)]
+    Unsupported,
+    InvalidLength(usize),
+    Unaligned(u64, usize),
+    PermissionDenied(Option<u32>),
+    Ptrace(&'static str, i32),
+    ProcessExited,
+}
+
+impl Display for DebugError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => {
+                write!(f, "Hardware watchpoints are only supported on Linux x86-64")
+            }
+            Self::InvalidLength(len) => write!(
+                f,
+                "Invalid watch length {len}: must be 1, 2, 4 or 8 bytes (1 for execute)"
+            ),
+            Self::Unaligned(addr, len) => {
+                write!(f, "Address 0x{addr:x} is not aligned to the watch length {len}")
+            }
+            Self::PermissionDenied(Some(scope)) => write!(
+                f,
+                "Permission denied attaching to process (ptrace_scope is {scope}, run as root or lower /proc/sys/kernel/yama/ptrace_scope)"
+            ),
+            Self::PermissionDenied(None) => {
+                write!(f, "Permission denied attaching to process, run as root")
+            }
+            Self::Ptrace(op, code) => write!(f, "{op} failed: OS Error ({code})"),
+            Self::ProcessExited => write!(f, "Process has exited"),
+        }
+    }
+}
+
+impl std::error::Error for DebugError {}
+
+/// A watchpoint trap: the thread that hit it and its instruction pointer. Data
+/// watchpoints trap after the access, so `rip` points at the instruction following the
+/// one that read or wrote the address.
+#[derive(Debug, Clone)]
+pub struct WatchHit {
+    pub tid: i32,
+    pub rip: u64,
+}
+
+/// DR7 value enabling debug register `slot` for `len` bytes under `condition`
+pub fn dr7_bits(
+    slot: usize,
+    address: u64,
+    len: usize,
+    condition: WatchCondition,
+) -> Result<u64, DebugError> {
+    let len_bits: u64 = match (condition, len) {
+        (WatchCondition::Execute, 1) => 0b00,
+        (WatchCondition::Execute, _) => return Err(DebugError::InvalidLength(len)),
+        (_, 1) => 0b00,
+        (_, 2) => 0b01,
+        (_, 4) => 0b11,
+        (_, 8) => 0b10,
+        _ => return Err(DebugError::InvalidLength(len)),
+    };
+    if !address.is_multiple_of(len as u64) {
+        return Err(DebugError::Unaligned(address, len));
+    }
+
+    let local_enable = 1 << (slot * 2);
+    let control = (condition.rw_bits() | (len_bits << 2)) << (16 + slot * 4);
+    Ok(local_enable | control)
+}
+
+/// Read the yama ptrace_scope setting, if the kernel has it
+pub fn ptrace_scope() -> Option<u32> {
+    std::fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catch Ctrl+C so long running commands can stop cleanly, e.g. a watch loop detaches
+/// instead of leaving debug registers set in the target and a scan keeps its partial
+/// results. Returns the flag that is raised on SIGINT. A second Ctrl+C while the flag is
+/// still raised exits immediately.
+pub fn interrupt_flag() -> &'static AtomicBool {
+    #[cfg(target_os = "linux")]
+    {
+        extern "C" fn on_sigint(_: i32) {
+            if INTERRUPTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                // only async-signal-safe calls in here
+                crate::core::proc::resume_paused_on_exit();
+                unsafe { libc::_exit(130) };
+            }
+        }
+        unsafe {
+            libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t);
+        }
+    }
+    &INTERRUPTED
+}
+
+// ptrace helpers, shared with `core::inject`
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub(crate) mod linux {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    const DR_SLOT: usize = 0;
+
+    pub(crate) fn errno() -> i32 {
+        std::io::Error::last_os_error().raw_os_error().unwrap_or(-1)
+    }
+
+    fn debugreg_offset(index: usize) -> usize {
+        std::mem::offset_of!(libc::user, u_debugreg) + index * std::mem::size_of::<u64>()
+    }
+
+    pub(crate) fn ptrace(
+        op: &'static str,
+        request: libc::c_uint,
+        tid: i32,
+        addr: usize,
+        data: usize,
+    ) -> Result<libc::c_long, DebugError> {
+        let ret = unsafe {
+            libc::ptrace(
+                request,
+                tid,
+                addr as *mut libc::c_void,
+                data as *mut libc::c_void,
+            )
+        };
+        if ret == -1 {
+            let code = errno();
+            return Err(match code {
+                libc::EPERM => DebugError::PermissionDenied(ptrace_scope()),
+                libc::ESRCH => DebugError::ProcessExited,
+                _ => DebugError::Ptrace(op, code),
+            });
+        }
+        Ok(ret)
+    }
+
+    fn peek_debugreg(tid: i32, index: usize) -> Result<u64, DebugError> {
+        unsafe { *libc::__errno_location() = 0 };
+        let value = unsafe {
+            libc::ptrace(
+                libc::PTRACE_PEEKUSER,
+                tid,
+                debugreg_offset(index) as *mut libc::c_void,
+                std::ptr::null_mut::<libc::c_void>(),
+            )
+        };
+        if value == -1 && errno() != 0 {
+            return Err(DebugError::Ptrace("PTRACE_PEEKUSER", errno()));
+        }
+        Ok(value as u64)
+    }
+
+    fn poke_debugreg(tid: i32, index: usize, value: u64) -> Result<(), DebugError> {
+        ptrace(
+            "PTRACE_POKEUSER",
+            libc::PTRACE_POKEUSER,
+            tid,
+            debugreg_offset(index),
+            value as usize,
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_regs(tid: i32) -> Result<libc::user_regs_struct, DebugError> {
+        let mut regs: libc::user_regs_struct = unsafe { std::mem::zeroed() };
+        ptrace(
+            "PTRACE_GETREGS",
+            libc::PTRACE_GETREGS,
+            tid,
+            0,
+            &mut regs as *mut _ as usize,
+        )?;
+        Ok(regs)
+    }
+
+    pub(crate) fn set_regs(tid: i32, regs: &libc::user_regs_struct) -> Result<(), DebugError> {
+        ptrace(
+            "PTRACE_SETREGS",
+            libc::PTRACE_SETREGS,
+            tid,
+            0,
+            regs as *const _ as usize,
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn cont(tid: i32, sig: i32) -> Result<(), DebugError> {
+        ptrace("PTRACE_CONT", libc::PTRACE_CONT, tid, 0, sig as usize)?;
+        Ok(())
+    }
+
+    /// Wait until `tid` is in a ptrace stop and return its stop signal and ptrace event
+    pub(crate) fn wait_for_stop(tid: i32) -> Result<(i32, i32), DebugError> {
+        let mut status = 0;
+        loop {
+            let ret = unsafe { libc::waitpid(tid, &mut status, libc::__WALL) };
+            if ret == -1 {
+                if errno() == libc::EINTR {
+                    continue;
+                }
+                return Err(DebugError::ProcessExited);
+            }
+            if libc::WIFEXITED(status) || libc::WIFSIGNALED(status) {
+                return Err(DebugError::ProcessExited);
+            }
+            if libc::WIFSTOPPED(status) {
+                return Ok((libc::WSTOPSIG(status), status >> 16));
+            }
+        }
+    }
+
+    /// Wait until `tid` is in a ptrace stop. Returns the signal to deliver when it is
+    /// continued, so signals arriving while we attach/detach aren't swallowed.
+    pub(crate) fn wait_stopped(tid: i32) -> Result<i32, DebugError> {
+        let (sig, event) = wait_for_stop(tid)?;
+        Ok(if event == 0 && sig != libc::SIGTRAP { sig } else { 0 })
+    }
+
+    /// A watchpoint in DR0 of every thread of a process. The process keeps running while
+    /// it's attached; [`Watchpoint::watch`] reports hits. Dropping it clears the debug
+    /// registers and detaches.
+    #[derive(Debug)]
+    pub struct Watchpoint {
+        pid: i32,
+        address: u64,
+        dr7: u64,
+        threads: Vec<i32>,
+        stopped: Vec<i32>,
+    }
+
+    impl Watchpoint {
+        pub fn attach(
+            pid: u32,
+            address: u64,
+            len: usize,
+            condition: WatchCondition,
+        ) -> Result<Self, DebugError> {
+            let dr7 = dr7_bits(DR_SLOT, address, len, condition)?;
+            let tids: Vec<i32> =
+                crate::core::proc::threads(pid).iter().map(|t| t.tid as i32).collect();
+            if tids.is_empty() {
+                return Err(DebugError::ProcessExited);
+            }
+            let pid = pid as i32;
+
+            let mut watchpoint = Watchpoint {
+                pid,
+                address,
+                dr7,
+                threads: vec![],
+                stopped: vec![],
+            };
+
+            for tid in tids {
+                // SEIZE + INTERRUPT instead of ATTACH, so no SIGSTOP is left pending on
+                // detach. Threads the process creates later are traced automatically.
+                ptrace(
+                    "PTRACE_SEIZE",
+                    libc::PTRACE_SEIZE,
+                    tid,
+                    0,
+                    libc::PTRACE_O_TRACECLONE as usize,
+                )?;
+                watchpoint.threads.push(tid);
+                ptrace("PTRACE_INTERRUPT", libc::PTRACE_INTERRUPT, tid, 0, 0)?;
+                let sig = wait_stopped(tid)?;
+                watchpoint.arm(tid)?;
+                cont(tid, sig)?;
+            }
+
+            Ok(watchpoint)
+        }
+
+        fn arm(&self, tid: i32) -> Result<(), DebugError> {
+            poke_debugreg(tid, DR_SLOT, self.address)?;
+            poke_debugreg(tid, 7, self.dr7)
+        }
+
+        fn rip(tid: i32) -> Result<u64, DebugError> {
+            Ok(get_regs(tid)?.rip)
+        }
+
+        /// Resume stopped threads and report every watchpoint hit to `on_hit` until it
+        /// returns `false`, `stop` is raised or the process exits. Signals unrelated to
+        /// the watchpoint are passed on to the process.
+        pub fn watch<F>(&mut self, stop: &AtomicBool, mut on_hit: F) -> Result<(), DebugError>
+        where
+            F: FnMut(&WatchHit) -> bool,
+        {
+            for tid in std::mem::take(&mut self.stopped) {
+                cont(tid, 0)?;
+            }
+
+            let mut status = 0;
+            loop {
+                if stop.load(Ordering::SeqCst) {
+                    return Ok(());
+                }
+
+                let tid = unsafe { libc::waitpid(-1, &mut status, libc::__WALL | libc::WNOHANG) };
+                if tid == 0 {
+                    std::thread::sleep(Duration::from_micros(200));
+                    continue;
+                }
+                if tid == -1 {
+                    match errno() {
+                        libc::EINTR => continue,
+                        libc::ECHILD => return Err(DebugError::ProcessExited),
+                        code => return Err(DebugError::Ptrace("waitpid", code)),
+                    }
+                }
+
+                if libc::WIFEXITED(status) || libc::WIFSIGNALED(status) {
+                    self.threads.retain(|t| *t != tid);
+                    if tid == self.pid || self.threads.is_empty() {
+                        self.threads.clear();
+                        return Err(DebugError::ProcessExited);
+                    }
+                    continue;
+                }
+                if !libc::WIFSTOPPED(status) {
+                    continue;
+                }
+
+                let sig = libc::WSTOPSIG(status);
+                let event = status >> 16;
+
+                // the first stop of a thread we haven't seen is a newly cloned thread
+                if !self.threads.contains(&tid) {
+                    self.threads.push(tid);
+                    self.arm(tid)?;
+                    cont(tid, 0)?;
+                    continue;
+                }
+
+                match (sig, event) {
+                    (libc::SIGTRAP, 0) => {
+                        let dr6 = peek_debugreg(tid, 6)?;
+                        if dr6 & (1 << DR_SLOT) == 0 {
+                            // someone else's trap (e.g. int3), deliver it
+                            cont(tid, sig)?;
+                            continue;
+                        }
+
+                        poke_debugreg(tid, 6, 0)?;
+                        let hit = WatchHit {
+                            tid,
+                            rip: Self::rip(tid)?,
+                        };
+                        if !on_hit(&hit) {
+                            self.stopped.push(tid);
+                            return Ok(());
+                        }
+                        cont(tid, 0)?;
+                    }
+                    // clone events and group/interrupt stops, nothing to deliver
+                    (_, e) if e != 0 => cont(tid, 0)?,
+                    _ => cont(tid, sig)?,
+                }
+            }
+        }
+
+        /// Clear the debug registers of all threads and detach. Called on drop.
+        pub fn detach(&mut self) {
+            for tid in std::mem::take(&mut self.threads) {
+                let sig = if let Some(pos) = self.stopped.iter().position(|t| *t == tid) {
+                    self.stopped.remove(pos);
+                    0
+                } else {
+                    if ptrace("PTRACE_INTERRUPT", libc::PTRACE_INTERRUPT, tid, 0, 0).is_err() {
+                        continue;
+                    }
+                    match wait_stopped(tid) {
+                        Ok(sig) => sig,
+                        Err(_) => continue,
+                    }
+                };
+
+                let _ = poke_debugreg(tid, 7, 0);
+                let _ = poke_debugreg(tid, 6, 0);
+                let _ = ptrace("PTRACE_DETACH", libc::PTRACE_DETACH, tid, 0, sig as usize);
+            }
+        }
+    }
+
+    impl Drop for Watchpoint {
+        fn drop(&mut self) {
+            self.detach();
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use linux::Watchpoint;
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+#[derive(Debug)]
+pub struct Watchpoint;
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+impl Watchpoint {
+    pub fn attach(
+        _pid: u32,
+        address: u64,
+        len: usize,
+        condition: WatchCondition,
+    ) -> Result<Self, DebugError> {
+        dr7_bits(0, address, len, condition)?;
+        Err(DebugError::Unsupported)
+    }
+
+    pub fn watch<F>(&mut self, _stop: &AtomicBool, _on_hit: F) -> Result<(), DebugError>
+    where
+        F: FnMut(&WatchHit) -> bool,
+    {
+        Err(DebugError::Unsupported)
+    }
+
+    pub fn detach(&mut self) {}
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_dr7_bits() {
+        // slot 0, write, 4 bytes: L0 | RW0=01 | LEN0=11
+        assert_eq!(
+            dr7_bits(0, 0x1000, 4, WatchCondition::Write).unwrap(),
+            1 | (0b1101 << 16)
+        );
+        // slot 1, access, 8 bytes: L1 | RW1=11 | LEN1=10
+        assert_eq!(
+            dr7_bits(1, 0x1000, 8, WatchCondition::Access).unwrap(),
+            (1 << 2) | (0b1011 << 20)
+        );
+        assert_eq!(dr7_bits(0, 0x1001, 1, WatchCondition::Execute).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_dr7_bits_invalid() {
+        assert_eq!(
+            dr7_bits(0, 0x1000, 3, WatchCondition::Write),
+            Err(DebugError::InvalidLength(3))
+        );
+        assert_eq!(
+            dr7_bits(0, 0x1000, 4, WatchCondition::Execute),
+            Err(DebugError::InvalidLength(4))
+        );
+        assert_eq!(
+            dr7_bits(0, 0x1002, 4, WatchCondition::Write),
+            Err(DebugError::Unaligned(0x1002, 4))
+        );
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    pub fn test_watchpoint_write_hit_and_detach() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_counter")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let mut stdin = proc.0.stdin.take().unwrap();
+        let mut reader = BufReader::new(proc.0.stdout.take().unwrap());
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let address = line
+            .split("0x")
+            .nth(1)
+            .and_then(|s| s.split(',').next())
+            .map(|s| u64::from_str_radix(s, 16).unwrap())
+            .unwrap();
+        reader.read_line(&mut line).unwrap();
+
+        let mut watchpoint =
+            Watchpoint::attach(proc.0.id(), address, 4, WatchCondition::Write).unwrap();
+        writeln!(stdin, "i").unwrap();
+
+        let mut hits = vec![];
+        watchpoint
+            .watch(&AtomicBool::new(false), |hit| {
+                hits.push(hit.clone());
+                false
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].tid, proc.0.id() as i32);
+        assert_ne!(hits[0].rip, 0);
+
+        // after detaching the process keeps running normally
+        drop(watchpoint);
+        writeln!(stdin, "i").unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), "4");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), "5");
+    }
+}
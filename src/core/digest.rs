@@ -0,0 +1,83 @@
+//! Checksums of memory contents, to tell whether a region changed or matches a known
+//! build.
+
+/// Digest algorithms [`Hasher`] implements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgo {
+    /// CRC-32 (IEEE, as used by zip and gzip), quick but not collision resistant
+    Crc32,
+    Sha256,
+}
+
+#[derive(Debug, Clone)]
+pub enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Sha256(sha2::Sha256),
+}
+
+impl Hasher {
+    pub fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            HashAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::default()),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+
+        match self {
+            Hasher::Crc32(crc) => crc.update(data),
+            Hasher::Sha256(sha) => sha.update(data),
+        }
+    }
+
+    /// The digest, big endian as digests are usually written
+    pub fn finish(self) -> Vec<u8> {
+        use sha2::Digest;
+
+        match self {
+            Hasher::Crc32(crc) => crc.finalize().to_be_bytes().to_vec(),
+            Hasher::Sha256(sha) => sha.finalize().to_vec(),
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_known_digests() {
+        let digest = |algo, data: &[u8]| {
+            let mut hasher = Hasher::new(algo);
+            hasher.update(data);
+            hex::encode(hasher.finish())
+        };
+
+        assert_eq!(digest(HashAlgo::Crc32, b""), "00000000");
+        assert_eq!(digest(HashAlgo::Crc32, b"123456789"), "cbf43926");
+        assert_eq!(
+            digest(HashAlgo::Sha256, b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            digest(HashAlgo::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        // fed in uneven pieces, spanning the 56 byte padding boundary
+        let data: Vec<u8> = (0..200_u8).collect();
+        for algo in [HashAlgo::Crc32, HashAlgo::Sha256] {
+            let mut hasher = Hasher::new(algo);
+            for piece in data.chunks(23) {
+                hasher.update(piece);
+            }
+            assert_eq!(hex::encode(hasher.finish()), digest(algo, &data));
+        }
+        assert_eq!(
+            digest(HashAlgo::Sha256, &[b'a'; 56]),
+            "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"
+        );
+    }
+}
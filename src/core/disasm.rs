@@ -0,0 +1,112 @@
+use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter};
+use std::fmt::Display;
+
+/// Longest possible x86 instruction, reads for decoding should include this much slack
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisasmError {
+    InvalidInstruction(u64),
+    SplitsInstruction { end: u64, next_boundary: u64 },
+}
+
+impl Display for DisasmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidInstruction(addr) => write!(f, "Invalid instruction at 0x{addr:x}"),
+            Self::SplitsInstruction { end, next_boundary } => write!(
+                f,
+                "Range ends at 0x{end:x} in the middle of an instruction, the next instruction boundary is 0x{next_boundary:x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DisasmError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedInstruction {
+    pub address: u64,
+    pub len: usize,
+    pub text: String,
+}
+
+/// Decode 64-bit x86 instructions from `code` (loaded at `address`) until `len` bytes are
+/// covered. Fails if the range doesn't end on an instruction boundary, `address` itself is
+/// assumed to be the start of an instruction.
+pub fn instructions_covering(
+    code: &[u8],
+    address: u64,
+    len: usize,
+) -> Result<Vec<DecodedInstruction>, DisasmError> {
+    let mut decoder = Decoder::with_ip(64, code, address, DecoderOptions::NONE);
+    let mut formatter = IntelFormatter::new();
+    let mut instructions = vec![];
+
+    while decoder.position() < len {
+        if !decoder.can_decode() {
+            return Err(DisasmError::InvalidInstruction(decoder.ip()));
+        }
+
+        let instruction = decoder.decode();
+        if instruction.is_invalid() {
+            return Err(DisasmError::InvalidInstruction(instruction.ip()));
+        }
+
+        let mut text = String::new();
+        formatter.format(&instruction, &mut text);
+        instructions.push(DecodedInstruction {
+            address: instruction.ip(),
+            len: instruction.len(),
+            text,
+        });
+    }
+
+    if decoder.position() != len {
+        return Err(DisasmError::SplitsInstruction {
+            end: address + len as u64,
+            next_boundary: decoder.ip(),
+        });
+    }
+
+    Ok(instructions)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    // push rbp; mov rbp, rsp; mov dword ptr [rbp-4], 1
+    #[allow(dead_code)]
+    const CODE: [u8; 11] = [
+        0x55, 0x48, 0x89, 0xe5, 0xc7, 0x45, 0xfc, 0x01, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_instructions_covering_whole_instructions() {
+        let instructions = instructions_covering(&CODE, 0x1000, 4).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].text, "push rbp");
+        assert_eq!(instructions[1].address, 0x1001);
+        assert_eq!(instructions[1].len, 3);
+        assert_eq!(instructions[1].text, "mov rbp,rsp");
+
+        assert_eq!(instructions_covering(&CODE, 0x1000, 11).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_instructions_covering_split() {
+        assert_eq!(
+            instructions_covering(&CODE, 0x1000, 2),
+            Err(DisasmError::SplitsInstruction {
+                end: 0x1002,
+                next_boundary: 0x1004
+            })
+        );
+        // truncated code can't be decoded
+        assert_eq!(
+            instructions_covering(&CODE[..6], 0x1000, 6),
+            Err(DisasmError::InvalidInstruction(0x1004))
+        );
+    }
+}
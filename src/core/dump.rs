@@ -0,0 +1,498 @@
+//! Memory dump files as a [`MemorySource`], so scans and reads can run offline.
+//!
+//! Without a map, an ELF core file is laid out by its `PT_LOAD` segments and any other
+//! file is treated as a single read/write region starting at address 0. A map file gives
+//! the layout explicitly, as JSON (see [`parse_json_map`]):
+//!
+//! ```text
+//! { "regions": [
+//!     { "start": "0x7f0000000000", "end": "0x7f0000001000", "perms": "rw-", "offset": 0 },
+//!     { "start": "0x400000", "end": "0x401000", "perms": "r-x", "offset": "0x1000" }
+//! ] }
+//! ```
+//!
+//! or as text, one region per line in the style of `/proc/<pid>/maps`, with all numbers
+//! in hex and `#` starting a comment:
+//!
+//! ```text
+//! # start-end               perms  file offset
+//! 7f0000000000-7f0000001000 rw-    0
+//! 400000-401000             r-x    1000
+//! ```
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+use crate::core::json::{Json, JsonError, parse_json};
+use crate::core::mem::{
+    DEFAULT_SEARCH_PERMS, MemoryError, MemoryRegion, MemoryRegionPerms, MemorySource,
+};
+
+#[derive(Debug)]
+pub enum DumpError {
+    Io(io::Error),
+    InvalidMap(usize, String),
+    /// A JSON map that doesn't parse
+    Json(JsonError),
+    /// A region of a JSON map, by its index, that isn't valid
+    InvalidMapEntry(usize, String),
+    InvalidElf(&'static str),
+    OutsideFile(u64, u64),
+}
+
+impl Display for DumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read dump: {e}"),
+            Self::InvalidMap(line, msg) => write!(f, "Invalid map line {line}: {msg}"),
+            Self::Json(e) => write!(f, "Invalid map: {e}"),
+            Self::InvalidMapEntry(index, msg) => write!(f, "Invalid map region #{index}: {msg}"),
+            Self::InvalidElf(msg) => write!(f, "Invalid ELF core file: {msg}"),
+            Self::OutsideFile(start, end) => write!(
+                f,
+                "Region 0x{start:x}-0x{end:x} points past the end of the dump file"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DumpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DumpError {
+    fn from(e: io::Error) -> Self {
+        DumpError::Io(e)
+    }
+}
+
+/// A region of the dump and where its bytes start in the file
+#[derive(Debug, Clone)]
+pub struct DumpRegion {
+    pub region: MemoryRegion,
+    pub offset: u64,
+}
+
+/// A dump file read on demand. Writes are refused, the file is never modified.
+#[derive(Debug)]
+pub struct DumpFile {
+    file: Mutex<File>,
+    regions: Vec<DumpRegion>,
+}
+
+impl DumpFile {
+    /// Open `path`, laid out by the map file at `map_path` if given
+    pub fn open(path: &str, map_path: Option<&str>) -> Result<Self, DumpError> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        let regions = match map_path {
+            Some(map_path) => {
+                let contents = std::fs::read_to_string(map_path)?;
+                match contents.trim_start().starts_with(['{', '[']) {
+                    true => parse_json_map(&contents)?,
+                    false => parse_map(&contents)?,
+                }
+            }
+            None => {
+                let mut magic = [0_u8; 4];
+                let is_elf = file.read_exact(&mut magic).is_ok() && &magic == b"\x7fELF";
+                file.seek(SeekFrom::Start(0))?;
+                if is_elf {
+                    elf_load_segments(&mut file)?
+                } else {
+                    vec![DumpRegion {
+                        region: MemoryRegion {
+                            start: 0,
+                            end: len,
+                            perms: vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write],
+                        },
+                        offset: 0,
+                    }]
+                }
+            }
+        };
+
+        Self::with_regions(file, len, regions)
+    }
+
+    fn with_regions(file: File, len: u64, mut regions: Vec<DumpRegion>) -> Result<Self, DumpError> {
+        for r in &regions {
+            let size = r.region.end.checked_sub(r.region.start);
+            if size.and_then(|size| r.offset.checked_add(size)).is_none_or(|end| end > len) {
+                return Err(DumpError::OutsideFile(r.region.start, r.region.end));
+            }
+        }
+        regions.sort_by_key(|r| r.region.start);
+
+        Ok(DumpFile {
+            file: Mutex::new(file),
+            regions,
+        })
+    }
+}
+
+impl MemorySource for DumpFile {
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+        let addr = addr as u64;
+        let end = addr.checked_add(size as u64).ok_or(MemoryError::MemRead(0))?;
+        let r = self
+            .regions
+            .iter()
+            .find(|r| r.region.start <= addr && end <= r.region.end)
+            .ok_or(MemoryError::MemRead(0))?;
+
+        let os_error = |e: io::Error| MemoryError::MemRead(e.raw_os_error().unwrap_or(0));
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(r.offset + addr - r.region.start))
+            .map_err(os_error)?;
+        let mut buf = vec![0_u8; size];
+        file.read_exact(&mut buf).map_err(os_error)?;
+        Ok(buf)
+    }
+
+    fn write(&self, _addr: usize, _value: &[u8]) -> Result<(), MemoryError> {
+        Err(MemoryError::MemWrite(0))
+    }
+
+    fn regions(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        search_perms: Option<&[MemoryRegionPerms]>,
+    ) -> Result<Vec<MemoryRegion>, MemoryError> {
+        let search_perms = search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS);
+        let start = start.unwrap_or(0);
+        let end = end.unwrap_or(u64::MAX);
+        Ok(self
+            .regions
+            .iter()
+            .map(|r| &r.region)
+            .filter(|r| r.end > start && r.start <= end)
+            .filter(|r| search_perms.iter().any(|p| r.perms.contains(p)))
+            .cloned()
+            .collect())
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+/// The permissions in a `/proc/<pid>/maps` style string such as `rw-p`
+fn parse_perms(perms: &str) -> Vec<MemoryRegionPerms> {
+    let mut region_perms = Vec::with_capacity(3);
+    for (c, perm) in [
+        ('r', MemoryRegionPerms::Read),
+        ('w', MemoryRegionPerms::Write),
+        ('x', MemoryRegionPerms::Execute),
+    ] {
+        if perms.contains(c) {
+            region_perms.push(perm);
+        }
+    }
+    region_perms
+}
+
+/// Parse a JSON map, an object with a `regions` array or the bare array. Each region has
+/// a `start` and `end` address, `perms` such as `"rw-"` and the `offset` of its bytes in
+/// the dump. Numbers may be strings, which may be `0x` hex.
+pub fn parse_json_map(contents: &str) -> Result<Vec<DumpRegion>, DumpError> {
+    let map = parse_json(contents).map_err(DumpError::Json)?;
+    let items = match (&map, map.field("regions")) {
+        (Json::Array(items), _) | (_, Some(Json::Array(items))) => items,
+        _ => {
+            return Err(DumpError::Json(JsonError {
+                position: contents.len() - contents.trim_start().len(),
+                message: String::from("expected an array of regions or an object with \"regions\""),
+            }));
+        }
+    };
+
+    let mut regions = vec![];
+    for (index, item) in items.iter().enumerate() {
+        let invalid = |msg: &str| DumpError::InvalidMapEntry(index, msg.to_string());
+        let number = |key: &str| match item.field(key).map(Json::integer) {
+            Some(Some(value)) if value >= 0 => Ok(value as u64),
+            Some(_) => Err(invalid(&format!("\"{key}\" must be a positive integer"))),
+            None => Err(invalid(&format!("missing \"{key}\""))),
+        };
+        let (start, end, offset) = (number("start")?, number("end")?, number("offset")?);
+        if start >= end {
+            return Err(invalid("\"end\" should be after \"start\""));
+        }
+        let perms = match item.field("perms") {
+            Some(Json::String(perms)) => parse_perms(perms),
+            _ => return Err(invalid("missing \"perms\", e.g. \"rw-\"")),
+        };
+        regions.push(DumpRegion { region: MemoryRegion { start, end, perms }, offset });
+    }
+
+    Ok(regions)
+}
+
+/// Parse map lines of the form `<start>-<end> <perms> <file offset>`
+pub fn parse_map(contents: &str) -> Result<Vec<DumpRegion>, DumpError> {
+    let mut regions = vec![];
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |msg: &str| DumpError::InvalidMap(i + 1, msg.to_string());
+
+        let mut parts = line.split_whitespace();
+        let (range, perms, offset) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(range), Some(perms), Some(offset)) => (range, perms, offset),
+            _ => return Err(invalid("expected '<start>-<end> <perms> <offset>'")),
+        };
+        let (start, end) = range
+            .split_once('-')
+            .and_then(|(start, end)| Some((parse_hex(start)?, parse_hex(end)?)))
+            .ok_or_else(|| invalid("invalid address range"))?;
+        if start >= end {
+            return Err(invalid("range end should be after its start"));
+        }
+        let offset = parse_hex(offset).ok_or_else(|| invalid("invalid file offset"))?;
+
+        regions.push(DumpRegion {
+            region: MemoryRegion {
+                start,
+                end,
+                perms: parse_perms(perms),
+            },
+            offset,
+        });
+    }
+
+    Ok(regions)
+}
+
+/// Regions of the `PT_LOAD` segments in a 64-bit little-endian ELF file. Segments that
+/// weren't written to the file (e.g. unreadable mappings in a core dump) are skipped.
+/// Only the ELF header and the program headers are read, wherever they are in the file.
+pub fn elf_load_segments(file: &mut (impl Read + Seek)) -> Result<Vec<DumpRegion>, DumpError> {
+    const PT_LOAD: u32 = 1;
+    const PF_X: u32 = 1;
+    const PF_W: u32 = 2;
+    const PF_R: u32 = 4;
+    // the fields up to p_filesz
+    const PHDR_LEN: usize = 0x28;
+
+    // `len` bytes at `at`, `None` if the file ends before
+    let mut read_at = |at: u64, len: usize| -> Result<Option<Vec<u8>>, DumpError> {
+        file.seek(SeekFrom::Start(at))?;
+        let mut data = vec![0_u8; len];
+        match file.read_exact(&mut data) {
+            Ok(()) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    };
+    let u16_at = |data: &[u8], at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let u32_at = |data: &[u8], at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    let u64_at = |data: &[u8], at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+
+    let header = read_at(0, 0x40)?.ok_or(DumpError::InvalidElf("header is truncated"))?;
+    // EI_CLASS 2 is 64-bit, EI_DATA 1 is little-endian
+    if header[..4] != *b"\x7fELF" || header[4] != 2 || header[5] != 1 {
+        return Err(DumpError::InvalidElf("only 64-bit little-endian files are supported"));
+    }
+    let phoff = u64_at(&header, 0x20);
+    let phentsize = u16_at(&header, 0x36) as u64;
+    let phnum = u16_at(&header, 0x38) as u64;
+    if phnum > 0 && phentsize < PHDR_LEN as u64 {
+        return Err(DumpError::InvalidElf("program headers are too small"));
+    }
+
+    let mut regions = vec![];
+    for i in 0..phnum {
+        let phdr = phoff
+            .checked_add(i * phentsize)
+            .ok_or(DumpError::InvalidElf("program headers are past the end of the file"))?;
+        let phdr =
+            read_at(phdr, PHDR_LEN)?.ok_or(DumpError::InvalidElf("program headers are truncated"))?;
+        if u32_at(&phdr, 0) != PT_LOAD {
+            continue;
+        }
+        let (flags, offset, vaddr, filesz) =
+            (u32_at(&phdr, 4), u64_at(&phdr, 8), u64_at(&phdr, 16), u64_at(&phdr, 32));
+        if filesz == 0 {
+            continue;
+        }
+        let end = vaddr
+            .checked_add(filesz)
+            .ok_or(DumpError::InvalidElf("a segment ends past the address space"))?;
+
+        let mut perms = Vec::with_capacity(3);
+        for (flag, perm) in [
+            (PF_R, MemoryRegionPerms::Read),
+            (PF_W, MemoryRegionPerms::Write),
+            (PF_X, MemoryRegionPerms::Execute),
+        ] {
+            if flags & flag != 0 {
+                perms.push(perm);
+            }
+        }
+
+        regions.push(DumpRegion {
+            region: MemoryRegion {
+                start: vaddr,
+                end,
+                perms,
+            },
+            offset,
+        });
+    }
+
+    Ok(regions)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn write_temp(name: &str, data: &[u8]) -> String {
+        let path = std::env::temp_dir().join(format!("cheat-engine-rs-{}-{name}", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let regions = parse_map(
+            "# comment\n7f0000000000-7f0000001000 rw-p 0\n\n0x400000-0x401000 r-x 1000 # text\n",
+        )
+        .unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].region.start, 0x7f00_0000_0000);
+        assert_eq!(regions[0].region.perms, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        assert_eq!(regions[1].region.end, 0x401000);
+        assert_eq!(regions[1].offset, 0x1000);
+
+        assert!(matches!(parse_map("1000-2000 rw-"), Err(DumpError::InvalidMap(1, _))));
+        assert!(matches!(parse_map("\n2000-1000 rw- 0"), Err(DumpError::InvalidMap(2, _))));
+    }
+
+    #[test]
+    fn test_dump_file_scan_with_map() {
+        use crate::core::scan::{Scan, ValueType};
+        use std::sync::Arc;
+
+        let mut data = vec![0_u8; 0x40];
+        data[0x24..0x28].copy_from_slice(&1337_u32.to_le_bytes());
+        let dump = write_temp("dump.raw", &data);
+        let map = write_temp("dump.map", b"10000-10020 r-- 0\n20000-20020 rw- 20\n");
+
+        let source = DumpFile::open(&dump, Some(&map)).unwrap();
+        assert_eq!(source.read(0x20004, 4).unwrap(), 1337_u32.to_le_bytes());
+        assert!(source.read(0x1001e, 4).is_err());
+        assert!(source.write(0x20004, &[0; 4]).is_err());
+
+        let mut scan = Scan::with_source(
+            Arc::new(source),
+            1337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].address, 0x20004);
+
+        // a map pointing past the end of the file is rejected
+        let bad_map = write_temp("bad.map", b"20000-20100 rw- 20\n");
+        assert!(matches!(
+            DumpFile::open(&dump, Some(&bad_map)),
+            Err(DumpError::OutsideFile(0x20000, 0x20100))
+        ));
+
+        // the same layout as JSON
+        let json_map = write_temp(
+            "dump.json",
+            br#"{"regions": [{"start": "0x10000", "end": "0x10020", "perms": "r--", "offset": 0},
+                {"start": "0x20000", "end": "0x20020", "perms": "rw-", "offset": "0x20"}]}"#,
+        );
+        let source = DumpFile::open(&dump, Some(&json_map)).unwrap();
+        assert_eq!(source.read(0x20004, 4).unwrap(), 1337_u32.to_le_bytes());
+        assert!(source.read(usize::MAX - 1, 4).is_err());
+
+        // without a map the whole file is one region at address 0
+        let raw = DumpFile::open(&dump, None).unwrap();
+        assert_eq!(raw.read(0x24, 4).unwrap(), 1337_u32.to_le_bytes());
+
+        for path in [dump, map, bad_map, json_map] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_parse_json_map() {
+        let map = r#"[{"start": 4096, "end": "0x2000", "perms": "r-x", "offset": "0x10"}]"#;
+        let regions = parse_json_map(map).unwrap();
+        let region = &regions[0];
+        assert_eq!((region.region.start, region.region.end, region.offset), (0x1000, 0x2000, 0x10));
+        assert_eq!(region.region.perms, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Execute]);
+
+        let bad = r#"{"regions": [{"start": 0, "end": 16, "perms": "r--", "offset": 0},
+            {"start": 16}]}"#;
+        assert!(matches!(parse_json_map(bad), Err(DumpError::InvalidMapEntry(1, _))));
+        assert!(matches!(parse_json_map("{"), Err(DumpError::Json(_))));
+        assert!(matches!(parse_json_map("{}"), Err(DumpError::Json(_))));
+    }
+
+    #[test]
+    fn test_elf_load_segments() {
+        let mut elf = vec![0_u8; 0x40 + 2 * 0x38];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[0x20..0x28].copy_from_slice(&0x40_u64.to_le_bytes());
+        elf[0x36..0x38].copy_from_slice(&0x38_u16.to_le_bytes());
+        elf[0x38..0x3a].copy_from_slice(&2_u16.to_le_bytes());
+
+        // PT_NOTE, then a rw PT_LOAD at 0x7000 backed by 0x100 bytes at offset 0x1000
+        elf[0x40..0x44].copy_from_slice(&4_u32.to_le_bytes());
+        let load = 0x40 + 0x38;
+        elf[load..load + 4].copy_from_slice(&1_u32.to_le_bytes());
+        elf[load + 4..load + 8].copy_from_slice(&6_u32.to_le_bytes());
+        elf[load + 8..load + 16].copy_from_slice(&0x1000_u64.to_le_bytes());
+        elf[load + 16..load + 24].copy_from_slice(&0x7000_u64.to_le_bytes());
+        elf[load + 32..load + 40].copy_from_slice(&0x100_u64.to_le_bytes());
+
+        let regions = elf_load_segments(&mut io::Cursor::new(&elf)).unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].region.start, regions[0].region.end), (0x7000, 0x7100));
+        assert_eq!(regions[0].region.perms, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        assert_eq!(regions[0].offset, 0x1000);
+
+        assert!(elf_load_segments(&mut io::Cursor::new(&elf[..0x50])).is_err());
+        assert!(elf_load_segments(&mut io::Cursor::new(b"not an elf")).is_err());
+
+        // program headers past the first MiB are found, overflowing ones are rejected
+        let mut far = elf.clone();
+        far[0x20..0x28].copy_from_slice(&0x200000_u64.to_le_bytes());
+        far.resize(0x200000, 0);
+        far.extend_from_slice(&elf[0x40..]);
+        let regions = elf_load_segments(&mut io::Cursor::new(&far)).unwrap();
+        assert_eq!((regions[0].region.start, regions[0].region.end), (0x7000, 0x7100));
+
+        let mut wrapping = elf.clone();
+        wrapping[load + 16..load + 24].copy_from_slice(&(u64::MAX - 0x10).to_le_bytes());
+        assert!(matches!(
+            elf_load_segments(&mut io::Cursor::new(&wrapping)),
+            Err(DumpError::InvalidElf(_))
+        ));
+        wrapping[0x20..0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(elf_load_segments(&mut io::Cursor::new(&wrapping)).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! Value histograms: how often each aligned value occurs in memory, to tell padding and
+//! filler apart from real data before picking a value to scan for.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::mem::{MemoryError, MemoryRegion, MemorySource};
+
+/// Distinct values tallied by default, further new values are only counted in total
+pub const DEFAULT_MAX_DISTINCT: usize = 1 << 20;
+
+const BLOCK_SIZE: usize = 0x10000;
+
+/// Occurrences of the values in some memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Value bytes and how often they occur, most frequent first, equal counts by value
+    pub counts: Vec<(Vec<u8>, u64)>,
+    /// Every value read, tallied or not
+    pub total: u64,
+    /// Values that weren't tallied because the distinct-value limit had been reached
+    pub untracked: u64,
+}
+
+/// Tally the `value_size` byte values aligned to their size in `regions` (`value_size` is
+/// 1 to 8). At most `max_distinct` different values are tallied; occurrences of values
+/// seen only once the limit is reached are counted in [`Histogram::untracked`]. Blocks that
+/// can't be read are skipped, and once `stop` is raised no further blocks are read.
+pub fn value_histogram(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    value_size: usize,
+    max_distinct: usize,
+    stop: &AtomicBool,
+) -> Result<Histogram, MemoryError> {
+    assert!((1..=8).contains(&value_size), "values must be 1 to 8 bytes");
+
+    let mut tally: HashMap<u64, u64> = HashMap::new();
+    let (mut total, mut untracked) = (0, 0);
+    for region in regions {
+        // Validate region with a single byte read to catch ProcessAttach errors early
+        if let Err(e @ MemoryError::ProcessAttach(_)) = source.read(region.start as usize, 1) {
+            return Err(e);
+        }
+
+        let mut address = region.start.next_multiple_of(value_size as u64);
+        while address + value_size as u64 <= region.end && !stop.load(Ordering::SeqCst) {
+            let len = std::cmp::min(BLOCK_SIZE as u64, region.end - address) as usize;
+            let len = len - len % value_size;
+            if let Ok(block) = source.read(address as usize, len) {
+                for bytes in block.chunks_exact(value_size) {
+                    let mut value = [0; 8];
+                    value[..value_size].copy_from_slice(bytes);
+                    let value = u64::from_le_bytes(value);
+
+                    total += 1;
+                    let distinct = tally.len();
+                    match tally.get_mut(&value) {
+                        Some(count) => *count += 1,
+                        None if distinct < max_distinct => {
+                            tally.insert(value, 1);
+                        }
+                        None => untracked += 1,
+                    }
+                }
+            }
+            address += len as u64;
+        }
+    }
+
+    let mut counts: Vec<(u64, u64)> = tally.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(Histogram {
+        counts: counts
+            .into_iter()
+            .map(|(value, count)| (value.to_le_bytes()[..value_size].to_vec(), count))
+            .collect(),
+        total,
+        untracked,
+    })
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_value_histogram() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x20];
+        data[0x04..0x08].copy_from_slice(&7_u32.to_le_bytes());
+        data[0x0c..0x10].copy_from_slice(&7_u32.to_le_bytes());
+        data[0x14..0x18].copy_from_slice(&9_u32.to_le_bytes());
+        // not aligned, read as 0x700 and 0
+        data[0x19..0x1d].copy_from_slice(&7_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let regions = memory.regions(None, None, None).unwrap();
+        let stop = AtomicBool::new(false);
+
+        let histogram = value_histogram(&memory, &regions, 4, DEFAULT_MAX_DISTINCT, &stop).unwrap();
+        assert_eq!(histogram.total, 8);
+        assert_eq!(histogram.untracked, 0);
+        assert_eq!(histogram.counts[0], (0_u32.to_le_bytes().to_vec(), 4));
+        assert_eq!(histogram.counts[1], (7_u32.to_le_bytes().to_vec(), 2));
+        assert_eq!(histogram.counts.len(), 4);
+
+        // only 0 and 7 fit, the other values are counted as untracked
+        let histogram = value_histogram(&memory, &regions, 4, 2, &stop).unwrap();
+        assert_eq!(histogram.counts.len(), 2);
+        assert_eq!(histogram.untracked, 2);
+
+        stop.store(true, Ordering::SeqCst);
+        assert_eq!(value_histogram(&memory, &regions, 4, 2, &stop).unwrap().total, 0);
+    }
+}
@@ -0,0 +1,477 @@
+//! Shellcode injection: allocate executable memory in a process, copy code into it and
+//! start a new thread running it.
+//!
+//! On Linux x86-64 the process is stopped with ptrace and made to call `mmap` and `clone`
+//! itself, through a `syscall` instruction found in its own executable mappings. On
+//! Windows this is `VirtualAllocEx` + `CreateRemoteThread`. Nothing checks what the code
+//! does; broken shellcode takes the whole process down with it.
+use crate::core::debug::DebugError;
+use crate::core::mem::MemoryError;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InjectError {
+    // only constructed on platforms without injection support
+    #[allow(dead_code)]
+    Unsupported,
+    EmptyShellcode,
+    Debug(DebugError),
+    Memory(MemoryError),
+    /// A syscall made on behalf of the target failed with this errno
+    #[allow(dead_code)]
+    RemoteSyscall(&'static str, i64),
+    #[allow(dead_code)]
+    NoSyscallInstruction,
+    #[allow(dead_code)]
+    Os(&'static str, i32),
+}
+
+impl Display for InjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => {
+                write!(f, "Code injection is only supported on Linux x86-64 and Windows")
+            }
+            Self::EmptyShellcode => write!(f, "Shellcode is empty"),
+            Self::Debug(e) => write!(f, "{e}"),
+            Self::Memory(e) => write!(f, "{e}"),
+            Self::RemoteSyscall(name, errno) => {
+                write!(f, "{name} failed in the target process: OS Error ({errno})")
+            }
+            Self::NoSyscallInstruction => write!(
+                f,
+                "No syscall instruction found in the executable memory of the process"
+            ),
+            Self::Os(call, code) => write!(f, "{call} failed: OS Error ({code})"),
+        }
+    }
+}
+
+impl std::error::Error for InjectError {}
+
+impl From<DebugError> for InjectError {
+    fn from(e: DebugError) -> Self {
+        InjectError::Debug(e)
+    }
+}
+
+impl From<MemoryError> for InjectError {
+    fn from(e: MemoryError) -> Self {
+        InjectError::Memory(e)
+    }
+}
+
+/// Where injected code ended up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Injection {
+    /// Start and size of the allocated memory, it stays mapped after the thread finishes
+    pub base: u64,
+    pub size: usize,
+    /// Address the shellcode was copied to
+    pub entry: u64,
+    pub tid: u32,
+}
+
+/// Offset of the shellcode in the allocation, the thread start stub comes before it
+#[allow(dead_code)]
+const CODE_OFFSET: usize = 64;
+
+/// Runs `clone` in the target. The parent stops on the `int3` with the new thread id (or
+/// the error) in rax, the child calls the shellcode and exits the thread once it returns.
+#[allow(dead_code)]
+const CLONE_STUB: [u8; 22] = [
+    0x0f, 0x05, // syscall
+    0x48, 0x85, 0xc0, // test rax, rax
+    0x75, 0x0e, // jnz int3
+    0xe8, 0x34, 0x00, 0x00, 0x00, // call CODE_OFFSET
+    0xb8, 0x3c, 0x00, 0x00, 0x00, // mov eax, SYS_exit
+    0x31, 0xff, // xor edi, edi
+    0x0f, 0x05, // syscall
+    0xcc, // int3
+];
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod linux {
+    use super::*;
+    use crate::core::debug::linux::{get_regs, ptrace, set_regs, wait_for_stop};
+    use crate::core::mem::{MemoryRegionPerms, MemorySource, ProcessHandle};
+
+    const PAGE_SIZE: usize = 4096;
+    const STACK_SIZE: usize = 64 * 1024;
+    const SYSCALL: [u8; 2] = [0x0f, 0x05];
+
+    /// The stopped main thread of the target. Dropping it puts the original registers back,
+    /// so the interrupted code carries on as if nothing happened, and detaches.
+    struct Tracee {
+        pid: i32,
+        saved: libc::user_regs_struct,
+        syscall_at: u64,
+        /// Signal that arrived while the process was stopped, delivered on detach
+        pending: i32,
+    }
+
+    impl Tracee {
+        fn attach(pid: i32) -> Result<Self, InjectError> {
+            ptrace("PTRACE_SEIZE", libc::PTRACE_SEIZE, pid, 0, 0)?;
+            let stopped = ptrace("PTRACE_INTERRUPT", libc::PTRACE_INTERRUPT, pid, 0, 0)
+                .and_then(|_| wait_for_stop(pid))
+                .and_then(|(sig, event)| Ok((sig, event, get_regs(pid)?)));
+            let (sig, event, saved) = match stopped {
+                Ok(stopped) => stopped,
+                Err(e) => {
+                    let _ = ptrace("PTRACE_DETACH", libc::PTRACE_DETACH, pid, 0, 0);
+                    return Err(e.into());
+                }
+            };
+
+            let mut tracee = Tracee {
+                pid,
+                saved,
+                syscall_at: 0,
+                pending: 0,
+            };
+            tracee.note_signal(sig, event);
+            tracee.syscall_at = find_syscall(pid as u32)?;
+            Ok(tracee)
+        }
+
+        fn note_signal(&mut self, sig: i32, event: i32) {
+            if event == 0 && sig != libc::SIGTRAP {
+                self.pending = sig;
+            }
+        }
+
+        /// Continue with `resume` until the thread stops on a SIGTRAP, signals in between
+        /// are held back until detach
+        fn run_until_trap(
+            &mut self,
+            op: &'static str,
+            resume: libc::c_uint,
+        ) -> Result<(), InjectError> {
+            loop {
+                ptrace(op, resume, self.pid, 0, 0)?;
+                let (sig, event) = wait_for_stop(self.pid)?;
+                if sig == libc::SIGTRAP && event == 0 {
+                    return Ok(());
+                }
+                self.note_signal(sig, event);
+            }
+        }
+
+        /// Run syscall `nr` in the target and return its result
+        fn syscall(
+            &mut self,
+            name: &'static str,
+            nr: libc::c_long,
+            args: [u64; 6],
+        ) -> Result<u64, InjectError> {
+            let mut regs = self.saved;
+            regs.rip = self.syscall_at;
+            regs.rax = nr as u64;
+            // stops the kernel from restarting a syscall the thread was interrupted in
+            regs.orig_rax = u64::MAX;
+            regs.rdi = args[0];
+            regs.rsi = args[1];
+            regs.rdx = args[2];
+            regs.r10 = args[3];
+            regs.r8 = args[4];
+            regs.r9 = args[5];
+            set_regs(self.pid, &regs)?;
+            self.run_until_trap("PTRACE_SINGLESTEP", libc::PTRACE_SINGLESTEP)?;
+            syscall_result(name, get_regs(self.pid)?.rax)
+        }
+
+        fn munmap(&mut self, base: u64, size: usize) {
+            let _ = self.syscall("munmap", libc::SYS_munmap, [base, size as u64, 0, 0, 0, 0]);
+        }
+
+        /// Start a thread at the clone stub in `base`, running on the stack ending at
+        /// `stack_top`
+        fn clone_thread(&mut self, base: u64, stack_top: u64) -> Result<u32, InjectError> {
+            let flags = libc::CLONE_VM
+                | libc::CLONE_FS
+                | libc::CLONE_FILES
+                | libc::CLONE_SIGHAND
+                | libc::CLONE_THREAD
+                | libc::CLONE_SYSVSEM;
+
+            let mut regs = self.saved;
+            regs.rip = base;
+            regs.rax = libc::SYS_clone as u64;
+            regs.orig_rax = u64::MAX;
+            regs.rdi = flags as u64;
+            regs.rsi = stack_top;
+            regs.rdx = 0;
+            regs.r10 = 0;
+            regs.r8 = 0;
+            set_regs(self.pid, &regs)?;
+            self.run_until_trap("PTRACE_CONT", libc::PTRACE_CONT)?;
+            Ok(syscall_result("clone", get_regs(self.pid)?.rax)? as u32)
+        }
+    }
+
+    impl Drop for Tracee {
+        fn drop(&mut self) {
+            let _ = set_regs(self.pid, &self.saved);
+            let _ = ptrace(
+                "PTRACE_DETACH",
+                libc::PTRACE_DETACH,
+                self.pid,
+                0,
+                self.pending as usize,
+            );
+        }
+    }
+
+    fn syscall_result(name: &'static str, rax: u64) -> Result<u64, InjectError> {
+        let ret = rax as i64;
+        if (-4095..0).contains(&ret) {
+            return Err(InjectError::RemoteSyscall(name, -ret));
+        }
+        Ok(rax)
+    }
+
+    /// Address of a `syscall` instruction in the target's executable memory (libc has
+    /// plenty)
+    fn find_syscall(pid: u32) -> Result<u64, InjectError> {
+        let handle = ProcessHandle::open(pid)?;
+        for region in handle.regions(None, None, Some(&[MemoryRegionPerms::Execute]))? {
+            // [vsyscall] and similar mappings can't be read
+            let Ok(data) = handle.read(region.start as usize, (region.end - region.start) as usize)
+            else {
+                continue;
+            };
+            if let Some(offset) = memchr::memmem::find(&data, &SYSCALL) {
+                return Ok(region.start + offset as u64);
+            }
+        }
+        Err(InjectError::NoSyscallInstruction)
+    }
+
+    fn align_up(value: usize, align: usize) -> usize {
+        value.div_ceil(align) * align
+    }
+
+    pub fn inject(pid: u32, code: &[u8]) -> Result<Injection, InjectError> {
+        if code.is_empty() {
+            return Err(InjectError::EmptyShellcode);
+        }
+
+        let code_size = align_up(CODE_OFFSET + code.len(), PAGE_SIZE);
+        let size = code_size + STACK_SIZE;
+        let mut tracee = Tracee::attach(pid as i32)?;
+
+        let base = tracee.syscall(
+            "mmap",
+            libc::SYS_mmap,
+            [
+                0,
+                size as u64,
+                (libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC) as u64,
+                (libc::MAP_PRIVATE | libc::MAP_ANONYMOUS) as u64,
+                u64::MAX,
+                0,
+            ],
+        )?;
+
+        let mut image = vec![0xcc; CODE_OFFSET];
+        image[..CLONE_STUB.len()].copy_from_slice(&CLONE_STUB);
+        image.extend_from_slice(code);
+
+        let started = ProcessHandle::open(pid)
+            .and_then(|handle| handle.write(base as usize, &image))
+            .map_err(InjectError::from)
+            .and_then(|_| tracee.clone_thread(base, base + size as u64));
+
+        match started {
+            Ok(tid) => Ok(Injection {
+                base,
+                size,
+                entry: base + CODE_OFFSET as u64,
+                tid,
+            }),
+            Err(e) => {
+                tracee.munmap(base, size);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Run `code` in a new thread of process `pid`.
+///
+/// On Linux the code is called on its own 64 KiB stack and may `ret` to end the thread.
+/// The thread shares the TLS of the thread it was cloned from, so calling into libc from
+/// it isn't safe. On Windows it's a regular thread start routine.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub fn inject(pid: u32, code: &[u8]) -> Result<Injection, InjectError> {
+    linux::inject(pid, code)
+}
+
+/// Run `code` in a new thread of process `pid`, started with `CreateRemoteThread`.
+#[cfg(target_os = "windows")]
+pub fn inject(pid: u32, code: &[u8]) -> Result<Injection, InjectError> {
+    use crate::core::mem::{MemorySource, ProcessHandle};
+    use windows::Win32::Foundation::{CloseHandle, GetLastError};
+    use windows::Win32::System::Memory::{
+        MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READWRITE, VirtualAllocEx,
+        VirtualFreeEx,
+    };
+    use windows::Win32::System::Threading::{
+        CreateRemoteThread, GetThreadId, LPTHREAD_START_ROUTINE, OpenProcess,
+        PROCESS_CREATE_THREAD, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION,
+        PROCESS_VM_READ, PROCESS_VM_WRITE,
+    };
+
+    if code.is_empty() {
+        return Err(InjectError::EmptyShellcode);
+    }
+
+    let process = unsafe {
+        OpenProcess(
+            PROCESS_CREATE_THREAD
+                | PROCESS_QUERY_INFORMATION
+                | PROCESS_VM_OPERATION
+                | PROCESS_VM_READ
+                | PROCESS_VM_WRITE,
+            false,
+            pid,
+        )
+    }
+    .map_err(|e| InjectError::Os("OpenProcess", e.code().0))?;
+
+    let base = unsafe {
+        VirtualAllocEx(
+            process,
+            None,
+            code.len(),
+            MEM_COMMIT | MEM_RESERVE,
+            PAGE_EXECUTE_READWRITE,
+        )
+    };
+    if base.is_null() {
+        let code = unsafe { GetLastError() }.0 as i32;
+        unsafe {
+            let _ = CloseHandle(process);
+        }
+        return Err(InjectError::Os("VirtualAllocEx", code));
+    }
+
+    let started = ProcessHandle::open(pid)
+        .and_then(|handle| handle.write(base as usize, code))
+        .map_err(InjectError::from)
+        .and_then(|_| {
+            let start: LPTHREAD_START_ROUTINE = unsafe { std::mem::transmute(base) };
+            unsafe { CreateRemoteThread(process, None, 0, start, None, 0, None) }
+                .map_err(|e| InjectError::Os("CreateRemoteThread", e.code().0))
+        });
+
+    let result = match started {
+        Ok(thread) => {
+            let tid = unsafe { GetThreadId(thread) };
+            unsafe {
+                let _ = CloseHandle(thread);
+            }
+            Ok(Injection {
+                base: base as u64,
+                size: code.len(),
+                entry: base as u64,
+                tid,
+            })
+        }
+        Err(e) => {
+            unsafe {
+                let _ = VirtualFreeEx(process, base, 0, MEM_RELEASE);
+            }
+            Err(e)
+        }
+    };
+
+    unsafe {
+        let _ = CloseHandle(process);
+    }
+    result
+}
+
+#[cfg(not(any(all(target_os = "linux", target_arch = "x86_64"), target_os = "windows")))]
+pub fn inject(_pid: u32, _code: &[u8]) -> Result<Injection, InjectError> {
+    Err(InjectError::Unsupported)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_clone_stub_branches() {
+        use iced_x86::{Decoder, DecoderOptions, Mnemonic};
+
+        let mut decoder = Decoder::with_ip(64, &CLONE_STUB, 0, DecoderOptions::NONE);
+        let instructions: Vec<_> = decoder.iter().collect();
+        assert!(instructions.iter().all(|i| !i.is_invalid()));
+        assert_eq!(instructions.len(), 8);
+
+        let int3 = instructions.last().unwrap();
+        assert_eq!(int3.mnemonic(), Mnemonic::Int3);
+        assert_eq!(instructions[2].mnemonic(), Mnemonic::Jne);
+        assert_eq!(instructions[2].near_branch_target(), int3.ip());
+        assert_eq!(instructions[3].mnemonic(), Mnemonic::Call);
+        assert_eq!(instructions[3].near_branch_target(), CODE_OFFSET as u64);
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    pub fn test_inject_writes_counter() {
+        use crate::core::mem::read_memory_address;
+        use std::io::{BufRead, BufReader, Write};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_counter")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let mut stdin = proc.0.stdin.take().unwrap();
+        let mut reader = BufReader::new(proc.0.stdout.take().unwrap());
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let address = line
+            .split("0x")
+            .nth(1)
+            .and_then(|s| s.split(',').next())
+            .map(|s| u64::from_str_radix(s, 16).unwrap())
+            .unwrap();
+        reader.read_line(&mut line).unwrap();
+
+        // mov rax, address; mov dword ptr [rax], 1234; ret
+        let mut code = vec![0x48, 0xb8];
+        code.extend_from_slice(&address.to_le_bytes());
+        code.extend_from_slice(&[0xc7, 0x00, 0xd2, 0x04, 0x00, 0x00, 0xc3]);
+
+        let injection = inject(proc.0.id(), &code).unwrap();
+        assert_eq!(injection.entry, injection.base + CODE_OFFSET as u64);
+        assert_ne!(injection.tid, 0);
+
+        let mut value = 0;
+        for _ in 0..100 {
+            let bytes = read_memory_address(proc.0.id(), address as usize, 4).unwrap();
+            value = u32::from_le_bytes(bytes.try_into().unwrap());
+            if value == 1234 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(value, 1234);
+
+        // the interrupted thread carries on
+        writeln!(stdin, "i").unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), "1235");
+        assert_eq!(inject(proc.0.id(), &[]), Err(InjectError::EmptyShellcode));
+    }
+}
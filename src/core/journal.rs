@@ -0,0 +1,400 @@
+//! Write journal: the bytes every write replaced, so the last writes can be undone.
+//!
+//! The CLI runs one command per invocation, so the journal of a process lives in a file
+//! keyed by its pid and start time, one write per line with the newest last. A command
+//! that writes to many addresses at once journals them on one line, undone together:
+//!
+//! ```text
+//! 0x<address> <previous bytes hex> <new bytes hex> [0x<address> <previous> <new>...]
+//! ```
+//!
+//! The journals are in a directory of the temp directory only the current user can
+//! access, since an undo writes whatever bytes its journal holds into the process.
+//! A directory or journal someone else could have planted (not owned by the user,
+//! accessible to others, or a symlink) is refused, and journals are replaced by a rename
+//! rather than written through, so no link in their place is followed.
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::mem::{MemoryError, MemorySource, ProcessHandle};
+
+/// Most writes kept per process, older ones are dropped first. The writes of one
+/// command count once
+pub const MAX_JOURNAL_ENTRIES: usize = 64;
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    InvalidLine(usize),
+    Memory(MemoryError),
+    /// The process is gone, or its start time can't be read to tell it from a later
+    /// process with the same pid
+    NoProcess(u32),
+    /// A journal directory or file that isn't the user's own, see the module docs
+    Untrusted(PathBuf),
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access the write journal: {e}"),
+            Self::InvalidLine(line) => write!(f, "Invalid write journal line {line}"),
+            Self::Memory(e) => write!(f, "Could not undo the write: {e}"),
+            Self::NoProcess(pid) => write!(f, "Could not read the start time of process {pid}"),
+            Self::Untrusted(path) => write!(
+                f,
+                "Refusing to use the write journal at {}, it isn't private to the current user",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Memory(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for JournalError {
+    fn from(e: io::Error) -> Self {
+        JournalError::Io(e)
+    }
+}
+
+/// One write: `previous` were the bytes at `address` before `new` replaced them
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub address: u64,
+    pub previous: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// Journal file of process `pid`, named after its pid and start time so a later process
+/// that reuses the pid doesn't get its journal. Creates the journal directory.
+pub fn journal_path(pid: u32) -> Result<PathBuf, JournalError> {
+    let started = crate::core::proc::start_time(pid).ok_or(JournalError::NoProcess(pid))?;
+    Ok(journal_dir()?.join(format!("{pid}-{started}")))
+}
+
+/// The current user's journal directory, created private to them
+fn journal_dir() -> Result<PathBuf, JournalError> {
+    // macOS and Windows temp directories are per user already
+    #[cfg(target_os = "linux")]
+    let dir = std::env::temp_dir().join(format!("cheat-engine-rs-{}", current_uid()));
+    #[cfg(not(target_os = "linux"))]
+    let dir = std::env::temp_dir().join("cheat-engine-rs-journal");
+
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    match builder.create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e.into()),
+        _ => {}
+    }
+    #[cfg(unix)]
+    check_private(&dir, &std::fs::symlink_metadata(&dir)?)?;
+    Ok(dir)
+}
+
+#[cfg(target_os = "linux")]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and can't fail
+    unsafe { libc::geteuid() }
+}
+
+/// Fail unless `metadata` (of `path`, not followed) is of a directory or plain file that
+/// nobody but the current user can write to
+#[cfg(unix)]
+fn check_private(path: &Path, metadata: &std::fs::Metadata) -> Result<(), JournalError> {
+    use std::os::unix::fs::MetadataExt;
+
+    let kind = metadata.file_type();
+    #[cfg(target_os = "linux")]
+    let owned = metadata.uid() == current_uid();
+    #[cfg(not(target_os = "linux"))]
+    let owned = true;
+    match !kind.is_symlink() && (kind.is_dir() || kind.is_file()) && owned && metadata.mode() & 0o077 == 0 {
+        true => Ok(()),
+        false => Err(JournalError::Untrusted(path.to_path_buf())),
+    }
+}
+
+/// Open `path` for reading without following a symlink in its place
+fn open_journal(path: &Path) -> io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true);
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NOFOLLOW);
+    }
+    options.open(path)
+}
+
+fn parse_line(line: &str) -> Option<Vec<JournalEntry>> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if !fields.len().is_multiple_of(3) {
+        return None;
+    }
+    fields
+        .chunks(3)
+        .map(|fields| {
+            let address = u64::from_str_radix(fields[0].strip_prefix("0x")?, 16).ok()?;
+            let previous = hex::decode(fields[1]).ok()?;
+            let new = hex::decode(fields[2]).ok()?;
+            (previous.len() == new.len()).then_some(JournalEntry { address, previous, new })
+        })
+        .collect()
+}
+
+/// The writes journaled in `path`, oldest first, each with the ones journaled together
+/// with it. A missing file is an empty journal.
+pub fn load(path: &Path) -> Result<Vec<Vec<JournalEntry>>, JournalError> {
+    use std::io::Read;
+
+    let mut file = match open_journal(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        // a symlink in the journal's place
+        #[cfg(target_os = "linux")]
+        Err(e) if e.raw_os_error() == Some(libc::ELOOP) => {
+            return Err(JournalError::Untrusted(path.to_path_buf()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    #[cfg(unix)]
+    check_private(path, &file.metadata()?)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line).ok_or(JournalError::InvalidLine(i + 1)))
+        .collect()
+}
+
+fn save(path: &Path, entries: &[Vec<JournalEntry>]) -> Result<(), JournalError> {
+    if entries.is_empty() {
+        return clear(path);
+    }
+    let contents: String = entries
+        .iter()
+        .map(|writes| {
+            let fields: Vec<String> = writes
+                .iter()
+                .map(|e| format!("0x{:x} {} {}", e.address, hex::encode(&e.previous), hex::encode(&e.new)))
+                .collect();
+            format!("{}\n", fields.join(" "))
+        })
+        .collect();
+    // written to a new file first, then renamed over the journal, which replaces a
+    // link in its place instead of following it
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let _ = std::fs::remove_file(&partial);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&partial)?;
+    io::Write::write_all(&mut file, contents.as_bytes())?;
+    drop(file);
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Append `writes` to the journal in `path` as one write, undone together, keeping the
+/// newest [`MAX_JOURNAL_ENTRIES`]. Nothing is journaled when `writes` is empty.
+pub fn record(path: &Path, writes: Vec<JournalEntry>) -> Result<(), JournalError> {
+    if writes.is_empty() {
+        return Ok(());
+    }
+    let mut entries = load(path)?;
+    entries.push(writes);
+    let excess = entries.len().saturating_sub(MAX_JOURNAL_ENTRIES);
+    entries.drain(..excess);
+    save(path, &entries)
+}
+
+/// Append `writes` to the journal of process `pid`, see [`record`]
+pub fn record_for(pid: u32, writes: Vec<JournalEntry>) -> Result<(), JournalError> {
+    record(&journal_path(pid)?, writes)
+}
+
+/// Forget every write in the journal in `path`
+pub fn clear(path: &Path) -> Result<(), JournalError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Write back the bytes replaced by the newest journaled write in process `pid`, with
+/// every write journaled together with it, and drop it from the journal. `None` if there
+/// is nothing to undo. Each restored write comes with whether its bytes were still in
+/// place, `false` when the process changed them since.
+pub fn undo_last(pid: u32) -> Result<Option<Vec<(JournalEntry, bool)>>, JournalError> {
+    let path = journal_path(pid)?;
+    let mut entries = load(&path)?;
+    let Some(last) = entries.pop() else {
+        return Ok(None);
+    };
+
+    let handle = ProcessHandle::open(pid).map_err(JournalError::Memory)?;
+    let mut restored = Vec::with_capacity(last.len());
+    // newest first, so overlapping writes end up with the oldest bytes
+    for entry in last.into_iter().rev() {
+        let current = handle.read(entry.address as usize, entry.new.len()).map_err(JournalError::Memory)?;
+        // written through the handle, so undoing isn't journaled as a write itself
+        handle.write(entry.address as usize, &entry.previous).map_err(JournalError::Memory)?;
+        let untouched = current == entry.new;
+        restored.push((entry, untouched));
+    }
+    save(&path, &entries)?;
+    restored.reverse();
+    Ok(Some(restored))
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_journal_record_and_cap() {
+        let path = journal_dir().unwrap().join(format!("test-{}", std::process::id()));
+        clear(&path).unwrap();
+
+        for i in 0..MAX_JOURNAL_ENTRIES as u64 + 2 {
+            let entry = JournalEntry { address: 0x1000 + i, previous: vec![i as u8], new: vec![0xff] };
+            record(&path, vec![entry]).unwrap();
+        }
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), MAX_JOURNAL_ENTRIES);
+        // the two oldest writes were dropped
+        assert_eq!(entries[0][0].address, 0x1002);
+
+        let last = &entries.last().unwrap()[0];
+        assert_eq!(last.address, 0x1000 + MAX_JOURNAL_ENTRIES as u64 + 1);
+        assert_eq!(last.previous, vec![MAX_JOURNAL_ENTRIES as u8 + 1]);
+
+        // a batch larger than the cap is one write and doesn't push itself out
+        let batch: Vec<JournalEntry> = (0..MAX_JOURNAL_ENTRIES as u64 * 2)
+            .map(|i| JournalEntry { address: 0x8000 + i * 4, previous: vec![1, 2], new: vec![3, 4] })
+            .collect();
+        record(&path, batch.clone()).unwrap();
+        record(&path, vec![]).unwrap();
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(entries.last().unwrap(), &batch);
+        assert_eq!(entries[0][0].address, 0x1003);
+
+        save(&path, &[]).unwrap();
+        std::fs::write(&path, "0x1000 0102 03\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        assert!(matches!(load(&path), Err(JournalError::InvalidLine(1))));
+
+        clear(&path).unwrap();
+        assert!(!path.exists());
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_journal_refuses_planted_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let pid = std::process::id();
+        let path = journal_path(pid).unwrap();
+        assert!(path.starts_with(journal_dir().unwrap()));
+        // keyed by the start time too
+        assert_eq!(path.file_name().unwrap().to_str().unwrap(), format!("{pid}-{}", crate::core::proc::start_time(pid).unwrap()));
+        let mode = std::fs::metadata(journal_dir().unwrap()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // forged undo bytes anyone could have written
+        let path = journal_dir().unwrap().join(format!("test-planted-{pid}"));
+        std::fs::write(&path, "0x1000 01 02\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666)).unwrap();
+        assert!(matches!(load(&path), Err(JournalError::Untrusted(_))));
+        std::fs::remove_file(&path).unwrap();
+
+        // a link to a file the journal would clobber is replaced, not written through
+        let victim = std::env::temp_dir().join(format!("cheat-engine-rs-{pid}-victim"));
+        std::fs::write(&victim, "keep").unwrap();
+        std::os::unix::fs::symlink(&victim, &path).unwrap();
+        assert!(matches!(load(&path), Err(JournalError::Untrusted(_))));
+        save(&path, &[vec![JournalEntry { address: 0x1000, previous: vec![1], new: vec![2] }]]).unwrap();
+        assert_eq!(std::fs::read_to_string(&victim).unwrap(), "keep");
+        assert_eq!(load(&path).unwrap().len(), 1);
+        clear(&path).unwrap();
+        std::fs::remove_file(&victim).unwrap();
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    fn test_undo_last_write() {
+        use crate::core::mem::{read_memory_address, write_memory_address};
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let pid = proc.0.id();
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let address = usize::from_str_radix(line.trim().trim_start_matches("0x"), 16).unwrap();
+        clear(&journal_path(pid).unwrap()).unwrap();
+
+        write_memory_address(pid, address, &1_u32.to_le_bytes()).unwrap();
+        write_memory_address(pid, address, &2_u32.to_le_bytes()).unwrap();
+        let read = || u32::from_le_bytes(read_memory_address(pid, address, 4).unwrap().try_into().unwrap());
+        assert_eq!(read(), 2);
+
+        let restored = undo_last(pid).unwrap().unwrap();
+        let (entry, untouched) = &restored[0];
+        assert_eq!(entry.address, address as u64);
+        assert!(untouched);
+        assert_eq!(read(), 1);
+        assert_eq!(undo_last(pid).unwrap().unwrap()[0].0.previous, 31337_u32.to_le_bytes());
+        assert_eq!(read(), 31337);
+        assert!(undo_last(pid).unwrap().is_none());
+
+        // writes journaled together are undone together, the newest first
+        let handle = ProcessHandle::open(pid).unwrap();
+        handle.write(address, &6_u32.to_le_bytes()).unwrap();
+        let write = |previous: u32, new: u32| JournalEntry {
+            address: address as u64,
+            previous: previous.to_le_bytes().to_vec(),
+            new: new.to_le_bytes().to_vec(),
+        };
+        record_for(pid, vec![write(31337, 5), write(5, 6)]).unwrap();
+        assert_eq!(undo_last(pid).unwrap().unwrap().len(), 2);
+        assert_eq!(read(), 31337);
+    }
+}
@@ -0,0 +1,274 @@
+//! The bit of JSON the input files of the CLI need (pointer paths, struct schemas, dump
+//! maps), so they don't need a serialization dependency. Numbers are kept as text, so
+//! offsets and addresses don't go through a float.
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    /// Byte offset of the problem in the text
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid JSON at byte {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Value of the key `name` of an object, the first one if it is repeated
+    pub fn field(&self, name: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(key, _)| key == name).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// A number or a string holding one, which may be `0x` hex and negative
+    pub fn integer(&self) -> Option<i64> {
+        let text = match self {
+            Json::Number(text) | Json::String(text) => text.trim(),
+            _ => return None,
+        };
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+            None => digits.parse().ok()?,
+        };
+        Some(if negative { -magnitude } else { magnitude })
+    }
+}
+
+/// How deeply arrays and objects may nest, deeper input is an error rather than a stack
+/// overflow
+pub const MAX_JSON_DEPTH: usize = 128;
+
+/// Parse `text` as a single JSON value
+pub fn parse_json(text: &str) -> Result<Json, JsonError> {
+    let mut parser = JsonParser { text, position: 0, depth: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return Err(parser.error("unexpected text after the value"));
+    }
+    Ok(value)
+}
+
+/// `text` as a JSON string, quoted and escaped
+pub fn quote(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    position: usize,
+    /// Arrays and objects around the current value
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError { position: self.position, message: message.to_string() }
+    }
+
+    fn rest(&self) -> &str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` after any whitespace, if it is next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        if self.rest().starts_with(['[', '{']) {
+            if self.depth == MAX_JSON_DEPTH {
+                return Err(self.error("arrays and objects are nested too deeply"));
+            }
+            self.depth += 1;
+            let value = self.container();
+            self.depth -= 1;
+            return value;
+        }
+        self.scalar()
+    }
+
+    fn container(&mut self) -> Result<Json, JsonError> {
+        match self.rest().chars().next() {
+            Some('[') => {
+                self.position += 1;
+                let mut items = vec![];
+                if self.eat("]") {
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    if self.eat("]") {
+                        return Ok(Json::Array(items));
+                    }
+                    if !self.eat(",") {
+                        return Err(self.error("expected ',' or ']'"));
+                    }
+                }
+            }
+            Some('{') => {
+                self.position += 1;
+                let mut fields = vec![];
+                if self.eat("}") {
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    if !self.eat(":") {
+                        return Err(self.error("expected ':'"));
+                    }
+                    fields.push((key, self.value()?));
+                    if self.eat("}") {
+                        return Ok(Json::Object(fields));
+                    }
+                    if !self.eat(",") {
+                        return Err(self.error("expected ',' or '}'"));
+                    }
+                }
+            }
+            _ => self.scalar(),
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Json, JsonError> {
+        match self.rest().chars().next() {
+            Some('"') => self.string().map(Json::String),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let len = self
+                    .rest()
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))
+                    .unwrap_or(self.rest().len());
+                let number = self.rest()[..len].to_string();
+                self.position += len;
+                Ok(Json::Number(number))
+            }
+            _ if self.eat("true") => Ok(Json::Bool(true)),
+            _ if self.eat("false") => Ok(Json::Bool(false)),
+            _ if self.eat("null") => Ok(Json::Null),
+            None => Err(self.error("unexpected end of file")),
+            Some(_) => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if !self.rest().starts_with('"') {
+            return Err(self.error("expected a string"));
+        }
+        self.position += 1;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += i + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => {
+                            self.position += i;
+                            return Err(self.error("invalid escape"));
+                        }
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+        self.position = self.text.len();
+        Err(self.error("unterminated string"))
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_json_values() {
+        let value = parse_json(r#" {"a": [1, "0x10", -0x4], "b": {"c": true, "d": null}, "e": "x\"\u0041"} "#).unwrap();
+        let a = value.field("a").unwrap();
+        let Json::Array(items) = a else { panic!("expected an array, got {a:?}") };
+        let integers: Vec<_> = items.iter().map(Json::integer).collect();
+        assert_eq!(integers, [Some(1), Some(16), Some(-4)]);
+        assert_eq!(value.field("b").and_then(|b| b.field("c")), Some(&Json::Bool(true)));
+        assert_eq!(value.field("b").and_then(|b| b.field("d")), Some(&Json::Null));
+        assert_eq!(value.field("e"), Some(&Json::String(String::from("x\"A"))));
+        assert_eq!(value.field("missing"), None);
+        assert_eq!(Json::String(String::from("1.5")).integer(), None);
+
+        assert_eq!(
+            parse_json("[1 2]"),
+            Err(JsonError { position: 3, message: String::from("expected ',' or ']'") })
+        );
+        assert_eq!(parse_json("[1] x").unwrap_err().message, "unexpected text after the value");
+        assert_eq!(parse_json("\"abc").unwrap_err().message, "unterminated string");
+
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse_json(&nested(MAX_JSON_DEPTH)).is_ok());
+        assert_eq!(
+            parse_json(&nested(MAX_JSON_DEPTH + 1)),
+            Err(JsonError { position: MAX_JSON_DEPTH, message: String::from("arrays and objects are nested too deeply") })
+        );
+        assert!(parse_json(&"{\"a\":".repeat(100_000)).is_err());
+    }
+}
@@ -1,18 +1,26 @@
+#[cfg(not(target_os = "linux"))]
 use process_memory::*;
 use std::fmt::Display;
 
+use crate::core::digest::{HashAlgo, Hasher};
+use crate::core::json::{Json, JsonError, parse_json};
+use crate::core::scan::ValueType;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum MemoryError {
     NoPermission(i32),
     MemRead(i32),
     MemWrite(i32),
     ProcessAttach(i32),
+    ProcessExited,
+    Unmapped(u64),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MemoryRegionPerms {
     Read,
     Write,
+    Execute,
 }
 
 pub const DEFAULT_SEARCH_PERMS: [MemoryRegionPerms; 1] = [MemoryRegionPerms::Write];
@@ -26,6 +34,8 @@ impl Display for MemoryError {
             Self::ProcessAttach(code) => {
                 write!(f, "Could not attach to process: OS Error ({code})")
             }
+            Self::ProcessExited => write!(f, "Process has exited"),
+            Self::Unmapped(address) => write!(f, "Address 0x{address:x} is not mapped"),
         }
     }
 }
@@ -33,7 +43,7 @@ impl Display for MemoryError {
 impl std::error::Error for MemoryError {}
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MemoryRegion {
     pub start: u64,
     pub end: u64,
@@ -41,6 +51,36 @@ pub struct MemoryRegion {
     pub perms: Vec<MemoryRegionPerms>,
 }
 
+/// `RWX` style permissions, only the ones the region has
+pub fn format_perms(perms: &[MemoryRegionPerms]) -> String {
+    perms
+        .iter()
+        .map(|p| match p {
+            MemoryRegionPerms::Read => "R",
+            MemoryRegionPerms::Write => "W",
+            MemoryRegionPerms::Execute => "X",
+        })
+        .collect()
+}
+
+/// Regions of `source` whatever their permissions, in the order `regions` numbers them
+pub fn all_regions(source: &dyn MemorySource) -> Result<Vec<MemoryRegion>, MemoryError> {
+    use MemoryRegionPerms::*;
+    source.regions(None, None, Some(&[Read, Write, Execute]))
+}
+
+/// A hash of the bounds and permissions of `regions`, cheap to compare to tell whether the
+/// process mapped, unmapped or reprotected memory in between (e.g. on a level load)
+pub fn maps_fingerprint(regions: &[MemoryRegion]) -> u64 {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    for region in regions {
+        (region.start, region.end, &region.perms).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
 #[cfg(target_os = "macos")]
 pub fn get_memory_regions(
     pid: u32,
@@ -53,7 +93,7 @@ pub fn get_memory_regions(
         port::mach_port_name_t,
         traps::{mach_task_self, task_for_pid},
         vm::mach_vm_region,
-        vm_prot::{VM_PROT_READ, VM_PROT_WRITE},
+        vm_prot::{VM_PROT_EXECUTE, VM_PROT_READ, VM_PROT_WRITE},
         vm_region::{VM_REGION_BASIC_INFO_64, vm_region_info_t},
         vm_types::{mach_vm_address_t, mach_vm_size_t, vm_map_t},
     };
@@ -114,6 +154,10 @@ pub fn get_memory_regions(
             perms.push(MemoryRegionPerms::Write);
         }
 
+        if info.protection & VM_PROT_EXECUTE != 0 {
+            perms.push(MemoryRegionPerms::Execute);
+        }
+
         if search_perms.iter().filter(|p| perms.contains(p)).count() > 0 {
             regions.push(MemoryRegion {
                 start: address,
@@ -135,27 +179,42 @@ pub fn get_memory_regions(
     end: Option<u64>,
     search_perms: Option<&[MemoryRegionPerms]>,
 ) -> Result<Vec<MemoryRegion>, MemoryError> {
-    use std::fs::File;
-    use std::io::{self, BufRead};
-    use std::path::PathBuf;
+    let path = format!("/proc/{}/maps", pid);
+    let maps = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::InvalidData => MemoryError::MemRead(0),
+        _ => MemoryError::NoPermission(e.raw_os_error().unwrap_or(-1)),
+    })?;
 
-    let search_perms = search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS);
-    let path = PathBuf::from(format!("/proc/{}/maps", pid));
-    let file = File::open(&path)
-        .map_err(|e| MemoryError::NoPermission(e.raw_os_error().unwrap_or(-1) as i32))?;
-    let reader = io::BufReader::new(file);
+    parse_maps_regions(&maps, start, end, search_perms)
+}
 
+/// Kernel mappings `/proc/<pid>/maps` lists with read or execute permission that fail to
+/// read anyway: the vDSO data pages and the legacy vsyscall page
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const UNREADABLE_MAPPINGS: [&str; 3] = ["[vvar]", "[vvar_vclock]", "[vsyscall]"];
+
+/// Regions of a `/proc/<pid>/maps` text with any of `search_perms` that overlap
+/// `start..=end`, normalized with [`merge_regions`]. [`UNREADABLE_MAPPINGS`] are left out,
+/// so scans never try to read them.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_maps_regions(
+    maps: &str,
+    start: Option<u64>,
+    end: Option<u64>,
+    search_perms: Option<&[MemoryRegionPerms]>,
+) -> Result<Vec<MemoryRegion>, MemoryError> {
+    let search_perms = search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS);
     let start_addr = start.unwrap_or(0);
     let end_addr = end.unwrap_or(u64::MAX);
 
     let mut regions = Vec::new();
 
-    for line in reader.lines() {
-        let line = line.map_err(|_| MemoryError::MemRead(0))?;
-        // 00400000-00452000 r-xp 00000000 fd:00
-        let mut parts = line.split_whitespace();
+    for line in maps.lines() {
+        // 00400000-00452000 r-xp 00000000 fd:00 1234 /usr/bin/game
+        let mut parts = line.splitn(6, char::is_whitespace);
         let range = parts.next().ok_or_else(|| MemoryError::MemRead(0))?;
         let perms = parts.next().unwrap_or("");
+        let path = parts.nth(3).map(str::trim).unwrap_or("");
 
         let mut range_split = range.split('-');
         let start_str = range_split.next().ok_or_else(|| MemoryError::MemRead(0))?;
@@ -167,9 +226,13 @@ pub fn get_memory_regions(
         if end_addr_val < start_addr || start_addr_val > end_addr {
             continue;
         }
+        if UNREADABLE_MAPPINGS.contains(&path) {
+            log::trace!("Skipping unreadable mapping {path} at 0x{start_addr_val:x}");
+            continue;
+        }
 
         let mut region_perms = Vec::with_capacity(2);
-        let perms = &perms[..3];
+        let perms = perms.get(..3).unwrap_or("");
 
         if perms.contains('r') {
             region_perms.push(MemoryRegionPerms::Read);
@@ -179,21 +242,55 @@ pub fn get_memory_regions(
             region_perms.push(MemoryRegionPerms::Write);
         }
 
+        if perms.contains('x') {
+            region_perms.push(MemoryRegionPerms::Execute);
+        }
+
         if search_perms
             .iter()
             .filter(|p| region_perms.contains(p))
             .count()
             > 0
         {
-            regions.push(MemoryRegion {
+            let region = MemoryRegion {
                 start: start_addr_val,
                 end: end_addr_val,
                 perms: region_perms,
-            });
+            };
+            regions.push((region, path));
         }
     }
 
-    Ok(regions)
+    Ok(merge_regions(regions))
+}
+
+/// Sort regions and normalize them for scanning: overlapping parts are only kept once
+/// (by the region starting first) and a region directly following another one with the
+/// same permissions and backing path (`""` for anonymous memory) is merged into it.
+/// Regions of different files are never merged, so every region still belongs to a
+/// single mapping name.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn merge_regions(mut regions: Vec<(MemoryRegion, &str)>) -> Vec<MemoryRegion> {
+    regions.sort_by_key(|(region, _)| (region.start, std::cmp::Reverse(region.end)));
+
+    let mut merged: Vec<(MemoryRegion, &str)> = Vec::with_capacity(regions.len());
+    for (mut region, path) in regions {
+        if let Some((last, last_path)) = merged.last_mut() {
+            if region.end <= last.end {
+                continue;
+            }
+            region.start = region.start.max(last.end);
+            if region.start == last.end && region.perms == last.perms && path == *last_path {
+                last.end = region.end;
+                continue;
+            }
+        }
+        if region.start < region.end {
+            merged.push((region, path));
+        }
+    }
+
+    merged.into_iter().map(|(region, _)| region).collect()
 }
 
 #[cfg(target_os = "windows")]
@@ -206,7 +303,7 @@ pub fn get_memory_regions(
     use windows::Win32::System::Threading::OpenProcess;
     use windows::Win32::System::Threading::PROCESS_QUERY_INFORMATION;
     use windows::Win32::System::Threading::PROCESS_VM_READ;
-    use windows::Win32::System::Memory::{VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_PROTECTION_FLAGS, PAGE_READONLY, PAGE_READWRITE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_WRITECOPY, PAGE_EXECUTE_WRITECOPY};
+    use windows::Win32::System::Memory::{VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_PROTECTION_FLAGS, PAGE_READONLY, PAGE_READWRITE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_WRITECOPY, PAGE_EXECUTE_WRITECOPY, PAGE_EXECUTE};
 
     let search_perms = search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS);
     let start_addr = start.unwrap_or(0);
@@ -270,6 +367,14 @@ pub fn get_memory_regions(
                 region_perms.push(MemoryRegionPerms::Write);
             }
 
+            // Check execute permission
+            if (mbi.Protect & PAGE_EXECUTE) != PAGE_PROTECTION_FLAGS(0) ||
+               (mbi.Protect & PAGE_EXECUTE_READ) != PAGE_PROTECTION_FLAGS(0) ||
+               (mbi.Protect & PAGE_EXECUTE_READWRITE) != PAGE_PROTECTION_FLAGS(0) ||
+               (mbi.Protect & PAGE_EXECUTE_WRITECOPY) != PAGE_PROTECTION_FLAGS(0) {
+                region_perms.push(MemoryRegionPerms::Execute);
+            }
+
             if search_perms
                 .iter()
                 .filter(|p| region_perms.contains(p))
@@ -290,203 +395,2218 @@ pub fn get_memory_regions(
     Ok(regions)
 }
 
-pub fn read_memory_address(pid: u32, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
-    let handle = (pid as Pid)
-        .try_into_process_handle()
-        .map_err(|e| MemoryError::ProcessAttach(e.raw_os_error().unwrap_or(-1)))?;
-
-    let mut result = vec![0; size];
-    handle.copy_address(addr, &mut result).map_err(|e| {
-        // in linux it can attach to process, but not read the memory
-        // so this is a 'hack' to make it like MacOS
-        if std::env::consts::OS == "linux" && e.raw_os_error().unwrap_or(-1) == 1 {
-            return MemoryError::ProcessAttach(1);
+/// Module-relative form of `address` in process `pid`, the inverse of resolving an
+/// address expression: `libgame.so+0x1234` in a mapped file (from its lowest mapping,
+/// the base `module+offset` expressions use), `[heap]+0x10` in other named mappings (from
+/// their start) and `[anon]` in anonymous memory, which has nothing to be relative to.
+pub fn addr_to_offset(pid: u32, address: u64) -> Result<String, MemoryError> {
+    use crate::core::proc::{mappings, modules};
+
+    relative_address(&mappings(pid), &modules(pid), address)
+        .map(|relative| relative.to_string())
+        .ok_or(MemoryError::Unmapped(address))
+}
+
+/// Where an address lies in a process, see [`relative_address`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelativeAddress {
+    /// `offset` bytes past the base of module `name`, which resolves again after the
+    /// module was loaded elsewhere (ASLR)
+    Module { name: String, offset: u64 },
+    /// `offset` bytes into a named mapping that isn't a module, e.g. `[heap]`
+    Mapping { name: String, offset: u64 },
+    /// Anonymous memory, which has nothing to be relative to
+    Anonymous,
+}
+
+impl Display for RelativeAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Module { name, offset } | Self::Mapping { name, offset } => write!(f, "{name}+0x{offset:x}"),
+            Self::Anonymous => write!(f, "[anon]"),
         }
-        MemoryError::MemRead(e.raw_os_error().unwrap_or(-1))
-    })?;
+    }
+}
+
+/// `address` relative to what it lies in, with `mappings` sorted by address as
+/// [`crate::core::proc::mappings`] returns them. `None` if it isn't mapped.
+pub fn relative_address(
+    mappings: &[crate::core::proc::Mapping],
+    modules: &[crate::core::proc::Module],
+    address: u64,
+) -> Option<RelativeAddress> {
+    let mapping = mappings[mappings.partition_point(|m| m.end <= address)..].first()?;
+    if address < mapping.start {
+        return None;
+    }
+    if mapping.path.is_empty() {
+        return Some(RelativeAddress::Anonymous);
+    }
+    Some(match modules.iter().find(|m| m.path.as_os_str() == mapping.path.as_str()) {
+        Some(module) => RelativeAddress::Module { name: module.name.clone(), offset: address - module.base },
+        None => RelativeAddress::Mapping { name: mapping.name().to_string(), offset: address - mapping.start },
+    })
+}
 
-    Ok(result)
+/// Read `size` bytes at `addr` in process `pid` through a [`ProcessHandle`] opened for
+/// the read
+pub fn read_memory_address(pid: u32, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+    ProcessHandle::open(pid)?.read(addr, size)
 }
 
+/// Write `value` at `addr` in process `pid`. The bytes it replaces are read first and
+/// recorded in the write journal of the process, so the write can be undone with
+/// [`crate::core::journal::undo_last`]. Journaling is best effort: the write still goes
+/// through if the old bytes can't be read or the journal can't be saved.
 pub fn write_memory_address(pid: u32, addr: usize, value: &[u8]) -> Result<(), MemoryError> {
-    let handle = (pid as Pid)
-        .try_into_process_handle()
-        .map_err(|e| MemoryError::ProcessAttach(e.raw_os_error().unwrap_or(-1)))?;
+    use crate::core::journal::{self, JournalEntry};
+
+    let handle = ProcessHandle::open(pid)?;
+    let previous = handle.read(addr, value.len()).ok();
+    handle.write(addr, value)?;
 
-    handle
-        .put_address(addr, value)
-        .map_err(|e| MemoryError::MemWrite(e.raw_os_error().unwrap_or(-1)))?;
+    if let Some(previous) = previous {
+        let entry = JournalEntry { address: addr as u64, previous, new: value.to_vec() };
+        let _ = journal::record_for(pid, vec![entry]);
+    }
 
     Ok(())
 }
 
-mod test {
-    #[allow(unused_imports)]
-    use super::*;
-    #[allow(unused_imports)]
-    use std::process::{Command, Stdio};
+/// A memory space that can be scanned. [`ProcessHandle`] implements it for live
+/// processes; anything else (e.g. an in-memory fake in tests) can be plugged into
+/// [`crate::core::scan::Scan`] the same way.
+pub trait MemorySource: Send + Sync + std::fmt::Debug {
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError>;
 
-    #[test]
-    pub fn test_get_regions_error() {
-        let result = get_memory_regions(0, None, None, None);
+    fn write(&self, addr: usize, value: &[u8]) -> Result<(), MemoryError>;
 
-        assert!(result.is_err());
+    fn regions(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        search_perms: Option<&[MemoryRegionPerms]>,
+    ) -> Result<Vec<MemoryRegion>, MemoryError>;
+}
 
-        if let Err(e) = result {
-            match e {
-                MemoryError::NoPermission(_) => assert!(true),
-                _ => assert!(false),
+impl<T: MemorySource + ?Sized> MemorySource for &T {
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+        (**self).read(addr, size)
+    }
+
+    fn write(&self, addr: usize, value: &[u8]) -> Result<(), MemoryError> {
+        (**self).write(addr, value)
+    }
+
+    fn regions(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        search_perms: Option<&[MemoryRegionPerms]>,
+    ) -> Result<Vec<MemoryRegion>, MemoryError> {
+        (**self).regions(start, end, search_perms)
+    }
+}
+
+/// How [`ProcessHandle`] accesses memory on Linux, other platforms always use the OS
+/// read/write calls
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoBackend {
+    /// `process_vm_readv`/`process_vm_writev`, falling back to `/proc/<pid>/mem` where
+    /// the syscalls aren't permitted
+    Auto,
+    /// Only `/proc/<pid>/mem`
+    ProcMem,
+}
+
+// 0 is Auto, 1 ProcMem
+static IO_BACKEND: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(0);
+
+/// Backend of the process handles opened from now on
+pub fn set_io_backend(backend: IoBackend) {
+    let value = match backend {
+        IoBackend::Auto => 0,
+        IoBackend::ProcMem => 1,
+    };
+    IO_BACKEND.store(value, std::sync::atomic::Ordering::Relaxed);
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn io_backend() -> IoBackend {
+    match IO_BACKEND.load(std::sync::atomic::Ordering::Relaxed) {
+        0 => IoBackend::Auto,
+        _ => IoBackend::ProcMem,
+    }
+}
+
+/// An open handle to a process' memory, kept for as long as the value lives so
+/// interactive sessions don't reopen `/proc/<pid>/mem` (or the OS equivalent) for every
+/// read. The handle is closed on drop. Reads report [`MemoryError::ProcessExited`] once
+/// the process is gone.
+///
+/// On Linux reads and writes go through `process_vm_readv`/`process_vm_writev` by default,
+/// one syscall per access instead of a seek and read on the `/proc` file. A syscall that
+/// fails is retried on `/proc/<pid>/mem`, which can also write to read-only pages, and
+/// once the syscalls turn out to be unavailable (`EPERM`, `ENOSYS`) the handle sticks to
+/// the file. See [`set_io_backend`].
+///
+/// The `/proc` file is read and written at an offset (`pread`/`pwrite`) and never seeked,
+/// so the parallel scan threads share one handle without racing for the file position.
+#[derive(Debug)]
+pub struct ProcessHandle {
+    pid: u32,
+    #[cfg(target_os = "linux")]
+    file: std::fs::File,
+    #[cfg(target_os = "linux")]
+    vm_io: std::sync::atomic::AtomicBool,
+    #[cfg(not(target_os = "linux"))]
+    handle: process_memory::ProcessHandle,
+}
+
+// The raw Windows HANDLE is a pointer, but process handles can be used from any thread.
+#[cfg(target_os = "windows")]
+unsafe impl Send for ProcessHandle {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for ProcessHandle {}
+
+impl ProcessHandle {
+    #[cfg(target_os = "linux")]
+    pub fn open(pid: u32) -> Result<Self, MemoryError> {
+        use std::fs::OpenOptions;
+
+        let path = format!("/proc/{}/mem", pid);
+        // fall back to read-only so scanning still works where writes aren't permitted
+        let file = match OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::debug!("Opening {path} for writing failed ({e}), opening it read-only");
+                OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+                    .map_err(|e| MemoryError::ProcessAttach(e.raw_os_error().unwrap_or(-1)))?
             }
-        } else {
-            assert!(false)
+        };
+
+        let vm_io = std::sync::atomic::AtomicBool::new(io_backend() == IoBackend::Auto);
+        Ok(ProcessHandle { pid, file, vm_io })
+    }
+
+    /// Read through `process_vm_readv`, a short read counts as `EFAULT`
+    #[cfg(target_os = "linux")]
+    fn vm_read(&self, addr: usize, size: usize) -> std::io::Result<Vec<u8>> {
+        let mut result = vec![0_u8; size];
+        let local = libc::iovec { iov_base: result.as_mut_ptr().cast(), iov_len: size };
+        let remote = libc::iovec { iov_base: addr as *mut libc::c_void, iov_len: size };
+        // SAFETY: `local` covers the `size` bytes of `result`, the remote side is only
+        // accessed by the kernel on behalf of the target
+        let read = unsafe { libc::process_vm_readv(self.pid as libc::pid_t, &local, 1, &remote, 1, 0) };
+        match read {
+            n if n < 0 => Err(std::io::Error::last_os_error()),
+            n if n as usize != size => Err(std::io::Error::from_raw_os_error(libc::EFAULT)),
+            _ => Ok(result),
         }
     }
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_get_regions_success() {
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+    /// Write through `process_vm_writev`, a short write counts as `EFAULT`
+    #[cfg(target_os = "linux")]
+    fn vm_write(&self, addr: usize, value: &[u8]) -> std::io::Result<()> {
+        let local = libc::iovec { iov_base: value.as_ptr() as *mut libc::c_void, iov_len: value.len() };
+        let remote = libc::iovec { iov_base: addr as *mut libc::c_void, iov_len: value.len() };
+        // SAFETY: `local` covers `value`, which the kernel only reads from
+        let written =
+            unsafe { libc::process_vm_writev(self.pid as libc::pid_t, &local, 1, &remote, 1, 0) };
+        match written {
+            n if n < 0 => Err(std::io::Error::last_os_error()),
+            n if n as usize != value.len() => Err(std::io::Error::from_raw_os_error(libc::EFAULT)),
+            _ => Ok(()),
+        }
+    }
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
-                let regions = get_memory_regions(proc.0.id(), None, None, None);
-                assert!(regions.is_ok());
-                let regions = regions.unwrap();
-                assert_ne!(regions.len(), 0);
-            }
+    /// Stop using the syscalls for good if `e` says they aren't permitted, rather than
+    /// the address being bad
+    #[cfg(target_os = "linux")]
+    fn vm_io_failed(&self, e: &std::io::Error) {
+        if matches!(e.raw_os_error(), Some(libc::EPERM | libc::ENOSYS | libc::EACCES)) {
+            log::debug!("process_vm_readv/writev unavailable ({e}), using /proc/{}/mem", self.pid);
+            self.vm_io.store(false, std::sync::atomic::Ordering::Relaxed);
         }
     }
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_get_regions_readonly_success() {
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+    #[cfg(not(target_os = "linux"))]
+    pub fn open(pid: u32) -> Result<Self, MemoryError> {
+        let handle = (pid as Pid)
+            .try_into_process_handle()
+            .map_err(|e| MemoryError::ProcessAttach(e.raw_os_error().unwrap_or(-1)))?;
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
-                let regions =
-                    get_memory_regions(proc.0.id(), None, None, Some(&[MemoryRegionPerms::Read]));
-                assert!(regions.is_ok());
-                let regions = regions.unwrap();
-                assert_ne!(regions.len(), 0);
-            }
+        Ok(ProcessHandle { pid, handle })
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn is_alive(&self) -> bool {
+        // zombies keep their /proc entry but no longer have any memory
+        match std::fs::read_to_string(format!("/proc/{}/stat", self.pid)) {
+            Err(_) => false,
+            Ok(stat) => stat
+                .rsplit_once(')')
+                .and_then(|(_, rest)| rest.trim_start().chars().next())
+                .is_some_and(|state| state != 'Z' && state != 'X'),
         }
     }
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_get_regions_with_range_success() {
-        use std::process::{Command, Stdio};
+    #[cfg(not(target_os = "linux"))]
+    pub fn is_alive(&self) -> bool {
+        let mut sys = sysinfo::System::new();
+        let pid = sysinfo::Pid::from_u32(self.pid);
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+        sys.process(pid).is_some()
+    }
 
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+    fn read_error(&self, e: std::io::Error) -> MemoryError {
+        if !self.is_alive() {
+            return MemoryError::ProcessExited;
+        }
+        // same mapping as read_memory_address so callers see consistent errors
+        let code = e.raw_os_error().unwrap_or(-1);
+        if std::env::consts::OS == "linux" && code == 1 {
+            return MemoryError::ProcessAttach(1);
+        }
+        MemoryError::MemRead(code)
+    }
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
-                let regions = get_memory_regions(proc.0.id(), Some(u64::MAX), None, None);
-                assert!(regions.is_ok());
-                let regions = regions.unwrap();
-                assert_eq!(regions.len(), 0);
+    fn write_error(&self, e: std::io::Error) -> MemoryError {
+        if !self.is_alive() {
+            return MemoryError::ProcessExited;
+        }
+        MemoryError::MemWrite(e.raw_os_error().unwrap_or(-1))
+    }
+}
 
-                let regions = get_memory_regions(proc.0.id(), None, Some(0), None);
-                assert!(regions.is_ok());
-                let regions = regions.unwrap();
-                assert_eq!(regions.len(), 0);
+impl MemorySource for ProcessHandle {
+    #[cfg(target_os = "linux")]
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+        use std::os::unix::fs::FileExt;
+
+        if self.vm_io.load(std::sync::atomic::Ordering::Relaxed) {
+            match self.vm_read(addr, size) {
+                Ok(result) => {
+                    log::trace!("Read {size} bytes at 0x{addr:x}");
+                    return Ok(result);
+                }
+                Err(e) => self.vm_io_failed(&e),
             }
         }
-    }
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_read_memory_address_success() {
-        use std::io::{BufRead, BufReader};
-        use std::process::{Command, Stdio};
+        let mut result = vec![0; size];
+        let mut filled = 0;
+        while filled < size {
+            match self.file.read_at(&mut result[filled..], (addr + filled) as u64) {
+                // reads from a process that has gone away come back empty
+                Ok(0) if !self.is_alive() => return Err(MemoryError::ProcessExited),
+                Ok(0) => return Err(MemoryError::MemRead(-1)),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    log::trace!("Reading {size} bytes at 0x{addr:x} failed: {e}");
+                    return Err(self.read_error(e));
+                }
+            }
+        }
+        log::trace!("Read {size} bytes at 0x{addr:x}");
 
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-            .unwrap();
+        Ok(result)
+    }
 
-        let mut proc = crate::core::utils::ChildGuard(proc);
-        let stdout = proc.0.stdout.take().expect("child had no stdout");
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
+    #[cfg(not(target_os = "linux"))]
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+        let mut result = vec![0; size];
+        self.handle
+            .copy_address(addr, &mut result)
+            .map_err(|e| self.read_error(e))?;
+        Ok(result)
+    }
 
-        reader.read_line(&mut line).unwrap();
+    #[cfg(target_os = "linux")]
+    fn write(&self, addr: usize, value: &[u8]) -> Result<(), MemoryError> {
+        use std::os::unix::fs::FileExt;
 
-        let hex_str = line.trim();
-        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
-            .expect("failed to parse hex");
+        log::debug!("Writing {} bytes at 0x{:x} in process {}", value.len(), addr, self.pid);
+        if self.vm_io.load(std::sync::atomic::Ordering::Relaxed) {
+            // read-only pages like code can only be written through the file
+            match self.vm_write(addr, value) {
+                Ok(()) => return Ok(()),
+                Err(e) => self.vm_io_failed(&e),
+            }
+        }
+        self.file.write_all_at(value, addr as u64).map_err(|e| {
+            log::debug!("Writing at 0x{addr:x} failed: {e}");
+            self.write_error(e)
+        })
+    }
 
-        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
-        let value = u32::from_le_bytes(value.try_into().unwrap());
-        assert_eq!(value, 31337_u32);
+    #[cfg(not(target_os = "linux"))]
+    fn write(&self, addr: usize, value: &[u8]) -> Result<(), MemoryError> {
+        log::debug!("Writing {} bytes at 0x{:x} in process {}", value.len(), addr, self.pid);
+        self.handle.put_address(addr, value).map_err(|e| {
+            log::debug!("Writing at 0x{addr:x} failed: {e}");
+            self.write_error(e)
+        })
     }
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_write_memory_address_success() {
-        use std::io::{BufRead, BufReader, Write};
-        use std::process::{Command, Stdio};
+    fn regions(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        search_perms: Option<&[MemoryRegionPerms]>,
+    ) -> Result<Vec<MemoryRegion>, MemoryError> {
+        let regions = get_memory_regions(self.pid, start, end, search_perms).map_err(|e| {
+            log::debug!("Listing the regions of process {} failed: {}", self.pid, e);
+            if self.is_alive() {
+                e
+            } else {
+                MemoryError::ProcessExited
+            }
+        })?;
+        log::debug!(
+            "{} region(s) of process {} with permissions {:?}",
+            regions.len(),
+            self.pid,
+            search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS)
+        );
+        for region in &regions {
+            log::trace!("Region 0x{:x}-0x{:x} {:?}", region.start, region.end, region.perms);
+        }
+        Ok(regions)
+    }
+}
 
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-            .unwrap();
+#[cfg(target_os = "windows")]
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
 
-        let mut proc = crate::core::utils::ChildGuard(proc);
-        let mut stdin = proc.0.stdin.take().expect("child has no stdin");
-        let stdout = proc.0.stdout.take().expect("child had no stdout");
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
+        unsafe {
+            let _ = CloseHandle(HANDLE(self.handle.0 as *mut std::ffi::c_void));
+        }
+    }
+}
 
-        reader.read_line(&mut line).unwrap();
+#[cfg(target_os = "macos")]
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        use mach_sys::{mach_port::mach_port_deallocate, traps::mach_task_self};
 
-        let hex_str = line.trim();
-        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
-            .expect("failed to parse hex");
+        unsafe {
+            mach_port_deallocate(mach_task_self(), self.handle.0);
+        }
+    }
+}
 
-        reader.read_line(&mut line).unwrap(); // consume readonly address
-        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
-        let value = u32::from_le_bytes(value.try_into().unwrap());
-        assert_eq!(value, 31337_u32);
+/// x86 single byte NOP
+pub const X86_NOP: u8 = 0x90;
+
+/// Overwrite memory at `addr` with `bytes` and return the bytes that were there, so the
+/// patch can be undone by writing them back
+pub fn patch_memory(
+    source: &dyn MemorySource,
+    addr: usize,
+    bytes: &[u8],
+) -> Result<Vec<u8>, MemoryError> {
+    let original = source.read(addr, bytes.len())?;
+    source.write(addr, bytes)?;
+    Ok(original)
+}
 
-        write_memory_address(proc.0.id(), address, &99999_u32.to_le_bytes().to_vec()).unwrap();
-        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
-        let value = u32::from_le_bytes(value.try_into().unwrap());
+/// Patches applied during a session. They are reverted newest first when the registry is
+/// dropped, which includes unwinding from a panic, unless [`PatchRegistry::keep`] was
+/// called.
+pub struct PatchRegistry<S: MemorySource> {
+    source: S,
+    patches: Vec<(usize, Vec<u8>)>,
+    keep: bool,
+}
 
-        writeln!(stdin, "read").unwrap();
-        stdin.flush().unwrap();
+impl<S: MemorySource> PatchRegistry<S> {
+    pub fn new(source: S) -> Self {
+        PatchRegistry {
+            source,
+            patches: vec![],
+            keep: false,
+        }
+    }
 
-        let mut response = String::new();
-        reader.read_line(&mut response).unwrap();
-        let response_value: u32 = response
-            .trim()
-            .parse()
-            .expect("failed to parse child response as u32");
+    /// Patch memory with [`patch_memory`] and remember the original bytes
+    pub fn apply(&mut self, addr: usize, bytes: &[u8]) -> Result<Vec<u8>, MemoryError> {
+        let original = patch_memory(&self.source, addr, bytes)?;
+        self.patches.push((addr, original.clone()));
+        Ok(original)
+    }
 
-        assert_eq!(value, 99999);
-        assert_eq!(value, response_value);
+    /// Remember the `original` bytes of a write made some other way, e.g. one that was
+    /// journaled
+    pub fn record(&mut self, addr: usize, original: Vec<u8>) {
+        self.patches.push((addr, original));
+    }
+
+    /// Leave the patches in place when the registry is dropped
+    pub fn keep(&mut self) {
+        self.keep = true;
+    }
+
+    /// Write back the original bytes of every patch, newest first so overlapping patches
+    /// unwind to the oldest original bytes. Keeps going after a failed write and returns
+    /// the first error.
+    pub fn restore(&mut self) -> Result<(), MemoryError> {
+        let mut result = Ok(());
+        while let Some((addr, original)) = self.patches.pop() {
+            if let Err(e) = self.source.write(addr, &original) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+}
+
+impl<S: MemorySource> Drop for PatchRegistry<S> {
+    fn drop(&mut self) {
+        if self.keep || self.patches.is_empty() {
+            return;
+        }
+        let count = self.patches.len();
+        match self.restore() {
+            Ok(()) => eprintln!("Restored {count} patch(es)"),
+            Err(e) => eprintln!("Failed to restore patches: {e}"),
+        }
+    }
+}
+
+/// A value change seen by [`poll_changes`]
+#[derive(Debug, Clone)]
+pub struct MemoryChange {
+    pub time: std::time::SystemTime,
+    pub elapsed: std::time::Duration,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// How long [`poll_changes`] waits between reads: a fixed interval, or an adaptive one
+/// that doubles after every read without a change, up to `max`, and drops back to `min`
+/// as soon as the value changes. Adaptive polling stays responsive while the value is
+/// busy and spends little CPU while it's idle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throttle {
+    min: std::time::Duration,
+    max: std::time::Duration,
+    current: std::time::Duration,
+}
+
+impl Throttle {
+    pub fn fixed(interval: std::time::Duration) -> Self {
+        Throttle { min: interval, max: interval, current: interval }
+    }
+
+    /// `max` below `min` is raised to `min`
+    pub fn adaptive(min: std::time::Duration, max: std::time::Duration) -> Self {
+        Throttle { min, max: max.max(min), current: min }
+    }
+
+    /// Time to wait before the next read
+    pub fn interval(&self) -> std::time::Duration {
+        self.current
+    }
+
+    /// Adjust to whether the last read saw a change
+    pub fn update(&mut self, changed: bool) {
+        self.current = if changed {
+            self.min
+        } else {
+            // doubling from zero needs a start
+            (self.current * 2).max(std::time::Duration::from_millis(1)).clamp(self.min, self.max)
+        };
+    }
+}
+
+impl From<std::time::Duration> for Throttle {
+    fn from(interval: std::time::Duration) -> Self {
+        Throttle::fixed(interval)
+    }
+}
+
+/// Read `size` bytes at `addr`, waiting `throttle` between reads, and call `on_change`
+/// whenever they differ from the previous read, until it returns `false`, `stop` is
+/// raised or a read fails. This is sampling: changes that are reverted between two reads
+/// are never seen.
+pub fn poll_changes<F>(
+    source: &dyn MemorySource,
+    addr: usize,
+    size: usize,
+    throttle: impl Into<Throttle>,
+    stop: &std::sync::atomic::AtomicBool,
+    mut on_change: F,
+) -> Result<(), MemoryError>
+where
+    F: FnMut(&MemoryChange) -> bool,
+{
+    poll_many_changes(source, &[(addr, size)], throttle, stop, |_, change| on_change(change))
+}
+
+/// [`poll_changes`] for every `(addr, size)` of `targets`, read one after the other on
+/// each poll. `on_change` also gets the index of the target that changed, and a change
+/// of any target resets an adaptive `throttle`.
+pub fn poll_many_changes<F>(
+    source: &dyn MemorySource,
+    targets: &[(usize, usize)],
+    throttle: impl Into<Throttle>,
+    stop: &std::sync::atomic::AtomicBool,
+    mut on_change: F,
+) -> Result<(), MemoryError>
+where
+    F: FnMut(usize, &MemoryChange) -> bool,
+{
+    let mut throttle = throttle.into();
+    let start = std::time::Instant::now();
+    let mut last = targets
+        .iter()
+        .map(|&(addr, size)| source.read(addr, size))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    loop {
+        if stop.load(std::sync::atomic::Ordering::SeqCst) {
+            return Ok(());
+        }
+        if !throttle.interval().is_zero() {
+            std::thread::sleep(throttle.interval());
+        }
+
+        let mut changed = false;
+        for (index, &(addr, size)) in targets.iter().enumerate() {
+            let current = source.read(addr, size)?;
+            if current == last[index] {
+                continue;
+            }
+            changed = true;
+            let change = MemoryChange {
+                time: std::time::SystemTime::now(),
+                elapsed: start.elapsed(),
+                old: std::mem::replace(&mut last[index], current.clone()),
+                new: current,
+            };
+            if !on_change(index, &change) {
+                return Ok(());
+            }
+        }
+        throttle.update(changed);
+    }
+}
+
+/// Values [`probe_value`] read at one address
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    /// Reads that succeeded
+    pub reads: usize,
+    /// Reads that failed, e.g. because the memory was unmapped meanwhile
+    pub failed: usize,
+    /// Distinct values and how often each was read, most frequent first
+    pub values: Vec<(Vec<u8>, usize)>,
+}
+
+impl Probe {
+    /// Whether every read succeeded and saw the same value
+    pub fn is_stable(&self) -> bool {
+        self.failed == 0 && self.values.len() == 1
+    }
+
+    /// Percentage of the successful reads that saw the most frequent value
+    pub fn stability(&self) -> f64 {
+        match (self.reads, self.values.first()) {
+            (0, _) | (_, None) => 0.0,
+            (reads, Some((_, count))) => *count as f64 * 100.0 / reads as f64,
+        }
+    }
+}
+
+/// Read `size` bytes at `addr` `samples` times, `interval` apart, and tally the values
+/// seen. Failed reads are counted, not returned, unless no read succeeds at all. Stops
+/// early once `stop` is raised.
+pub fn probe_value(
+    source: &dyn MemorySource,
+    addr: usize,
+    size: usize,
+    samples: usize,
+    interval: std::time::Duration,
+    stop: &std::sync::atomic::AtomicBool,
+) -> Result<Probe, MemoryError> {
+    let mut probe = Probe { reads: 0, failed: 0, values: vec![] };
+    let mut last_error = None;
+    for sample in 0..samples {
+        if stop.load(std::sync::atomic::Ordering::SeqCst) {
+            break;
+        }
+        if sample > 0 && !interval.is_zero() {
+            std::thread::sleep(interval);
+        }
+
+        match source.read(addr, size) {
+            Ok(value) => {
+                probe.reads += 1;
+                match probe.values.iter_mut().find(|(seen, _)| *seen == value) {
+                    Some((_, count)) => *count += 1,
+                    None => probe.values.push((value, 1)),
+                }
+            }
+            Err(e) => {
+                probe.failed += 1;
+                last_error = Some(e);
+            }
+        }
+    }
+
+    if let (0, Some(e)) = (probe.reads, last_error) {
+        return Err(e);
+    }
+    // stable sort, equally frequent values stay in the order they were first seen
+    probe.values.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    Ok(probe)
+}
+
+/// Default longest string [`read_cstring`] reads
+pub const DEFAULT_MAX_CSTRING_LEN: usize = 4096;
+
+/// Where [`read_cstring`] stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CStringEnd {
+    /// At the terminating null byte
+    Null,
+    /// At the length cap, without finding a null
+    MaxLength,
+    /// At memory that can't be read, e.g. the end of the region
+    Unreadable,
+}
+
+/// Read the null-terminated string at `addr`, at most `max` bytes of it, returning the
+/// bytes before the null. Reads go page by page, so a string that runs into unmapped
+/// memory ends there instead of failing; only a first byte that can't be read is an
+/// error.
+pub fn read_cstring(
+    source: &dyn MemorySource,
+    addr: u64,
+    max: usize,
+) -> Result<(Vec<u8>, CStringEnd), MemoryError> {
+    const PAGE_SIZE: u64 = 0x1000;
+
+    let mut bytes = vec![];
+    while bytes.len() < max {
+        let current = addr + bytes.len() as u64;
+        let to_page_end = (current / PAGE_SIZE + 1) * PAGE_SIZE - current;
+        let len = std::cmp::min(to_page_end, (max - bytes.len()) as u64) as usize;
+        // A dump region can end inside the page, fall back to single bytes to read up to it
+        let (chunk, readable) = match source.read(current as usize, len) {
+            Ok(chunk) => (chunk, true),
+            Err(_) => {
+                let mut chunk = vec![];
+                for i in 0..len as u64 {
+                    match source.read((current + i) as usize, 1) {
+                        Ok(byte) => chunk.push(byte[0]),
+                        Err(e) if bytes.is_empty() && chunk.is_empty() => return Err(e),
+                        Err(_) => break,
+                    }
+                    if chunk.last() == Some(&0) {
+                        break;
+                    }
+                }
+                let readable = chunk.len() == len;
+                (chunk, readable)
+            }
+        };
+        if let Some(null) = memchr::memchr(0, &chunk) {
+            bytes.extend_from_slice(&chunk[..null]);
+            return Ok((bytes, CStringEnd::Null));
+        }
+        bytes.extend_from_slice(&chunk);
+        if !readable {
+            return Ok((bytes, CStringEnd::Unreadable));
+        }
+    }
+    Ok((bytes, CStringEnd::MaxLength))
+}
+
+/// Digest of the readable part of an address range
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionHash {
+    pub digest: Vec<u8>,
+    /// Bytes that went into the digest
+    pub hashed: u64,
+    /// Bytes left out because they couldn't be read
+    pub unreadable: u64,
+}
+
+/// Hash the memory from `start` to `end` with `algo`. Pages that can't be read are left
+/// out of the digest and counted in [`RegionHash::unreadable`], so a range reaching past
+/// a mapping still hashes what is mapped; comparing digests only makes sense when the
+/// byte counts agree.
+pub fn hash_region(
+    source: &dyn MemorySource,
+    start: u64,
+    end: u64,
+    algo: HashAlgo,
+) -> Result<RegionHash, MemoryError> {
+    const PAGE_SIZE: u64 = 0x1000;
+    const BLOCK_SIZE: u64 = 0x10000;
+
+    let mut hasher = Hasher::new(algo);
+    let (mut hashed, mut unreadable) = (0, 0);
+    let mut address = start;
+    while address < end {
+        let len = std::cmp::min((address / BLOCK_SIZE + 1) * BLOCK_SIZE, end) - address;
+        match source.read(address as usize, len as usize) {
+            Ok(block) => {
+                hasher.update(&block);
+                hashed += len;
+            }
+            Err(e @ MemoryError::ProcessAttach(_)) => return Err(e),
+            // retry page by page to keep the readable part of the block
+            Err(_) => {
+                let block_end = address + len;
+                let mut page = address;
+                while page < block_end {
+                    let len = std::cmp::min((page / PAGE_SIZE + 1) * PAGE_SIZE, block_end) - page;
+                    match source.read(page as usize, len as usize) {
+                        Ok(bytes) => {
+                            hasher.update(&bytes);
+                            hashed += len;
+                        }
+                        Err(_) => unreadable += len,
+                    }
+                    page += len;
+                }
+            }
+        }
+        address += len;
+    }
+
+    Ok(RegionHash { digest: hasher.finish(), hashed, unreadable })
+}
+
+/// Why an array of consecutive values couldn't be read or written. Elements are
+/// numbered from 0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayError {
+    /// The element at `address` isn't inside a region with the needed permission
+    OutsideRegion { index: usize, address: u64 },
+    /// Accessing the element at `address` failed
+    Element {
+        index: usize,
+        address: u64,
+        error: MemoryError,
+    },
+    /// The element at `address` ends past the end of the address space
+    PastEnd { index: usize, address: u64 },
+}
+
+impl Display for ArrayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutsideRegion { index, address } => write!(
+                f,
+                "Element {index} at 0x{address:x} is outside the accessible memory regions"
+            ),
+            Self::Element { index, address, error } => {
+                write!(f, "Element {index} at 0x{address:x}: {error}")
+            }
+            Self::PastEnd { index, address } => {
+                write!(f, "Element {index} at 0x{address:x} ends past the end of the address space")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArrayError {}
+
+/// The address ranges of a source's regions with one permission, listed once to check
+/// many addresses against, see [`offset_address`]
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Sorted, with overlapping and adjacent regions merged
+    spans: Vec<(u64, u64)>,
+}
+
+impl Coverage {
+    /// The regions of `source` with `perm`
+    pub fn new(source: &dyn MemorySource, perm: MemoryRegionPerms) -> Result<Coverage, MemoryError> {
+        Ok(Coverage::of(source.regions(None, None, Some(&[perm]))?))
+    }
+
+    fn of(mut regions: Vec<MemoryRegion>) -> Coverage {
+        regions.sort_by_key(|r| r.start);
+        let mut spans: Vec<(u64, u64)> = vec![];
+        for region in regions {
+            match spans.last_mut() {
+                Some((_, end)) if region.start <= *end => *end = (*end).max(region.end),
+                _ => spans.push((region.start, region.end)),
+            }
+        }
+        Coverage { spans }
+    }
+
+    /// Offset of the first byte of `addr..addr + len` outside the regions, `None` if
+    /// they cover all of it. A range running past the end of the address space is
+    /// uncovered from there.
+    pub fn uncovered_offset(&self, addr: u64, len: u64) -> Option<u64> {
+        // the span holding `addr` is the last one starting at or before it
+        let covered = match self.spans.partition_point(|&(start, _)| start <= addr).checked_sub(1) {
+            Some(i) if self.spans[i].1 > addr => self.spans[i].1,
+            _ => addr,
+        };
+        match addr.checked_add(len) {
+            Some(end) if covered >= end => None,
+            _ => Some(covered - addr),
+        }
+    }
+}
+
+/// Check that `addr..addr + len` is covered by regions with `perm`, returning the offset
+/// of the first byte that isn't
+fn uncovered_offset(
+    source: &dyn MemorySource,
+    addr: u64,
+    len: u64,
+    perm: MemoryRegionPerms,
+) -> Result<Option<u64>, MemoryError> {
+    let last = addr.saturating_add(len).saturating_sub(1);
+    let regions = source.regions(Some(addr), Some(last), Some(&[perm]))?;
+    Ok(Coverage::of(regions).uncovered_offset(addr, len))
+}
+
+/// Why [`offset_address`] refused an address
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffsetError {
+    /// `address + offset` is below 0 or above the address space
+    Overflow { address: u64, offset: i64 },
+    /// The bytes at `target` aren't inside a region with the needed permission
+    Unmapped { address: u64, offset: i64, target: u64 },
+    Memory(MemoryError),
+}
+
+/// `+0x10` or `-0x8`
+pub fn format_offset(offset: i64) -> String {
+    match offset < 0 {
+        true => format!("-0x{:x}", offset.unsigned_abs()),
+        false => format!("+0x{offset:x}"),
+    }
+}
+
+impl Display for OffsetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow { address, offset } => {
+                write!(f, "0x{address:x}{} is outside the address space", format_offset(*offset))
+            }
+            Self::Unmapped { address, offset, target } => write!(
+                f,
+                "0x{address:x}{} = 0x{target:x} is outside the accessible memory regions",
+                format_offset(*offset)
+            ),
+            Self::Memory(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for OffsetError {}
+
+/// `address` moved by the signed `offset`, e.g. from a struct base found by a scan to a
+/// field of it. The `size` bytes there have to be inside `coverage`, the regions with the
+/// needed permission.
+pub fn offset_address(coverage: &Coverage, address: u64, offset: i64, size: usize) -> Result<u64, OffsetError> {
+    let target = address.checked_add_signed(offset).ok_or(OffsetError::Overflow { address, offset })?;
+    match coverage.uncovered_offset(target, size.max(1) as u64) {
+        None => Ok(target),
+        Some(_) => Err(OffsetError::Unmapped { address, offset, target }),
+    }
+}
+
+/// Read `count` consecutive values of `size` bytes at `addr`. The whole array has to be
+/// in readable regions; when the single read of the array fails, the elements are read
+/// one by one to report the first one that can't be read.
+pub fn read_array(
+    source: &dyn MemorySource,
+    addr: u64,
+    size: usize,
+    count: usize,
+) -> Result<Vec<Vec<u8>>, ArrayError> {
+    let element = |index: usize| addr + (index * size) as u64;
+    let Some(len) = size.checked_mul(count).filter(|&len| addr.checked_add(len as u64).is_some()) else {
+        // the first element that doesn't fit
+        let index = ((u64::MAX - addr) / size.max(1) as u64) as usize;
+        return Err(ArrayError::PastEnd { index, address: element(index) });
+    };
+    let len = len as u64;
+    let outside = uncovered_offset(source, addr, len, MemoryRegionPerms::Read)
+        .map_err(|error| ArrayError::Element { index: 0, address: addr, error })?;
+    if let Some(offset) = outside {
+        let index = offset as usize / size;
+        return Err(ArrayError::OutsideRegion { index, address: element(index) });
+    }
+
+    match source.read(addr as usize, len as usize) {
+        Ok(bytes) => Ok(bytes.chunks_exact(size).map(<[u8]>::to_vec).collect()),
+        Err(_) => (0..count)
+            .map(|index| {
+                source
+                    .read(element(index) as usize, size)
+                    .map_err(|error| ArrayError::Element { index, address: element(index), error })
+            })
+            .collect(),
+    }
+}
+
+/// Write `values` back to back starting at `addr`. The whole array has to be in writable
+/// regions; when the single write of the array fails, the elements are written one by one
+/// to report the first one that can't be written (the ones before it are written).
+pub fn write_array(
+    source: &dyn MemorySource,
+    addr: u64,
+    values: &[Vec<u8>],
+) -> Result<(), ArrayError> {
+    let mut addresses = Vec::with_capacity(values.len());
+    let mut next = addr;
+    for (index, value) in values.iter().enumerate() {
+        addresses.push(next);
+        next = next.checked_add(value.len() as u64).ok_or(ArrayError::PastEnd { index, address: next })?;
+    }
+    let index_at = |address: u64| addresses.iter().rposition(|&a| a <= address).unwrap_or(0);
+
+    let outside = uncovered_offset(source, addr, next - addr, MemoryRegionPerms::Write)
+        .map_err(|error| ArrayError::Element { index: 0, address: addr, error })?;
+    if let Some(offset) = outside {
+        let index = index_at(addr + offset);
+        return Err(ArrayError::OutsideRegion { index, address: addresses[index] });
+    }
+
+    if source.write(addr as usize, &values.concat()).is_ok() {
+        return Ok(());
+    }
+    for (index, (value, &address)) in values.iter().zip(&addresses).enumerate() {
+        source
+            .write(address as usize, value)
+            .map_err(|error| ArrayError::Element { index, address, error })?;
+    }
+    Ok(())
+}
+
+/// What [`apply_op`] does to the value it reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WriteOp {
+    Add(f64),
+    Sub(f64),
+    Mul(f64),
+    Div(f64),
+    Half,
+    /// The largest value of the type
+    SetMax,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpError {
+    /// Operations need an integer or float type
+    NotNumeric(ValueType),
+    DivideByZero,
+    /// The operand is NaN or infinite, which no integer and hardly any game value can take
+    NonFinite(f64),
+    Memory(MemoryError),
+}
+
+impl Display for OpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotNumeric(value_type) => {
+                let names: Vec<&str> = OP_TYPES.iter().map(ValueType::name).collect();
+                write!(f, "--op needs an integer or float type ({}), not {}", names.join(", "), value_type.name())
+            }
+            Self::DivideByZero => write!(f, "Can't divide by zero"),
+            Self::NonFinite(by) => write!(f, "--by must be a finite number, not {by}"),
+            Self::Memory(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for OpError {}
+
+/// Types [`WriteOp`]s work on
+const OP_TYPES: [ValueType; 6] =
+    [ValueType::U32, ValueType::I32, ValueType::U64, ValueType::I64, ValueType::F32, ValueType::F64];
+
+/// The value an [`apply_op`] replaced and the one it wrote
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpWrite {
+    pub previous: Vec<u8>,
+    pub new: Vec<u8>,
+    /// The result didn't fit the type and was clamped to its smallest or largest value
+    pub saturated: bool,
+}
+
+/// The bytes of `op` applied to the `value_type` value in `current`. Integer results
+/// that don't fit the type saturate at its limits and fractions are cut off (`half` of
+/// -3 is -1), floats follow IEEE rules.
+pub fn op_result(value_type: ValueType, current: &[u8], op: WriteOp) -> Result<OpWrite, OpError> {
+    match op {
+        WriteOp::Add(by) | WriteOp::Sub(by) | WriteOp::Mul(by) | WriteOp::Div(by) if !by.is_finite() => {
+            return Err(OpError::NonFinite(by));
+        }
+        WriteOp::Div(0.0) => return Err(OpError::DivideByZero),
+        _ => {}
+    }
+    let size = value_type.get_size() as usize;
+    let current = current.get(..size).filter(|_| size > 0).ok_or(OpError::NotNumeric(value_type))?;
+    let float = |value: f64, max: f64| match op {
+        WriteOp::Add(by) => value + by,
+        WriteOp::Sub(by) => value - by,
+        WriteOp::Mul(by) => value * by,
+        WriteOp::Div(by) => value / by,
+        WriteOp::Half => value / 2.0,
+        WriteOp::SetMax => max,
+    };
+    // integer operands are applied exactly, fractional ones (mul 1.5) through a float
+    let integer = |value: i128, (min, max): (i128, i128)| {
+        let exact = |by: f64| (by.fract() == 0.0 && by.abs() < 2_f64.powi(100)).then_some(by as i128);
+        let result = match op {
+            WriteOp::Add(by) => exact(by).map(|by| value + by),
+            WriteOp::Sub(by) => exact(by).map(|by| value - by),
+            WriteOp::Mul(by) => exact(by).and_then(|by| value.checked_mul(by)),
+            WriteOp::Div(by) => exact(by).map(|by| value / by),
+            WriteOp::Half => Some(value / 2),
+            WriteOp::SetMax => Some(max),
+        };
+        // `as` saturates, out of range floats become i128::MIN/MAX
+        let result = result.unwrap_or_else(|| float(value as f64, max as f64) as i128);
+        (result.clamp(min, max), !(min..=max).contains(&result))
+    };
+
+    let (new, saturated) = match value_type {
+        ValueType::U32 => {
+            let (v, s) = integer(u32::from_le_bytes(current.try_into().unwrap()) as i128, (0, u32::MAX as i128));
+            ((v as u32).to_le_bytes().to_vec(), s)
+        }
+        ValueType::I32 => {
+            let value = i32::from_le_bytes(current.try_into().unwrap()) as i128;
+            let (v, s) = integer(value, (i32::MIN as i128, i32::MAX as i128));
+            ((v as i32).to_le_bytes().to_vec(), s)
+        }
+        ValueType::U64 => {
+            let (v, s) = integer(u64::from_le_bytes(current.try_into().unwrap()) as i128, (0, u64::MAX as i128));
+            ((v as u64).to_le_bytes().to_vec(), s)
+        }
+        ValueType::I64 => {
+            let value = i64::from_le_bytes(current.try_into().unwrap()) as i128;
+            let (v, s) = integer(value, (i64::MIN as i128, i64::MAX as i128));
+            ((v as i64).to_le_bytes().to_vec(), s)
+        }
+        ValueType::F32 => {
+            let value = f32::from_le_bytes(current.try_into().unwrap()) as f64;
+            ((float(value, f32::MAX as f64) as f32).to_le_bytes().to_vec(), false)
+        }
+        ValueType::F64 => {
+            let value = f64::from_le_bytes(current.try_into().unwrap());
+            (float(value, f64::MAX).to_le_bytes().to_vec(), false)
+        }
+        _ => return Err(OpError::NotNumeric(value_type)),
+    };
+    Ok(OpWrite { previous: current.to_vec(), new, saturated })
+}
+
+/// Read the `value_type` value at `addr`, apply `op` to it and write the result back,
+/// see [`op_result`]
+pub fn apply_op(
+    source: &dyn MemorySource,
+    addr: u64,
+    value_type: ValueType,
+    op: WriteOp,
+) -> Result<OpWrite, OpError> {
+    // checked before reading, a string type has no size to read
+    if !OP_TYPES.contains(&value_type) {
+        return Err(OpError::NotNumeric(value_type));
+    }
+    let current = source.read(addr as usize, value_type.get_size() as usize).map_err(OpError::Memory)?;
+    let write = op_result(value_type, &current, op)?;
+    source.write(addr as usize, &write.new).map_err(OpError::Memory)?;
+    Ok(write)
+}
+
+/// The largest field of a struct schema, a bigger `count` is most likely a typo
+pub const MAX_STRUCT_FIELD_SIZE: usize = 1 << 20;
+
+/// Why a struct schema or a field of the struct it describes couldn't be read. Fields
+/// are numbered from 0, in messages too.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructError {
+    Json(JsonError),
+    /// The schema is valid JSON but not an array of fields or an object with them
+    InvalidSchema(String),
+    /// Field `index` doesn't have the expected shape
+    InvalidField { index: usize, message: String },
+    /// `field` starts before `previous` ends, which needs `allow_overlap`
+    Overlap { field: String, previous: String },
+    /// Reading `field` at `address` failed
+    Unreadable { field: String, address: u64, error: MemoryError },
+    /// `field` of the struct at `base` would end past the end of the address space
+    OutOfRange { field: String, base: u64 },
+}
+
+impl Display for StructError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Invalid schema: {e}"),
+            Self::InvalidSchema(message) => write!(f, "Invalid schema: {message}"),
+            Self::InvalidField { index, message } => write!(f, "Invalid field #{index}: {message}"),
+            Self::Overlap { field, previous } => write!(
+                f,
+                "Field '{field}' starts inside or before '{previous}', order the fields by offset \
+                 or set \"allow_overlap\": true"
+            ),
+            Self::Unreadable { field, address, error } => {
+                write!(f, "Field '{field}' at 0x{address:x} can't be read: {error}")
+            }
+            Self::OutOfRange { field, base } => {
+                write!(f, "Field '{field}' of the struct at 0x{base:x} ends past the end of memory")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StructError {}
+
+/// A field of a struct schema, see [`parse_struct_schema`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub name: String,
+    /// Bytes from the start of the struct
+    pub offset: u64,
+    pub value_type: ValueType,
+    /// Number of consecutive values, 1 for strings and hex
+    pub count: usize,
+    /// Bytes of each value, the length of strings and hex
+    pub element_size: usize,
+}
+
+impl StructField {
+    /// Bytes of the whole field, saturating for fields not checked by
+    /// [`parse_struct_schema`]
+    pub fn size(&self) -> usize {
+        self.element_size.saturating_mul(self.count)
+    }
+}
+
+/// Parse a struct schema, JSON with the fields of the struct in offset order:
+///
+/// ```text
+/// { "allow_overlap": false, "fields": [
+///     { "name": "health", "offset": "0x10", "type": "i32" },
+///     { "name": "position", "offset": "0x20", "type": "f32", "count": 3 },
+///     { "name": "name", "offset": "0x40", "type": "string", "count": 16 }
+/// ] }
+/// ```
+///
+/// A bare array of fields works too. Offsets are numbers or strings, which may be `0x`
+/// hex. `count` is the number of values (1 by default), or the byte length of strings and
+/// hex, which need one, and no field may be larger than [`MAX_STRUCT_FIELD_SIZE`]. `type` is resolved with `parse_type` and pointers are
+/// `pointer_size` bytes. Fields must not overlap unless `allow_overlap` is set, e.g. for
+/// the members of a union.
+pub fn parse_struct_schema(
+    text: &str,
+    pointer_size: usize,
+    parse_type: impl Fn(&str) -> Option<ValueType>,
+) -> Result<Vec<StructField>, StructError> {
+    let schema = parse_json(text).map_err(StructError::Json)?;
+    let (items, allow_overlap) = match &schema {
+        Json::Array(items) => (items, false),
+        Json::Object(_) => match (schema.field("fields"), schema.field("allow_overlap")) {
+            (Some(Json::Array(items)), None | Some(Json::Bool(false))) => (items, false),
+            (Some(Json::Array(items)), Some(Json::Bool(true))) => (items, true),
+            (Some(Json::Array(_)), Some(_)) => {
+                return Err(StructError::InvalidSchema(String::from("\"allow_overlap\" must be true or false")));
+            }
+            _ => {
+                return Err(StructError::InvalidSchema(String::from("expected a \"fields\" array")));
+            }
+        },
+        _ => {
+            return Err(StructError::InvalidSchema(String::from("expected an array of fields or an object with \"fields\"")));
+        }
+    };
+
+    let mut fields: Vec<StructField> = vec![];
+    for (index, item) in items.iter().enumerate() {
+        let invalid = |message: String| StructError::InvalidField { index, message };
+        if !matches!(item, Json::Object(_)) {
+            return Err(invalid(String::from("expected an object")));
+        }
+        let name = match item.field("name") {
+            Some(Json::String(name)) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return Err(invalid(String::from("missing \"name\""))),
+        };
+        let invalid = |message: &str| StructError::InvalidField { index, message: format!("'{name}' {message}") };
+        let offset = match item.field("offset").map(Json::integer) {
+            Some(Some(offset)) if offset >= 0 => offset as u64,
+            Some(_) => return Err(invalid("needs a positive integer \"offset\"")),
+            None => return Err(invalid("is missing \"offset\"")),
+        };
+        let value_type = match item.field("type") {
+            Some(Json::String(name)) => parse_type(name.trim()).ok_or_else(|| invalid("has an unknown \"type\""))?,
+            _ => return Err(invalid("is missing \"type\"")),
+        };
+        let count = match item.field("count").map(Json::integer) {
+            None => None,
+            Some(Some(count)) if count > 0 => Some(count as usize),
+            Some(_) => return Err(invalid("needs a \"count\" of at least 1")),
+        };
+        let (count, element_size) = match value_type {
+            ValueType::String | ValueType::Hex => match count {
+                Some(len) => (1, len),
+                None => return Err(invalid("needs a \"count\", the byte length of strings and hex")),
+            },
+            ValueType::Pointer => (count.unwrap_or(1), pointer_size),
+            _ => match value_type.get_size() as usize {
+                0 => return Err(invalid("has a type without a fixed size")),
+                size => (count.unwrap_or(1), size),
+            },
+        };
+        if element_size.checked_mul(count).is_none_or(|size| size > MAX_STRUCT_FIELD_SIZE) {
+            return Err(invalid("is larger than 1 MiB, check its \"count\""));
+        }
+        let field = StructField { name, offset, value_type, count, element_size };
+        if let Some(previous) = fields.last()
+            && !allow_overlap
+            && field.offset < previous.offset.saturating_add(previous.size() as u64)
+        {
+            return Err(StructError::Overlap { field: field.name, previous: previous.name.clone() });
+        }
+        fields.push(field);
+    }
+    Ok(fields)
+}
+
+/// Read each of `fields` of the struct at `base`. Every field is read on its own, so the
+/// fields before and after one that can't be read still are.
+pub fn read_struct(
+    source: &dyn MemorySource,
+    base: u64,
+    fields: &[StructField],
+) -> Vec<Result<Vec<u8>, StructError>> {
+    fields
+        .iter()
+        .map(|field| {
+            let Some(address) = base.checked_add(field.offset).filter(|a| a.checked_add(field.size() as u64).is_some())
+            else {
+                return Err(StructError::OutOfRange { field: field.name.clone(), base });
+            };
+            let unreadable = |error| StructError::Unreadable { field: field.name.clone(), address, error };
+            match source.read(address as usize, field.size()) {
+                Ok(bytes) if bytes.len() == field.size() => Ok(bytes),
+                Ok(bytes) => Err(unreadable(MemoryError::Unmapped(address + bytes.len() as u64))),
+                Err(error) => Err(unreadable(error)),
+            }
+        })
+        .collect()
+}
+
+/// How writing a value to one address of [`write_each`] went
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteStatus {
+    Written,
+    /// Written and read back unchanged
+    Verified,
+    /// Written, but reading back gave these bytes, e.g. the target reset the value
+    Mismatch(Vec<u8>),
+    Failed(MemoryError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOutcome {
+    pub address: u64,
+    /// Bytes at the address before the write, `None` when they couldn't be read
+    pub previous: Option<Vec<u8>>,
+    pub status: WriteStatus,
+}
+
+/// Write `value` to each of `addresses`, going on after the ones that fail. With `verify`
+/// every written address is read back.
+pub fn write_each(
+    source: &dyn MemorySource,
+    addresses: &[u64],
+    value: &[u8],
+    verify: bool,
+) -> Vec<WriteOutcome> {
+    addresses
+        .iter()
+        .map(|&address| {
+            let previous = source.read(address as usize, value.len()).ok();
+            let status = match source.write(address as usize, value) {
+                Err(e) => WriteStatus::Failed(e),
+                Ok(()) if !verify => WriteStatus::Written,
+                Ok(()) => match source.read(address as usize, value.len()) {
+                    Ok(read_back) if read_back == value => WriteStatus::Verified,
+                    Ok(read_back) => WriteStatus::Mismatch(read_back),
+                    Err(e) => WriteStatus::Failed(e),
+                },
+            };
+            WriteOutcome { address, previous, status }
+        })
+        .collect()
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use std::process::{Command, Stdio};
+
+    #[test]
+    pub fn test_get_regions_error() {
+        let result = get_memory_regions(0, None, None, None);
+
+        assert!(result.is_err());
+
+        if let Err(e) = result {
+            match e {
+                MemoryError::NoPermission(_) => assert!(true),
+                _ => assert!(false),
+            }
+        } else {
+            assert!(false)
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_get_regions_success() {
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+                let regions = get_memory_regions(proc.0.id(), None, None, None);
+                assert!(regions.is_ok());
+                let regions = regions.unwrap();
+                assert_ne!(regions.len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_get_regions_readonly_success() {
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+                let regions =
+                    get_memory_regions(proc.0.id(), None, None, Some(&[MemoryRegionPerms::Read]));
+                assert!(regions.is_ok());
+                let regions = regions.unwrap();
+                assert_ne!(regions.len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_get_regions_with_range_success() {
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+                let regions = get_memory_regions(proc.0.id(), Some(u64::MAX), None, None);
+                assert!(regions.is_ok());
+                let regions = regions.unwrap();
+                assert_eq!(regions.len(), 0);
+
+                let regions = get_memory_regions(proc.0.id(), None, Some(0), None);
+                assert!(regions.is_ok());
+                let regions = regions.unwrap();
+                assert_eq!(regions.len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_addr_to_offset() {
+        use crate::core::proc::{Mapping, Module};
+
+        let mapping = |start, end, path: &str| Mapping { start, end, path: path.to_string() };
+        let mappings = [
+            mapping(0x1000, 0x2000, "/usr/lib/libgame.so"),
+            mapping(0x2000, 0x4000, "/usr/lib/libgame.so"),
+            mapping(0x4000, 0x5000, ""),
+            mapping(0x8000, 0x9000, "[heap]"),
+        ];
+        let modules = [Module {
+            name: String::from("libgame.so"),
+            path: "/usr/lib/libgame.so".into(),
+            base: 0x1000,
+            end: 0x4000,
+        }];
+        let offset = |address| relative_address(&mappings, &modules, address).map(|r| r.to_string());
+        // the second mapping of a file is still relative to the file's base
+        assert_eq!(offset(0x3234).as_deref(), Some("libgame.so+0x2234"));
+        assert_eq!(offset(0x4010).as_deref(), Some("[anon]"));
+        assert_eq!(offset(0x8010).as_deref(), Some("[heap]+0x10"));
+        assert_eq!(offset(0x5000), None);
+
+        let pid = std::process::id();
+        assert_eq!(addr_to_offset(pid, 0x8), Err(MemoryError::Unmapped(0x8)));
+        static MARKER: u32 = 7;
+        let exe = std::env::current_exe().unwrap();
+        let exe = exe.file_name().unwrap().to_string_lossy();
+        let in_exe = addr_to_offset(pid, &MARKER as *const u32 as u64).unwrap();
+        assert!(in_exe.starts_with(&format!("{exe}+0x")), "{in_exe}");
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_read_memory_address_success() {
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
+        let value = u32::from_le_bytes(value.try_into().unwrap());
+        assert_eq!(value, 31337_u32);
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_write_memory_address_success() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let mut stdin = proc.0.stdin.take().expect("child has no stdin");
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        reader.read_line(&mut line).unwrap(); // consume readonly address
+        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
+        let value = u32::from_le_bytes(value.try_into().unwrap());
+        assert_eq!(value, 31337_u32);
+
+        write_memory_address(proc.0.id(), address, &99999_u32.to_le_bytes().to_vec()).unwrap();
+        let value = read_memory_address(proc.0.id(), address, 4).unwrap();
+        let value = u32::from_le_bytes(value.try_into().unwrap());
+
+        writeln!(stdin, "read").unwrap();
+        stdin.flush().unwrap();
+
+        let mut response = String::new();
+        reader.read_line(&mut response).unwrap();
+        let response_value: u32 = response
+            .trim()
+            .parse()
+            .expect("failed to parse child response as u32");
+
+        assert_eq!(value, 99999);
+        assert_eq!(value, response_value);
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_process_handle_read_write_and_exit() {
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        let handle = ProcessHandle::open(proc.0.id()).unwrap();
+        assert!(handle.is_alive());
+        assert!(!handle.regions(None, None, None).unwrap().is_empty());
+
+        let value = handle.read(address, 4).unwrap();
+        assert_eq!(u32::from_le_bytes(value.try_into().unwrap()), 31337_u32);
+
+        handle.write(address, &424242_u32.to_le_bytes()).unwrap();
+        let value = handle.read(address, 4).unwrap();
+        assert_eq!(u32::from_le_bytes(value.try_into().unwrap()), 424242_u32);
+
+        proc.0.kill().unwrap();
+        proc.0.wait().unwrap();
+
+        assert!(!handle.is_alive());
+        assert_eq!(handle.read(address, 4), Err(MemoryError::ProcessExited));
+    }
+
+    #[test]
+    pub fn test_poll_changes_reports_each_change() {
+        use crate::core::utils::FakeMemory;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x1000, 0_u32.to_le_bytes().to_vec(), vec![MemoryRegionPerms::Write]);
+
+        let writer = {
+            let memory = memory.clone();
+            std::thread::spawn(move || {
+                for i in 1..=5_u32 {
+                    std::thread::sleep(Duration::from_millis(20));
+                    memory.write(0x1000, &i.to_le_bytes()).unwrap();
+                }
+            })
+        };
+
+        let mut changes = vec![];
+        let stop = std::sync::atomic::AtomicBool::new(false);
+        poll_changes(memory.as_ref(), 0x1000, 4, Duration::from_millis(1), &stop, |change| {
+            changes.push(change.clone());
+            change.new != 5_u32.to_le_bytes()
+        })
+        .unwrap();
+        writer.join().unwrap();
+
+        assert!(!changes.is_empty());
+        assert_eq!(changes[0].old, 0_u32.to_le_bytes());
+        assert_eq!(changes.last().unwrap().new, 5_u32.to_le_bytes());
+        for pair in changes.windows(2) {
+            assert_eq!(pair[0].new, pair[1].old);
+            assert!(pair[0].elapsed <= pair[1].elapsed);
+        }
+    }
+
+    #[test]
+    pub fn test_poll_many_changes_names_the_target() {
+        use crate::core::utils::FakeMemory;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x1000, vec![0_u8; 16], vec![MemoryRegionPerms::Write]);
+
+        let writer = {
+            let memory = memory.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                memory.write(0x1008, &[1]).unwrap();
+                std::thread::sleep(Duration::from_millis(20));
+                memory.write(0x1000, &[2]).unwrap();
+            })
+        };
+
+        let mut changed = vec![];
+        let stop = std::sync::atomic::AtomicBool::new(false);
+        let targets = [(0x1000, 4), (0x1008, 4)];
+        poll_many_changes(memory.as_ref(), &targets, Duration::from_millis(1), &stop, |i, change| {
+            changed.push((i, change.new[0]));
+            changed.len() < 2
+        })
+        .unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(changed, vec![(1, 1), (0, 2)]);
+    }
+
+    #[test]
+    pub fn test_poll_changes_sleeps_the_interval() {
+        use crate::core::utils::FakeMemory;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::time::{Duration, Instant};
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![0_u8; 4], vec![MemoryRegionPerms::Write]);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                stop.store(true, Ordering::SeqCst);
+            })
+        };
+
+        let started = Instant::now();
+        poll_changes(&memory, 0x1000, 4, Duration::from_millis(20), &stop, |_| true).unwrap();
+        stopper.join().unwrap();
+        // one read up front and one per 20ms sleep, never more
+        let reads = memory.read_count();
+        assert!((3..=12).contains(&reads), "{reads} reads in {:?}", started.elapsed());
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    pub fn test_adaptive_throttle() {
+        use std::time::Duration;
+
+        let ms = Duration::from_millis;
+        let mut throttle = Throttle::adaptive(ms(5), ms(40));
+        assert_eq!(throttle.interval(), ms(5));
+        let mut intervals = vec![];
+        for _ in 0..5 {
+            throttle.update(false);
+            intervals.push(throttle.interval());
+        }
+        assert_eq!(intervals, vec![ms(10), ms(20), ms(40), ms(40), ms(40)]);
+        throttle.update(true);
+        assert_eq!(throttle.interval(), ms(5));
+
+        let mut throttle = Throttle::adaptive(Duration::ZERO, ms(4));
+        throttle.update(false);
+        assert_eq!(throttle.interval(), ms(1));
+        throttle.update(true);
+        assert_eq!(throttle.interval(), Duration::ZERO);
+
+        let mut fixed = Throttle::from(ms(7));
+        fixed.update(false);
+        assert_eq!(fixed.interval(), ms(7));
+    }
+
+    #[test]
+    pub fn test_probe_value() {
+        use crate::core::utils::FakeMemory;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        // 1 for three reads, a failed read, then 2
+        #[derive(Debug, Default)]
+        struct Changing(AtomicUsize);
+        impl MemorySource for Changing {
+            fn read(&self, _addr: usize, _size: usize) -> Result<Vec<u8>, MemoryError> {
+                match self.0.fetch_add(1, Ordering::SeqCst) {
+                    0..3 => Ok(vec![1]),
+                    3 => Err(MemoryError::MemRead(5)),
+                    _ => Ok(vec![2]),
+                }
+            }
+            fn write(&self, _addr: usize, _value: &[u8]) -> Result<(), MemoryError> {
+                Err(MemoryError::MemWrite(0))
+            }
+            fn regions(
+                &self,
+                _start: Option<u64>,
+                _end: Option<u64>,
+                _search_perms: Option<&[MemoryRegionPerms]>,
+            ) -> Result<Vec<MemoryRegion>, MemoryError> {
+                Ok(vec![])
+            }
+        }
+
+        let stop = AtomicBool::new(false);
+        let probe = probe_value(&Changing::default(), 0x1000, 1, 10, Duration::ZERO, &stop).unwrap();
+        assert_eq!(probe.reads, 9);
+        assert_eq!(probe.failed, 1);
+        assert_eq!(probe.values, vec![(vec![2], 6), (vec![1], 3)]);
+        assert!(!probe.is_stable());
+        assert!((probe.stability() - 66.67).abs() < 0.01);
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![7; 4], vec![MemoryRegionPerms::Read]);
+        let probe = probe_value(&memory, 0x1000, 4, 5, Duration::ZERO, &stop).unwrap();
+        assert!(probe.is_stable());
+        assert_eq!(probe.stability(), 100.0);
+        assert!(probe_value(&memory, 0x2000, 4, 5, Duration::ZERO, &stop).is_err());
+    }
+
+    #[test]
+    pub fn test_poll_changes_stops_on_read_error() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let stop = std::sync::atomic::AtomicBool::new(false);
+        let result = poll_changes(&memory, 0x1000, 4, std::time::Duration::ZERO, &stop, |_| true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn test_offset_address_within_the_regions() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![0; 0x100], vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        memory.add_region(0x2000, vec![0; 0x100], vec![MemoryRegionPerms::Read]);
+        memory.add_region(0x2100, vec![0; 0x100], vec![MemoryRegionPerms::Read]);
+        let read = Coverage::new(&memory, MemoryRegionPerms::Read).unwrap();
+        let write = Coverage::new(&memory, MemoryRegionPerms::Write).unwrap();
+
+        assert_eq!(offset_address(&read, 0x1000, 0x10, 4), Ok(0x1010));
+        assert_eq!(offset_address(&read, 0x1080, -0x80, 4), Ok(0x1000));
+        // past the region, or running out of it
+        assert_eq!(
+            offset_address(&read, 0x1000, -0x10, 4),
+            Err(OffsetError::Unmapped { address: 0x1000, offset: -0x10, target: 0xff0 })
+        );
+        assert!(matches!(offset_address(&read, 0x1000, 0xfe, 4), Err(OffsetError::Unmapped { .. })));
+        assert_eq!(
+            offset_address(&read, 0x10, -0x20, 4),
+            Err(OffsetError::Overflow { address: 0x10, offset: -0x20 })
+        );
+        // mapped, but not writable
+        assert_eq!(offset_address(&read, 0x1f00, 0x100, 4), Ok(0x2000));
+        assert!(offset_address(&write, 0x1f00, 0x100, 4).is_err());
+        // adjacent regions are merged, a read may span both
+        assert_eq!(read.uncovered_offset(0x20f0, 0x20), None);
+        assert_eq!(read.uncovered_offset(0x10f0, 0x20), Some(0x10));
+        assert_eq!(read.uncovered_offset(0x1000, 0x100), None);
+        assert_eq!(read.uncovered_offset(0x800, 4), Some(0));
+        assert_eq!(read.uncovered_offset(u64::MAX - 1, 4), Some(0));
+        assert_eq!(
+            OffsetError::Unmapped { address: 0x1000, offset: -0x10, target: 0xff0 }.to_string(),
+            "0x1000-0x10 = 0xff0 is outside the accessible memory regions"
+        );
+    }
+
+    #[test]
+    pub fn test_apply_op_to_the_current_value() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x20];
+        data[0..4].copy_from_slice(&1000_u32.to_le_bytes());
+        data[4..8].copy_from_slice(&(-3_i32).to_le_bytes());
+        data[8..12].copy_from_slice(&1.5_f32.to_le_bytes());
+        data[16..24].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let read = |addr: usize, size: usize| memory.read(addr, size).unwrap();
+
+        let write = apply_op(&memory, 0x1000, ValueType::U32, WriteOp::Mul(2.0)).unwrap();
+        assert_eq!((write.previous, write.saturated), (1000_u32.to_le_bytes().to_vec(), false));
+        assert_eq!(read(0x1000, 4), 2000_u32.to_le_bytes());
+        apply_op(&memory, 0x1000, ValueType::U32, WriteOp::Mul(1.5)).unwrap();
+        assert_eq!(read(0x1000, 4), 3000_u32.to_le_bytes());
+        // below 0 saturates at the type's minimum
+        assert!(apply_op(&memory, 0x1000, ValueType::U32, WriteOp::Sub(5000.0)).unwrap().saturated);
+        assert_eq!(read(0x1000, 4), 0_u32.to_le_bytes());
+
+        apply_op(&memory, 0x1004, ValueType::I32, WriteOp::Half).unwrap();
+        assert_eq!(read(0x1004, 4), (-1_i32).to_le_bytes());
+        apply_op(&memory, 0x1004, ValueType::I32, WriteOp::SetMax).unwrap();
+        assert_eq!(read(0x1004, 4), i32::MAX.to_le_bytes());
+        apply_op(&memory, 0x1008, ValueType::F32, WriteOp::Add(0.25)).unwrap();
+        assert_eq!(read(0x1008, 4), 1.75_f32.to_le_bytes());
+        // exact above 2^53, where a float would round
+        assert!(apply_op(&memory, 0x1010, ValueType::U64, WriteOp::Add(2.0)).unwrap().saturated);
+        assert_eq!(read(0x1010, 8), u64::MAX.to_le_bytes());
+        apply_op(&memory, 0x1010, ValueType::U64, WriteOp::Sub(1.0)).unwrap();
+        assert_eq!(read(0x1010, 8), (u64::MAX - 1).to_le_bytes());
+
+        assert_eq!(
+            apply_op(&memory, 0x1000, ValueType::String, WriteOp::Half),
+            Err(OpError::NotNumeric(ValueType::String))
+        );
+        assert_eq!(apply_op(&memory, 0x1000, ValueType::U32, WriteOp::Div(0.0)), Err(OpError::DivideByZero));
+        // NaN would become 0 in an integer, infinity its limit
+        let before = read(0x1000, 4);
+        assert!(matches!(
+            apply_op(&memory, 0x1000, ValueType::U32, WriteOp::Add(f64::NAN)),
+            Err(OpError::NonFinite(by)) if by.is_nan()
+        ));
+        assert_eq!(
+            apply_op(&memory, 0x1008, ValueType::F32, WriteOp::Mul(f64::INFINITY)),
+            Err(OpError::NonFinite(f64::INFINITY))
+        );
+        assert_eq!(read(0x1000, 4), before);
+        assert!(matches!(apply_op(&memory, 0x9000, ValueType::U32, WriteOp::Half), Err(OpError::Memory(_))));
+    }
+
+    #[test]
+    pub fn test_struct_schema_reads_each_field() {
+        use crate::core::utils::FakeMemory;
+
+        let parse_type = |name: &str| ValueType::all().into_iter().find(|t| t.name() == name);
+        let schema = r#"{"fields": [
+            {"name": "health", "offset": "0x0", "type": "i32"},
+            {"name": "position", "offset": 4, "type": "f32", "count": 2},
+            {"name": "target", "offset": "0x10", "type": "ptr"},
+            {"name": "name", "offset": "0x18", "type": "string", "count": 8}
+        ]}"#;
+        let fields = parse_struct_schema(schema, 8, parse_type).unwrap();
+        let sizes: Vec<(u64, usize)> = fields.iter().map(|f| (f.offset, f.size())).collect();
+        assert_eq!(sizes, [(0, 4), (4, 8), (0x10, 8), (0x18, 8)]);
+        assert_eq!(fields[1].count, 2);
+
+        let overlapping = r#"[{"name": "a", "offset": 0, "type": "u64"}, {"name": "b", "offset": 4, "type": "u32"}]"#;
+        assert_eq!(
+            parse_struct_schema(overlapping, 8, parse_type),
+            Err(StructError::Overlap { field: String::from("b"), previous: String::from("a") })
+        );
+        let union = format!(r#"{{"allow_overlap": true, "fields": {overlapping}}}"#);
+        assert_eq!(parse_struct_schema(&union, 8, parse_type).unwrap().len(), 2);
+        let invalid = |schema: &str| match parse_struct_schema(schema, 8, parse_type) {
+            Err(StructError::InvalidField { index, message }) => (index, message),
+            other => panic!("expected an invalid field, got {other:?}"),
+        };
+        assert_eq!(invalid(r#"[{"name": "s", "offset": 0, "type": "string"}]"#).0, 0);
+        assert_eq!(
+            invalid(r#"[{"name": "a", "offset": 0, "type": "u8"}]"#).1,
+            "'a' has an unknown \"type\""
+        );
+        assert!(matches!(parse_struct_schema("{}", 8, parse_type), Err(StructError::InvalidSchema(_))));
+        assert!(matches!(parse_struct_schema("[", 8, parse_type), Err(StructError::Json(_))));
+        assert_eq!(
+            invalid(r#"[{"name": "a", "offset": 0, "type": "u64", "count": 4611686018427387904}]"#).1,
+            "'a' is larger than 1 MiB, check its \"count\""
+        );
+
+        // the struct runs off the end of its region, only the name is lost
+        let memory = FakeMemory::new();
+        let mut data = vec![];
+        data.extend_from_slice(&100_i32.to_le_bytes());
+        data.extend_from_slice(&1.5_f32.to_le_bytes());
+        data.extend_from_slice(&(-2.0_f32).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&0x7f00_u64.to_le_bytes());
+        data.extend_from_slice(b"bob");
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read]);
+        let values = read_struct(&memory, 0x1000, &fields);
+        assert_eq!(values[0], Ok(100_i32.to_le_bytes().to_vec()));
+        assert_eq!(values[1].as_ref().unwrap()[4..], (-2.0_f32).to_le_bytes());
+        assert_eq!(values[2], Ok(0x7f00_u64.to_le_bytes().to_vec()));
+        assert!(matches!(&values[3], Err(StructError::Unreadable { field, address: 0x1018, .. }) if field == "name"));
+        assert!(matches!(&read_struct(&memory, u64::MAX - 8, &fields)[3], Err(StructError::OutOfRange { .. })));
+    }
+
+    #[test]
+    pub fn test_read_cstring() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut page = vec![b'a'; 0x1000];
+        page[0x10..0x16].copy_from_slice(b"hello\0");
+        memory.add_region(0x1000, page, vec![MemoryRegionPerms::Read]);
+        // the string at the end of the first page continues into the next one
+        memory.add_region(0x2000, b"bc\0".to_vec(), vec![MemoryRegionPerms::Read]);
+
+        assert_eq!(read_cstring(&memory, 0x1010, 4096).unwrap(), (b"hello".to_vec(), CStringEnd::Null));
+        assert_eq!(read_cstring(&memory, 0x1015, 4096).unwrap(), (vec![], CStringEnd::Null));
+        let (bytes, end) = read_cstring(&memory, 0x1ffe, 4096).unwrap();
+        assert_eq!((bytes.as_slice(), end), (b"aabc".as_slice(), CStringEnd::Null));
+        assert_eq!(read_cstring(&memory, 0x1100, 8).unwrap(), (b"aaaaaaaa".to_vec(), CStringEnd::MaxLength));
+
+        // runs into unmapped memory
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![b'x'; 0x1000], vec![MemoryRegionPerms::Read]);
+        let (bytes, end) = read_cstring(&memory, 0x1ff0, 4096).unwrap();
+        assert_eq!((bytes.len(), end), (0x10, CStringEnd::Unreadable));
+        assert!(read_cstring(&memory, 0x3000, 4096).is_err());
+    }
+
+    #[test]
+    pub fn test_parse_maps_merges_adjacent_regions() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 rw-p 00000000 08:01 1234 /usr/bin/game
+55d0c0a02000-55d0c0a04000 rw-p 00002000 08:01 1234 /usr/bin/game
+55d0c0a04000-55d0c0a05000 rw-p 00000000 00:00 0
+55d0c0a05000-55d0c0a06000 rw-p 00000000 00:00 0
+55d0c0a06000-55d0c0a07000 r--p 00000000 00:00 0
+55d0c0a07000-55d0c0a08000 rw-p 00000000 00:00 0
+7f0000000000-7f0000002000 rw-p 00000000 08:01 99   /usr/lib/libc.so.6
+7f0000002000-7f0000003000 rw-p 00000000 08:01 100  /usr/lib/libm.so.6
+7f0000001000-7f0000002800 rw-p 00000000 08:01 99   /usr/lib/libc.so.6
+7f0000001000-7f0000001800 rw-p 00000000 08:01 99   /usr/lib/libc.so.6
+";
+        let all = [MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        let regions = parse_maps_regions(maps, None, None, Some(&all)).unwrap();
+        let ranges: Vec<(u64, u64)> = regions.iter().map(|r| (r.start, r.end)).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                // both mappings of the executable
+                (0x55d0c0a00000, 0x55d0c0a04000),
+                // anonymous memory is kept apart from the file and split by permissions
+                (0x55d0c0a04000, 0x55d0c0a06000),
+                (0x55d0c0a06000, 0x55d0c0a07000),
+                (0x55d0c0a07000, 0x55d0c0a08000),
+                // the overlapping libc mappings are merged and the contained one dropped,
+                // libm keeps its own region without the part libc already covers
+                (0x7f0000000000, 0x7f0000002800),
+                (0x7f0000002800, 0x7f0000003000),
+            ]
+        );
+
+        let writable = parse_maps_regions(maps, Some(0x55d0c0a06000), Some(0x55d0c0a07000), None).unwrap();
+        let ranges: Vec<(u64, u64)> = writable.iter().map(|r| (r.start, r.end)).collect();
+        assert_eq!(ranges, vec![(0x55d0c0a05000, 0x55d0c0a06000), (0x55d0c0a07000, 0x55d0c0a08000)]);
+    }
+
+    #[test]
+    pub fn test_parse_maps_skips_unreadable_mappings() {
+        let maps = "\
+7ffd1a000000-7ffd1a021000 rw-p 00000000 00:00 0    [stack]
+7ffd1a1f0000-7ffd1a1f4000 r--p 00000000 00:00 0    [vvar]
+7ffd1a1f4000-7ffd1a1f6000 r-xp 00000000 00:00 0    [vdso]
+ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0  [vsyscall]
+";
+        let all = [MemoryRegionPerms::Read, MemoryRegionPerms::Write, MemoryRegionPerms::Execute];
+        let regions = parse_maps_regions(maps, None, None, Some(&all)).unwrap();
+        let ranges: Vec<(u64, u64)> = regions.iter().map(|r| (r.start, r.end)).collect();
+        assert_eq!(ranges, vec![(0x7ffd1a000000, 0x7ffd1a021000), (0x7ffd1a1f4000, 0x7ffd1a1f6000)]);
+        let executable = parse_maps_regions(maps, Some(0xffffffffff600000), None, Some(&all)).unwrap();
+        assert!(executable.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    pub fn test_io_backends_agree() {
+        use std::sync::atomic::Ordering;
+
+        let data: Vec<u8> = (0..0x3000_u32).map(|i| (i * 7) as u8).collect();
+        let addr = data.as_ptr() as usize;
+        let vm = ProcessHandle::open(std::process::id()).unwrap();
+        let proc_mem = ProcessHandle::open(std::process::id()).unwrap();
+        proc_mem.vm_io.store(false, Ordering::Relaxed);
+
+        assert_eq!(vm.read(addr, data.len()).unwrap(), data);
+        assert_eq!(proc_mem.read(addr, data.len()).unwrap(), data);
+        // unmapped memory fails either way, without giving up on the syscalls
+        assert!(vm.read(0x10, 4).is_err());
+        assert!(vm.vm_io.load(Ordering::Relaxed));
+
+        let target = vec![0_u8; 8];
+        vm.write(target.as_ptr() as usize, &[1, 2, 3, 4]).unwrap();
+        proc_mem.write(target.as_ptr() as usize + 4, &[5, 6, 7, 8]).unwrap();
+        assert_eq!(std::hint::black_box(&target), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    pub fn test_concurrent_positional_reads() {
+        use crate::core::utils::FakeMemory;
+
+        // every 4 byte word holds its own index, so a read from the wrong offset shows
+        let data: Vec<u8> = (0..0x4000_u32).flat_map(|i| i.to_le_bytes()).collect();
+        let check = |source: &dyn MemorySource, base: usize| {
+            std::thread::scope(|threads| {
+                for thread in 0..8_usize {
+                    threads.spawn(move || {
+                        for i in 0..500 {
+                            let word = (thread * 7919 + i * 104729) % 0x3f00;
+                            let size = 4 * (1 + (i % 64));
+                            let read = source.read(base + word * 4, size).unwrap();
+                            let expected: Vec<u8> =
+                                (word as u32..).take(size / 4).flat_map(|i| i.to_le_bytes()).collect();
+                            assert_eq!(read, expected, "thread {thread} read {i} at word {word}");
+                        }
+                    });
+                }
+            });
+        };
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x10000, data.clone(), vec![MemoryRegionPerms::Read]);
+        check(&memory, 0x10000);
+
+        // one `/proc/<pid>/mem` descriptor shared by every thread
+        #[cfg(target_os = "linux")]
+        {
+            let handle = ProcessHandle::open(std::process::id()).unwrap();
+            handle.vm_io.store(false, std::sync::atomic::Ordering::Relaxed);
+            check(&handle, data.as_ptr() as usize);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "benchmark, run with --nocapture to see the timings"]
+    pub fn bench_io_backends() {
+        use std::sync::atomic::Ordering;
+        use std::time::Instant;
+
+        // a large scan reads 64 KiB blocks
+        let data = vec![0x5a_u8; 64 << 20];
+        let vm = ProcessHandle::open(std::process::id()).unwrap();
+        let proc_mem = ProcessHandle::open(std::process::id()).unwrap();
+        proc_mem.vm_io.store(false, Ordering::Relaxed);
+
+        for (name, handle) in [("process_vm_readv", &vm), ("/proc/<pid>/mem", &proc_mem)] {
+            let started = Instant::now();
+            for block in (0..data.len()).step_by(0x10000) {
+                handle.read(data.as_ptr() as usize + block, 0x10000).unwrap();
+            }
+            println!("{name}: {} MiB in {:?}", data.len() >> 20, started.elapsed());
+        }
+    }
+
+    #[test]
+    pub fn test_hash_region_skips_unreadable_pages() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![1; 0x1000], vec![MemoryRegionPerms::Read]);
+        memory.add_region(0x3000, vec![2; 0x800], vec![MemoryRegionPerms::Read]);
+
+        let digest = |data: &[u8]| {
+            let mut hasher = Hasher::new(HashAlgo::Sha256);
+            hasher.update(data);
+            hasher.finish()
+        };
+        let hash = hash_region(&memory, 0x1800, 0x3800, HashAlgo::Sha256).unwrap();
+        assert_eq!((hash.hashed, hash.unreadable), (0x1000, 0x1000));
+        let mut readable = vec![1; 0x800];
+        readable.extend([2; 0x800]);
+        assert_eq!(hash.digest, digest(&readable));
+
+        let hash = hash_region(&memory, 0x1000, 0x1004, HashAlgo::Crc32).unwrap();
+        assert_eq!((hash.hashed, hash.unreadable, hash.digest.len()), (4, 0, 4));
+        let hash = hash_region(&memory, 0x5000, 0x6000, HashAlgo::Crc32).unwrap();
+        assert_eq!((hash.hashed, hash.unreadable), (0, 0x1000));
+    }
+
+    #[test]
+    pub fn test_read_write_array_bounds() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let data: Vec<u8> = (0..4_u32).flat_map(|v| v.to_le_bytes()).collect();
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        memory.add_region(0x1010, vec![0; 8], vec![MemoryRegionPerms::Read]);
+
+        let values = read_array(&memory, 0x1004, 4, 5).unwrap();
+        assert_eq!(values.len(), 5);
+        assert_eq!(values[0], 1_u32.to_le_bytes());
+        assert_eq!(values[4], [0; 4]);
+        assert_eq!(
+            read_array(&memory, 0x1008, 4, 6),
+            Err(ArrayError::OutsideRegion { index: 4, address: 0x1018 })
+        );
+        assert_eq!(
+            read_array(&memory, 0x1000, 4, usize::MAX / 2),
+            Err(ArrayError::PastEnd { index: (u64::MAX - 0x1000) as usize / 4, address: 0x1000 + (u64::MAX - 0x1000) / 4 * 4 })
+        );
+
+        let new: Vec<Vec<u8>> = [7_u32, 8].iter().map(|v| v.to_le_bytes().to_vec()).collect();
+        write_array(&memory, 0x1008, &new).unwrap();
+        assert_eq!(memory.read(0x1008, 8).unwrap(), [7, 0, 0, 0, 8, 0, 0, 0]);
+        // the second region is read only
+        assert_eq!(
+            write_array(&memory, 0x100c, &new),
+            Err(ArrayError::OutsideRegion { index: 1, address: 0x1010 })
+        );
+    }
+
+    #[test]
+    pub fn test_write_each_reports_every_address() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![1; 0x10], vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+
+        let value = 7_u32.to_le_bytes();
+        let outcomes = write_each(&memory, &[0x1000, 0x1008, 0x2000], &value, true);
+        assert_eq!(outcomes[0].previous, Some(vec![1; 4]));
+        assert_eq!(outcomes[0].status, WriteStatus::Verified);
+        assert_eq!(outcomes[1].address, 0x1008);
+        // the write outside the region fails without stopping the others
+        assert_eq!(outcomes[2].previous, None);
+        assert!(matches!(outcomes[2].status, WriteStatus::Failed(_)));
+        assert_eq!(memory.read(0x1008, 4).unwrap(), value);
+
+        let outcomes = write_each(&memory, &[0x100c], &value, false);
+        assert_eq!(outcomes[0].status, WriteStatus::Written);
+    }
+
+    #[test]
+    pub fn test_patch_memory_returns_original() {
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![0x55, 0x48, 0x89, 0xe5], vec![MemoryRegionPerms::Execute]);
+
+        let original = patch_memory(&memory, 0x1001, &[X86_NOP; 3]).unwrap();
+        assert_eq!(original, vec![0x48, 0x89, 0xe5]);
+        assert_eq!(memory.read(0x1000, 4).unwrap(), vec![0x55, 0x90, 0x90, 0x90]);
+
+        patch_memory(&memory, 0x1001, &original).unwrap();
+        assert_eq!(memory.read(0x1000, 4).unwrap(), vec![0x55, 0x48, 0x89, 0xe5]);
+        assert!(patch_memory(&memory, 0x1002, &[X86_NOP; 4]).is_err());
+    }
+
+    #[test]
+    pub fn test_patch_registry_restores_on_panic() {
+        use crate::core::utils::FakeMemory;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x1000, vec![0x55, 0x48, 0x89, 0xe5], vec![MemoryRegionPerms::Execute]);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut patches = PatchRegistry::new(&memory);
+            patches.apply(0x1001, &[X86_NOP; 2]).unwrap();
+            patches.apply(0x1002, &[0xcc; 2]).unwrap();
+            assert_eq!(memory.read(0x1000, 4).unwrap(), vec![0x55, 0x90, 0xcc, 0xcc]);
+            panic!("simulated crash");
+        }));
+        assert!(result.is_err());
+        assert_eq!(memory.read(0x1000, 4).unwrap(), vec![0x55, 0x48, 0x89, 0xe5]);
+
+        let mut patches = PatchRegistry::new(&memory);
+        patches.apply(0x1000, &[X86_NOP]).unwrap();
+        patches.keep();
+        drop(patches);
+        assert_eq!(memory.read(0x1000, 1).unwrap(), vec![0x90]);
+
+        // a write made elsewhere, e.g. by the TUI, is reverted too
+        let mut patches = PatchRegistry::new(&memory);
+        memory.write(0x1002, &[0xcc]).unwrap();
+        patches.record(0x1002, vec![0x89]);
+        drop(patches);
+        assert_eq!(memory.read(0x1000, 4).unwrap(), vec![0x90, 0x48, 0x89, 0xe5]);
     }
 }
@@ -1,4 +1,22 @@
+pub mod address;
+pub mod codescan;
+pub mod cache;
+pub mod checkpoint;
+pub mod config;
+pub mod debug;
+pub mod digest;
+pub mod disasm;
+pub mod dump;
+pub mod histogram;
+pub mod inject;
+pub mod journal;
+pub mod json;
 pub mod mem;
+pub mod pairscan;
 pub mod proc;
+pub mod ptrpath;
+pub mod ptrscan;
+pub mod results;
 pub mod scan;
+pub mod spill;
 pub mod utils;
@@ -0,0 +1,198 @@
+//! Scans across two processes: compare the values at the same place in two instances of
+//! a program, e.g. two copies of a game showing the same (or a different) state.
+//!
+//! The processes rarely share addresses, so regions are paired by the mapping they belong
+//! to and compared at the same offset from the mapping's base: `game+0x1234` in one
+//! process against `game+0x1234` in the other. Anonymous memory has no name to pair it by
+//! and is left out.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::mem::{MemoryError, MemoryRegion, MemorySource};
+use crate::core::proc::Mapping;
+
+const BLOCK_SIZE: u64 = 0x10000;
+
+/// Which pairs of values [`scan_pair`] reports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PairMode {
+    Equal,
+    Differ,
+}
+
+/// A region and the mapping it belongs to: offsets in it count from `base`, the lowest
+/// address the mapping `name` starts at
+#[derive(Debug, Clone)]
+pub struct LayoutRegion {
+    pub name: String,
+    pub base: u64,
+    pub region: MemoryRegion,
+}
+
+/// Values at the same offset into mapping `name` of both processes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairedValue {
+    pub name: String,
+    pub offset: u64,
+    pub address_a: u64,
+    pub address_b: u64,
+    pub value_a: Vec<u8>,
+    pub value_b: Vec<u8>,
+}
+
+/// The named `regions` with the base of their mapping, anonymous regions are left out
+pub fn layout(regions: &[MemoryRegion], mappings: &[Mapping]) -> Vec<LayoutRegion> {
+    let named: Vec<&Mapping> = mappings.iter().filter(|m| !m.path.is_empty()).collect();
+    let mut bases: HashMap<&str, u64> = HashMap::new();
+    for mapping in &named {
+        let base = bases.entry(&mapping.path).or_insert(mapping.start);
+        *base = (*base).min(mapping.start);
+    }
+
+    regions
+        .iter()
+        .filter_map(|region| {
+            let mapping =
+                named.iter().find(|m| m.start <= region.start && region.start < m.end)?;
+            Some(LayoutRegion {
+                name: mapping.name().to_string(),
+                base: bases[mapping.path.as_str()],
+                region: region.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Compare the `value_size` byte values at each offset, aligned to the value size, that
+/// both layouts cover for a mapping of the same name, in the order of `layout_a`. Stops
+/// after `max_results` pairs or once `stop` is raised; blocks that can't be read in either
+/// process are skipped.
+pub fn scan_pair(
+    (source_a, layout_a): (&dyn MemorySource, &[LayoutRegion]),
+    (source_b, layout_b): (&dyn MemorySource, &[LayoutRegion]),
+    value_size: usize,
+    mode: PairMode,
+    max_results: usize,
+    stop: &AtomicBool,
+) -> Result<Vec<PairedValue>, MemoryError> {
+    let size = value_size.max(1) as u64;
+    let mut found = vec![];
+    for (source, layout) in [(source_a, layout_a), (source_b, layout_b)] {
+        // Validate a region with a single byte read to catch ProcessAttach errors early
+        if let Some(first) = layout.first()
+            && let Err(e @ MemoryError::ProcessAttach(_)) =
+                source.read(first.region.start as usize, 1)
+        {
+            return Err(e);
+        }
+    }
+
+    for a in layout_a {
+        for b in layout_b.iter().filter(|b| b.name == a.name) {
+            let start = (a.region.start - a.base).max(b.region.start - b.base);
+            let end = (a.region.end - a.base).min(b.region.end - b.base);
+
+            let mut offset = start.next_multiple_of(size);
+            while offset + size <= end {
+                if stop.load(Ordering::SeqCst) || found.len() >= max_results {
+                    return Ok(found);
+                }
+                let len = std::cmp::min(BLOCK_SIZE, end - offset);
+                let len = len - len % size;
+                let read = |source: &dyn MemorySource, base: u64| {
+                    source.read((base + offset) as usize, len as usize)
+                };
+                if let (Ok(block_a), Ok(block_b)) = (read(source_a, a.base), read(source_b, b.base)) {
+                    let pairs = block_a
+                        .chunks_exact(size as usize)
+                        .zip(block_b.chunks_exact(size as usize));
+                    for (i, (value_a, value_b)) in pairs.enumerate() {
+                        if (value_a == value_b) != (mode == PairMode::Equal) {
+                            continue;
+                        }
+                        let value_offset = offset + i as u64 * size;
+                        found.push(PairedValue {
+                            name: a.name.clone(),
+                            offset: value_offset,
+                            address_a: a.base + value_offset,
+                            address_b: b.base + value_offset,
+                            value_a: value_a.to_vec(),
+                            value_b: value_b.to_vec(),
+                        });
+                        if found.len() >= max_results {
+                            return Ok(found);
+                        }
+                    }
+                }
+                offset += len;
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_scan_pair_by_module_offset() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let mapping = |start, end, path: &str| Mapping { start, end, path: path.to_string() };
+        let rw = || vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+
+        // the same module at different bases, with a second region only mapped in `a`
+        let a = FakeMemory::new();
+        let mut data_a = vec![0_u8; 0x20];
+        data_a[0x08..0x0c].copy_from_slice(&100_u32.to_le_bytes());
+        data_a[0x10..0x14].copy_from_slice(&5_u32.to_le_bytes());
+        a.add_region(0x401000, data_a, rw());
+        a.add_region(0x500000, vec![0; 0x10], rw());
+        let b = FakeMemory::new();
+        let mut data_b = vec![0_u8; 0x20];
+        data_b[0x08..0x0c].copy_from_slice(&100_u32.to_le_bytes());
+        data_b[0x10..0x14].copy_from_slice(&6_u32.to_le_bytes());
+        b.add_region(0x7f0001000, data_b, rw());
+
+        let layout_a = layout(
+            &a.regions(None, None, None).unwrap(),
+            &[
+                mapping(0x400000, 0x401000, "/opt/game/game"),
+                mapping(0x401000, 0x401020, "/opt/game/game"),
+                mapping(0x500000, 0x500010, ""),
+            ],
+        );
+        assert_eq!(layout_a.len(), 1);
+        assert_eq!((layout_a[0].name.as_str(), layout_a[0].base), ("game", 0x400000));
+        let layout_b = layout(
+            &b.regions(None, None, None).unwrap(),
+            &[
+                mapping(0x7f0000000, 0x7f0001000, "/home/user/game"),
+                mapping(0x7f0001000, 0x7f0001020, "/home/user/game"),
+            ],
+        );
+
+        let stop = AtomicBool::new(false);
+        let differ = scan_pair((&a, &layout_a), (&b, &layout_b), 4, PairMode::Differ, 100, &stop).unwrap();
+        assert_eq!(
+            differ,
+            vec![PairedValue {
+                name: String::from("game"),
+                offset: 0x1010,
+                address_a: 0x401010,
+                address_b: 0x7f0001010,
+                value_a: 5_u32.to_le_bytes().to_vec(),
+                value_b: 6_u32.to_le_bytes().to_vec(),
+            }]
+        );
+
+        let equal = scan_pair((&a, &layout_a), (&b, &layout_b), 4, PairMode::Equal, 100, &stop).unwrap();
+        assert_eq!(equal.len(), 7);
+        assert!(equal.iter().any(|p| p.offset == 0x1008 && p.value_a == 100_u32.to_le_bytes()));
+        let capped = scan_pair((&a, &layout_a), (&b, &layout_b), 4, PairMode::Equal, 2, &stop).unwrap();
+        assert_eq!(capped.len(), 2);
+    }
+}
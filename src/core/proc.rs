@@ -1,5 +1,10 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use sysinfo::System;
 
+use crate::core::mem::{MemoryError, MemoryRegion, MemoryRegionPerms, MemorySource, all_regions};
+
 #[derive(Debug, Clone)]
 pub struct ProcInfo {
     pub pid: u32,
@@ -38,6 +43,847 @@ pub fn get_list(filter: Option<&str>) -> Vec<ProcInfo> {
     proc_list
 }
 
+/// How long a process has to keep running after it appeared for [`wait_for_process`] to
+/// return it, launchers often start a short-lived process of the same name first
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Time between the process list checks of [`wait_for_process`]
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Wait for at most `timeout` until a process whose name starts with `pattern` runs, matched
+/// like [`get_list`] does. Returns the matches still running [`SETTLE_TIME`] after they were
+/// seen, in [`get_list`] order, this process left out. Fails with `TimedOut` when none
+/// appeared in time, or `Interrupted` once `stop` is raised.
+pub fn wait_for_process(pattern: &str, timeout: Duration, stop: &AtomicBool) -> std::io::Result<Vec<ProcInfo>> {
+    use std::io::{Error, ErrorKind};
+
+    let started = Instant::now();
+    let matches = || -> Vec<ProcInfo> {
+        get_list(Some(pattern)).into_iter().filter(|p| p.pid != std::process::id()).collect()
+    };
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            return Err(Error::new(ErrorKind::Interrupted, "interrupted"));
+        }
+        let seen = matches();
+        if !seen.is_empty() {
+            std::thread::sleep(SETTLE_TIME);
+            let running = matches();
+            let stable: Vec<ProcInfo> =
+                seen.into_iter().filter(|p| running.iter().any(|r| r.pid == p.pid)).collect();
+            if !stable.is_empty() {
+                return Ok(stable);
+            }
+        }
+        if started.elapsed() >= timeout {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!("no process matching '{pattern}' appeared within {timeout:?}"),
+            ));
+        }
+        std::thread::sleep(WAIT_POLL_INTERVAL.min(timeout.saturating_sub(started.elapsed())));
+    }
+}
+
+/// What is known about a running process. Fields are `None` when the OS doesn't expose
+/// them or they can't be read, e.g. without permission or for zombie processes.
+#[derive(Debug, Clone)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub name: String,
+    pub status: String,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Option<Vec<String>>,
+    pub cwd: Option<PathBuf>,
+    /// Seconds since the Unix epoch
+    pub start_time: Option<u64>,
+    pub environ: Option<Vec<String>>,
+}
+
+/// Details of process `pid`, `None` if there is no such process
+pub fn process_details(pid: u32) -> Option<ProcessDetails> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate};
+
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::everything(),
+    );
+    let process = sys.process(pid)?;
+
+    let strings = |values: &[std::ffi::OsString]| -> Option<Vec<String>> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(values.iter().map(|v| v.to_string_lossy().into_owned()).collect())
+    };
+
+    Some(ProcessDetails {
+        pid: pid.as_u32(),
+        name: process.name().to_string_lossy().into_owned(),
+        status: process.status().to_string(),
+        exe: process.exe().map(|p| p.to_path_buf()),
+        cmdline: strings(process.cmd()),
+        cwd: process.cwd().map(|p| p.to_path_buf()),
+        start_time: Some(process.start_time()).filter(|t| *t > 0),
+        environ: strings(process.environ()),
+    })
+}
+
+/// Where a process comes from, to recognise the process that takes over from it after
+/// it forks or re-executes itself, see [`find_successor`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessLineage {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub exe: PathBuf,
+    /// Seconds since the Unix epoch
+    pub start_time: u64,
+    /// Processes seen descending from it while it ran, see
+    /// [`ProcessLineage::record_descendants`]. Its children get a new parent once it
+    /// exits, these are still recognised as its own then.
+    pub descendants: Vec<u32>,
+}
+
+impl ProcessLineage {
+    /// Add the processes descending from this one right now to `descendants`. Ones
+    /// recorded before stay, even once they've exited.
+    pub fn record_descendants(&mut self) {
+        self.add_descendants(&lineages(None));
+    }
+
+    fn add_descendants(&mut self, processes: &[ProcessLineage]) {
+        loop {
+            let known = self.descendants.len();
+            for process in processes {
+                let descends = process
+                    .parent
+                    .is_some_and(|parent| parent == self.pid || self.descendants.contains(&parent));
+                // a pid reused by a process older than this one can't descend from it
+                if descends
+                    && process.pid != self.pid
+                    && process.start_time >= self.start_time
+                    && !self.descendants.contains(&process.pid)
+                {
+                    self.descendants.push(process.pid);
+                }
+            }
+            if self.descendants.len() == known {
+                break;
+            }
+        }
+    }
+}
+
+/// Records the descendants of a process from a background thread while it runs, for
+/// [`find_successor`] to recognise its children after they got a new parent
+pub struct LineageRecorder {
+    lineage: std::sync::Arc<std::sync::Mutex<ProcessLineage>>,
+    stop: std::sync::Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl LineageRecorder {
+    /// Start recording the descendants of `lineage` every `interval`
+    pub fn start(lineage: ProcessLineage, interval: Duration) -> Self {
+        use std::sync::{Arc, Mutex};
+
+        let lineage = Arc::new(Mutex::new(lineage));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (lineage, stop) = (lineage.clone(), stop.clone());
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let processes = lineages(None);
+                    if let Ok(mut lineage) = lineage.lock() {
+                        lineage.add_descendants(&processes);
+                    }
+                    let until = Instant::now() + interval;
+                    while !stop.load(Ordering::SeqCst) && Instant::now() < until {
+                        std::thread::sleep(Duration::from_millis(10).min(interval));
+                    }
+                }
+            })
+        };
+        LineageRecorder {
+            lineage,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stop recording, the lineage with every descendant seen
+    pub fn finish(mut self) -> ProcessLineage {
+        self.stop_thread();
+        self.lineage.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn stop_thread(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for LineageRecorder {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+/// Lineage of process `pid`, `None` if there is no such process or its executable can't
+/// be read
+pub fn lineage(pid: u32) -> Option<ProcessLineage> {
+    lineages(Some(pid)).pop()
+}
+
+/// Lineages of process `pid`, or of every process. Zombies are left out, their memory is
+/// gone.
+fn lineages(pid: Option<u32>) -> Vec<ProcessLineage> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, UpdateKind};
+
+    let pids = pid.map(|pid| [Pid::from_u32(pid)]);
+    let which = match &pids {
+        Some(pids) => ProcessesToUpdate::Some(pids),
+        None => ProcessesToUpdate::All,
+    };
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(which, true, ProcessRefreshKind::nothing().with_exe(UpdateKind::Always));
+    sys.processes()
+        .iter()
+        .filter(|(_, process)| process.status() != ProcessStatus::Zombie)
+        .filter_map(|(pid, process)| {
+            Some(ProcessLineage {
+                pid: pid.as_u32(),
+                parent: process.parent().map(|p| p.as_u32()),
+                exe: process.exe()?.to_path_buf(),
+                start_time: process.start_time(),
+                descendants: vec![],
+            })
+        })
+        .collect()
+}
+
+/// The process that most likely took over from `of` once it's gone: one running the same
+/// executable that is `of` itself after an `exec`, else a child of it (or one of the
+/// descendants recorded in `of`, children are reparented once it exits), else a sibling
+/// (same parent). The latest started one wins among children or siblings. This is a
+/// heuristic, an unrelated instance of the program started by the same parent is taken
+/// for a successor too.
+pub fn find_successor(of: &ProcessLineage) -> Option<ProcessLineage> {
+    pick_successor(of, lineages(None))
+}
+
+fn pick_successor(of: &ProcessLineage, candidates: Vec<ProcessLineage>) -> Option<ProcessLineage> {
+    let rank = |candidate: &ProcessLineage| {
+        if candidate.pid == of.pid {
+            Some(0)
+        } else if candidate.parent == Some(of.pid) || of.descendants.contains(&candidate.pid) {
+            Some(1)
+        } else if candidate.parent.is_some() && candidate.parent == of.parent {
+            Some(2)
+        } else {
+            None
+        }
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.exe == of.exe)
+        .filter_map(|candidate| Some((rank(&candidate)?, candidate)))
+        .min_by_key(|(rank, candidate)| (*rank, std::cmp::Reverse(candidate.start_time)))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Pointer width of process `pid` in bytes (4 or 8), read from the ELF class of its
+/// executable. `None` if it can't be read or on other platforms.
+pub fn pointer_width(pid: u32) -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        elf_pointer_width(Path::new(&format!("/proc/{pid}/exe")))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Whether process `pid` is 64-bit, see [`pointer_width`]. Processes whose executable
+/// can't be read are taken to match the host.
+pub fn process_is_64bit(pid: u32) -> bool {
+    pointer_width(pid).unwrap_or(size_of::<usize>()) == 8
+}
+
+/// When process `pid` started, to tell it from a later process that got the same pid:
+/// clock ticks since boot on Linux (field 22 of `/proc/<pid>/stat`), seconds since the
+/// Unix epoch elsewhere. `None` if there is no such process.
+pub fn start_time(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // the name in parentheses can hold spaces, the fields after it can't
+        let rest = &stat[stat.rfind(')')? + 1..];
+        rest.split_whitespace().nth(19)?.parse().ok()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        process_details(pid)?.start_time
+    }
+}
+
+/// Pointer width in bytes of the ELF file at `path` by its class, `None` if it isn't one
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn elf_pointer_width(path: &Path) -> Option<usize> {
+    use std::io::Read;
+
+    let mut ident = [0_u8; 5];
+    std::fs::File::open(path).ok()?.read_exact(&mut ident).ok()?;
+    if &ident[..4] != b"\x7fELF" {
+        return None;
+    }
+    match ident[4] {
+        1 => Some(4),
+        2 => Some(8),
+        _ => None,
+    }
+}
+
+/// A file mapped into a process, e.g. the executable or a shared library
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    /// File name, e.g. `libc.so.6`
+    pub name: String,
+    pub path: PathBuf,
+    /// Lowest address the file is mapped at
+    pub base: u64,
+    /// End of the highest mapping of the file, `base..end` is the span it covers
+    pub end: u64,
+}
+
+/// Files mapped into process `pid`, sorted by base address. Empty if the mappings can't
+/// be read or on other platforms than Linux.
+pub fn modules(pid: u32) -> Vec<Module> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(maps) = std::fs::read_to_string(format!("/proc/{pid}/maps")) else {
+            return vec![];
+        };
+        modules_in(&parse_mappings(&maps))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        vec![]
+    }
+}
+
+/// Modules of a process by its `mappings`, each file's mappings joined into one module
+pub fn modules_in(mappings: &[Mapping]) -> Vec<Module> {
+    let mut modules: Vec<Module> = vec![];
+    for mapping in mappings {
+        if !mapping.path.starts_with('/') {
+            continue;
+        }
+
+        let path = PathBuf::from(&mapping.path);
+        match modules.iter_mut().find(|m| m.path == path) {
+            Some(module) => {
+                module.base = module.base.min(mapping.start);
+                module.end = module.end.max(mapping.end);
+            }
+            None => modules.push(Module {
+                name: mapping.name().to_string(),
+                path,
+                base: mapping.start,
+                end: mapping.end,
+            }),
+        }
+    }
+    modules.sort_by_key(|m| m.base);
+    modules
+}
+
+/// An address range mapped in a process and what backs it: a file path, a pseudo name
+/// like `[heap]` or `[stack]`, or nothing for anonymous memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub start: u64,
+    pub end: u64,
+    pub path: String,
+}
+
+impl Mapping {
+    /// File name for file mappings, the path as is otherwise
+    pub fn name(&self) -> &str {
+        match self.path.starts_with('/') {
+            true => self.path.rsplit('/').next().unwrap_or(&self.path),
+            false => &self.path,
+        }
+    }
+}
+
+/// The named mappings of a process sorted by address, read once so that the mapping of
+/// many addresses (e.g. every scan result) can be looked up without reading the maps again
+#[derive(Debug, Clone, Default)]
+pub struct RegionNames {
+    mappings: Vec<Mapping>,
+}
+
+impl RegionNames {
+    /// Index the mappings that have a name, anonymous ones are left out
+    pub fn new(mut mappings: Vec<Mapping>) -> Self {
+        mappings.retain(|m| !m.path.is_empty());
+        mappings.sort_by_key(|m| m.start);
+        RegionNames { mappings }
+    }
+
+    pub fn for_process(pid: u32) -> Self {
+        Self::new(mappings(pid))
+    }
+
+    /// Index of the named mapping holding `address`
+    pub fn index_for(&self, address: u64) -> Option<u32> {
+        let i = self.mappings.partition_point(|m| m.start <= address).checked_sub(1)?;
+        (address < self.mappings[i].end).then_some(i as u32)
+    }
+
+    /// Name of the mapping at `index`, see [`Mapping::name`]
+    pub fn name(&self, index: u32) -> Option<&str> {
+        self.mappings.get(index as usize).map(Mapping::name)
+    }
+
+    /// Name of the mapping holding `address`, `None` for anonymous memory
+    pub fn region_name_for(&self, address: u64) -> Option<&str> {
+        self.name(self.index_for(address)?)
+    }
+}
+
+/// What a region holds, guessed from its permissions and mapping, see [`classify_region`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// Executable, a module's code or JIT output
+    Code,
+    /// `[stack]`, the main thread's stack
+    Stack,
+    /// `[heap]` and writable anonymous memory, where malloc's big blocks, other threads'
+    /// stacks and most allocators' arenas are
+    Heap,
+    /// Data of a mapped file, e.g. a module's globals
+    File,
+    /// The rest: read-only anonymous memory and pseudo mappings like `[vvar]`
+    Data,
+}
+
+pub const REGION_KINDS: [RegionKind; 5] =
+    [RegionKind::Code, RegionKind::Stack, RegionKind::Heap, RegionKind::File, RegionKind::Data];
+
+impl RegionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegionKind::Code => "code",
+            RegionKind::Stack => "stack",
+            RegionKind::Heap => "heap",
+            RegionKind::File => "file",
+            RegionKind::Data => "data",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RegionKind> {
+        REGION_KINDS.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Kind of a region with `perms`, in the mapping called `mapping` (see
+    /// [`Mapping::name`]), `None` for anonymous memory
+    pub fn classify(perms: &[MemoryRegionPerms], mapping: Option<&str>) -> RegionKind {
+        match mapping {
+            Some(name) if name.starts_with("[stack") => RegionKind::Stack,
+            Some("[heap]") => RegionKind::Heap,
+            _ if perms.contains(&MemoryRegionPerms::Execute) => RegionKind::Code,
+            Some(name) if !name.starts_with('[') => RegionKind::File,
+            None if perms.contains(&MemoryRegionPerms::Write) => RegionKind::Heap,
+            _ => RegionKind::Data,
+        }
+    }
+}
+
+/// Kind of `region`, named from `region_names`
+pub fn classify_region(region: &MemoryRegion, region_names: &RegionNames) -> RegionKind {
+    RegionKind::classify(&region.perms, region_names.region_name_for(region.start))
+}
+
+/// A region of a process with the mapping it belongs to, see [`region_list`]
+#[derive(Debug, Clone)]
+pub struct RegionEntry {
+    pub region: MemoryRegion,
+    /// Name of the mapping, `None` for anonymous memory
+    pub mapping: Option<String>,
+    /// Mapped from the executable of the process
+    pub main_module: bool,
+}
+
+/// Every region of `source` in the order `regions` numbers them, named from `region_names`.
+/// Regions of the mapping called `main_module` (the executable's file name) are marked.
+pub fn region_list(
+    source: &dyn MemorySource,
+    region_names: &RegionNames,
+    main_module: Option<&str>,
+) -> Result<Vec<RegionEntry>, MemoryError> {
+    Ok(all_regions(source)?
+        .into_iter()
+        .map(|region| {
+            let mapping = region_names.region_name_for(region.start).map(str::to_string);
+            let main_module = main_module.is_some() && mapping.as_deref() == main_module;
+            RegionEntry { region, mapping, main_module }
+        })
+        .collect())
+}
+
+/// Mappings of process `pid` in address order. Empty if the mappings can't be read or
+/// on other platforms than Linux.
+pub fn mappings(pid: u32) -> Vec<Mapping> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(maps) = std::fs::read_to_string(format!("/proc/{pid}/maps")) else {
+            return vec![];
+        };
+        parse_mappings(&maps)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        vec![]
+    }
+}
+
+/// Mappings in the text of a `/proc/<pid>/maps` file
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_mappings(maps: &str) -> Vec<Mapping> {
+    maps.lines()
+        .filter_map(|line| {
+            // start-end perms offset dev inode path, the path may contain spaces
+            let mut fields = line.splitn(6, char::is_whitespace);
+            let (start, end) = fields.next()?.split_once('-')?;
+            Some(Mapping {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                path: fields.nth(4).map(str::trim).unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A thread of a process, see [`threads`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    /// Name from `/proc/<pid>/task/<tid>/comm`, empty if it can't be read
+    pub name: String,
+    /// State letter from its `stat` file, e.g. `R` running, `S` sleeping
+    pub state: Option<char>,
+}
+
+/// Threads of process `pid` ordered by thread ID, the main thread (tid = pid) first. Empty
+/// if there is no such process or on other platforms than Linux.
+pub fn threads(pid: u32) -> Vec<ThreadInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(entries) = std::fs::read_dir(format!("/proc/{pid}/task")) else {
+            return vec![];
+        };
+        let mut threads: Vec<ThreadInfo> = entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .map(|tid: u32| {
+                let task = format!("/proc/{pid}/task/{tid}");
+                ThreadInfo {
+                    tid,
+                    name: std::fs::read_to_string(format!("{task}/comm"))
+                        .map(|name| name.trim_end().to_string())
+                        .unwrap_or_default(),
+                    state: task_state(&task),
+                }
+            })
+            .collect();
+        threads.sort_by_key(|t| (t.tid != pid, t.tid));
+        threads
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        vec![]
+    }
+}
+
+/// The mapping holding the stack of thread `tid` of process `pid`. The main thread's is
+/// the `[stack]` mapping; the kernel doesn't name the stacks of other threads, they are
+/// found from the stack pointer, which is only known while the thread waits in a system
+/// call (most threads of a game do most of the time) and needs ptrace access to the
+/// process. Fails with `NotFound` if `tid` isn't a thread of `pid`.
+pub fn thread_stack(pid: u32, tid: u32) -> std::io::Result<Mapping> {
+    use std::io::{Error, ErrorKind};
+
+    if !threads(pid).iter().any(|t| t.tid == tid) {
+        return Err(Error::new(ErrorKind::NotFound, format!("thread {tid} doesn't belong to process {pid}")));
+    }
+    let mappings = mappings(pid);
+    if tid == pid {
+        return mappings
+            .into_iter()
+            .find(|m| m.path == "[stack]")
+            .ok_or_else(|| Error::other(format!("process {pid} has no [stack] mapping")));
+    }
+
+    let syscall = std::fs::read_to_string(format!("/proc/{pid}/task/{tid}/syscall"))?;
+    let stack_pointer = parse_syscall_stack_pointer(&syscall).ok_or_else(|| {
+        Error::other(format!("thread {tid} isn't waiting in a system call, its stack pointer is unknown"))
+    })?;
+    mappings
+        .into_iter()
+        .find(|m| (m.start..m.end).contains(&stack_pointer))
+        .ok_or_else(|| Error::other(format!("stack pointer 0x{stack_pointer:x} of thread {tid} isn't mapped")))
+}
+
+/// Stack pointer in the text of a `/proc/<pid>/task/<tid>/syscall` file: the system call
+/// number and its six arguments, then the stack and instruction pointer. A running thread
+/// shows `running` instead.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_syscall_stack_pointer(syscall: &str) -> Option<u64> {
+    let fields: Vec<&str> = syscall.split_whitespace().collect();
+    if fields.len() != 9 {
+        return None;
+    }
+    u64::from_str_radix(fields[7].trim_start_matches("0x"), 16).ok()
+}
+
+/// Process paused by the live [`PausedProcess`], so a second Ctrl+C that exits right
+/// away can still let it run again. 0 when none is paused.
+#[cfg(target_os = "linux")]
+static PAUSED_PID: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+
+/// A process stopped by [`pause`]. It runs again when this is dropped, including when an
+/// error is returned or a panic unwinds past it.
+///
+/// Keep the pause short: a game stopped for long can lose its connection to a server,
+/// and timers measured by wall clock jump once it runs again.
+#[derive(Debug)]
+pub struct PausedProcess {
+    pid: u32,
+    /// Threads suspended with `SuspendThread`, resumed in the same order
+    #[cfg(target_os = "windows")]
+    threads: Vec<windows::Win32::Foundation::HANDLE>,
+}
+
+/// Stop process `pid` until the returned guard is dropped, for a snapshot of its memory
+/// that doesn't change while it's read. On Linux the process gets `SIGSTOP` and this
+/// waits (briefly) until it has stopped; on Windows every thread of it is suspended.
+pub fn pause(pid: u32) -> std::io::Result<PausedProcess> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::sync::atomic::Ordering;
+
+        if unsafe { libc::kill(pid as i32, libc::SIGSTOP) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        PAUSED_PID.store(pid as i32, Ordering::SeqCst);
+        let paused = PausedProcess { pid };
+        // SIGSTOP is delivered asynchronously, threads may still be running for a moment
+        for _ in 0..100 {
+            if process_state(pid).is_none_or(|state| state == 'T' || state == 't') {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        Ok(paused)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::SuspendThread;
+
+        let mut paused = PausedProcess { pid, threads: vec![] };
+        for thread in open_threads(pid)? {
+            if unsafe { SuspendThread(thread) } == u32::MAX {
+                unsafe {
+                    let _ = CloseHandle(thread);
+                }
+            } else {
+                paused.threads.push(thread);
+            }
+        }
+        if paused.threads.is_empty() {
+            return Err(std::io::Error::other(format!("no thread of process {pid} could be suspended")));
+        }
+        Ok(paused)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = pid;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "pausing a process isn't supported on this platform",
+        ))
+    }
+}
+
+/// Handles with `THREAD_SUSPEND_RESUME` access to the threads of process `pid` from a
+/// Toolhelp snapshot, threads that can't be opened are left out. The caller closes them.
+#[cfg(target_os = "windows")]
+fn open_threads(pid: u32) -> std::io::Result<Vec<windows::Win32::Foundation::HANDLE>> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, TH32CS_SNAPTHREAD, THREADENTRY32, Thread32First, Thread32Next,
+    };
+    use windows::Win32::System::Threading::{OpenThread, THREAD_SUSPEND_RESUME};
+
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) }
+        .map_err(|e| std::io::Error::from_raw_os_error(e.code().0))?;
+    let mut threads = vec![];
+    let mut entry = THREADENTRY32 {
+        dwSize: size_of::<THREADENTRY32>() as u32,
+        ..Default::default()
+    };
+    let mut next = unsafe { Thread32First(snapshot, &mut entry) };
+    while next.is_ok() {
+        if entry.th32OwnerProcessID == pid
+            && let Ok(thread) = unsafe { OpenThread(THREAD_SUSPEND_RESUME, false, entry.th32ThreadID) }
+        {
+            threads.push(thread);
+        }
+        next = unsafe { Thread32Next(snapshot, &mut entry) };
+    }
+    unsafe {
+        let _ = CloseHandle(snapshot);
+    }
+    Ok(threads)
+}
+
+impl Drop for PausedProcess {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            PAUSED_PID.store(0, std::sync::atomic::Ordering::SeqCst);
+            unsafe {
+                libc::kill(self.pid as i32, libc::SIGCONT);
+            }
+        }
+        #[cfg(target_os = "windows")]
+        for thread in self.threads.drain(..) {
+            use windows::Win32::Foundation::CloseHandle;
+            use windows::Win32::System::Threading::ResumeThread;
+
+            unsafe {
+                ResumeThread(thread);
+                let _ = CloseHandle(thread);
+            }
+        }
+    }
+}
+
+/// Send `SIGCONT` to the process a [`PausedProcess`] stopped, for exit paths that skip
+/// destructors. Only async-signal-safe calls, so it can run in a signal handler.
+#[cfg(target_os = "linux")]
+pub(crate) fn resume_paused_on_exit() {
+    let pid = PAUSED_PID.load(std::sync::atomic::Ordering::SeqCst);
+    if pid != 0 {
+        unsafe {
+            libc::kill(pid, libc::SIGCONT);
+        }
+    }
+}
+
+/// What [`control_process`] does to a process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessAction {
+    Suspend,
+    Resume,
+    Kill,
+}
+
+/// Suspend, resume or kill process `pid`: `SIGSTOP`, `SIGCONT` or `SIGKILL` on Linux, every
+/// thread suspended or resumed or the process terminated on Windows. Unlike [`pause`] a
+/// suspended process stays stopped until it is resumed. Pid 0, init and this process itself
+/// are refused.
+pub fn control_process(pid: u32, action: ProcessAction) -> std::io::Result<()> {
+    if pid == 0 || pid == 1 || pid == std::process::id() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("refusing to {action:?} process {pid}").to_lowercase(),
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let signal = match action {
+            ProcessAction::Suspend => libc::SIGSTOP,
+            ProcessAction::Resume => libc::SIGCONT,
+            ProcessAction::Kill => libc::SIGKILL,
+        };
+        if unsafe { libc::kill(pid as i32, signal) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, PROCESS_TERMINATE, ResumeThread, SuspendThread, TerminateProcess,
+        };
+
+        let os_error = |e: windows::core::Error| std::io::Error::from_raw_os_error(e.code().0);
+        if action == ProcessAction::Kill {
+            let process = unsafe { OpenProcess(PROCESS_TERMINATE, false, pid) }.map_err(os_error)?;
+            let terminated = unsafe { TerminateProcess(process, 1) };
+            unsafe {
+                let _ = CloseHandle(process);
+            }
+            return terminated.map_err(os_error);
+        }
+
+        let mut changed = 0;
+        for thread in open_threads(pid)? {
+            let previous = match action {
+                ProcessAction::Suspend => unsafe { SuspendThread(thread) },
+                _ => unsafe { ResumeThread(thread) },
+            };
+            if previous != u32::MAX {
+                changed += 1;
+            }
+            unsafe {
+                let _ = CloseHandle(thread);
+            }
+        }
+        if changed == 0 {
+            return Err(std::io::Error::other(format!("no thread of process {pid} could be changed")));
+        }
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = action;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "controlling a process isn't supported on this platform",
+        ))
+    }
+}
+
+/// State letter from `/proc/<pid>/stat`, e.g. `R` running, `S` sleeping, `T` stopped
+#[cfg(target_os = "linux")]
+fn process_state(pid: u32) -> Option<char> {
+    task_state(&format!("/proc/{pid}"))
+}
+
+/// State letter from the `stat` file in a `/proc/<pid>` or `/proc/<pid>/task/<tid>` directory
+#[cfg(target_os = "linux")]
+fn task_state(dir: &str) -> Option<char> {
+    let stat = std::fs::read_to_string(format!("{dir}/stat")).ok()?;
+    // the name in parentheses may contain spaces and parentheses itself
+    stat.rsplit_once(')')?.1.trim_start().chars().next()
+}
+
 mod test {
     #[allow(unused_imports)]
     use super::*;
@@ -52,4 +898,348 @@ mod test {
             assert!(proc.name.to_lowercase().starts_with("car"));
         }
     }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_wait_for_process() {
+        let stop = AtomicBool::new(false);
+        let err = wait_for_process("no-such-process-name", Duration::from_millis(100), &stop).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let child = crate::core::utils::ChildGuard(child);
+        let found = wait_for_process("slee", Duration::from_secs(5), &stop).unwrap();
+        assert!(found.iter().any(|p| p.pid == child.0.id()));
+
+        stop.store(true, Ordering::SeqCst);
+        let err = wait_for_process("no-such-process-name", Duration::from_secs(5), &stop).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn test_process_details_self() {
+        let details = process_details(std::process::id()).unwrap();
+        assert_eq!(details.pid, std::process::id());
+        assert!(!details.name.is_empty());
+        assert!(details.exe.is_some());
+        assert!(!details.cmdline.unwrap().is_empty());
+        assert_eq!(details.cwd, std::env::current_dir().ok());
+        assert!(details.start_time.is_some());
+        assert!(start_time(std::process::id()).is_some());
+        assert_eq!(start_time(std::process::id()), start_time(std::process::id()));
+
+        assert!(process_details(u32::MAX - 1).is_none());
+        assert!(start_time(u32::MAX - 1).is_none());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_pointer_width_self() {
+        assert_eq!(pointer_width(std::process::id()), Some(size_of::<usize>()));
+        assert_eq!(pointer_width(u32::MAX - 1), None);
+        assert_eq!(process_is_64bit(std::process::id()), size_of::<usize>() == 8);
+    }
+
+    #[test]
+    fn test_elf_pointer_width() {
+        let path = std::env::temp_dir().join(format!("cheat-engine-rs-elf-{}", std::process::id()));
+        // e_ident of a 32-bit little endian executable, the rest of the header isn't read
+        let mut header = b"\x7fELF\x01\x01\x01".to_vec();
+        header.resize(52, 0);
+        for (class, width) in [(1, Some(4)), (2, Some(8)), (3, None)] {
+            header[4] = class;
+            std::fs::write(&path, &header).unwrap();
+            assert_eq!(elf_pointer_width(&path), width);
+        }
+        std::fs::write(&path, b"#!/bin/sh\n").unwrap();
+        assert_eq!(elf_pointer_width(&path), None);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(elf_pointer_width(&path), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_thread_stack_holds_the_threads_locals() {
+        let pid = std::process::id();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (done, wait) = std::sync::mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let local = 0_u64;
+            sender.send((unsafe { libc::gettid() } as u32, &local as *const u64 as u64)).unwrap();
+            // blocks in a system call, so the stack pointer can be read
+            let _ = wait.recv();
+        });
+        let (tid, local) = receiver.recv().unwrap();
+
+        let list = threads(pid);
+        assert_eq!(list[0].tid, pid);
+        assert!(list.iter().any(|t| t.tid == tid));
+        let mut stack = Err(std::io::Error::other("not read"));
+        for _ in 0..100 {
+            stack = thread_stack(pid, tid);
+            if stack.is_ok() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let stack = stack.unwrap();
+        assert!((stack.start..stack.end).contains(&local));
+        assert_eq!(thread_stack(pid, pid).unwrap().path, "[stack]");
+        assert_eq!(thread_stack(pid, u32::MAX - 1).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        drop(done);
+        thread.join().unwrap();
+
+        assert_eq!(parse_syscall_stack_pointer("running\n"), None);
+        assert_eq!(parse_syscall_stack_pointer("7 0x1 0x2 0x3 0x4 0x5 0x6 0x7ffd1000 0x401000\n"), Some(0x7ffd1000));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_control_process() {
+        let child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let mut child = crate::core::utils::ChildGuard(child);
+        let pid = child.0.id();
+        let wait_for = |stopped: bool| {
+            for _ in 0..100 {
+                if process_state(pid).is_some_and(|state| (state == 'T') == stopped) {
+                    return true;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            false
+        };
+
+        control_process(pid, ProcessAction::Suspend).unwrap();
+        assert!(wait_for(true));
+        control_process(pid, ProcessAction::Resume).unwrap();
+        assert!(wait_for(false));
+        control_process(pid, ProcessAction::Kill).unwrap();
+        assert!(!child.0.wait().unwrap().success());
+
+        for pid in [0, 1, std::process::id()] {
+            let err = control_process(pid, ProcessAction::Kill).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn test_parse_modules() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 08:01 1234 /usr/bin/game
+55d0c0a02000-55d0c0a08000 r-xp 00002000 08:01 1234 /usr/bin/game
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0    [heap]
+7f0000000000-7f0000028000 r--p 00000000 08:01 99   /usr/lib/libc.so.6
+7f0000100000-7f0000101000 rw-p 00000000 08:01 100  /tmp/save data.bin
+7ffc00000000-7ffc00021000 rw-p 00000000 00:00 0    [stack]
+";
+        let modules = modules_in(&parse_mappings(maps));
+        let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["game", "libc.so.6", "save data.bin"]);
+        assert_eq!(modules[0].base, 0x55d0c0a00000);
+        assert_eq!(modules[0].end, 0x55d0c0a08000);
+        assert_eq!(modules[2].path, PathBuf::from("/tmp/save data.bin"));
+    }
+
+    #[test]
+    fn test_parse_mappings() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 08:01 1234 /usr/bin/game
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0    [heap]
+7f0000050000-7f0000051000 rw-p 00000000 00:00 0
+7f0000100000-7f0000101000 rw-p 00000000 08:01 100  /tmp/save data.bin
+";
+        let mappings = parse_mappings(maps);
+        let names: Vec<&str> = mappings.iter().map(Mapping::name).collect();
+        assert_eq!(names, vec!["game", "[heap]", "", "save data.bin"]);
+        assert_eq!((mappings[1].start, mappings[1].end), (0x55d0c1000000, 0x55d0c1021000));
+        assert_eq!(mappings[3].path, "/tmp/save data.bin");
+    }
+
+    #[test]
+    fn test_classify_region() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 08:01 1234 /usr/bin/game
+55d0c0a02000-55d0c0a04000 r-xp 00002000 08:01 1234 /usr/bin/game
+55d0c0a04000-55d0c0a05000 rw-p 00004000 08:01 1234 /usr/bin/game
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0    [heap]
+7f0000050000-7f0000051000 rw-p 00000000 00:00 0
+7f0000060000-7f0000061000 r--p 00000000 00:00 0
+7f0000070000-7f0000071000 rwxp 00000000 00:00 0
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0    [stack]
+7ffd00100000-7ffd00104000 r--p 00000000 00:00 0    [vvar]
+7ffd00104000-7ffd00106000 r-xp 00000000 00:00 0    [vdso]
+";
+        let region_names = RegionNames::new(parse_mappings(maps));
+        let kinds: Vec<&str> = maps
+            .lines()
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                let (start, end) = fields.next().unwrap().split_once('-').unwrap();
+                let perms = fields.next().unwrap();
+                let region = MemoryRegion {
+                    start: u64::from_str_radix(start, 16).unwrap(),
+                    end: u64::from_str_radix(end, 16).unwrap(),
+                    perms: [('r', MemoryRegionPerms::Read), ('w', MemoryRegionPerms::Write), ('x', MemoryRegionPerms::Execute)]
+                        .into_iter()
+                        .filter(|(c, _)| perms.contains(*c))
+                        .map(|(_, perm)| perm)
+                        .collect(),
+                };
+                classify_region(&region, &region_names).name()
+            })
+            .collect();
+        assert_eq!(kinds, ["file", "code", "file", "heap", "heap", "data", "code", "stack", "data", "code"]);
+        assert_eq!(RegionKind::from_name("heap"), Some(RegionKind::Heap));
+        assert_eq!(RegionKind::from_name("bss"), None);
+    }
+
+    #[test]
+    fn test_region_name_for() {
+        let mapping = |start, end, path: &str| Mapping { start, end, path: path.to_string() };
+        let names = RegionNames::new(vec![
+            mapping(0x7f0000000000, 0x7f0000028000, "/usr/lib/libc.so.6"),
+            mapping(0x400000, 0x401000, "/usr/bin/game"),
+            mapping(0x401000, 0x402000, ""),
+            mapping(0x402000, 0x403000, "/usr/bin/game"),
+            mapping(0x1000000, 0x1021000, "[heap]"),
+        ]);
+
+        assert_eq!(names.region_name_for(0x400000), Some("game"));
+        assert_eq!(names.region_name_for(0x400fff), Some("game"));
+        assert_eq!(names.region_name_for(0x401000), None);
+        assert_eq!(names.region_name_for(0x402800), Some("game"));
+        assert_eq!(names.region_name_for(0x1000010), Some("[heap]"));
+        assert_eq!(names.region_name_for(0x7f0000027fff), Some("libc.so.6"));
+        assert_eq!(names.region_name_for(0x7f0000028000), None);
+        assert_eq!(names.region_name_for(0x1000), None);
+        assert_eq!(names.index_for(0x402000), Some(1));
+        assert_eq!(RegionNames::default().region_name_for(0x400000), None);
+    }
+
+    #[test]
+    fn test_region_list_marks_the_main_module() {
+        use crate::core::mem::MemoryRegionPerms::*;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        memory.add_region(0x400000, vec![0; 0x1000], vec![Read, Execute]);
+        memory.add_region(0x401000, vec![0; 0x1000], vec![Read, Write]);
+        memory.add_region(0x7f0000000000, vec![0; 0x1000], vec![Read]);
+        let mapping = |start, end, path: &str| Mapping { start, end, path: path.to_string() };
+        let names = RegionNames::new(vec![
+            mapping(0x400000, 0x401000, "/usr/bin/game"),
+            mapping(0x7f0000000000, 0x7f0000001000, "/usr/lib/libc.so.6"),
+        ]);
+
+        let regions = region_list(&memory, &names, Some("game")).unwrap();
+        let rows: Vec<_> = regions.iter().map(|e| (e.region.start, e.mapping.as_deref(), e.main_module)).collect();
+        assert_eq!(
+            rows,
+            [(0x400000, Some("game"), true), (0x401000, None, false), (0x7f0000000000, Some("libc.so.6"), false)]
+        );
+        assert!(region_list(&memory, &names, None).unwrap().iter().all(|e| !e.main_module));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_pause_and_resume() {
+        let mut child = std::process::Command::new("sleep").arg("10").spawn().unwrap();
+        let pid = child.id();
+
+        let paused = pause(pid).unwrap();
+        assert_eq!(process_state(pid), Some('T'));
+        drop(paused);
+        let mut state = process_state(pid);
+        for _ in 0..100 {
+            if state != Some('T') {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            state = process_state(pid);
+        }
+        assert_ne!(state, Some('T'));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(pause(pid).is_err());
+    }
+
+    #[test]
+    fn test_pick_successor() {
+        let process = |pid, parent, exe: &str, start_time| ProcessLineage {
+            pid,
+            parent,
+            exe: PathBuf::from(exe),
+            start_time,
+            descendants: vec![],
+        };
+        let original = process(100, Some(1), "/opt/game/game", 10);
+
+        let sibling = process(300, Some(1), "/opt/game/game", 30);
+        let older_child = process(200, Some(100), "/opt/game/game", 20);
+        let child = process(201, Some(100), "/opt/game/game", 25);
+        let other = process(400, Some(100), "/usr/bin/helper", 40);
+        let unrelated = process(500, Some(7), "/opt/game/game", 50);
+        let candidates = vec![sibling.clone(), older_child, child.clone(), other.clone(), unrelated.clone()];
+        assert_eq!(pick_successor(&original, candidates.clone()), Some(child));
+
+        // re-executed in place
+        let mut with_self = candidates;
+        with_self.push(process(100, Some(1), "/opt/game/game", 10));
+        assert_eq!(pick_successor(&original, with_self).map(|p| p.pid), Some(100));
+
+        assert_eq!(pick_successor(&original, vec![sibling.clone(), other.clone()]), Some(sibling.clone()));
+        assert_eq!(pick_successor(&original, vec![other.clone(), unrelated.clone()]), None);
+
+        // a child reparented to init once the original exited, known from its descendants
+        let orphan = process(202, Some(1), "/opt/game/game", 26);
+        let mut recorded = original.clone();
+        recorded.add_descendants(&[process(202, Some(100), "/opt/game/game", 26), other.clone()]);
+        assert_eq!(recorded.descendants, vec![202, 400]);
+        assert_eq!(
+            pick_successor(&recorded, vec![sibling.clone(), orphan.clone(), unrelated]),
+            Some(orphan.clone())
+        );
+        assert_eq!(pick_successor(&original, vec![sibling.clone(), orphan]), Some(sibling));
+        // a reused pid started before the original isn't a descendant
+        recorded.add_descendants(&[process(600, Some(202), "/opt/game/game", 5)]);
+        assert_eq!(recorded.descendants, vec![202, 400]);
+
+        let me = lineage(std::process::id()).unwrap();
+        assert_eq!(me.exe, std::env::current_exe().unwrap());
+        assert_eq!(find_successor(&me).map(|p| p.pid), Some(std::process::id()));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_successor_after_fork_and_exit() {
+        use std::process::Command;
+
+        // the child shell outlives the original, which exits without waiting for it
+        let mut original = Command::new("sh")
+            .args(["-c", "sh -c 'sleep 5; :' & sleep 0.5; exit 0"])
+            .spawn()
+            .unwrap();
+        let pid = original.id();
+        let mut lineage = lineage(pid).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while lineage.descendants.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            lineage.record_descendants();
+        }
+        let recorder = LineageRecorder::start(lineage, Duration::from_millis(20));
+        original.wait().unwrap();
+        let lineage = recorder.finish();
+        assert!(!lineage.descendants.is_empty());
+
+        let successor = find_successor(&lineage).unwrap();
+        assert_ne!(successor.pid, pid);
+        assert_ne!(successor.parent, Some(pid));
+        assert!(lineage.descendants.contains(&successor.pid));
+        for descendant in &lineage.descendants {
+            unsafe {
+                libc::kill(*descendant as i32, libc::SIGKILL);
+            }
+        }
+    }
 }
@@ -0,0 +1,183 @@
+//! Pointer paths: a base address and the offsets to follow from it, so a value that moves
+//! between runs of the target can be found again from a static address.
+//!
+//! A path resolves like `[[[base] + a] + b] + c`: the pointer at the base is read and the
+//! first offset added, the pointer there is read and the next offset added, and so on. The
+//! address left after the last offset holds the value.
+//!
+//! Paths files are JSON, an array of objects with the base as an address expression (e.g.
+//! `libgame.so+0x1a2b0`, resolved by the caller) and the offsets as numbers or as strings,
+//! which may be `0x` hex and negative:
+//!
+//! ```text
+//! [
+//!   { "base": "libgame.so+0x1a2b0", "offsets": ["0x10", 8, "-0x4"] },
+//!   { "base": "0x601040", "offsets": [] }
+//! ]
+//! ```
+//!
+//! Other keys are skipped, so a pointer scan can store more about each path.
+use std::fmt::Display;
+
+use crate::core::json::{Json, JsonError, parse_json};
+use crate::core::mem::MemorySource;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    /// The paths file isn't valid JSON, `position` is the byte offset of the problem
+    Json { position: usize, message: String },
+    /// Path `index` (from 0) doesn't have the expected shape
+    InvalidPath { index: usize, message: String },
+    /// The pointer read for offset `step` (from 0) failed
+    Unreadable { step: usize, address: u64 },
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json { position, message } => {
+                write!(f, "Invalid paths file at byte {position}: {message}")
+            }
+            Self::InvalidPath { index, message } => write!(f, "Invalid path #{}: {message}", index + 1),
+            Self::Unreadable { step, address } => {
+                write!(f, "offset #{}: pointer at 0x{address:x} can't be read", step + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerPath {
+    /// Address expression of the first pointer
+    pub base: String,
+    pub offsets: Vec<i64>,
+}
+
+/// `libgame.so+0x1a2b0 -> 0x10 -> -0x4`
+impl Display for PointerPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.base)?;
+        for offset in &self.offsets {
+            if *offset < 0 {
+                write!(f, " -> -0x{:x}", offset.unsigned_abs())?;
+            } else {
+                write!(f, " -> 0x{offset:x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Follow `offsets` from the pointer at `base`, reading `pointer_size` byte pointers (4 for
+/// a 32-bit process), and return the final address
+pub fn resolve_chain(
+    source: &dyn MemorySource,
+    pointer_size: usize,
+    base: u64,
+    offsets: &[i64],
+) -> Result<u64, PathError> {
+    let mut address = base;
+    for (step, offset) in offsets.iter().enumerate() {
+        let bytes = source
+            .read(address as usize, pointer_size)
+            .ok()
+            .filter(|bytes| bytes.len() == pointer_size)
+            .ok_or(PathError::Unreadable { step, address })?;
+        let pointer = match pointer_size {
+            4 => u32::from_le_bytes(bytes[..4].try_into().unwrap()) as u64,
+            _ => u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+        };
+        address = pointer.wrapping_add_signed(*offset);
+    }
+    Ok(address)
+}
+
+/// Parse a paths file, see the module docs for the format
+pub fn parse_paths(text: &str) -> Result<Vec<PointerPath>, PathError> {
+    let value = parse_json(text).map_err(|JsonError { position, message }| PathError::Json { position, message })?;
+    let Json::Array(items) = value else {
+        return Err(PathError::Json { position: 0, message: String::from("expected an array of paths") });
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let invalid = |message: &str| PathError::InvalidPath { index, message: message.to_string() };
+            if !matches!(item, Json::Object(_)) {
+                return Err(invalid("expected an object"));
+            }
+            let field = |name: &str| item.field(name);
+            let base = match field("base") {
+                Some(Json::String(base)) if !base.trim().is_empty() => base.trim().to_string(),
+                Some(_) => return Err(invalid("\"base\" must be an address string")),
+                None => return Err(invalid("missing \"base\"")),
+            };
+            let offsets = match field("offsets") {
+                None => vec![],
+                Some(Json::Array(offsets)) => offsets
+                    .iter()
+                    .map(|offset| offset.integer().ok_or_else(|| invalid("offsets must be integers")))
+                    .collect::<Result<_, _>>()?,
+                Some(_) => return Err(invalid("\"offsets\" must be an array")),
+            };
+            Ok(PointerPath { base, offsets })
+        })
+        .collect()
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_paths_and_resolve_chain() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let paths = parse_paths(
+            r#"[
+                {"base": "libgame.so+0x1a2b0", "offsets": ["0x10", 8, "-0x4"], "score": 0.5},
+                {"base": " 0x1000 "}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(paths[0].offsets, [0x10, 8, -4]);
+        assert_eq!(paths[0].to_string(), "libgame.so+0x1a2b0 -> 0x10 -> 0x8 -> -0x4");
+        assert_eq!(paths[1], PointerPath { base: String::from("0x1000"), offsets: vec![] });
+
+        assert!(matches!(parse_paths("[{\"base\": 1}]"), Err(PathError::InvalidPath { index: 0, .. })));
+        assert!(matches!(
+            parse_paths("[{\"base\": \"a\"}, {\"offsets\": []}]"),
+            Err(PathError::InvalidPath { index: 1, .. })
+        ));
+        assert!(matches!(
+            parse_paths("[{\"base\": \"a\", \"offsets\": [1.5]}]"),
+            Err(PathError::InvalidPath { index: 0, .. })
+        ));
+        assert_eq!(
+            parse_paths("[{\"base\": \"a\"} {}]"),
+            Err(PathError::Json { position: 15, message: String::from("expected ',' or ']'") })
+        );
+        assert!(matches!(parse_paths("{\"base\": \"a\"}"), Err(PathError::Json { .. })));
+
+        // 0x1000 -> 0x2000, 0x2010 -> 0x3000, value at 0x3008
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x3000];
+        data[0..8].copy_from_slice(&0x2000_u64.to_le_bytes());
+        data[0x1010..0x1018].copy_from_slice(&0x3000_u64.to_le_bytes());
+        data[0x2008..0x200c].copy_from_slice(&1234_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read]);
+
+        assert_eq!(resolve_chain(&memory, 8, 0x1000, &[0x10, 8]), Ok(0x3008));
+        assert_eq!(resolve_chain(&memory, 8, 0x1000, &[]), Ok(0x1000));
+        // 32-bit pointers only read the low half
+        assert_eq!(resolve_chain(&memory, 4, 0x1000, &[0x18]), Ok(0x2018));
+        // the pointer at 0x3008 is 1234, which isn't mapped
+        assert_eq!(
+            resolve_chain(&memory, 8, 0x1000, &[0x10, 8, 0, 0]),
+            Err(PathError::Unreadable { step: 3, address: 1234 })
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Pointer scanning: find memory that holds pointers to addresses of interest.
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rayon::prelude::*;
+
+use crate::core::mem::{MemoryError, MemoryRegion, MemoryRegionPerms, MemorySource};
+
+const BLOCK_SIZE: usize = 0x10000;
+
+/// A pointer-aligned value at `address` that equals `target`. Pointers are 4 or 8 bytes,
+/// the width of the target process (see [`crate::core::proc::process_is_64bit`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub address: u64,
+    pub target: u64,
+}
+
+/// Find every pointer-aligned value in `regions` that equals one of `targets`, sorted by
+/// address. Blocks that can't be read are skipped, and once `stop` is raised no further
+/// blocks are read.
+pub fn find_references(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    pointer_size: usize,
+    targets: &HashSet<u64>,
+    stop: &AtomicBool,
+) -> Result<Vec<Reference>, MemoryError> {
+    if targets.is_empty() {
+        return Ok(vec![]);
+    }
+    scan_pointers(source, regions, pointer_size, stop, |value| targets.contains(&value))
+}
+
+/// Find every pointer-aligned value in `regions` that points into `range`, e.g. the span
+/// a module is mapped at, sorted by address. Pointers into a module's code or data are
+/// often vtables or back-references of the structure holding them. Blocks that can't be
+/// read are skipped, and once `stop` is raised no further blocks are read.
+pub fn find_pointers_into(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    pointer_size: usize,
+    range: Range<u64>,
+    stop: &AtomicBool,
+) -> Result<Vec<Reference>, MemoryError> {
+    scan_pointers(source, regions, pointer_size, stop, |value| range.contains(&value))
+}
+
+/// The regions of `source` to search for pointers to a known address: every readable one,
+/// as vtables and relocated data (`.data.rel.ro`) are read-only once the program runs, or
+/// only the writable ones with `writable_only`
+pub fn pointer_regions(source: &dyn MemorySource, writable_only: bool) -> Result<Vec<MemoryRegion>, MemoryError> {
+    let perm = match writable_only {
+        true => MemoryRegionPerms::Write,
+        false => MemoryRegionPerms::Read,
+    };
+    source.regions(None, None, Some(&[perm]))
+}
+
+/// Find every pointer-aligned value in `regions` that equals `target`, e.g. the address of
+/// a function to find the callbacks and vtable slots holding it, sorted by address. Like
+/// [`find_references`] with one target, but compares each value against it directly.
+pub fn find_pointers_to(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    pointer_size: usize,
+    target: u64,
+    stop: &AtomicBool,
+) -> Result<Vec<Reference>, MemoryError> {
+    scan_pointers(source, regions, pointer_size, stop, |value| value == target)
+}
+
+/// Every pointer-aligned value in `regions` that `is_match`, with its address. Values are
+/// `pointer_size` bytes, 4 reads them as the pointers of a 32-bit process.
+fn scan_pointers(
+    source: &dyn MemorySource,
+    regions: &[MemoryRegion],
+    pointer_size: usize,
+    stop: &AtomicBool,
+    is_match: impl Fn(u64) -> bool + Sync,
+) -> Result<Vec<Reference>, MemoryError> {
+    let mut blocks = vec![];
+    for region in regions {
+        // Validate region with a single byte read to catch ProcessAttach errors early
+        if let Err(e @ MemoryError::ProcessAttach(_)) = source.read(region.start as usize, 1) {
+            return Err(e);
+        }
+
+        // blocks are a multiple of the pointer size, so no pointer straddles two of them
+        let mut address = region.start.next_multiple_of(pointer_size as u64);
+        while address + pointer_size as u64 <= region.end {
+            let len = std::cmp::min(BLOCK_SIZE as u64, region.end - address) as usize;
+            let len = len - len % pointer_size;
+            blocks.push((address, len));
+            address += len as u64;
+        }
+    }
+
+    let mut references: Vec<Reference> = blocks
+        .par_iter()
+        .filter_map(|&(address, len)| {
+            if stop.load(Ordering::SeqCst) {
+                return None;
+            }
+            source.read(address as usize, len).ok().map(|b| (address, b))
+        })
+        .flat_map_iter(|(address, block)| {
+            block
+                .chunks_exact(pointer_size)
+                .enumerate()
+                .filter_map(|(i, bytes)| {
+                    let value = match pointer_size {
+                        4 => u32::from_le_bytes(bytes.try_into().unwrap()) as u64,
+                        _ => u64::from_le_bytes(bytes.try_into().unwrap()),
+                    };
+                    is_match(value).then(|| Reference {
+                        address: address + (i * pointer_size) as u64,
+                        target: value,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    references.sort_by_key(|r| r.address);
+
+    Ok(references)
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_find_references() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x40];
+        data[0x08..0x10].copy_from_slice(&0x2010_u64.to_le_bytes());
+        data[0x18..0x20].copy_from_slice(&0x2000_u64.to_le_bytes());
+        // not pointer-aligned, ignored
+        data[0x23..0x2b].copy_from_slice(&0x2000_u64.to_le_bytes());
+        data[0x30..0x38].copy_from_slice(&0x2008_u64.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let regions = memory.regions(None, None, None).unwrap();
+
+        let targets = HashSet::from([0x2000, 0x2010]);
+        let stop = AtomicBool::new(false);
+        let references = find_references(&memory, &regions, 8, &targets, &stop).unwrap();
+        assert_eq!(
+            references,
+            vec![
+                Reference { address: 0x1008, target: 0x2010 },
+                Reference { address: 0x1018, target: 0x2000 },
+            ]
+        );
+
+        assert!(find_references(&memory, &regions, 8, &HashSet::new(), &stop).unwrap().is_empty());
+
+        // the pointers of a 32-bit process are 4 bytes, read as 8 they run together
+        let memory32 = FakeMemory::new();
+        let data: Vec<u8> = [0x2000_u32, 0x1234, 0x2010, 0].iter().flat_map(|p| p.to_le_bytes()).collect();
+        memory32.add_region(0x3000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let regions32 = memory32.regions(None, None, None).unwrap();
+        let addresses = |size| -> Vec<u64> {
+            let references = find_references(&memory32, &regions32, size, &targets, &stop).unwrap();
+            references.iter().map(|r| r.address).collect()
+        };
+        assert_eq!(addresses(4), vec![0x3000, 0x3008]);
+        assert_eq!(addresses(8), vec![0x3008]);
+        stop.store(true, Ordering::SeqCst);
+        assert!(find_references(&memory, &regions, 8, &targets, &stop).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_pointers_into() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x30];
+        data[0x00..0x08].copy_from_slice(&0x400000_u64.to_le_bytes());
+        data[0x08..0x10].copy_from_slice(&0x3fffff_u64.to_le_bytes());
+        data[0x10..0x18].copy_from_slice(&0x401234_u64.to_le_bytes());
+        // the end of the range is outside of it
+        data[0x18..0x20].copy_from_slice(&0x402000_u64.to_le_bytes());
+        data[0x21..0x29].copy_from_slice(&0x401000_u64.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let regions = memory.regions(None, None, None).unwrap();
+
+        let stop = AtomicBool::new(false);
+        let pointers = find_pointers_into(&memory, &regions, 8, 0x400000..0x402000, &stop).unwrap();
+        assert_eq!(
+            pointers,
+            vec![
+                Reference { address: 0x1000, target: 0x400000 },
+                Reference { address: 0x1010, target: 0x401234 },
+            ]
+        );
+        assert!(find_pointers_into(&memory, &regions, 8, 0..0, &stop).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_pointers_to() {
+        use crate::core::mem::MemoryRegionPerms;
+        use crate::core::utils::FakeMemory;
+
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 0x30];
+        data[0x00..0x08].copy_from_slice(&0x401230_u64.to_le_bytes());
+        data[0x08..0x10].copy_from_slice(&0x401234_u64.to_le_bytes());
+        // not pointer-aligned, ignored
+        data[0x11..0x19].copy_from_slice(&0x401230_u64.to_le_bytes());
+        data[0x20..0x28].copy_from_slice(&0x401230_u64.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        // a vtable in read-only data
+        memory.add_region(0x2000, 0x401230_u64.to_le_bytes().to_vec(), vec![MemoryRegionPerms::Read]);
+        let regions = pointer_regions(&memory, true).unwrap();
+
+        let stop = AtomicBool::new(false);
+        let readable = pointer_regions(&memory, false).unwrap();
+        let pointers = find_pointers_to(&memory, &readable, 8, 0x401230, &stop).unwrap();
+        let found: Vec<u64> = pointers.iter().map(|p| p.address).collect();
+        assert_eq!(found, vec![0x1000, 0x1020, 0x2000]);
+
+        let addresses = |pointer_size, target| -> Vec<u64> {
+            let pointers = find_pointers_to(&memory, &regions, pointer_size, target, &stop).unwrap();
+            assert!(pointers.iter().all(|p| p.target == target));
+            pointers.iter().map(|p| p.address).collect()
+        };
+        assert_eq!(addresses(8, 0x401230), vec![0x1000, 0x1020]);
+        assert_eq!(addresses(8, 0x401234), vec![0x1008]);
+        // as 4-byte pointers the low halves match, the zero high halves don't
+        assert_eq!(addresses(4, 0x401230), vec![0x1000, 0x1020]);
+        assert!(addresses(8, 0x1234).is_empty());
+    }
+}
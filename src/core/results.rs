@@ -0,0 +1,779 @@
+//! Compact binary file format for scan results, meant for result sets with millions of
+//! addresses.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic     b"CERS"
+//! version   u16
+//! flags     u16      bit 0: values are stored
+//! pid       u32
+//! type      u8       see `type_code`
+//! count     u64
+//! meta len  u32      since version 2
+//! metadata  meta len bytes, see `ScanMetadata`
+//! addresses count * u64
+//! values    count * (u16 length + bytes), only with the values flag
+//! ```
+//!
+//! The metadata are UTF-8 `key=value` lines, backslashes and newlines in values written as
+//! `\\` and `\n`. Keys that aren't known are skipped, so fields can be added without a
+//! version bump.
+//!
+//! Region permissions aren't stored, loaded results have none.
+//!
+//! The same results can be saved as JSON instead, for other tools to read, see
+//! [`save_results_json`]. [`load_saved`] reads either.
+use std::fmt::Display;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+
+use crate::core::json::{Json, parse_json, quote};
+use crate::core::scan::{ScanResult, ValueType};
+
+const MAGIC: &[u8; 4] = b"CERS";
+const VERSION: u16 = 2;
+const FLAG_VALUES: u16 = 1;
+const HEADER_LEN: usize = 21;
+
+/// How a result set was produced, saved along with it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanMetadata {
+    /// Seconds since the Unix epoch
+    pub created: u64,
+    /// Version of the tool that wrote the file
+    pub tool_version: String,
+    /// Value as given on the command line, empty for an unknown value scan
+    pub value: String,
+    /// Type as given on the command line, e.g. `xor:1f` which the type code can't tell
+    pub type_name: String,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    /// Filters the scan applied, e.g. `not` or `compare=increased`
+    pub filters: Vec<String>,
+    /// When the scanned process started, see [`crate::core::proc::start_time`], to tell
+    /// it from a later process that reuses its pid. `None` for a dump or unknown
+    pub process_start: Option<u64>,
+}
+
+impl ScanMetadata {
+    /// Metadata stamped with the current time and this build's version
+    pub fn now(value: &str, type_name: &str, start: Option<u64>, end: Option<u64>) -> Self {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        ScanMetadata {
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            value: value.to_string(),
+            type_name: type_name.to_string(),
+            start,
+            end,
+            filters: vec![],
+            process_start: None,
+        }
+    }
+
+    fn encode(&self) -> String {
+        let mut lines = vec![
+            format!("created={}", self.created),
+            format!("tool_version={}", escape(&self.tool_version)),
+            format!("value={}", escape(&self.value)),
+            format!("type={}", escape(&self.type_name)),
+        ];
+        lines.extend(self.start.map(|start| format!("start={start:x}")));
+        lines.extend(self.end.map(|end| format!("end={end:x}")));
+        lines.extend(self.filters.iter().map(|filter| format!("filter={}", escape(filter))));
+        lines.extend(self.process_start.map(|started| format!("process_start={started}")));
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    fn decode(text: &str) -> io::Result<Self> {
+        let mut metadata = ScanMetadata::default();
+        for line in text.lines() {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("Bad metadata line '{line}'")))?;
+            let value = unescape(value);
+            let address = |value: &str| {
+                u64::from_str_radix(value, 16)
+                    .map_err(|_| invalid(format!("Bad address '{value}' in metadata")))
+            };
+            match key {
+                "created" => {
+                    metadata.created = value
+                        .parse()
+                        .map_err(|_| invalid(format!("Bad timestamp '{value}' in metadata")))?
+                }
+                "tool_version" => metadata.tool_version = value,
+                "value" => metadata.value = value,
+                "type" => metadata.type_name = value,
+                "start" => metadata.start = Some(address(&value)?),
+                "end" => metadata.end = Some(address(&value)?),
+                "filter" => metadata.filters.push(value),
+                "process_start" => {
+                    metadata.process_start = Some(
+                        value
+                            .parse()
+                            .map_err(|_| invalid(format!("Bad process start '{value}' in metadata")))?,
+                    )
+                }
+                _ => {}
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Format of a results file, see the module docs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ResultsFormat {
+    #[default]
+    Bin,
+    Json,
+}
+
+impl ResultsFormat {
+    pub fn parse(name: &str) -> Result<ResultsFormat, String> {
+        match name {
+            "bin" => Ok(ResultsFormat::Bin),
+            "json" => Ok(ResultsFormat::Json),
+            _ => Err(format!("Unknown results format '{name}', use bin or json")),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResultsFormat::Bin => "bin",
+            ResultsFormat::Json => "json",
+        }
+    }
+}
+
+/// The part of a results file before the addresses, see [`load_header_bin`]
+#[derive(Debug, Clone)]
+pub struct SavedHeader {
+    pub format: ResultsFormat,
+    pub version: u16,
+    pub pid: u32,
+    pub value_type: ValueType,
+    pub count: usize,
+    pub has_values: bool,
+    /// `None` for files written before version 2
+    pub metadata: Option<ScanMetadata>,
+}
+
+/// Results read back from a file written by [`save_results_bin`]
+#[derive(Debug, Clone)]
+pub struct SavedResults {
+    pub pid: u32,
+    pub value_type: ValueType,
+    pub results: Vec<ScanResult>,
+    pub has_values: bool,
+    pub metadata: Option<ScanMetadata>,
+}
+
+fn type_code(value_type: ValueType) -> u8 {
+    match value_type {
+        ValueType::U64 => 0,
+        ValueType::I64 => 1,
+        ValueType::U32 => 2,
+        ValueType::I32 => 3,
+        ValueType::String => 4,
+        // the codec isn't known when the file is read back, so the bytes are shown as hex,
+        // raw values are hex anyway and keep their width in the stored value
+        ValueType::Hex | ValueType::Raw(_) | ValueType::Custom(_) => 5,
+        ValueType::F64 => 6,
+        ValueType::F32 => 7,
+        ValueType::Pointer => 8,
+        ValueType::Bool => 9,
+    }
+}
+
+fn type_from_code(code: u8) -> Option<ValueType> {
+    Some(match code {
+        0 => ValueType::U64,
+        1 => ValueType::I64,
+        2 => ValueType::U32,
+        3 => ValueType::I32,
+        4 => ValueType::String,
+        5 => ValueType::Hex,
+        6 => ValueType::F64,
+        7 => ValueType::F32,
+        8 => ValueType::Pointer,
+        9 => ValueType::Bool,
+        _ => return None,
+    })
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+pub fn save_results_bin<W: Write>(
+    writer: &mut W,
+    pid: u32,
+    value_type: ValueType,
+    results: &[ScanResult],
+    with_values: bool,
+    metadata: &ScanMetadata,
+) -> io::Result<()> {
+    let flags = if with_values { FLAG_VALUES } else { 0 };
+    let metadata = metadata.encode();
+
+    let mut header = Vec::with_capacity(HEADER_LEN + 4 + metadata.len());
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&flags.to_le_bytes());
+    header.extend_from_slice(&pid.to_le_bytes());
+    header.push(type_code(value_type));
+    header.extend_from_slice(&(results.len() as u64).to_le_bytes());
+    header.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+    header.extend_from_slice(metadata.as_bytes());
+    writer.write_all(&header)?;
+
+    let addresses: Vec<u8> = results
+        .iter()
+        .flat_map(|r| r.address.to_le_bytes())
+        .collect();
+    writer.write_all(&addresses)?;
+
+    if with_values {
+        let mut values = Vec::new();
+        for result in results {
+            let len = u16::try_from(result.value.len())
+                .map_err(|_| invalid(format!("Value at 0x{:x} is too long", result.address)))?;
+            values.extend_from_slice(&len.to_le_bytes());
+            values.extend_from_slice(&result.value);
+        }
+        writer.write_all(&values)?;
+    }
+
+    writer.flush()
+}
+
+/// Read the header and metadata of a results file, leaving `reader` at the addresses
+pub fn load_header_bin<R: Read>(reader: &mut R) -> io::Result<SavedHeader> {
+    let mut header = [0_u8; HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    if &header[0..4] != MAGIC {
+        return Err(invalid(String::from("Not a saved scan results file")));
+    }
+
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version > VERSION {
+        return Err(invalid(format!(
+            "Results file version {version} is newer than supported version {VERSION}"
+        )));
+    }
+    let flags = u16::from_le_bytes([header[6], header[7]]);
+    let pid = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    let value_type = type_from_code(header[12])
+        .ok_or_else(|| invalid(format!("Unknown value type code {}", header[12])))?;
+    let count = u64::from_le_bytes(header[13..21].try_into().unwrap()) as usize;
+
+    let metadata = if version >= 2 {
+        let mut len = [0_u8; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as u64;
+        let mut text = Vec::new();
+        reader.take(len).read_to_end(&mut text)?;
+        if text.len() as u64 != len {
+            return Err(invalid(String::from("Results file is truncated")));
+        }
+        let text = String::from_utf8(text)
+            .map_err(|_| invalid(String::from("Results file metadata isn't UTF-8")))?;
+        Some(ScanMetadata::decode(&text)?)
+    } else {
+        None
+    };
+
+    Ok(SavedHeader {
+        format: ResultsFormat::Bin,
+        version,
+        pid,
+        value_type,
+        count,
+        has_values: flags & FLAG_VALUES != 0,
+        metadata,
+    })
+}
+
+pub fn load_results_bin<R: Read>(reader: &mut R) -> io::Result<SavedResults> {
+    let SavedHeader { pid, value_type, count, has_values, metadata, .. } = load_header_bin(reader)?;
+
+    // read the address block in one go, but don't trust the count for the allocation
+    let len = count
+        .checked_mul(8)
+        .ok_or_else(|| invalid(format!("Results file claims {count} results, more than fit in memory")))?;
+    let mut addresses = Vec::new();
+    reader.take(len as u64).read_to_end(&mut addresses)?;
+    if addresses.len() != len {
+        return Err(invalid(String::from("Results file is truncated")));
+    }
+
+    let mut values = Vec::new();
+    if has_values {
+        reader.read_to_end(&mut values)?;
+    }
+
+    let mut results = Vec::with_capacity(count);
+    let mut offset = 0;
+    for chunk in addresses.chunks_exact(8) {
+        let address = u64::from_le_bytes(chunk.try_into().unwrap());
+        let value = if has_values {
+            let len_bytes = values
+                .get(offset..offset + 2)
+                .ok_or_else(|| invalid(String::from("Results file is truncated")))?;
+            let len = u16::from_le_bytes([len_bytes[0], len_bytes[1]]) as usize;
+            let value = values
+                .get(offset + 2..offset + 2 + len)
+                .ok_or_else(|| invalid(String::from("Results file is truncated")))?;
+            offset += 2 + len;
+            value.to_vec()
+        } else {
+            vec![]
+        };
+        results.push(ScanResult::new(address, value_type, value, vec![]));
+    }
+
+    Ok(SavedResults {
+        pid,
+        value_type,
+        results,
+        has_values,
+        metadata,
+    })
+}
+
+/// Save `results` in `format`, see [`save_results_bin`] and [`save_results_json`]
+pub fn save_results<W: Write>(
+    writer: &mut W,
+    format: ResultsFormat,
+    pid: u32,
+    value_type: ValueType,
+    results: &[ScanResult],
+    with_values: bool,
+    metadata: &ScanMetadata,
+) -> io::Result<()> {
+    match format {
+        ResultsFormat::Bin => save_results_bin(writer, pid, value_type, results, with_values, metadata),
+        ResultsFormat::Json => save_results_json(writer, pid, value_type, results, with_values, metadata),
+    }
+}
+
+/// Read a results file of either format, telling them apart by the magic of the binary one
+pub fn load_saved<R: BufRead>(reader: &mut R) -> io::Result<SavedResults> {
+    match reader.fill_buf()?.starts_with(MAGIC) {
+        true => load_results_bin(reader),
+        false => load_results_json(reader),
+    }
+}
+
+/// The header of a results file of either format. For JSON the whole file is read
+pub fn load_saved_header<R: BufRead>(reader: &mut R) -> io::Result<SavedHeader> {
+    if reader.fill_buf()?.starts_with(MAGIC) {
+        return load_header_bin(reader);
+    }
+    let saved = load_results_json(reader)?;
+    Ok(SavedHeader {
+        format: ResultsFormat::Json,
+        version: VERSION,
+        pid: saved.pid,
+        value_type: saved.value_type,
+        count: saved.results.len(),
+        has_values: saved.has_values,
+        metadata: saved.metadata,
+    })
+}
+
+/// Save `results` as JSON, one result per line:
+///
+/// ```text
+/// {"version": 2, "pid": 1234, "type": "u32", "values": true,
+///  "metadata": {"created": 1700000000, "tool_version": "0.1.0", "value": "100", "type": "u32", "filters": []},
+///  "results": [
+///   {"address": "0x7f0000001000", "value": "64000000"}
+/// ]}
+/// ```
+///
+/// Addresses are hex strings, as JSON numbers lose precision above 2^53, and values are
+/// the raw bytes as hex. `start`, `end` and `process_start` are left out when unknown.
+pub fn save_results_json<W: Write>(
+    writer: &mut W,
+    pid: u32,
+    value_type: ValueType,
+    results: &[ScanResult],
+    with_values: bool,
+    metadata: &ScanMetadata,
+) -> io::Result<()> {
+    // the names of the types the binary format keeps, so both load the same
+    let type_name = type_from_code(type_code(value_type)).unwrap_or(ValueType::Hex).name();
+    let mut fields = vec![
+        format!("\"created\": {}", metadata.created),
+        format!("\"tool_version\": {}", quote(&metadata.tool_version)),
+        format!("\"value\": {}", quote(&metadata.value)),
+        format!("\"type\": {}", quote(&metadata.type_name)),
+    ];
+    fields.extend(metadata.start.map(|start| format!("\"start\": \"0x{start:x}\"")));
+    fields.extend(metadata.end.map(|end| format!("\"end\": \"0x{end:x}\"")));
+    let filters: Vec<String> = metadata.filters.iter().map(|filter| quote(filter)).collect();
+    fields.push(format!("\"filters\": [{}]", filters.join(", ")));
+    fields.extend(metadata.process_start.map(|started| format!("\"process_start\": {started}")));
+    writeln!(
+        writer,
+        "{{\"version\": {VERSION}, \"pid\": {pid}, \"type\": \"{type_name}\", \"values\": {with_values},"
+    )?;
+    writeln!(writer, " \"metadata\": {{{}}},", fields.join(", "))?;
+    write!(writer, " \"results\": [")?;
+    for (i, result) in results.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        match with_values {
+            true => write!(
+                writer,
+                "{separator}\n  {{\"address\": \"0x{:x}\", \"value\": \"{}\"}}",
+                result.address,
+                hex::encode(&result.value)
+            )?,
+            false => write!(writer, "{separator}\n  {{\"address\": \"0x{:x}\"}}", result.address)?,
+        }
+    }
+    writeln!(writer, "\n]}}")?;
+    writer.flush()
+}
+
+/// Read results saved by [`save_results_json`]
+pub fn load_results_json<R: Read>(reader: &mut R) -> io::Result<SavedResults> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    if !text.trim_start().starts_with('{') {
+        return Err(invalid(String::from("Not a saved scan results file")));
+    }
+    let json = parse_json(&text).map_err(|e| invalid(format!("Results file: {e}")))?;
+
+    let number = |value: Option<&Json>, key: &str| match value {
+        Some(Json::Number(text)) => text.parse::<u64>().map_err(|_| invalid(format!("Bad \"{key}\" '{text}'"))),
+        _ => Err(invalid(format!("Results file needs a \"{key}\" number"))),
+    };
+    let string = |value: Option<&Json>, key: &str| match value {
+        Some(Json::String(text)) => Ok(text.clone()),
+        _ => Err(invalid(format!("Results file needs a \"{key}\" string"))),
+    };
+    let address = |value: Option<&Json>, key: &str| {
+        let text = string(value, key)?;
+        let digits = text.strip_prefix("0x").unwrap_or(&text);
+        u64::from_str_radix(digits, 16).map_err(|_| invalid(format!("Bad address '{text}' in \"{key}\"")))
+    };
+
+    let version = number(json.field("version"), "version")?;
+    if version > VERSION as u64 {
+        return Err(invalid(format!(
+            "Results file version {version} is newer than supported version {VERSION}"
+        )));
+    }
+    let pid = u32::try_from(number(json.field("pid"), "pid")?).map_err(|_| invalid(String::from("Bad \"pid\"")))?;
+    let type_name = string(json.field("type"), "type")?;
+    let value_type = ValueType::all()
+        .into_iter()
+        .find(|t| t.name() == type_name)
+        .ok_or_else(|| invalid(format!("Unknown value type '{type_name}'")))?;
+    let has_values = match json.field("values") {
+        Some(Json::Bool(values)) => *values,
+        _ => return Err(invalid(String::from("Results file needs \"values\" true or false"))),
+    };
+
+    let metadata = match json.field("metadata") {
+        None => None,
+        Some(meta) => Some(ScanMetadata {
+            created: number(meta.field("created"), "created")?,
+            tool_version: string(meta.field("tool_version"), "tool_version")?,
+            value: string(meta.field("value"), "value")?,
+            type_name: string(meta.field("type"), "type")?,
+            start: meta.field("start").map(|start| address(Some(start), "start")).transpose()?,
+            end: meta.field("end").map(|end| address(Some(end), "end")).transpose()?,
+            filters: match meta.field("filters") {
+                Some(Json::Array(filters)) => {
+                    filters.iter().map(|filter| string(Some(filter), "filters")).collect::<io::Result<_>>()?
+                }
+                _ => vec![],
+            },
+            process_start: meta
+                .field("process_start")
+                .map(|started| number(Some(started), "process_start"))
+                .transpose()?,
+        }),
+    };
+
+    let Some(Json::Array(items)) = json.field("results") else {
+        return Err(invalid(String::from("Results file needs a \"results\" array")));
+    };
+    let results = items
+        .iter()
+        .map(|item| {
+            let value = match has_values {
+                true => hex::decode(string(item.field("value"), "value")?)
+                    .map_err(|_| invalid(String::from("Bad hex \"value\" in results file")))?,
+                false => vec![],
+            };
+            Ok(ScanResult::new(address(item.field("address"), "address")?, value_type, value, vec![]))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(SavedResults { pid, value_type, results, has_values, metadata })
+}
+
+/// Results of a result set picked by index: `5..15`, `-10..` (the last ten), `..5` or a
+/// single `7` or `-1`. Indices count from 0, negative ones from the end, and the end of a
+/// slice is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    Index(i64),
+    Slice { start: Option<i64>, end: Option<i64> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectError {
+    Invalid(String),
+    OutOfBounds { index: i64, len: usize },
+    /// The slice picks no results, e.g. `5..5`
+    Empty { start: usize, end: usize },
+}
+
+impl Display for SelectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(text) => write!(f, "Invalid selection '{text}', expected e.g. 5..15, -10.. or 7"),
+            Self::OutOfBounds { index, len } => write!(f, "Index {index} is out of bounds for {len} result(s)"),
+            Self::Empty { start, end } => write!(f, "The selection {start}..{end} holds no results"),
+        }
+    }
+}
+
+impl std::error::Error for SelectError {}
+
+impl Selection {
+    pub fn parse(text: &str) -> Result<Selection, SelectError> {
+        let invalid = || SelectError::Invalid(text.to_string());
+        let index = |part: &str| part.trim().parse::<i64>().map_err(|_| invalid());
+        let bound = |part: &str| match part.trim() {
+            "" => Ok(None),
+            part => index(part).map(Some),
+        };
+        match text.split_once("..") {
+            None => index(text).map(Selection::Index),
+            Some((start, end)) => Ok(Selection::Slice { start: bound(start)?, end: bound(end)? }),
+        }
+    }
+
+    /// Indices of the results picked from a result set of `len`
+    pub fn range(&self, len: usize) -> Result<Range<usize>, SelectError> {
+        let resolve = |index: i64| {
+            let resolved = match index < 0 {
+                true => len.checked_sub(index.unsigned_abs() as usize),
+                false => Some(index as usize),
+            };
+            resolved.filter(|&i| i <= len).ok_or(SelectError::OutOfBounds { index, len })
+        };
+        let (start, end) = match *self {
+            // a slice may end at `len`, an index has to be below it
+            Selection::Index(index) => match resolve(index)? {
+                start if start == len => return Err(SelectError::OutOfBounds { index, len }),
+                start => (start, start + 1),
+            },
+            Selection::Slice { start, end } => {
+                (start.map_or(Ok(0), resolve)?, end.map_or(Ok(len), resolve)?)
+            }
+        };
+        if start >= end {
+            return Err(SelectError::Empty { start, end });
+        }
+        Ok(start..end)
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn sample_results() -> Vec<ScanResult> {
+        (0..100_u64)
+            .map(|i| {
+                ScanResult::new(
+                    0x7f00_0000_0000 + i * 4,
+                    ValueType::U32,
+                    (i as u32).to_le_bytes().to_vec(),
+                    vec![],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_results_bin_roundtrip() {
+        let results = sample_results();
+
+        let metadata = ScanMetadata::default();
+        let meta_len = 4 + metadata.encode().len();
+        let mut file = vec![];
+        save_results_bin(&mut file, 1234, ValueType::U32, &results, true, &metadata).unwrap();
+        assert_eq!(file.len(), 21 + meta_len + 100 * 8 + 100 * (2 + 4));
+
+        let saved = load_results_bin(&mut file.as_slice()).unwrap();
+        assert_eq!(saved.pid, 1234);
+        assert_eq!(saved.value_type, ValueType::U32);
+        assert!(saved.has_values);
+        assert_eq!(saved.results.len(), 100);
+        assert_eq!(saved.results[42].address, results[42].address);
+        assert_eq!(saved.results[42].value, results[42].value);
+
+        let mut file = vec![];
+        save_results_bin(&mut file, 1234, ValueType::U32, &results, false, &metadata).unwrap();
+        assert_eq!(file.len(), 21 + meta_len + 100 * 8);
+        let saved = load_results_bin(&mut file.as_slice()).unwrap();
+        assert!(!saved.has_values);
+        assert!(saved.results[0].value.is_empty());
+    }
+
+    #[test]
+    fn test_results_bin_rejects_bad_files() {
+        let mut file = vec![];
+        save_results_bin(&mut file, 1, ValueType::Hex, &sample_results(), true, &ScanMetadata::default())
+            .unwrap();
+
+        let mut wrong_magic = file.clone();
+        wrong_magic[0] = b'X';
+        assert!(load_results_bin(&mut wrong_magic.as_slice()).is_err());
+
+        let mut newer = file.clone();
+        newer[4] = 3;
+        assert!(load_results_bin(&mut newer.as_slice()).is_err());
+
+        let truncated = &file[..file.len() - 3];
+        assert!(load_results_bin(&mut &truncated[..]).is_err());
+    }
+
+    #[test]
+    fn test_results_bin_rejects_overflowing_count() {
+        let mut file = vec![];
+        save_results_bin(&mut file, 1, ValueType::U32, &[], false, &ScanMetadata::default()).unwrap();
+        file[13..21].copy_from_slice(&u64::MAX.to_le_bytes());
+        let error = load_results_bin(&mut file.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_results_json_roundtrip() {
+        let results = sample_results();
+        let mut metadata = ScanMetadata::now("say \"hi\"", "u32", Some(0x1000), None);
+        metadata.filters = vec![String::from("compare=increased")];
+        metadata.process_start = Some(42);
+
+        for with_values in [true, false] {
+            let mut file = vec![];
+            save_results(&mut file, ResultsFormat::Json, 1234, ValueType::U32, &results, with_values, &metadata)
+                .unwrap();
+            assert!(file.starts_with(b"{"));
+            let saved = load_saved(&mut file.as_slice()).unwrap();
+            assert_eq!((saved.pid, saved.value_type, saved.has_values), (1234, ValueType::U32, with_values));
+            assert_eq!(saved.metadata.as_ref(), Some(&metadata));
+            assert_eq!(saved.results.len(), 100);
+            assert_eq!(saved.results[42].address, results[42].address);
+            let value = if with_values { results[42].value.clone() } else { vec![] };
+            assert_eq!(saved.results[42].value, value);
+
+            let header = load_saved_header(&mut file.as_slice()).unwrap();
+            assert_eq!((header.format, header.count), (ResultsFormat::Json, 100));
+        }
+
+        // the binary files load through the same function
+        let mut file = vec![];
+        save_results(&mut file, ResultsFormat::Bin, 1, ValueType::U32, &results, true, &metadata).unwrap();
+        assert_eq!(load_saved(&mut file.as_slice()).unwrap().results[7].value, results[7].value);
+        assert_eq!(load_saved_header(&mut file.as_slice()).unwrap().format, ResultsFormat::Bin);
+
+        assert!(load_saved(&mut &b"not a file"[..]).is_err());
+        assert!(load_saved(&mut &br#"{"version": 2, "pid": 1, "type": "u32", "values": false}"#[..]).is_err());
+        assert_eq!(ResultsFormat::parse("json"), Ok(ResultsFormat::Json));
+        assert!(ResultsFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_results_bin_metadata() {
+        let mut metadata = ScanMetadata::now("line one\nback\\slash", "xor:1f", Some(0x1000), None);
+        metadata.filters = vec![String::from("not"), String::from("compare=increased")];
+        metadata.process_start = Some(123456);
+        assert!(metadata.created > 0);
+
+        let mut file = vec![];
+        save_results_bin(&mut file, 7, ValueType::Hex, &sample_results(), false, &metadata).unwrap();
+        let header = load_header_bin(&mut file.as_slice()).unwrap();
+        assert_eq!((header.version, header.pid, header.count), (VERSION, 7, 100));
+        assert_eq!(header.metadata.as_ref(), Some(&metadata));
+        assert_eq!(load_results_bin(&mut file.as_slice()).unwrap().metadata, Some(metadata));
+
+        // version 1 files have no metadata, the addresses follow the header
+        let mut old = file[..HEADER_LEN].to_vec();
+        old[4] = 1;
+        old.extend_from_slice(&0x1234_u64.to_le_bytes());
+        old[13..21].copy_from_slice(&1_u64.to_le_bytes());
+        let saved = load_results_bin(&mut old.as_slice()).unwrap();
+        assert!(saved.metadata.is_none());
+        assert_eq!(saved.results[0].address, 0x1234);
+
+        // unknown keys are skipped
+        assert_eq!(ScanMetadata::decode("type=u32\ncolour=blue\n").unwrap().type_name, "u32");
+        assert!(ScanMetadata::decode("start=zz\n").is_err());
+    }
+
+    #[test]
+    fn test_result_selections() {
+        let range = |text: &str, len| Selection::parse(text).and_then(|selection| selection.range(len));
+        // forward
+        assert_eq!(range("5..15", 20), Ok(5..15));
+        assert_eq!(range("7", 20), Ok(7..8));
+        assert_eq!(range("0..20", 20), Ok(0..20));
+        // reverse
+        assert_eq!(range("-10..", 20), Ok(10..20));
+        assert_eq!(range("-1", 20), Ok(19..20));
+        assert_eq!(range("-5..-2", 20), Ok(15..18));
+        // open-ended
+        assert_eq!(range("..5", 20), Ok(0..5));
+        assert_eq!(range("15..", 20), Ok(15..20));
+        assert_eq!(range("..", 20), Ok(0..20));
+
+        assert_eq!(range("5..25", 20), Err(SelectError::OutOfBounds { index: 25, len: 20 }));
+        assert_eq!(range("-21..", 20), Err(SelectError::OutOfBounds { index: -21, len: 20 }));
+        assert_eq!(range("20", 20), Err(SelectError::OutOfBounds { index: 20, len: 20 }));
+        assert_eq!(range("0", 0), Err(SelectError::OutOfBounds { index: 0, len: 0 }));
+        assert_eq!(range("5..5", 20), Err(SelectError::Empty { start: 5, end: 5 }));
+        assert_eq!(range("-2..-5", 20), Err(SelectError::Empty { start: 18, end: 15 }));
+        assert_eq!(range("..", 0), Err(SelectError::Empty { start: 0, end: 0 }));
+        for text in ["", "a..b", "1..2..3", "1.5"] {
+            assert!(matches!(Selection::parse(text), Err(SelectError::Invalid(_))), "{text}");
+        }
+    }
+}
@@ -1,11 +1,24 @@
 use memchr::memmem;
 use rayon::prelude::*;
-use std::{array::TryFromSliceError, str};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    str,
+    sync::{
+        Arc, LazyLock, RwLock,
+        atomic::{AtomicBool, AtomicU64},
+    },
+};
 
+use crate::core::cache::{CacheStats, ReadCache};
+use crate::core::checkpoint::ScanPosition;
+use crate::core::journal::JournalEntry;
 use crate::core::mem::{
-    DEFAULT_SEARCH_PERMS, MemoryError, MemoryRegion, MemoryRegionPerms, get_memory_regions,
-    read_memory_address, write_memory_address,
+    DEFAULT_SEARCH_PERMS, MemoryError, MemoryRegion, MemoryRegionPerms, MemorySource,
+    ProcessHandle, all_regions, maps_fingerprint,
 };
+use crate::core::proc::RegionNames;
+use crate::core::spill::{SpillError, SpillFile};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ValueType {
@@ -13,16 +26,169 @@ pub enum ValueType {
     I64,
     U32,
     I32,
+    F64,
+    F32,
+    /// Address sized unsigned value, 4 or 8 bytes depending on the target's pointer
+    /// width (see [`Scan::set_pointer_width`]). Shown as hex.
+    Pointer,
+    /// Single byte flag, 0 is false and anything else true
+    Bool,
     String,
     Hex,
+    /// Fixed width byte blob, e.g. a 128-bit vector or an integer wider than 64 bits.
+    /// Entered and shown as hex, matched byte for byte.
+    Raw(usize),
+    /// Value encoded by the [`ValueCodec`] registered under this name with
+    /// [`register_codec`]
+    Custom(&'static str),
+}
+
+/// Encoding of a value type the scanner doesn't know, e.g. obfuscated or fixed-point
+/// values. Scanning a [`ValueType::Custom`] encodes the entered value and searches for the
+/// bytes, results are decoded for display. Values of custom types only compare as bytes:
+/// exact and changed/unchanged scans work, increased/decreased ones don't.
+///
+/// A library user registers a codec once, before creating scans of its type:
+///
+/// ```ignore
+/// register_codec("xor32", Box::new(XorCodec { key: 0x5a5a5a5a }));
+/// let value_type = custom_value_type("xor32").unwrap();
+/// let mut scan = Scan::new(pid, vec![], value_type, None, None, None)?;
+/// scan.set_value_from_str("100")?;
+/// ```
+pub trait ValueCodec: Send + Sync {
+    /// Size of an encoded value in bytes, 0 for variable length encodings
+    fn size(&self) -> usize;
+    /// Bytes a value entered by the user is stored as
+    fn encode(&self, value: &str) -> Result<Vec<u8>, ScanError>;
+    /// Text shown for the stored bytes
+    fn decode(&self, bytes: &[u8]) -> String;
+}
+
+/// `u32` values stored XOR-ed with `key`, how some games hide their counters from plain
+/// value scans
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XorCodec {
+    pub key: u32,
+}
+
+impl ValueCodec for XorCodec {
+    fn size(&self) -> usize {
+        4
+    }
+
+    fn encode(&self, value: &str) -> Result<Vec<u8>, ScanError> {
+        let value = value.parse::<u32>().map_err(|_| ScanError::InvalidValue)?;
+        Ok((value ^ self.key).to_le_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match <[u8; 4]>::try_from(bytes) {
+            Ok(bytes) => format!("{}", u32::from_le_bytes(bytes) ^ self.key),
+            Err(_) => hex::encode(bytes),
+        }
+    }
+}
+
+type CodecRegistry = Vec<(&'static str, Arc<dyn ValueCodec>)>;
+
+static CODECS: LazyLock<RwLock<CodecRegistry>> = LazyLock::new(Default::default);
+
+/// Register `codec` as the encoding of [`ValueType::Custom`]`(name)`, replacing an earlier
+/// codec of the same name
+pub fn register_codec(name: &'static str, codec: Box<dyn ValueCodec>) {
+    let mut codecs = CODECS.write().unwrap();
+    codecs.retain(|(registered, _)| *registered != name);
+    codecs.push((name, Arc::from(codec)));
+}
+
+/// The custom value type registered as `name`, if any
+pub fn custom_value_type(name: &str) -> Option<ValueType> {
+    let codecs = CODECS.read().unwrap();
+    codecs.iter().find(|(registered, _)| *registered == name).map(|(name, _)| ValueType::Custom(name))
+}
+
+fn codec(name: &str) -> Option<Arc<dyn ValueCodec>> {
+    let codecs = CODECS.read().unwrap();
+    codecs.iter().find(|(registered, _)| *registered == name).map(|(_, codec)| codec.clone())
 }
 
 impl ValueType {
+    /// Every built-in type, custom types are looked up with [`custom_value_type`]
+    pub fn all() -> [ValueType; 10] {
+        [
+            ValueType::U64,
+            ValueType::I64,
+            ValueType::U32,
+            ValueType::I32,
+            ValueType::F64,
+            ValueType::F32,
+            ValueType::Pointer,
+            ValueType::Bool,
+            ValueType::String,
+            ValueType::Hex,
+        ]
+    }
+
+    /// Name the type is given as on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::U64 => "u64",
+            ValueType::I64 => "i64",
+            ValueType::U32 => "u32",
+            ValueType::I32 => "i32",
+            ValueType::F64 => "f64",
+            ValueType::F32 => "f32",
+            ValueType::Pointer => "ptr",
+            ValueType::Bool => "bool",
+            ValueType::String => "string",
+            ValueType::Hex => "hex",
+            ValueType::Raw(_) => "raw",
+            ValueType::Custom(name) => name,
+        }
+    }
+
+    /// Offsets unknown value and inverse scans step by, exact value scans match at any
+    /// offset unless aligned with [`Scan::set_align_to`]
+    pub fn alignment(&self) -> usize {
+        std::cmp::max(self.get_size() as usize, 1)
+    }
+
+    /// A value of the type as it is entered, e.g. for `--value`
+    pub fn example_value(&self) -> &'static str {
+        match self {
+            ValueType::U64 | ValueType::U32 => "100",
+            ValueType::I64 | ValueType::I32 => "-100",
+            ValueType::F64 | ValueType::F32 => "1.5",
+            ValueType::Pointer => "0x7ffd12345678",
+            ValueType::Bool => "true",
+            ValueType::String => "player1",
+            ValueType::Hex => "deadbeef",
+            ValueType::Raw(_) | ValueType::Custom(_) => "",
+        }
+    }
+
+    /// Size in bytes, 0 for variable length types. Pointers report the host pointer
+    /// width, [`Scan::value_size`] gives the width of the scanned process.
     pub fn get_size(&self) -> u64 {
         match self {
-            ValueType::U64 | ValueType::I64 => 8,
-            ValueType::U32 | ValueType::I32 => 4,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+            ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+            ValueType::Pointer => size_of::<usize>() as u64,
+            ValueType::Bool => 1,
             ValueType::String | ValueType::Hex => 0,
+            ValueType::Raw(size) => *size as u64,
+            ValueType::Custom(name) => codec(name).map_or(0, |codec| codec.size() as u64),
+        }
+    }
+
+    /// Size of a value of this type held in `len` bytes: pointers read from 32-bit
+    /// processes are 4 bytes, everything else has a fixed size
+    fn size_for_len(&self, len: usize) -> usize {
+        match self {
+            ValueType::Pointer if len == 4 => 4,
+            ValueType::Pointer => 8,
+            _ => self.get_size() as usize,
         }
     }
 
@@ -32,21 +198,59 @@ impl ValueType {
             ValueType::I64 => format!("i64 ({}B)", self.get_size()),
             ValueType::U32 => format!("u32 ({}B)", self.get_size()),
             ValueType::I32 => format!("i32 ({}B)", self.get_size()),
+            ValueType::F64 => format!("f64 ({}B)", self.get_size()),
+            ValueType::F32 => format!("f32 ({}B)", self.get_size()),
+            ValueType::Pointer => String::from("pointer"),
+            ValueType::Bool => String::from("bool (1B)"),
             ValueType::String => String::from("string"),
             ValueType::Hex => String::from("hex"),
+            ValueType::Raw(size) => format!("raw ({size}B)"),
+            ValueType::Custom(name) => name.to_string(),
         }
     }
 
-    pub fn get_value_string(&self, value: &[u8]) -> Result<String, TryFromSliceError> {
+    pub fn get_value_string(&self, value: &[u8]) -> Result<String, ScanError> {
+        self.format_value(value, None)
+    }
+
+    /// Like [`ValueType::get_value_string`], but floats are shown with `precision`
+    /// decimal places. Without a precision floats get the shortest representation
+    /// that reads back as the same value; other types ignore it. Fixed size values must
+    /// be exactly their size: a short read is [`ScanError::InsufficientBytes`], a longer
+    /// buffer [`ScanError::TypeMismatch`].
+    pub fn format_value(&self, value: &[u8], precision: Option<usize>) -> Result<String, ScanError> {
         if value.is_empty() {
             return Ok(String::new());
         }
 
         Ok(match self {
-            ValueType::U64 => format!("{}", u64::from_le_bytes(value.try_into()?)),
-            ValueType::I64 => format!("{}", i64::from_le_bytes(value.try_into()?)),
-            ValueType::U32 => format!("{}", u32::from_le_bytes(value.try_into()?)),
-            ValueType::I32 => format!("{}", i32::from_le_bytes(value.try_into()?)),
+            ValueType::U64 => format!("{}", u64::from_le_bytes(exact_bytes(value)?)),
+            ValueType::I64 => format!("{}", i64::from_le_bytes(exact_bytes(value)?)),
+            ValueType::U32 => format!("{}", u32::from_le_bytes(exact_bytes(value)?)),
+            ValueType::I32 => format!("{}", i32::from_le_bytes(exact_bytes(value)?)),
+            ValueType::F64 => {
+                let v = f64::from_le_bytes(exact_bytes(value)?);
+                match precision {
+                    Some(precision) => format!("{v:.precision$}"),
+                    None => format!("{v}"),
+                }
+            }
+            ValueType::F32 => {
+                let v = f32::from_le_bytes(exact_bytes(value)?);
+                match precision {
+                    Some(precision) => format!("{v:.precision$}"),
+                    None => format!("{v}"),
+                }
+            }
+            // 4 byte pointers of 32-bit targets, anything shorter is a partial one
+            ValueType::Pointer if value.len() <= 4 => {
+                format!("0x{:x}", u32::from_le_bytes(exact_bytes(value)?))
+            }
+            ValueType::Pointer => format!("0x{:x}", u64::from_le_bytes(exact_bytes(value)?)),
+            ValueType::Bool => {
+                let [byte] = exact_bytes(value)?;
+                format!("{}", byte != 0)
+            }
             ValueType::String => {
                 let valid_end = str::from_utf8(value)
                     .map(|_| value.len())
@@ -63,1272 +267,5138 @@ impl ValueType {
                     .collect::<String>()
             }
             ValueType::Hex => hex::encode(value),
+            ValueType::Raw(size) => match value.len().cmp(size) {
+                Ordering::Less => {
+                    return Err(ScanError::InsufficientBytes { needed: *size, got: value.len() });
+                }
+                Ordering::Equal => hex::encode(value),
+                Ordering::Greater => return Err(ScanError::TypeMismatch),
+            },
+            ValueType::Custom(name) => match codec(name) {
+                Some(codec) => codec.decode(value),
+                None => hex::encode(value),
+            },
         })
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct ScanResult {
-    pub address: u64,
-    pub value_type: ValueType,
-    pub perms: Vec<MemoryRegionPerms>,
-    pub value: Vec<u8>,
-}
+    pub fn is_numeric(&self) -> bool {
+        !matches!(
+            self,
+            ValueType::String | ValueType::Hex | ValueType::Raw(_) | ValueType::Custom(_)
+        )
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ScanError {
-    InvalidValue,
-    EmptyValue,
-    InvalidAddress,
-    AddressMismatch,
-    ReadSizeInvalid(usize, usize),
-    Memory(MemoryError),
-    TypeMismatch,
-}
-impl std::fmt::Display for ScanError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidValue => write!(f, "Invalid scan value provided"),
-            Self::EmptyValue => write!(f, "Value is reqeuired to be set before scan"),
-            Self::InvalidAddress => write!(f, "Invalid address hex"),
-            Self::AddressMismatch => write!(f, "Start address should be smaller than end address"),
-            Self::TypeMismatch => write!(f, "Invalid type for value"),
-            Self::ReadSizeInvalid(min, max) => {
-                write!(f, "Read size should be in range {min}-{max}")
+    /// The value decoded as a number for [`CompareExpr`], `None` for string/hex values or
+    /// bytes that don't hold a full value
+    pub fn numeric_value(&self, bytes: &[u8]) -> Option<f64> {
+        let size = self.size_for_len(bytes.len());
+        let bytes = bytes.get(..size).filter(|_| size > 0)?;
+        Some(match self {
+            ValueType::U64 => u64::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::I64 => i64::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::U32 => u32::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::I32 => i32::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::F64 => f64::from_le_bytes(bytes.try_into().ok()?),
+            ValueType::F32 => f32::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::Pointer if size == 4 => u32::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::Pointer => u64::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ValueType::Bool => f64::from(bytes[0] != 0),
+            ValueType::String | ValueType::Hex | ValueType::Raw(_) | ValueType::Custom(_) => {
+                return None;
             }
-            Self::Memory(e) => write!(f, "{e}"),
-        }
+        })
     }
-}
 
-impl std::error::Error for ScanError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Self::Memory(e) => Some(e),
-            _ => None,
+    /// Numerically compare two values of this type, `None` for string/hex values or
+    /// byte slices that don't hold a full value
+    pub fn compare_values(&self, a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let size = self.size_for_len(a.len().min(b.len()));
+        if size == 0 || a.len() < size || b.len() < size {
+            return None;
         }
+        let (a, b) = (&a[..size], &b[..size]);
+
+        Some(match self {
+            ValueType::U64 => u64::from_le_bytes(a.try_into().ok()?)
+                .cmp(&u64::from_le_bytes(b.try_into().ok()?)),
+            ValueType::I64 => i64::from_le_bytes(a.try_into().ok()?)
+                .cmp(&i64::from_le_bytes(b.try_into().ok()?)),
+            ValueType::U32 => u32::from_le_bytes(a.try_into().ok()?)
+                .cmp(&u32::from_le_bytes(b.try_into().ok()?)),
+            ValueType::I32 => i32::from_le_bytes(a.try_into().ok()?)
+                .cmp(&i32::from_le_bytes(b.try_into().ok()?)),
+            // NaN isn't ordered, so it never counts as increased or decreased
+            ValueType::F64 => {
+                return f64::from_le_bytes(a.try_into().ok()?)
+                    .partial_cmp(&f64::from_le_bytes(b.try_into().ok()?));
+            }
+            ValueType::F32 => {
+                return f32::from_le_bytes(a.try_into().ok()?)
+                    .partial_cmp(&f32::from_le_bytes(b.try_into().ok()?));
+            }
+            ValueType::Pointer if size == 4 => u32::from_le_bytes(a.try_into().ok()?)
+                .cmp(&u32::from_le_bytes(b.try_into().ok()?)),
+            ValueType::Pointer => u64::from_le_bytes(a.try_into().ok()?)
+                .cmp(&u64::from_le_bytes(b.try_into().ok()?)),
+            ValueType::Bool => (a[0] != 0).cmp(&(b[0] != 0)),
+            ValueType::String | ValueType::Hex | ValueType::Raw(_) | ValueType::Custom(_) => {
+                return None;
+            }
+        })
     }
-}
 
-impl ScanResult {
-    pub fn new(
-        address: u64,
-        value_type: ValueType,
-        value: Vec<u8>,
-        perms: Vec<MemoryRegionPerms>,
-    ) -> Self {
-        ScanResult {
-            address,
-            value_type,
-            perms,
-            value,
+    /// Describe how a value changed: `old -> new (Δ+5)` for integers, with the relative
+    /// change added for floats, and which bytes differ for string/hex values
+    pub fn describe_change(&self, old: &[u8], new: &[u8], precision: Option<usize>) -> String {
+        let size = self.size_for_len(old.len().min(new.len()));
+        if let ValueType::Custom(_) = self {
+            let (old_str, new_str) = (self.format_value(old, None), self.format_value(new, None));
+            return format!("{} -> {}", old_str.unwrap_or_default(), new_str.unwrap_or_default());
+        }
+        if size == 0 || old.len() < size || new.len() < size {
+            let first = old.iter().zip(new).position(|(a, b)| a != b);
+            let changed = old.iter().zip(new).filter(|(a, b)| a != b).count()
+                + old.len().abs_diff(new.len());
+            return match first {
+                Some(offset) => format!("{changed} byte(s) changed from offset {offset}"),
+                None if changed > 0 => format!("length changed to {} byte(s)", new.len()),
+                None => String::from("unchanged"),
+            };
         }
-    }
 
-    pub fn get_string(&self) -> Result<String, ScanError> {
-        self.value_type
-            .get_value_string(self.value.as_slice())
-            .map_err(|_| ScanError::TypeMismatch)
-    }
+        let (old_str, new_str) = (
+            self.format_value(&old[..size], precision).unwrap_or_default(),
+            self.format_value(&new[..size], precision).unwrap_or_default(),
+        );
+        let int = |bytes: &[u8]| -> i128 {
+            match self {
+                ValueType::U64 => u64::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                ValueType::I64 => i64::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                ValueType::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                ValueType::Pointer if size == 4 => {
+                    u32::from_le_bytes(bytes.try_into().unwrap()) as i128
+                }
+                ValueType::Pointer => u64::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                ValueType::Bool => i128::from(bytes[0] != 0),
+                _ => i32::from_le_bytes(bytes.try_into().unwrap()) as i128,
+            }
+        };
+        let float = |bytes: &[u8]| -> f64 {
+            match self {
+                ValueType::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+                _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            }
+        };
 
-    pub fn is_read_only(&self) -> bool {
-        !self.perms.contains(&MemoryRegionPerms::Write)
+        let delta = match self {
+            ValueType::F64 | ValueType::F32 => {
+                let (old, new) = (float(&old[..size]), float(&new[..size]));
+                let delta = match precision {
+                    Some(precision) => format!("{:+.precision$}", new - old),
+                    None => format!("{:+}", new - old),
+                };
+                if old != 0.0 && old.is_finite() {
+                    format!("{delta}, {:+.2}%", (new - old) / old.abs() * 100.0)
+                } else {
+                    delta
+                }
+            }
+            _ => format!("{:+}", int(&new[..size]) - int(&old[..size])),
+        };
+
+        format!("{old_str} -> {new_str} (Δ{delta})")
     }
 }
 
-#[derive(Debug)]
-pub struct Scan {
-    pub pid: u32,
-    pub value: Vec<u8>,
+/// Types [`interpret_bytes`] reads the bytes as, the ones a value is most often stored in
+const INTERPRETED_TYPES: [ValueType; 7] = [
+    ValueType::U32,
+    ValueType::I32,
+    ValueType::F32,
+    ValueType::U64,
+    ValueType::I64,
+    ValueType::F64,
+    ValueType::String,
+];
+
+/// The bytes at an address read as one type, see [`interpret_bytes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interpretation {
     pub value_type: ValueType,
-    pub results: Vec<ScanResult>,
-    pub watchlist: Vec<ScanResult>,
-    read_size: Option<usize>,
-    start_address: Option<u64>,
-    end_address: Option<u64>,
-    memory_permissions: Vec<MemoryRegionPerms>,
-    memory_regions: Vec<MemoryRegion>,
+    pub value: String,
 }
 
-impl Scan {
-    pub fn new(
-        pid: u32,
-        value: Vec<u8>,
-        value_type: ValueType,
-        start_address: Option<u64>,
-        end_address: Option<u64>,
-        memory_permissions: Option<Vec<MemoryRegionPerms>>,
-    ) -> Result<Self, ScanError> {
-        let memory_permissions = memory_permissions.unwrap_or(DEFAULT_SEARCH_PERMS.to_vec());
-        let memory_regions =
-            get_memory_regions(pid, start_address, end_address, Some(&memory_permissions))
-                .map_err(ScanError::Memory)?;
-
-        Ok(Scan {
-            pid,
-            read_size: None,
-            value,
-            start_address,
-            end_address,
-            memory_regions,
-            value_type,
-            memory_permissions,
-            results: vec![],
-            watchlist: vec![],
+/// `bytes` read as each of the common value types, for someone who knows a value but not
+/// how it is stored. Types longer than `bytes` are left out, the string stops at the first
+/// null byte and is left out when empty.
+pub fn interpret_bytes(bytes: &[u8]) -> Vec<Interpretation> {
+    INTERPRETED_TYPES
+        .into_iter()
+        .filter_map(|value_type| {
+            let value = match value_type {
+                ValueType::String => {
+                    let end = memchr::memchr(0, bytes).unwrap_or(bytes.len());
+                    Some(&bytes[..end]).filter(|text| !text.is_empty())?
+                }
+                _ => bytes.get(..value_type.get_size() as usize)?,
+            };
+            let value = value_type.format_value(value, None).ok()?;
+            Some(Interpretation { value_type, value })
         })
-    }
+        .collect()
+}
 
-    pub fn set_mem_permissions(
-        &mut self,
-        memory_permissions: Vec<MemoryRegionPerms>,
-    ) -> Result<(), ScanError> {
-        self.memory_permissions = memory_permissions;
-        self.update_memory_regions()?;
-        Ok(())
+/// Hooks into the initial scan of a [`Scan`], for front ends that show regions, matches
+/// and progress as they come in instead of polling [`Scan::progress`], see
+/// [`Scan::set_observer`]. Every hook does nothing by default.
+///
+/// Regions are scanned in parallel, so the hooks run on the scan's worker threads, for
+/// several regions at once and in no particular order across regions. They take `&self`
+/// and should return quickly, e.g. bump a counter or send over a channel, since a hook
+/// that blocks holds up the worker it runs on.
+pub trait ScanObserver: Send + Sync + std::fmt::Debug {
+    /// `region` is about to be scanned
+    fn on_region_start(&self, _region: &MemoryRegion) {}
+
+    /// The scan found `result`, each match once. Matches of one block arrive in address
+    /// order
+    fn on_match(&self, _result: &ScanResult) {}
+
+    /// The scan is done with `done` of the `total` bytes it covers
+    fn on_progress(&self, _done: u64, _total: u64) {}
+
+    /// The scan finished with `found` results, after a stop too. Not called when it fails
+    fn on_complete(&self, _found: usize) {}
+}
+
+/// Runs initial scans as one queue of (scan, region) work items on a work-stealing
+/// thread pool. Scanning several processes through one executor keeps the thread count
+/// bounded by the pool size instead of growing with the number of processes.
+#[derive(Debug)]
+pub struct ScanExecutor {
+    // `None` uses rayon's global pool
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl ScanExecutor {
+    /// Executor on the global pool, which [`Scan::init`] uses
+    pub fn global() -> Self {
+        ScanExecutor { pool: None }
     }
 
-    pub fn set_value_type(
-        &mut self,
-        value_type: ValueType,
-        value_str: Option<&str>,
-    ) -> Result<(), ScanError> {
-        self.value_type = value_type;
-        if let Some(value) = value_str {
-            self.set_value_from_str(value)?;
-        }
-        Ok(())
+    /// Executor with its own pool of `threads` threads
+    pub fn with_threads(threads: usize) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+        Ok(ScanExecutor { pool: Some(pool) })
     }
 
-    pub fn set_read_size(&mut self, size: Option<usize>) -> Result<(), ScanError> {
-        const MAX_READ_SIZE: usize = 256;
-        const MIN_READ_SIZE: usize = 1;
+    /// Run the initial scan of every scan. Each scan gets its own results (and refreshed
+    /// watchlist), sorted by address without duplicates regardless of how the blocks
+    /// were split across threads. The returned list has an entry per scan in the same
+    /// order. Scans with a spill file run after the others, see [`Scan::set_spill_file`].
+    pub fn init(&self, scans: &mut [Scan]) -> Vec<Result<(), ScanError>> {
+        for scan in scans.iter_mut() {
+            scan.progress.store(0, std::sync::atomic::Ordering::Relaxed);
+            scan.progress_total = scan.total_bytes();
+        }
+        let run = || {
+            let checks: Vec<Result<(), ScanError>> =
+                scans.iter().map(|scan| scan.check_value()).collect();
+            for (scan, check) in scans.iter().zip(&checks) {
+                if let Err(e) = check {
+                    log::debug!("Not scanning {:?}: {}", scan.source, e);
+                }
+            }
+            let items: Vec<(usize, &MemoryRegion)> = scans
+                .iter()
+                .enumerate()
+                .filter(|(i, scan)| checks[*i].is_ok() && scan.spill.is_none())
+                .flat_map(|(i, scan)| scan.memory_regions.iter().map(move |r| (i, r)))
+                .collect();
+            log::debug!("Scanning {} region(s) of {} scan(s)", items.len(), scans.len());
+
+            let mut region_results: Vec<(usize, Result<Vec<ScanResult>, SkippedRegion>)> = items
+                .par_iter()
+                .map(|&(i, region)| {
+                    let results = scans[i]
+                        .scan_region(region)
+                        .map_err(|error| SkippedRegion { region: region.clone(), error });
+                    (i, results)
+                })
+                .collect();
+            let seams = scans
+                .par_iter()
+                .enumerate()
+                .filter(|(i, scan)| checks[*i].is_ok() && scan.spill.is_none())
+                .map(|(i, scan)| (i, Ok(scan.scan_seams())))
+                .collect::<Vec<_>>();
+            region_results.extend(seams);
+            (checks, region_results)
+        };
+        let (mut checks, region_results) = match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
+        };
 
-        if let Some(size) = size {
-            if !(MIN_READ_SIZE..=MAX_READ_SIZE).contains(&size) {
-                return Err(ScanError::ReadSizeInvalid(MIN_READ_SIZE, MAX_READ_SIZE));
+        // items were queued scan by scan, so each scan's regions stay in order
+        let mut grouped: Vec<Vec<ScanResult>> = vec![vec![]; scans.len()];
+        let mut skipped: Vec<Vec<SkippedRegion>> = vec![vec![]; scans.len()];
+        for (i, result) in region_results {
+            match result {
+                Ok(results) => grouped[i].extend(results),
+                Err(region) => {
+                    if let Err(e) = scans[i].skip_region(region, &mut skipped[i])
+                        && checks[i].is_ok()
+                    {
+                        checks[i] = Err(ScanError::Memory(e));
+                    }
+                }
             }
-            self.read_size = Some(size);
-        } else {
-            self.read_size = None;
         }
-        Ok(())
+
+        scans
+            .iter_mut()
+            .zip(grouped)
+            .zip(skipped)
+            .zip(checks)
+            .map(|(((scan, mut results), skipped), check)| {
+                scan.skipped_regions = skipped;
+                check?;
+                scan.record_maps();
+                if scan.spill.is_some() {
+                    match &self.pool {
+                        Some(pool) => pool.install(|| scan.fill_spill())?,
+                        None => scan.fill_spill()?,
+                    };
+                    scan.notify_complete();
+                    return Ok(());
+                }
+                // regions and seams finish in any order, keep one result per address
+                results.sort_by_key(|r| r.address);
+                results.dedup_by_key(|r| r.address);
+                scan.results = results;
+                scan.refresh_watchlist()?;
+                scan.notify_complete();
+                Ok(())
+            })
+            .collect()
     }
+}
 
-    pub fn value_from_str(&self, value_str: &str) -> Result<Vec<u8>, ScanError> {
-        Ok(match self.value_type {
-            ValueType::U64 => value_str
-                .parse::<u64>()
-                .map_err(|_| ScanError::InvalidValue)?
-                .to_le_bytes()
-                .to_vec(),
-            ValueType::I64 => value_str
-                .parse::<i64>()
-                .map_err(|_| ScanError::InvalidValue)?
-                .to_le_bytes()
-                .to_vec(),
-            ValueType::U32 => value_str
-                .parse::<u32>()
-                .map_err(|_| ScanError::InvalidValue)?
-                .to_le_bytes()
-                .to_vec(),
-            ValueType::I32 => value_str
-                .parse::<i32>()
-                .map_err(|_| ScanError::InvalidValue)?
-                .to_le_bytes()
-                .to_vec(),
-            ValueType::String => value_str.as_bytes().to_vec(),
-            ValueType::Hex => {
-                let hex_str = value_str.trim_start_matches("0x");
-                hex::decode(hex_str).map_err(|_| ScanError::InvalidValue)?
+/// Blocks a spilled initial scan reads before it writes their results out, the results
+/// of a batch are the most it holds in memory
+const SPILL_BATCH_BLOCKS: usize = 16;
+
+/// Blocks [`Scan::init_resumable`] scans between checkpoints, so a stop loses at most
+/// this many
+pub const CHECKPOINT_BLOCKS: usize = 16;
+
+/// Results a next scan or compare pass checks before it adds them to
+/// [`Scan::pass_progress`]
+const PASS_PROGRESS_CHUNK: usize = 4096;
+
+/// Longest value [`find_exact`] looks up by its first byte, numbers and short strings
+const QUICK_SCAN_MAX_LEN: usize = 16;
+
+/// Offsets of every occurrence of `value` in `block`, overlapping ones included, so an
+/// aligned match isn't hidden by an unaligned one that overlaps it. Short values jump
+/// from one occurrence of their first byte to the next with `memchr`, which beats a
+/// substring search when that byte is uncommon in the block. Once it turns out
+/// to be common (more candidates than 1 in 2048 bytes) the rest of the block is left to
+/// `memmem`, whose prefilter picks a rarer byte of the value.
+#[doc(hidden)]
+pub fn find_exact(block: &[u8], value: &[u8]) -> Vec<usize> {
+    let mut found = vec![];
+    let mut from = 0;
+    if !value.is_empty() && value.len() <= QUICK_SCAN_MAX_LEN {
+        let budget = block.len() / 2048 + 8;
+        let mut candidates = memchr::memchr_iter(value[0], block).enumerate();
+        from = loop {
+            match candidates.next() {
+                None => return found,
+                Some((count, i)) if count >= budget => break i,
+                Some((_, i)) if block[i..].starts_with(value) => found.push(i),
+                Some(_) => {}
             }
-        })
+        };
     }
 
-    pub fn set_value_from_str(&mut self, value_str: &str) -> Result<(), ScanError> {
-        self.value = self.value_from_str(value_str)?;
-
-        Ok(())
+    // memmem's iterator skips overlapping matches, so search again after each one
+    let finder = memmem::Finder::new(value);
+    while let Some(i) = finder.find(&block[from..]) {
+        found.push(from + i);
+        from += i + 1;
     }
+    found
+}
 
-    fn parse_address_hex(addr_hex: &str) -> Result<Option<u64>, ScanError> {
-        if addr_hex.is_empty() {
-            Ok(None)
-        } else {
-            let parsed_addr = u64::from_str_radix(addr_hex.trim_start_matches("0x"), 16)
-                .map_err(|_| ScanError::InvalidAddress)?;
-            Ok(Some(parsed_addr))
-        }
-    }
+/// [`find_exact`] comparing `value` at every offset, what the benchmark measures the fast
+/// path against
+#[doc(hidden)]
+pub fn find_exact_scalar(block: &[u8], value: &[u8]) -> Vec<usize> {
+    block.windows(value.len()).enumerate().filter(|(_, w)| *w == value).map(|(i, _)| i).collect()
+}
 
-    fn update_memory_regions(&mut self) -> Result<(), ScanError> {
-        self.memory_regions = get_memory_regions(
-            self.pid,
-            self.start_address,
-            self.end_address,
-            Some(&self.memory_permissions),
-        )
-        .map_err(ScanError::Memory)?;
-        Ok(())
+/// `value` as an array of a fixed size value, without panicking on short reads
+fn exact_bytes<const N: usize>(value: &[u8]) -> Result<[u8; N], ScanError> {
+    match value.len().cmp(&N) {
+        Ordering::Less => Err(ScanError::InsufficientBytes { needed: N, got: value.len() }),
+        Ordering::Equal => Ok(value.try_into().expect("length checked")),
+        Ordering::Greater => Err(ScanError::TypeMismatch),
     }
+}
 
-    pub fn set_start_address(&mut self, addr_hex: &str) -> Result<(), ScanError> {
-        let parsed_addr = Self::parse_address_hex(addr_hex)?;
+/// One position of a [`Glob`]
+#[derive(Debug, Clone, PartialEq)]
+enum GlobToken {
+    Byte(u8),
+    Any,
+    /// Inclusive byte ranges, matching bytes outside them when negated
+    Class { ranges: Vec<(u8, u8)>, negated: bool },
+}
 
-        if let (Some(start), Some(end)) = (parsed_addr, self.end_address)
-            && start > end
-        {
-            return Err(ScanError::AddressMismatch);
+impl GlobToken {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            GlobToken::Byte(b) => *b == byte,
+            GlobToken::Any => true,
+            GlobToken::Class { ranges, negated } => {
+                ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&byte)) != *negated
+            }
         }
-
-        self.start_address = parsed_addr;
-        self.update_memory_regions()?;
-
-        Ok(())
     }
+}
 
-    pub fn set_end_address(&mut self, addr_hex: &str) -> Result<(), ScanError> {
-        let parsed_addr = Self::parse_address_hex(addr_hex)?;
-
-        if let (Some(start), Some(end)) = (self.start_address, parsed_addr)
-            && end < start
-        {
-            return Err(ScanError::AddressMismatch);
-        }
-
-        self.end_address = parsed_addr;
-        self.update_memory_regions()?;
+/// Fixed length string pattern: `?` matches any single byte, `[abc]`, `[0-9]` and
+/// `[!0-9]` (or `[^0-9]`) match one byte of or outside a class, and `\` makes the next
+/// character literal. Every other byte matches itself, so a match is as long as the
+/// pattern has positions. `*` is rejected since results have a fixed length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glob {
+    tokens: Vec<GlobToken>,
+}
 
-        Ok(())
+impl Glob {
+    pub fn parse(pattern: &[u8]) -> Result<Self, ScanError> {
+        let invalid = |msg: &str| ScanError::InvalidPattern(msg.to_string());
+        let mut tokens = vec![];
+        let mut bytes = pattern.iter().copied();
+        while let Some(byte) = bytes.next() {
+            tokens.push(match byte {
+                b'?' => GlobToken::Any,
+                b'*' => return Err(invalid("'*' isn't supported, use one '?' per character")),
+                b'\\' => GlobToken::Byte(bytes.next().ok_or_else(|| invalid("trailing '\\'"))?),
+                b'[' => {
+                    let mut class: Vec<u8> = vec![];
+                    loop {
+                        match bytes.next() {
+                            None => return Err(invalid("unterminated '['")),
+                            // a ']' right after the '[' (or the negation) is literal
+                            Some(b']') if !matches!(class.as_slice(), [] | [b'!' | b'^']) => break,
+                            Some(byte) => class.push(byte),
+                        }
+                    }
+                    let negated = matches!(class.first(), Some(b'!' | b'^'));
+                    let class = if negated { &class[1..] } else { &class[..] };
+                    let mut ranges = vec![];
+                    let mut i = 0;
+                    while i < class.len() {
+                        if i + 2 < class.len() && class[i + 1] == b'-' {
+                            let (lo, hi) = (class[i], class[i + 2]);
+                            if lo > hi {
+                                return Err(invalid("class range out of order"));
+                            }
+                            ranges.push((lo, hi));
+                            i += 3;
+                        } else {
+                            ranges.push((class[i], class[i]));
+                            i += 1;
+                        }
+                    }
+                    GlobToken::Class { ranges, negated }
+                }
+                byte => GlobToken::Byte(byte),
+            });
+        }
+        if tokens.is_empty() {
+            return Err(ScanError::EmptyValue);
+        }
+        Ok(Glob { tokens })
     }
 
-    fn scan_region(&self, region: &MemoryRegion) -> Result<Vec<ScanResult>, MemoryError> {
-        let start = region.start as usize;
-        let end = region.end as usize;
-        let size = self.read_size.unwrap_or(self.value.len());
-
-        const BLOCK_SIZE: usize = 0x10000;
+    /// Bytes a match spans
+    pub(crate) fn len(&self) -> usize {
+        self.tokens.len()
+    }
 
-        // Validate region with a single byte read to catch ProcessAttach errors early
-        if let Err(e) = read_memory_address(self.pid, start, 1)
-            && let MemoryError::ProcessAttach(_) = e {
-                return Err(e);
-            }
+    /// Whether `bytes` start with a match
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.tokens.len()
+            && self.tokens.iter().zip(bytes).all(|(token, &byte)| token.matches(byte))
+    }
 
-        // Generate all block addresses to scan
-        let block_addresses: Vec<usize> = {
-            let mut addresses = Vec::new();
-            let mut current_address = start;
-            while current_address < end {
-                let to_read = std::cmp::min(BLOCK_SIZE, end - current_address);
-                if to_read < size {
-                    break;
-                }
-                addresses.push(current_address);
-                current_address += to_read - (size - 1);
-            }
-            addresses
+    /// Offsets of every match in `block`, jumping between occurrences of the first byte
+    /// with `memchr` when the pattern starts with a literal
+    fn find_all(&self, block: &[u8]) -> Vec<usize> {
+        let last = match block.len().checked_sub(self.len()) {
+            Some(last) => last,
+            None => return vec![],
         };
+        match self.tokens[0] {
+            GlobToken::Byte(first) => memchr::memchr_iter(first, &block[..=last])
+                .filter(|&i| self.matches(&block[i..]))
+                .collect(),
+            _ => (0..=last).filter(|&i| self.matches(&block[i..])).collect(),
+        }
+    }
+}
 
-        // Parallel scan of all blocks
-        let results: Vec<Vec<ScanResult>> = block_addresses
-            .par_iter()
-            .filter_map(|&current_address| {
-                let to_read = std::cmp::min(BLOCK_SIZE, end - current_address);
-
-                match read_memory_address(self.pid, current_address, to_read) {
-                    Err(_) => None, // Ignore all errors during parallel scan
-                    Ok(val) => {
-                        let block_results: Vec<ScanResult> = memmem::find_iter(&val, &self.value)
-                            .map(|i| {
-                                // Take all available data from position i, up to size bytes
-                                let end_offset = std::cmp::min(i + size, val.len());
-                                ScanResult::new(
-                                    (current_address + i) as u64,
-                                    self.value_type,
-                                    val[i..end_offset].to_vec(),
-                                    region.perms.clone(),
-                                )
-                            })
-                            .collect();
-                        Some(block_results)
-                    }
-                }
-            })
-            .collect();
+/// Digits of a `0x` prefixed hex number
+fn hex_digits(value_str: &str) -> Option<&str> {
+    value_str.strip_prefix("0x").or_else(|| value_str.strip_prefix("0X"))
+}
 
-        // Flatten results
-        Ok(results.into_iter().flatten().collect())
+/// The byte `region` consists of if it's a single repeated byte. A few strided samples
+/// are read first and the whole region only when they all agree, and any read error
+/// counts as not uniform.
+fn uniform_byte(source: &dyn MemorySource, region: &MemoryRegion) -> Option<u8> {
+    const SAMPLES: u64 = 8;
+    const SAMPLE_LEN: u64 = 64;
+    const BLOCK_SIZE: usize = 0x10000;
+
+    let len = region.end.checked_sub(region.start)?;
+    if len == 0 {
+        return None;
+    }
+    let byte = source.read(region.start as usize, 1).ok()?[0];
+    let stride = std::cmp::max(len / SAMPLES, 1);
+    let mut offset = 0;
+    while offset < len {
+        let sample_len = std::cmp::min(SAMPLE_LEN, len - offset) as usize;
+        let sample = source.read((region.start + offset) as usize, sample_len).ok()?;
+        if sample.iter().any(|&b| b != byte) {
+            return None;
+        }
+        offset += stride;
     }
 
-    fn check_value(&self) -> Result<(), ScanError> {
-        if self.value.is_empty() {
-            return Err(ScanError::EmptyValue);
+    let mut address = region.start as usize;
+    while address < region.end as usize {
+        let to_read = std::cmp::min(BLOCK_SIZE, region.end as usize - address);
+        let block = source.read(address, to_read).ok()?;
+        if block.iter().any(|&b| b != byte) {
+            return None;
         }
+        address += to_read;
+    }
+    Some(byte)
+}
 
-        self.value_type
-            .get_value_string(&self.value)
-            .map_err(|_| ScanError::TypeMismatch)?;
+/// How a compare scan checks the current value against the baseline value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareMode {
+    Increased,
+    Decreased,
+    Changed,
+    Unchanged,
+}
 
-        Ok(())
+impl CompareMode {
+    pub fn matches(&self, value_type: ValueType, baseline: &[u8], current: &[u8]) -> bool {
+        match self {
+            CompareMode::Changed => baseline != current,
+            CompareMode::Unchanged => baseline == current,
+            CompareMode::Increased => {
+                value_type.compare_values(current, baseline) == Some(Ordering::Greater)
+            }
+            CompareMode::Decreased => {
+                value_type.compare_values(current, baseline) == Some(Ordering::Less)
+            }
+        }
     }
+}
 
-    fn refresh_watchlist(&mut self) -> Result<(), ScanError> {
-        self.check_value()?;
+/// Which value a compare scan checks against: the value found by the first scan,
+/// or the value seen by the last scan
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CompareBaseline {
+    Initial,
+    #[default]
+    Previous,
+}
 
-        if self.watchlist.is_empty() {
-            return Ok(());
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExprOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
 
-        // Early validation with single read to catch ProcessAttach errors
-        if let Some(first) = self.watchlist.first() {
-            let read_size = self.read_size.unwrap_or(first.value.len());
-            if let Err(e) = read_memory_address(self.pid, first.address as usize, read_size)
-                && let MemoryError::ProcessAttach(_) = e {
-                    return Err(ScanError::Memory(e));
+#[derive(Debug, Clone, PartialEq)]
+enum ExprNode {
+    Number(f64),
+    Prev,
+    Cur,
+    Neg(Box<ExprNode>),
+    Binary(ExprOp, Box<ExprNode>, Box<ExprNode>),
+}
+
+impl ExprNode {
+    fn number(&self, prev: f64, cur: f64) -> f64 {
+        match self {
+            ExprNode::Number(n) => *n,
+            ExprNode::Prev => prev,
+            ExprNode::Cur => cur,
+            ExprNode::Neg(node) => -node.number(prev, cur),
+            ExprNode::Binary(op, a, b) => {
+                let (a, b) = (a.number(prev, cur), b.number(prev, cur));
+                match op {
+                    ExprOp::Add => a + b,
+                    ExprOp::Sub => a - b,
+                    ExprOp::Mul => a * b,
+                    ExprOp::Div => a / b,
+                    // the parser only puts arithmetic below a comparison
+                    _ => f64::NAN,
                 }
+            }
         }
+    }
 
-        // Parallel refresh
-        let updated_watchlist: Vec<ScanResult> = self
-            .watchlist
-            .par_iter()
-            .filter_map(|result| {
-                let read_size = self.read_size.unwrap_or(result.value.len());
-                match read_memory_address(self.pid, result.address as usize, read_size) {
-                    Err(_) => None, // Ignore errors during parallel scan
-                    Ok(val) => {
-                        let mut updated = result.clone();
-                        updated.value_type = self.value_type;
-                        updated.value = val;
-                        Some(updated)
-                    }
+    fn truth(&self, prev: f64, cur: f64) -> bool {
+        let ExprNode::Binary(op, a, b) = self else {
+            return false;
+        };
+        match op {
+            ExprOp::And => a.truth(prev, cur) && b.truth(prev, cur),
+            ExprOp::Or => a.truth(prev, cur) || b.truth(prev, cur),
+            op => {
+                let (a, b) = (a.number(prev, cur), b.number(prev, cur));
+                match op {
+                    ExprOp::Eq => a == b,
+                    ExprOp::Ne => a != b,
+                    ExprOp::Lt => a < b,
+                    ExprOp::Le => a <= b,
+                    ExprOp::Gt => a > b,
+                    ExprOp::Ge => a >= b,
+                    _ => false,
                 }
-            })
-            .collect();
-
-        self.watchlist = updated_watchlist;
-        Ok(())
+            }
+        }
     }
+}
 
-    pub fn init(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
-        self.check_value()?;
-
-        // Parallel scan across memory regions
-        let results: Result<Vec<Vec<ScanResult>>, MemoryError> = self
-            .memory_regions
-            .par_iter()
-            .map(|region| self.scan_region(region))
-            .collect();
-
-        let results = results.map_err(ScanError::Memory)?;
-        self.results = results.into_iter().flatten().collect();
-        self.refresh_watchlist()?;
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(f64),
+    Name(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
 
-        Ok(&self.results)
+fn tokenize_expr(text: &str) -> Result<Vec<ExprToken>, ScanError> {
+    const OPS: [&str; 14] = ["==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "<", ">", "(", ")"];
+    let mut tokens = vec![];
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(match *op {
+                "(" => ExprToken::Open,
+                ")" => ExprToken::Close,
+                op => ExprToken::Op(op),
+            });
+            rest = &rest[op.len()..];
+        } else if rest.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '.').unwrap_or(rest.len());
+            let literal = &rest[..end];
+            let number = match hex_digits(literal) {
+                Some(digits) => u64::from_str_radix(digits, 16).map(|n| n as f64).ok(),
+                None => literal.parse::<f64>().ok(),
+            };
+            let number = number
+                .ok_or_else(|| ScanError::InvalidExpression(format!("invalid number '{literal}'")))?;
+            tokens.push(ExprToken::Number(number));
+            rest = &rest[end..];
+        } else if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push(ExprToken::Name(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            return Err(ScanError::InvalidExpression(format!("unexpected '{c}'")));
+        }
+        rest = rest.trim_start();
     }
+    Ok(tokens)
+}
 
-    pub fn refresh(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
-        self.check_value()?;
-
-        if self.results.is_empty() {
-            self.refresh_watchlist()?;
-            return Ok(&self.results);
-        }
+/// Recursive descent parser over the tokens of a [`CompareExpr`], each level returns its
+/// node and whether it is a condition (true) or a number (false)
+struct ExprParser {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+}
 
-        // Early validation with single read to catch ProcessAttach errors
-        if let Some(first) = self.results.first() {
-            let read_size = self.read_size.unwrap_or(first.value.len());
-            if let Err(e) = read_memory_address(self.pid, first.address as usize, read_size)
-                && let MemoryError::ProcessAttach(_) = e {
-                    return Err(ScanError::Memory(e));
-                }
+impl ExprParser {
+    fn peek_op(&self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(ExprToken::Op(op)) if ops.contains(op) => Some(op),
+            _ => None,
         }
+    }
 
-        // Parallel refresh
-        let updated_results: Vec<ScanResult> = self
-            .results
-            .par_iter()
-            .filter_map(|result| {
-                let read_size = self.read_size.unwrap_or(result.value.len());
-                match read_memory_address(self.pid, result.address as usize, read_size) {
-                    Err(_) => None, // Ignore errors during parallel scan
-                    Ok(val) => {
-                        let mut updated = result.clone();
-                        updated.value_type = self.value_type;
-                        updated.value = val;
-                        Some(updated)
-                    }
-                }
-            })
-            .collect();
+    fn fail<T>(&self, msg: &str) -> Result<T, ScanError> {
+        Err(ScanError::InvalidExpression(msg.to_string()))
+    }
 
-        self.results = updated_results;
-        self.refresh_watchlist()?;
+    /// `operand (op operand)*` for left associative levels, operands must all be
+    /// conditions (`logic`) or numbers
+    fn chain(
+        &mut self,
+        ops: &[&'static str],
+        logic: bool,
+        operand: fn(&mut Self) -> Result<(ExprNode, bool), ScanError>,
+    ) -> Result<(ExprNode, bool), ScanError> {
+        let (mut node, mut is_condition) = operand(self)?;
+        while let Some(op) = self.peek_op(ops) {
+            self.pos += 1;
+            let (rhs, rhs_condition) = operand(self)?;
+            if is_condition != logic || rhs_condition != logic {
+                let expected = if logic { "conditions" } else { "numbers" };
+                return self.fail(&format!("'{op}' needs {expected} on both sides"));
+            }
+            node = ExprNode::Binary(expr_op(op), Box::new(node), Box::new(rhs));
+            is_condition = logic;
+        }
+        Ok((node, is_condition))
+    }
 
-        Ok(&self.results)
+    fn or(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        self.chain(&["||"], true, Self::and)
     }
 
-    pub fn next_scan(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
-        self.check_value()?;
+    fn and(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        self.chain(&["&&"], true, Self::comparison)
+    }
 
-        if self.results.is_empty() {
-            self.refresh_watchlist()?;
-            return Ok(&self.results);
+    fn comparison(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        let (lhs, lhs_condition) = self.sum()?;
+        let Some(op) = self.peek_op(&["==", "!=", "<=", ">=", "<", ">"]) else {
+            return Ok((lhs, lhs_condition));
+        };
+        self.pos += 1;
+        let (rhs, rhs_condition) = self.sum()?;
+        if lhs_condition || rhs_condition {
+            return self.fail(&format!("'{op}' compares numbers, group conditions with && and ||"));
         }
-
-        // Early validation with single read to catch ProcessAttach errors
-        if let Some(first) = self.results.first() {
-            let read_size = self.read_size.unwrap_or(first.value.len());
-            if let Err(e) = read_memory_address(self.pid, first.address as usize, read_size)
-                && let MemoryError::ProcessAttach(_) = e {
-                    return Err(ScanError::Memory(e));
-                }
+        if self.peek_op(&["==", "!=", "<=", ">=", "<", ">"]).is_some() {
+            return self.fail("comparisons can't be chained, join them with &&");
         }
+        Ok((ExprNode::Binary(expr_op(op), Box::new(lhs), Box::new(rhs)), true))
+    }
 
-        // Parallel next scan
-        let new_results: Vec<ScanResult> = self
-            .results
-            .par_iter()
-            .filter_map(|result| {
-                let read_size = self.read_size.unwrap_or(result.value.len());
-                match read_memory_address(self.pid, result.address as usize, read_size) {
-                    Err(_) => None, // Ignore errors during parallel scan
-                    Ok(val) => {
-                        // check only prefix - ensure bounds are valid
-                        if val.len() >= self.value.len() && val[..self.value.len()] == self.value {
-                            let mut new_result = result.clone();
-                            new_result.value_type = self.value_type;
-                            new_result.value = val;
-                            Some(new_result)
-                        } else {
-                            None
-                        }
-                    }
-                }
-            })
-            .collect();
+    fn sum(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        self.chain(&["+", "-"], false, Self::product)
+    }
 
-        self.results = new_results;
-        self.refresh_watchlist()?;
+    fn product(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        self.chain(&["*", "/"], false, Self::unary)
+    }
 
-        Ok(&self.results)
+    fn unary(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        if self.peek_op(&["-"]).is_some() {
+            self.pos += 1;
+            let (node, is_condition) = self.unary()?;
+            if is_condition {
+                return self.fail("'-' needs a number");
+            }
+            return Ok((ExprNode::Neg(Box::new(node)), false));
+        }
+        self.atom()
     }
 
-    pub fn add_to_watchlist(&mut self, result: ScanResult) {
-        let already_existing = self
-            .watchlist
-            .iter()
-            .position(|w| w.address == result.address);
-        if already_existing.is_some() {
-            return;
+    fn atom(&mut self) -> Result<(ExprNode, bool), ScanError> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(ExprToken::Number(n)) => Ok((ExprNode::Number(n), false)),
+            Some(ExprToken::Name(name)) => match name.as_str() {
+                "prev" => Ok((ExprNode::Prev, false)),
+                "cur" => Ok((ExprNode::Cur, false)),
+                _ => self.fail(&format!("unknown variable '{name}', use prev or cur")),
+            },
+            Some(ExprToken::Open) => {
+                let inner = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(ExprToken::Close) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    _ => self.fail("missing ')'"),
+                }
+            }
+            Some(ExprToken::Op(op)) => self.fail(&format!("expected a value before '{op}'")),
+            Some(ExprToken::Close) => self.fail("unexpected ')'"),
+            None => self.fail("expression ends early"),
         }
+    }
+}
 
-        self.watchlist.push(result);
+fn expr_op(op: &str) -> ExprOp {
+    match op {
+        "+" => ExprOp::Add,
+        "-" => ExprOp::Sub,
+        "*" => ExprOp::Mul,
+        "/" => ExprOp::Div,
+        "==" => ExprOp::Eq,
+        "!=" => ExprOp::Ne,
+        "<" => ExprOp::Lt,
+        "<=" => ExprOp::Le,
+        ">" => ExprOp::Gt,
+        ">=" => ExprOp::Ge,
+        "&&" => ExprOp::And,
+        _ => ExprOp::Or,
     }
+}
 
-    pub fn remove_from_watchlist(&mut self, address: u64) {
-        let already_existing = self.watchlist.iter().position(|w| w.address == address);
-        if already_existing.is_none() {
-            return;
-        }
+/// Condition over the baseline value `prev` and the current value `cur` of a result, for
+/// compare scans like `cur > prev * 2` or `cur == prev + 100`. Grammar, loosest first:
+///
+/// ```text
+/// condition  := and ("||" and)*
+/// and        := comparison ("&&" comparison)*
+/// comparison := sum (("==" | "!=" | "<" | "<=" | ">" | ">=") sum)?
+/// sum        := product (("+" | "-") product)*
+/// product    := unary (("*" | "/") unary)*
+/// unary      := "-" unary | number | "prev" | "cur" | "(" condition ")"
+/// ```
+///
+/// The whole expression must be a condition and operands keep their kind: `&&`/`||`
+/// join conditions, arithmetic and comparisons take numbers, and `a < b < c` is
+/// rejected in favour of `a < b && b < c`. Other names than `prev` and `cur` are errors.
+///
+/// Numbers are decimal (`1.5`) or `0x` hex. Values are decoded as their type and compared
+/// as `f64`, so integers above 2^53 lose precision, and dividing by zero gives an
+/// infinity (or NaN, which makes every comparison false) instead of an error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareExpr {
+    root: ExprNode,
+}
 
-        self.watchlist.remove(already_existing.unwrap());
+impl CompareExpr {
+    pub fn parse(text: &str) -> Result<Self, ScanError> {
+        let mut parser = ExprParser { tokens: tokenize_expr(text)?, pos: 0 };
+        if parser.tokens.is_empty() {
+            return parser.fail("the expression is empty");
+        }
+        let (root, is_condition) = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return parser.fail("unexpected input after the expression");
+        }
+        if !is_condition {
+            return parser.fail("the expression needs a comparison, e.g. cur > prev");
+        }
+        Ok(CompareExpr { root })
     }
 
-    pub fn update_value(&mut self, address: u64, value_str: &str) -> Result<(), ScanError> {
-        let value = self.value_from_str(value_str)?;
-        write_memory_address(self.pid, address as usize, &value).map_err(ScanError::Memory)?;
-        Ok(())
+    pub fn matches(&self, prev: f64, cur: f64) -> bool {
+        self.root.truth(prev, cur)
     }
 }
 
-mod test {
-    #[allow(unused_imports)]
-    use crate::core::mem::write_memory_address;
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    pub address: u64,
+    pub value_type: ValueType,
+    pub perms: Vec<MemoryRegionPerms>,
+    pub value: Vec<u8>,
+    /// Value found when the result was first scanned, kept for compare scans
+    pub initial_value: Vec<u8>,
+    /// Index of the named mapping holding the result in the scan's
+    /// [`Scan::region_names`], attached when the result is found
+    pub region: Option<u32>,
+    /// Whether the address is a multiple of the type size, aligned matches are more likely
+    /// to be real values. Always set for strings and hex
+    pub aligned: bool,
+}
 
-    #[test]
-    #[ignore = "requires root"]
-    pub fn test_scan_creation_success() {
-        use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+/// A region the initial scan couldn't read, see [`Scan::set_continue_on_error`]
+#[derive(Debug, Clone)]
+pub struct SkippedRegion {
+    pub region: MemoryRegion,
+    pub error: MemoryError,
+}
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+/// `3 region(s) skipped: 2 x <reason>, 1 x <reason>`, the most common reason first.
+/// `None` when nothing was skipped
+pub fn skipped_summary(skipped: &[SkippedRegion]) -> Option<String> {
+    if skipped.is_empty() {
+        return None;
+    }
+    let mut reasons: Vec<(String, usize)> = vec![];
+    for region in skipped {
+        let reason = region.error.to_string();
+        match reasons.iter_mut().find(|(r, _)| *r == reason) {
+            Some((_, count)) => *count += 1,
+            None => reasons.push((reason, 1)),
+        }
+    }
+    // stable, reasons with the same count stay in the order they were first seen
+    reasons.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let reasons: Vec<String> = reasons.iter().map(|(reason, count)| format!("{count} x {reason}")).collect();
+    Some(format!("{} region(s) skipped: {}", skipped.len(), reasons.join(", ")))
+}
 
-                let scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanError {
+    InvalidValue,
+    EmptyValue,
+    InvalidAddress,
+    AddressMismatch,
+    ReadSizeInvalid(usize, usize),
+    Memory(MemoryError),
+    TypeMismatch,
+    CompareUnsupported,
+    InvalidAlignment,
+    InvalidStride,
+    RangeUnsupported,
+    InvalidRange,
+    UnknownUnsupported,
+    GlobUnsupported,
+    InvalidPattern(String),
+    InvalidExpression(String),
+    InsufficientBytes { needed: usize, got: usize },
+    Spill(SpillError),
+    /// Writing a checkpoint of [`Scan::init_resumable`] failed
+    Checkpoint(String),
+}
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidValue => write!(f, "Invalid scan value provided"),
+            Self::EmptyValue => write!(f, "Value is reqeuired to be set before scan"),
+            Self::InvalidAddress => write!(f, "Invalid address hex"),
+            Self::AddressMismatch => write!(f, "Start address should be smaller than end address"),
+            Self::TypeMismatch => write!(f, "Invalid type for value"),
+            Self::CompareUnsupported => {
+                write!(f, "Increased/decreased and expression compares need a numeric value type")
+            }
+            Self::InvalidAlignment => write!(f, "Alignment should be at least 1"),
+            Self::InvalidStride => {
+                write!(f, "Stride should be at least 1, and small enough that it times the alignment fits an address")
+            }
+            Self::RangeUnsupported => write!(f, "Value ranges (a..b) need a numeric value type"),
+            Self::InvalidRange => write!(f, "The start of a value range should not be above its end"),
+            Self::UnknownUnsupported => {
+                write!(f, "Unknown value scans need a fixed size value type")
+            }
+            Self::GlobUnsupported => write!(f, "Wildcard patterns need the string type"),
+            Self::InvalidPattern(msg) => write!(f, "Invalid wildcard pattern: {msg}"),
+            Self::InvalidExpression(msg) => write!(f, "Invalid compare expression: {msg}"),
+            Self::InsufficientBytes { needed, got } => {
+                write!(f, "Value needs {needed} byte(s), only {got} were read")
+            }
+            Self::ReadSizeInvalid(min, max) => {
+                write!(f, "Read size should be in range {min}-{max}")
+            }
+            Self::Memory(e) => write!(f, "{e}"),
+            Self::Spill(e) => write!(f, "{e}"),
+            Self::Checkpoint(e) => write!(f, "Could not write the checkpoint: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Memory(e) => Some(e),
+            Self::Spill(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl ScanResult {
+    pub fn new(
+        address: u64,
+        value_type: ValueType,
+        value: Vec<u8>,
+        perms: Vec<MemoryRegionPerms>,
+    ) -> Self {
+        let size = value_type.get_size().max(1);
+        ScanResult {
+            address,
+            value_type,
+            perms,
+            initial_value: value.clone(),
+            value,
+            region: None,
+            aligned: address.is_multiple_of(size),
+        }
+    }
+
+    pub fn get_string(&self) -> Result<String, ScanError> {
+        self.value_type.get_value_string(self.value.as_slice())
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        !self.perms.contains(&MemoryRegionPerms::Write)
+    }
+}
+
+/// What [`sort_results`] orders results by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Address,
+    /// The decoded value, numerically for numeric types and by bytes for string/hex
+    Value,
+    /// Name of the mapping holding the result, anonymous memory last, then address
+    Module,
+}
+
+impl SortKey {
+    pub fn parse(key: &str) -> Option<Self> {
+        match key.to_lowercase().as_str() {
+            "address" => Some(Self::Address),
+            "value" => Some(Self::Value),
+            "module" => Some(Self::Module),
+            _ => None,
+        }
+    }
+}
+
+/// Sort `results` by `key`, the region indices of module sorting are looked up in
+/// `region_names`. `descending` reverses the order of the keys only: results without one
+/// (NaN floats, anonymous memory) go last and ties go in address order either way
+pub fn sort_results(
+    results: &mut [ScanResult],
+    key: SortKey,
+    descending: bool,
+    region_names: &RegionNames,
+) {
+    let name = |result: &ScanResult| result.region.and_then(|index| region_names.name(index));
+    let order = |a: &ScanResult, b: &ScanResult| {
+        let (keyless, by_key) = match key {
+            SortKey::Address => (Ordering::Equal, a.address.cmp(&b.address)),
+            SortKey::Value => {
+                let nan = |r: &ScanResult| is_nan(r.value_type, &r.value);
+                (nan(a).cmp(&nan(b)), value_order(a.value_type, &a.value, &b.value))
+            }
+            SortKey::Module => match (name(a), name(b)) {
+                (Some(x), Some(y)) => (Ordering::Equal, x.cmp(y)),
+                (x, y) => (x.is_none().cmp(&y.is_none()), Ordering::Equal),
+            },
+        };
+        let by_key = if descending { by_key.reverse() } else { by_key };
+        keyless.then(by_key).then(a.address.cmp(&b.address))
+    };
+    results.sort_by(order);
+}
+
+/// Keep the results at most `within` bytes before or after `reference`, e.g. the other
+/// fields of the struct a known result is in
+pub fn retain_near(results: &mut Vec<ScanResult>, reference: u64, within: u64) {
+    results.retain(|result| result.address.abs_diff(reference) <= within);
+}
+
+/// `bytes` as a float of `value_type`, `None` for the other types
+fn float_value(value_type: ValueType, bytes: &[u8]) -> Option<f64> {
+    match value_type {
+        ValueType::F32 => bytes.get(..4).map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64),
+        ValueType::F64 => bytes.get(..8).map(|b| f64::from_le_bytes(b.try_into().unwrap())),
+        _ => None,
+    }
+}
+
+fn is_nan(value_type: ValueType, bytes: &[u8]) -> bool {
+    float_value(value_type, bytes).is_some_and(f64::is_nan)
+}
+
+/// Total order of two values: floats with NaN after the numbers, values that don't
+/// compare numerically by their bytes
+fn value_order(value_type: ValueType, a: &[u8], b: &[u8]) -> Ordering {
+    let float = |bytes: &[u8]| float_value(value_type, bytes);
+    if let (Some(a), Some(b)) = (float(a), float(b)) {
+        // -NaN sorts after the numbers too
+        return a.is_nan().cmp(&b.is_nan()).then(a.total_cmp(&b));
+    }
+    value_type.compare_values(a, b).unwrap_or_else(|| a.cmp(b))
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    source: Arc<dyn MemorySource>,
+    pub value: Vec<u8>,
+    pub value_type: ValueType,
+    pub results: Vec<ScanResult>,
+    pub watchlist: Vec<ScanResult>,
+    read_size: Option<usize>,
+    inverse: bool,
+    unknown: bool,
+    align_to: Option<usize>,
+    /// Check only every n-th aligned position, see [`Scan::set_stride`]
+    stride: usize,
+    stop: Option<&'static AtomicBool>,
+    pointer_width: usize,
+    skip_uniform: bool,
+    glob: Option<Glob>,
+    /// Upper bound of a range scan, `value` holds the lower one, see
+    /// [`Scan::set_value_from_str`]
+    range_end: Option<Vec<u8>>,
+    /// Take hex values byte-reversed, see [`Scan::set_reverse_hex`]
+    reverse_hex: bool,
+    stitch_regions: bool,
+    continue_on_error: bool,
+    /// Regions the last initial scan skipped, see [`Scan::set_continue_on_error`]
+    skipped_regions: Vec<SkippedRegion>,
+    /// Pages read by the passes over the results, see [`Scan::invalidate_cache`]
+    cache: ReadCache,
+    /// [`maps_fingerprint`] of the source's regions when the results were last read,
+    /// see [`Scan::maps_changed`]
+    maps_fingerprint: Option<u64>,
+    maps_changed: bool,
+    /// Bytes of the regions the running initial scan is done with, see [`Scan::progress`]
+    progress: Arc<AtomicU64>,
+    /// [`Scan::total_bytes`] when the running initial scan started, for
+    /// [`ScanObserver::on_progress`]
+    progress_total: u64,
+    /// Results the running next scan or compare pass has checked, see
+    /// [`Scan::pass_progress`]
+    pass_progress: Arc<AtomicU64>,
+    /// Hooks the initial scan calls, see [`Scan::set_observer`]
+    observer: Option<Arc<dyn ScanObserver>>,
+    region_names: Arc<RegionNames>,
+    start_address: Option<u64>,
+    end_address: Option<u64>,
+    memory_permissions: Vec<MemoryRegionPerms>,
+    memory_regions: Vec<MemoryRegion>,
+    /// File the results live in instead of `results`, see [`Scan::set_spill_file`]
+    spill: Option<SpillFile>,
+}
+
+impl Scan {
+    pub fn new(
+        pid: u32,
+        value: Vec<u8>,
+        value_type: ValueType,
+        start_address: Option<u64>,
+        end_address: Option<u64>,
+        memory_permissions: Option<Vec<MemoryRegionPerms>>,
+    ) -> Result<Self, ScanError> {
+        let handle = ProcessHandle::open(pid).map_err(ScanError::Memory)?;
+        let mut scan = Self::with_source(
+            Arc::new(handle),
+            value,
+            value_type,
+            start_address,
+            end_address,
+            memory_permissions,
+        )?;
+        scan.set_pointer_width(if crate::core::proc::process_is_64bit(pid) { 8 } else { 4 })?;
+        scan.set_region_names(RegionNames::for_process(pid));
+        Ok(scan)
+    }
+
+    /// Create a scan over any memory source, e.g. an already open [`ProcessHandle`]
+    pub fn with_source(
+        source: Arc<dyn MemorySource>,
+        value: Vec<u8>,
+        value_type: ValueType,
+        start_address: Option<u64>,
+        end_address: Option<u64>,
+        memory_permissions: Option<Vec<MemoryRegionPerms>>,
+    ) -> Result<Self, ScanError> {
+        let memory_permissions = memory_permissions.unwrap_or(DEFAULT_SEARCH_PERMS.to_vec());
+        let memory_regions = source
+            .regions(start_address, end_address, Some(&memory_permissions))
+            .map_err(ScanError::Memory)?;
+
+        Ok(Scan {
+            source,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: size_of::<usize>(),
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Arc::default(),
+            value,
+            start_address,
+            end_address,
+            memory_regions,
+            value_type,
+            memory_permissions,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+        })
+    }
+
+    pub fn set_mem_permissions(
+        &mut self,
+        memory_permissions: Vec<MemoryRegionPerms>,
+    ) -> Result<(), ScanError> {
+        self.memory_permissions = memory_permissions;
+        self.update_memory_regions()?;
+        Ok(())
+    }
+
+    /// Name the regions results are found in, for results whose [`ScanResult::region`] is
+    /// looked up here. Scans created with [`Scan::new`] read them from the process.
+    pub fn set_region_names(&mut self, region_names: RegionNames) {
+        self.region_names = Arc::new(region_names);
+    }
+
+    pub fn region_names(&self) -> Arc<RegionNames> {
+        self.region_names.clone()
+    }
+
+    /// Scan exactly `regions` instead of the regions in the address range with the scan's
+    /// permissions, until the range or the permissions are changed
+    pub fn set_regions(&mut self, regions: Vec<MemoryRegion>) {
+        self.memory_regions = regions;
+    }
+
+    pub fn memory_regions(&self) -> &[MemoryRegion] {
+        &self.memory_regions
+    }
+
+    pub fn memory_permissions(&self) -> &[MemoryRegionPerms] {
+        &self.memory_permissions
+    }
+
+    pub fn set_value_type(
+        &mut self,
+        value_type: ValueType,
+        value_str: Option<&str>,
+    ) -> Result<(), ScanError> {
+        self.value_type = value_type;
+        if let Some(value) = value_str {
+            self.set_value_from_str(value)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_read_size(&mut self, size: Option<usize>) -> Result<(), ScanError> {
+        const MAX_READ_SIZE: usize = 256;
+        const MIN_READ_SIZE: usize = 1;
+
+        if let Some(size) = size {
+            if !(MIN_READ_SIZE..=MAX_READ_SIZE).contains(&size) {
+                return Err(ScanError::ReadSizeInvalid(MIN_READ_SIZE, MAX_READ_SIZE));
+            }
+            self.read_size = Some(size);
+        } else {
+            self.read_size = None;
+        }
+        Ok(())
+    }
+
+    /// Invert the value comparison so scans match addresses whose value is NOT equal
+    /// to the scan value. On an initial scan this matches almost every (type-aligned)
+    /// offset, so it's mostly useful as a next scan filter.
+    pub fn set_inverse(&mut self, inverse: bool) {
+        self.inverse = inverse;
+    }
+
+    /// Take `hex` and `raw:<n>` values byte-reversed. By default hex is bytes in memory
+    /// order as typed, `01020304` is the bytes 01 02 03 04, which suits byte patterns but
+    /// not a number written in hex: reversed, `0x01020304` is 04 03 02 01, the bytes of
+    /// the little-endian u32 0x01020304. Values already set aren't changed, and values
+    /// are still shown in memory order.
+    pub fn set_reverse_hex(&mut self, reverse: bool) {
+        self.reverse_hex = reverse;
+    }
+
+    /// Make [`Scan::init`] an unknown initial value scan: no value is needed and every
+    /// position is kept with its current value, to be narrowed down with
+    /// [`Scan::next_compare`]. Only positions aligned to the value type size (or
+    /// [`Scan::set_align_to`]) are snapshotted, which is 4-8x smaller than every byte
+    /// offset but misses unaligned values; use an alignment of 1 for a dense snapshot.
+    /// Compare rounds re-read the snapshotted addresses, so they keep the alignment.
+    pub fn set_unknown(&mut self, unknown: bool) -> Result<(), ScanError> {
+        if unknown && self.value_size() == 0 {
+            return Err(ScanError::UnknownUnsupported);
+        }
+        self.unknown = unknown;
+        Ok(())
+    }
+
+    /// Stop reading new blocks once `stop` is raised, e.g. by
+    /// [`crate::core::debug::interrupt_flag`]. The scan then finishes with the results
+    /// found so far.
+    pub fn set_stop_flag(&mut self, stop: &'static AtomicBool) {
+        self.stop = Some(stop);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop
+            .is_some_and(|stop| stop.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    /// Have the initial scan call `observer` as it goes, see [`ScanObserver`]
+    pub fn set_observer(&mut self, observer: Arc<dyn ScanObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Count `bytes` more of the running initial scan as done
+    fn add_progress(&self, bytes: u64) {
+        let done = self.progress.fetch_add(bytes, std::sync::atomic::Ordering::Relaxed) + bytes;
+        if let Some(observer) = &self.observer {
+            observer.on_progress(done, self.progress_total);
+        }
+    }
+
+    fn notify_matches(&self, results: &[ScanResult]) {
+        if let Some(observer) = &self.observer {
+            results.iter().for_each(|result| observer.on_match(result));
+        }
+    }
+
+    fn notify_complete(&self) {
+        if let Some(observer) = &self.observer {
+            observer.on_complete(self.result_count());
+        }
+    }
+
+    /// Counter of the bytes the initial scan has covered, reset when it starts. It can be
+    /// polled from another thread while [`Scan::init`] runs, up to [`Scan::total_bytes`]
+    pub fn progress(&self) -> Arc<AtomicU64> {
+        self.progress.clone()
+    }
+
+    /// Counter of the results the running next scan or compare pass has checked, reset
+    /// when it starts. It can be polled from another thread, up to the
+    /// [`Scan::result_count`] the pass started with
+    pub fn pass_progress(&self) -> Arc<AtomicU64> {
+        self.pass_progress.clone()
+    }
+
+    /// Size of the regions the initial scan covers
+    pub fn total_bytes(&self) -> u64 {
+        self.memory_regions.iter().map(|r| r.end - r.start).sum()
+    }
+
+    /// Skip regions that are a single repeated byte (e.g. untouched zero pages) when
+    /// the scan value can't match there. This is a heuristic: each region is sampled at
+    /// a few strided offsets and only read in full when the samples agree, and a region
+    /// written between that check and the scan is still skipped. Unknown value scans
+    /// drop uniform regions entirely.
+    pub fn set_skip_uniform(&mut self, skip: bool) {
+        self.skip_uniform = skip;
+    }
+
+    /// Treat the string scan value as a [`Glob`] pattern, so `Player?` matches `Player1`
+    /// and `Player2`. Applies to the value set at the time of the call, set it again after
+    /// changing the value.
+    pub fn set_glob(&mut self, glob: bool) -> Result<(), ScanError> {
+        self.glob = match glob {
+            false => None,
+            true if self.value_type != ValueType::String => return Err(ScanError::GlobUnsupported),
+            true => Some(Glob::parse(&self.value)?),
+        };
+        Ok(())
+    }
+
+    /// Also find values that start at the end of one region and continue in the next,
+    /// when the two are contiguous in memory (on by default). The per-region scan can't
+    /// see those, e.g. a signature running from a module's code into its read-only data.
+    /// Doesn't apply to unknown value, inverse and range scans, whose values are aligned.
+    pub fn set_stitch_regions(&mut self, stitch: bool) {
+        self.stitch_regions = stitch;
+    }
+
+    /// Skip the regions the initial scan can't read and record why instead of failing
+    /// the scan, see [`Scan::skipped_regions`]. Without it a lost process fails the scan
+    /// and other unreadable regions are passed over silently.
+    pub fn set_continue_on_error(&mut self, continue_on_error: bool) {
+        self.continue_on_error = continue_on_error;
+    }
+
+    /// Regions the last initial scan skipped with [`Scan::set_continue_on_error`]
+    pub fn skipped_regions(&self) -> &[SkippedRegion] {
+        &self.skipped_regions
+    }
+
+    /// Handle a region the initial scan couldn't read: record it in `skipped` when
+    /// continuing on errors, otherwise fail the scan if the process is gone
+    fn skip_region(&self, skipped: SkippedRegion, into: &mut Vec<SkippedRegion>) -> Result<(), MemoryError> {
+        match skipped.error {
+            _ if self.continue_on_error => {
+                log::debug!(
+                    "Skipping region 0x{:x}-0x{:x}: {}",
+                    skipped.region.start,
+                    skipped.region.end,
+                    skipped.error
+                );
+                into.push(skipped);
+                Ok(())
+            }
+            MemoryError::ProcessAttach(_) => Err(skipped.error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `bytes` start with the scan value, a value in the range of a range scan or
+    /// a match of the pattern with [`Scan::set_glob`]
+    fn value_matches(&self, bytes: &[u8]) -> bool {
+        if let Some(high) = &self.range_end {
+            let value_type = self.value_type;
+            return value_type.compare_values(bytes, &self.value).is_some_and(Ordering::is_ge)
+                && value_type.compare_values(bytes, high).is_some_and(Ordering::is_le);
+        }
+        match &self.glob {
+            Some(glob) => glob.matches(bytes),
+            None => bytes.starts_with(&self.value),
+        }
+    }
+
+    /// Pointer width of the target in bytes (4 or 8) used by [`ValueType::Pointer`].
+    /// Scans of a process detect it, other memory sources default to the host width.
+    pub fn set_pointer_width(&mut self, width: usize) -> Result<(), ScanError> {
+        if width != 4 && width != 8 {
+            return Err(ScanError::InvalidValue);
+        }
+        self.pointer_width = width;
+        Ok(())
+    }
+
+    /// Size of a value of the scan type in the target, 0 for variable length types
+    pub fn value_size(&self) -> usize {
+        match self.value_type {
+            ValueType::Pointer => self.pointer_width,
+            value_type => value_type.get_size() as usize,
+        }
+    }
+
+    /// Length of a match: the scan value, or a whole value of the type for unknown scans
+    fn match_len(&self) -> usize {
+        match &self.glob {
+            _ if self.unknown => self.value_size(),
+            Some(glob) => glob.len(),
+            None => self.value.len(),
+        }
+    }
+
+    /// Only report addresses that are a multiple of `align`, e.g. 16 for values in
+    /// 16-byte aligned structs. Takes precedence over the value type alignment used by
+    /// inverse scans.
+    pub fn set_align_to(&mut self, align: Option<usize>) -> Result<(), ScanError> {
+        if align == Some(0) {
+            return Err(ScanError::InvalidAlignment);
+        }
+        if self.step_for(align, self.stride).is_none() {
+            return Err(ScanError::InvalidStride);
+        }
+        self.align_to = align;
+        Ok(())
+    }
+
+    /// Check only every `stride`-th aligned position: addresses that are a multiple of
+    /// the alignment (`align_to`, or the type size) times `stride`. Much less is compared
+    /// and reported, but matches in between are missed, so this is for a rough first pass
+    /// over a big process that finds the area to rescan fully. Exact value scans, which
+    /// otherwise match at any offset, only match on that grid too. 1 checks every position.
+    pub fn set_stride(&mut self, stride: usize) -> Result<(), ScanError> {
+        if stride == 0 || self.step_for(self.align_to, stride).is_none() {
+            return Err(ScanError::InvalidStride);
+        }
+        self.stride = stride;
+        Ok(())
+    }
+
+    /// Distance between the positions unknown and inverse scans check, and exact scans too
+    /// with a stride
+    fn position_step(&self) -> usize {
+        // the setters refuse steps that overflow, a step past every address checks none
+        self.step_for(self.align_to, self.stride).unwrap_or(usize::MAX)
+    }
+
+    /// [`Scan::position_step`] with `align_to` and `stride`, `None` if it overflows
+    fn step_for(&self, align_to: Option<usize>, stride: usize) -> Option<usize> {
+        align_to.unwrap_or(std::cmp::max(self.value_size(), 1)).checked_mul(stride)
+    }
+
+    fn is_aligned(&self, address: usize) -> bool {
+        match self.stride {
+            1 => self.align_to.is_none_or(|align| address.is_multiple_of(align)),
+            _ => address.is_multiple_of(self.position_step()),
+        }
+    }
+
+    /// Bytes of `value_str` as a value of the scan's type. Integers may be given in hex with
+    /// a `0x` prefix, which signed types take as the two's complement bit pattern:
+    /// `0xffffffff` is -1 as an i32.
+    pub fn value_from_str(&self, value_str: &str) -> Result<Vec<u8>, ScanError> {
+        Ok(match self.value_type {
+            ValueType::U64 => match hex_digits(value_str) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => value_str.parse::<u64>(),
+            }
+            .map_err(|_| ScanError::InvalidValue)?
+            .to_le_bytes()
+            .to_vec(),
+            ValueType::I64 => match hex_digits(value_str) {
+                Some(hex) => u64::from_str_radix(hex, 16).map(|bits| bits as i64),
+                None => value_str.parse::<i64>(),
+            }
+            .map_err(|_| ScanError::InvalidValue)?
+            .to_le_bytes()
+            .to_vec(),
+            ValueType::U32 => match hex_digits(value_str) {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => value_str.parse::<u32>(),
+            }
+            .map_err(|_| ScanError::InvalidValue)?
+            .to_le_bytes()
+            .to_vec(),
+            ValueType::I32 => match hex_digits(value_str) {
+                Some(hex) => u32::from_str_radix(hex, 16).map(|bits| bits as i32),
+                None => value_str.parse::<i32>(),
+            }
+            .map_err(|_| ScanError::InvalidValue)?
+            .to_le_bytes()
+            .to_vec(),
+            ValueType::F64 => value_str
+                .parse::<f64>()
+                .map_err(|_| ScanError::InvalidValue)?
+                .to_le_bytes()
+                .to_vec(),
+            ValueType::F32 => value_str
+                .parse::<f32>()
+                .map_err(|_| ScanError::InvalidValue)?
+                .to_le_bytes()
+                .to_vec(),
+            ValueType::Pointer => {
+                let address = u64::from_str_radix(value_str.trim_start_matches("0x"), 16)
+                    .map_err(|_| ScanError::InvalidValue)?;
+                if self.pointer_width == 4 {
+                    u32::try_from(address)
+                        .map_err(|_| ScanError::InvalidValue)?
+                        .to_le_bytes()
+                        .to_vec()
+                } else {
+                    address.to_le_bytes().to_vec()
+                }
+            }
+            ValueType::Bool => match value_str.to_lowercase().as_str() {
+                "true" | "1" => vec![1],
+                "false" | "0" => vec![0],
+                _ => return Err(ScanError::InvalidValue),
+            },
+            ValueType::String => value_str.as_bytes().to_vec(),
+            ValueType::Hex => {
+                let hex_str = value_str.trim_start_matches("0x");
+                let mut bytes = hex::decode(hex_str).map_err(|_| ScanError::InvalidValue)?;
+                if self.reverse_hex {
+                    bytes.reverse();
+                }
+                bytes
+            }
+            ValueType::Raw(size) => {
+                let mut bytes = hex::decode(value_str.trim_start_matches("0x"))
+                    .map_err(|_| ScanError::InvalidValue)?;
+                if bytes.len() != size {
+                    return Err(ScanError::InvalidValue);
+                }
+                if self.reverse_hex {
+                    bytes.reverse();
+                }
+                bytes
+            }
+            ValueType::Custom(name) => codec(name).ok_or(ScanError::InvalidValue)?.encode(value_str)?,
+        })
+    }
+
+    /// Set the scan value. For numeric types `a..b` scans for every value from `a` to `b`
+    /// inclusive at the aligned positions, e.g. `100..110` for one of a few known IDs.
+    /// Strings are taken as they are, `..` included.
+    pub fn set_value_from_str(&mut self, value_str: &str) -> Result<(), ScanError> {
+        let range = value_str.split_once("..").filter(|_| self.value_type != ValueType::String);
+        let Some((low, high)) = range else {
+            self.value = self.value_from_str(value_str)?;
+            self.range_end = None;
+            return Ok(());
+        };
+
+        if !self.value_type.is_numeric() {
+            return Err(ScanError::RangeUnsupported);
+        }
+        let (low, high) = (self.value_from_str(low.trim())?, self.value_from_str(high.trim())?);
+        if self.value_type.compare_values(&low, &high) == Some(Ordering::Greater) {
+            return Err(ScanError::InvalidRange);
+        }
+        self.value = low;
+        self.range_end = Some(high);
+        Ok(())
+    }
+
+    /// Use the `size` bytes currently at `address` as the scan value, to find other
+    /// addresses holding the same value
+    pub fn set_value_from_address(&mut self, address: u64, size: usize) -> Result<(), ScanError> {
+        self.value = self
+            .source
+            .read(address as usize, size)
+            .map_err(ScanError::Memory)?;
+        self.range_end = None;
+
+        Ok(())
+    }
+
+    fn parse_address_hex(addr_hex: &str) -> Result<Option<u64>, ScanError> {
+        if addr_hex.is_empty() {
+            Ok(None)
+        } else {
+            let parsed_addr = u64::from_str_radix(addr_hex.trim_start_matches("0x"), 16)
+                .map_err(|_| ScanError::InvalidAddress)?;
+            Ok(Some(parsed_addr))
+        }
+    }
+
+    fn update_memory_regions(&mut self) -> Result<(), ScanError> {
+        self.memory_regions = self
+            .source
+            .regions(
+                self.start_address,
+                self.end_address,
+                Some(&self.memory_permissions),
+            )
+            .map_err(ScanError::Memory)?;
+        Ok(())
+    }
+
+    pub fn set_start_address(&mut self, addr_hex: &str) -> Result<(), ScanError> {
+        let parsed_addr = Self::parse_address_hex(addr_hex)?;
+
+        if let (Some(start), Some(end)) = (parsed_addr, self.end_address)
+            && start > end
+        {
+            return Err(ScanError::AddressMismatch);
+        }
+
+        self.start_address = parsed_addr;
+        self.update_memory_regions()?;
+
+        Ok(())
+    }
+
+    pub fn set_end_address(&mut self, addr_hex: &str) -> Result<(), ScanError> {
+        let parsed_addr = Self::parse_address_hex(addr_hex)?;
+
+        if let (Some(start), Some(end)) = (self.start_address, parsed_addr)
+            && end < start
+        {
+            return Err(ScanError::AddressMismatch);
+        }
+
+        self.end_address = parsed_addr;
+        self.update_memory_regions()?;
+
+        Ok(())
+    }
+
+    /// Matches that start in the last `match_len - 1` bytes of a region and run into the
+    /// region right after it, see [`Scan::set_stitch_regions`]. Both regions must be
+    /// readable; seams that can't be read are skipped.
+    fn scan_seams(&self) -> Vec<ScanResult> {
+        let len = self.match_len();
+        if !self.stitch_regions || self.unknown || self.inverse || self.range_end.is_some() || len < 2 {
+            return vec![];
+        }
+        let size = self.read_size.unwrap_or(len).max(len);
+        let readable = |region: &MemoryRegion| region.perms.contains(&MemoryRegionPerms::Read);
+
+        let seams: Vec<ScanResult> = self
+            .memory_regions
+            .windows(2)
+            .filter(|pair| pair[0].end == pair[1].start && readable(&pair[0]) && readable(&pair[1]))
+            .filter(|_| !self.is_stopped())
+            .flat_map(|pair| {
+                let (before, after) = (&pair[0], &pair[1]);
+                let tail = (len as u64 - 1).min(before.end - before.start);
+                let head = (size as u64 - 1).min(after.end - after.start);
+                let start = before.end - tail;
+                let window = match self.source.read(start as usize, (tail + head) as usize) {
+                    Ok(window) => window,
+                    Err(e) => {
+                        log::trace!("Skipping the seam at 0x{:x}: {e}", before.end);
+                        return vec![];
+                    }
+                };
+                let positions = match &self.glob {
+                    Some(glob) => glob.find_all(&window),
+                    None => find_exact(&window, &self.value),
+                };
+                let region_index = self.region_names.index_for(before.start);
+                positions
+                    .into_iter()
+                    // matches that end before the seam were found in `before` already
+                    .filter(|&i| (i as u64) < tail && self.is_aligned(start as usize + i))
+                    .map(|i| ScanResult {
+                        region: region_index,
+                        ..ScanResult::new(
+                            start + i as u64,
+                            self.value_type,
+                            window[i..std::cmp::min(i + size, window.len())].to_vec(),
+                            before.perms.clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        self.notify_matches(&seams);
+        seams
+    }
+
+    fn scan_region(&self, region: &MemoryRegion) -> Result<Vec<ScanResult>, MemoryError> {
+        let mut results = vec![];
+        self.scan_region_batches(region, usize::MAX, |batch, _| results.extend(batch))?;
+        Ok(results)
+    }
+
+    /// Scan `region` `batch_blocks` blocks at a time, each batch in parallel, and hand
+    /// the results of each batch to `emit` in address order, with the address the next
+    /// batch starts at (the region end after the last one)
+    fn scan_region_batches(
+        &self,
+        region: &MemoryRegion,
+        batch_blocks: usize,
+        mut emit: impl FnMut(Vec<ScanResult>, u64),
+    ) -> Result<(), MemoryError> {
+        let start = region.start as usize;
+        let end = region.end as usize;
+        let size = self.read_size.unwrap_or(self.match_len());
+
+        const BLOCK_SIZE: usize = 0x10000;
+
+        if let Some(observer) = &self.observer {
+            observer.on_region_start(region);
+        }
+
+        // Validate region with a single byte read to catch ProcessAttach errors early
+        if let Err(e) = self.source.read(start, 1)
+            && let MemoryError::ProcessAttach(_) = e {
+                return Err(e);
+            }
+
+        if self.skip_uniform
+            && let Some(byte) = uniform_byte(self.source.as_ref(), region)
+        {
+            // every position of the region holds the same bytes
+            let matches =
+                !self.unknown && self.value_matches(&vec![byte; self.match_len()]) != self.inverse;
+            if !matches {
+                log::debug!(
+                    "Skipping region 0x{:x}-0x{:x}, every byte is 0x{:02x}",
+                    region.start,
+                    region.end,
+                    byte
+                );
+                self.add_progress(region.end - region.start);
+                return Ok(());
+            }
+        }
+
+        // a region is a single mapping, so its name is looked up once for all its results
+        let region_index = self.region_names.index_for(region.start);
+
+        // Generate all block addresses to scan
+        let block_addresses: Vec<usize> = {
+            let mut addresses = Vec::new();
+            let mut current_address = start;
+            while current_address < end {
+                let to_read = std::cmp::min(BLOCK_SIZE, end - current_address);
+                if to_read < size {
+                    break;
+                }
+                addresses.push(current_address);
+                current_address += to_read - (size - 1);
+            }
+            addresses
+        };
+
+        // Parallel scan of the blocks of each batch
+        let mut read_blocks = 0;
+        let mut result_count = 0;
+        let mut block_error = None;
+        let batch_blocks = batch_blocks.max(1);
+        for (batch_index, batch) in block_addresses.chunks(batch_blocks).enumerate() {
+            let results: Vec<Result<Vec<ScanResult>, MemoryError>> = batch
+                .par_iter()
+                .filter_map(|&current_address| {
+                    if self.is_stopped() {
+                        return None;
+                    }
+                    let to_read = std::cmp::min(BLOCK_SIZE, end - current_address);
+                    // blocks overlap by the match length, which the next block counts
+                    let step = (to_read - (size - 1)) as u64;
+                    self.add_progress(step);
+                    // so a match starting in the overlap is the next block's, unless there
+                    // is none and the tail is left to this one
+                    let followed = end - (current_address + step as usize) >= size;
+                    let owned = |i: &usize| !followed || (*i as u64) < step;
+
+                    match self.source.read(current_address, to_read) {
+                        // a block that can't be read is skipped, the region only fails when
+                        // none of its blocks can be read
+                        Err(e) => {
+                            log::trace!("Skipping block 0x{current_address:x} ({to_read} bytes): {e}");
+                            Some(Err(e))
+                        }
+                        Ok(val) => {
+                            let positions: Vec<usize> = if self.unknown {
+                                self.aligned_positions(&val, current_address, end).collect()
+                            } else if self.inverse || self.stride > 1 || self.range_end.is_some() {
+                                // a sparse grid compares fewer bytes than searching the block,
+                                // and a range has no bytes to search for
+                                self.aligned_positions(&val, current_address, end)
+                                    .filter(|&i| self.value_matches(&val[i..]) != self.inverse)
+                                    .collect()
+                            } else if let Some(glob) = &self.glob {
+                                glob.find_all(&val)
+                                    .into_iter()
+                                    .filter(|&i| self.is_aligned(current_address + i))
+                                    .collect()
+                            } else {
+                                find_exact(&val, &self.value)
+                                    .into_iter()
+                                    .filter(|&i| self.is_aligned(current_address + i))
+                                    .collect()
+                            };
+
+                            let block_results: Vec<ScanResult> = positions
+                                .into_iter()
+                                .filter(owned)
+                                .map(|i| {
+                                    // Take all available data from position i, up to size bytes
+                                    let end_offset = std::cmp::min(i + size, val.len());
+                                    ScanResult {
+                                        region: region_index,
+                                        ..ScanResult::new(
+                                            (current_address + i) as u64,
+                                            self.value_type,
+                                            val[i..end_offset].to_vec(),
+                                            region.perms.clone(),
+                                        )
+                                    }
+                                })
+                                .collect();
+                            Some(Ok(block_results))
+                        }
+                    }
+                })
+                .collect();
+            let mut read = vec![];
+            for result in results {
+                match result {
+                    Ok(results) => read.push(results),
+                    Err(e) => {
+                        block_error.get_or_insert(e);
+                    }
+                }
+            }
+            read_blocks += read.len();
+            let results: Vec<ScanResult> = read.into_iter().flatten().collect();
+            result_count += results.len();
+            self.notify_matches(&results);
+            let next = block_addresses.get((batch_index + 1).saturating_mul(batch_blocks)).map_or(end, |&a| a);
+            emit(results, next as u64);
+        }
+        // the tail of the last block (or a region shorter than a match) has no block of its own
+        let stepped: u64 = block_addresses
+            .iter()
+            .map(|&a| (std::cmp::min(BLOCK_SIZE, end - a) - (size - 1)) as u64)
+            .sum();
+        self.add_progress((region.end - region.start).saturating_sub(stepped));
+
+        log::debug!(
+            "Region 0x{:x}-0x{:x}: read {} of {} block(s), {} result(s)",
+            region.start,
+            region.end,
+            read_blocks,
+            block_addresses.len(),
+            result_count
+        );
+        match block_error {
+            Some(e) if read_blocks == 0 && !self.is_stopped() => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Offsets in `block`, which starts at `block_address`, where a value can start: the
+    /// ones aligned to [`Scan::position_step`] with room for a whole match. Unless the block
+    /// reaches `region_end`, offsets in its overlap with the next block are left to that
+    /// block, so no offset is checked twice.
+    fn aligned_positions(
+        &self,
+        block: &[u8],
+        block_address: usize,
+        region_end: usize,
+    ) -> impl Iterator<Item = usize> {
+        let len = self.match_len();
+        if block.len() < len || len == 0 {
+            return (0..0).step_by(1);
+        }
+
+        let size = self.read_size.unwrap_or(len);
+        let step = self.position_step();
+        let last = block.len() - len + 1;
+        let limit = if block_address + block.len() >= region_end {
+            last
+        } else {
+            std::cmp::min(block.len() - (size.max(1) - 1), last)
+        };
+        let first = (step - block_address % step) % step;
+
+        (first..limit).step_by(step)
+    }
+
+    fn check_value(&self) -> Result<(), ScanError> {
+        if self.unknown && self.value.is_empty() {
+            return Ok(());
+        }
+        if self.value.is_empty() {
+            return Err(ScanError::EmptyValue);
+        }
+
+        self.value_type
+            .get_value_string(&self.value)
+            .map_err(|_| ScanError::TypeMismatch)?;
+
+        Ok(())
+    }
+
+    pub fn refresh_watchlist(&mut self) -> Result<(), ScanError> {
+        self.check_value()?;
+        self.cache.begin_pass();
+        self.read_watchlist()?;
+        self.cache.end_pass();
+        Ok(())
+    }
+
+    /// Whether the last [`Scan::next_scan`], [`Scan::refresh`] or compare pass (e.g.
+    /// [`Scan::next_compare`]) found the process's memory
+    /// map changed since the results were read before, e.g. after a level load. Cached
+    /// pages were dropped then and every result read again, results that were unmapped are
+    /// gone and the others may be in a different mapping now.
+    pub fn maps_changed(&self) -> bool {
+        self.maps_changed
+    }
+
+    /// Remember the fingerprint of the source's regions to compare the next pass against
+    fn record_maps(&mut self) {
+        self.maps_fingerprint = all_regions(self.source.as_ref()).ok().map(|r| maps_fingerprint(&r));
+    }
+
+    /// Compare the source's regions against the ones of the previous pass, dropping the
+    /// cached pages if they changed. A source whose regions can't be listed counts as
+    /// unchanged, the pass reports its error.
+    fn check_maps(&mut self) {
+        self.maps_changed = false;
+        let Some(before) = self.maps_fingerprint else {
+            return self.record_maps();
+        };
+        let Ok(regions) = all_regions(self.source.as_ref()) else {
+            return;
+        };
+        let now = maps_fingerprint(&regions);
+        if now != before {
+            log::debug!("Memory map of {:?} changed since the last pass, reading every result again", self.source);
+            self.cache.invalidate();
+            self.maps_fingerprint = Some(now);
+            self.maps_changed = true;
+        }
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Keep the results in a memory-mapped file at `path` instead of `results`, for scans
+    /// with more matches than fit in memory (see [`crate::core::spill`]). Set it after the
+    /// value and read size, the records are sized for them. `results` stays empty, use
+    /// [`Scan::result_count`] and [`Scan::spilled_results`].
+    pub fn set_spill_file(&mut self, path: &std::path::Path) -> Result<(), ScanError> {
+        let width = self.read_size.unwrap_or(self.match_len()).max(1);
+        self.spill =
+            Some(SpillFile::create(path, self.value_type, width).map_err(ScanError::Spill)?);
+        Ok(())
+    }
+
+    /// Number of results, spilled or not
+    pub fn result_count(&self) -> usize {
+        match &self.spill {
+            Some(spill) => spill.len(),
+            None => self.results.len(),
+        }
+    }
+
+    /// The results in the spill file, `None` unless [`Scan::set_spill_file`] was called
+    pub fn spilled_results(&self) -> Option<crate::core::spill::SpillIter<'_>> {
+        self.spill.as_ref().map(SpillFile::iter)
+    }
+
+    fn first_result(&self) -> Option<ScanResult> {
+        match &self.spill {
+            Some(spill) => spill.get(0),
+            None => self.results.first().cloned(),
+        }
+    }
+
+    /// Initial scan into the spill file. Regions are scanned one after another (their
+    /// blocks still in parallel) and each one's results are written out before the next,
+    /// so only a region's worth of results is held in memory.
+    fn fill_spill(&mut self) -> Result<(), ScanError> {
+        let Some(mut spill) = self.spill.take() else {
+            return Ok(());
+        };
+        spill.clear();
+        let mut skipped = vec![];
+        let filled = self.spill_regions(&mut spill, &mut skipped);
+        self.spill = Some(spill);
+        self.skipped_regions = skipped;
+        filled?;
+        self.refresh_watchlist()
+    }
+
+    fn spill_regions(&self, spill: &mut SpillFile, skipped: &mut Vec<SkippedRegion>) -> Result<(), ScanError> {
+        // seams are found in region order, each one after the results of the region it
+        // starts in
+        let mut seams = self.scan_seams().into_iter().peekable();
+        let mut last: Option<u64> = None;
+        let mut failed = None;
+        let mut push = |mut results: Vec<ScanResult>| {
+            results.sort_by_key(|r| r.address);
+            // batches overlap by the match length like blocks do
+            for result in &results {
+                if failed.is_none() && last.is_none_or(|last| result.address > last) {
+                    failed = spill.push(result).err();
+                    last = Some(result.address);
+                }
+            }
+        };
+        for region in &self.memory_regions {
+            if let Err(error) = self.scan_region_batches(region, SPILL_BATCH_BLOCKS, |results, _| push(results)) {
+                self.skip_region(SkippedRegion { region: region.clone(), error }, skipped)
+                    .map_err(ScanError::Memory)?;
+            }
+            let region_seams = std::iter::from_fn(|| seams.next_if(|seam| seam.address < region.end));
+            push(region_seams.collect());
+        }
+        failed.map_or(Ok(()), |e| Err(ScanError::Spill(e)))
+    }
+
+    /// Replace the results with what `keep` returns for each, in parallel. Spilled results
+    /// are rewritten in place.
+    fn filter_results(
+        &mut self,
+        keep: impl Fn(&Scan, &ScanResult) -> Option<ScanResult> + Sync,
+    ) -> Result<(), ScanError> {
+        use std::sync::atomic::Ordering;
+
+        self.pass_progress.store(0, Ordering::Relaxed);
+        match self.spill.take() {
+            Some(mut spill) => {
+                let filtered = spill.retain(|result| {
+                    self.pass_progress.fetch_add(1, Ordering::Relaxed);
+                    keep(self, result)
+                });
+                self.spill = Some(spill);
+                filtered.map_err(ScanError::Spill)
+            }
+            None => {
+                let results = std::mem::take(&mut self.results);
+                // counted a chunk at a time, the threads would fight over a shared counter
+                self.results = results
+                    .par_chunks(PASS_PROGRESS_CHUNK)
+                    .flat_map_iter(|chunk| {
+                        let kept: Vec<ScanResult> =
+                            chunk.iter().filter_map(|result| keep(self, result)).collect();
+                        self.pass_progress.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                        kept
+                    })
+                    .collect();
+                Ok(())
+            }
+        }
+    }
+
+    fn read_watchlist(&mut self) -> Result<(), ScanError> {
+        if self.watchlist.is_empty() {
+            return Ok(());
+        }
+
+        // Early validation with single read to catch ProcessAttach errors
+        if let Some(first) = self.watchlist.first() {
+            let read_size = self.read_size.unwrap_or(first.value.len());
+            if let Err(e) = self.source.read(first.address as usize, read_size)
+                && let MemoryError::ProcessAttach(_) = e {
+                    return Err(ScanError::Memory(e));
+                }
+        }
+
+        // Parallel refresh
+        let updated_watchlist: Vec<ScanResult> = self
+            .watchlist
+            .par_iter()
+            .filter_map(|result| {
+                let read_size = self.read_size.unwrap_or(result.value.len());
+                match self.cache.read(self.source.as_ref(), result.address as usize, read_size) {
+                    Err(_) => None, // Ignore errors during parallel scan
+                    Ok(val) => {
+                        let mut updated = result.clone();
+                        updated.value_type = self.value_type;
+                        updated.value = val;
+                        Some(updated)
+                    }
+                }
+            })
+            .collect();
+
+        self.watchlist = updated_watchlist;
+        Ok(())
+    }
+
+    pub fn init(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
+        ScanExecutor::global()
+            .init(std::slice::from_mut(self))
+            .pop()
+            .unwrap_or(Ok(()))?;
+
+        Ok(&self.results)
+    }
+
+    /// Run the initial scan region by region, [`CHECKPOINT_BLOCKS`] blocks at a time, for
+    /// scans that take long enough to be interrupted (see [`crate::core::checkpoint`]). It
+    /// starts at `from` in [`Scan::memory_regions`] with the results `found` before it,
+    /// and hands `checkpoint` the position reached and the results found since its last
+    /// call once `every` passed, and when the scan is stopped. The blocks a stop cuts
+    /// short don't count as done and their results are left out. Returns whether every
+    /// region was scanned, `false` when the scan was stopped before and the last
+    /// checkpoint holds what is left. Spilled scans run like [`Scan::init`].
+    pub fn init_resumable(
+        &mut self,
+        from: ScanPosition,
+        found: Vec<ScanResult>,
+        every: std::time::Duration,
+        mut checkpoint: impl FnMut(ScanPosition, &[ScanResult]) -> std::io::Result<()>,
+    ) -> Result<bool, ScanError> {
+        if self.spill.is_some() {
+            self.init()?;
+            return Ok(true);
+        }
+        self.check_value()?;
+        let regions = &self.memory_regions;
+        let from = match regions.get(from.region) {
+            Some(region) => ScanPosition { offset: from.offset.min(region.end - region.start), ..from },
+            None => ScanPosition { region: regions.len(), offset: 0 },
+        };
+        let done_bytes: u64 = regions[..from.region].iter().map(|r| r.end - r.start).sum();
+        self.progress.store(done_bytes + from.offset, std::sync::atomic::Ordering::Relaxed);
+        self.progress_total = self.total_bytes();
+
+        // results read back from a file have no permissions and mapping
+        let mut results: Vec<ScanResult> = found
+            .into_iter()
+            .map(|result| ScanResult {
+                value_type: self.value_type,
+                perms: regions
+                    .partition_point(|r| r.start <= result.address)
+                    .checked_sub(1)
+                    .map(|i| &regions[i])
+                    .filter(|r| result.address < r.end)
+                    .map(|r| r.perms.clone())
+                    .unwrap_or_default(),
+                region: self.region_names.index_for(result.address),
+                ..result
+            })
+            .collect();
+        // the results before `unsaved` were handed to `checkpoint` already
+        let mut unsaved = results.len();
+        let mut skipped = vec![];
+        let mut position = from;
+        let mut last_checkpoint = std::time::Instant::now();
+        let mut failed = None;
+        for (index, region) in regions.iter().enumerate().skip(from.region) {
+            let offset = if index == from.region { from.offset } else { 0 };
+            let rest = MemoryRegion { start: region.start + offset, ..region.clone() };
+            let scanned = self.scan_region_batches(&rest, CHECKPOINT_BLOCKS, |batch, next| {
+                // a batch the stop cut short is left to the resumed scan
+                if self.is_stopped() || failed.is_some() {
+                    return;
+                }
+                results.extend(batch);
+                position = match next < region.end {
+                    true => ScanPosition { region: index, offset: next - region.start },
+                    false => ScanPosition { region: index + 1, offset: 0 },
+                };
+                if last_checkpoint.elapsed() >= every {
+                    match checkpoint(position, &results[unsaved..]) {
+                        Ok(()) => unsaved = results.len(),
+                        Err(e) => failed = Some(e),
+                    }
+                    last_checkpoint = std::time::Instant::now();
+                }
+            });
+            if let Some(e) = failed {
+                return Err(ScanError::Checkpoint(e.to_string()));
+            }
+            if self.is_stopped() {
+                break;
+            }
+            if let Err(error) = scanned {
+                self.skip_region(SkippedRegion { region: region.clone(), error }, &mut skipped)
+                    .map_err(ScanError::Memory)?;
+            }
+            position = ScanPosition { region: index + 1, offset: 0 };
+        }
+        let complete = position.region == regions.len();
+        match complete {
+            false => checkpoint(position, &results[unsaved..]).map_err(|e| ScanError::Checkpoint(e.to_string()))?,
+            true => results.extend(self.scan_seams()),
+        }
+
+        results.sort_by_key(|r| r.address);
+        results.dedup_by_key(|r| r.address);
+        self.results = results;
+        self.skipped_regions = skipped;
+        self.record_maps();
+        self.refresh_watchlist()?;
+        self.notify_complete();
+        Ok(complete)
+    }
+
+    pub fn refresh(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
+        self.check_value()?;
+        self.check_maps();
+        self.cache.begin_pass();
+
+        if self.result_count() == 0 {
+            self.refresh_watchlist()?;
+            return Ok(&self.results);
+        }
+
+        // Early validation with single read to catch ProcessAttach errors
+        if let Some(first) = self.first_result() {
+            let read_size = self.read_size.unwrap_or(first.value.len());
+            if let Err(e) = self.source.read(first.address as usize, read_size)
+                && let MemoryError::ProcessAttach(_) = e {
+                    return Err(ScanError::Memory(e));
+                }
+        }
+
+        // Parallel refresh
+        self.filter_results(|scan, result| {
+            let read_size = scan.read_size.unwrap_or(result.value.len());
+            match scan.cache.read(scan.source.as_ref(), result.address as usize, read_size) {
+                Err(_) => None, // Ignore errors during parallel scan
+                Ok(val) => {
+                    let mut updated = result.clone();
+                    updated.value_type = scan.value_type;
+                    updated.value = val;
+                    Some(updated)
+                }
+            }
+        })?;
+        self.refresh_watchlist()?;
+
+        Ok(&self.results)
+    }
+
+    pub fn next_scan(&mut self) -> Result<&Vec<ScanResult>, ScanError> {
+        self.check_value()?;
+        self.check_maps();
+        self.cache.begin_pass();
+
+        if self.result_count() == 0 {
+            self.refresh_watchlist()?;
+            return Ok(&self.results);
+        }
+
+        // Early validation with single read to catch ProcessAttach errors
+        if let Some(first) = self.first_result() {
+            let read_size = self.read_size.unwrap_or(first.value.len());
+            if let Err(e) = self.source.read(first.address as usize, read_size)
+                && let MemoryError::ProcessAttach(_) = e {
+                    return Err(ScanError::Memory(e));
+                }
+        }
+
+        // Parallel next scan
+        self.filter_results(|scan, result| {
+            let read_size = scan.read_size.unwrap_or(result.value.len());
+            match scan.cache.read(scan.source.as_ref(), result.address as usize, read_size) {
+                Err(_) => None, // Ignore errors during parallel scan
+                Ok(val) => {
+                    // check only prefix
+                    let equal = scan.value_matches(&val);
+                    if equal != scan.inverse {
+                        let mut new_result = result.clone();
+                        new_result.value_type = scan.value_type;
+                        new_result.value = val;
+                        Some(new_result)
+                    } else {
+                        None
+                    }
+                }
+            }
+        })?;
+        self.refresh_watchlist()?;
+
+        Ok(&self.results)
+    }
+
+    /// Start from the `saved` addresses (e.g. loaded from a results file) instead of an
+    /// initial scan: each one is read again and kept if it matches the scan value, like a
+    /// [`Scan::next_scan`] over them. With `also_scan` the regions holding a saved address
+    /// are scanned for the value too, whatever their permissions, and the matches found
+    /// there are added, for values that moved within their region. Returns how many of
+    /// the results weren't among the saved addresses.
+    pub fn rescan_saved(&mut self, saved: Vec<ScanResult>, also_scan: bool) -> Result<usize, ScanError> {
+        self.check_value()?;
+        // sorted once, the regions and the addresses are matched up by binary search
+        let mut saved_addresses: Vec<u64> = saved.iter().map(|r| r.address).collect();
+        saved_addresses.sort_unstable();
+        saved_addresses.dedup();
+        let mut regions = all_regions(self.source.as_ref()).map_err(ScanError::Memory)?;
+        regions.sort_by_key(|r| r.start);
+        let region_of = |address: u64| {
+            let index = regions.partition_point(|r| r.start <= address).checked_sub(1)?;
+            Some(&regions[index]).filter(|r| address < r.end)
+        };
+        // saved files may not hold the values, the value's length is what is read. They
+        // don't hold the permissions and mapping either.
+        self.results = saved
+            .into_iter()
+            .map(|result| ScanResult {
+                value: self.value.clone(),
+                perms: region_of(result.address).map(|r| r.perms.clone()).unwrap_or_default(),
+                region: self.region_names.index_for(result.address),
+                ..result
+            })
+            .collect();
+        self.results.sort_by_key(|r| r.address);
+        self.results.dedup_by_key(|r| r.address);
+        self.next_scan()?;
+        for result in &mut self.results {
+            if result.initial_value.is_empty() {
+                result.initial_value = result.value.clone();
+            }
+        }
+        if !also_scan {
+            return Ok(0);
+        }
+
+        let kept = std::mem::take(&mut self.results);
+        let regions: Vec<MemoryRegion> = regions
+            .iter()
+            .filter(|region| {
+                let first = saved_addresses.partition_point(|&a| a < region.start);
+                saved_addresses.get(first).is_some_and(|&a| a < region.end)
+            })
+            .cloned()
+            .collect();
+        let scanned_regions = std::mem::replace(&mut self.memory_regions, regions);
+        let fresh = self.init().map(|_| ());
+        self.memory_regions = scanned_regions;
+        fresh?;
+
+        let found = std::mem::take(&mut self.results);
+        let new = found.iter().filter(|r| saved_addresses.binary_search(&r.address).is_err()).count();
+        // a saved address found by both keeps the kept result
+        let kept_addresses: HashSet<u64> = kept.iter().map(|r| r.address).collect();
+        self.results = kept;
+        self.results.extend(found.into_iter().filter(|r| !kept_addresses.contains(&r.address)));
+        self.results.sort_by_key(|r| r.address);
+        Ok(new)
+    }
+
+    /// Keep results whose current value compares to the baseline according to `mode`.
+    /// Unlike [`Scan::next_scan`] this doesn't need a scan value to be set.
+    pub fn next_compare(
+        &mut self,
+        mode: CompareMode,
+        baseline: CompareBaseline,
+    ) -> Result<&Vec<ScanResult>, ScanError> {
+        if matches!(mode, CompareMode::Increased | CompareMode::Decreased)
+            && !self.value_type.is_numeric()
+        {
+            return Err(ScanError::CompareUnsupported);
+        }
+        let value_type = self.value_type;
+        self.compare_results(baseline, |base, current| mode.matches(value_type, base, current))
+    }
+
+    /// Sample every result `samples` times and keep the ones whose value moved strictly
+    /// in `direction` (increased or decreased) from each sample to the next, e.g. timers
+    /// and cooldowns. Each result's value holds its last sample, results that can't be
+    /// read at some sample are dropped. `wait` runs before every sample after the first
+    /// and returns `false` to stop early. Returns the number of samples taken.
+    pub fn next_monotonic(
+        &mut self,
+        direction: CompareMode,
+        samples: usize,
+        mut wait: impl FnMut() -> bool,
+    ) -> Result<usize, ScanError> {
+        if !self.value_type.is_numeric() {
+            return Err(ScanError::CompareUnsupported);
+        }
+        self.compare_results(CompareBaseline::Previous, |_, _| true)?;
+        for taken in 1..samples {
+            if !wait() {
+                return Ok(taken);
+            }
+            self.next_compare(direction, CompareBaseline::Previous)?;
+        }
+        Ok(samples.max(1))
+    }
+
+    /// Read every result once more after `wait` and keep the ones still holding the scan
+    /// value, or for unknown value scans the value they were found with. Run after
+    /// [`Scan::init`] it weeds out values that only matched in passing, e.g. a scratch
+    /// buffer the target reuses. `wait` returns `false` to skip the read, the results
+    /// then stay as they are. Returns whether the read ran
+    pub fn confirm_stable(&mut self, wait: impl FnOnce() -> bool) -> Result<bool, ScanError> {
+        if !wait() {
+            return Ok(false);
+        }
+        if self.unknown {
+            self.compare_results(CompareBaseline::Previous, |base, current| base == current)?;
+        } else {
+            self.next_scan()?;
+        }
+        Ok(true)
+    }
+
+    /// Keep the results whose values satisfy `expr`, with the baseline value as `prev`
+    /// and the current one as `cur`
+    pub fn next_compare_expr(
+        &mut self,
+        expr: &CompareExpr,
+        baseline: CompareBaseline,
+    ) -> Result<&Vec<ScanResult>, ScanError> {
+        if !self.value_type.is_numeric() {
+            return Err(ScanError::CompareUnsupported);
+        }
+        let value_type = self.value_type;
+        self.compare_results(baseline, |base, current| {
+            match (value_type.numeric_value(base), value_type.numeric_value(current)) {
+                (Some(prev), Some(cur)) => expr.matches(prev, cur),
+                _ => false,
+            }
+        })
+    }
+
+    /// Re-read every result and keep the ones `keep(baseline value, current value)`
+    /// accepts
+    fn compare_results(
+        &mut self,
+        baseline: CompareBaseline,
+        keep: impl Fn(&[u8], &[u8]) -> bool + Sync,
+    ) -> Result<&Vec<ScanResult>, ScanError> {
+        self.check_maps();
+        self.cache.begin_pass();
+
+        if self.result_count() == 0 {
+            self.read_watchlist()?;
+            return Ok(&self.results);
+        }
+
+        // Early validation with single read to catch ProcessAttach errors
+        if let Some(first) = self.first_result() {
+            let read_size = self.read_size.unwrap_or(first.value.len());
+            if let Err(e) = self.source.read(first.address as usize, read_size)
+                && let MemoryError::ProcessAttach(_) = e {
+                    return Err(ScanError::Memory(e));
+                }
+        }
+
+        // Parallel compare scan
+        self.filter_results(|scan, result| {
+            let read_size = scan.read_size.unwrap_or(result.value.len());
+            match scan.cache.read(scan.source.as_ref(), result.address as usize, read_size) {
+                Err(_) => None, // Ignore errors during parallel scan
+                Ok(val) => {
+                    let base = match baseline {
+                        CompareBaseline::Initial => &result.initial_value,
+                        CompareBaseline::Previous => &result.value,
+                    };
+                    if keep(base, &val) {
+                        let mut new_result = result.clone();
+                        new_result.value_type = scan.value_type;
+                        new_result.value = val;
+                        Some(new_result)
+                    } else {
+                        None
+                    }
+                }
+            }
+        })?;
+        self.read_watchlist()?;
+        self.cache.end_pass();
+
+        Ok(&self.results)
+    }
+
+    pub fn add_to_watchlist(&mut self, result: ScanResult) {
+        let already_existing = self
+            .watchlist
+            .iter()
+            .position(|w| w.address == result.address);
+        if already_existing.is_some() {
+            return;
+        }
+
+        self.watchlist.push(result);
+    }
+
+    /// Addresses of the watchlist entries that aren't among the results, e.g. because a
+    /// next scan filtered them out since they were added
+    pub fn watch_entries_not_in_results(&self) -> HashSet<u64> {
+        let results: HashSet<u64> = match self.spilled_results() {
+            Some(spilled) => spilled.map(|r| r.address).collect(),
+            None => self.results.iter().map(|r| r.address).collect(),
+        };
+        self.watchlist
+            .iter()
+            .map(|w| w.address)
+            .filter(|address| !results.contains(address))
+            .collect()
+    }
+
+    /// Remove the watchlist entries that aren't among the results, returns how many.
+    /// Watched values are only read, never frozen, so none of them needs to be kept
+    pub fn prune_watchlist(&mut self) -> usize {
+        let dropped = self.watch_entries_not_in_results();
+        self.watchlist.retain(|w| !dropped.contains(&w.address));
+        dropped.len()
+    }
+
+    pub fn remove_from_watchlist(&mut self, address: u64) {
+        let already_existing = self.watchlist.iter().position(|w| w.address == address);
+        if already_existing.is_none() {
+            return;
+        }
+
+        self.watchlist.remove(already_existing.unwrap());
+    }
+
+    /// Write `value_str` at `address`, returns how many bytes were written and the write
+    /// with the bytes it replaced for the write journal (`None` if they couldn't be read)
+    pub fn update_value(
+        &mut self,
+        address: u64,
+        value_str: &str,
+    ) -> Result<(usize, Option<JournalEntry>), ScanError> {
+        let value = self.value_from_str(value_str)?;
+        let previous = self.source.read(address as usize, value.len()).ok();
+        self.source.write(address as usize, &value).map_err(ScanError::Memory)?;
+        self.cache.invalidate();
+        let len = value.len();
+        Ok((len, previous.map(|previous| JournalEntry { address, previous, new: value })))
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use crate::core::mem::write_memory_address;
+    #[allow(unused_imports)]
+    use crate::core::utils::FakeMemory;
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_scan_creation_success() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                );
+                assert!(scan.is_ok());
+                let scan = scan.unwrap();
+                assert_eq!(scan.results.len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_scan_init_success() {
+        use super::*;
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        let scan = Scan::new(
+            proc.0.id(),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        );
+        assert!(scan.is_ok());
+        let mut scan = scan.unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.address, address as u64);
+        assert_eq!(
+            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
+            31337_u32
+        );
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_scan_refresh_success() {
+        use super::*;
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        let scan = Scan::new(
+            proc.0.id(),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        );
+        assert!(scan.is_ok());
+        let mut scan = scan.unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.address, address as u64);
+        assert_eq!(
+            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
+            31337_u32
+        );
+
+        scan.update_value(address as u64, "333333").unwrap();
+
+        let results = scan.refresh().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.address, address as u64);
+        assert_eq!(
+            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
+            333333_u32
+        );
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_next_scan_success() {
+        use super::*;
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        let scan = Scan::new(
+            proc.0.id(),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        );
+        assert!(scan.is_ok());
+        let mut scan = scan.unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.address, address as u64);
+        assert_eq!(
+            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
+            31337_u32
+        );
+
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.address, address as u64);
+        assert_eq!(
+            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
+            31337_u32
+        );
+
+        write_memory_address(proc.0.id(), address, &333333_u32.to_le_bytes().to_vec()).unwrap();
+
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_scan_inverse_success() {
+        use super::*;
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut proc = crate::core::utils::ChildGuard(proc);
+        let stdout = proc.0.stdout.take().expect("child had no stdout");
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+
+        let hex_str = line.trim();
+        let address = u64::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+            .expect("failed to parse hex");
+
+        // Restrict the region to the known value so only its offset is checked
+        let mut scan = Scan::new(
+            proc.0.id(),
+            vec![],
+            ValueType::U32,
+            Some(address),
+            Some(address + 4),
+            None,
+        )
+        .unwrap();
+        scan.memory_regions = vec![MemoryRegion {
+            start: address,
+            end: address + 4,
+            perms: vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write],
+        }];
+        scan.set_inverse(true);
+
+        scan.set_value_from_str("31337").unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 0);
+
+        scan.set_value_from_str("1").unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].address, address);
+
+        // Next scan keeps the address while the value differs and drops it once it matches
+        scan.set_value_from_str("2").unwrap();
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 1);
+
+        scan.set_value_from_str("31337").unwrap();
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_u64_success() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::U64,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        let result = scan.set_value_from_str("12345");
+        assert!(result.is_ok());
+        assert_eq!(scan.value, 12345_u64.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_i64_success() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::I64,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        let result = scan.set_value_from_str("-54321");
+        assert!(result.is_ok());
+        assert_eq!(scan.value, (-54321_i64).to_le_bytes().to_vec());
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_u32_success() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::U32,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        let result = scan.set_value_from_str("31337");
+        assert!(result.is_ok());
+        assert_eq!(scan.value, 31337_u32.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_i32_success() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::I32,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        let result = scan.set_value_from_str("-999");
+        assert!(result.is_ok());
+        assert_eq!(scan.value, (-999_i32).to_le_bytes().to_vec());
+    }
+
+    #[test]
+    pub fn test_value_from_str_signed_hex() {
+        use super::*;
+        let mut scan =
+            Scan::with_source(Arc::new(FakeMemory::new()), vec![], ValueType::I32, None, None, None)
+                .unwrap();
+        assert_eq!(scan.value_from_str("0xFFFFFFFF"), scan.value_from_str("-1"));
+        assert_eq!(scan.value_from_str("0x80000000").unwrap(), i32::MIN.to_le_bytes());
+        assert_eq!(scan.value_from_str("0x7fffffff").unwrap(), i32::MAX.to_le_bytes());
+        assert_eq!(scan.value_from_str("0x1ffffffff"), Err(ScanError::InvalidValue));
+
+        scan.set_value_type(ValueType::I64, None).unwrap();
+        assert_eq!(scan.value_from_str("0xffffffffffffffff"), scan.value_from_str("-1"));
+        assert_eq!(scan.value_from_str("0XFFFFFFFFFFFFFFFE").unwrap(), (-2_i64).to_le_bytes());
+        assert_eq!(scan.value_from_str("0x8000000000000000").unwrap(), i64::MIN.to_le_bytes());
+
+        scan.set_value_type(ValueType::U32, None).unwrap();
+        assert_eq!(scan.value_from_str("0x10").unwrap(), 16_u32.to_le_bytes());
+    }
+
+    #[test]
+    pub fn test_hex_values_as_typed_and_reversed() {
+        use super::*;
+        let mut scan =
+            Scan::with_source(Arc::new(FakeMemory::new()), vec![], ValueType::Hex, None, None, None)
+                .unwrap();
+        assert_eq!(scan.value_from_str("0x01020304").unwrap(), [1, 2, 3, 4]);
+        assert_eq!(scan.value_from_str("deadbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+
+        scan.set_reverse_hex(true);
+        assert_eq!(scan.value_from_str("0x01020304").unwrap(), 0x01020304_u32.to_le_bytes());
+        assert_eq!(scan.value_from_str("deadbeef").unwrap(), [0xef, 0xbe, 0xad, 0xde]);
+        scan.set_value_type(ValueType::Raw(2), None).unwrap();
+        assert_eq!(scan.value_from_str("0102").unwrap(), [2, 1]);
+        // numbers are little-endian whatever the setting
+        scan.set_value_type(ValueType::U32, None).unwrap();
+        assert_eq!(scan.value_from_str("0x01020304").unwrap(), 0x01020304_u32.to_le_bytes());
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_invalid_value() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::U32,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        let result = scan.set_value_from_str("not_a_number");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ScanError::InvalidValue));
+    }
+
+    #[test]
+    pub fn test_set_value_from_str_overflow() {
+        use super::*;
+        let mut scan = Scan {
+            source: Arc::new(FakeMemory::new()),
+            value: vec![],
+            value_type: ValueType::U32,
+            results: vec![],
+            watchlist: vec![],
+            spill: None,
+            start_address: None,
+            end_address: None,
+            read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
+            memory_regions: vec![],
+            memory_permissions: vec![],
+        };
+
+        // This value is too large for u32
+        let result = scan.set_value_from_str("99999999999999");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ScanError::InvalidValue));
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_start_address_success() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+                let result = scan.set_start_address("0x1000");
+                assert!(result.is_ok());
+                assert_eq!(scan.start_address, Some(0x1000));
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_start_address_without_prefix() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+                let result = scan.set_start_address("ABCD");
+                assert!(result.is_ok());
+                assert_eq!(scan.start_address, Some(0xABCD));
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_start_address_empty_clears() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    Some(0x1000),
+                    None,
+                    None,
+                )
+                .unwrap();
+
+                assert_eq!(scan.start_address, Some(0x1000));
+                let result = scan.set_start_address("");
+                assert!(result.is_ok());
+                assert_eq!(scan.start_address, None);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_start_address_invalid_hex() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
                     ValueType::U32,
                     None,
                     None,
                     None,
-                );
-                assert!(scan.is_ok());
-                let scan = scan.unwrap();
-                assert_eq!(scan.results.len(), 0);
+                )
+                .unwrap();
+
+                let result = scan.set_start_address("0xGHIJ");
+                assert!(result.is_err());
+                assert!(matches!(result.unwrap_err(), ScanError::InvalidAddress));
             }
         }
     }
 
     #[test]
     #[ignore = "requires root"]
-    pub fn test_scan_init_success() {
+    pub fn test_set_start_address_mismatch_with_end() {
         use super::*;
-        use std::io::{BufRead, BufReader};
         use std::process::{Command, Stdio};
-
         let proc = Command::new("./target/debug/examples/simple_program")
             .stdin(Stdio::null())
-            .stdout(Stdio::piped())
+            .stdout(Stdio::null())
             .stderr(Stdio::null())
-            .spawn()
-            .unwrap();
-
-        let mut proc = crate::core::utils::ChildGuard(proc);
-        let stdout = proc.0.stdout.take().expect("child had no stdout");
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
+            .spawn();
 
-        reader.read_line(&mut line).unwrap();
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
 
-        let hex_str = line.trim();
-        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
-            .expect("failed to parse hex");
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    Some(0x1000),
+                    None,
+                )
+                .unwrap();
 
-        let scan = Scan::new(
-            proc.0.id(),
-            31337_u32.to_le_bytes().to_vec(),
-            ValueType::U32,
-            None,
-            None,
-            None,
-        );
-        assert!(scan.is_ok());
-        let mut scan = scan.unwrap();
-        let results = scan.init().unwrap();
-        assert_eq!(results.len(), 1);
-        let result = &results[0];
-        assert_eq!(result.address, address as u64);
-        assert_eq!(
-            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
-            31337_u32
-        );
+                // Try to set start address greater than end address
+                let result = scan.set_start_address("0x2000");
+                assert!(result.is_err());
+                assert!(matches!(result.unwrap_err(), ScanError::AddressMismatch));
+            }
+        }
     }
 
     #[test]
     #[ignore = "requires root"]
-    pub fn test_scan_refresh_success() {
+    pub fn test_set_end_address_success() {
         use super::*;
-        use std::io::{BufRead, BufReader};
         use std::process::{Command, Stdio};
-
         let proc = Command::new("./target/debug/examples/simple_program")
             .stdin(Stdio::null())
-            .stdout(Stdio::piped())
+            .stdout(Stdio::null())
             .stderr(Stdio::null())
-            .spawn()
-            .unwrap();
+            .spawn();
 
-        let mut proc = crate::core::utils::ChildGuard(proc);
-        let stdout = proc.0.stdout.take().expect("child had no stdout");
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
 
-        reader.read_line(&mut line).unwrap();
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
 
-        let hex_str = line.trim();
-        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
-            .expect("failed to parse hex");
+                let result = scan.set_end_address("0xFFFFFFFF");
+                assert!(result.is_ok());
+                assert_eq!(scan.end_address, Some(0xFFFFFFFF));
+            }
+        }
+    }
 
-        let scan = Scan::new(
-            proc.0.id(),
-            31337_u32.to_le_bytes().to_vec(),
-            ValueType::U32,
-            None,
-            None,
-            None,
-        );
-        assert!(scan.is_ok());
-        let mut scan = scan.unwrap();
-        let results = scan.init().unwrap();
-        assert_eq!(results.len(), 1);
-        let result = &results[0];
-        assert_eq!(result.address, address as u64);
-        assert_eq!(
-            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
-            31337_u32
-        );
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_end_address_without_prefix() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
 
-        scan.update_value(address as u64, "333333").unwrap();
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
 
-        let results = scan.refresh().unwrap();
-        assert_eq!(results.len(), 1);
-        let result = &results[0];
-        assert_eq!(result.address, address as u64);
-        assert_eq!(
-            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
-            333333_u32
-        );
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+                let result = scan.set_end_address("DEED");
+                assert!(result.is_ok());
+                assert_eq!(scan.end_address, Some(0xDEED));
+            }
+        }
     }
 
     #[test]
     #[ignore = "requires root"]
-    pub fn test_next_scan_success() {
+    pub fn test_set_end_address_empty_clears() {
         use super::*;
-        use std::io::{BufRead, BufReader};
         use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
+
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    Some(0xFFFF),
+                    None,
+                )
+                .unwrap();
+
+                assert_eq!(scan.end_address, Some(0xFFFF));
+                let result = scan.set_end_address("");
+                assert!(result.is_ok());
+                assert_eq!(scan.end_address, None);
+            }
+        }
+    }
 
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_end_address_invalid_hex() {
+        use super::*;
+        use std::process::{Command, Stdio};
         let proc = Command::new("./target/debug/examples/simple_program")
             .stdin(Stdio::null())
-            .stdout(Stdio::piped())
+            .stdout(Stdio::null())
             .stderr(Stdio::null())
-            .spawn()
-            .unwrap();
+            .spawn();
 
-        let mut proc = crate::core::utils::ChildGuard(proc);
-        let stdout = proc.0.stdout.take().expect("child had no stdout");
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
 
-        reader.read_line(&mut line).unwrap();
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
 
-        let hex_str = line.trim();
-        let address = usize::from_str_radix(hex_str.trim_start_matches("0x"), 16)
-            .expect("failed to parse hex");
+                let result = scan.set_end_address("0xXYZ");
+                assert!(result.is_err());
+                assert!(matches!(result.unwrap_err(), ScanError::InvalidAddress));
+            }
+        }
+    }
 
-        let scan = Scan::new(
-            proc.0.id(),
-            31337_u32.to_le_bytes().to_vec(),
-            ValueType::U32,
-            None,
-            None,
-            None,
-        );
-        assert!(scan.is_ok());
-        let mut scan = scan.unwrap();
-        let results = scan.init().unwrap();
-        assert_eq!(results.len(), 1);
-        let result = &results[0];
-        assert_eq!(result.address, address as u64);
-        assert_eq!(
-            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
-            31337_u32
-        );
+    #[test]
+    #[ignore = "requires root"]
+    pub fn test_set_end_address_mismatch_with_start() {
+        use super::*;
+        use std::process::{Command, Stdio};
+        let proc = Command::new("./target/debug/examples/simple_program")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
 
-        let results = scan.next_scan().unwrap();
-        assert_eq!(results.len(), 1);
-        let result = &results[0];
-        assert_eq!(result.address, address as u64);
-        assert_eq!(
-            u32::from_le_bytes(result.value.as_slice().try_into().unwrap()),
-            31337_u32
-        );
+        match proc {
+            Err(e) => assert!(false, "Error running simple program: {e}"),
+            Ok(child) => {
+                let proc = crate::core::utils::ChildGuard(child);
 
-        write_memory_address(proc.0.id(), address, &333333_u32.to_le_bytes().to_vec()).unwrap();
+                let mut scan = Scan::new(
+                    proc.0.id(),
+                    31337_u32.to_le_bytes().to_vec(),
+                    ValueType::U32,
+                    Some(0x2000),
+                    None,
+                    None,
+                )
+                .unwrap();
 
-        let results = scan.next_scan().unwrap();
-        assert_eq!(results.len(), 0);
+                // Try to set end address smaller than start address
+                let result = scan.set_end_address("0x1000");
+                assert!(result.is_err());
+                assert!(matches!(result.unwrap_err(), ScanError::AddressMismatch));
+            }
+        }
     }
 
     #[test]
-    pub fn test_set_value_from_str_u64_success() {
+    pub fn test_add_to_watchlist_success() {
         use super::*;
         let mut scan = Scan {
-            pid: 0,
+            source: Arc::new(FakeMemory::new()),
             value: vec![],
-            value_type: ValueType::U64,
+            value_type: ValueType::U32,
             results: vec![],
             watchlist: vec![],
+            spill: None,
             start_address: None,
             end_address: None,
             read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
             memory_regions: vec![],
             memory_permissions: vec![],
         };
 
-        let result = scan.set_value_from_str("12345");
-        assert!(result.is_ok());
-        assert_eq!(scan.value, 12345_u64.to_le_bytes().to_vec());
+        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
+
+        scan.add_to_watchlist(result1);
+        assert_eq!(scan.watchlist.len(), 1);
+        assert_eq!(scan.watchlist[0].address, 0x1000);
+
+        scan.add_to_watchlist(result2);
+        assert_eq!(scan.watchlist.len(), 2);
+        assert_eq!(scan.watchlist[1].address, 0x2000);
     }
 
     #[test]
-    pub fn test_set_value_from_str_i64_success() {
+    pub fn test_add_to_watchlist_duplicate_ignores() {
         use super::*;
         let mut scan = Scan {
-            pid: 0,
+            source: Arc::new(FakeMemory::new()),
             value: vec![],
-            value_type: ValueType::I64,
+            value_type: ValueType::U32,
             results: vec![],
             watchlist: vec![],
+            spill: None,
             start_address: None,
             end_address: None,
             read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
             memory_regions: vec![],
             memory_permissions: vec![],
         };
 
-        let result = scan.set_value_from_str("-54321");
-        assert!(result.is_ok());
-        assert_eq!(scan.value, (-54321_i64).to_le_bytes().to_vec());
+        let result = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+
+        scan.add_to_watchlist(result.clone());
+        assert_eq!(scan.watchlist.len(), 1);
+
+        // Try to add the same address again
+        scan.add_to_watchlist(result);
+        assert_eq!(scan.watchlist.len(), 1);
     }
 
     #[test]
-    pub fn test_set_value_from_str_u32_success() {
+    pub fn test_remove_from_watchlist_success() {
         use super::*;
         let mut scan = Scan {
-            pid: 0,
+            source: Arc::new(FakeMemory::new()),
             value: vec![],
             value_type: ValueType::U32,
             results: vec![],
             watchlist: vec![],
+            spill: None,
             start_address: None,
             end_address: None,
             read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
             memory_regions: vec![],
             memory_permissions: vec![],
         };
 
-        let result = scan.set_value_from_str("31337");
-        assert!(result.is_ok());
-        assert_eq!(scan.value, 31337_u32.to_le_bytes().to_vec());
+        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
+
+        scan.add_to_watchlist(result1.clone());
+        scan.add_to_watchlist(result2.clone());
+        assert_eq!(scan.watchlist.len(), 2);
+
+        scan.remove_from_watchlist(result1.address);
+        assert_eq!(scan.watchlist.len(), 1);
+        assert_eq!(scan.watchlist[0].address, 0x2000);
     }
 
     #[test]
-    pub fn test_set_value_from_str_i32_success() {
+    pub fn test_remove_from_watchlist_not_present() {
         use super::*;
         let mut scan = Scan {
-            pid: 0,
+            source: Arc::new(FakeMemory::new()),
             value: vec![],
-            value_type: ValueType::I32,
+            value_type: ValueType::U32,
             results: vec![],
             watchlist: vec![],
+            spill: None,
             start_address: None,
             end_address: None,
             read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
             memory_regions: vec![],
             memory_permissions: vec![],
         };
 
-        let result = scan.set_value_from_str("-999");
-        assert!(result.is_ok());
-        assert_eq!(scan.value, (-999_i32).to_le_bytes().to_vec());
+        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
+
+        scan.add_to_watchlist(result1);
+        assert_eq!(scan.watchlist.len(), 1);
+
+        // Try to remove an address that's not in the watchlist
+        scan.remove_from_watchlist(result2.address);
+        assert_eq!(scan.watchlist.len(), 1);
+        assert_eq!(scan.watchlist[0].address, 0x1000);
     }
 
     #[test]
-    pub fn test_set_value_from_str_invalid_value() {
+    pub fn test_remove_from_watchlist_empty() {
         use super::*;
         let mut scan = Scan {
-            pid: 0,
+            source: Arc::new(FakeMemory::new()),
             value: vec![],
             value_type: ValueType::U32,
             results: vec![],
             watchlist: vec![],
+            spill: None,
             start_address: None,
             end_address: None,
             read_size: None,
+            inverse: false,
+            unknown: false,
+            align_to: None,
+            stride: 1,
+            stop: None,
+            pointer_width: 8,
+            skip_uniform: false,
+            glob: None,
+            range_end: None,
+            reverse_hex: false,
+            continue_on_error: false,
+            skipped_regions: vec![],
+            stitch_regions: true,
+            cache: ReadCache::default(),
+            maps_fingerprint: None,
+            maps_changed: false,
+            progress: Arc::default(),
+            progress_total: 0,
+            pass_progress: Arc::default(),
+            observer: None,
+            region_names: Default::default(),
             memory_regions: vec![],
             memory_permissions: vec![],
         };
 
-        let result = scan.set_value_from_str("not_a_number");
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ScanError::InvalidValue));
+        let result = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+
+        // Try to remove from empty watchlist
+        scan.remove_from_watchlist(result.address);
+        assert_eq!(scan.watchlist.len(), 0);
     }
 
     #[test]
-    pub fn test_set_value_from_str_overflow() {
+    pub fn test_scan_with_fake_source() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 64];
+        data[8..12].copy_from_slice(&31337_u32.to_le_bytes());
+        data[40..44].copy_from_slice(&31337_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let memory = Arc::new(memory);
+
+        let mut scan = Scan::with_source(
+            memory.clone(),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let results = scan.init().unwrap();
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1008, 0x1028]);
+
+        scan.update_value(0x1028, "5").unwrap();
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].address, 0x1008);
+    }
+
+    #[allow(dead_code)]
+    fn oscillating_scan(memory: &std::sync::Arc<FakeMemory>) -> super::Scan {
+        use super::*;
+        let mut scan = Scan::with_source(
+            memory.clone(),
+            10_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 2);
+        scan
+    }
+
+    #[test]
+    pub fn test_unknown_scan_snapshots_aligned_positions() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x1000, vec![0_u8; 64], vec![MemoryRegionPerms::Write]);
+
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 16);
+        assert!(results.iter().all(|r| r.address % 4 == 0 && r.value.len() == 4));
+
+        // dense snapshot of every offset that holds a whole value
+        scan.set_align_to(Some(1)).unwrap();
+        assert_eq!(scan.init().unwrap().len(), 61);
+
+        // compare rounds keep the snapshotted, aligned addresses
+        scan.set_align_to(None).unwrap();
+        scan.init().unwrap();
+        memory.write(0x1008, &5_u32.to_le_bytes()).unwrap();
+        let changed = scan.next_compare(CompareMode::Changed, CompareBaseline::Initial).unwrap();
+        assert_eq!(changed.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x1008]);
+
+        let mut strings =
+            Scan::with_source(memory, vec![], ValueType::String, None, None, None).unwrap();
+        assert_eq!(strings.set_unknown(true), Err(ScanError::UnknownUnsupported));
+    }
+
+    #[test]
+    pub fn test_float_display_precision() {
+        use super::*;
+        let value = 99.99998_f32.to_le_bytes();
+        assert_eq!(ValueType::F32.get_value_string(&value).unwrap(), "99.99998");
+        assert_eq!(ValueType::F32.format_value(&value, Some(2)).unwrap(), "100.00");
+        assert_eq!(
+            ValueType::F64.format_value(&1.5_f64.to_le_bytes(), Some(3)).unwrap(),
+            "1.500"
+        );
+        // integers ignore the precision
+        assert_eq!(
+            ValueType::I32.format_value(&(-7_i32).to_le_bytes(), Some(2)).unwrap(),
+            "-7"
+        );
+    }
+
+    #[test]
+    pub fn test_pointer_scan_uses_target_width() {
+        use super::*;
+        let mut data = vec![0_u8; 32];
+        data[8..16].copy_from_slice(&0x1000_u64.to_le_bytes());
+        data[20..24].copy_from_slice(&0x2000_u32.to_le_bytes());
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x4000, data, vec![MemoryRegionPerms::Write]);
+
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::Pointer, None, None, None).unwrap();
+        scan.set_pointer_width(8).unwrap();
+        scan.set_value_from_str("0x1000").unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x4008]);
+        assert_eq!(ValueType::Pointer.format_value(&results[0].value, None).unwrap(), "0x1000");
+
+        // 32-bit targets: 4 byte values at 4 byte alignment, wider addresses are rejected
+        scan.set_pointer_width(4).unwrap();
+        scan.set_value_from_str("2000").unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x4014]);
+        assert_eq!(results[0].value.len(), 4);
+        assert_eq!(ValueType::Pointer.format_value(&results[0].value, None).unwrap(), "0x2000");
+        assert_eq!(scan.value_from_str("0x100000000"), Err(ScanError::InvalidValue));
+        assert_eq!(scan.set_pointer_width(2), Err(ScanError::InvalidValue));
+
+        memory.write(0x4014, &0x2010_u32.to_le_bytes()).unwrap();
+        let increased = scan.next_compare(CompareMode::Increased, CompareBaseline::Initial).unwrap();
+        assert_eq!(increased.len(), 1);
+    }
+
+    #[test]
+    pub fn test_skip_uniform_regions() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x10000, vec![0_u8; 0x4000], vec![MemoryRegionPerms::Write]);
+        memory.add_region(0x20000, vec![0xaa_u8; 0x4000], vec![MemoryRegionPerms::Write]);
+        // only differs between the samples, so the full check has to catch it
+        let mut mostly_zero = vec![0_u8; 0x4000];
+        mostly_zero[0x1101] = 1;
+        memory.add_region(0x30000, mostly_zero, vec![MemoryRegionPerms::Write]);
+
+        // unknown scans keep every position, unless uniform regions are skipped
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        assert_eq!(scan.init().unwrap().len(), 3 * 0x1000);
+        scan.set_skip_uniform(true);
+        let results = scan.init().unwrap();
+        assert_eq!(results.len(), 0x1000);
+        assert!(results.iter().all(|r| r.address >= 0x30000));
+
+        scan.set_unknown(false).unwrap();
+        scan.set_value_from_str("1").unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x31101]);
+
+        // the value is the repeated byte, so the zero regions are kept
+        scan.set_value_from_str("0").unwrap();
+        let results = scan.init().unwrap();
+        assert!(results.iter().any(|r| r.address < 0x20000));
+        assert!(!results.iter().any(|r| (0x20000..0x24000).contains(&r.address)));
+
+        // inverse scans can only skip the regions made of the value itself
+        scan.set_inverse(true);
+        let results = scan.init().unwrap();
+        assert!(results.iter().any(|r| (0x20000..0x24000).contains(&r.address)));
+        assert!(!results.iter().any(|r| r.address < 0x20000));
+    }
+
+    #[test]
+    pub fn test_results_carry_their_region() {
+        use super::*;
+        use crate::core::proc::Mapping;
+
+        let memory = Arc::new(FakeMemory::new());
+        for start in [0x10000, 0x20000, 0x30000] {
+            memory.add_region(start, 5_u32.to_le_bytes().to_vec(), vec![MemoryRegionPerms::Write]);
+        }
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_region_names(RegionNames::new(vec![
+            Mapping { start: 0x10000, end: 0x11000, path: String::from("/usr/bin/game") },
+            Mapping { start: 0x30000, end: 0x31000, path: String::from("[heap]") },
+        ]));
+        scan.set_value_from_str("5").unwrap();
+        scan.init().unwrap();
+
+        let names = scan.region_names();
+        let found: Vec<(u64, Option<&str>)> = scan
+            .results
+            .iter()
+            .map(|r| (r.address, r.region.and_then(|i| names.name(i))))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(0x10000, Some("game")), (0x20000, None), (0x30000, Some("[heap]"))]
+        );
+    }
+
+    #[test]
+    pub fn test_sort_results_by_each_key() {
+        use super::*;
+        use crate::core::proc::Mapping;
+
+        let names = RegionNames::new(vec![
+            Mapping { start: 0x1000, end: 0x2000, path: String::from("/usr/lib/libz.so") },
+            Mapping { start: 0x3000, end: 0x4000, path: String::from("/usr/bin/game") },
+        ]);
+        let result = |address: u64, value: i32| ScanResult {
+            region: names.index_for(address),
+            ..ScanResult::new(address, ValueType::I32, value.to_le_bytes().to_vec(), vec![])
+        };
+        // as strings "-5" < "10" < "9", as little-endian bytes 9 < 10 < -5
+        let mut results = vec![result(0x3010, 9), result(0x1010, -5), result(0x8000, 10), result(0x3000, 10)];
+        let sorted = |results: &[ScanResult]| results.iter().map(|r| r.address).collect::<Vec<_>>();
+
+        sort_results(&mut results, SortKey::Address, false, &names);
+        assert_eq!(sorted(&results), [0x1010, 0x3000, 0x3010, 0x8000]);
+        sort_results(&mut results, SortKey::Address, true, &names);
+        assert_eq!(sorted(&results), [0x8000, 0x3010, 0x3000, 0x1010]);
+
+        // the two 10s come in reverse address order, ties go by address anyway
+        sort_results(&mut results, SortKey::Value, false, &names);
+        assert_eq!(sorted(&results), [0x1010, 0x3010, 0x3000, 0x8000]);
+        sort_results(&mut results, SortKey::Value, true, &names);
+        assert_eq!(sorted(&results), [0x3000, 0x8000, 0x3010, 0x1010]);
+
+        // game before libz.so, anonymous memory last, in both directions
+        sort_results(&mut results, SortKey::Module, false, &names);
+        assert_eq!(sorted(&results), [0x3000, 0x3010, 0x1010, 0x8000]);
+        sort_results(&mut results, SortKey::Module, true, &names);
+        assert_eq!(sorted(&results), [0x1010, 0x3000, 0x3010, 0x8000]);
+
+        // NaN doesn't break the order of floats, it goes after the numbers
+        let float = |address: u64, value: f32| {
+            ScanResult::new(address, ValueType::F32, value.to_le_bytes().to_vec(), vec![])
         };
+        let mut floats = vec![float(1, 2.5), float(2, f32::NAN), float(3, -1.0), float(4, 0.5)];
+        sort_results(&mut floats, SortKey::Value, false, &names);
+        assert_eq!(sorted(&floats), [3, 4, 1, 2]);
+        sort_results(&mut floats, SortKey::Value, true, &names);
+        assert_eq!(sorted(&floats), [1, 4, 3, 2]);
+
+        assert_eq!(SortKey::parse("Value"), Some(SortKey::Value));
+        assert_eq!(SortKey::parse("size"), None);
+    }
 
-        // This value is too large for u32
-        let result = scan.set_value_from_str("99999999999999");
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ScanError::InvalidValue));
+    #[test]
+    pub fn test_retain_near_keeps_the_neighbourhood() {
+        use super::*;
+
+        let mut results: Vec<ScanResult> = [0x0, 0xff0, 0x1000, 0x1010, 0x10ff, 0x1100, 0x1101, 0x5000]
+            .into_iter()
+            .map(|address| ScanResult::new(address, ValueType::U32, vec![0; 4], vec![]))
+            .collect();
+        let addresses = |results: &[ScanResult]| results.iter().map(|r| r.address).collect::<Vec<_>>();
+
+        let mut near = results.clone();
+        retain_near(&mut near, 0x1000, 0x100);
+        assert_eq!(addresses(&near), [0xff0, 0x1000, 0x1010, 0x10ff, 0x1100]);
+        retain_near(&mut near, 0x1000, 0);
+        assert_eq!(addresses(&near), [0x1000]);
+
+        // no underflow below the reference, nothing in range leaves nothing
+        retain_near(&mut results, 0x8, 0x10);
+        assert_eq!(addresses(&results), [0x0]);
+        retain_near(&mut results, 0x9000, 0x100);
+        assert!(results.is_empty());
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_start_address_success() {
+    pub fn test_scan_results_sorted_without_duplicates_at_block_boundaries() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+        const BLOCK: usize = 0x10000;
+        let value = 0xdead_beef_u32.to_le_bytes();
+        let offsets = [0, BLOCK - 6, BLOCK - 2, BLOCK + 3, 2 * BLOCK - 3, 3 * BLOCK - 4];
+        let mut data = vec![0_u8; 3 * BLOCK];
+        for &offset in &offsets {
+            data[offset..offset + 4].copy_from_slice(&value);
+        }
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x100000, data, vec![MemoryRegionPerms::Write]);
+        memory.add_region(0x10000, [&value[..], &[0; 12]].concat(), vec![MemoryRegionPerms::Write]);
+
+        let mut expected = vec![0x10000];
+        expected.extend(offsets.iter().map(|&o| 0x100000 + o as u64));
+        let mut scan =
+            Scan::with_source(memory, value.to_vec(), ValueType::Hex, None, None, None).unwrap();
+        for _ in 0..5 {
+            let results = scan.init().unwrap();
+            assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), expected);
+        }
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        // a read size past the value makes the blocks overlap more than the match
+        scan.set_read_size(Some(16)).unwrap();
+        let results = scan.init().unwrap();
+        assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), expected);
+    }
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
+    #[test]
+    pub fn test_raw_values() {
+        use super::*;
+
+        let vector: Vec<u8> = (0..16).map(|i| 0xf0 + i).collect();
+        let text = hex::encode(&vector);
+        let memory = Arc::new(FakeMemory::new());
+        let mut data = vec![0_u8; 0x100];
+        data[0x20..0x30].copy_from_slice(&vector);
+        // misaligned copy, and a prefix that isn't a match of the full width
+        data[0x48..0x58].copy_from_slice(&vector);
+        data[0x80..0x88].copy_from_slice(&vector[..8]);
+        memory.add_region(0x10000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+
+        let raw = ValueType::Raw(16);
+        assert_eq!((raw.get_size(), raw.alignment()), (16, 16));
+        assert!(!raw.is_numeric());
+        let mut scan = Scan::with_source(memory.clone(), vec![], raw, None, None, None).unwrap();
+        assert_eq!(scan.value_from_str(&format!("0x{text}")).unwrap(), vector);
+        // the value must be exactly as wide as the type
+        assert_eq!(scan.value_from_str("deadbeef"), Err(ScanError::InvalidValue));
+        assert_eq!(scan.value_from_str("xyz"), Err(ScanError::InvalidValue));
+
+        // read
+        let bytes = memory.read(0x10020, 16).unwrap();
+        assert_eq!(raw.get_value_string(&bytes).unwrap(), text);
+        assert_eq!(
+            raw.get_value_string(&bytes[..8]),
+            Err(ScanError::InsufficientBytes { needed: 16, got: 8 })
+        );
+
+        // scan, every offset matches unless aligned to the width
+        scan.set_value_from_str(&text).unwrap();
+        let results = scan.init().unwrap();
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, [0x10020, 0x10048]);
+        assert_eq!(results.iter().map(|r| r.aligned).collect::<Vec<_>>(), [true, false]);
+        scan.set_align_to(Some(raw.alignment())).unwrap();
+        assert_eq!(scan.init().unwrap().iter().map(|r| r.address).collect::<Vec<_>>(), [0x10020]);
+
+        // write
+        let zeros = "00".repeat(16);
+        let (len, entry) = scan.update_value(0x10020, &zeros).unwrap();
+        let entry = entry.unwrap();
+        assert_eq!(len, entry.new.len());
+        assert_eq!(entry.previous, vector);
+        assert_eq!(memory.read(0x10020, 16).unwrap(), vec![0; 16]);
+        assert!(scan.update_value(0x10020, "00").is_err());
+    }
+
+    #[test]
+    pub fn test_bool_values() {
+        use super::*;
+        let scan =
+            Scan::with_source(Arc::new(FakeMemory::new()), vec![], ValueType::Bool, None, None, None)
                 .unwrap();
+        for (text, byte) in [("true", 1), ("TRUE", 1), ("1", 1), ("false", 0), ("False", 0), ("0", 0)] {
+            assert_eq!(scan.value_from_str(text).unwrap(), vec![byte], "{text}");
+        }
+        for text in ["yes", "2", "", "truee"] {
+            assert_eq!(scan.value_from_str(text), Err(ScanError::InvalidValue), "{text}");
+        }
+
+        assert_eq!(ValueType::Bool.get_size(), 1);
+        assert_eq!(ValueType::Bool.get_value_string(&[0]).unwrap(), "false");
+        assert_eq!(ValueType::Bool.get_value_string(&[1]).unwrap(), "true");
+        // any non-zero byte is true
+        assert_eq!(ValueType::Bool.get_value_string(&[0x80]).unwrap(), "true");
+        assert!(ValueType::Bool.get_value_string(&[1, 0]).is_err());
+        assert_eq!(ValueType::Bool.compare_values(&[0], &[7]), Some(Ordering::Less));
+        assert_eq!(ValueType::Bool.compare_values(&[2], &[7]), Some(Ordering::Equal));
+        assert_eq!(ValueType::Bool.describe_change(&[0], &[1], None), "false -> true (Δ+1)");
+    }
+
+    #[test]
+    pub fn test_short_values_are_insufficient_bytes() {
+        use super::*;
+
+        let bytes = [1_u8, 2, 3, 4, 5, 6, 7, 8, 9];
+        for value_type in ValueType::all() {
+            let size = match value_type {
+                ValueType::Pointer => 8,
+                value_type => value_type.get_size() as usize,
+            };
+            if size == 0 {
+                continue;
+            }
+            for len in 1..size {
+                // 4 bytes are a whole 32-bit pointer
+                if value_type == ValueType::Pointer && len == 4 {
+                    continue;
+                }
+                let needed = if value_type == ValueType::Pointer && len < 4 { 4 } else { size };
+                assert_eq!(
+                    value_type.get_value_string(&bytes[..len]),
+                    Err(ScanError::InsufficientBytes { needed, got: len }),
+                    "{} from {len} byte(s)",
+                    value_type.name()
+                );
+            }
+            assert!(value_type.get_value_string(&bytes[..size]).is_ok(), "{}", value_type.name());
+            assert_eq!(value_type.get_value_string(&bytes[..size + 1]), Err(ScanError::TypeMismatch));
+        }
+
+        let short = ScanResult::new(0x1000, ValueType::U32, vec![1, 2], vec![]);
+        assert_eq!(
+            short.get_string().unwrap_err().to_string(),
+            "Value needs 4 byte(s), only 2 were read"
+        );
+        assert_eq!(ValueType::U32.describe_change(&[1, 2], &[1, 3], None), "1 byte(s) changed from offset 1");
+        assert_eq!(ValueType::U32.compare_values(&[1, 2], &[1, 3]), None);
+    }
+
+    #[test]
+    pub fn test_quick_scan_matches_naive_scan() {
+        use super::*;
+        use crate::core::utils::FakeMemory;
+
+        // pseudo-random bytes from a small alphabet, so every value occurs often and the
+        // first byte of some values is common enough to switch to memmem mid-block
+        let mut state: u64 = 0x9e3779b97f4a7c15;
+        let data: Vec<u8> = (0..0x30000)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if i % 3 == 0 { 0 } else { (state % 24) as u8 }
+            })
+            .collect();
+        let naive = |value: &[u8]| -> Vec<u64> {
+            (0..=data.len() - value.len())
+                .filter(|&i| data[i..i + value.len()] == *value)
+                .map(|i| 0x10000 + i as u64)
+                .collect()
+        };
 
-                let result = scan.set_start_address("0x1000");
-                assert!(result.is_ok());
-                assert_eq!(scan.start_address, Some(0x1000));
-            }
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x10000, data.clone(), vec![MemoryRegionPerms::Write]);
+        let values: Vec<Vec<u8>> = vec![
+            vec![5, 0],
+            vec![0, 0, 0],
+            vec![23, 1],
+            vec![7],
+            // longer than the quick scan length
+            data[0x200..0x220].to_vec(),
+            // crosses the first block boundary
+            data[0xfffe..0x10002].to_vec(),
+            vec![0xaa, 0xbb],
+        ];
+        for value in values {
+            let mut scan = Scan::with_source(memory.clone(), value.clone(), ValueType::Hex, None, None, None).unwrap();
+            let found: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+            assert_eq!(found, naive(&value), "{}", hex::encode(&value));
         }
+
+        assert_eq!(find_exact(&[1, 2, 1, 2, 1], &[1, 2, 1]), vec![0, 2]);
+        assert!(find_exact(&[1, 2], &[1, 2, 3]).is_empty());
+        assert_eq!(find_exact_scalar(&[1, 2, 1, 2, 1], &[1, 2, 1]), vec![0, 2]);
+        assert!(find_exact_scalar(&[1, 2], &[1, 2, 3]).is_empty());
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_start_address_without_prefix() {
+    pub fn test_glob_string_scan() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+        use crate::core::utils::FakeMemory;
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(
+            0x1000,
+            b"Player1 Player2 PlayerX Player? player3 Level9".to_vec(),
+            vec![MemoryRegionPerms::Write],
+        );
+        let scan_for = |pattern: &str, glob: bool| -> Vec<(u64, String)> {
+            let mut scan =
+                Scan::with_source(memory.clone(), vec![], ValueType::String, None, None, None).unwrap();
+            scan.set_value_from_str(pattern).unwrap();
+            scan.set_glob(glob).unwrap();
+            let results = scan.init().unwrap();
+            results.iter().map(|r| (r.address, r.get_string().unwrap())).collect()
+        };
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
-                .unwrap();
+        let all = scan_for("Player?", true);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[2], (0x1010, String::from("PlayerX")));
+        assert_eq!(scan_for("Player?", false), vec![(0x1018, String::from("Player?"))]);
+        assert_eq!(scan_for("Player\\?", true), vec![(0x1018, String::from("Player?"))]);
+        let digits: Vec<u64> = scan_for("Player[0-9]", true).iter().map(|r| r.0).collect();
+        assert_eq!(digits, vec![0x1000, 0x1008]);
+        let others: Vec<u64> = scan_for("Player[!0-9]", true).iter().map(|r| r.0).collect();
+        assert_eq!(others, vec![0x1010, 0x1018]);
+        // a leading class, and one with a literal ']'
+        assert_eq!(scan_for("[Pp]layer3", true), vec![(0x1020, String::from("player3"))]);
+        assert_eq!(scan_for("Level[]9]", true).len(), 1);
+
+        // next scans match the pattern as well
+        let mut scan = Scan::with_source(memory.clone(), vec![], ValueType::String, None, None, None).unwrap();
+        scan.set_value_from_str("Player[0-9]").unwrap();
+        scan.set_glob(true).unwrap();
+        scan.init().unwrap();
+        memory.write(0x1006, b"z").unwrap();
+        let results = scan.next_scan().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].get_string().unwrap(), "Player2");
 
-                let result = scan.set_start_address("ABCD");
-                assert!(result.is_ok());
-                assert_eq!(scan.start_address, Some(0xABCD));
-            }
+        for bad in ["Player[0-9", "Player*", "Player\\", "[9-0]"] {
+            assert!(matches!(Glob::parse(bad.as_bytes()), Err(ScanError::InvalidPattern(_))), "{bad}");
         }
+        let mut numeric = Scan::with_source(memory, vec![], ValueType::U32, None, None, None).unwrap();
+        numeric.set_value_from_str("1").unwrap();
+        assert_eq!(numeric.set_glob(true), Err(ScanError::GlobUnsupported));
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_start_address_empty_clears() {
+    pub fn test_pattern_across_adjacent_regions() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        use crate::core::utils::FakeMemory;
+
+        // a signature split by the boundary of two contiguous regions with different
+        // perms, one inside the second region, and one split by a gap
+        let memory = Arc::new(FakeMemory::spanning());
+        let signature = [0x48, 0x8b, 0x05, 0x99];
+        let mut code = vec![0x90; 0x100];
+        code[0xfe..].copy_from_slice(&signature[..2]);
+        let mut data = vec![0; 0x100];
+        data[..2].copy_from_slice(&signature[2..]);
+        data[0x40..0x44].copy_from_slice(&signature);
+        let mut far = vec![0; 0x100];
+        far[0xfd..].copy_from_slice(&signature[..3]);
+        memory.add_region(0x1000, code, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Execute]);
+        memory.add_region(0x1100, data, vec![MemoryRegionPerms::Read]);
+        memory.add_region(0x2000, far, vec![MemoryRegionPerms::Read]);
+        memory.add_region(0x2200, signature[3..].to_vec(), vec![MemoryRegionPerms::Read]);
+
+        let scan_with = |stitch: bool| -> Vec<ScanResult> {
+            let perms = Some(vec![MemoryRegionPerms::Read]);
+            let mut scan =
+                Scan::with_source(memory.clone(), signature.to_vec(), ValueType::Hex, None, None, perms)
+                    .unwrap();
+            scan.set_stitch_regions(stitch);
+            scan.set_read_size(Some(6)).unwrap();
+            scan.init().unwrap().clone()
+        };
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    Some(0x1000),
-                    None,
-                    None,
-                )
-                .unwrap();
+        let results = scan_with(true);
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x10fe, 0x1140]);
+        assert_eq!(results[0].value, [0x48, 0x8b, 0x05, 0x99, 0, 0]);
+        assert!(results[0].perms.contains(&MemoryRegionPerms::Execute));
+        let addresses: Vec<u64> = scan_with(false).iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1140]);
+    }
 
-                assert_eq!(scan.start_address, Some(0x1000));
-                let result = scan.set_start_address("");
-                assert!(result.is_ok());
-                assert_eq!(scan.start_address, None);
+    #[test]
+    pub fn test_spilled_scan_matches_in_memory_scan() {
+        use super::*;
+        use crate::core::utils::FakeMemory;
+
+        let memory = Arc::new(FakeMemory::spanning());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        let mut data = vec![0_u8; 0x100];
+        data[0xfe..].copy_from_slice(&[0x12, 0x34]);
+        memory.add_region(0x1000, data, rw.clone());
+        memory.add_region(0x1100, [0x56, 0x78].repeat(0x40), rw.clone());
+        memory.add_region(0x3000, 7_u32.to_le_bytes().repeat(0x10), rw);
+
+        let path = std::env::temp_dir().join(format!("cheat-engine-rs-scan-spill-{}", std::process::id()));
+        let scan_with = |spill: bool, value: &[u8], value_type: ValueType| {
+            let mut scan =
+                Scan::with_source(memory.clone(), value.to_vec(), value_type, None, None, None).unwrap();
+            if value.is_empty() {
+                scan.set_unknown(true).unwrap();
+            }
+            if spill {
+                scan.set_spill_file(&path).unwrap();
+            }
+            scan.init().unwrap();
+            scan
+        };
+        let addresses = |scan: &Scan| -> Vec<(u64, Vec<u8>)> {
+            match scan.spilled_results() {
+                Some(results) => results.map(|r| (r.address, r.value)).collect(),
+                None => scan.results.iter().map(|r| (r.address, r.value.clone())).collect(),
             }
+        };
+
+        // a pattern across the seam of the first two regions is found in both
+        let pattern = [0x12, 0x34, 0x56, 0x78];
+        let spilled = scan_with(true, &pattern, ValueType::Hex);
+        assert!(spilled.results.is_empty());
+        assert_eq!(addresses(&spilled), addresses(&scan_with(false, &pattern, ValueType::Hex)));
+        assert_eq!(spilled.result_count(), 1);
+        drop(spilled);
+
+        let mut in_memory = scan_with(false, &[], ValueType::U32);
+        let mut spilled = scan_with(true, &[], ValueType::U32);
+        assert_eq!(spilled.result_count(), 0x70);
+        assert_eq!(addresses(&spilled), addresses(&in_memory));
+        memory.write(0x1010, &1_u32.to_le_bytes()).unwrap();
+        memory.write(0x3008, &9_u32.to_le_bytes()).unwrap();
+        for scan in [&mut in_memory, &mut spilled] {
+            scan.next_compare(CompareMode::Increased, CompareBaseline::Previous).unwrap();
         }
+        assert_eq!(addresses(&spilled), [(0x1010, vec![1, 0, 0, 0]), (0x3008, vec![9, 0, 0, 0])]);
+        assert_eq!(addresses(&spilled), addresses(&in_memory));
+        drop(spilled);
+        let _ = std::fs::remove_file(&path);
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_start_address_invalid_hex() {
+    pub fn test_exact_scan_reports_overlapping_matches() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        use crate::core::utils::FakeMemory;
+
+        let memory = Arc::new(FakeMemory::new());
+        let mut data = vec![0xff_u8; 0x20];
+        data[2..7].copy_from_slice(&[1, 2, 1, 2, 1]);
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Write]);
+
+        let mut scan = Scan::with_source(memory.clone(), vec![1, 2, 1], ValueType::Hex, None, None, None).unwrap();
+        let found: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(found, vec![0x1002, 0x1004]);
+
+        // the aligned match overlaps an unaligned one before it
+        let mut scan = Scan::with_source(memory, vec![1, 2, 1], ValueType::Hex, None, None, None).unwrap();
+        scan.set_align_to(Some(4)).unwrap();
+        let found: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(found, vec![0x1004]);
+    }
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
-                .unwrap();
+    #[test]
+    pub fn test_custom_codec_scan() {
+        use super::*;
+        use crate::core::utils::FakeMemory;
+
+        assert_eq!(custom_value_type("test-xor"), None);
+        register_codec("test-xor", Box::new(XorCodec { key: 0xff00ff00 }));
+        let value_type = custom_value_type("test-xor").unwrap();
+        assert_eq!(value_type, ValueType::Custom("test-xor"));
+        assert_eq!(value_type.get_size(), 4);
+        assert!(!value_type.is_numeric());
+
+        let memory = Arc::new(FakeMemory::new());
+        let mut data = vec![0_u8; 16];
+        data[4..8].copy_from_slice(&(100_u32 ^ 0xff00ff00).to_le_bytes());
+        // the plain value isn't a match
+        data[8..12].copy_from_slice(&100_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Write]);
+
+        let mut scan = Scan::with_source(memory.clone(), vec![], value_type, None, None, None).unwrap();
+        scan.set_value_from_str("100").unwrap();
+        let results = scan.init().unwrap().clone();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].address, 0x1004);
+        assert_eq!(value_type.get_value_string(&results[0].value).unwrap(), "100");
+        assert_eq!(scan.value_from_str("-1"), Err(ScanError::InvalidValue));
+
+        memory.write(0x1004, &(250_u32 ^ 0xff00ff00).to_le_bytes()).unwrap();
+        let old = results[0].value.clone();
+        let new = memory.read(0x1004, 4).unwrap();
+        assert_eq!(value_type.describe_change(&old, &new, None), "100 -> 250");
+        assert!(matches!(
+            scan.next_compare(CompareMode::Increased, CompareBaseline::Previous),
+            Err(ScanError::CompareUnsupported)
+        ));
+
+        // registering again replaces the codec
+        register_codec("test-xor", Box::new(XorCodec { key: 0 }));
+        assert_eq!(value_type.get_value_string(&100_u32.to_le_bytes()).unwrap(), "100");
+    }
 
-                let result = scan.set_start_address("0xGHIJ");
-                assert!(result.is_err());
-                assert!(matches!(result.unwrap_err(), ScanError::InvalidAddress));
+    #[test]
+    pub fn test_scan_executor_groups_results_per_scan() {
+        use super::*;
+        use crate::core::utils::FakeMemory;
+
+        let mut scans = vec![];
+        for copies in [1_usize, 3] {
+            let memory = FakeMemory::new();
+            for i in 0..copies {
+                let mut data = vec![0_u8; 16];
+                data[8..12].copy_from_slice(&42_u32.to_le_bytes());
+                memory.add_region(0x1000 * (i as u64 + 1), data, vec![MemoryRegionPerms::Write]);
             }
+            let scan = Scan::with_source(
+                Arc::new(memory),
+                42_u32.to_le_bytes().to_vec(),
+                ValueType::U32,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            scans.push(scan);
         }
+        let empty = Scan::with_source(
+            Arc::new(FakeMemory::new()),
+            vec![],
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        scans.push(empty);
+
+        let executor = ScanExecutor::with_threads(2).unwrap();
+        let outcomes = executor.init(&mut scans);
+        assert!(outcomes[0].is_ok() && outcomes[1].is_ok());
+        assert!(matches!(outcomes[2], Err(ScanError::EmptyValue)));
+
+        assert_eq!(scans[0].results.len(), 1);
+        let addresses: Vec<u64> = scans[1].results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1008, 0x2008, 0x3008]);
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_start_address_mismatch_with_end() {
+    pub fn test_describe_change() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        assert_eq!(
+            ValueType::U32.describe_change(&10_u32.to_le_bytes(), &15_u32.to_le_bytes(), None),
+            "10 -> 15 (Δ+5)"
+        );
+        assert_eq!(
+            ValueType::U64.describe_change(&u64::MAX.to_le_bytes(), &0_u64.to_le_bytes(), None),
+            format!("{} -> 0 (Δ-{})", u64::MAX, u64::MAX)
+        );
+        assert_eq!(
+            ValueType::F32.describe_change(&2.0_f32.to_le_bytes(), &3.0_f32.to_le_bytes(), Some(1)),
+            "2.0 -> 3.0 (Δ+1.0, +50.00%)"
+        );
+        // no relative change from zero
+        assert_eq!(
+            ValueType::F64.describe_change(&0.0_f64.to_le_bytes(), &1.5_f64.to_le_bytes(), None),
+            "0 -> 1.5 (Δ+1.5)"
+        );
+        assert_eq!(
+            ValueType::Hex.describe_change(&[1, 2, 3, 4], &[1, 9, 3, 8], None),
+            "2 byte(s) changed from offset 1"
+        );
+        assert_eq!(
+            ValueType::String.describe_change(b"ab", b"abc", None),
+            "length changed to 3 byte(s)"
+        );
+    }
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    Some(0x1000),
-                    None,
-                )
-                .unwrap();
+    #[test]
+    pub fn test_float_compare_values() {
+        use super::*;
+        let (a, b) = (1.25_f32.to_le_bytes(), 2.5_f32.to_le_bytes());
+        assert_eq!(ValueType::F32.compare_values(&a, &b), Some(Ordering::Less));
+        assert!(!CompareMode::Increased.matches(ValueType::F32, &b, &f32::NAN.to_le_bytes()));
+        assert!(CompareMode::Decreased.matches(
+            ValueType::F64,
+            &8.0_f64.to_le_bytes(),
+            &(-1.0_f64).to_le_bytes()
+        ));
+    }
 
-                // Try to set start address greater than end address
-                let result = scan.set_start_address("0x2000");
-                assert!(result.is_err());
-                assert!(matches!(result.unwrap_err(), ScanError::AddressMismatch));
-            }
-        }
+    #[test]
+    pub fn test_set_value_from_address() {
+        use super::*;
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 32];
+        data[4..8].copy_from_slice(&777_u32.to_le_bytes());
+        data[20..24].copy_from_slice(&777_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+
+        let mut scan =
+            Scan::with_source(Arc::new(memory), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_value_from_address(0x1004, 4).unwrap();
+        assert_eq!(scan.value, 777_u32.to_le_bytes());
+
+        let addresses: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1004, 0x1014]);
+
+        assert!(matches!(
+            scan.set_value_from_address(0x5000, 4),
+            Err(ScanError::Memory(_))
+        ));
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_end_address_success() {
+    pub fn test_scan_align_to_checks_only_aligned_offsets() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 64];
+        for offset in [4, 16, 36, 48] {
+            data[offset..offset + 4].copy_from_slice(&31337_u32.to_le_bytes());
+        }
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 4);
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
-                .unwrap();
+        scan.set_align_to(Some(16)).unwrap();
+        let addresses: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1010, 0x1030]);
 
-                let result = scan.set_end_address("0xFFFFFFFF");
-                assert!(result.is_ok());
-                assert_eq!(scan.end_address, Some(0xFFFFFFFF));
-            }
-        }
+        // inverse scans step by the alignment instead of the type size
+        scan.set_inverse(true);
+        let addresses: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1000, 0x1020]);
+
+        assert_eq!(scan.set_align_to(Some(0)), Err(ScanError::InvalidAlignment));
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_end_address_without_prefix() {
+    pub fn test_value_range_scan_is_inclusive() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        let memory = Arc::new(FakeMemory::new());
+        let data: Vec<u8> = [99_i32, 100, 105, 110, 111, -3, -2, 0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let scan_for = |range: &str| -> Vec<i32> {
+            let mut scan =
+                Scan::with_source(memory.clone(), vec![], ValueType::I32, None, None, None).unwrap();
+            scan.set_value_from_str(range).unwrap();
+            let results = scan.init().unwrap();
+            results.iter().map(|r| i32::from_le_bytes(r.value[..4].try_into().unwrap())).collect()
+        };
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
-                .unwrap();
+        assert_eq!(scan_for("100..110"), [100, 105, 110]);
+        assert_eq!(scan_for("105..105"), [105]);
+        // compared as signed numbers, not bytes
+        assert_eq!(scan_for("-2 .. 100"), [99, 100, -2, 0]);
 
-                let result = scan.set_end_address("DEED");
-                assert!(result.is_ok());
-                assert_eq!(scan.end_address, Some(0xDEED));
-            }
-        }
+        // rescans keep the results still in range
+        let mut scan = Scan::with_source(memory.clone(), vec![], ValueType::I32, None, None, None).unwrap();
+        scan.set_value_from_str("100..110").unwrap();
+        scan.init().unwrap();
+        memory.write(0x1004, &200_i32.to_le_bytes()).unwrap();
+        let kept: Vec<u64> = scan.next_scan().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(kept, [0x1008, 0x100c]);
+
+        assert_eq!(scan.set_value_from_str("110..100"), Err(ScanError::InvalidRange));
+        let mut hex = Scan::with_source(memory.clone(), vec![], ValueType::Hex, None, None, None).unwrap();
+        assert_eq!(hex.set_value_from_str("00..ff"), Err(ScanError::RangeUnsupported));
+        // strings may hold `..`
+        let mut string =
+            Scan::with_source(memory.clone(), vec![], ValueType::String, None, None, None).unwrap();
+        string.set_value_from_str("a..b").unwrap();
+        assert_eq!(string.value, b"a..b");
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_end_address_empty_clears() {
+    pub fn test_continue_on_error_skips_unreadable_regions() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+        let memory = Arc::new(FakeMemory::new());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x1000, 42_u32.to_le_bytes().repeat(4), rw.clone());
+        memory.add_unreadable_region(0x2000, 0x1000, rw.clone());
+        memory.add_region(0x4000, 42_u32.to_le_bytes().to_vec(), rw.clone());
+        memory.add_unreadable_region(0x5000, 0x20000, rw);
+
+        let mut scan =
+            Scan::with_source(memory.clone(), 42_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap();
+        scan.set_continue_on_error(true);
+        let found: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(found, [0x1000, 0x1004, 0x1008, 0x100c, 0x4000]);
+        let skipped: Vec<u64> = scan.skipped_regions().iter().map(|s| s.region.start).collect();
+        assert_eq!(skipped, [0x2000, 0x5000]);
+        assert_eq!(
+            skipped_summary(scan.skipped_regions()).unwrap(),
+            "2 region(s) skipped: 2 x Could not read memory: OS Error (5)"
+        );
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        // without it the unreadable regions are passed over without a record
+        scan.set_continue_on_error(false);
+        assert_eq!(scan.init().unwrap().len(), 5);
+        assert!(scan.skipped_regions().is_empty());
+        assert_eq!(skipped_summary(&[]), None);
+    }
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    Some(0xFFFF),
-                    None,
-                )
+    #[test]
+    pub fn test_rescan_saved_with_and_without_also_scan() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        // the value is still at 0x1000, moved from 0x1020 to 0x1030, and is in a region
+        // none of the saved addresses is in at 0x5000
+        let mut data = vec![0_u8; 0x40];
+        data[0..4].copy_from_slice(&7_u32.to_le_bytes());
+        data[0x30..0x34].copy_from_slice(&7_u32.to_le_bytes());
+        memory.add_region(0x1000, data, rw.clone());
+        memory.add_region(0x5000, 7_u32.to_le_bytes().to_vec(), rw);
+        let saved =
+            || [0x1020, 0x1000].map(|address| ScanResult::new(address, ValueType::U32, vec![], vec![])).to_vec();
+        let addresses = |scan: &Scan| scan.results.iter().map(|r| r.address).collect::<Vec<_>>();
+
+        let mut scan =
+            Scan::with_source(memory.clone(), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
                 .unwrap();
+        assert_eq!(scan.rescan_saved(saved(), false).unwrap(), 0);
+        assert_eq!(addresses(&scan), [0x1000]);
+        assert_eq!(scan.results[0].value, 7_u32.to_le_bytes());
+
+        assert_eq!(scan.rescan_saved(saved(), true).unwrap(), 1);
+        assert_eq!(addresses(&scan), [0x1000, 0x1030]);
+        // the regions of the scan are left as they were
+        assert_eq!(scan.memory_regions().len(), 2);
+    }
 
-                assert_eq!(scan.end_address, Some(0xFFFF));
-                let result = scan.set_end_address("");
-                assert!(result.is_ok());
-                assert_eq!(scan.end_address, None);
-            }
+    #[test]
+    pub fn test_interrupted_scan_resumes_from_its_checkpoint() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        for start in [0x1000, 0x3000, 0x5000] {
+            let mut data = vec![0_u8; 0x100];
+            data[0x10..0x14].copy_from_slice(&7_u32.to_le_bytes());
+            data[0x80..0x84].copy_from_slice(&7_u32.to_le_bytes());
+            memory.add_region(start, data, rw.clone());
         }
+        // a region of several checkpoint batches, with a match in the first and the last
+        let big = 2 * CHECKPOINT_BLOCKS * 0x10000;
+        let mut data = vec![0_u8; big];
+        data[0x20..0x24].copy_from_slice(&7_u32.to_le_bytes());
+        data[big - 4..].copy_from_slice(&7_u32.to_le_bytes());
+        memory.add_region(0x100_0000, data, rw.clone());
+        let new_scan = || {
+            Scan::with_source(memory.clone(), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap()
+        };
+        let addresses = |results: &[ScanResult]| results.iter().map(|r| r.address).collect::<Vec<_>>();
+        let full = addresses(new_scan().init().unwrap());
+        assert_eq!(full.len(), 8);
+
+        // stopped after the first region's checkpoint, the second region doesn't count
+        let stop: &'static AtomicBool = Box::leak(Box::new(AtomicBool::new(false)));
+        let mut scan = new_scan();
+        scan.set_stop_flag(stop);
+        let mut checkpoints = vec![];
+        let complete = scan
+            .init_resumable(ScanPosition::default(), vec![], std::time::Duration::ZERO, |position, results| {
+                checkpoints.push((position, results.to_vec()));
+                stop.store(true, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            })
+            .unwrap();
+        assert!(!complete);
+        let positions: Vec<ScanPosition> = checkpoints.iter().map(|(position, _)| *position).collect();
+        assert_eq!(positions, [ScanPosition { region: 1, offset: 0 }; 2]);
+        // each checkpoint only gets the new results
+        assert_eq!(addresses(&checkpoints[0].1), [0x1010, 0x1080]);
+        assert!(checkpoints[1].1.is_empty());
+
+        // stopped inside the big region, after its first batch
+        stop.store(false, std::sync::atomic::Ordering::SeqCst);
+        let mut scan = new_scan();
+        scan.set_stop_flag(stop);
+        let (mut position, mut found) = (ScanPosition::default(), vec![]);
+        scan.init_resumable(ScanPosition::default(), vec![], std::time::Duration::ZERO, |at, results| {
+            position = at;
+            found.extend_from_slice(results);
+            if at.region == 3 && at.offset > 0 {
+                stop.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(position.region, 3);
+        assert!(position.offset > 0 && position.offset < big as u64);
+        assert_eq!(found.len(), 7);
+
+        let mut resumed = new_scan();
+        let mut written = 0;
+        let complete = resumed
+            .init_resumable(position, found, std::time::Duration::ZERO, |_, _| {
+                written += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(complete);
+        assert_eq!(addresses(&resumed.results), full);
+        assert!(resumed.results.iter().all(|r| r.perms == rw));
+        // blocks overlap, so the region takes a third batch for its last block
+        assert_eq!(written, 2);
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_end_address_invalid_hex() {
+    pub fn test_changed_maps_invalidate_the_cache() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+        let memory = Arc::new(FakeMemory::new());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x1000, [7_u32.to_le_bytes(), 9_u32.to_le_bytes()].concat(), rw.clone());
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    None,
-                    None,
-                    None,
-                )
+        let mut scan =
+            Scan::with_source(memory.clone(), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
                 .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 1);
+        assert_eq!(scan.next_scan().unwrap().len(), 1);
+        assert!(!scan.maps_changed());
+
+        // the same address remapped with other contents
+        memory.remove_region(0x1000);
+        memory.add_region(0x1000, vec![0; 0x10], rw);
+        assert!(scan.next_scan().unwrap().is_empty());
+        assert!(scan.maps_changed());
+        assert_eq!(scan.next_scan().unwrap().len(), 0);
+        assert!(!scan.maps_changed());
+
+        // compare passes check the map too
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x2000, 9_u32.to_le_bytes().to_vec(), rw);
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        let found = scan.init().unwrap().len();
+        scan.next_compare(CompareMode::Unchanged, CompareBaseline::Previous).unwrap();
+        assert!(!scan.maps_changed());
+        memory.remove_region(0x2000);
+        assert!(scan.next_compare(CompareMode::Unchanged, CompareBaseline::Previous).unwrap().len() < found);
+        assert!(scan.maps_changed());
+    }
 
-                let result = scan.set_end_address("0xXYZ");
-                assert!(result.is_err());
-                assert!(matches!(result.unwrap_err(), ScanError::InvalidAddress));
-            }
-        }
+    #[test]
+    pub fn test_scan_stride_reports_every_kth_aligned_match() {
+        use super::*;
+        let memory = FakeMemory::new();
+        // the value at every aligned position, and once unaligned
+        let mut data: Vec<u8> = std::iter::repeat_n(7_u32.to_le_bytes(), 32).flatten().collect();
+        data.extend([0, 7, 0, 0, 0, 0, 0, 0]);
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+
+        let mut scan =
+            Scan::with_source(Arc::new(memory), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 33);
+
+        scan.set_stride(3).unwrap();
+        let addresses: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        // the grid is in addresses, so scans of different ranges check the same positions
+        let every_third: Vec<u64> = (0x1000..0x1080).filter(|a| a % 12 == 0).collect();
+        assert_eq!(addresses, every_third);
+
+        // the stride multiplies --align-to, and applies to unknown value snapshots too
+        scan.set_align_to(Some(8)).unwrap();
+        let addresses: Vec<u64> = scan.init().unwrap().iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1008, 0x1020, 0x1038, 0x1050, 0x1068]);
+        scan.set_align_to(None).unwrap();
+        scan.set_unknown(true).unwrap();
+        assert_eq!(scan.init().unwrap().len(), (0x1000..=0x1084).filter(|a| a % 12 == 0).count());
+
+        assert_eq!(scan.set_stride(0), Err(ScanError::InvalidStride));
+        // a stride that wraps the step to 0 is refused instead of dividing by it
+        assert_eq!(scan.set_stride(usize::MAX / 2 + 1), Err(ScanError::InvalidStride));
+        scan.set_stride(usize::MAX / 8).unwrap();
+        assert_eq!(scan.set_align_to(Some(16)), Err(ScanError::InvalidStride));
     }
 
     #[test]
-    #[ignore = "requires root"]
-    pub fn test_set_end_address_mismatch_with_start() {
+    pub fn test_progress_counts_every_region_byte() {
         use super::*;
-        use std::process::{Command, Stdio};
-        let proc = Command::new("./target/debug/examples/simple_program")
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
+        let memory = FakeMemory::new();
+        let rw = || vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x10000, (0..0x25003).map(|i| i as u8).collect(), rw());
+        memory.add_region(0x100000, vec![0; 0x1000], rw());
+        memory.add_region(0x200000, vec![2; 2], rw());
+
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        scan.set_skip_uniform(true);
+        let progress = scan.progress();
+        scan.init().unwrap();
+        assert_eq!(scan.total_bytes(), 0x25003 + 0x1000 + 2);
+        assert_eq!(progress.load(std::sync::atomic::Ordering::Relaxed), scan.total_bytes());
+        // counted from zero again
+        scan.init().unwrap();
+        assert_eq!(progress.load(std::sync::atomic::Ordering::Relaxed), scan.total_bytes());
+    }
 
-        match proc {
-            Err(e) => assert!(false, "Error running simple program: {e}"),
-            Ok(child) => {
-                let proc = crate::core::utils::ChildGuard(child);
+    #[test]
+    pub fn test_pass_progress_counts_every_result() {
+        use super::*;
+        use std::sync::atomic::Ordering;
+        let memory = FakeMemory::new();
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x10000, vec![0; 0x2000], rw);
 
-                let mut scan = Scan::new(
-                    proc.0.id(),
-                    31337_u32.to_le_bytes().to_vec(),
-                    ValueType::U32,
-                    Some(0x2000),
-                    None,
-                    None,
-                )
-                .unwrap();
+        let mut scan = Scan::with_source(Arc::new(memory), vec![0; 4], ValueType::U32, None, None, None)
+            .unwrap();
+        scan.init().unwrap();
+        let checked = scan.result_count() as u64;
+        assert!(checked > PASS_PROGRESS_CHUNK as u64);
+        let progress = scan.pass_progress();
+        scan.next_compare(CompareMode::Unchanged, CompareBaseline::Previous).unwrap();
+        assert_eq!(progress.load(Ordering::Relaxed), checked);
+        // counted from zero again
+        scan.next_compare(CompareMode::Changed, CompareBaseline::Previous).unwrap();
+        assert_eq!(progress.load(Ordering::Relaxed), checked);
+        assert_eq!(scan.result_count(), 0);
+    }
 
-                // Try to set end address smaller than start address
-                let result = scan.set_end_address("0x1000");
-                assert!(result.is_err());
-                assert!(matches!(result.unwrap_err(), ScanError::AddressMismatch));
+    #[test]
+    pub fn test_observer_sees_regions_matches_and_progress() {
+        use super::*;
+        use std::sync::Mutex;
+
+        // a front end would forward these to its UI thread, the hooks run on workers
+        #[derive(Debug, Default)]
+        struct Counting {
+            regions: AtomicU64,
+            matches: Mutex<Vec<u64>>,
+            progress: Mutex<Vec<(u64, u64)>>,
+            completed: Mutex<Vec<usize>>,
+        }
+        impl ScanObserver for Counting {
+            fn on_region_start(&self, _region: &MemoryRegion) {
+                self.regions.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+            fn on_match(&self, result: &ScanResult) {
+                self.matches.lock().unwrap().push(result.address);
+            }
+            fn on_progress(&self, done: u64, total: u64) {
+                self.progress.lock().unwrap().push((done, total));
             }
+            fn on_complete(&self, found: usize) {
+                self.completed.lock().unwrap().push(found);
+            }
+        }
+
+        let memory = FakeMemory::new();
+        let rw = || vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        let mut bytes = vec![0_u8; 0x30000];
+        for address in [0x10, 0x18000, 0x2fff0] {
+            bytes[address..address + 4].copy_from_slice(&31337_u32.to_le_bytes());
         }
+        memory.add_region(0x10000, bytes, rw());
+        memory.add_region(0x100000, 31337_u32.to_le_bytes().to_vec(), rw());
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let observer = Arc::new(Counting::default());
+        scan.set_observer(observer.clone());
+        scan.init().unwrap();
+
+        assert_eq!(observer.regions.load(std::sync::atomic::Ordering::Relaxed), 2);
+        let mut matches = observer.matches.lock().unwrap().clone();
+        matches.sort();
+        assert_eq!(matches, vec![0x10010, 0x28000, 0x3fff0, 0x100000]);
+        let progress = observer.progress.lock().unwrap();
+        // a call per block and one for the tails, all counting up to the total
+        assert!(progress.len() >= 4);
+        assert!(progress.iter().all(|&(done, total)| done <= total && total == 0x30004));
+        assert_eq!(progress.iter().map(|&(done, _)| done).max(), Some(0x30004));
+        assert_eq!(*observer.completed.lock().unwrap(), vec![4]);
+
+        // reading more than the value makes blocks overlap by more than a match, one in the
+        // overlap is still reported once
+        let memory = FakeMemory::new();
+        let mut bytes = vec![0_u8; 0x20000];
+        bytes[0xfffc..0x10000].copy_from_slice(&31337_u32.to_le_bytes());
+        memory.add_region(0x10000, bytes, rw());
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        scan.set_read_size(Some(8)).unwrap();
+        let observer = Arc::new(Counting::default());
+        scan.set_observer(observer.clone());
+        scan.init().unwrap();
+        assert_eq!(*observer.matches.lock().unwrap(), vec![0x1fffc]);
+        assert_eq!(*observer.completed.lock().unwrap(), vec![1]);
+        assert_eq!(scan.results[0].value.len(), 8);
     }
 
     #[test]
-    pub fn test_add_to_watchlist_success() {
+    pub fn test_results_flag_misaligned_matches() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
-        };
-
-        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
-        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 32];
+        for offset in [4, 9, 18] {
+            data[offset..offset + 4].copy_from_slice(&31337_u32.to_le_bytes());
+        }
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
 
-        scan.add_to_watchlist(result1);
-        assert_eq!(scan.watchlist.len(), 1);
-        assert_eq!(scan.watchlist[0].address, 0x1000);
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            31337_u32.to_le_bytes().to_vec(),
+            ValueType::U32,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        scan.set_align_to(Some(1)).unwrap();
+        let flags: Vec<(u64, bool)> =
+            scan.init().unwrap().iter().map(|r| (r.address, r.aligned)).collect();
+        assert_eq!(flags, vec![(0x1004, true), (0x1009, false), (0x1012, false)]);
 
-        scan.add_to_watchlist(result2);
-        assert_eq!(scan.watchlist.len(), 2);
-        assert_eq!(scan.watchlist[1].address, 0x2000);
+        let string = ScanResult::new(0x1003, ValueType::String, b"abc".to_vec(), vec![]);
+        assert!(string.aligned);
     }
 
     #[test]
-    pub fn test_add_to_watchlist_duplicate_ignores() {
+    pub fn test_compare_baseline_initial_vs_previous() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
-        };
-
-        let result = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
-
-        scan.add_to_watchlist(result.clone());
-        assert_eq!(scan.watchlist.len(), 1);
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 16];
+        data[0..4].copy_from_slice(&10_u32.to_le_bytes());
+        data[8..12].copy_from_slice(&10_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let memory = Arc::new(memory);
+
+        let mut previous = oscillating_scan(&memory);
+        let mut initial = oscillating_scan(&memory);
+
+        // both values go up: 10 -> 20
+        memory.write(0x1000, &20_u32.to_le_bytes()).unwrap();
+        memory.write(0x1008, &20_u32.to_le_bytes()).unwrap();
+        let results = previous.next_compare(CompareMode::Increased, CompareBaseline::Previous);
+        assert_eq!(results.unwrap().len(), 2);
+        let results = initial.next_compare(CompareMode::Increased, CompareBaseline::Initial);
+        assert_eq!(results.unwrap().len(), 2);
+
+        // both values come down, one of them below where it started: 20 -> 15, 20 -> 5
+        memory.write(0x1000, &15_u32.to_le_bytes()).unwrap();
+        memory.write(0x1008, &5_u32.to_le_bytes()).unwrap();
+        let results = previous
+            .next_compare(CompareMode::Decreased, CompareBaseline::Previous)
+            .unwrap();
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1000, 0x1008]);
 
-        // Try to add the same address again
-        scan.add_to_watchlist(result);
-        assert_eq!(scan.watchlist.len(), 1);
+        let results = initial
+            .next_compare(CompareMode::Increased, CompareBaseline::Initial)
+            .unwrap();
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1000]);
+        assert_eq!(results[0].value, 15_u32.to_le_bytes().to_vec());
+        assert_eq!(results[0].initial_value, 10_u32.to_le_bytes().to_vec());
+
+        // back to the starting value: changed since the last scan, unchanged since the first
+        memory.write(0x1000, &10_u32.to_le_bytes()).unwrap();
+        let results = initial
+            .next_compare(CompareMode::Unchanged, CompareBaseline::Initial)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        let results = previous
+            .next_compare(CompareMode::Unchanged, CompareBaseline::Previous)
+            .unwrap();
+        let addresses: Vec<u64> = results.iter().map(|r| r.address).collect();
+        assert_eq!(addresses, vec![0x1008]);
     }
 
     #[test]
-    pub fn test_remove_from_watchlist_success() {
+    pub fn test_monotonic_samples_keep_steady_countdowns() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
+        let memory = Arc::new(FakeMemory::new());
+        memory.add_region(0x1000, vec![0_u8; 16], vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        assert_eq!(scan.init().unwrap().len(), 4);
+
+        // the value of each address at each sample, the first one read after the scan
+        let samples: [[u32; 4]; 4] = [[9, 50, 20, 7], [8, 49, 21, 7], [7, 49, 22, 6], [6, 48, 23, 5]];
+        let write_sample = |sample: &[u32; 4]| {
+            for (i, value) in sample.iter().enumerate() {
+                memory.write(0x1000 + i * 4, &value.to_le_bytes()).unwrap();
+            }
         };
+        write_sample(&samples[0]);
+        let mut next = 1;
+        let taken = scan
+            .next_monotonic(CompareMode::Decreased, samples.len(), || {
+                write_sample(&samples[next]);
+                next += 1;
+                true
+            })
+            .unwrap();
+        assert_eq!(taken, 4);
+        // 50 -> 49 -> 49 stalls, 7 -> 7 doesn't move and 20 counts up
+        let results: Vec<(u64, Vec<u8>)> = scan.results.iter().map(|r| (r.address, r.value.clone())).collect();
+        assert_eq!(results, vec![(0x1000, 6_u32.to_le_bytes().to_vec())]);
+
+        // stopping early keeps what the samples so far agree on
+        let mut scan =
+            Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        scan.init().unwrap();
+        write_sample(&[3, 3, 3, 3]);
+        let mut first = true;
+        let taken = scan
+            .next_monotonic(CompareMode::Increased, 5, || {
+                write_sample(&[4, 4, 2, 3]);
+                std::mem::take(&mut first)
+            })
+            .unwrap();
+        assert_eq!(taken, 2);
+        assert_eq!(scan.results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x1000, 0x1004]);
+    }
 
-        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
-        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
-
-        scan.add_to_watchlist(result1.clone());
-        scan.add_to_watchlist(result2.clone());
-        assert_eq!(scan.watchlist.len(), 2);
+    #[test]
+    pub fn test_stable_scan_drops_flapping_values() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        let mut bytes = Vec::new();
+        for value in [100_u32, 100, 7, 100] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        memory.add_region(0x1000, bytes, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let mut scan =
+            Scan::with_source(memory.clone(), 100_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 3);
+
+        // the second value flaps away and the third one flaps in between the reads
+        let confirmed = scan
+            .confirm_stable(|| {
+                memory.write(0x1004, &5_u32.to_le_bytes()).unwrap();
+                memory.write(0x1008, &100_u32.to_le_bytes()).unwrap();
+                true
+            })
+            .unwrap();
+        assert!(confirmed);
+        assert_eq!(scan.results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x1000, 0x100c]);
 
-        scan.remove_from_watchlist(result1.address);
-        assert_eq!(scan.watchlist.len(), 1);
-        assert_eq!(scan.watchlist[0].address, 0x2000);
+        // unknown value scans keep what read the same twice, a skipped read keeps everything
+        let mut scan = Scan::with_source(memory.clone(), vec![], ValueType::U32, None, None, None).unwrap();
+        scan.set_unknown(true).unwrap();
+        scan.init().unwrap();
+        assert!(!scan.confirm_stable(|| false).unwrap());
+        assert_eq!(scan.result_count(), 4);
+        scan.confirm_stable(|| {
+            memory.write(0x1000, &1_u32.to_le_bytes()).unwrap();
+            true
+        })
+        .unwrap();
+        assert_eq!(scan.results.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x1004, 0x1008, 0x100c]);
     }
 
     #[test]
-    pub fn test_remove_from_watchlist_not_present() {
+    pub fn test_compare_rounds_read_fresh_memory() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
-        };
-
-        let result1 = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
-        let result2 = ScanResult::new(0x2000, ValueType::U32, vec![5, 6, 7, 8], vec![]);
+        let memory = Arc::new(FakeMemory::new());
+        let mut bytes = 7_u32.to_le_bytes().repeat(4);
+        bytes.extend([0; 0x10]);
+        memory.add_region(0x1000, bytes, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let mut scan =
+            Scan::with_source(memory.clone(), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 4);
+
+        // rounds right after one another, all on the same page
+        memory.write(0x1004, &8_u32.to_le_bytes()).unwrap();
+        assert_eq!(scan.next_compare(CompareMode::Unchanged, CompareBaseline::Previous).unwrap().len(), 3);
+        memory.write(0x1008, &9_u32.to_le_bytes()).unwrap();
+        let unchanged = scan.next_compare(CompareMode::Unchanged, CompareBaseline::Previous).unwrap();
+        assert_eq!(unchanged.iter().map(|r| r.address).collect::<Vec<_>>(), vec![0x1000, 0x100c]);
+        memory.write(0x100c, &1_u32.to_le_bytes()).unwrap();
+        let changed = scan.next_compare(CompareMode::Changed, CompareBaseline::Previous).unwrap();
+        let changed: Vec<(u64, Vec<u8>)> = changed.iter().map(|r| (r.address, r.value.clone())).collect();
+        assert_eq!(changed, vec![(0x100c, 1_u32.to_le_bytes().to_vec())]);
+    }
 
-        scan.add_to_watchlist(result1);
-        assert_eq!(scan.watchlist.len(), 1);
+    #[test]
+    pub fn test_compare_order_needs_numeric_type() {
+        use super::*;
+        let memory = FakeMemory::new();
+        memory.add_region(
+            0x1000,
+            b"abcdabcd".to_vec(),
+            vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write],
+        );
+        let mut scan = Scan::with_source(
+            Arc::new(memory),
+            b"abcd".to_vec(),
+            ValueType::String,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(scan.init().unwrap().len(), 2);
 
-        // Try to remove an address that's not in the watchlist
-        scan.remove_from_watchlist(result2.address);
-        assert_eq!(scan.watchlist.len(), 1);
-        assert_eq!(scan.watchlist[0].address, 0x1000);
+        let err = scan.next_compare(CompareMode::Increased, CompareBaseline::Previous);
+        assert_eq!(err.unwrap_err(), ScanError::CompareUnsupported);
+        let results = scan.next_compare(CompareMode::Unchanged, CompareBaseline::Initial);
+        assert_eq!(results.unwrap().len(), 2);
     }
 
     #[test]
-    pub fn test_remove_from_watchlist_empty() {
+    pub fn test_compare_expression() {
         use super::*;
-        let mut scan = Scan {
-            pid: 0,
-            value: vec![],
-            value_type: ValueType::U32,
-            results: vec![],
-            watchlist: vec![],
-            start_address: None,
-            end_address: None,
-            read_size: None,
-            memory_regions: vec![],
-            memory_permissions: vec![],
-        };
 
-        let result = ScanResult::new(0x1000, ValueType::U32, vec![1, 2, 3, 4], vec![]);
+        let expr = CompareExpr::parse("cur > prev * 2 || (cur == prev + 0x10 && -cur < 0)").unwrap();
+        assert!(expr.matches(10.0, 21.0));
+        assert!(expr.matches(10.0, 26.0));
+        assert!(!expr.matches(10.0, 20.0));
+        assert!(CompareExpr::parse("cur / 0 > 1").unwrap().matches(1.0, 1.0));
+        assert!(!CompareExpr::parse("cur / 0 == 1").unwrap().matches(0.0, 0.0));
+
+        for (text, error) in [
+            ("cur > health", "unknown variable 'health'"),
+            ("cur + prev", "needs a comparison"),
+            ("cur > 1 + (prev < 2)", "needs numbers"),
+            ("(cur > 1) == (prev > 1)", "compares numbers"),
+            ("cur && prev", "needs conditions"),
+            ("1 < cur < 3", "can't be chained"),
+            ("(cur > 1", "missing ')'"),
+            ("cur > 1 prev", "unexpected input"),
+            ("cur >", "ends early"),
+            ("cur = 1", "unexpected '='"),
+            ("", "empty"),
+        ] {
+            match CompareExpr::parse(text) {
+                Err(ScanError::InvalidExpression(msg)) => assert!(msg.contains(error), "{text}: {msg}"),
+                other => panic!("{text}: {other:?}"),
+            }
+        }
 
-        // Try to remove from empty watchlist
-        scan.remove_from_watchlist(result.address);
-        assert_eq!(scan.watchlist.len(), 0);
+        let memory = FakeMemory::new();
+        let mut data = vec![0_u8; 16];
+        data[0..4].copy_from_slice(&10_u32.to_le_bytes());
+        data[8..12].copy_from_slice(&10_u32.to_le_bytes());
+        memory.add_region(0x1000, data, vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write]);
+        let memory = Arc::new(memory);
+        let mut scan = oscillating_scan(&memory);
+        memory.write(0x1000, &25_u32.to_le_bytes()).unwrap();
+        memory.write(0x1008, &15_u32.to_le_bytes()).unwrap();
+        let expr = CompareExpr::parse("cur > prev * 2").unwrap();
+        let results = scan.next_compare_expr(&expr, CompareBaseline::Previous).unwrap();
+        assert_eq!(results.iter().map(|r| r.address).collect::<Vec<_>>(), [0x1000]);
     }
 
     #[test]
@@ -1490,6 +5560,35 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_watchlist_entries_dropped_by_a_rescan() {
+        use super::*;
+        let memory = Arc::new(FakeMemory::new());
+        let rw = vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write];
+        memory.add_region(0x1000, 7_u32.to_le_bytes().repeat(4), rw);
+        let mut scan =
+            Scan::with_source(memory.clone(), 7_u32.to_le_bytes().to_vec(), ValueType::U32, None, None, None)
+                .unwrap();
+        scan.init().unwrap();
+        for result in scan.results.clone() {
+            scan.add_to_watchlist(result);
+        }
+        assert!(scan.watch_entries_not_in_results().is_empty());
+
+        memory.write(0x1004, &8_u32.to_le_bytes()).unwrap();
+        memory.write(0x100c, &9_u32.to_le_bytes()).unwrap();
+        scan.next_scan().unwrap();
+        // still watched and read, only flagged
+        assert_eq!(scan.watchlist.len(), 4);
+        assert_eq!(scan.watchlist[3].value, 9_u32.to_le_bytes());
+        assert_eq!(scan.watch_entries_not_in_results(), HashSet::from([0x1004, 0x100c]));
+
+        assert_eq!(scan.prune_watchlist(), 2);
+        let kept: Vec<u64> = scan.watchlist.iter().map(|w| w.address).collect();
+        assert_eq!(kept, [0x1000, 0x1008]);
+        assert_eq!(scan.prune_watchlist(), 0);
+    }
+
     #[test]
     #[ignore = "requires root"]
     pub fn test_refresh_watchlist_multiple_entries() {
@@ -1631,4 +5730,23 @@ mod test {
         assert!(readonly.perms.contains(&MemoryRegionPerms::Read));
         assert!(!readonly.perms.contains(&MemoryRegionPerms::Write));
     }
+
+    #[test]
+    fn test_interpret_bytes_as_common_types() {
+        use super::*;
+
+        let mut bytes = 1.5_f32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        let values: Vec<(&str, String)> =
+            interpret_bytes(&bytes).into_iter().map(|i| (i.value_type.name(), i.value)).collect();
+        assert_eq!(values[0], ("u32", 1069547520.to_string()));
+        assert_eq!(values[2], ("f32", String::from("1.5")));
+        assert_eq!(values[3], ("u64", 1069547520.to_string()));
+        // the first byte is 0, no string
+        assert_eq!(values.len(), 6);
+
+        let values = interpret_bytes(b"hp\0\x01");
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[3], Interpretation { value_type: ValueType::String, value: String::from("hp") });
+    }
 }
@@ -0,0 +1,354 @@
+//! Scan results kept in a memory-mapped file instead of a `Vec`, for scans with tens of
+//! millions of matches (unknown value or zero scans of a big process).
+//!
+//! Every result is a fixed size record, so the n-th result is at `n * record_size` and
+//! passes over the results rewrite the file in place, keeping the records that passed at
+//! the front. The records of a `width` byte value type are laid out as
+//!
+//! ```text
+//! address u64 | region u32 (u32::MAX: none) | perms u8 | aligned u8
+//! | value len u16 | initial value len u16 | value [width] | initial value [width]
+//! ```
+//!
+//! all little endian. Only linux can map the file, elsewhere [`SpillFile::create`] fails.
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::core::mem::MemoryRegionPerms;
+use crate::core::scan::{ScanResult, ValueType};
+
+/// Bytes of a record before the values
+const RECORD_HEADER_LEN: usize = 18;
+
+/// Records the file grows by at least, so pushing doesn't remap for every region
+const MIN_GROWTH: usize = 1 << 16;
+
+/// Records a pass decodes and filters at a time, only these are held in memory
+const PASS_CHUNK: usize = 1 << 16;
+
+const REGION_NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpillError {
+    Io(String),
+    ValueTooLong { address: u64, len: usize, width: usize },
+    Unsupported,
+}
+
+impl Display for SpillError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access the spill file: {e}"),
+            Self::ValueTooLong { address, len, width } => write!(
+                f,
+                "Value at 0x{address:x} is {len} byte(s), the spill file holds {width} per result"
+            ),
+            Self::Unsupported => write!(f, "Spilling results to a file is only supported on linux"),
+        }
+    }
+}
+
+impl std::error::Error for SpillError {}
+
+impl From<std::io::Error> for SpillError {
+    fn from(e: std::io::Error) -> Self {
+        SpillError::Io(e.to_string())
+    }
+}
+
+fn encode_perms(perms: &[MemoryRegionPerms]) -> u8 {
+    perms.iter().fold(0, |bits, perm| {
+        bits | match perm {
+            MemoryRegionPerms::Read => 1,
+            MemoryRegionPerms::Write => 2,
+            MemoryRegionPerms::Execute => 4,
+        }
+    })
+}
+
+fn decode_perms(bits: u8) -> Vec<MemoryRegionPerms> {
+    [(1, MemoryRegionPerms::Read), (2, MemoryRegionPerms::Write), (4, MemoryRegionPerms::Execute)]
+        .into_iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|(_, perm)| perm)
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn map_file(file: &File, len: usize) -> Result<*mut u8, SpillError> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: a fresh shared mapping of a file we own, unmapped before the file is resized
+    let map = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if map == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(map as *mut u8)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn map_file(_file: &File, _len: usize) -> Result<*mut u8, SpillError> {
+    Err(SpillError::Unsupported)
+}
+
+#[cfg(target_os = "linux")]
+fn unmap(map: *mut u8, len: usize) {
+    // SAFETY: `map` came from `map_file` with the same length
+    unsafe { libc::munmap(map as *mut libc::c_void, len) };
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unmap(_map: *mut u8, _len: usize) {}
+
+/// Scan results in a memory-mapped file of fixed size records, see the module docs
+#[derive(Debug)]
+pub struct SpillFile {
+    file: File,
+    value_type: ValueType,
+    width: usize,
+    /// Start of the mapping, null while nothing is mapped
+    map: *mut u8,
+    /// Records the mapping has room for
+    capacity: usize,
+    len: usize,
+}
+
+// SAFETY: the mapping is owned like a `Vec` buffer, it is only written through `&mut self`
+unsafe impl Send for SpillFile {}
+unsafe impl Sync for SpillFile {}
+
+impl SpillFile {
+    /// Create (or truncate) the file at `path` for results of `value_type` whose values
+    /// are at most `width` bytes
+    pub fn create(path: &Path, value_type: ValueType, width: usize) -> Result<Self, SpillError> {
+        if cfg!(not(target_os = "linux")) {
+            return Err(SpillError::Unsupported);
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(SpillFile { file, value_type, width, map: std::ptr::null_mut(), capacity: 0, len: 0 })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn record_size(&self) -> usize {
+        RECORD_HEADER_LEN + 2 * self.width
+    }
+
+    /// Drop every record, the file keeps its size until it is dropped
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn record(&self, index: usize) -> &[u8] {
+        let size = self.record_size();
+        // SAFETY: index < len <= capacity, which the mapping holds
+        unsafe { std::slice::from_raw_parts(self.map.add(index * size), size) }
+    }
+
+    fn record_mut(&mut self, index: usize) -> &mut [u8] {
+        let size = self.record_size();
+        // SAFETY: as in `record`, and `&mut self` makes this the only reference
+        unsafe { std::slice::from_raw_parts_mut(self.map.add(index * size), size) }
+    }
+
+    pub fn get(&self, index: usize) -> Option<ScanResult> {
+        (index < self.len).then(|| self.decode(self.record(index)))
+    }
+
+    pub fn iter(&self) -> SpillIter<'_> {
+        SpillIter { spill: self, next: 0 }
+    }
+
+    fn decode(&self, record: &[u8]) -> ScanResult {
+        let u16_at = |at: usize| u16::from_le_bytes([record[at], record[at + 1]]) as usize;
+        let address = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        let region = u32::from_le_bytes(record[8..12].try_into().unwrap());
+        let values = RECORD_HEADER_LEN;
+        let initial = values + self.width;
+        ScanResult {
+            address,
+            value_type: self.value_type,
+            perms: decode_perms(record[12]),
+            value: record[values..values + u16_at(14)].to_vec(),
+            initial_value: record[initial..initial + u16_at(16)].to_vec(),
+            region: (region != REGION_NONE).then_some(region),
+            aligned: record[13] != 0,
+        }
+    }
+
+    fn encode(&mut self, index: usize, result: &ScanResult) -> Result<(), SpillError> {
+        let width = self.width;
+        if let Some(len) = [result.value.len(), result.initial_value.len()]
+            .into_iter()
+            .find(|len| *len > width.min(u16::MAX as usize))
+        {
+            return Err(SpillError::ValueTooLong { address: result.address, len, width });
+        }
+        let record = self.record_mut(index);
+        record[0..8].copy_from_slice(&result.address.to_le_bytes());
+        record[8..12].copy_from_slice(&result.region.unwrap_or(REGION_NONE).to_le_bytes());
+        record[12] = encode_perms(&result.perms);
+        record[13] = result.aligned as u8;
+        record[14..16].copy_from_slice(&(result.value.len() as u16).to_le_bytes());
+        record[16..18].copy_from_slice(&(result.initial_value.len() as u16).to_le_bytes());
+        let values = RECORD_HEADER_LEN;
+        record[values..values + result.value.len()].copy_from_slice(&result.value);
+        let initial = values + width;
+        record[initial..initial + result.initial_value.len()].copy_from_slice(&result.initial_value);
+        Ok(())
+    }
+
+    /// Resize the file to `capacity` records and map it again
+    fn resize(&mut self, capacity: usize) -> Result<(), SpillError> {
+        if !self.map.is_null() {
+            unmap(self.map, self.capacity * self.record_size());
+            self.map = std::ptr::null_mut();
+        }
+        let bytes = capacity * self.record_size();
+        self.file.set_len(bytes as u64)?;
+        if bytes > 0 {
+            self.map = map_file(&self.file, bytes)?;
+        }
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    pub fn push(&mut self, result: &ScanResult) -> Result<(), SpillError> {
+        if self.len == self.capacity {
+            self.resize(self.capacity + self.capacity.max(MIN_GROWTH))?;
+        }
+        self.encode(self.len, result)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Replace every result with what `keep` returns for it, dropping the ones it returns
+    /// `None` for. Results are filtered in parallel a chunk at a time and written back in
+    /// place, in the same order.
+    pub fn retain(
+        &mut self,
+        keep: impl Fn(&ScanResult) -> Option<ScanResult> + Sync,
+    ) -> Result<(), SpillError> {
+        let mut kept = 0;
+        for start in (0..self.len).step_by(PASS_CHUNK) {
+            let end = (start + PASS_CHUNK).min(self.len);
+            let chunk: Vec<ScanResult> =
+                (start..end).map(|i| self.decode(self.record(i))).collect();
+            let chunk: Vec<ScanResult> = chunk.par_iter().filter_map(&keep).collect();
+            // `kept <= start`, so this never overwrites a record that wasn't read yet
+            for result in &chunk {
+                self.encode(kept, result)?;
+                kept += 1;
+            }
+        }
+        self.len = kept;
+        Ok(())
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = self.resize(self.len);
+        if !self.map.is_null() {
+            unmap(self.map, self.capacity * self.record_size());
+        }
+    }
+}
+
+pub struct SpillIter<'a> {
+    spill: &'a SpillFile,
+    next: usize,
+}
+
+impl Iterator for SpillIter<'_> {
+    type Item = ScanResult;
+
+    fn next(&mut self) -> Option<ScanResult> {
+        let result = self.spill.get(self.next)?;
+        self.next += 1;
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.spill.len - self.next;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for SpillIter<'_> {}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_spill_file_round_trip_and_retain() {
+        let path = std::env::temp_dir().join(format!("cheat-engine-rs-spill-{}", std::process::id()));
+        let mut spill = SpillFile::create(&path, ValueType::U32, 4).unwrap();
+        let results: Vec<ScanResult> = (0..MIN_GROWTH as u64 + 10)
+            .map(|i| {
+                let mut result = ScanResult::new(
+                    0x1000 + i * 4,
+                    ValueType::U32,
+                    (i as u32).to_le_bytes().to_vec(),
+                    vec![MemoryRegionPerms::Read, MemoryRegionPerms::Write],
+                );
+                result.region = (i % 2 == 0).then_some(i as u32);
+                result
+            })
+            .collect();
+        // the last pushes grow the file past its first mapping
+        for result in &results {
+            spill.push(result).unwrap();
+        }
+        assert_eq!(spill.len(), results.len());
+        let back = spill.get(3).unwrap();
+        assert_eq!((back.address, back.region, back.aligned), (0x100c, None, true));
+        assert_eq!((back.value, back.initial_value), (vec![3, 0, 0, 0], vec![3, 0, 0, 0]));
+        assert_eq!(back.perms, results[3].perms);
+        assert_eq!(spill.iter().len(), results.len());
+        assert_eq!(spill.get(results.len()).map(|r| r.address), None);
+
+        // keep every third result with a new value, in order
+        spill
+            .retain(|result| {
+                (result.address % 12 == 4).then(|| ScanResult { value: vec![9; 2], ..result.clone() })
+            })
+            .unwrap();
+        let kept: Vec<ScanResult> = spill.iter().collect();
+        assert_eq!(kept.len(), results.iter().filter(|r| r.address % 12 == 4).count());
+        assert!(kept.windows(2).all(|pair| pair[0].address + 12 == pair[1].address));
+        assert!(kept.iter().all(|r| r.value == [9, 9] && r.initial_value.len() == 4));
+
+        let too_long = ScanResult::new(0, ValueType::U32, vec![0; 5], vec![]);
+        assert_eq!(
+            spill.push(&too_long),
+            Err(SpillError::ValueTooLong { address: 0, len: 5, width: 4 })
+        );
+        let record_size = spill.record_size();
+        drop(spill);
+        // the file is cut down to the records that were kept
+        let size = std::fs::metadata(&path).unwrap().len() as usize;
+        assert_eq!(size, kept.len() * record_size);
+        let _ = std::fs::remove_file(&path);
+    }
+}
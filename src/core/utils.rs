@@ -1,4 +1,10 @@
 use std::process::Child;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::core::mem::{
+    DEFAULT_SEARCH_PERMS, MemoryError, MemoryRegion, MemoryRegionPerms, MemorySource,
+};
 
 #[allow(dead_code)]
 pub struct ChildGuard(pub Child);
@@ -14,3 +20,128 @@ impl Drop for ChildGuard {
         let _ = self.0.wait(); // reap zombie
     }
 }
+
+/// In-memory [`MemorySource`] for tests: a set of regions backed by plain byte buffers.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub struct FakeMemory {
+    regions: RwLock<Vec<(MemoryRegion, Vec<u8>)>>,
+    /// Listed by `regions` like the others, but every read of them fails
+    unreadable: RwLock<Vec<MemoryRegion>>,
+    reads: AtomicUsize,
+    /// Whether reads may run on into the next region, see [`FakeMemory::spanning`]
+    spanning: bool,
+}
+
+#[allow(dead_code)]
+impl FakeMemory {
+    pub fn new() -> Self {
+        FakeMemory::default()
+    }
+
+    /// Memory whose reads may run on into the next region when it starts right where the
+    /// previous one ends, like reads of process memory across adjacent mappings. Reads of
+    /// [`FakeMemory::new`] stay within one region.
+    pub fn spanning() -> Self {
+        FakeMemory { spanning: true, ..FakeMemory::default() }
+    }
+
+    pub fn add_region(&self, start: u64, data: Vec<u8>, perms: Vec<MemoryRegionPerms>) {
+        let region = MemoryRegion {
+            start,
+            end: start + data.len() as u64,
+            perms,
+        };
+        let mut regions = self.regions.write().unwrap();
+        regions.push((region, data));
+        regions.sort_by_key(|(r, _)| r.start);
+    }
+
+    /// A region of `len` bytes at `start` that is mapped but can't be read, like a guard
+    /// page or memory unmapped during a scan
+    pub fn add_unreadable_region(&self, start: u64, len: u64, perms: Vec<MemoryRegionPerms>) {
+        self.unreadable.write().unwrap().push(MemoryRegion { start, end: start + len, perms });
+    }
+
+    /// Unmap the region starting at `start`, readable or not
+    pub fn remove_region(&self, start: u64) {
+        self.regions.write().unwrap().retain(|(r, _)| r.start != start);
+        self.unreadable.write().unwrap().retain(|r| r.start != start);
+    }
+
+    /// Number of reads so far, failed ones included
+    pub fn read_count(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+}
+
+impl MemorySource for FakeMemory {
+    fn read(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemoryError> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        let regions = self.regions.read().unwrap();
+        if !self.spanning {
+            let addr = addr as u64;
+            return regions
+                .iter()
+                .find(|(r, _)| r.start <= addr && addr + size as u64 <= r.end)
+                .map(|(r, data)| {
+                    let offset = (addr - r.start) as usize;
+                    data[offset..offset + size].to_vec()
+                })
+                .ok_or(MemoryError::MemRead(5));
+        }
+
+        let (mut addr, end) = (addr as u64, addr as u64 + size as u64);
+        let mut value = Vec::with_capacity(size);
+        loop {
+            let (r, data) = regions
+                .iter()
+                .find(|(r, _)| r.start <= addr && (addr < r.end || (size == 0 && addr == r.end)))
+                .ok_or(MemoryError::MemRead(5))?;
+            let offset = (addr - r.start) as usize;
+            let take = std::cmp::min(end, r.end) - addr;
+            value.extend_from_slice(&data[offset..offset + take as usize]);
+            addr += take;
+            if addr >= end {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    fn write(&self, addr: usize, value: &[u8]) -> Result<(), MemoryError> {
+        let mut regions = self.regions.write().unwrap();
+        let addr = addr as u64;
+        let (region, data) = regions
+            .iter_mut()
+            .find(|(r, _)| r.start <= addr && addr + value.len() as u64 <= r.end)
+            .ok_or(MemoryError::MemWrite(5))?;
+        let offset = (addr - region.start) as usize;
+        data[offset..offset + value.len()].copy_from_slice(value);
+        Ok(())
+    }
+
+    fn regions(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        search_perms: Option<&[MemoryRegionPerms]>,
+    ) -> Result<Vec<MemoryRegion>, MemoryError> {
+        let search_perms = search_perms.unwrap_or(&DEFAULT_SEARCH_PERMS);
+        let start = start.unwrap_or(0);
+        let end = end.unwrap_or(u64::MAX);
+        let mut regions: Vec<MemoryRegion> = self
+            .regions
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(r, _)| r)
+            .chain(self.unreadable.read().unwrap().iter())
+            .filter(|r| r.end > start && r.start <= end)
+            .filter(|r| search_perms.iter().any(|p| r.perms.contains(p)))
+            .cloned()
+            .collect();
+        regions.sort_by_key(|r| r.start);
+        Ok(regions)
+    }
+}
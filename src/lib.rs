@@ -0,0 +1,3 @@
+//! The memory scanning, reading and writing behind the CLI and TUI, a library so the
+//! benchmarks can use it too.
+pub mod core;
@@ -1,18 +1,39 @@
-mod core;
 mod tui;
 mod cli;
 
+use cheat_engine_rs::core;
+
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 enum Command {
     /// Run in TUI mode (default)
-    Tui,
+    Tui {
+        /// Keep the values and bytes written in the TUI after exiting instead of restoring
+        /// the original bytes
+        #[arg(long)]
+        no_restore: bool,
+    },
     /// Run in CLI mode
     Cli {
+        /// Log diagnostics to stderr, -v for debug and -vv for trace messages, given before
+        /// the subcommand. RUST_LOG can set the level too, but only as a bare level (`debug`)
+        /// or `cheat_engine_rs=<level>`, comma separated with the last one winning; module
+        /// paths, other crates and `/regex` filters are ignored
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        verbose: u8,
+        /// How process memory is accessed on Linux: auto (process_vm_readv/writev, falling
+        /// back to /proc/<pid>/mem) or proc-mem (only /proc/<pid>/mem)
+        #[arg(long, default_value = "auto")]
+        io: String,
+        /// Format of the result rows of the scan, read and search commands (scan, read,
+        /// find-refs, find-code, value-histogram, ...): table, json, csv or plain
+        /// (`<address> <value>` lines). Other than with the table, status lines go to stderr
+        #[arg(long, global = true, default_value = "table")]
+        output_format: String,
         #[command(subcommand)]
-        command: cli::Commands,
+        command: Box<cli::Commands>,
     },
 }
 
@@ -20,36 +41,20 @@ fn main() {
     let command = Command::parse();
 
     match command {
-        Command::Tui => {
-            if let Err(e) = tui::run() {
+        Command::Tui { no_restore } => {
+            // the calculator reads values with the configured sizes too
+            if let Err(e) = core::config::load().and_then(|()| tui::run(no_restore)) {
                 panic!("{}", e);
             }
         }
-        Command::Cli { command: cli_command } => {
-            // Call the corresponding CLI function based on the command
-            match cli_command {
-                cli::Commands::ListProcesses => {
-                    if let Err(e) = cli::list_processes() {
-                        panic!("{}", e);
-                    }
-                }
-                cli::Commands::Scan { pid, value, r#type, start, end } => {
-                    if let Err(e) = cli::scan_memory(pid, &value, &r#type, start.as_deref(), end.as_deref()) {
-                        panic!("{}", e);
-                    }
-                }
-                cli::Commands::Read { pid, address, r#type, size } => {
-                    if let Err(e) = cli::read_memory(pid, &address, &r#type, size) {
-                        panic!("{}", e);
-                    }
-                }
-                cli::Commands::Write { pid, address, value, r#type } => {
-                    if let Err(e) = cli::write_memory(pid, &address, &value, &r#type) {
-                        panic!("{}", e);
-                    }
-                }
+        Command::Cli { verbose, io, output_format, command: cli_command } => {
+            cli::logger::init(verbose);
+            let setup = cli::set_io_backend(&io)
+                .and_then(|()| core::config::load())
+                .and_then(|()| cli::output_format(&output_format));
+            if let Err(e) = setup.and_then(|format| cli::run(*cli_command, format)) {
+                panic!("{}", e);
             }
         }
     }
 }
-
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     error::Error,
     time::{Duration, Instant},
 };
@@ -8,10 +8,20 @@ use crate::tui::utils::cursor;
 use crate::{
     core::{
         self,
-        proc::{ProcInfo, get_list},
-        scan::{Scan, ScanError, ValueType},
+        journal::{self, undo_last},
+        mem::{
+            MemoryError, MemoryRegionPerms, PatchRegistry, ProcessHandle, get_memory_regions,
+            read_memory_address,
+        },
+        proc::{ProcInfo, RegionEntry, RegionNames, get_list, process_details, region_list},
+        scan::{CompareBaseline, CompareMode, Scan, ScanError, ValueType},
+    },
+    tui::{
+        calculator::{self, Calculation},
+        hex_editor::{BYTES_PER_ROW, HexEditor, HexWriteMode},
+        status::{StatusEvent, StatusLine},
+        utils,
     },
-    tui::utils,
 };
 
 use ratatui::{
@@ -25,6 +35,9 @@ pub enum CurrentScreen {
     ProcessList,
     Scan,
     ValueEditing,
+    HexEditor,
+    Calculator,
+    Regions,
     Exiting,
 }
 
@@ -36,6 +49,7 @@ pub enum SelectedInput {
     EndAddress,
     ResultValue,
     ReadSize,
+    Calculator,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -63,32 +77,28 @@ pub enum InputMode {
     Insert,
 }
 
-#[derive(Clone, PartialEq, Default)]
-pub enum AppMessageType {
-    #[default]
-    Info,
-    Error,
-}
-
-#[derive(Clone, Default)]
-pub struct AppMessage {
-    pub msg: String,
-    pub msg_type: AppMessageType,
-}
-
-impl AppMessage {
-    pub fn new(msg: &str, msg_type: AppMessageType) -> Self {
-        AppMessage {
-            msg: msg.to_owned(),
-            msg_type,
-        }
-    }
-}
 #[derive(Clone, PartialEq)]
 pub enum AppAction {
     New,
     Refresh,
     Next,
+    Compare(CompareMode),
+}
+
+/// How many result sets compare filters keep around for undo
+const MAX_UNDO_RESULTS: usize = 10;
+
+/// How often a running compare filter reports how far it got
+const COMPARE_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A compare filter running on a worker thread, so the UI keeps drawing. The scan is
+/// moved to the worker and comes back with the result, see [`App::poll_compare`], its
+/// progress goes to the status line meanwhile
+pub struct CompareTask {
+    pub mode: CompareMode,
+    /// Results before the filter, shown while it runs and kept for undo
+    pub previous: Vec<core::scan::ScanResult>,
+    done: std::sync::mpsc::Receiver<(Box<Scan>, Result<usize, ScanError>)>,
 }
 
 // Command pattern for user actions
@@ -118,12 +128,31 @@ pub enum Command {
     NextScan,
     RefreshScan,
     ToggleReadWrite,
+    CompareScan(CompareMode),
+    UndoScan,
 
     // Result commands
     AddToWatchlist,
     RemoveFromWatchlist,
+    PruneWatchlist,
     EditValue,
     CopyValue,
+    OpenHexEditor,
+    OpenCalculator,
+    ShowRegions,
+    UndoWrite,
+
+    // Region list commands
+    RefreshRegions,
+    ScopeScanToRegion,
+
+    // Hex editor commands
+    PageUp,
+    PageDown,
+    ToggleHexEditMode,
+    ToggleHexWriteMode,
+    CommitHexEdits,
+    DiscardHexEdits,
 
     // List commands
     MoveUp,
@@ -132,11 +161,64 @@ pub enum Command {
     MoveToBottom,
 
     // App commands
+    ShowHelp,
     Quit,
     ConfirmQuit,
     CancelQuit,
 }
 
+impl Command {
+    /// What the command does, as listed in the help overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            Command::NextWidget => "Next pane",
+            Command::PrevWidget => "Previous pane",
+            Command::GoBack => "Back",
+            Command::ExitInsertMode => "Stop editing",
+            Command::AcceptInput => "Accept input",
+            Command::InsertChar(_) => "Type a character",
+            Command::DeleteChar => "Delete a character",
+            Command::MoveCursorLeft => "Cursor left",
+            Command::MoveCursorRight => "Cursor right",
+            Command::ShowProcessList => "Refresh the process list",
+            Command::SelectProcess => "Select the process",
+            Command::NewScan => "New scan",
+            Command::NextScan => "Next scan",
+            Command::RefreshScan => "Refresh result values",
+            Command::ToggleReadWrite => "Toggle read-only regions",
+            Command::CompareScan(CompareMode::Increased) => "Keep increased values",
+            Command::CompareScan(CompareMode::Decreased) => "Keep decreased values",
+            Command::CompareScan(CompareMode::Changed) => "Keep changed values",
+            Command::CompareScan(CompareMode::Unchanged) => "Keep unchanged values",
+            Command::UndoScan => "Undo the last filter",
+            Command::AddToWatchlist => "Add to watchlist",
+            Command::RemoveFromWatchlist => "Remove from watchlist",
+            Command::PruneWatchlist => "Remove watched entries the results dropped",
+            Command::EditValue => "Edit the value",
+            Command::CopyValue => "Copy the value",
+            Command::OpenHexEditor => "Hex editor",
+            Command::OpenCalculator => "Address calculator",
+            Command::ShowRegions => "Memory regions",
+            Command::RefreshRegions => "Refresh the region list",
+            Command::ScopeScanToRegion => "Scan only this region",
+            Command::UndoWrite => "Undo the last write",
+            Command::PageUp => "Page up",
+            Command::PageDown => "Page down",
+            Command::ToggleHexEditMode => "Switch between hex and ASCII",
+            Command::ToggleHexWriteMode => "Toggle batched writes",
+            Command::CommitHexEdits => "Write pending edits",
+            Command::DiscardHexEdits => "Discard pending edits",
+            Command::MoveUp => "Up",
+            Command::MoveDown => "Down",
+            Command::MoveToTop => "Top",
+            Command::MoveToBottom => "Bottom",
+            Command::ShowHelp => "Show this help",
+            Command::Quit | Command::ConfirmQuit => "Quit",
+            Command::CancelQuit => "Stay",
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Direction {
     Up,
@@ -163,6 +245,26 @@ impl KeyPress {
             modifiers: event.modifiers,
         }
     }
+
+    /// Key as shown in the help overlay, e.g. `Ctrl+z` or `↑`
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => String::from("Space"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => String::from("↑"),
+            KeyCode::Down => String::from("↓"),
+            KeyCode::Left => String::from("←"),
+            KeyCode::Right => String::from("→"),
+            KeyCode::BackTab => String::from("Shift+Tab"),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("Ctrl+{key}")
+        } else {
+            key
+        }
+    }
 }
 
 // Key bindings system
@@ -171,8 +273,12 @@ pub struct KeyBindings {
     // Screen-specific bindings
     process_list_normal: HashMap<KeyPress, Command>,
     scan_view_normal: HashMap<KeyPress, Command>,
+    // Scan results pane, these shadow the scan view bindings
+    scan_results_normal: HashMap<KeyPress, Command>,
     exiting_screen: HashMap<KeyPress, Command>,
     insert_mode: HashMap<KeyPress, Command>,
+    hex_editor: HashMap<KeyPress, Command>,
+    regions: HashMap<KeyPress, Command>,
     // Global bindings (work across all screens)
     global: HashMap<KeyPress, Command>,
 }
@@ -182,8 +288,11 @@ impl KeyBindings {
         let mut bindings = KeyBindings {
             process_list_normal: HashMap::new(),
             scan_view_normal: HashMap::new(),
+            scan_results_normal: HashMap::new(),
             exiting_screen: HashMap::new(),
             insert_mode: HashMap::new(),
+            hex_editor: HashMap::new(),
+            regions: HashMap::new(),
             global: HashMap::new(),
         };
 
@@ -201,6 +310,10 @@ impl KeyBindings {
             KeyPress::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
             Command::Quit,
         );
+        self.global.insert(
+            KeyPress::new(KeyCode::F(1), KeyModifiers::NONE),
+            Command::ShowHelp,
+        );
 
         // Process list bindings (normal mode)
         self.process_list_normal.insert(
@@ -239,6 +352,10 @@ impl KeyBindings {
             KeyPress::new(KeyCode::BackTab, KeyModifiers::SHIFT),
             Command::PrevWidget,
         );
+        self.process_list_normal.insert(
+            KeyPress::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            Command::ShowHelp,
+        );
 
         // Scan view bindings (normal mode)
         self.scan_view_normal.insert(
@@ -265,6 +382,10 @@ impl KeyBindings {
             KeyPress::new(KeyCode::Char('d'), KeyModifiers::NONE),
             Command::RemoveFromWatchlist,
         );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('p'), KeyModifiers::NONE),
+            Command::PruneWatchlist,
+        );
         self.scan_view_normal.insert(
             KeyPress::new(KeyCode::Char('u'), KeyModifiers::NONE),
             Command::EditValue,
@@ -277,6 +398,18 @@ impl KeyBindings {
             KeyPress::new(KeyCode::Char('y'), KeyModifiers::NONE),
             Command::CopyValue,
         );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('x'), KeyModifiers::NONE),
+            Command::OpenHexEditor,
+        );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('='), KeyModifiers::NONE),
+            Command::OpenCalculator,
+        );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('m'), KeyModifiers::NONE),
+            Command::ShowRegions,
+        );
         self.scan_view_normal.insert(
             KeyPress::new(KeyCode::Tab, KeyModifiers::NONE),
             Command::NextWidget,
@@ -313,6 +446,36 @@ impl KeyBindings {
             KeyPress::new(KeyCode::Char('G'), KeyModifiers::SHIFT),
             Command::MoveToBottom,
         );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('z'), KeyModifiers::NONE),
+            Command::UndoScan,
+        );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            Command::UndoWrite,
+        );
+        self.scan_view_normal.insert(
+            KeyPress::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            Command::ShowHelp,
+        );
+
+        // Scan results bindings (normal mode), one-key compare filters
+        self.scan_results_normal.insert(
+            KeyPress::new(KeyCode::Char('i'), KeyModifiers::NONE),
+            Command::CompareScan(CompareMode::Increased),
+        );
+        self.scan_results_normal.insert(
+            KeyPress::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            Command::CompareScan(CompareMode::Decreased),
+        );
+        self.scan_results_normal.insert(
+            KeyPress::new(KeyCode::Char('c'), KeyModifiers::NONE),
+            Command::CompareScan(CompareMode::Changed),
+        );
+        self.scan_results_normal.insert(
+            KeyPress::new(KeyCode::Char('u'), KeyModifiers::NONE),
+            Command::CompareScan(CompareMode::Unchanged),
+        );
 
         // Exiting screen bindings
         self.exiting_screen.insert(
@@ -336,6 +499,110 @@ impl KeyBindings {
             Command::CancelQuit,
         );
 
+        // Hex editor bindings, other characters are typed into the editor
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Up, KeyModifiers::NONE),
+            Command::MoveUp,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Down, KeyModifiers::NONE),
+            Command::MoveDown,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Left, KeyModifiers::NONE),
+            Command::MoveCursorLeft,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Right, KeyModifiers::NONE),
+            Command::MoveCursorRight,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::PageUp, KeyModifiers::NONE),
+            Command::PageUp,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::PageDown, KeyModifiers::NONE),
+            Command::PageDown,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Home, KeyModifiers::NONE),
+            Command::MoveToTop,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::End, KeyModifiers::NONE),
+            Command::MoveToBottom,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Tab, KeyModifiers::NONE),
+            Command::ToggleHexEditMode,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Enter, KeyModifiers::NONE),
+            Command::CommitHexEdits,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+            Command::ToggleHexWriteMode,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+            Command::DiscardHexEdits,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            Command::UndoWrite,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::Esc, KeyModifiers::NONE),
+            Command::GoBack,
+        );
+        self.hex_editor.insert(
+            KeyPress::new(KeyCode::F(1), KeyModifiers::NONE),
+            Command::ShowHelp,
+        );
+
+        // Region list bindings
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('j'), KeyModifiers::NONE),
+            Command::MoveDown,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Down, KeyModifiers::NONE),
+            Command::MoveDown,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('k'), KeyModifiers::NONE),
+            Command::MoveUp,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Up, KeyModifiers::NONE),
+            Command::MoveUp,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Command::MoveToBottom,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('r'), KeyModifiers::NONE),
+            Command::RefreshRegions,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('s'), KeyModifiers::NONE),
+            Command::ScopeScanToRegion,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Enter, KeyModifiers::NONE),
+            Command::OpenHexEditor,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Esc, KeyModifiers::NONE),
+            Command::GoBack,
+        );
+        self.regions.insert(
+            KeyPress::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            Command::ShowHelp,
+        );
+
         // Insert mode bindings
         self.insert_mode.insert(
             KeyPress::new(KeyCode::Esc, KeyModifiers::NONE),
@@ -367,6 +634,49 @@ impl KeyBindings {
         );
     }
 
+    /// Every binding for the help overlay: a title per screen, then each command with
+    /// the keys bound to it, sorted by description
+    pub fn help_sections(&self) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+        let sections = [
+            ("Everywhere", &self.global),
+            ("Process list", &self.process_list_normal),
+            ("Scan view", &self.scan_view_normal),
+            ("Scan results", &self.scan_results_normal),
+            ("Hex editor", &self.hex_editor),
+            ("Regions", &self.regions),
+            ("Typing", &self.insert_mode),
+            ("Exit prompt", &self.exiting_screen),
+        ];
+        sections
+            .into_iter()
+            .map(|(title, bindings)| {
+                let mut keys: Vec<(&'static str, String)> = bindings
+                    .iter()
+                    .map(|(key, command)| (command.description(), key.label()))
+                    .collect();
+                keys.sort();
+                let mut rows: Vec<(String, &'static str)> = vec![];
+                for (description, key) in keys {
+                    match rows.last_mut() {
+                        Some((keys, last)) if *last == description => *keys = format!("{keys}/{key}"),
+                        _ => rows.push((key, description)),
+                    }
+                }
+                (title, rows)
+            })
+            .collect()
+    }
+
+    /// Command for a key pressed while the scan results pane is selected in normal mode,
+    /// falls back to the scan view bindings
+    pub fn get_results_command(&self, key_event: KeyEvent) -> Option<Command> {
+        let key_press = KeyPress::from_key_event(key_event);
+        self.scan_results_normal
+            .get(&key_press)
+            .cloned()
+            .or_else(|| self.get_command(key_event, &CurrentScreen::Scan, &InputMode::Normal))
+    }
+
     pub fn get_command(
         &self,
         key_event: KeyEvent,
@@ -380,7 +690,24 @@ impl KeyBindings {
             return self.exiting_screen.get(&key_press).cloned();
         }
 
-        if let Some(cmd) = self.global.get(&key_press) {
+        // the hex editor takes plain characters as input, so global keys don't apply
+        if *screen == CurrentScreen::HexEditor {
+            if let Some(cmd) = self.hex_editor.get(&key_press) {
+                return Some(cmd.clone());
+            }
+            if let KeyCode::Char(c) = key_event.code
+                && (key_event.modifiers == KeyModifiers::NONE
+                    || key_event.modifiers == KeyModifiers::SHIFT)
+            {
+                return Some(Command::InsertChar(c));
+            }
+            return None;
+        }
+
+        // module names are typed into the calculator, so global keys don't apply there
+        if *screen != CurrentScreen::Calculator
+            && let Some(cmd) = self.global.get(&key_press)
+        {
             return Some(cmd.clone());
         }
 
@@ -398,6 +725,7 @@ impl KeyBindings {
             InputMode::Normal => match screen {
                 CurrentScreen::ProcessList => self.process_list_normal.get(&key_press).cloned(),
                 CurrentScreen::Scan => self.scan_view_normal.get(&key_press).cloned(),
+                CurrentScreen::Regions => self.regions.get(&key_press).cloned(),
                 _ => None,
             },
         }
@@ -430,6 +758,7 @@ pub struct InputBuffers {
     pub end_address: String,
     pub result_value: String,
     pub read_size: String,
+    pub calculator: String,
 }
 
 impl InputBuffers {
@@ -441,6 +770,7 @@ impl InputBuffers {
             end_address: String::new(),
             result_value: String::new(),
             read_size: String::new(),
+            calculator: String::new(),
         }
     }
 
@@ -452,6 +782,7 @@ impl InputBuffers {
             SelectedInput::EndAddress => &mut self.end_address,
             SelectedInput::ResultValue => &mut self.result_value,
             SelectedInput::ReadSize => &mut self.read_size,
+            SelectedInput::Calculator => &mut self.calculator,
         }
     }
 
@@ -463,6 +794,7 @@ impl InputBuffers {
             SelectedInput::EndAddress => &self.end_address,
             SelectedInput::ResultValue => &self.result_value,
             SelectedInput::ReadSize => &self.read_size,
+            SelectedInput::Calculator => &self.calculator,
         }
     }
 
@@ -477,6 +809,7 @@ pub struct ListStates {
     pub value_type: ListState,
     pub scan_results: ListState,
     pub scan_watchlist: ListState,
+    pub regions: ListState,
 }
 
 impl ListStates {
@@ -486,6 +819,7 @@ impl ListStates {
             value_type: ListState::default(),
             scan_results: ListState::default(),
             scan_watchlist: ListState::default(),
+            regions: ListState::default(),
         }
     }
 }
@@ -495,6 +829,7 @@ pub struct ScrollStates {
     pub proc_list_vertical: ScrollbarState,
     pub scan_results_vertical: ScrollbarState,
     pub scan_watchlist_vertical: ScrollbarState,
+    pub regions_vertical: ScrollbarState,
 }
 
 impl ScrollStates {
@@ -503,6 +838,7 @@ impl ScrollStates {
             proc_list_vertical: ScrollbarState::default(),
             scan_results_vertical: ScrollbarState::default(),
             scan_watchlist_vertical: ScrollbarState::default(),
+            regions_vertical: ScrollbarState::default(),
         }
     }
 }
@@ -577,12 +913,51 @@ pub struct App {
     pub selected_value_type: usize,
     pub selected_process: Option<ProcInfo>,
     pub selected_value: Option<core::scan::ScanResult>,
+    pub hex_editor: Option<HexEditor>,
+    /// Regions of the selected process, read when the region list is opened or refreshed
+    pub regions: Vec<RegionEntry>,
+    /// Last evaluation of the calculator expression, `None` while it is empty
+    pub calculation: Option<Result<Calculation, String>>,
+    pub undo_results: Vec<Vec<core::scan::ScanResult>>,
+    /// Watchlist addresses the results no longer hold since the last scan pass, marked
+    /// in the watchlist until they are pruned
+    pub dropped_watch: HashSet<u64>,
     pub value_types: Vec<ValueType>,
-    pub app_message: AppMessage,
+    /// Result of the last action, shown in the message box of a screen, or at the bottom of
+    /// the screens without one
+    pub status: StatusLine,
+    /// The keybinding overlay is open, any key closes it
+    pub show_help: bool,
     pub app_action: Option<AppAction>,
     pub key_bindings: KeyBindings,
     pub include_readonly_regions: bool,
     pub clipboard: Option<arboard::Clipboard>,
+    /// Original bytes of every write this session, per process. They are put back when the
+    /// TUI exits (or panics) unless `keep_writes` is set, from `tui --no-restore`
+    pub patches: HashMap<u32, PatchRegistry<ProcessHandle>>,
+    pub keep_writes: bool,
+    /// The compare filter running in the background, `scan` is `None` meanwhile
+    pub compare: Option<CompareTask>,
+}
+
+/// The session patches of process `pid`, see [`App::patches`]
+fn patches_of(
+    patches: &mut HashMap<u32, PatchRegistry<ProcessHandle>>,
+    keep: bool,
+    pid: u32,
+) -> Result<&mut PatchRegistry<ProcessHandle>, MemoryError> {
+    use std::collections::hash_map::Entry;
+
+    Ok(match patches.entry(pid) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let mut registry = PatchRegistry::new(ProcessHandle::open(pid)?);
+            if keep {
+                registry.keep();
+            }
+            entry.insert(registry)
+        }
+    })
 }
 
 impl App {
@@ -594,20 +969,22 @@ impl App {
             proc_list: vec![],
             selected_value_type: 0,
             selected_value: None,
+            hex_editor: None,
+            regions: vec![],
+            calculation: None,
+            undo_results: vec![],
+            dropped_watch: HashSet::new(),
             selected_process: None,
-            value_types: vec![
-                ValueType::U64,
-                ValueType::I64,
-                ValueType::U32,
-                ValueType::I32,
-                ValueType::String,
-                ValueType::Hex,
-            ],
-            app_message: AppMessage::default(),
+            value_types: ValueType::all().to_vec(),
+            status: StatusLine::default(),
+            show_help: false,
             app_action: None,
             key_bindings: KeyBindings::default(),
             include_readonly_regions: false,
             clipboard: arboard::Clipboard::new().ok(),
+            patches: HashMap::new(),
+            keep_writes: false,
+            compare: None,
         }
     }
 
@@ -665,10 +1042,7 @@ impl App {
 
         match result {
             Err(e) => {
-                self.app_message = AppMessage::new(
-                    &format!("Error initializing scan: {}", e),
-                    AppMessageType::Error,
-                )
+                self.status.push(StatusEvent::Failed(format!("Error initializing scan: {}", e)))
             }
             Ok(scan) => self.scan = Some(scan),
         }
@@ -718,8 +1092,10 @@ impl App {
         self.ui.input_buffers.read_size = String::new();
         self.include_readonly_regions = false;
         self.scan = None;
+        self.undo_results.clear();
+        self.dropped_watch.clear();
         self.selected_process = None;
-        self.app_message = AppMessage::default();
+        self.status.clear();
     }
 
     fn enable_auto_input(&mut self) {
@@ -848,7 +1224,7 @@ impl App {
         if let Some(scan) = &self.scan
             && let Err(e) = scan.value_from_str(&self.ui.input_buffers.scan_value)
         {
-            self.app_message = AppMessage::new(&format!("{e}"), AppMessageType::Error);
+            self.status.push(StatusEvent::Failed(format!("{e}")));
             self.select_widget(ScanViewWidget::ValueInput);
             return false;
         }
@@ -864,17 +1240,15 @@ impl App {
             None => {}
             Some(scan) => match scan.init() {
                 Err(e) => {
-                    self.app_message = AppMessage::new(
-                        &format!("Error while scanning: {e}"),
-                        AppMessageType::Error,
-                    );
+                    self.status.push(StatusEvent::Failed(format!("Scan failed: {e}")));
                 }
                 Ok(results) => {
+                    self.status.push(StatusEvent::ScanFinished { matches: results.len() });
                     if !results.is_empty() {
                         self.ui.list_states.scan_results.select(Some(0));
                         self.select_widget(ScanViewWidget::ScanResults);
                     }
-                    self.app_message = AppMessage::default();
+                    self.undo_results.clear();
                 }
             },
         }
@@ -898,17 +1272,21 @@ impl App {
             None => {}
             Some(scan) => match scan.next_scan() {
                 Err(e) => {
-                    self.app_message = AppMessage::new(
-                        &format!("Error while scanning: {e}"),
-                        AppMessageType::Error,
-                    );
+                    self.status.push(StatusEvent::Failed(format!("Next scan failed: {e}")));
                 }
                 Ok(results) => {
-                    if !results.is_empty() {
+                    let matches = results.len();
+                    self.status.push(match scan.maps_changed() {
+                        true => StatusEvent::Info(format!(
+                            "Scan found {matches} match(es), the memory map changed since the \
+                             last scan so every result was read again"
+                        )),
+                        false => StatusEvent::ScanFinished { matches },
+                    });
+                    if matches > 0 {
                         self.ui.list_states.scan_results.select(Some(0));
                         self.select_widget(ScanViewWidget::ScanResults);
                     }
-                    self.app_message = AppMessage::default();
                 }
             },
         }
@@ -924,6 +1302,194 @@ impl App {
         }
     }
 
+    fn reset_results_view(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let selected = if scan.results.is_empty() { None } else { Some(0) };
+        self.ui.list_states.scan_results.select(selected);
+        self.ui.scroll_states.scan_results_vertical = self
+            .ui
+            .scroll_states
+            .scan_results_vertical
+            .content_length(scan.results.len())
+            .position(0);
+    }
+
+    // Keep results whose value changed according to `mode` since the last scan, the
+    // Start the compare filter on a worker thread, `poll_compare` picks up the result. The
+    // previous results are kept so the filter can be undone
+    fn compare_scan(&mut self, mode: CompareMode) {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::mpsc;
+
+        if self
+            .scan
+            .as_ref()
+            .is_none_or(|scan| scan.results.is_empty())
+        {
+            self.status.push(StatusEvent::Info(String::from(
+                "No results to filter, run a scan first",
+            )));
+            return;
+        }
+        let Some(mut scan) = self.scan.take() else {
+            return;
+        };
+
+        let previous = scan.results.clone();
+        let total = previous.len();
+        let progress = scan.pass_progress();
+        let status = self.status.sender();
+        let (finish, done) = mpsc::channel();
+        std::thread::spawn(move || {
+            let finished = AtomicBool::new(false);
+            let result = std::thread::scope(|threads| {
+                threads.spawn(|| {
+                    while !finished.load(Ordering::SeqCst) {
+                        let checked = progress.load(Ordering::Relaxed);
+                        let _ = status.send(StatusEvent::Info(format!(
+                            "{mode:?}: checked {checked} of {total} result(s)"
+                        )));
+                        std::thread::sleep(COMPARE_PROGRESS_INTERVAL);
+                    }
+                });
+                let result = scan
+                    .next_compare(mode, CompareBaseline::Previous)
+                    .map(|results| results.len());
+                finished.store(true, Ordering::SeqCst);
+                result
+            });
+            let _ = finish.send((Box::new(scan), result));
+        });
+        self.status.push(StatusEvent::Info(format!("{mode:?}: filtering {total} result(s)")));
+        self.compare = Some(CompareTask { mode, previous, done });
+    }
+
+    // Take back the scan once the compare filter running in the background is done
+    fn poll_compare(&mut self) {
+        use std::sync::mpsc::TryRecvError;
+
+        let Some(task) = &self.compare else {
+            return;
+        };
+        let (scan, result) = match task.done.try_recv() {
+            Ok(done) => done,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.compare = None;
+                self.status.push(StatusEvent::Failed(String::from(
+                    "The filter crashed, the scan is lost",
+                )));
+                return;
+            }
+        };
+        let Some(CompareTask { mode, previous, .. }) = self.compare.take() else {
+            return;
+        };
+        self.scan = Some(*scan);
+        self.finish_compare(mode, previous, result);
+        self.update_dropped_watch();
+    }
+
+    fn finish_compare(
+        &mut self,
+        mode: CompareMode,
+        previous: Vec<core::scan::ScanResult>,
+        result: Result<usize, ScanError>,
+    ) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        match result {
+            Err(e) => {
+                self.status.push(StatusEvent::Failed(format!("Filter failed: {e}")));
+            }
+            Ok(remaining) => {
+                self.status.push(match scan.maps_changed() {
+                    true => StatusEvent::Info(format!(
+                        "{mode:?}: {remaining} result(s) left, the memory map changed so every \
+                         result was read again, z to undo"
+                    )),
+                    false => StatusEvent::Filtered { mode, remaining },
+                });
+                self.undo_results.push(previous);
+                if self.undo_results.len() > MAX_UNDO_RESULTS {
+                    self.undo_results.remove(0);
+                }
+                self.reset_results_view();
+            }
+        }
+    }
+
+    fn undo_scan(&mut self) {
+        let Some(scan) = &mut self.scan else {
+            return;
+        };
+        match self.undo_results.pop() {
+            None => {
+                self.status.push(StatusEvent::Info(String::from("Nothing to undo")));
+            }
+            Some(results) => {
+                scan.results = results;
+                self.status.push(StatusEvent::Info(format!(
+                    "Restored {} result(s)",
+                    scan.results.len()
+                )));
+                self.reset_results_view();
+                self.update_dropped_watch();
+            }
+        }
+    }
+
+    // Cross-check the watchlist against the results, after every pass that changes them
+    fn update_dropped_watch(&mut self) {
+        self.dropped_watch = self
+            .scan
+            .as_ref()
+            .map(Scan::watch_entries_not_in_results)
+            .unwrap_or_default();
+    }
+
+    fn prune_watchlist(&mut self) {
+        let Some(scan) = &mut self.scan else {
+            return;
+        };
+        let pruned = scan.prune_watchlist();
+        self.dropped_watch.clear();
+        let len = scan.watchlist.len();
+        self.ui.scroll_states.scan_watchlist_vertical =
+            self.ui.scroll_states.scan_watchlist_vertical.content_length(len);
+        if self.ui.list_states.scan_watchlist.selected().is_some_and(|i| i >= len) {
+            self.ui.list_states.scan_watchlist.select(len.checked_sub(1));
+        }
+        self.status.push(StatusEvent::Info(format!(
+            "Removed {pruned} watched address(es) the results dropped"
+        )));
+    }
+
+    // Write back the bytes replaced by the last journaled write, see `cli undo-last`
+    fn undo_write(&mut self) {
+        let Some(process) = &self.selected_process else {
+            return;
+        };
+        self.status.push(match undo_last(process.pid) {
+            Ok(None) => StatusEvent::Info(String::from("No writes to undo")),
+            Ok(Some(restored)) => match restored.as_slice() {
+                [(entry, _)] => StatusEvent::Restored {
+                    address: entry.address,
+                    bytes: entry.previous.len(),
+                },
+                _ => StatusEvent::Info(format!("Restored {} writes", restored.len())),
+            },
+            Err(e) => StatusEvent::Failed(format!("Undo failed: {e}")),
+        });
+        if self.scan.is_some() {
+            self.app_action = Some(AppAction::Refresh);
+        }
+        self.refresh_hex_editor();
+    }
+
     fn refresh_scan(&mut self) {
         if !self.check_value_before_scan() {
             return;
@@ -931,21 +1497,34 @@ impl App {
         match &mut self.scan {
             None => {}
             Some(scan) => {
+                // a refresh follows a write, whose status stays
                 if let Err(e) = scan.refresh() {
-                    self.app_message = AppMessage::new(
-                        &format!("Error while scanning: {e}"),
-                        AppMessageType::Error,
-                    );
-                } else {
-                    self.app_message = AppMessage::default();
+                    self.status.push(StatusEvent::Failed(format!("Refresh failed: {e}")));
                 }
             }
         }
     }
 
+    // Keep watched values live, reusing the scan's open process handle
+    fn refresh_watchlist(&mut self) {
+        if self.state.current_screen != CurrentScreen::Scan {
+            return;
+        }
+
+        if let Some(scan) = &mut self.scan
+            && !scan.watchlist.is_empty()
+            && let Err(e) = scan.refresh_watchlist()
+        {
+            self.status.push(StatusEvent::Failed(format!("Error while refreshing watchlist: {e}")));
+        }
+    }
+
     fn handle_normal_mode_event(&mut self, key: KeyEvent) {
         // Special handling for 'g' key to detect gg
-        if key.code == KeyCode::Char('g') && key.modifiers == KeyModifiers::NONE {
+        if key.code == KeyCode::Char('g')
+            && key.modifiers == KeyModifiers::NONE
+            && self.state.current_screen != CurrentScreen::HexEditor
+        {
             if let Some(t) = self.ui.last_g_press_time
                 && t.elapsed() < Duration::from_millis(500)
             {
@@ -957,10 +1536,16 @@ impl App {
             return;
         }
 
-        if let Some(cmd) =
+        let cmd = if self.state.current_screen == CurrentScreen::Scan
+            && self.ui.selected_widgets.scan_view_selected_widget == ScanViewWidget::ScanResults
+        {
+            self.key_bindings.get_results_command(key)
+        } else {
             self.key_bindings
                 .get_command(key, &self.state.current_screen, &InputMode::Normal)
-        {
+        };
+
+        if let Some(cmd) = cmd {
             self.handle_command(cmd);
         }
     }
@@ -974,43 +1559,30 @@ impl App {
             match selected_input {
                 SelectedInput::ResultValue => {
                     let result = self.selected_value.as_ref().unwrap();
-                    match scan.update_value(result.address, &self.ui.input_buffers.result_value) {
-                        Err(e) => match e {
-                            ScanError::EmptyValue => {
-                                self.app_message = AppMessage::new(
-                                    "New value can not be empty",
-                                    AppMessageType::Error,
-                                );
-                            }
-                            ScanError::InvalidValue => {
-                                self.app_message = AppMessage::new(
-                                    &format!(
-                                        "Invalid value: {:.10} for type: {}",
-                                        self.ui.input_buffers.result_value,
-                                        scan.value_type.get_string(),
-                                    ),
-                                    AppMessageType::Error,
-                                );
-                            }
-                            ScanError::Memory(e) => {
-                                self.app_message = AppMessage::new(
-                                    &format!("Error while updating memory address: {e}",),
-                                    AppMessageType::Error,
-                                );
-                            }
-                            _ => {}
-                        },
-                        Ok(_) => {
-                            self.app_action = Some(AppAction::Refresh);
-                            self.app_message = AppMessage::new(
-                                &format!(
-                                    "Value at address 0x{:x} set to {}",
-                                    result.address, self.ui.input_buffers.result_value
-                                ),
-                                AppMessageType::Info,
-                            );
+                    let pid = self.selected_process.as_ref().unwrap().pid;
+                    let written = scan
+                        .update_value(result.address, &self.ui.input_buffers.result_value);
+                    if let Ok((_, Some(entry))) = &written {
+                        let _ = journal::record_for(pid, vec![entry.clone()]);
+                        if let Ok(patches) = patches_of(&mut self.patches, self.keep_writes, pid) {
+                            patches.record(entry.address as usize, entry.previous.clone());
                         }
                     }
+                    self.status.push(match written {
+                        Err(ScanError::EmptyValue) => {
+                            StatusEvent::Failed(String::from("New value can not be empty"))
+                        }
+                        Err(ScanError::InvalidValue) => StatusEvent::Failed(format!(
+                            "Invalid value: {:.10} for type: {}",
+                            self.ui.input_buffers.result_value,
+                            scan.value_type.get_string(),
+                        )),
+                        Err(e) => StatusEvent::Failed(format!("Write failed: {e}")),
+                        Ok((bytes, _)) => {
+                            self.app_action = Some(AppAction::Refresh);
+                            StatusEvent::Wrote { address: result.address, bytes }
+                        }
+                    });
                     self.go_back();
                 }
                 SelectedInput::ScanValue => {
@@ -1019,17 +1591,14 @@ impl App {
                             .set_value_from_str(&self.ui.input_buffers.scan_value)
                             .is_err()
                     {
-                        self.app_message = AppMessage::new(
-                            &format!(
+                        self.status.push(StatusEvent::Failed(format!(
                                 "Invalid value: {:.10} for type: {}",
                                 self.ui.input_buffers.scan_value,
                                 scan.value_type.get_string(),
-                            ),
-                            AppMessageType::Error,
-                        );
+                            )));
                         self.insert_mode_for(SelectedInput::ScanValue);
                     } else {
-                        self.app_message = AppMessage::default();
+                        self.status.clear();
                     }
                 }
                 SelectedInput::ReadSize => {
@@ -1040,19 +1609,17 @@ impl App {
 
                     match self.ui.input_buffers.read_size.parse::<usize>() {
                         Err(_) => {
-                            self.app_message = AppMessage::new(
+                            self.status.push(StatusEvent::Failed(String::from(
                                 "Read size should be integer",
-                                AppMessageType::Error,
-                            );
+                            )));
                             self.insert_mode_for(SelectedInput::ReadSize);
                         }
                         Ok(size) => {
                             if let Err(e) = scan.set_read_size(Some(size)) {
-                                self.app_message =
-                                    AppMessage::new(&format!("{e}",), AppMessageType::Error);
+                                self.status.push(StatusEvent::Failed(format!("{e}",)));
                                 self.insert_mode_for(SelectedInput::ReadSize);
                             } else {
-                                self.app_message = AppMessage::default();
+                                self.status.clear();
                             }
                         }
                     }
@@ -1061,62 +1628,52 @@ impl App {
                     if let Err(e) = scan.set_start_address(&self.ui.input_buffers.start_address) {
                         match e {
                             ScanError::InvalidAddress => {
-                                self.app_message = AppMessage::new(
-                                    &format!(
+                                self.status.push(StatusEvent::Failed(format!(
                                         "Invalid hex value: {:.16}",
                                         self.ui.input_buffers.start_address
-                                    ),
-                                    AppMessageType::Error,
-                                );
+                                    )));
                             }
                             ScanError::AddressMismatch => {
-                                self.app_message = AppMessage::new(
+                                self.status.push(StatusEvent::Failed(String::from(
                                     "Start address should be smaller than end address",
-                                    AppMessageType::Error,
-                                );
+                                )));
                             }
                             ScanError::Memory(e) => {
-                                self.app_message = AppMessage::new(
-                                    &format!("Error getting memory regions: {e}"),
-                                    AppMessageType::Error,
-                                );
+                                self.status.push(StatusEvent::Failed(format!(
+                                    "Error getting memory regions: {e}"
+                                )));
                             }
                             _ => {}
                         }
                         self.insert_mode_for(SelectedInput::StartAddress);
                     } else {
-                        self.app_message = AppMessage::default();
+                        self.status.clear();
                     }
                 }
                 SelectedInput::EndAddress => {
                     if let Err(e) = scan.set_end_address(&self.ui.input_buffers.end_address) {
                         match e {
                             ScanError::InvalidAddress => {
-                                self.app_message = AppMessage::new(
-                                    &format!(
+                                self.status.push(StatusEvent::Failed(format!(
                                         "Invalid hex value: {:.16}",
                                         self.ui.input_buffers.end_address
-                                    ),
-                                    AppMessageType::Error,
-                                );
+                                    )));
                             }
                             ScanError::AddressMismatch => {
-                                self.app_message = AppMessage::new(
+                                self.status.push(StatusEvent::Failed(String::from(
                                     "End address should be bigger than start address",
-                                    AppMessageType::Error,
-                                );
+                                )));
                             }
                             ScanError::Memory(e) => {
-                                self.app_message = AppMessage::new(
-                                    &format!("Error getting memory regions: {e}"),
-                                    AppMessageType::Error,
-                                );
+                                self.status.push(StatusEvent::Failed(format!(
+                                    "Error getting memory regions: {e}"
+                                )));
                             }
                             _ => {}
                         }
                         self.insert_mode_for(SelectedInput::EndAddress);
                     } else {
-                        self.app_message = AppMessage::default();
+                        self.status.clear();
                     }
                 }
                 _ => {}
@@ -1124,8 +1681,353 @@ impl App {
         }
     }
 
+    fn selected_result(&self) -> Option<&core::scan::ScanResult> {
+        let scan = self.scan.as_ref()?;
+        match self.ui.selected_widgets.scan_view_selected_widget {
+            ScanViewWidget::ScanResults => scan
+                .results
+                .get(self.ui.list_states.scan_results.selected()?),
+            ScanViewWidget::WatchList => scan
+                .watchlist
+                .get(self.ui.list_states.scan_watchlist.selected()?),
+            _ => None,
+        }
+    }
+
+    // Open the hex editor on the region of the selected result, or on the
+    // start/end address range when no result is selected
+    fn open_hex_editor(&mut self) {
+        let Some(process) = &self.selected_process else {
+            return;
+        };
+        let pid = process.pid;
+
+        let range_start = u64::from_str_radix(
+            self.ui.input_buffers.start_address.trim_start_matches("0x"),
+            16,
+        )
+        .ok();
+        let range_end = u64::from_str_radix(
+            self.ui.input_buffers.end_address.trim_start_matches("0x"),
+            16,
+        )
+        .ok();
+        let Some(address) = self
+            .selected_result()
+            .map(|result| result.address)
+            .or(range_start)
+        else {
+            self.status.push(StatusEvent::Info(String::from(
+                "Select a result or set a start address to open the hex editor",
+            )));
+            return;
+        };
+
+        let regions = match get_memory_regions(
+            pid,
+            None,
+            None,
+            Some(&[MemoryRegionPerms::Read, MemoryRegionPerms::Write]),
+        ) {
+            Ok(regions) => regions,
+            Err(e) => {
+                self.status.push(StatusEvent::Failed(format!("Error getting memory regions: {e}")));
+                return;
+            }
+        };
+
+        let Some(region) = regions
+            .iter()
+            .find(|r| r.start <= address && address < r.end)
+        else {
+            self.status.push(StatusEvent::Failed(format!(
+                "Address 0x{address:x} is not in a mapped region"
+            )));
+            return;
+        };
+
+        let (start, end) = match (self.selected_result(), range_start) {
+            (None, Some(start)) => {
+                (start, range_end.unwrap_or(region.end).clamp(start + 1, region.end))
+            }
+            _ => (region.start, region.end),
+        };
+        let writable = region.perms.contains(&MemoryRegionPerms::Write);
+        self.show_hex_editor(HexEditor::new(start, end, address, writable));
+    }
+
+    fn show_hex_editor(&mut self, editor: HexEditor) {
+        self.hex_editor = Some(editor);
+        self.refresh_hex_editor();
+        self.ui.input_mode = InputMode::Normal;
+        self.status.clear();
+        self.go_to(CurrentScreen::HexEditor);
+    }
+
+    // Re-read the bytes currently shown in the hex editor
+    fn refresh_hex_editor(&mut self) {
+        if let Some(process) = &self.selected_process
+            && let Some(editor) = &mut self.hex_editor
+        {
+            let (address, size) = editor.window();
+            let data = read_memory_address(process.pid, address as usize, size).unwrap_or_default();
+            editor.set_data(address, data);
+        }
+    }
+
+    fn commit_hex_edits(&mut self) {
+        let Some(pid) = self.selected_process.as_ref().map(|p| p.pid) else {
+            return;
+        };
+        let Some(editor) = &mut self.hex_editor else {
+            return;
+        };
+
+        let runs = editor.take_pending_runs();
+        let mut written = 0;
+        let mut first_written = None;
+        for (address, bytes) in runs {
+            let applied = patches_of(&mut self.patches, self.keep_writes, pid)
+                .and_then(|patches| patches.apply(address as usize, &bytes));
+            match applied {
+                Ok(_) => {
+                    written += bytes.len();
+                    first_written.get_or_insert(address);
+                }
+                Err(e) => {
+                    editor.restore_run(address, &bytes);
+                    self.status.push(StatusEvent::Failed(format!(
+                        "Write to 0x{address:x} failed: {e}"
+                    )));
+                }
+            }
+        }
+
+        // a failed run keeps its error on show
+        if let Some(address) = first_written
+            && editor.pending.is_empty()
+        {
+            self.status.push(StatusEvent::Wrote { address, bytes: written });
+        }
+        self.refresh_hex_editor();
+    }
+
+    // Hex editor reuses the generic movement/input commands, returns false for
+    // commands it doesn't handle
+    fn handle_hex_editor_command(&mut self, cmd: &Command) -> bool {
+        let Some(editor) = &mut self.hex_editor else {
+            return false;
+        };
+
+        match cmd {
+            Command::MoveUp => editor.move_by(-(BYTES_PER_ROW as i64)),
+            Command::MoveDown => editor.move_by(BYTES_PER_ROW as i64),
+            Command::MoveCursorLeft => editor.move_by(-1),
+            Command::MoveCursorRight => editor.move_by(1),
+            Command::PageUp => editor.page_up(),
+            Command::PageDown => editor.page_down(),
+            Command::MoveToTop => editor.move_to(editor.start),
+            Command::MoveToBottom => editor.move_to(editor.end),
+            Command::ToggleHexEditMode => editor.toggle_edit_mode(),
+            Command::ToggleHexWriteMode => {
+                editor.toggle_write_mode();
+                if editor.write_mode == HexWriteMode::Immediate {
+                    self.commit_hex_edits();
+                }
+            }
+            Command::InsertChar(c) => {
+                if !editor.writable {
+                    self.status.push(StatusEvent::Failed(String::from(
+                        "Cannot edit read-only memory region",
+                    )));
+                } else if editor.input(*c) && editor.write_mode == HexWriteMode::Immediate {
+                    self.commit_hex_edits();
+                }
+            }
+            Command::CommitHexEdits => self.commit_hex_edits(),
+            Command::DiscardHexEdits => {
+                let discarded = editor.discard_pending();
+                self.status.push(StatusEvent::Info(format!(
+                    "Discarded {discarded} pending byte edit(s)"
+                )));
+            }
+            Command::GoBack => {
+                let discarded = editor.discard_pending();
+                if discarded > 0 {
+                    self.status.push(StatusEvent::Info(format!(
+                        "Discarded {discarded} pending byte edit(s)"
+                    )));
+                } else {
+                    self.status.clear();
+                }
+                self.hex_editor = None;
+                self.go_back();
+                return true;
+            }
+            _ => return false,
+        }
+
+        self.refresh_hex_editor();
+        true
+    }
+
+    fn open_regions(&mut self) {
+        if self.selected_process.is_none() {
+            return;
+        }
+        self.refresh_regions();
+        self.ui.input_mode = InputMode::Normal;
+        self.go_to(CurrentScreen::Regions);
+    }
+
+    // Re-read the regions of the process, keeping the selection where it was if it can
+    fn refresh_regions(&mut self) {
+        let Some(process) = &self.selected_process else {
+            return;
+        };
+        let pid = process.pid;
+        let exe = process_details(pid).and_then(|details| details.exe);
+        let main_module = exe.as_ref().and_then(|exe| exe.file_name()).and_then(|name| name.to_str());
+        let names = RegionNames::for_process(pid);
+        let source = match core::mem::ProcessHandle::open(pid) {
+            Ok(handle) => handle,
+            Err(e) => {
+                self.status.push(StatusEvent::Failed(format!("Error opening the process: {e}")));
+                return;
+            }
+        };
+        match region_list(&source, &names, main_module) {
+            Ok(regions) => self.regions = regions,
+            Err(e) => {
+                self.status.push(StatusEvent::Failed(format!("Error getting memory regions: {e}")));
+                return;
+            }
+        }
+
+        let selected = match self.ui.list_states.regions.selected() {
+            _ if self.regions.is_empty() => None,
+            Some(i) => Some(i.min(self.regions.len() - 1)),
+            None => Some(0),
+        };
+        self.ui.list_states.regions.select(selected);
+        self.ui.scroll_states.regions_vertical = self
+            .ui
+            .scroll_states
+            .regions_vertical
+            .content_length(self.regions.len())
+            .position(selected.unwrap_or(0));
+    }
+
+    fn selected_region(&self) -> Option<&RegionEntry> {
+        self.regions.get(self.ui.list_states.regions.selected()?)
+    }
+
+    // Scope the scan to the selected region, the address inputs show its range. Editing
+    // them or the permissions goes back to scanning the range.
+    fn scope_scan_to_region(&mut self) {
+        let Some(entry) = self.selected_region().cloned() else {
+            return;
+        };
+        let Some(scan) = &mut self.scan else {
+            return;
+        };
+        scan.set_regions(vec![entry.region.clone()]);
+        self.ui.input_buffers.start_address = format!("0x{:x}", entry.region.start);
+        self.ui.input_buffers.end_address = format!("0x{:x}", entry.region.end);
+        self.status.push(StatusEvent::Info(format!(
+                "Scanning only 0x{:x}-0x{:x} {}",
+                entry.region.start,
+                entry.region.end,
+                entry.mapping.as_deref().unwrap_or("(anonymous)")
+            )));
+        self.go_back();
+        self.select_widget(ScanViewWidget::ValueInput);
+    }
+
+    // The region list reuses the list and hex editor commands, returns false for commands
+    // it doesn't handle
+    fn handle_regions_command(&mut self, cmd: &Command) -> bool {
+        match cmd {
+            Command::RefreshRegions => self.refresh_regions(),
+            Command::ScopeScanToRegion => self.scope_scan_to_region(),
+            Command::OpenHexEditor => {
+                if let Some(entry) = self.selected_region() {
+                    let region = &entry.region;
+                    let writable = region.perms.contains(&MemoryRegionPerms::Write);
+                    self.show_hex_editor(HexEditor::new(region.start, region.end, region.start, writable));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn open_calculator(&mut self) {
+        if self.selected_process.is_none() {
+            return;
+        }
+        self.go_to(CurrentScreen::Calculator);
+        self.insert_mode_for(SelectedInput::Calculator);
+        self.evaluate_calculator();
+    }
+
+    // Resolve the calculator expression, reading the value with the scan's value type
+    fn evaluate_calculator(&mut self) {
+        let Some(process) = &self.selected_process else {
+            return;
+        };
+        let expression = self.ui.input_buffers.calculator.trim();
+        if expression.is_empty() {
+            self.calculation = None;
+            return;
+        }
+        // the type selected in the scan view, the scan keeps its old one if it rejected it
+        let value_type = self
+            .ui
+            .list_states
+            .value_type
+            .selected()
+            .and_then(|selected| self.value_types.get(selected).copied())
+            .or_else(|| self.scan.as_ref().map(|scan| scan.value_type))
+            .unwrap_or(ValueType::U32);
+        let read_size = self.ui.input_buffers.read_size.parse().ok();
+        self.calculation = Some(calculator::evaluate(
+            process.pid,
+            expression,
+            value_type,
+            read_size,
+        ));
+    }
+
+    // The calculator stays in insert mode, Enter re-reads the value and Esc closes it
+    fn handle_calculator_command(&mut self, cmd: &Command) -> bool {
+        match cmd {
+            Command::AcceptInput => self.evaluate_calculator(),
+            Command::ExitInsertMode | Command::GoBack => self.go_back(),
+            Command::NextWidget | Command::PrevWidget => {}
+            _ => return false,
+        }
+        true
+    }
+
     // Command handler
     pub fn handle_command(&mut self, cmd: Command) {
+        if self.state.current_screen == CurrentScreen::HexEditor
+            && self.handle_hex_editor_command(&cmd)
+        {
+            return;
+        }
+        if self.state.current_screen == CurrentScreen::Calculator
+            && self.handle_calculator_command(&cmd)
+        {
+            return;
+        }
+        if self.state.current_screen == CurrentScreen::Regions
+            && self.handle_regions_command(&cmd)
+        {
+            return;
+        }
+
         match cmd {
             // Navigation commands
             Command::NextWidget => {
@@ -1170,6 +2072,9 @@ impl App {
                         self.show_process_list();
                     }
                 }
+                if self.ui.selected_input == Some(SelectedInput::Calculator) {
+                    self.evaluate_calculator();
+                }
             }
             Command::DeleteChar => {
                 if let Some(selected_input) = &self.ui.selected_input {
@@ -1181,6 +2086,9 @@ impl App {
                         self.show_process_list();
                     }
                 }
+                if self.ui.selected_input == Some(SelectedInput::Calculator) {
+                    self.evaluate_calculator();
+                }
             }
             Command::MoveCursorLeft => {
                 if let Some(selected_input) = &self.ui.selected_input {
@@ -1202,25 +2110,32 @@ impl App {
             // Scan commands
             Command::NewScan => {
                 if self.scan.is_some() {
-                    self.app_message =
-                        AppMessage::new("Starting new scan...", AppMessageType::Info);
+                    self.status.push(StatusEvent::Info(String::from("Starting new scan...")));
                     self.app_action = Some(AppAction::New);
                 }
             }
             Command::NextScan => {
                 if self.scan.is_some() {
-                    self.app_message =
-                        AppMessage::new("Starting next scan...", AppMessageType::Info);
+                    self.status.push(StatusEvent::Info(String::from("Starting next scan...")));
                     self.app_action = Some(AppAction::Next);
                 }
             }
             Command::RefreshScan => {
                 if self.scan.is_some() {
-                    self.app_message =
-                        AppMessage::new("Refreshing current scan...", AppMessageType::Info);
+                    self.status.push(StatusEvent::Info(String::from("Refreshing current scan...")));
                     self.app_action = Some(AppAction::Refresh);
                 }
             }
+            Command::CompareScan(mode) => {
+                if self.scan.is_some() {
+                    self.status.push(StatusEvent::Info(format!(
+                        "Filtering {} values...",
+                        format!("{mode:?}").to_lowercase()
+                    )));
+                    self.app_action = Some(AppAction::Compare(mode));
+                }
+            }
+            Command::UndoScan => self.undo_scan(),
             Command::ToggleReadWrite => {
                 if self.ui.selected_widgets.scan_view_selected_widget
                     == ScanViewWidget::PermissionsCheckbox
@@ -1230,10 +2145,9 @@ impl App {
                     if let Some(scan) = &mut self.scan
                         && let Err(e) = scan.set_mem_permissions(perms)
                     {
-                        self.app_message = AppMessage::new(
-                            &format!("Error setting memory permissions: {}", e),
-                            AppMessageType::Error,
-                        );
+                        self.status.push(StatusEvent::Failed(format!(
+                            "Error setting memory permissions: {}", e
+                        )));
                     }
                 }
             }
@@ -1257,8 +2171,7 @@ impl App {
                     {
                         self.ui.list_states.scan_watchlist.select(Some(0));
                     }
-                    self.app_message =
-                        AppMessage::new("Address added to watchlist", AppMessageType::Info);
+                    self.status.push(StatusEvent::Info(String::from("Address added to watchlist")));
                 }
             }
             Command::RemoveFromWatchlist => {
@@ -1268,16 +2181,20 @@ impl App {
                     && let Some(selected) = self.ui.list_states.scan_watchlist.selected()
                     && let Some(result) = scan.watchlist.get(selected)
                 {
-                    scan.remove_from_watchlist(result.address);
+                    let address = result.address;
+                    scan.remove_from_watchlist(address);
+                    self.dropped_watch.remove(&address);
                     self.ui.scroll_states.scan_watchlist_vertical = self
                         .ui
                         .scroll_states
                         .scan_watchlist_vertical
                         .content_length(scan.watchlist.len());
-                    self.app_message =
-                        AppMessage::new("Address removed from watchlist", AppMessageType::Info);
+                    self.status.push(StatusEvent::Info(String::from(
+                        "Address removed from watchlist",
+                    )));
                 }
             }
+            Command::PruneWatchlist => self.prune_watchlist(),
             Command::EditValue => match self.ui.selected_widgets.scan_view_selected_widget {
                 ScanViewWidget::ValueInput => self.insert_mode_for(SelectedInput::ScanValue),
                 ScanViewWidget::StartAddressInput => {
@@ -1304,15 +2221,13 @@ impl App {
 
                     if let Some(selected_value) = &self.selected_value {
                         if selected_value.is_read_only() {
-                            self.app_message = AppMessage::new(
+                            self.status.push(StatusEvent::Failed(String::from(
                                 "Cannot edit read-only memory region",
-                                AppMessageType::Error,
-                            );
+                            )));
                         } else {
                             match selected_value.get_string() {
                                 Err(e) => {
-                                    self.app_message =
-                                        AppMessage::new(&format!("{e}"), AppMessageType::Info);
+                                    self.status.push(StatusEvent::Info(format!("{e}")));
                                 }
                                 Ok(result_value) => {
                                     self.ui.input_buffers.result_value = result_value;
@@ -1322,10 +2237,9 @@ impl App {
                             }
                         }
                     } else {
-                        self.app_message = AppMessage::new(
+                        self.status.push(StatusEvent::Info(String::from(
                             "No result selected for editing.",
-                            AppMessageType::Info,
-                        );
+                        )));
                     }
                 }
                 ScanViewWidget::PermissionsCheckbox => {
@@ -1357,37 +2271,43 @@ impl App {
                             Ok(value) => {
                                 if let Some(clipboard) = &mut self.clipboard {
                                     if clipboard.set_text(&value).is_ok() {
-                                        self.app_message = AppMessage::new(
-                                            "Value copied to clipboard",
-                                            AppMessageType::Info,
-                                        );
+                                        self.status.push(StatusEvent::Copied);
                                     } else {
-                                        self.app_message = AppMessage::new(
+                                        self.status.push(StatusEvent::Failed(String::from(
                                             "Failed to copy to clipboard",
-                                            AppMessageType::Error,
-                                        );
+                                        )));
                                     }
                                 } else {
-                                    self.app_message = AppMessage::new(
+                                    self.status.push(StatusEvent::Failed(String::from(
                                         "Clipboard not available",
-                                        AppMessageType::Error,
-                                    );
+                                    )));
                                 }
                             }
                             Err(_) => {
-                                self.app_message = AppMessage::new(
+                                self.status.push(StatusEvent::Failed(String::from(
                                     "Failed to get value",
-                                    AppMessageType::Error,
-                                );
+                                )));
                             }
                         }
                     } else {
-                        self.app_message =
-                            AppMessage::new("No result selected", AppMessageType::Info);
+                        self.status.push(StatusEvent::Info(String::from("No result selected")));
                     }
                 }
             }
 
+            Command::OpenHexEditor => self.open_hex_editor(),
+            Command::OpenCalculator => self.open_calculator(),
+            Command::ShowRegions => self.open_regions(),
+            Command::RefreshRegions | Command::ScopeScanToRegion => {}
+            Command::UndoWrite => self.undo_write(),
+            Command::ShowHelp => self.show_help = true,
+            Command::PageUp
+            | Command::PageDown
+            | Command::ToggleHexEditMode
+            | Command::ToggleHexWriteMode
+            | Command::CommitHexEdits
+            | Command::DiscardHexEdits => {}
+
             // List commands
             Command::MoveUp => self.handle_navigate(Direction::Up),
             Command::MoveDown => self.handle_navigate(Direction::Down),
@@ -1412,6 +2332,15 @@ impl App {
     // Handle navigation (list movement)
     fn handle_navigate(&mut self, dir: Direction) {
         match self.state.current_screen {
+            CurrentScreen::Regions => {
+                utils::handle_list_navigation(
+                    dir,
+                    &mut self.ui.list_states.regions,
+                    self.regions.len(),
+                    Some(&mut self.ui.scroll_states.regions_vertical),
+                    &mut self.ui.last_g_press_time,
+                );
+            }
             CurrentScreen::ProcessList => {
                 // Only navigate the list if the ProcessList widget is selected
                 if self.ui.selected_widgets.process_list_selected_widget
@@ -1463,14 +2392,11 @@ impl App {
                                         Some(&self.ui.input_buffers.scan_value),
                                     )
                                 {
-                                    self.app_message = AppMessage::new(
-                                        &format!(
+                                    self.status.push(StatusEvent::Failed(format!(
                                             "Invalid value: {:.10} for type: {}",
                                             self.ui.input_buffers.result_value,
                                             scan.value_type.get_string(),
-                                        ),
-                                        AppMessageType::Error,
-                                    );
+                                        )));
                                 }
 
                                 // when string or hex type is selected ReadSize option should be available
@@ -1503,7 +2429,7 @@ impl App {
                                     self.ui.selected_widgets.scan_view_widgets.remove(idx);
                                 }
 
-                                self.app_message = AppMessage::default();
+                                self.status.clear();
                             }
                         }
                         _ => {}
@@ -1537,15 +2463,25 @@ impl App {
                 return Ok(());
             }
 
+            self.poll_compare();
+            self.status.receive();
             terminal.draw(|f| super::ui::draw_ui(f, self))?;
 
-            if let Some(app_action) = &mut self.app_action {
+            // actions wait for a running compare filter, it has the scan
+            if self.compare.is_none()
+                && let Some(app_action) = &mut self.app_action
+            {
                 match app_action {
                     AppAction::New => self.new_scan(),
                     AppAction::Next => self.next_scan(),
                     AppAction::Refresh => self.refresh_scan(),
+                    AppAction::Compare(mode) => {
+                        let mode = *mode;
+                        self.compare_scan(mode)
+                    }
                 }
                 self.app_action = None;
+                self.update_dropped_watch();
                 continue;
             }
 
@@ -1567,6 +2503,12 @@ impl App {
                     continue;
                 }
 
+                // any key closes the help overlay without doing anything else
+                if self.show_help {
+                    self.show_help = false;
+                    continue;
+                }
+
                 match self.ui.input_mode {
                     InputMode::Normal => self.handle_normal_mode_event(key),
                     InputMode::Insert => self.handle_insert_mode_event(key),
@@ -1574,8 +2516,48 @@ impl App {
             }
 
             if last_tick.elapsed() >= tick_rate {
+                self.refresh_watchlist();
+                if self.state.current_screen == CurrentScreen::HexEditor {
+                    self.refresh_hex_editor();
+                }
+                // keep the value under the calculator's address live
+                if self.state.current_screen == CurrentScreen::Calculator {
+                    self.evaluate_calculator();
+                }
                 last_tick = Instant::now();
             }
         }
     }
 }
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_help_lists_every_binding() {
+        let bindings = KeyBindings::default();
+        let sections = bindings.help_sections();
+        let scan_view = &sections.iter().find(|(title, _)| *title == "Scan view").unwrap().1;
+        // keys of the same command share a row
+        assert!(scan_view.contains(&(String::from("j/↓"), "Down")));
+        assert!(scan_view.contains(&(String::from("Ctrl+z"), "Undo the last write")));
+        assert!(scan_view.contains(&(String::from("?"), "Show this help")));
+
+        let listed: usize = sections
+            .iter()
+            .flat_map(|(_, rows)| rows)
+            .map(|(keys, _)| keys.split('/').count())
+            .sum();
+        let bound = bindings.global.len()
+            + bindings.process_list_normal.len()
+            + bindings.scan_view_normal.len()
+            + bindings.scan_results_normal.len()
+            + bindings.hex_editor.len()
+            + bindings.regions.len()
+            + bindings.insert_mode.len()
+            + bindings.exiting_screen.len();
+        assert_eq!(listed, bound);
+        assert_eq!(KeyPress::new(KeyCode::BackTab, KeyModifiers::SHIFT).label(), "Shift+Tab");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::core::address::resolve_address;
+use crate::core::config::{self, resolve_read_size};
+use crate::core::mem::read_memory_address;
+use crate::core::proc::{Module, modules};
+use crate::core::scan::ValueType;
+
+/// What an address expression typed into the calculator pane resolves to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calculation {
+    pub address: u64,
+    /// `module+0xoffset` when the address falls into a mapped file
+    pub module_offset: Option<String>,
+    /// Value at the address decoded as the selected type, or why it couldn't be read
+    pub value: Result<String, String>,
+}
+
+/// The address as an offset into the module it falls into, e.g. `libc.so.6+0x1234`
+pub fn module_offset(modules: &[Module], address: u64) -> Option<String> {
+    modules
+        .iter()
+        .find(|m| m.base <= address && address < m.end)
+        .map(|m| format!("{}+0x{:x}", m.name, address - m.base))
+}
+
+/// Resolve `expression` like `cli read --address` does and read the value there, `read_size`
+/// overrides the size for strings and hex. Only a bad expression is an error, an address
+/// that can't be read still resolves with the read error as its value.
+pub fn evaluate(
+    pid: u32,
+    expression: &str,
+    value_type: ValueType,
+    read_size: Option<usize>,
+) -> Result<Calculation, String> {
+    let address = resolve_address(Some(pid), expression).map_err(|e| e.to_string())?;
+    let size = match value_type {
+        ValueType::String | ValueType::Hex => read_size,
+        _ => None,
+    };
+    let size = resolve_read_size(size, value_type, Some(pid), config::current());
+
+    let value = match read_memory_address(pid, address as usize, size) {
+        Ok(bytes) => value_type.get_value_string(&bytes).map_err(|e| e.to_string()),
+        Err(e) => Err(format!("0x{address:x} can't be read: {e}")),
+    };
+
+    Ok(Calculation {
+        address,
+        module_offset: module_offset(&modules(pid), address),
+        value,
+    })
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_evaluate_expression() {
+        let pid = std::process::id();
+        let value = 0x1234_5678_u32;
+        let address = &value as *const u32 as u64;
+
+        let calculation = evaluate(pid, &format!("0x{:x}-4+4", address), ValueType::U32, None).unwrap();
+        assert_eq!(calculation.address, address);
+        assert_eq!(calculation.value, Ok(value.to_string()));
+
+        let module = modules(pid).into_iter().next().expect("the test binary is mapped");
+        let calculation = evaluate(pid, &format!("{}+0x10", module.name), ValueType::Bool, None);
+        assert_eq!(
+            calculation.map(|c| c.module_offset),
+            Ok(Some(format!("{}+0x10", module.name)))
+        );
+        assert_eq!(module_offset(std::slice::from_ref(&module), module.end), None);
+
+        assert!(evaluate(pid, "0x10+", ValueType::U32, None).unwrap_err().contains("missing a term"));
+        let unmapped = evaluate(pid, "0x8", ValueType::U32, None).unwrap();
+        assert!(unmapped.value.is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+pub const BYTES_PER_ROW: u64 = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HexEditMode {
+    Hex,
+    Ascii,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HexWriteMode {
+    /// Edited bytes are kept until the user commits them
+    Batch,
+    /// Every edited byte is written right away
+    Immediate,
+}
+
+/// State of the hex editor pane over the address range `start..end`.
+///
+/// Only the visible rows are read from memory (see [`HexEditor::window`]), edits are
+/// kept in `pending` until they are taken with [`HexEditor::take_pending_runs`].
+#[derive(Clone, Debug)]
+pub struct HexEditor {
+    pub start: u64,
+    pub end: u64,
+    pub cursor: u64,
+    pub top: u64,
+    pub visible_rows: u64,
+    pub edit_mode: HexEditMode,
+    pub write_mode: HexWriteMode,
+    pub writable: bool,
+    pub pending: BTreeMap<u64, u8>,
+    data_start: u64,
+    data: Vec<u8>,
+    low_nibble: bool,
+}
+
+impl HexEditor {
+    pub fn new(start: u64, end: u64, cursor: u64, writable: bool) -> Self {
+        let mut editor = HexEditor {
+            start,
+            end: end.max(start + 1),
+            cursor: start,
+            top: start,
+            visible_rows: 16,
+            edit_mode: HexEditMode::Hex,
+            write_mode: HexWriteMode::Batch,
+            writable,
+            pending: BTreeMap::new(),
+            data_start: start,
+            data: vec![],
+            low_nibble: false,
+        };
+        editor.move_to(cursor);
+        editor
+    }
+
+    fn row_of(&self, addr: u64) -> u64 {
+        self.start + (addr - self.start) / BYTES_PER_ROW * BYTES_PER_ROW
+    }
+
+    /// Address and size of the memory currently shown, to be read on refresh
+    pub fn window(&self) -> (u64, usize) {
+        let size = (self.visible_rows * BYTES_PER_ROW).min(self.end - self.top);
+        (self.top, size as usize)
+    }
+
+    pub fn set_data(&mut self, data_start: u64, data: Vec<u8>) {
+        self.data_start = data_start;
+        self.data = data;
+    }
+
+    pub fn set_visible_rows(&mut self, rows: u64) {
+        self.visible_rows = rows.max(1);
+        self.scroll_to_cursor();
+    }
+
+    /// Byte at `addr` as it will be after commit, `None` if it couldn't be read
+    pub fn byte_at(&self, addr: u64) -> Option<u8> {
+        if let Some(byte) = self.pending.get(&addr) {
+            return Some(*byte);
+        }
+        let offset = addr.checked_sub(self.data_start)? as usize;
+        self.data.get(offset).copied()
+    }
+
+    pub fn is_pending(&self, addr: u64) -> bool {
+        self.pending.contains_key(&addr)
+    }
+
+    pub fn move_to(&mut self, addr: u64) {
+        self.cursor = addr.clamp(self.start, self.end - 1);
+        self.low_nibble = false;
+        self.scroll_to_cursor();
+    }
+
+    pub fn move_by(&mut self, delta: i64) {
+        let target = if delta < 0 {
+            self.cursor.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.cursor.saturating_add(delta as u64)
+        };
+        self.move_to(target);
+    }
+
+    pub fn page_up(&mut self) {
+        self.move_by(-((self.visible_rows * BYTES_PER_ROW) as i64));
+    }
+
+    pub fn page_down(&mut self) {
+        self.move_by((self.visible_rows * BYTES_PER_ROW) as i64);
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let cursor_row = self.row_of(self.cursor);
+        let page = (self.visible_rows - 1) * BYTES_PER_ROW;
+        if cursor_row < self.top {
+            self.top = cursor_row;
+        } else if cursor_row > self.top + page {
+            self.top = cursor_row - page;
+        }
+    }
+
+    pub fn toggle_edit_mode(&mut self) {
+        self.edit_mode = match self.edit_mode {
+            HexEditMode::Hex => HexEditMode::Ascii,
+            HexEditMode::Ascii => HexEditMode::Hex,
+        };
+        self.low_nibble = false;
+    }
+
+    pub fn toggle_write_mode(&mut self) {
+        self.write_mode = match self.write_mode {
+            HexWriteMode::Batch => HexWriteMode::Immediate,
+            HexWriteMode::Immediate => HexWriteMode::Batch,
+        };
+    }
+
+    /// Apply a typed character at the cursor. In hex mode two digits make up a byte,
+    /// in ASCII mode every printable character replaces a byte. Returns `false` when
+    /// the character isn't valid for the current mode.
+    pub fn input(&mut self, c: char) -> bool {
+        let current = self.byte_at(self.cursor).unwrap_or(0);
+        match self.edit_mode {
+            HexEditMode::Hex => {
+                let Some(digit) = c.to_digit(16) else {
+                    return false;
+                };
+                let digit = digit as u8;
+                if self.low_nibble {
+                    self.pending.insert(self.cursor, (current & 0xf0) | digit);
+                    self.move_by(1);
+                } else {
+                    self.pending.insert(self.cursor, (digit << 4) | (current & 0x0f));
+                    self.low_nibble = true;
+                }
+            }
+            HexEditMode::Ascii => {
+                if !c.is_ascii() || c.is_ascii_control() {
+                    return false;
+                }
+                self.pending.insert(self.cursor, c as u8);
+                self.move_by(1);
+            }
+        }
+        true
+    }
+
+    pub fn discard_pending(&mut self) -> usize {
+        self.low_nibble = false;
+        std::mem::take(&mut self.pending).len()
+    }
+
+    /// Take pending edits grouped into runs of consecutive bytes, so each run can be
+    /// written with a single call. A byte with only its first hex digit typed stays
+    /// pending, the next digit completes it.
+    pub fn take_pending_runs(&mut self) -> Vec<(u64, Vec<u8>)> {
+        let mut pending = std::mem::take(&mut self.pending);
+        if self.low_nibble
+            && let Some(half) = pending.remove(&self.cursor)
+        {
+            self.pending.insert(self.cursor, half);
+        }
+        let mut runs: Vec<(u64, Vec<u8>)> = vec![];
+        for (addr, byte) in pending {
+            match runs.last_mut() {
+                Some((start, bytes)) if *start + bytes.len() as u64 == addr => bytes.push(byte),
+                _ => runs.push((addr, vec![byte])),
+            }
+
+            // keep the committed value visible until the next refresh
+            if let Some(offset) = addr.checked_sub(self.data_start)
+                && let Some(b) = self.data.get_mut(offset as usize)
+            {
+                *b = byte;
+            }
+        }
+        runs
+    }
+
+    /// Put edits from a run that failed to write back into the pending set
+    pub fn restore_run(&mut self, addr: u64, bytes: &[u8]) {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.pending.entry(addr + i as u64).or_insert(*byte);
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_hex_input_edits_nibbles_and_advances() {
+        let mut editor = HexEditor::new(0x1000, 0x1100, 0x1000, true);
+        editor.set_data(0x1000, vec![0xaa; 0x20]);
+
+        assert!(editor.input('1'));
+        assert_eq!(editor.byte_at(0x1000), Some(0x1a));
+        assert_eq!(editor.cursor, 0x1000);
+        assert!(editor.input('f'));
+        assert_eq!(editor.byte_at(0x1000), Some(0x1f));
+        assert_eq!(editor.cursor, 0x1001);
+        assert!(!editor.input('z'));
+        assert!(editor.is_pending(0x1000));
+        assert!(!editor.is_pending(0x1001));
+    }
+
+    #[test]
+    fn test_ascii_input() {
+        let mut editor = HexEditor::new(0x1000, 0x1100, 0x1004, true);
+        editor.toggle_edit_mode();
+        assert!(editor.input('h'));
+        assert!(editor.input('i'));
+        assert!(!editor.input('\n'));
+        assert_eq!(editor.byte_at(0x1004), Some(b'h'));
+        assert_eq!(editor.byte_at(0x1005), Some(b'i'));
+        assert_eq!(editor.cursor, 0x1006);
+    }
+
+    #[test]
+    fn test_cursor_clamped_and_view_scrolls() {
+        let mut editor = HexEditor::new(0x1000, 0x1100, 0x1000, true);
+        editor.set_visible_rows(4);
+        editor.move_by(-1);
+        assert_eq!(editor.cursor, 0x1000);
+
+        editor.move_by(BYTES_PER_ROW as i64 * 5);
+        assert_eq!(editor.cursor, 0x1050);
+        assert_eq!(editor.top, 0x1020);
+        assert_eq!(editor.window(), (0x1020, 0x40));
+
+        editor.page_down();
+        editor.page_down();
+        editor.page_down();
+        assert_eq!(editor.cursor, 0x10ff);
+        assert_eq!(editor.top, 0x10c0);
+
+        editor.move_to(0x1000);
+        assert_eq!(editor.top, 0x1000);
+    }
+
+    #[test]
+    fn test_pending_runs_are_grouped() {
+        let mut editor = HexEditor::new(0x1000, 0x1100, 0x1000, true);
+        editor.set_data(0x1000, vec![0; 0x20]);
+        editor.toggle_edit_mode();
+        editor.input('a');
+        editor.input('b');
+        editor.move_to(0x1010);
+        editor.input('c');
+
+        let runs = editor.take_pending_runs();
+        assert_eq!(runs, vec![(0x1000, b"ab".to_vec()), (0x1010, b"c".to_vec())]);
+        assert!(editor.pending.is_empty());
+        assert_eq!(editor.byte_at(0x1001), Some(b'b'));
+
+        editor.restore_run(0x1010, b"c");
+        assert_eq!(editor.discard_pending(), 1);
+    }
+
+    #[test]
+    fn test_half_typed_byte_is_not_committed() {
+        let mut editor = HexEditor::new(0x1000, 0x1100, 0x1000, true);
+        editor.set_data(0x1000, vec![0xaa; 0x20]);
+        // three digits: one whole byte and the first nibble of the next
+        for c in ['1', '2', '3'] {
+            editor.input(c);
+        }
+        assert_eq!(editor.take_pending_runs(), vec![(0x1000, vec![0x12])]);
+        assert!(editor.is_pending(0x1001));
+        assert_eq!(editor.byte_at(0x1001), Some(0x3a));
+
+        // the next digit still goes into the low nibble of the same byte
+        editor.input('4');
+        assert_eq!(editor.cursor, 0x1002);
+        assert_eq!(editor.take_pending_runs(), vec![(0x1001, vec![0x34])]);
+        assert!(editor.pending.is_empty());
+    }
+}
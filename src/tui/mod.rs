@@ -11,11 +11,15 @@ use ratatui::{
 };
 
 mod app;
+mod calculator;
+mod hex_editor;
+mod status;
 mod ui;
 mod utils;
 use app::App;
 
-pub fn run() -> Result<(), Box<dyn Error>> {
+/// Run the TUI. The writes made in it are reverted on exit unless `keep_writes` is set.
+pub fn run(keep_writes: bool) -> Result<(), Box<dyn Error>> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -24,6 +28,7 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::new();
+    app.keep_writes = keep_writes;
     app.run(&mut terminal)?;
     // restore terminal
     disable_raw_mode()?;
@@ -0,0 +1,167 @@
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::Paragraph,
+};
+
+use crate::core::scan::CompareMode;
+
+/// How long the status line shows a result before it clears
+pub const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of an action, shown in the message box of a screen, or on the status line of
+/// the screens without one
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatusEvent {
+    /// Anything else worth telling, e.g. how far a running filter got
+    Info(String),
+    ScanFinished { matches: usize },
+    Filtered { mode: CompareMode, remaining: usize },
+    Wrote { address: u64, bytes: usize },
+    /// A journaled write was undone
+    Restored { address: u64, bytes: usize },
+    Copied,
+    Failed(String),
+}
+
+impl StatusEvent {
+    pub fn text(&self) -> String {
+        match self {
+            StatusEvent::Info(message) => message.clone(),
+            StatusEvent::ScanFinished { matches } => format!("Scan found {matches} match(es)"),
+            StatusEvent::Filtered { mode, remaining } => {
+                format!("{mode:?}: {remaining} result(s) left")
+            }
+            StatusEvent::Wrote { address, bytes } => format!("Wrote {bytes} byte(s) at 0x{address:x}"),
+            StatusEvent::Restored { address, bytes } => {
+                format!("Restored {bytes} byte(s) at 0x{address:x}")
+            }
+            StatusEvent::Copied => String::from("Copied the value to the clipboard"),
+            StatusEvent::Failed(message) => message.clone(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, StatusEvent::Failed(_))
+    }
+}
+
+/// Last action result. The message boxes show it until the next one, the status line
+/// for [`STATUS_TIMEOUT`]. Worker threads post theirs through [`StatusLine::sender`].
+#[derive(Debug)]
+pub struct StatusLine {
+    last: Option<(StatusEvent, Instant)>,
+    sender: Sender<StatusEvent>,
+    posted: Receiver<StatusEvent>,
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        let (sender, posted) = channel();
+        StatusLine { last: None, sender, posted }
+    }
+}
+
+impl StatusLine {
+    pub fn push(&mut self, event: StatusEvent) {
+        self.push_at(event, Instant::now());
+    }
+
+    pub fn push_at(&mut self, event: StatusEvent, now: Instant) {
+        self.last = Some((event, now));
+    }
+
+    /// Forget the last event
+    pub fn clear(&mut self) {
+        self.last = None;
+    }
+
+    /// For a worker thread to post events, they show once [`StatusLine::receive`] runs
+    pub fn sender(&self) -> Sender<StatusEvent> {
+        self.sender.clone()
+    }
+
+    /// Take in the events worker threads posted, in the order they came
+    pub fn receive(&mut self) {
+        while let Ok(event) = self.posted.try_recv() {
+            self.push(event);
+        }
+    }
+
+    /// The last event however old, for the message boxes
+    pub fn message(&self) -> Option<&StatusEvent> {
+        self.last.as_ref().map(|(event, _)| event)
+    }
+
+    /// The event to show at `now`, `None` once it timed out
+    pub fn visible(&self, now: Instant) -> Option<&StatusEvent> {
+        self.last
+            .as_ref()
+            .filter(|(_, at)| now.duration_since(*at) < STATUS_TIMEOUT)
+            .map(|(event, _)| event)
+    }
+
+    /// Draw the visible event into `area`, errors in red
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let Some(event) = self.visible(Instant::now()) else {
+            return;
+        };
+        let style = if event.is_error() {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+        frame.render_widget(Paragraph::new(Line::styled(format!(" {}", event.text()), style)), area);
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_status_line_clears() {
+        let start = Instant::now();
+        let mut status = StatusLine::default();
+        assert_eq!(status.visible(start), None);
+
+        status.push_at(StatusEvent::Wrote { address: 0x1000, bytes: 4 }, start);
+        let event = status.visible(start + Duration::from_secs(1)).unwrap();
+        assert_eq!(event.text(), "Wrote 4 byte(s) at 0x1000");
+        assert!(!event.is_error());
+        assert_eq!(status.visible(start + STATUS_TIMEOUT), None);
+
+        // the message boxes keep it
+        assert_eq!(
+            status.message().map(StatusEvent::text).as_deref(),
+            Some("Wrote 4 byte(s) at 0x1000")
+        );
+
+        // a newer event replaces the old one and restarts the timeout
+        status.push_at(StatusEvent::Failed(String::from("boom")), start + STATUS_TIMEOUT);
+        assert!(status.visible(start + STATUS_TIMEOUT).unwrap().is_error());
+        status.clear();
+        assert_eq!(status.message(), None);
+
+        // posted from another thread
+        let sender = status.sender();
+        std::thread::spawn(move || {
+            sender.send(StatusEvent::Info(String::from("one"))).unwrap();
+            sender.send(StatusEvent::Info(String::from("two"))).unwrap();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(status.message(), None);
+        status.receive();
+        assert_eq!(status.message(), Some(&StatusEvent::Info(String::from("two"))));
+        assert_eq!(
+            StatusEvent::Filtered { mode: CompareMode::Increased, remaining: 3 }.text(),
+            "Increased: 3 result(s) left"
+        );
+    }
+}
@@ -10,8 +10,15 @@ use ratatui::{
 };
 
 use crate::{
-    core::scan::ValueType,
-    tui::app::{App, AppMessageType, CurrentScreen, InputMode, ScanViewWidget, SelectedInput},
+    core::{
+        mem::{MemoryRegionPerms, format_perms},
+        scan::ValueType,
+    },
+    tui::{
+        app::{App, CurrentScreen, InputMode, ScanViewWidget, SelectedInput},
+        hex_editor::{BYTES_PER_ROW, HexEditMode, HexWriteMode},
+        status::StatusEvent,
+    },
 };
 
 pub fn draw_process_list(frame: &mut Frame, app: &mut App) {
@@ -80,6 +87,7 @@ pub fn draw_process_list(frame: &mut Frame, app: &mut App) {
         Span::from("Tab/Shift Tab: Change Pane | ").fg(Color::Green),
         Span::from("r: Refresh | ").fg(Color::Green),
         Span::from("Enter: Select | ").fg(Color::Green),
+        Span::from("?: Help | ").fg(Color::Green),
         Span::from("q: Quit").fg(Color::Green),
     ]);
 
@@ -98,11 +106,21 @@ fn get_active_widget_style(app: &App, widget: ScanViewWidget) -> Style {
     }
 }
 
+// the message boxes show the last status event until the next one
+fn get_message_text(app: &App) -> String {
+    app.status.message().map(StatusEvent::text).unwrap_or_default()
+}
+
+fn get_message_box_style(app: &App) -> Style {
+    if app.status.message().is_some_and(StatusEvent::is_error) {
+        Style::default().bg(Color::Red)
+    } else {
+        Style::default()
+    }
+}
+
 fn get_message_style(app: &App) -> Style {
-    let mut style = match app.app_message.msg_type {
-        AppMessageType::Info => Style::default(),
-        AppMessageType::Error => Style::default().bg(Color::Red),
-    };
+    let mut style = get_message_box_style(app);
 
     if app.ui.selected_widgets.scan_view_selected_widget == ScanViewWidget::AppMessage {
         style = style.fg(Color::Yellow);
@@ -135,6 +153,8 @@ pub fn draw_scan_screen(frame: &mut Frame, app: &mut App) {
     if let Some(scan) = &app.scan {
         scan_result_items = &scan.results;
         watchlist_items = &scan.watchlist;
+    } else if let Some(task) = &app.compare {
+        scan_result_items = &task.previous;
     }
 
     let result_items: Vec<ListItem> = scan_result_items
@@ -178,31 +198,40 @@ pub fn draw_scan_screen(frame: &mut Frame, app: &mut App) {
         &mut app.ui.scroll_states.scan_results_vertical,
     );
 
-    // Watchlist
+    // Watchlist, entries the last scan pass dropped from the results are crossed out
     let watchlist_items_display: Vec<ListItem> = watchlist_items
         .iter()
         .map(|result| {
-            let color = if result.is_read_only() {
-                Color::Yellow
+            let dropped = app.dropped_watch.contains(&result.address);
+            let style = if dropped {
+                Style::new().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)
+            } else if result.is_read_only() {
+                Style::new().fg(Color::Yellow)
             } else {
-                Color::Green
+                Style::new().fg(Color::Green)
             };
             ListItem::new(Line::from(format!(
-                "0x{:x} | {}",
+                "{}0x{:x} | {}",
+                if dropped { "✗ " } else { "" },
                 result.address,
                 result.get_string().unwrap_or("TypeMismatch".to_owned())
             )))
-            .style(Style::new().fg(color))
+            .style(style)
         })
         .collect();
 
+    let dropped = watchlist_items.iter().filter(|w| app.dropped_watch.contains(&w.address)).count();
+    let watchlist_title = match dropped {
+        0 => String::from("Watchlist"),
+        dropped => format!("Watchlist - {dropped} no longer in the results, p: prune"),
+    };
     let watchlist_widget = List::new(watchlist_items_display)
         .highlight_style(Style::new().bg(Color::Blue).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ")
         .highlight_spacing(HighlightSpacing::Always)
         .block(
             Block::bordered()
-                .title("Watchlist")
+                .title(watchlist_title)
                 .style(get_active_widget_style(app, ScanViewWidget::WatchList)),
         );
 
@@ -327,7 +356,7 @@ pub fn draw_scan_screen(frame: &mut Frame, app: &mut App) {
         .block(Block::bordered().title("End Address - hex (optional)"));
     frame.render_widget(end_address_input, options_view_chunks[3]);
 
-    let msg_box = Paragraph::new(app.app_message.msg.as_str())
+    let msg_box = Paragraph::new(get_message_text(app))
         .style(get_message_style(app))
         .block(Block::bordered().title("App Message"));
     frame.render_widget(msg_box, options_view_chunks[4]);
@@ -387,24 +416,38 @@ pub fn draw_scan_screen(frame: &mut Frame, app: &mut App) {
         ]);
     }
 
-    if app.ui.selected_widgets.scan_view_selected_widget == ScanViewWidget::ScanResults {
-        help_text_items.extend(vec![Span::from("w: Add to watchlist | ").fg(Color::Green)]);
-    }
-
-    if app.ui.selected_widgets.scan_view_selected_widget == ScanViewWidget::WatchList {
-        help_text_items.push(Span::from("d: Remove from watchlist | ").fg(Color::Green));
+    if !app.undo_results.is_empty() {
+        help_text_items.push(Span::from("z: Undo | ").fg(Color::Green));
     }
+    help_text_items.push(Span::from("Ctrl+z: Undo write | ").fg(Color::Green));
 
     match app.ui.selected_widgets.scan_view_selected_widget {
-        ScanViewWidget::ScanResults | ScanViewWidget::WatchList => {
+        ScanViewWidget::ScanResults => {
             help_text_items.extend(vec![
+                Span::from("i/d/c/u: Increased/Decreased/Changed/Unchanged | ").fg(Color::Green),
+                Span::from("w: Add to watchlist | ").fg(Color::Green),
+                Span::from("y: Copy | ").fg(Color::Green),
+                Span::from("Enter: Update Value | ").fg(Color::Green),
+                Span::from("x: Hex Editor | ").fg(Color::Green),
+            ]);
+        }
+        ScanViewWidget::WatchList => {
+            help_text_items.extend(vec![
+                Span::from("d: Remove from watchlist | ").fg(Color::Green),
+                Span::from("p: Prune dropped | ").fg(Color::Green),
                 Span::from("c: Copy | ").fg(Color::Green),
                 Span::from("Enter/u: Update Value | ").fg(Color::Green),
+                Span::from("x: Hex Editor | ").fg(Color::Green),
             ]);
         }
         _ => {}
     }
 
+    if app.ui.input_mode == InputMode::Normal {
+        help_text_items.push(Span::from("=: Calculator | ").fg(Color::Green));
+        help_text_items.push(Span::from("m: Regions | ").fg(Color::Green));
+        help_text_items.push(Span::from("?: Help | ").fg(Color::Green));
+    }
     help_text_items.push(Span::from("q: Quit").fg(Color::Green));
 
     let help_bar = Paragraph::new(Line::from(help_text_items))
@@ -465,6 +508,249 @@ pub fn draw_value_editing_screen(frame: &mut Frame, app: &mut App) {
     frame.render_widget(value_input, area);
 }
 
+pub fn draw_hex_editor_screen(frame: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(frame.area());
+
+    let Some(editor) = &mut app.hex_editor else {
+        return;
+    };
+    editor.set_visible_rows(chunks[0].height.saturating_sub(2) as u64);
+
+    let hex_mode = editor.edit_mode == HexEditMode::Hex;
+    let cursor_style = |active: bool| {
+        if active {
+            Style::new().bg(Color::Blue).add_modifier(Modifier::BOLD)
+        } else {
+            Style::new().add_modifier(Modifier::UNDERLINED)
+        }
+    };
+
+    let (window_start, window_size) = editor.window();
+    let mut lines = vec![];
+    let mut row = window_start;
+    while row < window_start + window_size as u64 {
+        let mut hex_spans = vec![Span::from(format!("0x{:012x}  ", row)).fg(Color::DarkGray)];
+        let mut ascii_spans = vec![Span::from(" |")];
+
+        for i in 0..BYTES_PER_ROW {
+            let addr = row + i;
+            if i == BYTES_PER_ROW / 2 {
+                hex_spans.push(Span::from(" "));
+            }
+            if addr >= editor.end {
+                hex_spans.push(Span::from("   "));
+                continue;
+            }
+
+            let byte = editor.byte_at(addr);
+            let mut style = match byte {
+                None => Style::new().fg(Color::DarkGray),
+                Some(_) if editor.is_pending(addr) => Style::new().fg(Color::Yellow),
+                Some(_) => Style::new().fg(Color::Green),
+            };
+            let mut ascii_style = style;
+            if addr == editor.cursor {
+                style = style.patch(cursor_style(hex_mode));
+                ascii_style = ascii_style.patch(cursor_style(!hex_mode));
+            }
+
+            let (hex, ascii) = match byte {
+                None => (String::from("??"), '.'),
+                Some(b) if b.is_ascii_graphic() || b == b' ' => (format!("{b:02x}"), b as char),
+                Some(b) => (format!("{b:02x}"), '.'),
+            };
+            hex_spans.push(Span::styled(hex, style));
+            hex_spans.push(Span::from(" "));
+            ascii_spans.push(Span::styled(ascii.to_string(), ascii_style));
+        }
+
+        ascii_spans.push(Span::from("|"));
+        hex_spans.extend(ascii_spans);
+        lines.push(Line::from(hex_spans));
+        row += BYTES_PER_ROW;
+    }
+
+    let title = format!(
+        " Hex Editor - 0x{:x}-0x{:x} | {} | {} | {} pending{} ",
+        editor.start,
+        editor.end,
+        if hex_mode { "HEX" } else { "ASCII" },
+        match editor.write_mode {
+            HexWriteMode::Batch => "batch",
+            HexWriteMode::Immediate => "immediate",
+        },
+        editor.pending.len(),
+        if editor.writable { "" } else { " | read-only" },
+    );
+    let editor_widget = Paragraph::new(lines).block(
+        Block::bordered()
+            .title(title)
+            .style(Style::default().fg(Color::Yellow)),
+    );
+    frame.render_widget(editor_widget, chunks[0]);
+
+    let msg_box = Paragraph::new(get_message_text(app))
+        .style(get_message_box_style(app))
+        .block(Block::bordered().title("App Message"));
+    frame.render_widget(msg_box, chunks[1]);
+
+    let help_text = Line::from(vec![
+        Span::from("Arrows: Move | ").fg(Color::Green),
+        Span::from("PgUp/PgDn: Scroll | ").fg(Color::Green),
+        Span::from("Tab: Hex/ASCII | ").fg(Color::Green),
+        Span::from("Enter: Commit | ").fg(Color::Green),
+        Span::from("Ctrl+b: Batch/Immediate | ").fg(Color::Green),
+        Span::from("Ctrl+u: Discard | ").fg(Color::Green),
+        Span::from("Ctrl+z: Undo write | ").fg(Color::Green),
+        Span::from("F1: Help | ").fg(Color::Green),
+        Span::from("Esc: Back").fg(Color::Green),
+    ]);
+
+    let help_bar = Paragraph::new(help_text)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::NONE));
+
+    frame.render_widget(help_bar, chunks[2]);
+}
+
+pub fn draw_calculator_screen(frame: &mut Frame, app: &mut App) {
+    frame.render_widget(Clear, frame.area());
+    let area = centered_rect(60, 40, frame.area());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    let input = Paragraph::new(app.ui.input_buffers.calculator.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::bordered()
+                .title(" Address Calculator - e.g. libc.so.6+0x1234-8 ")
+                .title_bottom(" Enter: Re-read | Esc: Back ")
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+        );
+    frame.set_cursor_position(Position::new(
+        chunks[0].x + app.ui.character_index as u16 + 1,
+        chunks[0].y + 1,
+    ));
+    frame.render_widget(input, chunks[0]);
+
+    let value_type = app
+        .scan
+        .as_ref()
+        .map(|scan| scan.value_type)
+        .unwrap_or(ValueType::U32);
+    let error_style = Style::default().fg(Color::Red);
+    let lines: Vec<Line> = match &app.calculation {
+        None => vec![Line::from("Type an address, module names stand for their base")],
+        Some(Err(e)) => e.lines().map(|l| Line::styled(l.to_string(), error_style)).collect(),
+        Some(Ok(calculation)) => vec![
+            Line::from(format!("Address: 0x{:x}", calculation.address)),
+            Line::from(format!(
+                "Module:  {}",
+                calculation.module_offset.as_deref().unwrap_or("-")
+            )),
+            match &calculation.value {
+                Ok(value) => Line::from(format!("Value:   {value}")),
+                Err(e) => Line::styled(format!("Value:   {e}"), error_style),
+            },
+        ],
+    };
+    let result = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::bordered()
+            .title(format!(" Result - {} ", value_type.get_string()))
+            .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+    );
+    frame.render_widget(result, chunks[1]);
+}
+
+pub fn draw_regions_screen(frame: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(frame.area());
+
+    // regions of the executable stand out, the rest is coloured by whether it can be written
+    let items: Vec<ListItem> = app
+        .regions
+        .iter()
+        .map(|entry| {
+            let region = &entry.region;
+            let line = format!(
+                "0x{:012x}-0x{:012x} {:<3} {:>10x}  {}",
+                region.start,
+                region.end,
+                format_perms(&region.perms),
+                region.end - region.start,
+                entry.mapping.as_deref().unwrap_or(""),
+            );
+            let style = if entry.main_module {
+                Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else if region.perms.contains(&MemoryRegionPerms::Write) {
+                Style::new().fg(Color::Green)
+            } else {
+                Style::new().fg(Color::DarkGray)
+            };
+            ListItem::new(Line::from(line)).style(style)
+        })
+        .collect();
+
+    let title = match &app.selected_process {
+        Some(process) => format!(" Regions - {} ({}) | {} ", process.name, process.pid, app.regions.len()),
+        None => String::from(" Regions "),
+    };
+    let list_widget = List::new(items)
+        .highlight_style(Style::new().bg(Color::Blue).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ")
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(
+            Block::bordered()
+                .title(title)
+                .style(Style::default().fg(Color::Yellow)),
+        );
+    frame.render_stateful_widget(list_widget, chunks[0], &mut app.ui.list_states.regions);
+
+    frame.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓")),
+        chunks[0],
+        &mut app.ui.scroll_states.regions_vertical,
+    );
+
+    let msg_box = Paragraph::new(get_message_text(app))
+        .style(get_message_box_style(app))
+        .block(Block::bordered().title("App Message"));
+    frame.render_widget(msg_box, chunks[1]);
+
+    let help_text = Line::from(vec![
+        Span::from("↑/k: Up | ").fg(Color::Green),
+        Span::from("↓/j: Down | ").fg(Color::Green),
+        Span::from("s: Scan this region | ").fg(Color::Green),
+        Span::from("Enter: Hex Editor | ").fg(Color::Green),
+        Span::from("r: Refresh | ").fg(Color::Green),
+        Span::from("?: Help | ").fg(Color::Green),
+        Span::from("Esc: Back").fg(Color::Green),
+    ]);
+
+    let help_bar = Paragraph::new(help_text)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::NONE));
+
+    frame.render_widget(help_bar, chunks[2]);
+}
+
 pub fn draw_ui(frame: &mut Frame, app: &mut App) {
     match app.state.current_screen {
         CurrentScreen::ProcessList => {
@@ -476,10 +762,81 @@ pub fn draw_ui(frame: &mut Frame, app: &mut App) {
         CurrentScreen::ValueEditing => {
             draw_value_editing_screen(frame, app);
         }
+        CurrentScreen::HexEditor => {
+            draw_hex_editor_screen(frame, app);
+        }
+        CurrentScreen::Calculator => {
+            draw_calculator_screen(frame, app);
+        }
+        CurrentScreen::Regions => {
+            draw_regions_screen(frame, app);
+        }
         CurrentScreen::Exiting => {
             draw_exit_screen(frame, app);
         }
     }
+
+    // the screens with a message box already show the last event there, on the others
+    // it goes to the empty second row of the help bar
+    let has_message_box = matches!(
+        app.state.current_screen,
+        CurrentScreen::Scan | CurrentScreen::HexEditor | CurrentScreen::Regions
+    );
+    if !has_message_box {
+        let area = frame.area();
+        let status_area =
+            Rect { y: area.bottom().saturating_sub(1), height: area.height.min(1), ..area };
+        app.status.draw(frame, status_area);
+    }
+
+    if app.show_help {
+        draw_help_overlay(frame, app);
+    }
+}
+
+/// Every keybinding, one section per screen, in two columns
+pub fn draw_help_overlay(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(80, 90, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::bordered()
+        .title(" Keybindings ")
+        .title_bottom(" Press any key to close ")
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White));
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(block.inner(area));
+    frame.render_widget(block, area);
+
+    let sections = app.key_bindings.help_sections();
+    let lines: Vec<Vec<Line>> = sections
+        .iter()
+        .map(|(title, rows)| {
+            let mut lines = vec![Line::styled(*title, Style::default().add_modifier(Modifier::BOLD))];
+            lines.extend(rows.iter().map(|(keys, description)| {
+                Line::from(vec![
+                    Span::from(format!("  {keys:<14}")).fg(Color::Green),
+                    Span::from(*description),
+                ])
+            }));
+            lines.push(Line::from(""));
+            lines
+        })
+        .collect();
+
+    // fill the left column first, starting the right one at a section boundary
+    let total: usize = lines.iter().map(Vec::len).sum();
+    let mut left = vec![];
+    let mut right = vec![];
+    for section in lines {
+        if right.is_empty() && left.len() + section.len() / 2 < total / 2 {
+            left.extend(section);
+        } else {
+            right.extend(section);
+        }
+    }
+    frame.render_widget(Paragraph::new(left), columns[0]);
+    frame.render_widget(Paragraph::new(right), columns[1]);
 }
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
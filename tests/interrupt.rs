@@ -0,0 +1,37 @@
+//! Ctrl+C handling of the CLI, signalled from outside like a terminal would
+
+#![cfg(target_os = "linux")]
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_log_changes_stops_on_sigint() {
+    // a target whose first mapping never changes, so only the signal ends the loop
+    let mut target = Command::new("sleep").arg("30").spawn().unwrap();
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", target.id())).unwrap();
+    let start = maps.split('-').next().unwrap();
+
+    let mut cli = Command::new(env!("CARGO_BIN_EXE_cheat-engine-rs"))
+        .args(["cli", "log-changes", "--pid", &target.id().to_string(), "--address"])
+        .arg(format!("0x{start}"))
+        .args(["--interval", "1"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    unsafe { libc::kill(cli.id() as i32, libc::SIGINT) };
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while cli.try_wait().unwrap().is_none() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let _ = cli.kill();
+    let output = cli.wait_with_output().unwrap();
+    target.kill().unwrap();
+    target.wait().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}: {stdout}", output.status);
+    assert!(stdout.contains("Interrupted, logged 0 change(s)"), "{stdout}");
+}